@@ -1,8 +1,11 @@
 use std::future::Future;
+use std::time::SystemTime;
 
 use futures::{StreamExt, TryStream};
+use serde::{de::DeserializeOwned, Serialize};
+use urn::Urn;
 
-use crate::{Error, EventStream};
+use crate::{Error, EventStream, Metadata};
 
 /// The result of [`Aggregate::handle_stream`]: an owned, boxed (`Send`) event stream or an error.
 #[cfg(not(target_arch = "wasm32"))]
@@ -13,6 +16,10 @@ type HandleStreamResult<E, Err> = Result<futures::stream::BoxStream<'static, Res
 type HandleStreamResult<E, Err> =
     Result<futures::stream::LocalBoxStream<'static, Result<E, Err>>, Err>;
 
+/// The result of [`WithOutput::handle_with_output`]: the events and the command's output, or
+/// an error.
+type HandleOutputResult<E, O, Err> = Result<(Vec<E>, O), Err>;
+
 /// An aggregate is a domain-driven design pattern that allows you to model a domain entity as a sequence of events.
 ///
 /// It extends the `EventStream` trait and adds a `Command` type that represents the commands that can be applied to the aggregate.
@@ -53,6 +60,17 @@ pub trait Aggregate: Sync + Send + EventStream {
     type Error: std::error::Error + From<Error> + Sync + Send;
     type Services: Sync + Send;
 
+    /// Whether `command` opens its stream, needs an opened one, or takes either (the
+    /// default); `#[create]` commands of `define_aggregate!` answer [`Lifecycle::Creates`]
+    /// and the others [`Lifecycle::Existing`].
+    ///
+    /// `Cqrs::execute` checks it against the loaded stream before calling `handle`, so
+    /// handlers don't have to re-check that the stream exists.
+    fn lifecycle(command: &Self::Command) -> Lifecycle {
+        let _ = command;
+        Lifecycle::Any
+    }
+
     fn handle(
         &self,
         command: Self::Command,
@@ -86,6 +104,27 @@ pub trait Aggregate: Sync + Send + EventStream {
         }
     }
 
+    /// Context-aware variant of [`handle_stream`](Aggregate::handle_stream), the one
+    /// `Cqrs::execute` calls: `context` holds the stream id, the version the aggregate was
+    /// loaded at, the command's metadata and when the command was accepted.
+    ///
+    /// The default ignores the context and calls `handle_stream`. Override it for rules that
+    /// depend on how many events the stream holds or on who sent the command, instead of
+    /// keeping that in the state.
+    fn handle_in_context(
+        &self,
+        command: Self::Command,
+        services: &Self::Services,
+        context: &CommandContext,
+    ) -> impl Future<Output = HandleStreamResult<Self::Event, Self::Error>> + Send
+    where
+        Self::Event: 'static,
+        Self::Error: 'static,
+    {
+        let _ = context;
+        self.handle_stream(command, services)
+    }
+
     fn handle_and_apply<'a>(
         &'a mut self,
         command: Self::Command,
@@ -139,6 +178,13 @@ pub trait Aggregate: Sync + EventStream {
     type Error: std::error::Error + From<Error> + Sync;
     type Services: Sync;
 
+    /// Whether `command` opens its stream, needs an opened one, or takes either (the
+    /// default); see the multi-threaded flavor of this trait.
+    fn lifecycle(command: &Self::Command) -> Lifecycle {
+        let _ = command;
+        Lifecycle::Any
+    }
+
     fn handle(
         &self,
         command: Self::Command,
@@ -169,6 +215,22 @@ pub trait Aggregate: Sync + EventStream {
         }
     }
 
+    /// Context-aware variant of [`handle_stream`](Aggregate::handle_stream); see the
+    /// multi-threaded flavor of this trait.
+    fn handle_in_context(
+        &self,
+        command: Self::Command,
+        services: &Self::Services,
+        context: &CommandContext,
+    ) -> impl Future<Output = HandleStreamResult<Self::Event, Self::Error>>
+    where
+        Self::Event: 'static,
+        Self::Error: 'static,
+    {
+        let _ = context;
+        self.handle_stream(command, services)
+    }
+
     fn handle_and_apply<'a>(
         &'a mut self,
         command: Self::Command,
@@ -185,6 +247,106 @@ pub trait Aggregate: Sync + EventStream {
     }
 }
 
+/// What [`Aggregate::handle_in_context`] knows about a command besides its payload.
+#[derive(Clone, Debug)]
+pub struct CommandContext {
+    stream_id: Urn,
+    version: i64,
+    metadata: Metadata,
+    timestamp: SystemTime,
+}
+
+impl CommandContext {
+    pub fn new(stream_id: Urn, version: i64, metadata: Metadata, timestamp: SystemTime) -> Self {
+        CommandContext {
+            stream_id,
+            version,
+            metadata,
+            timestamp,
+        }
+    }
+
+    /// The stream the command targets.
+    pub fn stream_id(&self) -> &Urn {
+        &self.stream_id
+    }
+
+    /// The version the aggregate was loaded at, `0` for a stream without events.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// The metadata the command's events will be appended with.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// When the command was accepted.
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+}
+
+/// How a command relates to the creation of its stream, from [`Aggregate::lifecycle`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Lifecycle {
+    /// Runs on a stream with or without events.
+    #[default]
+    Any,
+    /// Opens the stream, so it is rejected with `Conflict` once the stream has events.
+    Creates,
+    /// Needs an opened stream, so it is rejected with `NotFound` while the stream is empty.
+    Existing,
+}
+
+impl Lifecycle {
+    /// Check a command of this lifecycle against `stream_id`, loaded at `version` (`0` for a
+    /// stream without events).
+    pub fn check(self, stream_id: &Urn, version: i64) -> crate::Result<()> {
+        let error = match self {
+            Lifecycle::Creates if version > 0 => Error::conflict("stream already exists"),
+            Lifecycle::Existing if version == 0 => Error::not_found("stream was not created"),
+            _ => return Ok(()),
+        };
+        Err(error
+            .with_operation("execute")
+            .with_context("stream_id", stream_id)
+            .with_context("version", version))
+    }
+}
+
+/// An [`Aggregate`] whose commands also hand a value back to the caller, such as a generated
+/// id or a computed receipt, next to the events they emit.
+///
+/// `Cqrs::execute_with_output` persists the events like `execute` and returns the output
+/// alongside the updated aggregate. [`handle`](Aggregate::handle) should agree with
+/// `handle_with_output` on the events; `define_aggregate!` derives one from the other.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait WithOutput: Aggregate {
+    type Output: Send;
+
+    fn handle_with_output(
+        &self,
+        command: Self::Command,
+        services: &Self::Services,
+    ) -> impl Future<Output = HandleOutputResult<Self::Event, Self::Output, Self::Error>> + Send;
+}
+
+/// An [`Aggregate`] whose commands also hand a value back to the caller, such as a generated
+/// id or a computed receipt, next to the events they emit.
+///
+/// Single-threaded (WASM) flavor: neither the output nor the future need to be `Send`.
+#[cfg(target_arch = "wasm32")]
+pub trait WithOutput: Aggregate {
+    type Output;
+
+    fn handle_with_output(
+        &self,
+        command: Self::Command,
+        services: &Self::Services,
+    ) -> impl Future<Output = HandleOutputResult<Self::Event, Self::Output, Self::Error>>;
+}
+
 /// A trait for aggregates that can produce the minimum set of events needed to reconstruct
 /// their current state, discarding redundant or superseded events from the full history.
 ///
@@ -280,7 +442,7 @@ pub trait Aggregate: Sync + EventStream {
 /// impl EventStream for BankAccountAggregate {
 ///     type Event = BankAccountEvent;
 ///
-///     fn stream_type() -> String { "BankAccount".to_string() }
+///     fn stream_type() -> &'static str { "BankAccount" }
 ///
 ///     fn apply(&mut self, event: Self::Event) {
 ///         match event {
@@ -375,6 +537,44 @@ impl<E> From<Vec<E>> for Compaction<E> {
     }
 }
 
+/// An [`EventStream`] whose state can be carried by one of its own events, so a store can
+/// replace a prefix of the stream with that event (`EventStore::compact_stream` in
+/// `replay-persistence`).
+///
+/// Unlike [`Compactable`], nothing is archived: the replaced events are gone, which suits
+/// streams such as telemetry whose full history is not worth keeping.
+///
+/// ```rust,ignore
+/// impl Snapshottable for Thermostat {
+///     fn snapshot_event(&self) -> ThermostatEvent {
+///         ThermostatEvent::Snapshotted {
+///             readings: self.readings,
+///             average: self.average,
+///         }
+///     }
+/// }
+///
+/// // `apply` restores the state from it:
+/// ThermostatEvent::Snapshotted { readings, average } => {
+///     self.readings = readings;
+///     self.average = average;
+/// }
+/// ```
+pub trait Snapshottable: EventStream {
+    /// An event that, applied to a fresh instance, reproduces the current state.
+    fn snapshot_event(&self) -> Self::Event;
+}
+
+/// An [`EventStream`] whose whole state can be written out and read back with serde, for the
+/// features that hand the state itself around rather than its events, such as
+/// `JsonCqrs::state` and `testing::check_command_sequence`.
+///
+/// Implemented for every such type. Replaying events never asks for it, so an aggregate may
+/// hold members that can't be serialized (see `#[transient]` in `define_aggregate!`).
+pub trait SnapshotState: EventStream + Serialize + DeserializeOwned {}
+
+impl<T> SnapshotState for T where T: EventStream + Serialize + DeserializeOwned {}
+
 // tests
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
@@ -404,11 +604,11 @@ mod tests {
     }
 
     impl crate::Event for BankAccountEvent {
-        fn event_type(&self) -> String {
+        fn event_type(&self) -> &'static str {
             match self {
-                BankAccountEvent::AccountOpened { .. } => "AccountOpened".to_string(),
-                BankAccountEvent::Deposited { .. } => "Deposited".to_string(),
-                BankAccountEvent::Withdrawn { .. } => "Withdrawn".to_string(),
+                BankAccountEvent::AccountOpened { .. } => "AccountOpened",
+                BankAccountEvent::Deposited { .. } => "Deposited",
+                BankAccountEvent::Withdrawn { .. } => "Withdrawn",
             }
         }
     }
@@ -466,8 +666,8 @@ mod tests {
     impl EventStream for BankAccountAggregate {
         type Event = BankAccountEvent;
 
-        fn stream_type() -> String {
-            "BankAccount".to_string()
+        fn stream_type() -> &'static str {
+            "BankAccount"
         }
 
         fn apply(&mut self, event: Self::Event) {
@@ -666,8 +866,8 @@ mod tests {
     }
 
     impl crate::Event for ImportEvent {
-        fn event_type(&self) -> String {
-            "RowImported".to_string()
+        fn event_type(&self) -> &'static str {
+            "RowImported"
         }
     }
 
@@ -708,8 +908,8 @@ mod tests {
     impl EventStream for ImportAggregate {
         type Event = ImportEvent;
 
-        fn stream_type() -> String {
-            "Import".to_string()
+        fn stream_type() -> &'static str {
+            "Import"
         }
 
         fn apply(&mut self, event: Self::Event) {
@@ -779,4 +979,18 @@ mod tests {
 
         assert!(events.is_empty());
     }
+
+    #[test]
+    fn lifecycle_checks_whether_the_stream_was_created() {
+        let stream_id: Urn = "urn:ledger:l-1".parse().unwrap();
+
+        assert!(Lifecycle::Creates.check(&stream_id, 0).is_ok());
+        assert!(Lifecycle::Existing.check(&stream_id, 3).is_ok());
+        assert!(Lifecycle::Any.check(&stream_id, 0).is_ok());
+
+        let exists = Lifecycle::Creates.check(&stream_id, 3).unwrap_err();
+        assert_eq!(exists.kind(), crate::ErrorKind::Conflict);
+        let missing = Lifecycle::Existing.check(&stream_id, 0).unwrap_err();
+        assert_eq!(missing.kind(), crate::ErrorKind::NotFound);
+    }
 }
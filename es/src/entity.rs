@@ -0,0 +1,246 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A child of an aggregate root, such as an order line inside an order, identified within
+/// its root by [`entity_id`](Entity::entity_id) and folded from the root's own events.
+///
+/// The root keeps its children in an [`Entities`] collection, which routes each event to
+/// the child it names. `#[entity(field = .., id = ..)]` on an event of `define_aggregate!`
+/// generates that routing in `apply`.
+///
+/// ```rust,ignore
+/// impl Entity for OrderLine {
+///     type Id = u32;
+///     type Event = OrderEvent;
+///
+///     fn with_entity_id(id: u32) -> Self {
+///         OrderLine { id, quantity: 0, removed: false }
+///     }
+///
+///     fn entity_id(&self) -> &u32 {
+///         &self.id
+///     }
+///
+///     fn apply(&mut self, event: OrderEvent) {
+///         match event {
+///             OrderEvent::LineAdded { quantity, .. } => self.quantity = quantity,
+///             OrderEvent::LineRemoved { .. } => self.removed = true,
+///             _ => {}
+///         }
+///     }
+///
+///     fn is_removed(&self) -> bool {
+///         self.removed
+///     }
+/// }
+/// ```
+pub trait Entity: Sized {
+    type Id: Ord + Clone + fmt::Debug;
+    /// The events of the aggregate root the entity belongs to.
+    type Event;
+
+    /// A child that has seen no events yet; the first event routed to `id` creates it.
+    fn with_entity_id(id: Self::Id) -> Self;
+
+    fn entity_id(&self) -> &Self::Id;
+
+    fn apply(&mut self, event: Self::Event);
+
+    /// Whether the last event applied removed the child, which [`Entities::route`] then
+    /// drops. By default children are never removed.
+    fn is_removed(&self) -> bool {
+        false
+    }
+}
+
+/// The children of an aggregate root, keyed by [`Entity::entity_id`].
+///
+/// Serialized as a sequence of children, so ids don't need to be valid map keys.
+#[derive(Clone, PartialEq)]
+pub struct Entities<E: Entity> {
+    entities: BTreeMap<E::Id, E>,
+}
+
+impl<E: Entity> Entities<E> {
+    pub fn new() -> Self {
+        Entities {
+            entities: BTreeMap::new(),
+        }
+    }
+
+    /// Apply `event` to the child `id`, creating it on its first event and dropping it when
+    /// the event [removes](Entity::is_removed) it.
+    pub fn route(&mut self, id: E::Id, event: E::Event) {
+        let entity = self
+            .entities
+            .entry(id.clone())
+            .or_insert_with(|| E::with_entity_id(id.clone()));
+        entity.apply(event);
+        if entity.is_removed() {
+            self.entities.remove(&id);
+        }
+    }
+
+    pub fn get(&self, id: &E::Id) -> Option<&E> {
+        self.entities.get(id)
+    }
+
+    pub fn contains(&self, id: &E::Id) -> bool {
+        self.entities.contains_key(id)
+    }
+
+    /// The children in id order.
+    pub fn iter(&self) -> impl Iterator<Item = &E> {
+        self.entities.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+impl<E: Entity> Default for Entities<E> {
+    fn default() -> Self {
+        Entities::new()
+    }
+}
+
+impl<E: Entity + fmt::Debug> fmt::Debug for Entities<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.entities.values()).finish()
+    }
+}
+
+impl<E: Entity + Serialize> Serialize for Entities<E> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.entities.values())
+    }
+}
+
+impl<'de, E: Entity + Deserialize<'de>> Deserialize<'de> for Entities<E> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entities = Vec::<E>::deserialize(deserializer)?
+            .into_iter()
+            .map(|entity| (entity.entity_id().clone(), entity))
+            .collect();
+        Ok(Entities { entities })
+    }
+}
+
+impl<'a, E: Entity> IntoIterator for &'a Entities<E> {
+    type Item = &'a E;
+    type IntoIter = std::collections::btree_map::Values<'a, E::Id, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entities.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum OrderEvent {
+        LineAdded { line: u32, quantity: u32 },
+        LineRemoved { line: u32 },
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct OrderLine {
+        id: u32,
+        quantity: u32,
+        #[serde(skip)]
+        removed: bool,
+    }
+
+    impl Entity for OrderLine {
+        type Id = u32;
+        type Event = OrderEvent;
+
+        fn with_entity_id(id: u32) -> Self {
+            OrderLine {
+                id,
+                quantity: 0,
+                removed: false,
+            }
+        }
+
+        fn entity_id(&self) -> &u32 {
+            &self.id
+        }
+
+        fn apply(&mut self, event: OrderEvent) {
+            match event {
+                OrderEvent::LineAdded { quantity, .. } => self.quantity += quantity,
+                OrderEvent::LineRemoved { .. } => self.removed = true,
+            }
+        }
+
+        fn is_removed(&self) -> bool {
+            self.removed
+        }
+    }
+
+    /// What the root's `apply` does: route by the line the event names.
+    fn route(lines: &mut Entities<OrderLine>, event: OrderEvent) {
+        let (OrderEvent::LineAdded { line, .. } | OrderEvent::LineRemoved { line }) = event;
+        lines.route(line, event);
+    }
+
+    #[test]
+    fn events_are_routed_to_the_child_they_name() {
+        let mut lines = Entities::new();
+        route(
+            &mut lines,
+            OrderEvent::LineAdded {
+                line: 2,
+                quantity: 1,
+            },
+        );
+        route(
+            &mut lines,
+            OrderEvent::LineAdded {
+                line: 1,
+                quantity: 5,
+            },
+        );
+        route(
+            &mut lines,
+            OrderEvent::LineAdded {
+                line: 2,
+                quantity: 3,
+            },
+        );
+
+        let quantities: Vec<_> = lines.iter().map(|line| (line.id, line.quantity)).collect();
+        assert_eq!(quantities, [(1, 5), (2, 4)]);
+
+        route(&mut lines, OrderEvent::LineRemoved { line: 1 });
+        assert!(!lines.contains(&1));
+        assert_eq!(lines.len(), 1);
+    }
+
+    #[test]
+    fn entities_serialize_as_a_sequence() {
+        let mut lines = Entities::new();
+        route(
+            &mut lines,
+            OrderEvent::LineAdded {
+                line: 7,
+                quantity: 2,
+            },
+        );
+
+        let json = serde_json::to_value(&lines).unwrap();
+        assert_eq!(json, serde_json::json!([{ "id": 7, "quantity": 2 }]));
+        let restored: Entities<OrderLine> = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, lines);
+    }
+}
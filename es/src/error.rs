@@ -1,4 +1,5 @@
 use std::fmt;
+use std::time::Duration;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -23,6 +24,8 @@ pub enum ErrorKind {
     Forbidden,
     /// Too many requests, rate limit exceeded. Safe to retry after delay.
     RateLimited,
+    /// The operation isn't implemented by this backend. Don't retry.
+    Unsupported,
 }
 
 impl fmt::Display for ErrorKind {
@@ -37,6 +40,7 @@ impl fmt::Display for ErrorKind {
             ErrorKind::Conflict => "Conflict",
             ErrorKind::Unavailable => "Unavailable",
             ErrorKind::BusinessRuleViolation => "Business Rule Violation",
+            ErrorKind::Unsupported => "Unsupported",
         };
         write!(f, "{}", s)
     }
@@ -54,6 +58,7 @@ impl fmt::Debug for ErrorKind {
             ErrorKind::Conflict => "\u{2694}\u{FE0F} Conflict",
             ErrorKind::Unavailable => "\u{26A0}\u{FE0F} Unavailable",
             ErrorKind::BusinessRuleViolation => "\u{1F4CB} Business Rule Violation",
+            ErrorKind::Unsupported => "\u{1F6A7} Unsupported",
         };
         write!(f, "{}", s)
     }
@@ -145,6 +150,8 @@ pub struct Error {
     source: Option<Box<dyn std::error::Error + Send + Sync>>,
     /// Location where the error was created (file:line:column)
     location: String,
+    /// How long to wait before retrying, for rate-limited errors (boxed to keep `Error` small)
+    retry_after: Option<Box<Duration>>,
 }
 
 impl Error {
@@ -159,6 +166,7 @@ impl Error {
             context: Vec::new(),
             source: None,
             location: std::panic::Location::caller().to_string(),
+            retry_after: None,
         }
     }
 
@@ -204,6 +212,12 @@ impl Error {
         Self::permanent(ErrorKind::Internal, message)
     }
 
+    /// Create a "rate limited" error, retryable once `retry_after` has passed.
+    #[track_caller]
+    pub fn rate_limited(message: impl Into<String>, retry_after: Duration) -> Self {
+        Self::temporary(ErrorKind::RateLimited, message).with_retry_after(retry_after)
+    }
+
     #[track_caller]
     pub fn business_rule_violation(message: impl Into<String>) -> Self {
         Self::permanent(ErrorKind::BusinessRuleViolation, message)
@@ -214,12 +228,29 @@ impl Error {
         Self::permanent(ErrorKind::Unauthorized, message)
     }
 
+    #[track_caller]
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::permanent(ErrorKind::Forbidden, message)
+    }
+
+    /// Create an "unsupported" error, for an operation a backend doesn't implement.
+    #[track_caller]
+    pub fn unsupported(message: impl Into<String>) -> Self {
+        Self::permanent(ErrorKind::Unsupported, message)
+    }
+
     /// Set the operation being performed when the error occurred.
     pub fn with_operation(mut self, operation: &'static str) -> Self {
         self.operation = operation;
         self
     }
 
+    /// Set how long the caller should wait before retrying.
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = Some(Box::new(retry_after));
+        self
+    }
+
     /// Add context as a key-value pair.
     pub fn with_context(mut self, key: &'static str, value: impl fmt::Display) -> Self {
         self.context.push((key, value.to_string()));
@@ -274,6 +305,7 @@ impl Error {
             context: Vec::new(),
             source: Some(Box::new(source)),
             location: std::panic::Location::caller().to_string(),
+            retry_after: None,
         }
     }
 
@@ -346,6 +378,63 @@ impl Error {
     pub fn location(&self) -> &str {
         &self.location
     }
+
+    /// Get how long to wait before retrying, if the error says.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.retry_after.as_deref().copied()
+    }
+
+    /// Record this error as a structured `tracing` event and onto the current span.
+    ///
+    /// The event (target `replay::error`) carries `error.kind`, `error.status`,
+    /// `error.operation`, `error.context` and `error.location` fields. Its level follows
+    /// what the error means for operators: `ERROR` for internal errors, `WARN` for
+    /// temporary ones, and `DEBUG` for expected domain outcomes (not found, invalid input,
+    /// business rule violations, ...).
+    ///
+    /// The same fields are recorded on [`tracing::Span::current`]; spans that want them
+    /// declare the fields up front, e.g. `error.kind = tracing::field::Empty`.
+    pub fn record(&self) {
+        let context = self
+            .context
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let span = tracing::Span::current();
+        span.record("error.kind", tracing::field::display(self.kind));
+        span.record("error.status", tracing::field::display(self.status));
+        span.record("error.operation", self.operation);
+
+        macro_rules! emit {
+            ($level:expr) => {
+                tracing::event!(
+                    target: "replay::error",
+                    $level,
+                    error.kind = %self.kind,
+                    error.status = %self.status,
+                    error.operation = self.operation,
+                    error.context = %context,
+                    error.location = %self.location,
+                    "{}",
+                    self.message
+                )
+            };
+        }
+
+        match (self.kind, self.status) {
+            (ErrorKind::Internal, _) => emit!(tracing::Level::ERROR),
+            (_, ErrorStatus::Temporary | ErrorStatus::Persistent) => emit!(tracing::Level::WARN),
+            _ => emit!(tracing::Level::DEBUG),
+        }
+    }
+
+    /// [`record`](Self::record) this error and return it, for `map_err` chains.
+    pub fn recorded(self) -> Self {
+        self.record();
+        self
+    }
 }
 
 impl fmt::Display for Error {
@@ -383,6 +472,10 @@ impl fmt::Debug for Error {
             }
         }
 
+        if let Some(retry_after) = &self.retry_after {
+            writeln!(f, "  Retry after: {:?}", retry_after)?;
+        }
+
         // Location - for developers to find the code
         writeln!(f, "  Location: {}", self.location)?;
 
@@ -431,6 +524,7 @@ impl fmt::Debug for Error {
             (ErrorKind::Unauthorized, _) => Some("💡 Check authentication credentials"),
             (ErrorKind::Forbidden, _) => Some("💡 Insufficient permissions for this operation"),
             (ErrorKind::InvalidInput, _) => Some("💡 Review and fix the request parameters"),
+            (ErrorKind::Unsupported, _) => Some("💡 Use a backend that implements this operation"),
             (ErrorKind::BusinessRuleViolation, _) => {
                 Some("💡 Review business logic and request parameters")
             }
@@ -482,6 +576,32 @@ mod tests {
         assert!(!err.is_permanent());
     }
 
+    #[test]
+    fn test_rate_limited_error_carries_retry_after() {
+        let err = Error::rate_limited("too many commands", Duration::from_millis(250));
+
+        assert_eq!(err.kind(), ErrorKind::RateLimited);
+        assert!(err.is_temporary());
+        assert_eq!(err.retry_after(), Some(Duration::from_millis(250)));
+        assert!(format!("{:?}", err).contains("Retry after: 250ms"));
+        assert_eq!(Error::unavailable("down").retry_after(), None);
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_record_emits_structured_fields() {
+        let err = Error::unavailable("database connection failed")
+            .with_operation("connect")
+            .with_context("host", "localhost:5432")
+            .recorded();
+
+        assert_eq!(err.kind(), ErrorKind::Unavailable);
+        assert!(logs_contain("WARN"));
+        assert!(logs_contain("database connection failed"));
+        assert!(logs_contain("error.operation=\"connect\""));
+        assert!(logs_contain("error.context=host=localhost:5432"));
+    }
+
     #[test]
     #[traced_test]
     fn test_error_display() {
@@ -23,10 +23,10 @@ use serde::{de::DeserializeOwned, Serialize};
 /// }
 ///
 /// impl Event for BankAccountEvent {
-///     fn event_type(&self) -> String {
+///     fn event_type(&self) -> &'static str {
 ///         match self {
-///             BankAccountEvent::Deposited { .. } => "Deposited".to_string(),
-///             BankAccountEvent::Withdrawn { .. } => "Withdrawn".to_string(),
+///             BankAccountEvent::Deposited { .. } => "Deposited",
+///             BankAccountEvent::Withdrawn { .. } => "Withdrawn",
 ///         }
 ///     }
 /// }
@@ -37,7 +37,44 @@ use serde::{de::DeserializeOwned, Serialize};
 pub trait Event:
     Serialize + DeserializeOwned + Clone + PartialEq + fmt::Debug + Sync + Send
 {
-    fn event_type(&self) -> String;
+    fn event_type(&self) -> &'static str;
+
+    /// Schema version of this event's payload, `1` unless overridden.
+    ///
+    /// Bump it when the payload shape changes; stores record any version other than `1` in
+    /// the event's metadata so readers can tell which upcast an old payload needs.
+    fn event_version(&self) -> u32 {
+        1
+    }
+
+    /// Whether this event ends its stream, e.g. an account's `Closed`; marked
+    /// `#[event(tombstone)]` under `derive(Event)`.
+    ///
+    /// Loading a stream that holds a tombstone fails with `NotFound`, so commands can't
+    /// append to it anymore.
+    fn is_tombstone(&self) -> bool {
+        false
+    }
+
+    /// Where the payload holds personal data, as JSON Pointers (RFC 6901) into the serialized
+    /// event: `/Registered/email` for the `email` field of an externally tagged `Registered`
+    /// variant. `derive(Event)` lists the fields marked `#[pii]`, following `#[serde]` renames
+    /// and tagging and the events wrapped by transparent variants.
+    ///
+    /// Field-level encryption can be configured from this list so marked fields are
+    /// encrypted, and crypto-shredded with their stream.
+    fn pii_paths() -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Every type string this event can take, with its schema version, in declaration order.
+    ///
+    /// `derive(Event)` lists them, following transparent variants into the events they wrap;
+    /// hand-written implementations list none unless they override it. Contract generators
+    /// such as AsyncAPI documents use it to name an event's messages.
+    fn event_types() -> Vec<(&'static str, u32)> {
+        Vec::new()
+    }
 }
 
 // tests
@@ -54,8 +91,8 @@ mod tests {
     }
 
     impl Event for TestEvent {
-        fn event_type(&self) -> String {
-            "TestEvent".to_string()
+        fn event_type(&self) -> &'static str {
+            "TestEvent"
         }
     }
 
@@ -67,6 +104,7 @@ mod tests {
         };
 
         assert_eq!(event.event_type(), "TestEvent");
+        assert_eq!(event.event_version(), 1);
     }
 
     // test serialize and deserialize event
@@ -91,10 +129,10 @@ mod tests {
     }
 
     impl Event for BankAccountEvent {
-        fn event_type(&self) -> String {
+        fn event_type(&self) -> &'static str {
             match self {
-                BankAccountEvent::Deposited { .. } => "Deposited".to_string(),
-                BankAccountEvent::Withdrawn { .. } => "Withdrawn".to_string(),
+                BankAccountEvent::Deposited { .. } => "Deposited",
+                BankAccountEvent::Withdrawn { .. } => "Withdrawn",
             }
         }
     }
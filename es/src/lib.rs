@@ -1,14 +1,50 @@
 mod aggregate;
+mod entity;
 mod error;
 mod event;
+pub mod local;
 mod metadata;
 mod stream;
+pub mod testing;
 
-pub use aggregate::{Aggregate, Compactable, Compaction};
+pub use aggregate::{
+    Aggregate, CommandContext, Compactable, Compaction, Lifecycle, SnapshotState, Snapshottable,
+    WithOutput,
+};
+pub use entity::{Entities, Entity};
 pub use error::{Error, ErrorKind, ErrorStatus, Result};
 pub use event::Event;
-pub use metadata::Metadata;
-pub use stream::{EventStream, ScopedUrn, WithId};
+pub use metadata::{Metadata, MetadataBuilder};
+pub use stream::{EventContext, EventStream, ScopedUrn, StreamId, WithId};
+
+/// Support for exported macros; not public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::stream::{key_urn, urn_key};
+    pub use futures::executor::block_on;
+    #[cfg(feature = "proptest")]
+    pub use proptest;
+    #[cfg(all(target_arch = "wasm32", feature = "js-bindings"))]
+    pub use {js_sys, urn::Urn, wasm_bindgen, wasm_bindgen_futures};
+}
+
+/// Keeps the `Arbitrary` impls `replay_macros` generates only when the `proptest` feature is
+/// on; not public API.
+#[cfg(feature = "proptest")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __proptest {
+    ($($item:tt)*) => {
+        $($item)*
+    };
+}
+
+#[cfg(not(feature = "proptest"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __proptest {
+    ($($item:tt)*) => {};
+}
 
 /// Convenience re-exports of the most commonly used traits.
 ///
@@ -24,5 +60,8 @@ pub use stream::{EventStream, ScopedUrn, WithId};
 /// let branch: BranchUrn = scoped.extract_scope::<BranchUrn>()?;
 /// ```
 pub mod prelude {
-    pub use super::{Aggregate, Compactable, Compaction, Event, EventStream, ScopedUrn, WithId};
+    pub use super::{
+        Aggregate, Compactable, Compaction, Event, EventStream, ScopedUrn, Snapshottable, StreamId,
+        WithId, WithOutput,
+    };
 }
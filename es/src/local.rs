@@ -0,0 +1,457 @@
+//! Single-threaded persistence, so the whole command/query loop runs where futures can't be
+//! `Send`, such as `wasm32-unknown-unknown` in a browser.
+//!
+//! [`LocalCqrs`] mirrors `replay_persistence::Cqrs` over a [`LocalEventStore`]: nothing in it
+//! requires `Send` or `Sync`, stores are shared through an `Rc`, and it only depends on what
+//! this crate already builds for WASM. [`LocalInMemoryEventStore`] keeps events in memory;
+//! any other backend implements [`LocalEventStore`] over JSON records.
+//!
+//! ```rust,ignore
+//! use replay::local::{LocalCqrs, LocalInMemoryEventStore};
+//!
+//! let cqrs = LocalCqrs::new(LocalInMemoryEventStore::new());
+//! let deposit = BankAccountCommand::Deposit { amount: 10.0 };
+//! let account = cqrs
+//!     .execute::<BankAccount>(&id, Metadata::default(), deposit, &services, None)
+//!     .await?;
+//!
+//! let mut balances = Balances::default();
+//! cqrs.run_query(&mut balances).await?;
+//! ```
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use urn::Urn;
+
+use crate::{Aggregate, Error, Event, Metadata, Result};
+
+#[cfg(all(target_arch = "wasm32", feature = "js-bindings"))]
+mod js;
+mod json;
+mod key_value;
+
+pub use json::JsonCqrs;
+pub use key_value::{KeyValueEventStore, KeyValueStorage, DEFAULT_QUOTA_BYTES};
+
+/// An event read from or written to a [`LocalEventStore`].
+///
+/// Stores keep `LocalEvent<Value>` records; [`LocalCqrs`] converts them to and from the
+/// typed events of an aggregate or query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocalEvent<E> {
+    pub stream_id: Urn,
+    pub stream_type: String,
+    pub r#type: String,
+    /// Position of the event in its stream, from `1`.
+    pub version: i64,
+    /// Position of the event across the store, from `1`; assigned by the store on append.
+    pub global_position: i64,
+    pub metadata: Metadata,
+    pub data: E,
+}
+
+impl LocalEvent<Value> {
+    /// The record with its payload deserialized as `E`.
+    pub fn decode<E: Event>(self) -> Result<LocalEvent<E>> {
+        let data = serde_json::from_value(self.data).map_err(|e| {
+            Error::internal(format!("Deserialization failed: {e}"))
+                .with_operation("deserialize")
+                .with_context("stream_id", self.stream_id.to_string())
+                .with_context("version", self.version)
+        })?;
+        Ok(LocalEvent {
+            stream_id: self.stream_id,
+            stream_type: self.stream_type,
+            r#type: self.r#type,
+            version: self.version,
+            global_position: self.global_position,
+            metadata: self.metadata,
+            data,
+        })
+    }
+}
+
+/// Storage behind a [`LocalCqrs`], for single-threaded targets.
+///
+/// Records carry their payload as JSON so one store holds every stream type. Implementations
+/// check that appended events continue their stream (the first one's `version` is the stream's
+/// head plus one) and reject them with a [`Conflict`](crate::ErrorKind::Conflict) otherwise.
+pub trait LocalEventStore {
+    /// The events of `stream_id`, oldest first.
+    fn load(&self, stream_id: &Urn) -> impl Future<Output = Result<Vec<LocalEvent<Value>>>>;
+
+    /// Append `events`, all of one stream and in version order, assigning their global
+    /// positions. Returns the events as stored.
+    fn append(
+        &self,
+        events: Vec<LocalEvent<Value>>,
+    ) -> impl Future<Output = Result<Vec<LocalEvent<Value>>>>;
+
+    /// Every event past the global position `after` (`0` for the whole store), in order.
+    fn read_all(&self, after: i64) -> impl Future<Output = Result<Vec<LocalEvent<Value>>>>;
+}
+
+/// A [`LocalEventStore`] holding events in memory, for the lifetime of the page or test.
+#[derive(Debug, Default)]
+pub struct LocalInMemoryEventStore {
+    events: RefCell<Vec<LocalEvent<Value>>>,
+}
+
+impl LocalInMemoryEventStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl LocalEventStore for LocalInMemoryEventStore {
+    async fn load(&self, stream_id: &Urn) -> Result<Vec<LocalEvent<Value>>> {
+        Ok(self
+            .events
+            .borrow()
+            .iter()
+            .filter(|event| &event.stream_id == stream_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn append(&self, events: Vec<LocalEvent<Value>>) -> Result<Vec<LocalEvent<Value>>> {
+        let mut stored = self.events.borrow_mut();
+        append_to(&mut stored, events)
+    }
+
+    async fn read_all(&self, after: i64) -> Result<Vec<LocalEvent<Value>>> {
+        Ok(self
+            .events
+            .borrow()
+            .iter()
+            .filter(|event| event.global_position > after)
+            .cloned()
+            .collect())
+    }
+}
+
+/// Append `events` to the in-order log `stored` once they are checked to continue their
+/// stream, for stores that keep the whole log at hand.
+pub(crate) fn append_to(
+    stored: &mut Vec<LocalEvent<Value>>,
+    events: Vec<LocalEvent<Value>>,
+) -> Result<Vec<LocalEvent<Value>>> {
+    let Some(first) = events.first() else {
+        return Ok(events);
+    };
+    let head = stored
+        .iter()
+        .filter(|event| event.stream_id == first.stream_id)
+        .map(|event| event.version)
+        .max()
+        .unwrap_or(0);
+    if first.version != head + 1 {
+        return Err(Error::conflict("Stream version mismatch")
+            .with_operation("append")
+            .with_context("stream_id", first.stream_id.to_string())
+            .with_context("expected_version", first.version - 1)
+            .with_context("actual_version", head));
+    }
+
+    let mut position = stored.last().map_or(0, |event| event.global_position);
+    let events: Vec<_> = events
+        .into_iter()
+        .map(|mut event| {
+            position += 1;
+            event.global_position = position;
+            event
+        })
+        .collect();
+    stored.extend(events.iter().cloned());
+    Ok(events)
+}
+
+/// A read model folded from the events of a [`LocalEventStore`] by [`LocalCqrs::run_query`].
+pub trait LocalQuery {
+    type Event: Event;
+
+    /// The stream types whose events the query reads; every stream when empty.
+    fn stream_types(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    fn update(&mut self, event: LocalEvent<Self::Event>);
+}
+
+/// Entry point for reading and writing aggregates through a [`LocalEventStore`], the
+/// single-threaded counterpart of `replay_persistence::Cqrs`.
+pub struct LocalCqrs<ES> {
+    store: Rc<ES>,
+}
+
+// Manual impl: the store sits behind an `Rc`, so cloning a handle never requires `ES: Clone`.
+impl<ES> Clone for LocalCqrs<ES> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+        }
+    }
+}
+
+impl<ES: LocalEventStore> LocalCqrs<ES> {
+    pub fn new(event_store: ES) -> Self {
+        Self {
+            store: Rc::new(event_store),
+        }
+    }
+
+    /// Shared handle to the underlying event store.
+    pub fn store(&self) -> &Rc<ES> {
+        &self.store
+    }
+
+    /// Reconstruct an aggregate at its latest state.
+    pub async fn fetch_aggregate<A: Aggregate>(
+        &self,
+        id: &A::StreamId,
+    ) -> std::result::Result<A, A::Error> {
+        self.hydrate::<A>(id).await.map(|(aggregate, _)| aggregate)
+    }
+
+    /// Handle `command` on the aggregate `id` and append the events it emits, returning the
+    /// updated aggregate.
+    ///
+    /// With `expected_version`, the command is rejected with a
+    /// [`Conflict`](crate::ErrorKind::Conflict) unless the stream is at that version.
+    pub async fn execute<A: Aggregate>(
+        &self,
+        id: &A::StreamId,
+        metadata: Metadata,
+        command: A::Command,
+        services: &A::Services,
+        expected_version: Option<i64>,
+    ) -> std::result::Result<A, A::Error> {
+        self.execute_recorded(id, metadata, command, services, expected_version)
+            .await
+            .map(|(aggregate, _)| aggregate)
+    }
+
+    /// Like [`execute`](Self::execute), also returning the events appended, as stored.
+    pub(crate) async fn execute_recorded<A: Aggregate>(
+        &self,
+        id: &A::StreamId,
+        metadata: Metadata,
+        command: A::Command,
+        services: &A::Services,
+        expected_version: Option<i64>,
+    ) -> std::result::Result<(A, Vec<LocalEvent<Value>>), A::Error> {
+        let (mut aggregate, head) = self.hydrate::<A>(id).await?;
+        let stream_id: Urn = id.clone().into();
+        if let Some(expected_version) = expected_version.filter(|version| *version != head) {
+            return Err(Error::conflict("Stream version mismatch")
+                .with_operation("execute")
+                .with_context("stream_id", stream_id.to_string())
+                .with_context("expected_version", expected_version)
+                .with_context("actual_version", head)
+                .into());
+        }
+        A::lifecycle(&command).check(&stream_id, head)?;
+
+        let events = aggregate.handle(command, services).await?;
+        let records = events
+            .iter()
+            .zip(head + 1..)
+            .map(|(event, version)| {
+                let data = serde_json::to_value(event).map_err(|e| {
+                    Error::internal(format!("Serialization failed: {e}"))
+                        .with_operation("serialize")
+                })?;
+                Ok(LocalEvent {
+                    stream_id: stream_id.clone(),
+                    stream_type: A::stream_type().to_string(),
+                    r#type: event.event_type().to_string(),
+                    version,
+                    global_position: 0,
+                    metadata: metadata.clone(),
+                    data,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let stored = self.store.append(records).await?;
+
+        aggregate.apply_all(events);
+        Ok((aggregate, stored))
+    }
+
+    /// Fold every event of the query's stream types into `query`, oldest first.
+    pub async fn run_query<Q: LocalQuery>(&self, query: &mut Q) -> Result<()> {
+        let stream_types = query.stream_types();
+        for event in self.store.read_all(0).await? {
+            if stream_types.is_empty() || stream_types.contains(&event.stream_type.as_str()) {
+                query.update(event.decode()?);
+            }
+        }
+        Ok(())
+    }
+
+    /// The aggregate `id` with its events applied, and its stream's version.
+    async fn hydrate<A: Aggregate>(
+        &self,
+        id: &A::StreamId,
+    ) -> std::result::Result<(A, i64), A::Error> {
+        let mut aggregate = A::with_id(id.clone());
+        let mut head = 0;
+        for event in self.store.load(&id.clone().into()).await? {
+            let event = event.decode::<A::Event>()?;
+            head = event.version;
+            aggregate.apply(event.data);
+        }
+        Ok((aggregate, head))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use urn::UrnBuilder;
+
+    use super::*;
+    use crate::{ErrorKind, EventStream, WithId};
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+    enum CounterEvent {
+        Incremented,
+    }
+
+    impl Event for CounterEvent {
+        fn event_type(&self) -> &'static str {
+            "Incremented"
+        }
+    }
+
+    #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+    struct CounterUrn(Urn);
+
+    impl From<CounterUrn> for Urn {
+        fn from(urn: CounterUrn) -> Self {
+            urn.0
+        }
+    }
+
+    impl TryFrom<Urn> for CounterUrn {
+        type Error = String;
+
+        fn try_from(urn: Urn) -> std::result::Result<Self, Self::Error> {
+            Ok(CounterUrn(urn))
+        }
+    }
+
+    #[derive(Debug)]
+    struct Counter {
+        id: CounterUrn,
+        count: u32,
+    }
+
+    impl WithId for Counter {
+        type StreamId = CounterUrn;
+
+        fn with_id(id: Self::StreamId) -> Self {
+            Counter { id, count: 0 }
+        }
+
+        fn get_id(&self) -> &Self::StreamId {
+            &self.id
+        }
+    }
+
+    impl EventStream for Counter {
+        type Event = CounterEvent;
+
+        fn stream_type() -> &'static str {
+            "Counter"
+        }
+
+        fn apply(&mut self, _event: Self::Event) {
+            self.count += 1;
+        }
+    }
+
+    impl Aggregate for Counter {
+        type Command = u32;
+        type Error = Error;
+        type Services = ();
+
+        async fn handle(&self, times: u32, _services: &()) -> Result<Vec<CounterEvent>> {
+            Ok((0..times).map(|_| CounterEvent::Incremented).collect())
+        }
+    }
+
+    #[derive(Default)]
+    struct Total(usize);
+
+    impl LocalQuery for Total {
+        type Event = CounterEvent;
+
+        fn update(&mut self, _event: LocalEvent<CounterEvent>) {
+            self.0 += 1;
+        }
+    }
+
+    fn counter_id(nss: &str) -> CounterUrn {
+        CounterUrn(UrnBuilder::new("counter", nss).build().unwrap())
+    }
+
+    #[test]
+    fn commands_and_queries_run_against_a_local_store() {
+        let cqrs = LocalCqrs::new(LocalInMemoryEventStore::new());
+        let (a, b) = (counter_id("a"), counter_id("b"));
+
+        block_on(async {
+            cqrs.execute::<Counter>(&a, Metadata::default(), 2, &(), None)
+                .await?;
+            let counter = cqrs
+                .execute::<Counter>(&a, Metadata::default(), 1, &(), Some(2))
+                .await?;
+            assert_eq!(counter.count, 3);
+            cqrs.execute::<Counter>(&b, Metadata::default(), 1, &(), None)
+                .await?;
+
+            assert_eq!(cqrs.fetch_aggregate::<Counter>(&a).await?.count, 3);
+            let mut total = Total::default();
+            cqrs.run_query(&mut total).await?;
+            assert_eq!(total.0, 4);
+
+            let positions: Vec<_> = cqrs
+                .store()
+                .read_all(2)
+                .await?
+                .iter()
+                .map(|event| event.global_position)
+                .collect();
+            assert_eq!(positions, [3, 4]);
+            Ok::<_, Error>(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn stale_expected_version_conflicts() {
+        let cqrs = LocalCqrs::new(LocalInMemoryEventStore::new());
+        let id = counter_id("a");
+
+        let err = block_on(async {
+            cqrs.execute::<Counter>(&id, Metadata::default(), 2, &(), None)
+                .await?;
+            cqrs.execute::<Counter>(&id, Metadata::default(), 1, &(), Some(1))
+                .await
+        })
+        .unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::Conflict);
+        assert_eq!(
+            block_on(cqrs.fetch_aggregate::<Counter>(&id))
+                .unwrap()
+                .count,
+            2
+        );
+    }
+}
@@ -0,0 +1,126 @@
+/// Export a [`JsonCqrs`](crate::local::JsonCqrs) for `aggregate` to JavaScript as the class
+/// `name` (feature `js-bindings`, on `wasm32`), so the domain logic shared with the backend
+/// runs in the browser.
+///
+/// Events are kept in a [`LocalInMemoryEventStore`](crate::local::LocalInMemoryEventStore)
+/// unless `store` gives a `replay::Result` of another
+/// [`LocalEventStore`](crate::local::LocalEventStore); `services` default to
+/// `Default::default()`:
+///
+/// ```rust,ignore
+/// replay::js_bindings!(
+///     Notes for Note,
+///     store: KeyValueEventStore<web_sys::Storage> = KeyValueEventStore::local_storage("notes:"),
+///     services = ()
+/// );
+/// ```
+///
+/// ```rust,ignore
+/// replay::js_bindings!(BankAccounts for BankAccount, services = Arc::new(BrowserServices));
+/// ```
+///
+/// From JavaScript:
+///
+/// ```js
+/// const accounts = new BankAccounts();
+/// const id = BankAccounts.newId();
+/// const events = JSON.parse(await accounts.dispatch(id, '{"Deposit":{"amount":10}}'));
+/// const account = JSON.parse(await accounts.state(id));
+/// ```
+///
+/// Every method returns a promise of a JSON string, rejected with the error's message.
+#[macro_export]
+macro_rules! js_bindings {
+    ($name:ident for $aggregate:ty) => {
+        $crate::js_bindings!($name for $aggregate, services = ::std::default::Default::default());
+    };
+
+    ($name:ident for $aggregate:ty, services = $services:expr) => {
+        $crate::js_bindings!(
+            $name for $aggregate,
+            store: $crate::local::LocalInMemoryEventStore =
+                ::std::result::Result::Ok($crate::local::LocalInMemoryEventStore::new()),
+            services = $services
+        );
+    };
+
+    (
+        $name:ident for $aggregate:ty,
+        store: $store_ty:ty = $store:expr,
+        services = $services:expr
+    ) => {
+        #[$crate::__private::wasm_bindgen::prelude::wasm_bindgen(
+            wasm_bindgen = $crate::__private::wasm_bindgen
+        )]
+        pub struct $name {
+            inner: $crate::local::JsonCqrs<$aggregate, $store_ty>,
+        }
+
+        #[$crate::__private::wasm_bindgen::prelude::wasm_bindgen(
+            wasm_bindgen = $crate::__private::wasm_bindgen
+        )]
+        impl $name {
+            #[wasm_bindgen(constructor)]
+            pub fn new() -> ::std::result::Result<$name, $crate::__private::wasm_bindgen::JsError> {
+                let store: $crate::Result<$store_ty> = $store;
+                let store = store.map_err(|e| {
+                    $crate::__private::wasm_bindgen::JsError::new(&e.to_string())
+                })?;
+                let cqrs = $crate::local::LocalCqrs::new(store);
+                Ok($name {
+                    inner: $crate::local::JsonCqrs::new(cqrs, $services),
+                })
+            }
+
+            /// A new random id for an aggregate.
+            #[wasm_bindgen(js_name = newId)]
+            pub fn new_id() -> String {
+                let id = <<$aggregate as $crate::WithId>::StreamId>::new_random();
+                ::std::convert::Into::<$crate::__private::Urn>::into(id).to_string()
+            }
+
+            /// Handle a JSON command; resolves to the JSON array of the events appended.
+            pub fn dispatch(
+                &self,
+                id: String,
+                command: String,
+                metadata: Option<String>,
+            ) -> $crate::__private::js_sys::Promise {
+                let inner = self.inner.clone();
+                $crate::__private::wasm_bindgen_futures::future_to_promise(async move {
+                    $crate::__js_result!(inner.dispatch(&id, &command, metadata.as_deref()).await)
+                })
+            }
+
+            /// Resolves to the JSON array of the aggregate's events.
+            pub fn events(&self, id: String) -> $crate::__private::js_sys::Promise {
+                let inner = self.inner.clone();
+                $crate::__private::wasm_bindgen_futures::future_to_promise(async move {
+                    $crate::__js_result!(inner.events(&id).await)
+                })
+            }
+
+            /// Resolves to the aggregate's current state as JSON.
+            pub fn state(&self, id: String) -> $crate::__private::js_sys::Promise {
+                let inner = self.inner.clone();
+                $crate::__private::wasm_bindgen_futures::future_to_promise(async move {
+                    $crate::__js_result!(inner.state(&id).await)
+                })
+            }
+        }
+    };
+}
+
+/// A JSON result as the outcome of a JS promise; not public API.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __js_result {
+    ($result:expr) => {
+        match $result {
+            Ok(json) => Ok($crate::__private::wasm_bindgen::JsValue::from(json)),
+            Err(error) => Err($crate::__private::wasm_bindgen::JsValue::from(
+                $crate::__private::wasm_bindgen::JsError::new(&error.to_string()),
+            )),
+        }
+    };
+}
@@ -0,0 +1,224 @@
+use std::rc::Rc;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{LocalCqrs, LocalEventStore};
+use crate::{Aggregate, Error, Metadata, SnapshotState, WithId};
+
+/// A [`LocalCqrs`] for one aggregate type driven with JSON strings, the shape JavaScript
+/// hands over; `js_bindings!` (feature `js-bindings`) exports one to JS with `wasm-bindgen`.
+///
+/// Ids are URN strings, commands are serialized like `A::Command` and events come back as
+/// the JSON array of the [`LocalEvent`](super::LocalEvent)s appended:
+///
+/// ```rust,ignore
+/// let accounts = JsonCqrs::<BankAccount, _>::new(LocalCqrs::new(store), services);
+/// let events = accounts
+///     .dispatch("urn:bank-account:42", r#"{"Deposit":{"amount":10.0}}"#, None)
+///     .await?;
+/// ```
+pub struct JsonCqrs<A: Aggregate, ES> {
+    cqrs: LocalCqrs<ES>,
+    services: Rc<A::Services>,
+}
+
+// Manual impl: handles share the store and services, which need not be `Clone`.
+impl<A: Aggregate, ES> Clone for JsonCqrs<A, ES> {
+    fn clone(&self) -> Self {
+        Self {
+            cqrs: self.cqrs.clone(),
+            services: self.services.clone(),
+        }
+    }
+}
+
+impl<A, ES> JsonCqrs<A, ES>
+where
+    A: Aggregate,
+    A::Command: DeserializeOwned,
+    ES: LocalEventStore,
+{
+    pub fn new(cqrs: LocalCqrs<ES>, services: A::Services) -> Self {
+        Self {
+            cqrs,
+            services: Rc::new(services),
+        }
+    }
+
+    /// Handle the JSON `command` on the aggregate `id`, with optional JSON `metadata`, and
+    /// return the events it appended as a JSON array.
+    pub async fn dispatch(
+        &self,
+        id: &str,
+        command: &str,
+        metadata: Option<&str>,
+    ) -> Result<String, A::Error> {
+        let id = stream_id::<A>(id)?;
+        let command: A::Command = from_json(command, "command")?;
+        let metadata = match metadata {
+            Some(metadata) => Metadata::new(from_json::<serde_json::Value>(metadata, "metadata")?),
+            None => Metadata::default(),
+        };
+
+        let (_, events) = self
+            .cqrs
+            .execute_recorded::<A>(&id, metadata, command, &self.services, None)
+            .await?;
+        Ok(to_json(&events)?)
+    }
+
+    /// The events of the aggregate `id` as a JSON array, oldest first.
+    pub async fn events(&self, id: &str) -> Result<String, A::Error> {
+        let id: urn::Urn = stream_id::<A>(id)?.into();
+        let events = self.cqrs.store().load(&id).await?;
+        Ok(to_json(&events)?)
+    }
+}
+
+impl<A, ES> JsonCqrs<A, ES>
+where
+    A: Aggregate + SnapshotState,
+    A::Command: DeserializeOwned,
+    ES: LocalEventStore,
+{
+    /// The current state of the aggregate `id` as JSON.
+    pub async fn state(&self, id: &str) -> Result<String, A::Error> {
+        let id = stream_id::<A>(id)?;
+        let aggregate = self.cqrs.fetch_aggregate::<A>(&id).await?;
+        Ok(to_json(&aggregate)?)
+    }
+}
+
+fn stream_id<A: WithId>(id: &str) -> crate::Result<A::StreamId> {
+    A::with_string_id(id).map(|aggregate| aggregate.get_id().clone())
+}
+
+fn from_json<T: DeserializeOwned>(json: &str, what: &str) -> crate::Result<T> {
+    serde_json::from_str(json).map_err(|e| {
+        Error::invalid_input(format!("invalid {what}: {e}")).with_operation("deserialize")
+    })
+}
+
+fn to_json<T: Serialize>(value: &T) -> crate::Result<String> {
+    serde_json::to_string(value).map_err(|e| {
+        Error::internal(format!("Serialization failed: {e}")).with_operation("serialize")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use urn::Urn;
+
+    use super::*;
+    use crate::local::LocalInMemoryEventStore;
+    use crate::{ErrorKind, Event, EventStream};
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+    enum NoteEvent {
+        Written { text: String },
+    }
+
+    impl Event for NoteEvent {
+        fn event_type(&self) -> &'static str {
+            "Written"
+        }
+    }
+
+    #[derive(Deserialize)]
+    enum NoteCommand {
+        Write { text: String },
+    }
+
+    #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+    struct NoteUrn(Urn);
+
+    impl From<NoteUrn> for Urn {
+        fn from(urn: NoteUrn) -> Self {
+            urn.0
+        }
+    }
+
+    impl TryFrom<Urn> for NoteUrn {
+        type Error = String;
+
+        fn try_from(urn: Urn) -> Result<Self, Self::Error> {
+            Ok(NoteUrn(urn))
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Note {
+        id: NoteUrn,
+        text: String,
+    }
+
+    impl WithId for Note {
+        type StreamId = NoteUrn;
+
+        fn with_id(id: Self::StreamId) -> Self {
+            Note {
+                id,
+                text: String::new(),
+            }
+        }
+
+        fn get_id(&self) -> &Self::StreamId {
+            &self.id
+        }
+    }
+
+    impl EventStream for Note {
+        type Event = NoteEvent;
+
+        fn stream_type() -> &'static str {
+            "Note"
+        }
+
+        fn apply(&mut self, NoteEvent::Written { text }: Self::Event) {
+            self.text = text;
+        }
+    }
+
+    impl Aggregate for Note {
+        type Command = NoteCommand;
+        type Error = Error;
+        type Services = ();
+
+        async fn handle(
+            &self,
+            NoteCommand::Write { text }: NoteCommand,
+            _: &(),
+        ) -> crate::Result<Vec<NoteEvent>> {
+            Ok(vec![NoteEvent::Written { text }])
+        }
+    }
+
+    #[test]
+    fn json_commands_return_json_events() {
+        let notes = JsonCqrs::<Note, _>::new(LocalCqrs::new(LocalInMemoryEventStore::new()), ());
+        let command = r#"{"Write":{"text":"hello"}}"#;
+
+        let events = block_on(notes.dispatch("urn:note:1", command, Some(r#"{"actor":"ada"}"#)));
+        let events: Value = serde_json::from_str(&events.unwrap()).unwrap();
+        let state = block_on(notes.state("urn:note:1")).unwrap();
+
+        assert_eq!(events[0]["data"], json!({ "Written": { "text": "hello" } }));
+        assert_eq!(events[0]["metadata"]["value"]["actor"], "ada");
+        assert_eq!(
+            serde_json::from_str::<Value>(&state).unwrap()["text"],
+            "hello"
+        );
+    }
+
+    #[test]
+    fn malformed_commands_are_invalid_input() {
+        let notes = JsonCqrs::<Note, _>::new(LocalCqrs::new(LocalInMemoryEventStore::new()), ());
+
+        let err = block_on(notes.dispatch("urn:note:1", r#"{"Erase":{}}"#, None)).unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
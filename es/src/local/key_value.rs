@@ -0,0 +1,258 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+use urn::Urn;
+
+use super::{append_to, LocalEvent, LocalEventStore};
+use crate::{Error, Result};
+
+/// Default [`KeyValueEventStore::with_quota`]: below the 5 MiB most browsers grant
+/// `localStorage` per origin.
+pub const DEFAULT_QUOTA_BYTES: usize = 4 * 1024 * 1024;
+
+/// Synchronous string storage, such as the browser's `localStorage`, a
+/// [`KeyValueEventStore`] keeps its events in.
+pub trait KeyValueStorage {
+    fn get(&self, key: &str) -> Result<Option<String>>;
+
+    /// Fails with [`Unavailable`](crate::ErrorKind::Unavailable) when the storage is full.
+    fn set(&self, key: &str, value: &str) -> Result<()>;
+}
+
+/// `localStorage` and `sessionStorage` (feature `web-storage`, on `wasm32`).
+#[cfg(all(target_arch = "wasm32", feature = "web-storage"))]
+impl KeyValueStorage for web_sys::Storage {
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        self.get_item(key).map_err(|e| {
+            Error::unavailable("web storage read failed")
+                .with_operation("get")
+                .with_context("key", key)
+                .with_context("error", format!("{e:?}"))
+        })
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<()> {
+        self.set_item(key, value).map_err(|e| {
+            Error::unavailable("web storage write failed")
+                .with_operation("set")
+                .with_context("key", key)
+                .with_context("error", format!("{e:?}"))
+        })
+    }
+}
+
+/// A [`LocalEventStore`] over a [`KeyValueStorage`], a lighter alternative to an IndexedDB
+/// backend for simple browser apps.
+///
+/// The whole log is kept as one JSON value under `{prefix}events`, rewritten on every
+/// append, so it suits stores of a few thousand events. Once it grows past the quota,
+/// streams are pruned oldest first, but only those whose events are all
+/// [synced](Self::mark_synced) elsewhere and can be reloaded from there; an append that
+/// still doesn't fit fails with [`Unavailable`](crate::ErrorKind::Unavailable).
+///
+/// ```rust,ignore
+/// let store = KeyValueEventStore::local_storage("banking:")?.with_quota(2 * 1024 * 1024);
+/// let cqrs = LocalCqrs::new(store);
+///
+/// // after pushing events up to `position` to the server:
+/// cqrs.store().mark_synced(position)?;
+/// ```
+#[derive(Debug)]
+pub struct KeyValueEventStore<S> {
+    storage: S,
+    prefix: String,
+    quota: usize,
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "web-storage"))]
+impl KeyValueEventStore<web_sys::Storage> {
+    /// A store in the window's `localStorage`, under keys starting with `prefix`.
+    pub fn local_storage(prefix: impl Into<String>) -> Result<Self> {
+        let storage = web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| {
+                Error::unavailable("localStorage is not available").with_operation("local_storage")
+            })?;
+        Ok(Self::new(storage, prefix))
+    }
+}
+
+impl<S: KeyValueStorage> KeyValueEventStore<S> {
+    /// A store in `storage`, under keys starting with `prefix`.
+    pub fn new(storage: S, prefix: impl Into<String>) -> Self {
+        Self {
+            storage,
+            prefix: prefix.into(),
+            quota: DEFAULT_QUOTA_BYTES,
+        }
+    }
+
+    /// Bytes of JSON the log may take before streams are pruned.
+    pub fn with_quota(mut self, bytes: usize) -> Self {
+        self.quota = bytes;
+        self
+    }
+
+    /// Record that every event up to the global position `position` is stored elsewhere, so
+    /// its streams may be pruned.
+    pub fn mark_synced(&self, position: i64) -> Result<()> {
+        self.storage.set(&self.key("synced"), &position.to_string())
+    }
+
+    /// The global position recorded by [`mark_synced`](Self::mark_synced), `0` if none.
+    pub fn synced(&self) -> Result<i64> {
+        Ok(self
+            .storage
+            .get(&self.key("synced"))?
+            .and_then(|position| position.parse().ok())
+            .unwrap_or(0))
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("{}{name}", self.prefix)
+    }
+
+    fn read_log(&self) -> Result<Vec<LocalEvent<Value>>> {
+        let Some(log) = self.storage.get(&self.key("events"))? else {
+            return Ok(Vec::new());
+        };
+        serde_json::from_str(&log).map_err(|e| {
+            Error::internal(format!("Deserialization failed: {e}"))
+                .with_operation("deserialize")
+                .with_context("key", self.key("events"))
+        })
+    }
+
+    fn write_log(&self, log: &[LocalEvent<Value>]) -> Result<()> {
+        let json = serde_json::to_string(log).map_err(|e| {
+            Error::internal(format!("Serialization failed: {e}")).with_operation("serialize")
+        })?;
+        if json.len() <= self.quota && self.storage.set(&self.key("events"), &json).is_ok() {
+            return Ok(());
+        }
+
+        let pruned = prune_synced_streams(log, self.synced()?);
+        if pruned.len() == log.len() {
+            return Err(Error::unavailable("local event store is full")
+                .with_operation("append")
+                .with_context("bytes", json.len())
+                .with_context("quota", self.quota));
+        }
+        self.write_log(&pruned)
+    }
+}
+
+/// `log` without its oldest stream whose events are all at or before `synced`, or `log`
+/// itself when there is none.
+fn prune_synced_streams(log: &[LocalEvent<Value>], synced: i64) -> Vec<LocalEvent<Value>> {
+    let unsynced: HashSet<&Urn> = log
+        .iter()
+        .filter(|event| event.global_position > synced)
+        .map(|event| &event.stream_id)
+        .collect();
+    let oldest = log
+        .iter()
+        .filter(|event| !unsynced.contains(&event.stream_id))
+        .min_by_key(|event| event.global_position)
+        .map(|event| event.stream_id.clone());
+
+    log.iter()
+        .filter(|event| Some(&event.stream_id) != oldest.as_ref())
+        .cloned()
+        .collect()
+}
+
+impl<S: KeyValueStorage> LocalEventStore for KeyValueEventStore<S> {
+    async fn load(&self, stream_id: &Urn) -> Result<Vec<LocalEvent<Value>>> {
+        let mut log = self.read_log()?;
+        log.retain(|event| &event.stream_id == stream_id);
+        Ok(log)
+    }
+
+    async fn append(&self, events: Vec<LocalEvent<Value>>) -> Result<Vec<LocalEvent<Value>>> {
+        let mut log = self.read_log()?;
+        let events = append_to(&mut log, events)?;
+        self.write_log(&log)?;
+        Ok(events)
+    }
+
+    async fn read_all(&self, after: i64) -> Result<Vec<LocalEvent<Value>>> {
+        let mut log = self.read_log()?;
+        log.retain(|event| event.global_position > after);
+        Ok(log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    use futures::executor::block_on;
+    use urn::UrnBuilder;
+
+    use super::*;
+    use crate::{ErrorKind, Metadata};
+
+    #[derive(Default)]
+    struct MapStorage(RefCell<HashMap<String, String>>);
+
+    impl KeyValueStorage for MapStorage {
+        fn get(&self, key: &str) -> Result<Option<String>> {
+            Ok(self.0.borrow().get(key).cloned())
+        }
+
+        fn set(&self, key: &str, value: &str) -> Result<()> {
+            self.0
+                .borrow_mut()
+                .insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+    }
+
+    fn event(stream: &str, version: i64) -> LocalEvent<Value> {
+        LocalEvent {
+            stream_id: UrnBuilder::new("note", stream).build().unwrap(),
+            stream_type: "Note".to_string(),
+            r#type: "Written".to_string(),
+            version,
+            global_position: 0,
+            metadata: Metadata::default(),
+            data: Value::String("x".repeat(100)),
+        }
+    }
+
+    #[test]
+    fn events_survive_a_new_store_over_the_same_storage() {
+        let store = KeyValueEventStore::new(MapStorage::default(), "app:");
+        block_on(store.append(vec![event("a", 1), event("a", 2)])).unwrap();
+
+        let reopened = KeyValueEventStore::new(store.storage, "app:");
+        let events = block_on(reopened.read_all(0)).unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].global_position, 2);
+    }
+
+    #[test]
+    fn only_synced_streams_are_pruned_to_fit_the_quota() {
+        let store = KeyValueEventStore::new(MapStorage::default(), "app:").with_quota(1200);
+        block_on(async {
+            store.append(vec![event("a", 1), event("a", 2)]).await?;
+            store.append(vec![event("b", 1), event("b", 2)]).await
+        })
+        .unwrap();
+
+        let full = block_on(store.append(vec![event("c", 1), event("c", 2)])).unwrap_err();
+        assert_eq!(full.kind(), ErrorKind::Unavailable);
+
+        store.mark_synced(2).unwrap();
+        block_on(store.append(vec![event("c", 1), event("c", 2)])).unwrap();
+        let streams: HashSet<String> = block_on(store.read_all(0))
+            .unwrap()
+            .into_iter()
+            .map(|event| event.stream_id.nss().to_string())
+            .collect();
+        assert_eq!(streams, HashSet::from(["b".to_string(), "c".to_string()]));
+    }
+}
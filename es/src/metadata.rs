@@ -1,22 +1,187 @@
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
 
+/// JSON metadata stored alongside events, usually an object of well-known and custom keys.
+///
+/// The `with_*` setters write into that object: empty (`null`) metadata becomes an object,
+/// and other non-object metadata is left untouched.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct Metadata {
     value: Value,
 }
 
 impl Metadata {
+    /// Well-known key holding the id shared by every event of one workflow.
+    pub const CORRELATION_ID_KEY: &'static str = "correlation_id";
+
+    /// Well-known key holding the id of the event (or command) that caused this one.
+    pub const CAUSATION_ID_KEY: &'static str = "causation_id";
+
+    /// Well-known key naming who issued the command that produced an event
+    /// (a user id, a service name, ...).
+    pub const ACTOR_KEY: &'static str = "actor";
+
+    /// Well-known key naming the tenant an event belongs to.
+    pub const TENANT_ID_KEY: &'static str = "tenant_id";
+
+    /// Well-known key holding the W3C `traceparent` of the span that appended the event.
+    pub const TRACEPARENT_KEY: &'static str = "traceparent";
+
+    /// Well-known key holding the W3C `tracestate` accompanying [`Self::TRACEPARENT_KEY`].
+    pub const TRACESTATE_KEY: &'static str = "tracestate";
+
+    /// Well-known key holding the payload's [`Event::event_version`](crate::Event::event_version)
+    /// when it is not `1`.
+    pub const EVENT_VERSION_KEY: &'static str = "event_version";
+
     pub fn new<S: Serialize>(value: S) -> Self {
         Metadata {
             value: serde_json::to_value(value).unwrap(),
         }
     }
 
+    /// Start composing metadata key by key.
+    pub fn builder() -> MetadataBuilder {
+        MetadataBuilder::default()
+    }
+
+    /// Continue composing from this metadata's entries.
+    ///
+    /// Non-object metadata has no entries to carry over and starts an empty builder.
+    pub fn into_builder(self) -> MetadataBuilder {
+        MetadataBuilder::default().merge(self)
+    }
+
     pub fn to_json(&self) -> Value {
         self.value.clone()
     }
 
+    /// Deserialize the value stored under `key`.
+    ///
+    /// Returns `None` when the key is missing or its value doesn't fit `T`.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.value
+            .get(key)
+            .and_then(|value| T::deserialize(value).ok())
+    }
+
+    /// Whether this metadata is an object carrying `key`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.value.get(key).is_some()
+    }
+
+    /// Check that every key in `keys` is present and not `null`.
+    ///
+    /// Fails with [`InvalidInput`](crate::ErrorKind::InvalidInput) listing the missing keys
+    /// under the `missing_keys` context entry.
+    ///
+    /// ```rust
+    /// use replay::Metadata;
+    ///
+    /// let metadata = Metadata::default().with_actor("user:alice");
+    /// let err = metadata
+    ///     .require(&[Metadata::TENANT_ID_KEY, Metadata::ACTOR_KEY])
+    ///     .unwrap_err();
+    /// assert_eq!(err.context(), [("missing_keys", "tenant_id".to_string())]);
+    /// ```
+    #[track_caller]
+    pub fn require(&self, keys: &[&str]) -> crate::Result<()> {
+        let missing: Vec<&str> = keys
+            .iter()
+            .copied()
+            .filter(|key| self.value.get(key).is_none_or(Value::is_null))
+            .collect();
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        Err(
+            crate::Error::invalid_input("metadata is missing required keys")
+                .with_operation("validate_metadata")
+                .with_context("missing_keys", missing.join(", ")),
+        )
+    }
+
+    /// The correlation id, if this metadata is an object carrying a string `correlation_id`.
+    pub fn correlation_id(&self) -> Option<&str> {
+        self.get_str(Self::CORRELATION_ID_KEY)
+    }
+
+    /// The causation id, if this metadata is an object carrying a string `causation_id`.
+    pub fn causation_id(&self) -> Option<&str> {
+        self.get_str(Self::CAUSATION_ID_KEY)
+    }
+
+    /// The actor, if this metadata is an object carrying a string `actor`.
+    pub fn actor(&self) -> Option<&str> {
+        self.get_str(Self::ACTOR_KEY)
+    }
+
+    /// The tenant, if this metadata is an object carrying a string `tenant_id`.
+    pub fn tenant_id(&self) -> Option<&str> {
+        self.get_str(Self::TENANT_ID_KEY)
+    }
+
+    /// The W3C `traceparent` header value, if recorded.
+    pub fn traceparent(&self) -> Option<&str> {
+        self.get_str(Self::TRACEPARENT_KEY)
+    }
+
+    /// The W3C `tracestate` header value, if recorded.
+    pub fn tracestate(&self) -> Option<&str> {
+        self.get_str(Self::TRACESTATE_KEY)
+    }
+
+    /// The payload schema version, if recorded.
+    pub fn event_version(&self) -> Option<u32> {
+        self.get(Self::EVENT_VERSION_KEY)
+    }
+
+    /// Set the correlation id, replacing any previous one.
+    pub fn with_correlation_id(mut self, id: impl Into<String>) -> Self {
+        self.set(Self::CORRELATION_ID_KEY, Value::String(id.into()));
+        self
+    }
+
+    /// Set the causation id, replacing any previous one.
+    pub fn with_causation_id(mut self, id: impl Into<String>) -> Self {
+        self.set(Self::CAUSATION_ID_KEY, Value::String(id.into()));
+        self
+    }
+
+    /// Set the actor, replacing any previous one.
+    pub fn with_actor(mut self, actor: impl Into<String>) -> Self {
+        self.set(Self::ACTOR_KEY, Value::String(actor.into()));
+        self
+    }
+
+    /// Set the tenant, replacing any previous one.
+    pub fn with_tenant_id(mut self, tenant_id: impl Into<String>) -> Self {
+        self.set(Self::TENANT_ID_KEY, Value::String(tenant_id.into()));
+        self
+    }
+
+    /// Set the payload schema version, replacing any previous one.
+    pub fn with_event_version(mut self, version: u32) -> Self {
+        self.set(Self::EVENT_VERSION_KEY, Value::from(version));
+        self
+    }
+
+    /// Record a W3C trace context, replacing any previous one.
+    ///
+    /// An empty `tracestate` is not stored, per the W3C recommendation to omit empty headers.
+    pub fn with_trace_context(mut self, traceparent: impl Into<String>, tracestate: &str) -> Self {
+        self.set(Self::TRACEPARENT_KEY, Value::String(traceparent.into()));
+        if let Value::Object(map) = &mut self.value {
+            map.remove(Self::TRACESTATE_KEY);
+        }
+        if !tracestate.is_empty() {
+            self.set(Self::TRACESTATE_KEY, Value::String(tracestate.to_string()));
+        }
+        self
+    }
+
     /// Check if one metadata matches another.
     ///
     /// If passed metadata has different type of current metadata, returns false
@@ -44,6 +209,118 @@ impl Metadata {
             _ => self_json == other_json,
         }
     }
+
+    fn get_str(&self, key: &str) -> Option<&str> {
+        self.value.get(key).and_then(Value::as_str)
+    }
+
+    /// Insert `key`, turning empty (`null`) metadata into an object; other non-object
+    /// metadata is left untouched.
+    fn set(&mut self, key: &str, value: Value) {
+        if self.value.is_null() {
+            self.value = Value::Object(Default::default());
+        }
+        if let Value::Object(map) = &mut self.value {
+            map.insert(key.to_string(), value);
+        }
+    }
+}
+
+/// Composes [`Metadata`] from several sources.
+///
+/// Entries are kept in a JSON object, so the built metadata serializes exactly like
+/// `Metadata::new(json!({ ... }))` would; a builder with no entries builds the same empty
+/// metadata as [`Metadata::default`].
+///
+/// ```
+/// # use replay::Metadata;
+/// let request = Metadata::builder()
+///     .correlation_id("req-1")
+///     .insert("tenant", "acme")
+///     .build();
+///
+/// let metadata = Metadata::builder()
+///     .actor("user:42")
+///     .merge(request)
+///     .insert("retries", 2)
+///     .remove("tenant")
+///     .build();
+///
+/// assert_eq!(metadata.actor(), Some("user:42"));
+/// assert_eq!(metadata.correlation_id(), Some("req-1"));
+/// assert_eq!(metadata.get::<u32>("retries"), Some(2));
+/// assert!(!metadata.contains_key("tenant"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetadataBuilder {
+    entries: Map<String, Value>,
+}
+
+impl MetadataBuilder {
+    /// Set `key` to the serialized `value`, replacing any previous entry.
+    pub fn insert<V: Serialize>(mut self, key: impl Into<String>, value: V) -> Self {
+        self.entries
+            .insert(key.into(), serde_json::to_value(value).unwrap());
+        self
+    }
+
+    /// Copy every entry of `other` into this builder; `other` wins on shared keys.
+    ///
+    /// Only object metadata has entries: merging empty or non-object metadata is a no-op.
+    pub fn merge(mut self, other: Metadata) -> Self {
+        if let Value::Object(entries) = other.value {
+            self.entries.extend(entries);
+        }
+        self
+    }
+
+    /// Drop the entry under `key`, if any.
+    pub fn remove(mut self, key: &str) -> Self {
+        self.entries.remove(key);
+        self
+    }
+
+    /// Deserialize the entry under `key`.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.entries
+            .get(key)
+            .and_then(|value| T::deserialize(value).ok())
+    }
+
+    /// Set the well-known [`Metadata::CORRELATION_ID_KEY`] entry.
+    pub fn correlation_id(self, id: impl Into<String>) -> Self {
+        self.insert(Metadata::CORRELATION_ID_KEY, id.into())
+    }
+
+    /// Set the well-known [`Metadata::CAUSATION_ID_KEY`] entry.
+    pub fn causation_id(self, id: impl Into<String>) -> Self {
+        self.insert(Metadata::CAUSATION_ID_KEY, id.into())
+    }
+
+    /// Set the well-known [`Metadata::ACTOR_KEY`] entry.
+    pub fn actor(self, actor: impl Into<String>) -> Self {
+        self.insert(Metadata::ACTOR_KEY, actor.into())
+    }
+
+    /// Set the well-known [`Metadata::TENANT_ID_KEY`] entry.
+    pub fn tenant_id(self, tenant_id: impl Into<String>) -> Self {
+        self.insert(Metadata::TENANT_ID_KEY, tenant_id.into())
+    }
+
+    pub fn build(self) -> Metadata {
+        if self.entries.is_empty() {
+            return Metadata::default();
+        }
+        Metadata {
+            value: Value::Object(self.entries),
+        }
+    }
+}
+
+impl From<MetadataBuilder> for Metadata {
+    fn from(builder: MetadataBuilder) -> Self {
+        builder.build()
+    }
 }
 
 impl From<Metadata> for Value {
@@ -51,3 +328,113 @@ impl From<Metadata> for Value {
         metadata.to_json()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn correlation_and_causation_ids_round_trip() {
+        let metadata = Metadata::default()
+            .with_correlation_id("req-1")
+            .with_causation_id("evt-9");
+
+        assert_eq!(metadata.correlation_id(), Some("req-1"));
+        assert_eq!(metadata.causation_id(), Some("evt-9"));
+        assert_eq!(
+            metadata.to_json(),
+            json!({ "correlation_id": "req-1", "causation_id": "evt-9" })
+        );
+    }
+
+    #[test]
+    fn ids_are_added_alongside_existing_fields() {
+        let metadata = Metadata::new(json!({ "user_id": "u-1" })).with_correlation_id("req-1");
+
+        assert_eq!(metadata.to_json()["user_id"], "u-1");
+        assert_eq!(metadata.correlation_id(), Some("req-1"));
+        assert_eq!(metadata.causation_id(), None);
+    }
+
+    #[test]
+    fn actor_round_trips() {
+        let metadata = Metadata::default().with_actor("user:42");
+
+        assert_eq!(metadata.actor(), Some("user:42"));
+        assert_eq!(metadata.to_json(), json!({ "actor": "user:42" }));
+    }
+
+    #[test]
+    fn event_version_round_trips() {
+        let metadata = Metadata::default().with_event_version(3);
+
+        assert_eq!(metadata.event_version(), Some(3));
+        assert_eq!(metadata.to_json(), json!({ "event_version": 3 }));
+        assert_eq!(Metadata::default().event_version(), None);
+    }
+
+    #[test]
+    fn builder_matches_json_metadata() {
+        let built = Metadata::builder()
+            .insert("user_id", "u-1")
+            .insert("attempt", 3)
+            .build();
+
+        assert_eq!(
+            built,
+            Metadata::new(json!({ "user_id": "u-1", "attempt": 3 }))
+        );
+        assert_eq!(Metadata::builder().build(), Metadata::default());
+    }
+
+    #[test]
+    fn builder_merge_later_source_wins_and_skips_non_objects() {
+        let metadata = Metadata::new(json!({ "a": 1, "b": 1 }))
+            .into_builder()
+            .merge(Metadata::new(json!({ "b": 2, "c": 2 })))
+            .merge(Metadata::new("plain"))
+            .merge(Metadata::default())
+            .build();
+
+        assert_eq!(metadata.to_json(), json!({ "a": 1, "b": 2, "c": 2 }));
+    }
+
+    #[test]
+    fn typed_getters_and_removal() {
+        let builder = Metadata::builder()
+            .insert("retries", 2)
+            .insert("tags", vec!["x", "y"]);
+        assert_eq!(builder.get::<u8>("retries"), Some(2));
+
+        let metadata = builder.remove("retries").build();
+
+        assert_eq!(metadata.get::<u8>("retries"), None);
+        assert_eq!(
+            metadata.get::<Vec<String>>("tags"),
+            Some(vec!["x".to_string(), "y".to_string()])
+        );
+        assert_eq!(metadata.get::<u8>("tags"), None);
+    }
+
+    #[test]
+    fn trace_context_round_trips_and_omits_empty_tracestate() {
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+        let metadata = Metadata::default().with_trace_context(traceparent, "vendor=1");
+        assert_eq!(metadata.traceparent(), Some(traceparent));
+        assert_eq!(metadata.tracestate(), Some("vendor=1"));
+
+        let metadata = metadata.with_trace_context(traceparent, "");
+        assert_eq!(metadata.tracestate(), None);
+    }
+
+    #[test]
+    fn non_object_metadata_has_no_ids() {
+        let metadata = Metadata::new("plain").with_correlation_id("req-1");
+
+        assert_eq!(metadata.correlation_id(), None);
+        assert_eq!(metadata.to_json(), json!("plain"));
+    }
+}
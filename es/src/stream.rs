@@ -1,7 +1,9 @@
+use std::time::SystemTime;
+
 use serde::{de::DeserializeOwned, Serialize};
 use urn::Urn;
 
-use crate::Error;
+use crate::{Error, Metadata};
 
 use super::Event;
 
@@ -165,11 +167,53 @@ pub trait ScopedUrn: Sized + Clone + Into<Urn> + TryFrom<Urn, Error: std::fmt::D
 /// `at` and `extract_scope` for free.
 impl<T> ScopedUrn for T where T: Sized + Clone + Into<Urn> + TryFrom<Urn, Error: std::fmt::Debug> {}
 
-/// A trait for types that have a stream identifier.
+/// The identifier of a stream.
 ///
-/// This trait provides constructor and accessor methods for working with stream identifiers.
-pub trait WithId: Sized {
-    type StreamId: Send
+/// Stores address every stream by a [`Urn`], so an id is any type that converts to and from
+/// one: the `#[derive(Urn)]` newtypes, or UUID and string keys through `#[derive(StreamId)]`,
+/// which files the key as the NSS of a fixed namespace. Implemented for every such type.
+pub trait StreamId:
+    Send
+    + Sync
+    + Into<Urn>
+    + TryFrom<Urn, Error: std::fmt::Debug>
+    + Clone
+    + PartialEq
+    + std::fmt::Debug
+    + Serialize
+    + DeserializeOwned
+{
+    /// The URN the stream is stored under.
+    fn stream_urn(&self) -> Urn {
+        self.clone().into()
+    }
+
+    /// The namespace (NID) of the [`stream_urn`](Self::stream_urn).
+    fn stream_namespace(&self) -> String {
+        self.stream_urn().nid().to_string()
+    }
+
+    /// Parse an id from its URN string, e.g. `urn:order:0190c3a1-...`.
+    fn parse_stream_id(input: &str) -> crate::Result<Self> {
+        use std::str::FromStr;
+
+        let urn = Urn::from_str(input).map_err(|e| {
+            Error::invalid_input("Invalid URN format")
+                .with_operation("parse_stream_id")
+                .with_context("id", input)
+                .with_context("error", format!("{:?}", e))
+        })?;
+        Self::try_from(urn).map_err(|e| {
+            Error::invalid_input("Failed to convert URN to StreamId type")
+                .with_operation("parse_stream_id")
+                .with_context("id", input)
+                .with_context("error", format!("{:?}", e))
+        })
+    }
+}
+
+impl<T> StreamId for T where
+    T: Send
         + Sync
         + Into<Urn>
         + TryFrom<Urn, Error: std::fmt::Debug>
@@ -177,7 +221,71 @@ pub trait WithId: Sized {
         + PartialEq
         + std::fmt::Debug
         + Serialize
-        + DeserializeOwned;
+        + DeserializeOwned
+{
+}
+
+/// The URN `urn:<namespace>:<key>` a `#[derive(StreamId)]` id is stored under. Characters a
+/// URN can't hold, and `@` (which would read as a [scope](ScopedUrn)), are percent-encoded.
+///
+/// # Panics
+///
+/// If `namespace` isn't a valid NID or `key` is empty. A derived id rules both out: the derive
+/// checks the namespace at compile time, and its `new`, `TryFrom<Urn>` and `Deserialize`
+/// refuse empty keys.
+pub fn key_urn(namespace: &str, key: &str) -> Urn {
+    let mut nss = String::with_capacity(key.len());
+    for (i, byte) in key.bytes().enumerate() {
+        let plain = byte.is_ascii_alphanumeric()
+            || b"-._~!$&'()*+,;=:".contains(&byte)
+            || (byte == b'/' && i > 0);
+        if plain {
+            nss.push(byte as char);
+        } else {
+            nss.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    urn::UrnBuilder::new(namespace, &nss)
+        .build()
+        .unwrap_or_else(|e| panic!("`{key}` can't be a `{namespace}` stream key: {e:?}"))
+}
+
+/// The key [`key_urn`] filed in `urn`, after checking its namespace.
+pub fn urn_key(namespace: &str, urn: &Urn) -> crate::Result<String> {
+    let invalid = |message: &'static str| {
+        Error::invalid_input(message)
+            .with_operation("urn_key")
+            .with_context("urn", urn.to_string())
+            .with_context("namespace", namespace)
+    };
+    if urn.nid() != namespace {
+        return Err(invalid("URN is not in the stream id's namespace"));
+    }
+    let nss = urn.nss().as_bytes();
+    let mut key = Vec::with_capacity(nss.len());
+    let mut i = 0;
+    while i < nss.len() {
+        if nss[i] == b'%' {
+            let byte = nss
+                .get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| invalid("URN holds a malformed percent-encoding"))?;
+            key.push(byte);
+            i += 3;
+        } else {
+            key.push(nss[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(key).map_err(|_| invalid("URN key is not UTF-8"))
+}
+
+/// A trait for types that have a stream identifier.
+///
+/// This trait provides constructor and accessor methods for working with stream identifiers.
+pub trait WithId: Sized {
+    type StreamId: StreamId;
 
     /// Creates a new instance with the given id.
     fn with_id(id: Self::StreamId) -> Self;
@@ -186,26 +294,10 @@ pub trait WithId: Sized {
     fn get_id(&self) -> &Self::StreamId;
 
     fn with_string_id(id: impl Into<String>) -> crate::Result<Self> {
-        use std::str::FromStr;
-        let id_string = id.into();
-
-        // Parse string as URN
-        let urn = Urn::from_str(&id_string).map_err(|e| {
-            Error::invalid_input("Invalid URN format")
-                .with_operation("with_string_id")
-                .with_context("id", id_string.clone())
-                .with_context("error", format!("{:?}", e))
-        })?;
-
-        // Convert URN to aggregate StreamId type
-        let aggregate_id: Self::StreamId = urn.try_into().map_err(|e| {
-            Error::invalid_input("Failed to convert URN to StreamId type")
-                .with_operation("with_string_id")
-                .with_context("id", id_string.clone())
-                .with_context("error", format!("{:?}", e))
-        })?;
-
-        Ok(Self::with_id(aggregate_id))
+        let id = id.into();
+        let stream_id =
+            Self::StreamId::parse_stream_id(&id).map_err(|e| e.with_operation("with_string_id"))?;
+        Ok(Self::with_id(stream_id))
     }
 
     /// Returns a new instance whose stream ID has the suffix `@<other.nid>:<other.nss>` appended
@@ -272,10 +364,10 @@ pub trait WithId: Sized {
 /// }
 ///
 /// impl Event for BankAccountEvent {
-///     fn event_type(&self) -> String {
+///     fn event_type(&self) -> &'static str {
 ///         match self {
-///             BankAccountEvent::Deposited { .. } => "Deposited".to_string(),
-///             BankAccountEvent::Withdrawn { .. } => "Withdrawn".to_string(),
+///             BankAccountEvent::Deposited { .. } => "Deposited",
+///             BankAccountEvent::Withdrawn { .. } => "Withdrawn",
 ///         }
 ///     }
 /// }
@@ -312,8 +404,8 @@ pub trait WithId: Sized {
 /// impl EventStream for BankAccountStream {
 ///    type Event = BankAccountEvent;
 ///
-///    fn stream_type() -> String {
-///        "BankAccount".to_string()
+///    fn stream_type() -> &'static str {
+///        "BankAccount"
 ///    }
 ///
 ///    fn apply(&mut self, event: Self::Event) {
@@ -331,10 +423,39 @@ pub trait WithId: Sized {
 pub trait EventStream: Sized + WithId {
     type Event: Event;
 
-    fn stream_type() -> String;
+    /// The type streams of this kind are stored under. `define_aggregate!` implements it, and
+    /// so does `#[event_stream]`, also on a hand-written impl that leaves it out: there it's
+    /// the type's name in kebab-case (`BankAccountStream` → `bank-account-stream`), like the
+    /// `STREAM_TYPE` constant of `#[derive(StreamType)]`.
+    ///
+    /// Stored events keep the stream type they were written with, so keep it when renaming
+    /// a type that already has events.
+    fn stream_type() -> &'static str;
 
     fn apply(&mut self, event: Self::Event);
 
+    /// [`apply`](Self::apply) an event that may not fit the current state, e.g. a withdrawal
+    /// from an account that was never opened. Loading a stream from the store goes through
+    /// here, so an error fails the load instead of producing a wrong state. By default the
+    /// event is applied and accepted.
+    fn try_apply(&mut self, event: Self::Event) -> crate::Result<()> {
+        self.apply(event);
+        Ok(())
+    }
+
+    /// [`try_apply`](Self::try_apply) an event read back from a store, with its envelope:
+    /// version, position, timestamp and metadata. Loading a stream goes through here, so
+    /// state such as a `last_updated` can be folded without copying the envelope into every
+    /// payload. By default the context is ignored.
+    fn apply_with_context(
+        &mut self,
+        event: Self::Event,
+        context: &EventContext<'_>,
+    ) -> crate::Result<()> {
+        let _ = context;
+        self.try_apply(event)
+    }
+
     fn apply_all(&mut self, events: Vec<Self::Event>) {
         for event in events {
             self.apply(event);
@@ -342,6 +463,57 @@ pub trait EventStream: Sized + WithId {
     }
 }
 
+/// The envelope of a stored event, handed to [`EventStream::apply_with_context`].
+#[derive(Clone, Copy, Debug)]
+pub struct EventContext<'a> {
+    stream_id: &'a Urn,
+    version: i64,
+    global_position: i64,
+    created: SystemTime,
+    metadata: &'a Metadata,
+}
+
+impl<'a> EventContext<'a> {
+    pub fn new(
+        stream_id: &'a Urn,
+        version: i64,
+        global_position: i64,
+        created: SystemTime,
+        metadata: &'a Metadata,
+    ) -> Self {
+        EventContext {
+            stream_id,
+            version,
+            global_position,
+            created,
+            metadata,
+        }
+    }
+
+    pub fn stream_id(&self) -> &'a Urn {
+        self.stream_id
+    }
+
+    /// The event's version within its stream.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// The event's store-wide append position.
+    pub fn global_position(&self) -> i64 {
+        self.global_position
+    }
+
+    /// When the event was appended.
+    pub fn created(&self) -> SystemTime {
+        self.created
+    }
+
+    pub fn metadata(&self) -> &'a Metadata {
+        self.metadata
+    }
+}
+
 /*
 /// Stream state is a representation of the current state of a stream, every time an event is applied the state is updated and the version will increment.
 ///
@@ -389,10 +561,10 @@ mod tests {
     }
 
     impl crate::Event for BankAccountEvent {
-        fn event_type(&self) -> String {
+        fn event_type(&self) -> &'static str {
             match self {
-                BankAccountEvent::Deposited { .. } => "Deposited".to_string(),
-                BankAccountEvent::Withdrawn { .. } => "Withdrawn".to_string(),
+                BankAccountEvent::Deposited { .. } => "Deposited",
+                BankAccountEvent::Withdrawn { .. } => "Withdrawn",
             }
         }
     }
@@ -431,8 +603,8 @@ mod tests {
     impl EventStream for BankAccountStream {
         type Event = BankAccountEvent;
 
-        fn stream_type() -> String {
-            "BankAccount".to_string()
+        fn stream_type() -> &'static str {
+            "BankAccount"
         }
 
         fn apply(&mut self, event: Self::Event) {
@@ -679,4 +851,14 @@ mod tests {
         assert_eq!(scope_urn.nid(), "catalog");
         assert_eq!(scope_urn.nss(), "that");
     }
+
+    #[test]
+    fn test_keys_round_trip_through_their_urn() {
+        let urn = key_urn("order", "eu/2024 #7@shop");
+        assert_eq!(urn.to_string(), "urn:order:eu/2024%20%237%40shop");
+        assert_eq!(urn_key("order", &urn).unwrap(), "eu/2024 #7@shop");
+
+        let err = urn_key("invoice", &urn).unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::InvalidInput);
+    }
 }
@@ -0,0 +1,373 @@
+//! Given-when-then tests for aggregates.
+
+use crate::{Aggregate, Error, ErrorKind};
+
+/// Generate given-events / when-command / then-events (or error) tests for an [`Aggregate`].
+///
+/// Each test starts from a fresh aggregate, applies the `given` events, handles the `when`
+/// command and checks the outcome:
+///
+/// - `then: [..]` — the command succeeds with exactly these events;
+/// - `then_error: Kind` — the command fails with this [`ErrorKind`](crate::ErrorKind); the
+///   aggregate's error type needs a `kind()` method, as [`Error`](crate::Error) has.
+///
+/// `id` defaults to the stream id's `new_random()` (generated by `#[derive(Urn)]`), `services`
+/// to `Default::default()` and `given` to no events. Tests run on a minimal executor under
+/// `#[test]`; put attributes such as `#[tokio::test]` before a test to make it an `async fn`
+/// under that harness instead.
+///
+/// ```rust,ignore
+/// replay::aggregate_test! {
+///     deposit_adds_to_the_balance: BankAccount {
+///         when: BankAccountCommand::Deposit { amount: 50.0 },
+///         then: [BankAccountEvent::Deposited { amount: 50.0 }],
+///     }
+///
+///     #[tokio::test]
+///     overdraft_is_rejected: BankAccount {
+///         services: Arc::new(StubServices),
+///         given: [BankAccountEvent::Deposited { amount: 100.0 }],
+///         when: BankAccountCommand::Withdraw { amount: 150.0 },
+///         then_error: BusinessRuleViolation,
+///     }
+/// }
+/// ```
+///
+/// [`Aggregate`]: crate::Aggregate
+#[macro_export]
+macro_rules! aggregate_test {
+    () => {};
+
+    (@id $aggregate:ty;) => {
+        <<$aggregate as $crate::WithId>::StreamId>::new_random()
+    };
+    (@id $aggregate:ty; $id:expr) => {
+        $id
+    };
+
+    (@services) => {
+        ::std::default::Default::default()
+    };
+    (@services $services:expr) => {
+        $services
+    };
+
+    (@then $result:ident then [$($event:expr),* $(,)?]) => {
+        match $result {
+            Ok(events) => assert_eq!(events, vec![$($event),*]),
+            Err(err) => panic!("expected events, got error: {err:?}"),
+        }
+    };
+    (@then $result:ident then_error $kind:ident) => {
+        match $result {
+            Ok(events) => panic!(
+                "expected a {:?} error, got events: {events:?}",
+                $crate::ErrorKind::$kind
+            ),
+            Err(err) => assert_eq!(err.kind(), $crate::ErrorKind::$kind, "{err:?}"),
+        }
+    };
+
+    (@body $aggregate:ty, [$($id:expr)?], [$($services:expr)?], [$($given:expr),*], $command:expr, $then:ident $expected:tt) => {{
+        let mut aggregate =
+            <$aggregate as $crate::WithId>::with_id($crate::aggregate_test!(@id $aggregate; $($id)?));
+        $crate::EventStream::apply_all(&mut aggregate, vec![$($given),*]);
+        let services: <$aggregate as $crate::Aggregate>::Services =
+            $crate::aggregate_test!(@services $($services)?);
+
+        let result = $crate::Aggregate::handle(&aggregate, $command, &services).await;
+        $crate::aggregate_test!(@then result $then $expected);
+    }};
+
+    (
+        #[$attr:meta] $(#[$more:meta])*
+        $name:ident : $aggregate:ty {
+            $(id: $id:expr,)?
+            $(services: $services:expr,)?
+            $(given: [$($given:expr),* $(,)?],)?
+            when: $command:expr,
+            $then:ident : $expected:tt $(,)?
+        }
+        $($rest:tt)*
+    ) => {
+        #[$attr]
+        $(#[$more])*
+        async fn $name() {
+            $crate::aggregate_test!(@body $aggregate, [$($id)?], [$($services)?], [$($($given),*)?], $command, $then $expected)
+        }
+
+        $crate::aggregate_test!($($rest)*);
+    };
+
+    (
+        $name:ident : $aggregate:ty {
+            $(id: $id:expr,)?
+            $(services: $services:expr,)?
+            $(given: [$($given:expr),* $(,)?],)?
+            when: $command:expr,
+            $then:ident : $expected:tt $(,)?
+        }
+        $($rest:tt)*
+    ) => {
+        #[test]
+        fn $name() {
+            $crate::__private::block_on(async {
+                $crate::aggregate_test!(@body $aggregate, [$($id)?], [$($services)?], [$($($given),*)?], $command, $then $expected)
+            })
+        }
+
+        $crate::aggregate_test!($($rest)*);
+    };
+}
+
+/// Given-when-then fixture for an [`Aggregate`], the builder counterpart of
+/// [`aggregate_test!`](crate::aggregate_test).
+///
+/// ```rust,ignore
+/// use replay::testing::TestFixture;
+///
+/// let account = TestFixture::<BankAccount>::with_services(id, Arc::new(StubServices))
+///     .given([BankAccountEvent::Deposited { amount: 100.0 }])
+///     .when(BankAccountCommand::Withdraw { amount: 30.0 })
+///     .await
+///     .then_expect_events([BankAccountEvent::Withdrawn { amount: 30.0 }]);
+/// assert_eq!(account.balance, 70.0);
+/// ```
+pub struct TestFixture<A: Aggregate> {
+    aggregate: A,
+    services: A::Services,
+}
+
+impl<A: Aggregate> TestFixture<A> {
+    /// Start from a fresh aggregate with `id` and default services.
+    pub fn with_id(id: A::StreamId) -> Self
+    where
+        A::Services: Default,
+    {
+        Self::with_services(id, A::Services::default())
+    }
+
+    /// Start from a fresh aggregate with `id`, handling commands with `services` (usually a
+    /// stub implementation of the aggregate's services trait).
+    pub fn with_services(id: A::StreamId, services: A::Services) -> Self {
+        Self {
+            aggregate: A::with_id(id),
+            services,
+        }
+    }
+
+    /// Apply past events to the aggregate.
+    pub fn given(mut self, events: impl IntoIterator<Item = A::Event>) -> Self {
+        self.aggregate.apply_all(events.into_iter().collect());
+        self
+    }
+
+    /// Handle `command`; check the outcome on the returned [`Then`].
+    pub async fn when(self, command: A::Command) -> Then<A> {
+        let result = self.aggregate.handle(command, &self.services).await;
+        Then {
+            aggregate: self.aggregate,
+            result,
+        }
+    }
+}
+
+/// Outcome of [`TestFixture::when`].
+pub struct Then<A: Aggregate> {
+    aggregate: A,
+    result: Result<Vec<A::Event>, A::Error>,
+}
+
+impl<A: Aggregate> Then<A> {
+    /// Assert the command emitted exactly `expected`; returns the aggregate with them applied.
+    #[track_caller]
+    pub fn then_expect_events(self, expected: impl IntoIterator<Item = A::Event>) -> A {
+        let mut aggregate = self.aggregate;
+        match self.result {
+            Ok(events) => {
+                assert_eq!(events, expected.into_iter().collect::<Vec<_>>());
+                aggregate.apply_all(events);
+                aggregate
+            }
+            Err(err) => panic!("expected events, got error: {err:?}"),
+        }
+    }
+
+    /// Assert the command failed; returns its error.
+    #[track_caller]
+    pub fn then_expect_error(self) -> A::Error {
+        match self.result {
+            Ok(events) => panic!("expected an error, got events: {events:?}"),
+            Err(err) => err,
+        }
+    }
+
+    /// Assert the command failed with an error of `kind`; returns the error.
+    #[track_caller]
+    pub fn then_expect_error_kind(self, kind: ErrorKind) -> Error
+    where
+        A: Aggregate<Error = Error>,
+    {
+        match self.result {
+            Ok(events) => panic!("expected a {kind:?} error, got events: {events:?}"),
+            Err(err) => {
+                assert_eq!(err.kind(), kind, "{err:?}");
+                err
+            }
+        }
+    }
+}
+
+/// Set to `1` to rewrite the golden files checked by [`assert_event_snapshots`].
+pub const UPDATE_SNAPSHOTS_ENV: &str = "REPLAY_UPDATE_SNAPSHOTS";
+
+/// Check that `samples` still serialize exactly as recorded in the golden files in `dir`, and
+/// that those files still deserialize into the samples.
+///
+/// Each sample is stored as pretty-printed JSON in `<dir>/<event_type>.json`; give one sample
+/// per variant. A missing file is written (commit it), and a file no sample matches fails the
+/// check, since its variant was renamed or removed. Set [`UPDATE_SNAPSHOTS_ENV`] to `1` to
+/// rewrite the files after an intended change.
+///
+/// ```rust,ignore
+/// #[test]
+/// fn bank_account_events_keep_their_format() {
+///     replay::testing::assert_event_snapshots(
+///         concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots/bank_account"),
+///         [
+///             BankAccountEvent::Deposited { amount: 100.0 },
+///             BankAccountEvent::Withdrawn { amount: 40.0 },
+///         ],
+///     );
+/// }
+/// ```
+#[track_caller]
+pub fn assert_event_snapshots<E: crate::Event>(
+    dir: impl AsRef<std::path::Path>,
+    samples: impl IntoIterator<Item = E>,
+) {
+    use std::collections::BTreeSet;
+    use std::fs;
+
+    let dir = dir.as_ref();
+    let update = std::env::var(UPDATE_SNAPSHOTS_ENV).is_ok_and(|value| value == "1");
+    fs::create_dir_all(dir)
+        .unwrap_or_else(|err| panic!("can't create snapshot dir {}: {err}", dir.display()));
+
+    let mut failures = Vec::new();
+    let mut expected_files = BTreeSet::new();
+    for sample in samples {
+        let file_name = format!("{}.json", sample.event_type());
+        let path = dir.join(&file_name);
+        if !expected_files.insert(file_name.clone()) {
+            panic!("more than one sample of {}", sample.event_type());
+        }
+
+        let actual = serde_json::to_string_pretty(&sample)
+            .unwrap_or_else(|err| panic!("can't serialize {sample:?}: {err}"))
+            + "\n";
+
+        let golden = match fs::read_to_string(&path) {
+            Ok(golden) if !update => golden,
+            _ => {
+                fs::write(&path, &actual)
+                    .unwrap_or_else(|err| panic!("can't write {}: {err}", path.display()));
+                continue;
+            }
+        };
+
+        if golden != actual {
+            failures.push(format!(
+                "{file_name} changed\n--- golden\n{golden}--- now\n{actual}"
+            ));
+        }
+        match serde_json::from_str::<E>(&golden) {
+            Ok(stored) if stored == sample => {}
+            Ok(stored) => failures.push(format!(
+                "{file_name} now deserializes as {stored:?}, expected {sample:?}"
+            )),
+            Err(err) => failures.push(format!("{file_name} no longer deserializes: {err}")),
+        }
+    }
+
+    let entries = fs::read_dir(dir)
+        .unwrap_or_else(|err| panic!("can't read snapshot dir {}: {err}", dir.display()));
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        if file_name.ends_with(".json") && !expected_files.contains(&file_name) {
+            failures.push(format!(
+                "{file_name} has no sample: was its variant renamed or removed?"
+            ));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "event snapshots in {} don't match (set {UPDATE_SNAPSHOTS_ENV}=1 to accept):\n\n{}",
+        dir.display(),
+        failures.join("\n")
+    );
+}
+
+/// Handle `commands` in order from a fresh aggregate, checking `invariant` after every accepted
+/// command and, at the end, that replaying the emitted events from scratch rebuilds the same
+/// state (compared as JSON). Rejected commands are skipped, as a random sequence will contain
+/// many.
+///
+/// Meant for `proptest!` bodies, with commands from the `Arbitrary` impls the macros derive;
+/// returns the final aggregate for further checks.
+///
+/// ```rust,ignore
+/// proptest! {
+///     #[test]
+///     fn balance_never_goes_negative(commands in vec(any::<BankAccountCommand>(), 0..20)) {
+///         replay::testing::check_command_sequence::<BankAccount>(
+///             BankAccountUrn::new_random(),
+///             &BankAccountServices::default(),
+///             commands,
+///             |account| match account.balance >= 0 {
+///                 true => Ok(()),
+///                 false => Err(format!("balance is {}", account.balance)),
+///             },
+///         )?;
+///     }
+/// }
+/// ```
+#[cfg(feature = "proptest")]
+pub fn check_command_sequence<A>(
+    id: A::StreamId,
+    services: &A::Services,
+    commands: impl IntoIterator<Item = A::Command>,
+    invariant: impl Fn(&A) -> Result<(), String>,
+) -> Result<A, proptest::test_runner::TestCaseError>
+where
+    A: Aggregate + crate::SnapshotState,
+{
+    use proptest::test_runner::TestCaseError;
+
+    let mut aggregate = A::with_id(id.clone());
+    let mut history = Vec::new();
+    for (i, command) in commands.into_iter().enumerate() {
+        let Ok(events) = futures::executor::block_on(aggregate.handle(command, services)) else {
+            continue;
+        };
+        history.extend(events.iter().cloned());
+        aggregate.apply_all(events);
+        invariant(&aggregate)
+            .map_err(|reason| TestCaseError::fail(format!("after command {i}: {reason}")))?;
+    }
+
+    let mut replayed = A::with_id(id);
+    replayed.apply_all(history);
+    let state = |aggregate: &A| {
+        serde_json::to_value(aggregate)
+            .map_err(|err| TestCaseError::fail(format!("can't serialize the aggregate: {err}")))
+    };
+    let (handled, rebuilt) = (state(&aggregate)?, state(&replayed)?);
+    if handled != rebuilt {
+        return Err(TestCaseError::fail(format!(
+            "replaying the events gives {rebuilt}, handling the commands gave {handled}"
+        )));
+    }
+    Ok(aggregate)
+}
@@ -0,0 +1,268 @@
+#![cfg(not(target_arch = "wasm32"))] // Skip for wasm target
+
+use replay::{Aggregate, EventStream, WithId};
+use replay_macros::{aggregate, commands, events, services};
+
+#[aggregate(namespace = "account")]
+pub struct BankAccount {
+    pub account_number: String,
+    pub balance: f64,
+}
+
+#[commands]
+pub enum BankAccountCommand {
+    OpenAccount { account_number: String },
+    Deposit { amount: f64 },
+    Close,
+}
+
+#[events]
+pub enum BankAccountEvent {
+    AccountOpened { account_number: String },
+    Deposited { amount: f64 },
+}
+
+#[services]
+pub trait BankAccountServices {
+    async fn validate_account_number(&self, account_number: &str) -> bool;
+}
+
+struct MinLength;
+
+#[async_trait::async_trait]
+impl BankAccountServices for MinLength {
+    async fn validate_account_number(&self, account_number: &str) -> bool {
+        account_number.len() >= 5
+    }
+}
+
+impl EventStream for BankAccount {
+    type Event = BankAccountEvent;
+
+    fn stream_type() -> &'static str {
+        "BankAccount"
+    }
+
+    fn apply(&mut self, event: Self::Event) {
+        match event {
+            BankAccountEvent::AccountOpened { account_number } => {
+                self.account_number = account_number;
+            }
+            BankAccountEvent::Deposited { amount } => self.balance += amount,
+        }
+    }
+}
+
+impl Aggregate for BankAccount {
+    type Command = BankAccountCommand;
+    type Error = replay::Error;
+    type Services = std::sync::Arc<dyn BankAccountServices>;
+
+    async fn handle(
+        &self,
+        command: Self::Command,
+        services: &Self::Services,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        let operation = command.command_name();
+        match command {
+            BankAccountCommand::OpenAccount { account_number } => {
+                if !services.validate_account_number(&account_number).await {
+                    return Err(
+                        replay::Error::business_rule_violation("Invalid account number")
+                            .with_operation(operation),
+                    );
+                }
+                Ok(vec![BankAccountEvent::AccountOpened { account_number }])
+            }
+            BankAccountCommand::Deposit { amount } => {
+                Ok(vec![BankAccountEvent::Deposited { amount }])
+            }
+            BankAccountCommand::Close => Ok(vec![]),
+        }
+    }
+}
+
+#[test]
+fn test_aggregate_attribute_generates_id_and_urn() {
+    let id = BankAccountUrn::new("acc-1").unwrap();
+    let account = BankAccount::with_id(id.clone());
+
+    assert_eq!(account.get_id(), &id);
+    assert_eq!(account.balance, 0.0);
+    assert_eq!(account.account_number, "");
+    assert_eq!(BankAccountUrn::namespace(), "account");
+    assert!(BankAccountUrn::parse("urn:bank-account:acc-1").is_err());
+
+    // Aggregates compare by id only
+    let mut other = BankAccount::with_id(id);
+    other.balance = 10.0;
+    assert_eq!(account, other);
+
+    // The id serializes as its URN string
+    let json = serde_json::to_value(&account).unwrap();
+    assert_eq!(json["id"], "urn:account:acc-1");
+    let back: BankAccount = serde_json::from_value(json).unwrap();
+    assert_eq!(back, account);
+}
+
+#[test]
+fn test_aggregate_attribute_defaults_namespace_to_kebab_case() {
+    #[aggregate]
+    struct HTTPConnection;
+
+    assert_eq!(HTTPConnectionUrn::namespace(), "http-connection");
+    let connection = HTTPConnection::with_id(HTTPConnectionUrn::new("c-1").unwrap());
+    assert_eq!(connection.id.to_string(), "urn:http-connection:c-1");
+}
+
+#[test]
+fn test_commands_and_events_attributes() {
+    use replay::Event;
+
+    assert_eq!(
+        BankAccountCommand::Deposit { amount: 1.0 }.command_name(),
+        "Deposit"
+    );
+    assert_eq!(BankAccountCommand::Close.command_name(), "Close");
+
+    let event = BankAccountEvent::Deposited { amount: 5.0 };
+    assert_eq!(event.event_type(), "Deposited");
+    let json = serde_json::to_value(&event).unwrap();
+    assert_eq!(
+        serde_json::from_value::<BankAccountEvent>(json).unwrap(),
+        event
+    );
+}
+
+#[tokio::test]
+async fn test_attribute_aggregate_handles_commands_with_services() {
+    let services: std::sync::Arc<dyn BankAccountServices> = std::sync::Arc::new(MinLength);
+    let mut account = BankAccount::with_id(BankAccountUrn::new("acc-2").unwrap());
+
+    account
+        .handle_and_apply(
+            BankAccountCommand::OpenAccount {
+                account_number: "ACC12345".to_string(),
+            },
+            &services,
+        )
+        .await
+        .unwrap();
+    account
+        .handle_and_apply(BankAccountCommand::Deposit { amount: 25.0 }, &services)
+        .await
+        .unwrap();
+
+    assert_eq!(account.account_number, "ACC12345");
+    assert_eq!(account.balance, 25.0);
+
+    let err = account
+        .handle(
+            BankAccountCommand::OpenAccount {
+                account_number: "A1".to_string(),
+            },
+            &services,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.operation(), "OpenAccount");
+}
+
+mod hand_written {
+    use replay::{EventStream, WithId};
+    use replay_macros::{aggregate, event_stream, events};
+
+    #[aggregate]
+    pub struct Wallet {
+        pub balance: i64,
+        pub owner: String,
+        pub frozen: bool,
+    }
+
+    #[events]
+    pub enum WalletEvent {
+        Opened { owner: String, opening_balance: i64 },
+        Deposited { amount: i64, reference: String },
+        Frozen,
+        Audited { auditor: String },
+    }
+
+    #[event_stream(event = WalletEvent)]
+    impl Wallet {
+        #[apply(Opened)]
+        fn on_opened(&mut self, opening_balance: i64, owner: String) {
+            self.owner = owner;
+            self.balance = opening_balance;
+        }
+
+        #[apply(Deposited)]
+        fn on_deposited(&mut self, amount: i64, _reference: String) {
+            self.balance += amount;
+        }
+
+        #[apply(Frozen)]
+        fn on_frozen(&mut self) {
+            self.frozen = true;
+        }
+
+        pub fn is_rich(&self) -> bool {
+            self.balance > 100
+        }
+    }
+
+    #[test]
+    fn test_event_stream_attribute_dispatches_to_apply_methods() {
+        let mut wallet = Wallet::with_id(WalletUrn::new("w-1").unwrap());
+        wallet.apply_all(vec![
+            WalletEvent::Opened {
+                owner: "ana".to_string(),
+                opening_balance: 100,
+            },
+            WalletEvent::Deposited {
+                amount: 50,
+                reference: "r-1".to_string(),
+            },
+            // No #[apply] method: the state is unchanged
+            WalletEvent::Audited {
+                auditor: "bob".to_string(),
+            },
+            WalletEvent::Frozen,
+        ]);
+
+        assert_eq!(Wallet::stream_type(), "Wallet");
+        assert_eq!(wallet.owner, "ana");
+        assert_eq!(wallet.balance, 150);
+        assert!(wallet.frozen);
+        assert!(wallet.is_rich());
+    }
+
+    #[aggregate]
+    pub struct PiggyBank {
+        pub coins: i64,
+    }
+
+    #[events]
+    pub enum PiggyBankEvent {
+        Dropped { coins: i64 },
+    }
+
+    #[event_stream]
+    impl EventStream for PiggyBank {
+        type Event = PiggyBankEvent;
+
+        fn apply(&mut self, event: Self::Event) {
+            match event {
+                PiggyBankEvent::Dropped { coins } => self.coins += coins,
+            }
+        }
+    }
+
+    #[test]
+    fn test_event_stream_attribute_fills_in_the_stream_type_of_an_impl() {
+        let mut piggy_bank = PiggyBank::with_id(PiggyBankUrn::new("p-1").unwrap());
+        piggy_bank.apply(PiggyBankEvent::Dropped { coins: 3 });
+
+        assert_eq!(PiggyBank::stream_type(), "piggy-bank");
+        assert_eq!(piggy_bank.coins, 3);
+    }
+}
@@ -45,8 +45,8 @@ impl BankAccountServices for MockBankAccountServices {
 impl EventStream for BankAccount {
     type Event = BankAccountEvent;
 
-    fn stream_type() -> String {
-        "BankAccount".to_string()
+    fn stream_type() -> &'static str {
+        "BankAccount"
     }
 
     fn apply(&mut self, event: Self::Event) {
@@ -754,8 +754,8 @@ mod tests {
         impl EventStream for FileManager<String> {
             type Event = FileManagerEvent<String>;
 
-            fn stream_type() -> String {
-                "FileManager".to_string()
+            fn stream_type() -> &'static str {
+                "FileManager"
             }
 
             fn apply(&mut self, event: Self::Event) {
@@ -786,8 +786,8 @@ mod tests {
         impl EventStream for FileManager<i32> {
             type Event = FileManagerEvent<i32>;
 
-            fn stream_type() -> String {
-                "FileManager".to_string()
+            fn stream_type() -> &'static str {
+                "FileManager"
             }
 
             fn apply(&mut self, event: Self::Event) {
@@ -835,8 +835,8 @@ mod tests {
         impl EventStream for Container<NoCompare> {
             type Event = ContainerEvent; // No <NoCompare> since T not used in events
 
-            fn stream_type() -> String {
-                "Container".to_string()
+            fn stream_type() -> &'static str {
+                "Container"
             }
 
             fn apply(&mut self, event: Self::Event) {
@@ -855,6 +855,69 @@ mod tests {
         assert_eq!(container.count, 5);
     }
 
+    #[test]
+    fn test_aggregate_with_lifetimes_several_params_and_where_clause() {
+        use std::borrow::Cow;
+        use std::collections::BTreeMap;
+
+        define_aggregate! {
+            Catalog<'a, K, V> where K: Ord, V: PartialEq + 'a {
+                state: {
+                    label: Cow<'a, str>,
+                    entries: BTreeMap<K, V>,
+                },
+                commands: {
+                    Rename { label: Cow<'a, str> },
+                    Put { key: K, value: V }
+                },
+                events: {
+                    Renamed { label: String },
+                    Stored { key: K, value: V }
+                }
+            }
+        }
+
+        impl<'a> EventStream for Catalog<'a, String, u32> {
+            // `'a` isn't used by any event, so neither it nor the `V: 'a` predicate is declared
+            type Event = CatalogEvent<String, u32>;
+
+            fn stream_type() -> &'static str {
+                "Catalog"
+            }
+
+            fn apply(&mut self, event: Self::Event) {
+                match event {
+                    CatalogEvent::Renamed { label } => self.label = Cow::Owned(label),
+                    CatalogEvent::Stored { key, value } => {
+                        self.entries.insert(key, value);
+                    }
+                }
+            }
+        }
+
+        let label = String::from("borrowed");
+        let command: CatalogCommand<'_, String, u32> = CatalogCommand::Rename {
+            label: Cow::Borrowed(&label),
+        };
+        let CatalogCommand::Rename { label: borrowed } = command else {
+            panic!("expected Rename");
+        };
+        assert!(matches!(borrowed, Cow::Borrowed("borrowed")));
+
+        let mut catalog: Catalog<String, u32> =
+            Catalog::with_id(CatalogUrn::new("catalog-1").unwrap());
+        catalog.apply(CatalogEvent::Renamed {
+            label: "books".to_string(),
+        });
+        catalog.apply(CatalogEvent::Stored {
+            key: "isbn".to_string(),
+            value: 3,
+        });
+
+        assert_eq!(catalog.label, "books");
+        assert_eq!(catalog.entries.get("isbn"), Some(&3));
+    }
+
     #[test]
     fn test_type_parameter_detection_avoids_false_positives() {
         // Test that type parameter detection uses proper AST traversal
@@ -892,8 +955,8 @@ mod tests {
         impl EventStream for Manager<String> {
             type Event = ManagerEvent; // No <String> - T not actually used in events!
 
-            fn stream_type() -> String {
-                "Manager".to_string()
+            fn stream_type() -> &'static str {
+                "Manager"
             }
 
             fn apply(&mut self, event: Self::Event) {
@@ -985,8 +1048,8 @@ mod tests {
         impl EventStream for Processor<StringContainer> {
             type Event = ProcessorEvent; // No <StringContainer> since T not used in events
 
-            fn stream_type() -> String {
-                "Processor".to_string()
+            fn stream_type() -> &'static str {
+                "Processor"
             }
 
             fn apply(&mut self, event: Self::Event) {
@@ -1086,8 +1149,8 @@ mod tests {
         impl EventStream for FileProcessor {
             type Event = FileProcessorEvent;
 
-            fn stream_type() -> String {
-                "FileProcessor".to_string()
+            fn stream_type() -> &'static str {
+                "FileProcessor"
             }
 
             fn apply(&mut self, event: Self::Event) {
@@ -1169,8 +1232,8 @@ mod tests {
         impl EventStream for StreamProcessor {
             type Event = StreamProcessorEvent;
 
-            fn stream_type() -> String {
-                "StreamProcessor".to_string()
+            fn stream_type() -> &'static str {
+                "StreamProcessor"
             }
 
             fn apply(&mut self, event: Self::Event) {
@@ -1231,4 +1294,676 @@ mod tests {
         processor.apply_all(events);
         assert_eq!(processor.messages_received, 3);
     }
+
+    #[tokio::test]
+    async fn test_generated_event_stream_from_apply_section() {
+        define_aggregate! {
+            Tally {
+                state: {
+                    total: u64,
+                    last_note: String,
+                    closed: bool,
+                },
+                commands: {
+                    Add { amount: u64 } => |_state, cmd, _svc| {
+                        Ok(vec![TallyEvent::Added { amount: cmd.amount }])
+                    }
+                },
+                events: {
+                    Added { amount: u64 },
+                    Noted { note: String },
+                    Closed
+                },
+                apply: {
+                    Added => |state, e| state.total += e.amount,
+                    Noted => |state, NotedEvent { note }| {
+                        state.last_note = note;
+                    }
+                }
+            }
+        }
+
+        // No apply entry: dispatched to `apply_closed`
+        impl Tally {
+            fn apply_closed(&mut self) {
+                self.closed = true;
+            }
+        }
+
+        assert_eq!(Tally::stream_type(), "Tally");
+
+        let mut tally = Tally::with_id(TallyUrn::new("t-1").unwrap());
+        tally
+            .handle_and_apply(TallyCommand::Add { amount: 3 }, &TallyServices)
+            .await
+            .unwrap();
+        tally.apply_all(vec![
+            TallyEvent::Added { amount: 4 },
+            TallyEvent::Noted {
+                note: "checked".to_string(),
+            },
+            TallyEvent::Closed,
+        ]);
+
+        assert_eq!(tally.total, 7);
+        assert_eq!(tally.last_note, "checked");
+        assert!(tally.closed);
+    }
+
+    #[tokio::test]
+    async fn test_generated_handle_with_inline_handlers_and_methods() {
+        define_aggregate! {
+            Wallet {
+                state: {
+                    balance: u64,
+                },
+                commands: {
+                    TopUp { amount: u64 } => |_state, cmd, _svc| {
+                        Ok(vec![WalletEvent::ToppedUp { amount: cmd.amount }])
+                    },
+                    Spend { amount: u64 } => |state, SpendCommand { amount }, svc| {
+                        if !svc.approve(amount).await || state.balance < amount {
+                            return Err(replay::Error::business_rule_violation("spend declined"));
+                        }
+                        Ok(vec![WalletEvent::Spent { amount }])
+                    },
+                    // No inline handler: dispatches to `on_reset_balance`
+                    ResetBalance
+                },
+                events: {
+                    ToppedUp { amount: u64 },
+                    Spent { amount: u64 }
+                },
+                service: {
+                    async fn approve(amount: u64) -> bool;
+                }
+            }
+        }
+
+        impl Wallet {
+            async fn on_reset_balance(
+                &self,
+                _services: &std::sync::Arc<dyn WalletServices>,
+            ) -> replay::Result<Vec<WalletEvent>> {
+                Ok(vec![WalletEvent::Spent {
+                    amount: self.balance,
+                }])
+            }
+        }
+
+        impl EventStream for Wallet {
+            type Event = WalletEvent;
+
+            fn stream_type() -> &'static str {
+                "Wallet"
+            }
+
+            fn apply(&mut self, event: Self::Event) {
+                match event {
+                    WalletEvent::ToppedUp { amount } => self.balance += amount,
+                    WalletEvent::Spent { amount } => self.balance -= amount,
+                }
+            }
+        }
+
+        struct UpToTen;
+
+        #[async_trait]
+        impl WalletServices for UpToTen {
+            async fn approve(&self, amount: u64) -> bool {
+                amount <= 10
+            }
+        }
+
+        let services: std::sync::Arc<dyn WalletServices> = std::sync::Arc::new(UpToTen);
+        let mut wallet = Wallet::with_id(WalletUrn::new("w-1").unwrap());
+
+        wallet
+            .handle_and_apply(WalletCommand::TopUp { amount: 50 }, &services)
+            .await
+            .unwrap();
+        wallet
+            .handle_and_apply(WalletCommand::Spend { amount: 8 }, &services)
+            .await
+            .unwrap();
+        assert_eq!(wallet.balance, 42);
+
+        let err = wallet
+            .handle(WalletCommand::Spend { amount: 20 }, &services)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), replay::ErrorKind::BusinessRuleViolation);
+
+        wallet
+            .handle_and_apply(WalletCommand::ResetBalance, &services)
+            .await
+            .unwrap();
+        assert_eq!(wallet.balance, 0);
+    }
+
+    #[tokio::test]
+    async fn test_generated_handle_with_custom_error_and_generic_command() {
+        #[derive(Debug)]
+        pub struct LedgerError(replay::Error);
+
+        impl std::fmt::Display for LedgerError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "ledger: {}", self.0)
+            }
+        }
+
+        impl std::error::Error for LedgerError {}
+
+        impl From<replay::Error> for LedgerError {
+            fn from(error: replay::Error) -> Self {
+                LedgerError(error)
+            }
+        }
+
+        define_aggregate! {
+            Ledger<T: PartialEq> {
+                state: {
+                    entries: Vec<T>,
+                },
+                commands: {
+                    Record { entry: T } => |_state, cmd, _svc| {
+                        Ok(vec![LedgerEvent::Recorded { entry: cmd.entry }])
+                    }
+                },
+                events: {
+                    Recorded { entry: T }
+                },
+                error: LedgerError
+            }
+        }
+
+        impl EventStream for Ledger<String> {
+            type Event = LedgerEvent<String>;
+
+            fn stream_type() -> &'static str {
+                "Ledger"
+            }
+
+            fn apply(&mut self, event: Self::Event) {
+                match event {
+                    LedgerEvent::Recorded { entry } => self.entries.push(entry),
+                }
+            }
+        }
+
+        let mut ledger = Ledger::<String>::with_id(LedgerUrn::new("l-1").unwrap());
+        let events: Result<Vec<LedgerEvent<String>>, LedgerError> = ledger
+            .handle_and_apply(
+                LedgerCommand::Record {
+                    entry: "opening".to_string(),
+                },
+                &LedgerServices,
+            )
+            .await;
+
+        assert_eq!(events.unwrap().len(), 1);
+        assert_eq!(ledger.entries, vec!["opening".to_string()]);
+    }
+
+    #[test]
+    fn test_attributes_are_forwarded_to_generated_types() {
+        define_aggregate! {
+            /// A customer profile.
+            Profile {
+                #[serde(rename_all = "camelCase")]
+                state: {
+                    /// Shown in the UI.
+                    display_name: String,
+                    #[serde(default)]
+                    login_count: u32,
+                },
+                commands: {
+                    /// Change the display name.
+                    Rename {
+                        /// The new name.
+                        display_name: String
+                    }
+                },
+                #[serde(tag = "type")]
+                events: {
+                    #[serde(rename = "profile_renamed")]
+                    Renamed {
+                        #[serde(rename = "name")]
+                        display_name: String
+                    },
+                    LoggedIn {
+                        #[serde(default)]
+                        device: Option<String>
+                    }
+                }
+            }
+        }
+
+        let event = ProfileEvent::Renamed {
+            display_name: "Ada".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({ "type": "profile_renamed", "name": "Ada" })
+        );
+
+        let logged_in: ProfileEvent =
+            serde_json::from_value(serde_json::json!({ "type": "LoggedIn" })).unwrap();
+        assert_eq!(logged_in, ProfileEvent::LoggedIn { device: None });
+
+        let profile: Profile = serde_json::from_value(serde_json::json!({
+            "id": "urn:profile:p-1",
+            "displayName": "Ada"
+        }))
+        .unwrap();
+        assert_eq!(profile.display_name, "Ada");
+        assert_eq!(profile.login_count, 0);
+
+        let ProfileCommand::Rename { display_name } = ProfileCommand::Rename {
+            display_name: "Grace".to_string(),
+        };
+        assert_eq!(display_name, "Grace");
+    }
+
+    #[tokio::test]
+    async fn test_command_builders_validate_fields() {
+        define_aggregate! {
+            Transfer {
+                state: {
+                    total: f64,
+                },
+                commands: {
+                    #[builder]
+                    Send {
+                        #[validate(range(min = 0.01, max = 10_000.0))]
+                        amount: f64,
+                        #[validate(non_empty, length(max = 34))]
+                        iban: String,
+                        reference: Option<String>
+                    } => |_state, cmd, _svc| {
+                        Ok(vec![TransferEvent::Sent { amount: cmd.amount }])
+                    }
+                },
+                events: {
+                    Sent { amount: f64 }
+                },
+                apply: {
+                    Sent => |state, e| state.total += e.amount,
+                }
+            }
+        }
+
+        let command = SendCommand::builder()
+            .amount(25.0)
+            .iban("NL91ABNA0417164300")
+            .build()
+            .unwrap();
+        let TransferCommand::Send {
+            amount,
+            iban,
+            reference,
+        } = &command;
+        assert_eq!(
+            (*amount, iban.as_str(), reference),
+            (25.0, "NL91ABNA0417164300", &None)
+        );
+
+        let transfer = Transfer::with_id(TransferUrn::new("t-1").unwrap());
+        let events = transfer.handle(command, &TransferServices).await.unwrap();
+        assert_eq!(events, vec![TransferEvent::Sent { amount: 25.0 }]);
+
+        let too_much = SendCommand::builder()
+            .amount(20_000.0)
+            .iban("NL91ABNA0417164300")
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(too_much.kind(), replay::ErrorKind::InvalidInput);
+        assert_eq!(too_much.operation(), "Send");
+
+        let empty_iban = SendCommand::builder()
+            .amount(1.0)
+            .iban("")
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(empty_iban.kind(), replay::ErrorKind::InvalidInput);
+        assert!(empty_iban.to_string().contains("iban must not be empty"));
+
+        let missing = SendCommand::builder()
+            .iban("NL91ABNA0417164300")
+            .build()
+            .err()
+            .unwrap();
+        assert!(missing.to_string().contains("amount is required"));
+
+        let args = SendCommand {
+            amount: 0.0,
+            iban: "NL91ABNA0417164300".to_string(),
+            reference: Some("rent".to_string()),
+        };
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_create_commands_declare_the_stream_lifecycle() {
+        define_aggregate! {
+            Ledger {
+                state: {
+                    entries: u32,
+                },
+                commands: {
+                    #[create]
+                    Open => |_state, _cmd, _svc| Ok(vec![LedgerEvent::Opened]),
+                    Post => |_state, _cmd, _svc| Ok(vec![LedgerEvent::Posted])
+                },
+                events: {
+                    Opened,
+                    Posted
+                },
+                apply: {
+                    Opened => |_state, _e| {},
+                    Posted => |state, _e| state.entries += 1,
+                }
+            }
+        }
+
+        assert_eq!(
+            Ledger::lifecycle(&LedgerCommand::Open),
+            replay::Lifecycle::Creates
+        );
+        assert_eq!(
+            Ledger::lifecycle(&LedgerCommand::Post),
+            replay::Lifecycle::Existing
+        );
+    }
+
+    #[test]
+    fn test_transient_state_holds_members_that_are_not_serializable() {
+        // No serde impls: only a transient state may hold it
+        #[derive(Clone, Debug, Default)]
+        struct Seen(Vec<u32>);
+
+        define_aggregate! {
+            Sensor {
+                #[transient]
+                state: {
+                    seen: Seen,
+                },
+                commands: {
+                    Record { value: u32 },
+                },
+                events: {
+                    Recorded { value: u32 }
+                },
+                apply: {
+                    Recorded => |state, e| state.seen.0.push(e.value),
+                }
+            }
+        }
+
+        let mut sensor = Sensor::with_id(SensorUrn::new("s-1").unwrap());
+        sensor.apply_all(vec![
+            SensorEvent::Recorded { value: 3 },
+            SensorEvent::Recorded { value: 5 },
+        ]);
+        assert_eq!(sensor.seen.0, [3, 5]);
+    }
+
+    #[test]
+    fn test_entity_events_are_routed_to_their_child() {
+        #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+        struct Line {
+            number: u32,
+            quantity: u32,
+            removed: bool,
+        }
+
+        impl replay::Entity for Line {
+            type Id = u32;
+            type Event = PurchaseEvent;
+
+            fn with_entity_id(number: u32) -> Self {
+                Line {
+                    number,
+                    quantity: 0,
+                    removed: false,
+                }
+            }
+
+            fn entity_id(&self) -> &u32 {
+                &self.number
+            }
+
+            fn apply(&mut self, event: PurchaseEvent) {
+                match event {
+                    PurchaseEvent::LineAdded { quantity, .. } => self.quantity += quantity,
+                    PurchaseEvent::LineRemoved { .. } => self.removed = true,
+                    PurchaseEvent::Placed => {}
+                }
+            }
+
+            fn is_removed(&self) -> bool {
+                self.removed
+            }
+        }
+
+        define_aggregate! {
+            Purchase {
+                state: {
+                    lines: replay::Entities<Line>,
+                    placed: bool,
+                },
+                commands: {
+                    Place,
+                },
+                events: {
+                    #[entity(field = lines, id = line)]
+                    LineAdded { line: u32, quantity: u32 },
+                    #[entity(field = lines, id = line)]
+                    LineRemoved { line: u32 },
+                    Placed
+                },
+                apply: {
+                    Placed => |state, _e| state.placed = true,
+                }
+            }
+        }
+
+        let mut purchase = Purchase::with_id(PurchaseUrn::new("p-1").unwrap());
+        purchase.apply_all(vec![
+            PurchaseEvent::LineAdded {
+                line: 1,
+                quantity: 2,
+            },
+            PurchaseEvent::LineAdded {
+                line: 2,
+                quantity: 1,
+            },
+            PurchaseEvent::LineAdded {
+                line: 1,
+                quantity: 3,
+            },
+            PurchaseEvent::LineRemoved { line: 2 },
+            PurchaseEvent::Placed,
+        ]);
+
+        let lines: Vec<_> = purchase
+            .lines
+            .iter()
+            .map(|line| (line.number, line.quantity))
+            .collect();
+        assert_eq!(lines, [(1, 5)]);
+        assert!(purchase.placed);
+    }
+
+    #[tokio::test]
+    async fn test_enum_state_models_the_lifecycle() {
+        define_aggregate! {
+            Loan {
+                #[serde(tag = "status")]
+                state: enum {
+                    Requested,
+                    Active { outstanding: u64 },
+                    Repaid
+                },
+                commands: {
+                    Grant { amount: u64 } => |loan, cmd, _svc| match loan.state {
+                        LoanState::Requested => Ok(vec![LoanEvent::Granted { amount: cmd.amount }]),
+                        _ => Err(replay::Error::business_rule_violation("loan already granted")),
+                    },
+                    Repay { amount: u64 } => |loan, cmd, _svc| match loan.state {
+                        LoanState::Active { outstanding } if cmd.amount <= outstanding => {
+                            Ok(vec![LoanEvent::Repaid { amount: cmd.amount }])
+                        }
+                        _ => Err(replay::Error::business_rule_violation("nothing to repay")),
+                    }
+                },
+                events: {
+                    Granted { amount: u64 },
+                    Repaid { amount: u64 }
+                },
+                apply: {
+                    Granted => |loan, e| loan.state = LoanState::Active { outstanding: e.amount },
+                    Repaid => |loan, e| {
+                        if let LoanState::Active { outstanding } = loan.state {
+                            loan.state = match outstanding - e.amount {
+                                0 => LoanState::Repaid,
+                                outstanding => LoanState::Active { outstanding },
+                            };
+                        }
+                    },
+                }
+            }
+        }
+
+        let mut loan = Loan::with_id(LoanUrn::new("l-1").unwrap());
+        assert_eq!(loan.state, LoanState::Requested);
+
+        loan.handle_and_apply(LoanCommand::Grant { amount: 100 }, &LoanServices)
+            .await
+            .unwrap();
+        loan.handle_and_apply(LoanCommand::Repay { amount: 40 }, &LoanServices)
+            .await
+            .unwrap();
+        assert_eq!(loan.state, LoanState::Active { outstanding: 60 });
+        assert_eq!(
+            serde_json::to_value(&loan).unwrap()["state"],
+            serde_json::json!({ "status": "Active", "outstanding": 60 })
+        );
+
+        loan.handle_and_apply(LoanCommand::Repay { amount: 60 }, &LoanServices)
+            .await
+            .unwrap();
+        assert_eq!(loan.state, LoanState::Repaid);
+
+        let err = loan
+            .handle(LoanCommand::Grant { amount: 10 }, &LoanServices)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), replay::ErrorKind::BusinessRuleViolation);
+    }
+
+    #[tokio::test]
+    async fn test_commands_with_outputs() {
+        use replay::WithOutput;
+
+        define_aggregate! {
+            Member {
+                state: {
+                    name: String,
+                    cards: u32,
+                },
+                commands: {
+                    Join { name: String } -> String => |_state, cmd, _svc| {
+                        let greeting = format!("welcome, {}", cmd.name);
+                        Ok((vec![MemberEvent::Joined { name: cmd.name }], greeting))
+                    },
+                    IssueCard -> u32,
+                    Leave => |_state, _cmd, _svc| Ok(vec![MemberEvent::Left])
+                },
+                events: {
+                    Joined { name: String },
+                    CardIssued,
+                    Left
+                },
+                apply: {
+                    Joined => |state, e| state.name = e.name,
+                    CardIssued => |state, _e| state.cards += 1,
+                    Left => |_state, _e| {},
+                }
+            }
+        }
+
+        impl Member {
+            async fn on_issue_card(
+                &self,
+                _services: &MemberServices,
+            ) -> replay::Result<(Vec<MemberEvent>, u32)> {
+                Ok((vec![MemberEvent::CardIssued], self.cards + 1))
+            }
+        }
+
+        let mut member = Member::with_id(MemberUrn::new("m-1").unwrap());
+        let (events, output) = member
+            .handle_with_output(
+                MemberCommand::Join {
+                    name: "Ada".to_string(),
+                },
+                &MemberServices,
+            )
+            .await
+            .unwrap();
+        assert_eq!(output.into_join().as_deref(), Some("welcome, Ada"));
+        member.apply_all(events);
+
+        let (events, output) = member
+            .handle_with_output(MemberCommand::IssueCard, &MemberServices)
+            .await
+            .unwrap();
+        assert_eq!(events, vec![MemberEvent::CardIssued]);
+        assert!(matches!(output, MemberOutput::IssueCard(1)));
+
+        // `handle` runs the same handlers and drops the output
+        let events = member
+            .handle(MemberCommand::Leave, &MemberServices)
+            .await
+            .unwrap();
+        assert_eq!(events, vec![MemberEvent::Left]);
+        let (_, output) = member
+            .handle_with_output(MemberCommand::Leave, &MemberServices)
+            .await
+            .unwrap();
+        assert!(output.into_join().is_none());
+    }
+
+    #[test]
+    fn test_pii_event_fields_are_redacted() {
+        define_aggregate! {
+            Patient {
+                state: {
+                    name: String,
+                },
+                commands: {
+                    Admit { name: String },
+                },
+                events: {
+                    Admitted { #[pii] name: String, ward: u32 },
+                }
+            }
+        };
+
+        let admitted = PatientEvent::Admitted {
+            name: "Ada".to_string(),
+            ward: 3,
+        };
+        assert_eq!(
+            format!("{admitted:?}"),
+            "Admitted { name: [REDACTED], ward: 3 }"
+        );
+        assert_eq!(
+            <PatientEvent as replay::Event>::pii_paths(),
+            ["/Admitted/name"]
+        );
+    }
 }
@@ -0,0 +1,132 @@
+#![cfg(not(target_arch = "wasm32"))] // Skip for wasm target
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use replay::testing::TestFixture;
+use replay::{aggregate_test, ErrorKind};
+use replay_macros::define_aggregate;
+
+define_aggregate! {
+    Account {
+        state: {
+            balance: u64,
+        },
+        commands: {
+            Deposit { amount: u64 } => |_state, cmd, _svc| {
+                Ok(vec![AccountEvent::Deposited { amount: cmd.amount }])
+            },
+            Withdraw { amount: u64 } => |state, cmd, _svc| {
+                if state.balance < cmd.amount {
+                    return Err(replay::Error::business_rule_violation("insufficient funds"));
+                }
+                Ok(vec![AccountEvent::Withdrawn { amount: cmd.amount }])
+            }
+        },
+        events: {
+            Deposited { amount: u64 },
+            Withdrawn { amount: u64 }
+        },
+        apply: {
+            Deposited => |state, e| state.balance += e.amount,
+            Withdrawn => |state, e| state.balance -= e.amount,
+        }
+    }
+}
+
+define_aggregate! {
+    Vault {
+        state: {},
+        commands: {
+            Open { code: String } => |_state, cmd, svc| {
+                if !svc.accepts(&cmd.code).await {
+                    return Err(replay::Error::unauthorized("wrong code"));
+                }
+                Ok(vec![VaultEvent::Opened])
+            }
+        },
+        events: {
+            Opened
+        },
+        apply: {
+            Opened => |_state, _e| {},
+        },
+        service: {
+            async fn accepts(code: &str) -> bool;
+        }
+    }
+}
+
+struct FixedCode;
+
+#[async_trait]
+impl VaultServices for FixedCode {
+    async fn accepts(&self, code: &str) -> bool {
+        code == "1234"
+    }
+}
+
+aggregate_test! {
+    deposit_emits_deposited: Account {
+        when: AccountCommand::Deposit { amount: 50 },
+        then: [AccountEvent::Deposited { amount: 50 }],
+    }
+
+    withdraw_within_balance: Account {
+        id: AccountUrn::new("acc-1").unwrap(),
+        given: [
+            AccountEvent::Deposited { amount: 100 },
+            AccountEvent::Withdrawn { amount: 30 },
+        ],
+        when: AccountCommand::Withdraw { amount: 70 },
+        then: [AccountEvent::Withdrawn { amount: 70 }],
+    }
+
+    overdraft_is_rejected: Account {
+        given: [AccountEvent::Deposited { amount: 100 }],
+        when: AccountCommand::Withdraw { amount: 150 },
+        then_error: BusinessRuleViolation,
+    }
+
+    #[tokio::test]
+    vault_opens_with_the_right_code: Vault {
+        services: Arc::new(FixedCode),
+        when: VaultCommand::Open { code: "1234".to_string() },
+        then: [VaultEvent::Opened],
+    }
+
+    #[tokio::test]
+    vault_rejects_a_wrong_code: Vault {
+        services: Arc::new(FixedCode),
+        when: VaultCommand::Open { code: "0000".to_string() },
+        then_error: Unauthorized,
+    }
+}
+
+#[tokio::test]
+async fn test_fixture_returns_the_aggregate_with_the_events_applied() {
+    let account = TestFixture::<Account>::with_id(AccountUrn::new("acc-2").unwrap())
+        .given([AccountEvent::Deposited { amount: 100 }])
+        .when(AccountCommand::Withdraw { amount: 40 })
+        .await
+        .then_expect_events([AccountEvent::Withdrawn { amount: 40 }]);
+
+    assert_eq!(account.balance, 60);
+}
+
+#[tokio::test]
+async fn test_fixture_checks_the_error_kind_with_stubbed_services() {
+    TestFixture::<Vault>::with_services(VaultUrn::new_random(), Arc::new(FixedCode))
+        .when(VaultCommand::Open {
+            code: "0000".to_string(),
+        })
+        .await
+        .then_expect_error_kind(ErrorKind::Unauthorized);
+
+    TestFixture::<Vault>::with_services(VaultUrn::new_random(), Arc::new(FixedCode))
+        .when(VaultCommand::Open {
+            code: "1234".to_string(),
+        })
+        .await
+        .then_expect_events([VaultEvent::Opened]);
+}
@@ -0,0 +1,108 @@
+#![cfg(not(target_arch = "wasm32"))] // Skip for wasm target
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use replay::testing::check_command_sequence;
+use replay_macros::{define_aggregate, Arbitrary, Urn};
+
+define_aggregate! {
+    Wallet {
+        state: {
+            balance: u64,
+            owner: Option<UserUrn>,
+        },
+        #[derive(Debug, Arbitrary)]
+        commands: {
+            Assign { owner: UserUrn } => |_state, cmd, _svc| {
+                Ok(vec![WalletEvent::Assigned { owner: cmd.owner }])
+            },
+            TopUp {
+                #[arbitrary(strategy = 1..1_000u64)]
+                amount: u64
+            } => |_state, cmd, _svc| {
+                Ok(vec![WalletEvent::ToppedUp { amount: cmd.amount }])
+            },
+            Spend {
+                #[arbitrary(strategy = 1..1_000u64)]
+                amount: u64
+            } => |state, cmd, _svc| {
+                if state.balance < cmd.amount {
+                    return Err(replay::Error::business_rule_violation("insufficient funds"));
+                }
+                Ok(vec![WalletEvent::Spent { amount: cmd.amount }])
+            }
+        },
+        #[derive(Arbitrary)]
+        events: {
+            Assigned { owner: UserUrn },
+            ToppedUp { amount: u64 },
+            Spent { amount: u64 },
+            #[arbitrary(skip)]
+            Frozen
+        },
+        apply: {
+            Assigned => |state, e| state.owner = Some(e.owner),
+            ToppedUp => |state, e| state.balance += e.amount,
+            Spent => |state, e| state.balance -= e.amount,
+            Frozen => |_state, _e| {},
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Urn)]
+pub struct UserUrn(urn::Urn);
+
+proptest! {
+    #[test]
+    fn generated_events_round_trip_through_json(event in any::<WalletEvent>()) {
+        prop_assert_ne!(&event, &WalletEvent::Frozen);
+        let json = serde_json::to_string(&event).unwrap();
+        prop_assert_eq!(serde_json::from_str::<WalletEvent>(&json).unwrap(), event);
+    }
+
+    #[test]
+    fn spending_never_overdraws(commands in vec(any::<WalletCommand>(), 0..30)) {
+        let mut topped_up = 0;
+        for command in &commands {
+            if let WalletCommand::TopUp { amount } = command {
+                topped_up += amount;
+            }
+        }
+
+        let wallet = check_command_sequence::<Wallet>(
+            WalletUrn::new_random(),
+            &Default::default(),
+            commands,
+            |wallet| match wallet.balance <= topped_up {
+                true => Ok(()),
+                false => Err(format!("balance {} exceeds top-ups {topped_up}", wallet.balance)),
+            },
+        )?;
+        if let Some(owner) = wallet.owner {
+            prop_assert_eq!(owner.nid(), "user");
+        }
+    }
+}
+
+#[test]
+fn failed_invariant_names_the_command() {
+    let result = check_command_sequence::<Wallet>(
+        WalletUrn::new_random(),
+        &Default::default(),
+        [
+            WalletCommand::TopUp { amount: 5 },
+            WalletCommand::Assign {
+                owner: UserUrn::new("alice").unwrap(),
+            },
+        ],
+        |wallet| match &wallet.owner {
+            None => Ok(()),
+            Some(owner) => Err(format!("owned by {owner}")),
+        },
+    );
+    let err = result.unwrap_err().to_string();
+    assert!(
+        err.contains("after command 1: owned by urn:user:alice"),
+        "{err}"
+    );
+}
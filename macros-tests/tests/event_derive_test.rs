@@ -64,3 +64,321 @@ fn test_serialize_deserialize_bank_account_event() {
     assert_eq!(deposited, deposited_deserialized);
     assert_eq!(withdrawn, withdrawn_deserialized);
 }
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, DeriveEvent)]
+#[event(rename_all = "dot.case")]
+enum AccountEvent {
+    AccountOpened {
+        owner: String,
+    },
+    #[event(rename = "account.closed.v2")]
+    Closed,
+    HTTPWebhookReceived,
+}
+
+#[test]
+fn test_rename_and_rename_all() {
+    let opened = AccountEvent::AccountOpened {
+        owner: "ada".to_string(),
+    };
+
+    assert_eq!(opened.event_type(), "account.opened");
+    assert_eq!(AccountEvent::Closed.event_type(), "account.closed.v2");
+    assert_eq!(
+        AccountEvent::HTTPWebhookReceived.event_type(),
+        "http.webhook.received"
+    );
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, DeriveEvent)]
+#[event(rename_all = "SCREAMING_SNAKE_CASE")]
+enum AuditEvent {
+    UserLoggedIn,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, DeriveEvent)]
+#[event(rename = "payments.refund_issued")]
+struct RefundIssued {
+    amount: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, DeriveEvent)]
+#[event(rename_all = "kebab-case")]
+struct InvoiceSent;
+
+#[test]
+fn test_casing_rules_and_struct_renames() {
+    assert_eq!(AuditEvent::UserLoggedIn.event_type(), "USER_LOGGED_IN");
+    assert_eq!(
+        RefundIssued { amount: 5 }.event_type(),
+        "payments.refund_issued"
+    );
+    assert_eq!(InvoiceSent.event_type(), "invoice-sent");
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, DeriveEvent)]
+enum PlatformEvent {
+    #[event(transparent)]
+    Banking(BankAccountEvent),
+    #[event(transparent)]
+    Accounts(AccountEvent),
+    MaintenanceStarted,
+}
+
+#[test]
+fn test_transparent_variants_delegate_to_the_wrapped_event() {
+    let deposited = PlatformEvent::Banking(BankAccountEvent::Deposited { amount: 1.0 });
+    let closed = PlatformEvent::Accounts(AccountEvent::Closed);
+
+    assert_eq!(deposited.event_type(), "Deposited");
+    assert_eq!(closed.event_type(), "account.closed.v2");
+    assert_eq!(
+        PlatformEvent::MaintenanceStarted.event_type(),
+        "MaintenanceStarted"
+    );
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, DeriveEvent)]
+#[event(transparent)]
+#[serde(untagged)]
+enum AnyAccountEvent {
+    Bank(BankAccountEvent),
+    Audit(AuditEvent),
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, DeriveEvent)]
+#[event(transparent)]
+struct Envelope(RefundIssued);
+
+#[test]
+fn test_container_level_transparent() {
+    assert_eq!(
+        AnyAccountEvent::Audit(AuditEvent::UserLoggedIn).event_type(),
+        "USER_LOGGED_IN"
+    );
+    assert_eq!(
+        Envelope(RefundIssued { amount: 1 }).event_type(),
+        "payments.refund_issued"
+    );
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, DeriveEvent)]
+#[event(version = 2)]
+enum OrderEvent {
+    Placed {
+        total: u64,
+    },
+    #[event(version = 3)]
+    Shipped {
+        carrier: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, DeriveEvent)]
+#[event(version = 4)]
+struct OrderArchived;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, DeriveEvent)]
+enum StoreEvent {
+    #[event(transparent)]
+    Orders(OrderEvent),
+    Opened,
+}
+
+#[test]
+fn test_event_versions() {
+    assert_eq!(OrderEvent::Placed { total: 1 }.event_version(), 2);
+    assert_eq!(
+        OrderEvent::Shipped {
+            carrier: "ups".to_string()
+        }
+        .event_version(),
+        3
+    );
+    assert_eq!(OrderArchived.event_version(), 4);
+    assert_eq!(InvoiceSent.event_version(), 1);
+
+    assert_eq!(
+        StoreEvent::Orders(OrderEvent::Placed { total: 1 }).event_version(),
+        2
+    );
+    assert_eq!(StoreEvent::Opened.event_version(), 1);
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, DeriveEvent)]
+enum SubscriptionEvent {
+    Started,
+    #[event(tombstone)]
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, DeriveEvent)]
+#[event(tombstone)]
+struct SubscriptionPurged;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, DeriveEvent)]
+#[event(transparent)]
+enum BillingEvent {
+    Subscriptions(SubscriptionEvent),
+    Orders(OrderEvent),
+}
+
+#[test]
+fn test_tombstones() {
+    assert!(SubscriptionEvent::Cancelled.is_tombstone());
+    assert!(!SubscriptionEvent::Started.is_tombstone());
+    assert!(SubscriptionPurged.is_tombstone());
+    assert!(!OrderArchived.is_tombstone());
+
+    assert!(BillingEvent::Subscriptions(SubscriptionEvent::Cancelled).is_tombstone());
+    assert!(!BillingEvent::Orders(OrderEvent::Placed { total: 1 }).is_tombstone());
+}
+
+#[test]
+fn test_event_types_are_listed_with_versions() {
+    assert_eq!(OrderEvent::event_types(), [("Placed", 2), ("Shipped", 3)]);
+    assert_eq!(OrderArchived::event_types(), [("OrderArchived", 4)]);
+    assert_eq!(
+        StoreEvent::event_types(),
+        [("Placed", 2), ("Shipped", 3), ("Opened", 1)]
+    );
+    assert_eq!(Envelope::event_types(), [("payments.refund_issued", 1)]);
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, DeriveEvent)]
+enum CustomerEvent {
+    Registered {
+        id: u64,
+        #[pii]
+        email: String,
+        #[pii]
+        name: String,
+    },
+    Renamed(u64, #[pii] String),
+    Closed,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, DeriveEvent)]
+struct CardAdded {
+    #[pii]
+    number: String,
+    issuer: String,
+}
+
+#[test]
+fn test_pii_fields_are_redacted_from_debug() {
+    let registered = CustomerEvent::Registered {
+        id: 7,
+        email: "ada@example.com".to_string(),
+        name: "Ada".to_string(),
+    };
+    assert_eq!(
+        format!("{registered:?}"),
+        r#"Registered { id: 7, email: [REDACTED], name: [REDACTED] }"#
+    );
+    assert_eq!(
+        format!("{:?}", CustomerEvent::Renamed(7, "Grace".to_string())),
+        "Renamed(7, [REDACTED])"
+    );
+    assert_eq!(format!("{:?}", CustomerEvent::Closed), "Closed");
+
+    let card = CardAdded {
+        number: "4111111111111111".to_string(),
+        issuer: "visa".to_string(),
+    };
+    assert_eq!(
+        format!("{card:?}"),
+        r#"CardAdded { number: [REDACTED], issuer: "visa" }"#
+    );
+
+    // Serialization is untouched: the store sees (and may encrypt) the real values
+    assert!(serde_json::to_string(&card)
+        .unwrap()
+        .contains("4111111111111111"));
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, DeriveEvent)]
+#[serde(tag = "type", content = "data", rename_all = "kebab-case")]
+enum ProfileEvent {
+    #[serde(rename_all = "camelCase")]
+    AddressChanged {
+        #[pii]
+        street_line: String,
+        #[pii]
+        #[serde(rename = "zip")]
+        postal_code: String,
+        #[pii]
+        #[serde(skip)]
+        cached: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, DeriveEvent)]
+enum CrmEvent {
+    #[event(transparent)]
+    Customer(CustomerEvent),
+    #[event(transparent)]
+    Card(CardAdded),
+}
+
+#[test]
+fn test_pii_fields_are_listed_by_their_serialized_paths() {
+    assert_eq!(
+        CustomerEvent::pii_paths(),
+        ["/Registered/email", "/Registered/name", "/Renamed/1"]
+    );
+    assert_eq!(CardAdded::pii_paths(), ["/number"]);
+    assert_eq!(ProfileEvent::pii_paths(), ["/data/streetLine", "/data/zip"]);
+    assert_eq!(
+        CrmEvent::pii_paths(),
+        [
+            "/Customer/Registered/email",
+            "/Customer/Registered/name",
+            "/Customer/Renamed/1",
+            "/Card/number",
+        ]
+    );
+    assert!(BankAccountEvent::pii_paths().is_empty());
+}
+
+#[test]
+fn test_bank_account_events_match_their_golden_files() {
+    replay::testing::assert_event_snapshots(
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/snapshots/bank_account_event"
+        ),
+        [
+            BankAccountEvent::Deposited { amount: 100.0 },
+            BankAccountEvent::Withdrawn { amount: 40.5 },
+        ],
+    );
+}
+
+#[test]
+fn test_event_snapshots_catch_format_changes() {
+    let dir = std::env::temp_dir().join(format!("replay-snapshots-{}", uuid::Uuid::now_v7()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("Deposited.json"),
+        "{\n  \"Deposited\": {\n    \"value\": 100.0\n  }\n}\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("Closed.json"), "\"Closed\"\n").unwrap();
+
+    let result = std::panic::catch_unwind(|| {
+        replay::testing::assert_event_snapshots(
+            &dir,
+            [BankAccountEvent::Deposited { amount: 100.0 }],
+        )
+    });
+    let message = *result.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.contains("Deposited.json changed"), "{message}");
+    assert!(
+        message.contains("Deposited.json no longer deserializes"),
+        "{message}"
+    );
+    assert!(message.contains("Closed.json has no sample"), "{message}");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
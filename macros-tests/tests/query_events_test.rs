@@ -14,11 +14,11 @@ pub enum UserEvent {
 }
 
 impl Event for UserEvent {
-    fn event_type(&self) -> String {
+    fn event_type(&self) -> &'static str {
         match self {
-            UserEvent::UserCreated { .. } => "UserCreated".to_string(),
-            UserEvent::UserUpdated { .. } => "UserUpdated".to_string(),
-            UserEvent::UserDeleted { .. } => "UserDeleted".to_string(),
+            UserEvent::UserCreated { .. } => "UserCreated",
+            UserEvent::UserUpdated { .. } => "UserUpdated",
+            UserEvent::UserDeleted { .. } => "UserDeleted",
         }
     }
 }
@@ -31,11 +31,11 @@ pub enum CatalogEvent {
 }
 
 impl Event for CatalogEvent {
-    fn event_type(&self) -> String {
+    fn event_type(&self) -> &'static str {
         match self {
-            CatalogEvent::ProductAdded { .. } => "ProductAdded".to_string(),
-            CatalogEvent::ProductUpdated { .. } => "ProductUpdated".to_string(),
-            CatalogEvent::ProductRemoved { .. } => "ProductRemoved".to_string(),
+            CatalogEvent::ProductAdded { .. } => "ProductAdded",
+            CatalogEvent::ProductUpdated { .. } => "ProductUpdated",
+            CatalogEvent::ProductRemoved { .. } => "ProductRemoved",
         }
     }
 }
@@ -271,4 +271,117 @@ mod tests {
         assert!(debug_str.contains("UserEvent"));
         assert!(debug_str.contains("UserCreated"));
     }
+
+    #[test]
+    fn test_try_from_back_conversions_and_is_helpers() {
+        let evt: UserHistoryEvent = CatalogEvent::ProductRemoved {
+            product_id: "prod-9".to_string(),
+        }
+        .into();
+
+        assert!(evt.is_catalog_event());
+        assert!(!evt.is_user_event());
+
+        let evt = UserEvent::try_from(evt).expect_err("not a user event");
+        assert_eq!(
+            CatalogEvent::try_from(evt).unwrap(),
+            CatalogEvent::ProductRemoved {
+                product_id: "prod-9".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_stream_types_from_named_aggregates() {
+        use replay::EventStream;
+        use replay_macros::define_aggregate;
+
+        define_aggregate! {
+            Profile {
+                state: {},
+                commands: {},
+                events: { Renamed { name: String } }
+            }
+        }
+
+        define_aggregate! {
+            Order {
+                state: {},
+                commands: {},
+                events: { Placed { total: u64 } }
+            }
+        }
+
+        impl EventStream for Profile {
+            type Event = ProfileEvent;
+
+            fn stream_type() -> &'static str {
+                "Profile"
+            }
+
+            fn apply(&mut self, _event: Self::Event) {}
+        }
+
+        impl EventStream for Order {
+            type Event = OrderEvent;
+
+            fn stream_type() -> &'static str {
+                "Order"
+            }
+
+            fn apply(&mut self, _event: Self::Event) {}
+        }
+
+        query_events!(ShopEvent => [ProfileEvent from Profile, OrderEvent from Order]);
+
+        assert_eq!(ShopEvent::stream_types(), vec!["Profile", "Order"]);
+        assert!(ShopEvent::from(OrderEvent::Placed { total: 3 }).is_order_event());
+    }
+
+    #[test]
+    fn test_tagged_merge_picks_the_type_named_by_the_tag() {
+        // A struct event ignores the `type` field, so it would also accept a `Registered` event
+        #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, replay_macros::Event)]
+        #[serde(tag = "type")]
+        pub struct Renamed {
+            name: String,
+        }
+
+        #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, replay_macros::Event)]
+        #[serde(tag = "type")]
+        pub enum MemberEvent {
+            Registered { name: String },
+        }
+
+        query_events!(UntaggedMemberEvent => [Renamed, MemberEvent]);
+        query_events!(#[serde(tag = "type")] TaggedMemberEvent => [Renamed, MemberEvent]);
+
+        let stored = serde_json::to_value(MemberEvent::Registered {
+            name: "Ada".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            stored,
+            serde_json::json!({ "type": "Registered", "name": "Ada" })
+        );
+
+        // Untagged: the first type that fits wins
+        let untagged: UntaggedMemberEvent = serde_json::from_value(stored.clone()).unwrap();
+        assert!(untagged.is_renamed());
+
+        // Tagged: the type whose event type matches the tag wins, and serializes back the same
+        let tagged: TaggedMemberEvent = serde_json::from_value(stored.clone()).unwrap();
+        assert!(tagged.is_member_event());
+        assert_eq!(serde_json::to_value(&tagged).unwrap(), stored);
+
+        let err = serde_json::from_value::<TaggedMemberEvent>(serde_json::json!({ "name": "Ada" }))
+            .unwrap_err();
+        assert!(err.to_string().contains("missing field `type`"));
+
+        let err = serde_json::from_value::<TaggedMemberEvent>(
+            serde_json::json!({ "type": "Closed", "name": "Ada" }),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Renamed: event type is `Renamed`"));
+    }
 }
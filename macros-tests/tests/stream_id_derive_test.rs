@@ -0,0 +1,121 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use replay::{ErrorKind, EventStream, StreamId, WithId};
+use replay_macros::{Event, StreamId, StreamType};
+use serde::{Deserialize, Serialize};
+use urn::Urn;
+use uuid::Uuid;
+
+/// UUID-keyed id; the namespace is the type name without `Id`: `"order"`.
+#[derive(Clone, Debug, PartialEq, StreamId)]
+struct OrderId(Uuid);
+
+/// String-keyed id with an explicit namespace.
+#[derive(Clone, Debug, PartialEq, StreamId)]
+#[stream_id(namespace = "sku")]
+struct ProductKey(String);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Event)]
+enum OrderEvent {
+    Placed,
+}
+
+#[derive(Debug, StreamType)]
+struct Order {
+    id: OrderId,
+    placed: bool,
+}
+
+impl WithId for Order {
+    type StreamId = OrderId;
+
+    fn with_id(id: Self::StreamId) -> Self {
+        Order { id, placed: false }
+    }
+
+    fn get_id(&self) -> &Self::StreamId {
+        &self.id
+    }
+}
+
+impl EventStream for Order {
+    type Event = OrderEvent;
+
+    fn stream_type() -> &'static str {
+        Self::STREAM_TYPE
+    }
+
+    fn apply(&mut self, event: Self::Event) {
+        match event {
+            OrderEvent::Placed => self.placed = true,
+        }
+    }
+}
+
+#[test]
+fn test_uuid_ids_are_stored_under_their_namespace() {
+    let uuid = Uuid::now_v7();
+    let id = OrderId(uuid);
+
+    assert_eq!(OrderId::namespace(), "order");
+    assert_eq!(id.to_string(), uuid.to_string());
+    assert_eq!(id.stream_urn().to_string(), format!("urn:order:{uuid}"));
+    assert_eq!(id.stream_namespace(), "order");
+    assert_eq!(OrderId::try_from(id.stream_urn()).unwrap(), id);
+    assert_eq!(serde_json::to_value(&id).unwrap(), uuid.to_string());
+}
+
+#[test]
+fn test_string_keys_are_percent_encoded() {
+    let key = ProductKey::new("red shirt@eu".to_string()).unwrap();
+
+    let urn: Urn = key.clone().into();
+    assert_eq!(urn.to_string(), "urn:sku:red%20shirt%40eu");
+    assert_eq!(ProductKey::parse_stream_id(urn.as_ref()).unwrap(), key);
+}
+
+#[test]
+fn test_ids_of_another_namespace_or_key_are_rejected() {
+    let err = ProductKey::parse_stream_id("urn:order:sku-1").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    let err = OrderId::parse_stream_id("urn:order:not-a-uuid").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_empty_keys_are_rejected_before_they_reach_a_urn() {
+    let err = ProductKey::new(String::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    assert!(serde_json::from_str::<ProductKey>(r#""""#).is_err());
+    let key: ProductKey = serde_json::from_str(r#""shirt""#).unwrap();
+    assert_eq!(key, ProductKey::new("shirt".to_string()).unwrap());
+}
+
+#[test]
+fn test_aggregates_can_be_keyed_by_uuid() {
+    let uuid = Uuid::now_v7();
+    let mut order = Order::with_string_id(format!("urn:order:{uuid}")).unwrap();
+    order.apply(OrderEvent::Placed);
+
+    assert_eq!(order.get_id(), &OrderId(uuid));
+    assert!(order.placed);
+}
+
+#[derive(StreamType)]
+struct HttpConnection;
+
+#[derive(StreamType)]
+#[stream_type(name = "BankAccount")]
+struct Account;
+
+#[test]
+fn test_stream_types_are_derived_at_compile_time() {
+    const ORDER: &str = Order::STREAM_TYPE;
+
+    assert_eq!(ORDER, "order");
+    assert_eq!(Order::stream_type(), "order");
+    assert_eq!(HttpConnection::STREAM_TYPE, "http-connection");
+    assert_eq!(Account::STREAM_TYPE, "BankAccount");
+}
@@ -0,0 +1,18 @@
+use replay_macros::define_aggregate;
+
+define_aggregate! {
+    BankAccount {
+        state: {
+            balance: f64,
+        },
+        commands: {
+            Deposit { amount: f64 }
+        },
+        events: {
+            Deposited { amount: f64 },
+            Deposited { amount: f64 }
+        }
+    }
+}
+
+fn main() {}
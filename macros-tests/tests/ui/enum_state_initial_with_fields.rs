@@ -0,0 +1,18 @@
+use replay_macros::define_aggregate;
+
+define_aggregate! {
+    Loan {
+        state: enum {
+            Active { outstanding: u64 },
+            Repaid
+        },
+        commands: {
+            Repay { amount: u64 }
+        },
+        events: {
+            Repaid { amount: u64 }
+        }
+    }
+}
+
+fn main() {}
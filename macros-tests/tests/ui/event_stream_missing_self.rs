@@ -0,0 +1,19 @@
+use replay_macros::{aggregate, event_stream, events};
+
+#[aggregate]
+pub struct Counter {
+    pub count: i64,
+}
+
+#[events]
+pub enum CounterEvent {
+    Incremented { by: i64 },
+}
+
+#[event_stream(event = CounterEvent)]
+impl Counter {
+    #[apply(Incremented)]
+    fn on_incremented(by: i64) {}
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use replay_macros::define_aggregate;
+
+define_aggregate! {
+    BankAccount {
+        state: {
+            balance: f64,
+        },
+        commands: {
+            Deposit { amount }
+        },
+        events: {
+            Deposited { amount: f64 }
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use replay_macros::define_aggregate;
+
+define_aggregate! {
+    state: {
+        balance: f64,
+    },
+    commands: {
+        Deposit { amount: f64 }
+    },
+    events: {
+        Deposited { amount: f64 }
+    }
+}
+
+fn main() {}
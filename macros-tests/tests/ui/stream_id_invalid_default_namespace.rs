@@ -0,0 +1,6 @@
+use replay_macros::StreamId;
+
+#[derive(Clone, Debug, PartialEq, StreamId)]
+struct XId(String);
+
+fn main() {}
@@ -0,0 +1,7 @@
+use replay_macros::StreamId;
+
+#[derive(Clone, Debug, PartialEq, StreamId)]
+#[stream_id(namespace = "sku:eu")]
+struct ProductKey(String);
+
+fn main() {}
@@ -0,0 +1,8 @@
+use replay_macros::Urn;
+
+#[derive(Urn)]
+struct AccountUrn {
+    urn: urn::Urn,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use replay_macros::Urn;
+
+#[derive(Urn)]
+#[urn(nid = "account")]
+struct AccountUrn(urn::Urn);
+
+fn main() {}
@@ -0,0 +1,9 @@
+#![cfg(not(target_arch = "wasm32"))] // Skip for wasm target
+
+// Compile-fail cases for the macros' input checks; each `tests/ui/*.rs` has the expected
+// diagnostic next to it in a `.stderr` file (regenerate with `TRYBUILD=overwrite`).
+#[test]
+fn macro_diagnostics() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
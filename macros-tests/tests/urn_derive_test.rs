@@ -213,6 +213,16 @@ fn test_new_random_produces_unique_values() {
     assert_ne!(BranchUrn::new_random(), BranchUrn::new_random());
 }
 
+#[test]
+fn test_new_random_is_time_ordered_uuid_v7() {
+    let first = BranchUrn::new_random();
+    let second = BranchUrn::new_random();
+
+    let uuid = uuid::Uuid::parse_str(first.nss()).unwrap();
+    assert_eq!(uuid.get_version_num(), 7);
+    assert!(first.nss() < second.nss());
+}
+
 // ── parse() — namespace attribute ────────────────────────────────────────────
 
 #[test]
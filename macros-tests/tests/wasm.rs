@@ -47,8 +47,8 @@ impl BankAccountServices for MockBankAccountServices {
 impl EventStream for BankAccount {
     type Event = BankAccountEvent;
 
-    fn stream_type() -> String {
-        "BankAccount".to_string()
+    fn stream_type() -> &'static str {
+        "BankAccount"
     }
 
     fn apply(&mut self, event: Self::Event) {
@@ -150,3 +150,27 @@ async fn test_service_validation_in_wasm() {
     let error = result.unwrap_err();
     assert_eq!(error.operation(), "OpenAccount");
 }
+
+#[wasm_bindgen_test]
+async fn test_commands_run_through_local_cqrs_in_wasm() {
+    use replay::local::{LocalCqrs, LocalInMemoryEventStore};
+    use replay::Metadata;
+
+    let cqrs = LocalCqrs::new(LocalInMemoryEventStore::new());
+    let id = BankAccountUrn::new_random();
+    let services: std::sync::Arc<dyn BankAccountServices> =
+        std::sync::Arc::new(MockBankAccountServices);
+
+    let open_account = BankAccountCommand::OpenAccount {
+        account_number: "123456".to_string(),
+    };
+    let deposit = BankAccountCommand::Deposit { amount: 100.0 };
+    for command in [open_account, deposit] {
+        cqrs.execute::<BankAccount>(&id, Metadata::default(), command, &services, None)
+            .await
+            .unwrap();
+    }
+
+    let account = cqrs.fetch_aggregate::<BankAccount>(&id).await.unwrap();
+    assert_eq!(account.balance, 100.0);
+}
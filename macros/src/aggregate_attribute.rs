@@ -0,0 +1,442 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse::Parser, parse_quote, Field, Fields, FnArg, ImplItem, Item, ItemEnum, ItemImpl,
+    ItemStruct, ItemTrait, Pat,
+};
+
+use crate::{aggregate_namespace, camel_to_kebab, urn_doc, urn_serde_impl};
+
+// Expand #[aggregate(namespace = "...", transient)] on the aggregate state struct
+pub fn aggregate(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    let mut namespace: Option<syn::LitStr> = None;
+    let mut transient = false;
+    let args = syn::meta::parser(|meta| {
+        if meta.path.is_ident("namespace") {
+            namespace = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("transient") {
+            transient = true;
+            Ok(())
+        } else {
+            Err(meta.error("unsupported aggregate argument, expected `namespace` or `transient`"))
+        }
+    });
+    args.parse2(attr)?;
+
+    let mut item: ItemStruct = syn::parse2(item)?;
+    let name = item.ident.clone();
+    let vis = item.vis.clone();
+    let urn_name = format_ident!("{}Urn", name);
+
+    let namespace_str = namespace
+        .map(|lit| lit.value())
+        .unwrap_or_else(|| aggregate_namespace(&name.to_string()));
+    let namespace = syn::LitStr::new(&namespace_str, name.span());
+
+    // State fields start at their `Default`; generic aggregates need that as a bound
+    let state_fields: Vec<(syn::Ident, syn::Type)> = match &item.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|f| (f.ident.clone().expect("named field"), f.ty.clone()))
+            .collect(),
+        Fields::Unit => Vec::new(),
+        Fields::Unnamed(fields) => {
+            return Err(syn::Error::new_spanned(
+                fields,
+                "#[aggregate] state must be a struct with named fields",
+            ))
+        }
+    };
+
+    let id_field = Field::parse_named.parse2(quote! {
+        /// Id of the stream this aggregate is rebuilt from.
+        pub id: #urn_name
+    })?;
+    match &mut item.fields {
+        Fields::Named(fields) => fields.named.insert(0, id_field),
+        fields => *fields = Fields::Named(parse_quote!({ #id_field })),
+    }
+
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+    let mut with_id_where = where_clause.cloned().unwrap_or_else(|| parse_quote!(where));
+    if !item.generics.params.is_empty() {
+        for (_, ty) in &state_fields {
+            with_id_where.predicates.push(parse_quote!(#ty: Default));
+        }
+    }
+    let state_field_names = state_fields.iter().map(|(ident, _)| ident);
+
+    let urn_serde_impl = urn_serde_impl(&urn_name, &namespace);
+    let urn_doc = urn_doc(&name, &namespace_str);
+
+    // A transient state is only rebuilt from events, never serialized
+    let derives = if transient {
+        quote! { #[derive(Clone, Debug)] }
+    } else {
+        quote! { #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)] }
+    };
+
+    Ok(quote! {
+        #derives
+        #item
+
+        impl #impl_generics replay::WithId for #name #ty_generics #with_id_where {
+            type StreamId = #urn_name;
+
+            fn with_id(id: Self::StreamId) -> Self {
+                Self {
+                    id,
+                    #(#state_field_names: Default::default()),*
+                }
+            }
+
+            fn get_id(&self) -> &Self::StreamId {
+                &self.id
+            }
+        }
+
+        // Aggregates compare by ID only
+        impl #impl_generics PartialEq for #name #ty_generics #where_clause {
+            fn eq(&self, other: &Self) -> bool {
+                self.id == other.id
+            }
+        }
+
+        #[derive(Clone, Debug, replay_macros::Urn)]
+        #[urn(namespace = #namespace)]
+        #[doc = #urn_doc]
+        #vis struct #urn_name(urn::Urn);
+
+        #urn_serde_impl
+    })
+}
+
+// Expand #[commands] on the command enum
+pub fn commands(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    no_arguments("commands", attr)?;
+
+    let item: ItemEnum = syn::parse2(item)?;
+    let name = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    let name_arms = item.variants.iter().map(|variant| {
+        let variant_name = &variant.ident;
+        let variant_str = variant_name.to_string();
+        quote! { Self::#variant_name { .. } => #variant_str }
+    });
+
+    Ok(quote! {
+        #item
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Name of this command's variant.
+            pub fn command_name(&self) -> &'static str {
+                match *self {
+                    #(#name_arms),*
+                }
+            }
+        }
+    })
+}
+
+// Expand #[events] on the event enum (or struct)
+pub fn events(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    no_arguments("events", attr)?;
+
+    let item: Item = syn::parse2(item)?;
+    if !matches!(item, Item::Enum(_) | Item::Struct(_)) {
+        return Err(syn::Error::new_spanned(
+            item,
+            "#[events] can only be applied to an enum or a struct",
+        ));
+    }
+
+    // Events with `#[pii]` fields get a redacting `Debug` from `derive(Event)`
+    let fields: Vec<&syn::Field> = match &item {
+        Item::Enum(item) => item.variants.iter().flat_map(|v| &v.fields).collect(),
+        Item::Struct(item) => item.fields.iter().collect(),
+        _ => Vec::new(),
+    };
+    let debug = (!crate::event_derive::has_pii(fields.into_iter())).then(|| quote! { Debug, });
+
+    Ok(quote! {
+        #[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, #debug replay_macros::Event)]
+        #item
+    })
+}
+
+// Expand #[services] on the services trait
+pub fn services(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    no_arguments("services", attr)?;
+
+    let mut item: ItemTrait = syn::parse2(item)?;
+
+    for bound in ["Send", "Sync"] {
+        let already_bound = item.supertraits.iter().any(
+            |existing| matches!(existing, syn::TypeParamBound::Trait(t) if t.path.is_ident(bound)),
+        );
+        if !already_bound {
+            let bound = format_ident!("{}", bound);
+            item.supertraits.push(parse_quote!(#bound));
+        }
+    }
+    if item.colon_token.is_none() {
+        item.colon_token = Some(Default::default());
+    }
+
+    let has_async = item
+        .items
+        .iter()
+        .any(|trait_item| matches!(trait_item, syn::TraitItem::Fn(f) if f.sig.asyncness.is_some()));
+    let async_trait_attr = if has_async {
+        quote! {
+            #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+            #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+        }
+    } else {
+        quote! {}
+    };
+
+    Ok(quote! {
+        #async_trait_attr
+        #item
+    })
+}
+
+// Expand #[event_stream(event = ...)] on an inherent impl whose methods carry #[apply(Variant)],
+// or #[event_stream] on a hand-written `impl EventStream for ...` lacking `stream_type()`
+pub fn event_stream(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    let mut event: Option<syn::Type> = None;
+    let mut stream_type: Option<syn::LitStr> = None;
+    let args = syn::meta::parser(|meta| {
+        if meta.path.is_ident("event") {
+            event = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("stream_type") {
+            stream_type = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported event_stream argument, expected `event` or `stream_type`"))
+        }
+    });
+    args.parse2(attr.clone())?;
+
+    let mut item: ItemImpl = syn::parse2(item)?;
+    if item.trait_.is_some() {
+        if let Some(event) = event {
+            return Err(syn::Error::new_spanned(
+                event,
+                "an `impl EventStream` names its event with `type Event`, drop `event = ...`",
+            ));
+        }
+        return event_stream_impl(stream_type, item);
+    }
+
+    let Some(event) = event else {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "#[event_stream] needs the event type, e.g. #[event_stream(event = BankAccountEvent)]",
+        ));
+    };
+
+    // Patterns name the variants through the event's path, generic arguments are inferred
+    let mut event_path = match &event {
+        syn::Type::Path(path) if path.qself.is_none() => path.path.clone(),
+        ty => {
+            return Err(syn::Error::new_spanned(
+                ty,
+                "the event must be a named enum type",
+            ))
+        }
+    };
+    if let Some(last) = event_path.segments.last_mut() {
+        last.arguments = syn::PathArguments::None;
+    }
+
+    let stream_type = match stream_type {
+        Some(lit) => lit.value(),
+        None => match &*item.self_ty {
+            syn::Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string())
+                .unwrap_or_default(),
+            ty => {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "can't name the stream type of this type, give `stream_type = \"...\"`",
+                ))
+            }
+        },
+    };
+
+    let mut arms = Vec::new();
+    let mut variants: Vec<syn::Ident> = Vec::new();
+    for impl_item in &mut item.items {
+        let ImplItem::Fn(method) = impl_item else {
+            continue;
+        };
+        let Some(position) = method
+            .attrs
+            .iter()
+            .position(|attr| attr.path().is_ident("apply"))
+        else {
+            continue;
+        };
+        let variant: syn::Ident = method.attrs.remove(position).parse_args()?;
+        if let Some(earlier) = variants.iter().find(|earlier| **earlier == variant) {
+            return Err(syn::Error::new_spanned(
+                &variant,
+                format!("event '{earlier}' already has an #[apply] method"),
+            ));
+        }
+
+        let sig = &method.sig;
+        if !matches!(
+            sig.inputs.first(),
+            Some(FnArg::Receiver(receiver)) if receiver.reference.is_some() && receiver.mutability.is_some()
+        ) {
+            return Err(syn::Error::new_spanned(
+                sig,
+                "#[apply] methods take `&mut self` followed by the event's fields",
+            ));
+        }
+        if sig.asyncness.is_some() {
+            return Err(syn::Error::new_spanned(
+                sig.asyncness,
+                "#[apply] methods can't be async",
+            ));
+        }
+
+        // Parameters are matched to event fields by name; a leading `_` is ignored
+        let mut fields = Vec::new();
+        let mut bindings = Vec::new();
+        for input in sig.inputs.iter().skip(1) {
+            let FnArg::Typed(typed) = input else {
+                continue;
+            };
+            let Pat::Ident(pat) = &*typed.pat else {
+                return Err(syn::Error::new_spanned(
+                    &typed.pat,
+                    "#[apply] parameters must be named after the event's fields",
+                ));
+            };
+            let binding = &pat.ident;
+            let field_str = binding.to_string();
+            let field = format_ident!(
+                "{}",
+                field_str.trim_start_matches('_'),
+                span = binding.span()
+            );
+            fields.push(if field == *binding {
+                quote! { #binding }
+            } else {
+                quote! { #field: #binding }
+            });
+            bindings.push(binding.clone());
+        }
+
+        let method_name = &sig.ident;
+        arms.push(quote! {
+            #event_path::#variant { #(#fields,)* .. } => self.#method_name(#(#bindings),*)
+        });
+        variants.push(variant);
+    }
+
+    if arms.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.self_ty,
+            "#[event_stream] found no methods marked #[apply(Variant)]",
+        ));
+    }
+
+    let self_ty = &item.self_ty;
+    let (impl_generics, _, where_clause) = item.generics.split_for_impl();
+
+    Ok(quote! {
+        #item
+
+        impl #impl_generics replay::EventStream for #self_ty #where_clause {
+            type Event = #event;
+
+            fn stream_type() -> &'static str {
+                #stream_type
+            }
+
+            fn apply(&mut self, event: Self::Event) {
+                // Events without an #[apply] method leave the state unchanged
+                #[allow(unreachable_patterns)]
+                match event {
+                    #(#arms,)*
+                    _ => {}
+                }
+            }
+        }
+    })
+}
+
+// Add `stream_type()` to a hand-written `impl EventStream`: the given name, or the kebab-cased
+// type name like #[derive(StreamType)]
+fn event_stream_impl(
+    stream_type: Option<syn::LitStr>,
+    mut item: ItemImpl,
+) -> syn::Result<TokenStream> {
+    let defined = item.items.iter().any(
+        |impl_item| matches!(impl_item, ImplItem::Fn(method) if method.sig.ident == "stream_type"),
+    );
+    if defined {
+        return Err(syn::Error::new_spanned(
+            stream_type.map_or_else(
+                || item.self_ty.to_token_stream(),
+                |lit| lit.to_token_stream(),
+            ),
+            "this impl already defines `stream_type()`, drop #[event_stream] or the method",
+        ));
+    }
+
+    let stream_type = match stream_type {
+        Some(lit) => lit.value(),
+        // Every instantiation of a generic type would be stored under the same stream type
+        None if !item.generics.params.is_empty() => {
+            return Err(syn::Error::new_spanned(
+                &item.generics,
+                "can't name the stream type of a generic type, give `stream_type = \"...\"`",
+            ))
+        }
+        None => match &*item.self_ty {
+            syn::Type::Path(path) if path.qself.is_none() => path
+                .path
+                .segments
+                .last()
+                .map(|segment| camel_to_kebab(&segment.ident.to_string()))
+                .unwrap_or_default(),
+            ty => {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "can't name the stream type of this type, give `stream_type = \"...\"`",
+                ))
+            }
+        },
+    };
+
+    item.items.push(parse_quote! {
+        fn stream_type() -> &'static str {
+            #stream_type
+        }
+    });
+    Ok(quote! { #item })
+}
+
+fn no_arguments(macro_name: &str, attr: TokenStream) -> syn::Result<()> {
+    if attr.is_empty() {
+        Ok(())
+    } else {
+        Err(syn::Error::new_spanned(
+            attr,
+            format!("#[{macro_name}] takes no arguments"),
+        ))
+    }
+}
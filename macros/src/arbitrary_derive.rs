@@ -0,0 +1,181 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Attribute, Data, DeriveInput, Expr, Fields};
+
+/// `#[arbitrary(...)]` options on a field.
+#[derive(Default)]
+struct FieldOptions {
+    /// Strategy used instead of `any::<FieldType>()`.
+    strategy: Option<Expr>,
+}
+
+/// `#[arbitrary(...)]` options on an enum variant.
+#[derive(Default)]
+struct VariantOptions {
+    /// The variant is never generated.
+    skip: bool,
+}
+
+fn parse_field_options(attrs: &[Attribute]) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("arbitrary"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("strategy") {
+                options.strategy = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported arbitrary field attribute, expected `strategy`"))
+            }
+        })?;
+    }
+    Ok(options)
+}
+
+fn parse_variant_options(attrs: &[Attribute]) -> syn::Result<VariantOptions> {
+    let mut options = VariantOptions::default();
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("arbitrary"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                options.skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported arbitrary variant attribute, expected `skip`"))
+            }
+        })?;
+    }
+    Ok(options)
+}
+
+/// Strategy building `constructor` from `fields`: the field strategies nested in pairs
+/// (`(a, (b, Just(())))`) so any number of fields fits, mapped into the value.
+fn fields_strategy(constructor: TokenStream, fields: &Fields) -> syn::Result<TokenStream> {
+    let proptest = quote! { replay::__private::proptest };
+
+    if matches!(fields, Fields::Unit) {
+        return Ok(quote! { #proptest::strategy::LazyJust::new(|| #constructor).boxed() });
+    }
+
+    let mut bindings = Vec::new();
+    let mut strategies = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let options = parse_field_options(&field.attrs)?;
+        let ty = &field.ty;
+        bindings.push(format_ident!("field_{}", i));
+        strategies.push(match options.strategy {
+            Some(strategy) => quote! { #strategy },
+            None => quote! { #proptest::arbitrary::any::<#ty>() },
+        });
+    }
+
+    let strategy = strategies.iter().rev().fold(
+        quote! { #proptest::strategy::Just(()) },
+        |rest, strategy| {
+            quote! { (#strategy, #rest) }
+        },
+    );
+    let pattern = bindings
+        .iter()
+        .rev()
+        .fold(quote! { () }, |rest, binding| quote! { (#binding, #rest) });
+
+    let value = match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|f| &f.ident);
+            quote! { #constructor { #(#names: #bindings),* } }
+        }
+        _ => quote! { #constructor ( #(#bindings),* ) },
+    };
+
+    Ok(quote! {
+        #strategy.prop_map(|#pattern| #value).boxed()
+    })
+}
+
+/// Derive `proptest::arbitrary::Arbitrary`, generating every field with `any::<T>()` unless
+/// `#[arbitrary(strategy = ...)]` gives another strategy; enum variants are picked uniformly
+/// and `#[arbitrary(skip)]` leaves one out.
+///
+/// The impl is wrapped in `replay::__proptest!`, so it only exists when `replay` is built with
+/// its `proptest` feature.
+pub fn derive_arbitrary(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let proptest = quote! { replay::__private::proptest };
+
+    let body = match &input.data {
+        Data::Struct(data) => fields_strategy(quote! { #name }, &data.fields)?,
+        Data::Enum(data) => {
+            let mut variants = Vec::new();
+            for variant in &data.variants {
+                if parse_variant_options(&variant.attrs)?.skip {
+                    continue;
+                }
+                let variant_name = &variant.ident;
+                variants.push(fields_strategy(
+                    quote! { #name::#variant_name },
+                    &variant.fields,
+                )?);
+            }
+            if variants.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "Arbitrary needs at least one variant that is not `#[arbitrary(skip)]`",
+                ));
+            }
+            quote! { #proptest::strategy::Union::new(vec![#(#variants),*]).boxed() }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "Arbitrary can only be derived for structs and enums",
+            ))
+        }
+    };
+
+    // Generic types need their parameters (and the field types built from them) to be
+    // arbitrary as well; `'static` because the strategy is boxed.
+    let mut generics = input.generics.clone();
+    if !generics.params.is_empty() {
+        let fields: Vec<_> = match &input.data {
+            Data::Struct(data) => data.fields.iter().collect(),
+            Data::Enum(data) => data.variants.iter().flat_map(|v| &v.fields).collect(),
+            Data::Union(_) => Vec::new(),
+        };
+        let where_clause = generics.make_where_clause();
+        for field in fields {
+            if parse_field_options(&field.attrs)?.strategy.is_some() {
+                continue;
+            }
+            let ty = &field.ty;
+            where_clause
+                .predicates
+                .push(syn::parse_quote!(#ty: #proptest::arbitrary::Arbitrary + 'static));
+        }
+        let type_params: Vec<_> = input.generics.type_params().map(|p| &p.ident).collect();
+        for param in type_params {
+            where_clause
+                .predicates
+                .push(syn::parse_quote!(#param: std::fmt::Debug + 'static));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        replay::__proptest! {
+            impl #impl_generics #proptest::arbitrary::Arbitrary for #name #ty_generics #where_clause {
+                type Parameters = ();
+                type Strategy = #proptest::strategy::BoxedStrategy<Self>;
+
+                fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                    use #proptest::strategy::Strategy as _;
+                    #body
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,300 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Attribute, Expr, Field, GenericParam, Ident, Type};
+
+/// A `#[validate(...)]` rule on a command field.
+pub enum FieldRule {
+    /// `range(min = .., max = ..)`: compared with `<` / `>`.
+    Range {
+        min: Option<Expr>,
+        max: Option<Expr>,
+    },
+    /// `length(min = .., max = ..)`: bounds on `.len()`.
+    Length {
+        min: Option<Expr>,
+        max: Option<Expr>,
+    },
+    /// `non_empty`: `.is_empty()` must be false.
+    NonEmpty,
+}
+
+/// Builder requested with `#[builder]` on a command variant, with the rules of its fields.
+pub struct CommandBuilder {
+    pub rules: Vec<(Ident, Vec<FieldRule>)>,
+}
+
+/// Take `#[builder]` off a command variant and `#[validate(...)]` off its fields; neither may be
+/// forwarded to the generated enum.
+pub fn take_builder(
+    attrs: &mut Vec<Attribute>,
+    fields: &mut [Field],
+) -> syn::Result<Option<CommandBuilder>> {
+    let builder_attr = attrs
+        .iter()
+        .position(|attr| attr.path().is_ident("builder"))
+        .map(|i| attrs.remove(i));
+    if let Some(attr) = &builder_attr {
+        attr.meta.require_path_only()?;
+    }
+
+    let mut rules = Vec::new();
+    for field in fields.iter_mut() {
+        let mut field_rules = Vec::new();
+        let mut validate_attrs = Vec::new();
+        field.attrs.retain(|attr| {
+            let is_validate = attr.path().is_ident("validate");
+            if is_validate {
+                validate_attrs.push(attr.clone());
+            }
+            !is_validate
+        });
+
+        for attr in &validate_attrs {
+            if builder_attr.is_none() {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "#[validate] rules are checked by the command builder; add #[builder] to the command",
+                ));
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("non_empty") {
+                    field_rules.push(FieldRule::NonEmpty);
+                    return Ok(());
+                }
+
+                let is_range = meta.path.is_ident("range");
+                if !is_range && !meta.path.is_ident("length") {
+                    return Err(meta.error(
+                        "unsupported validation, expected `range(..)`, `length(..)` or `non_empty`",
+                    ));
+                }
+
+                let (mut min, mut max) = (None, None);
+                meta.parse_nested_meta(|bound| {
+                    if bound.path.is_ident("min") {
+                        min = Some(bound.value()?.parse()?);
+                    } else if bound.path.is_ident("max") {
+                        max = Some(bound.value()?.parse()?);
+                    } else {
+                        return Err(bound.error("expected `min` or `max`"));
+                    }
+                    Ok(())
+                })?;
+                if min.is_none() && max.is_none() {
+                    return Err(meta.error("give `min`, `max` or both"));
+                }
+
+                field_rules.push(if is_range {
+                    FieldRule::Range { min, max }
+                } else {
+                    FieldRule::Length { min, max }
+                });
+                Ok(())
+            })?;
+        }
+
+        if !field_rules.is_empty() {
+            let ident = field.ident.clone().expect("command fields are named");
+            rules.push((ident, field_rules));
+        }
+    }
+
+    Ok(builder_attr.map(|_| CommandBuilder { rules }))
+}
+
+/// Whether `ty` is written as `Option<..>`; such fields may be left unset in the builder.
+fn is_option(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.qself.is_none()
+        && path.path.segments.last().is_some_and(|segment| segment.ident == "Option"))
+}
+
+fn same_param(a: &GenericParam, b: &GenericParam) -> bool {
+    match (a, b) {
+        (GenericParam::Type(a), GenericParam::Type(b)) => a.ident == b.ident,
+        (GenericParam::Lifetime(a), GenericParam::Lifetime(b)) => a.lifetime == b.lifetime,
+        (GenericParam::Const(a), GenericParam::Const(b)) => a.ident == b.ident,
+        _ => false,
+    }
+}
+
+fn rule_checks(variant: &str, field: &Ident, rule: &FieldRule) -> TokenStream {
+    let field_str = field.to_string();
+    let fail = |message: TokenStream| {
+        quote! {
+            return Err(replay::Error::invalid_input(#message)
+                .with_operation(#variant)
+                .with_context("field", #field_str));
+        }
+    };
+
+    match rule {
+        FieldRule::NonEmpty => {
+            let fail = fail(quote! { format!("{} must not be empty", #field_str) });
+            quote! {
+                if self.#field.is_empty() {
+                    #fail
+                }
+            }
+        }
+        FieldRule::Range { min, max } => {
+            let min_check = min.as_ref().map(|min| {
+                let fail = fail(quote! { format!("{} must be at least {:?}", #field_str, #min) });
+                quote! {
+                    if self.#field < #min {
+                        #fail
+                    }
+                }
+            });
+            let max_check = max.as_ref().map(|max| {
+                let fail = fail(quote! { format!("{} must be at most {:?}", #field_str, #max) });
+                quote! {
+                    if self.#field > #max {
+                        #fail
+                    }
+                }
+            });
+            quote! { #min_check #max_check }
+        }
+        FieldRule::Length { min, max } => {
+            let min_check = min.as_ref().map(|min| {
+                let fail = fail(quote! {
+                    format!("{} must have a length of at least {}", #field_str, #min)
+                });
+                quote! {
+                    if self.#field.len() < #min {
+                        #fail
+                    }
+                }
+            });
+            let max_check = max.as_ref().map(|max| {
+                let fail = fail(quote! {
+                    format!("{} must have a length of at most {}", #field_str, #max)
+                });
+                quote! {
+                    if self.#field.len() > #max {
+                        #fail
+                    }
+                }
+            });
+            quote! { #min_check #max_check }
+        }
+    }
+}
+
+/// Generate `<Variant>Command` (the variant's fields, with `builder()` and `validate()`), its
+/// `<Variant>CommandBuilder`, and `From<<Variant>Command>` for the command enum.
+pub fn command_builder(
+    builder: &CommandBuilder,
+    command_name: &Ident,
+    command_generics: &syn::Generics,
+    variant_name: &Ident,
+    fields: &[Field],
+) -> TokenStream {
+    let args_name = format_ident!("{}Command", variant_name);
+    let builder_name = format_ident!("{}CommandBuilder", variant_name);
+    let variant_str = variant_name.to_string();
+
+    let (command_impl_generics, command_ty_generics, command_where_clause) =
+        command_generics.split_for_impl();
+    let command_params = &command_generics.params;
+    let args_generics = crate::define_aggregate_macro::generics_used_by(command_generics, fields);
+    let (args_impl_generics, args_ty_generics, args_where_clause) = args_generics.split_for_impl();
+    let args_params = &args_generics.params;
+    // `builder()` is generic over the command parameters its fields leave out
+    let builder_params: Vec<_> = command_params
+        .iter()
+        .filter(|param| !args_params.iter().any(|used| same_param(param, used)))
+        .collect();
+
+    let field_names: Vec<_> = fields.iter().map(|f| &f.ident).collect();
+    let field_types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    // Only doc comments carry over: other attributes may need derives the enum has and this lacks
+    let field_docs = fields.iter().map(|f| {
+        f.attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"))
+            .collect::<Vec<_>>()
+    });
+
+    let field_values = fields.iter().map(|f| {
+        let ident = &f.ident;
+        let ident_str = ident.as_ref().map(ToString::to_string);
+        if is_option(&f.ty) {
+            quote! { #ident: self.#ident.unwrap_or_default() }
+        } else {
+            quote! {
+                #ident: self.#ident.ok_or_else(|| {
+                    replay::Error::invalid_input(format!("{} is required", #ident_str))
+                        .with_operation(#variant_str)
+                        .with_context("field", #ident_str)
+                })?
+            }
+        }
+    });
+
+    let checks: Vec<_> = builder
+        .rules
+        .iter()
+        .flat_map(|(field, rules)| rules.iter().map(move |rule| (field, rule)))
+        .map(|(field, rule)| rule_checks(&variant_str, field, rule))
+        .collect();
+
+    let args_doc = format!(
+        "Fields of [`{command_name}::{variant_name}`]; build one with [`{args_name}::builder`]."
+    );
+    let builder_doc = format!("Builds a validated [`{command_name}::{variant_name}`].");
+
+    quote! {
+        #[doc = #args_doc]
+        pub struct #args_name <#args_params> #args_where_clause {
+            #(#(#field_docs)* pub #field_names: #field_types),*
+        }
+
+        impl #args_impl_generics #args_name #args_ty_generics #args_where_clause {
+            /// Start building the command; `build()` checks every field.
+            pub fn builder <#(#builder_params),*> () -> #builder_name #command_ty_generics #command_where_clause {
+                #builder_name {
+                    #(#field_names: None,)*
+                    _command: std::marker::PhantomData,
+                }
+            }
+
+            /// Check the `#[validate]` rules, failing with `InvalidInput` naming the field.
+            pub fn validate(&self) -> replay::Result<()> {
+                #(#checks)*
+                Ok(())
+            }
+        }
+
+        impl #command_impl_generics From<#args_name #args_ty_generics> for #command_name #command_ty_generics #command_where_clause {
+            fn from(args: #args_name #args_ty_generics) -> Self {
+                #command_name::#variant_name { #(#field_names: args.#field_names),* }
+            }
+        }
+
+        #[doc = #builder_doc]
+        pub struct #builder_name <#command_params> #command_where_clause {
+            #(#field_names: Option<#field_types>,)*
+            _command: std::marker::PhantomData<fn() -> #command_name #command_ty_generics>,
+        }
+
+        impl #command_impl_generics #builder_name #command_ty_generics #command_where_clause {
+            #(
+                pub fn #field_names(mut self, #field_names: impl Into<#field_types>) -> Self {
+                    self.#field_names = Some(#field_names.into());
+                    self
+                }
+            )*
+
+            /// Validate the fields and build the command; unset `Option` fields are `None`,
+            /// any other unset field is an `InvalidInput` error.
+            pub fn build(self) -> replay::Result<#command_name #command_ty_generics> {
+                let args = #args_name {
+                    #(#field_values),*
+                };
+                args.validate()?;
+                Ok(args.into())
+            }
+        }
+    }
+}
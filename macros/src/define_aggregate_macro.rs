@@ -1,32 +1,176 @@
+use std::collections::HashSet;
+
 use syn::{
-    parse::{Parse, ParseStream},
+    parse::{Parse, ParseStream, Parser},
     token::Brace,
-    Field, FnArg, Ident, ReturnType, Token, Type,
+    visit::Visit,
+    Attribute, Field, FnArg, GenericParam, Ident, ReturnType, Token, Type, WherePredicate,
 };
 
 // Struct to parse the define_aggregate! macro input
 pub struct AggregateDefinition {
+    /// Attributes before the aggregate name and on the `state` section, forwarded to the
+    /// state struct.
+    pub attrs: Vec<Attribute>,
     pub name: Ident,
     pub generics: syn::Generics,
     pub namespace: Option<syn::LitStr>,
     pub state_fields: Vec<Field>,
+    /// `state: enum { ... }`: the variants of the generated `<Name>State` enum, held in a
+    /// single `state` field of the aggregate.
+    pub state_variants: Option<Vec<StateVariant>>,
+    /// Attributes on an enum `state` section, forwarded to the state enum.
+    pub state_enum_attrs: Vec<Attribute>,
+    /// `#[transient]` on the `state` section: the state struct derives no serde traits, so it
+    /// may hold members that can't be serialized.
+    pub transient_state: bool,
     pub commands: Vec<CommandVariant>,
+    /// Attributes on the `commands` section, forwarded to the command enum.
+    pub command_attrs: Vec<Attribute>,
     pub events: Vec<EventVariant>,
+    /// Attributes on the `events` section, forwarded to the event enum.
+    pub event_attrs: Vec<Attribute>,
     pub base_service_traits: Vec<syn::Path>,
+    /// Attributes on the `service` section, forwarded to the services trait.
+    pub service_attrs: Vec<Attribute>,
     pub service_functions: Vec<ServiceFunction>,
+    /// Error type of the generated `Aggregate` impl (`error: MyError`).
+    pub error: Option<Type>,
+    /// The `apply` section (`apply: { Deposited => |state, e| ... }`); when present the
+    /// `EventStream` impl is generated.
+    pub appliers: Option<Vec<EventApplier>>,
+}
+
+impl AggregateDefinition {
+    /// Whether to generate `impl Aggregate`: requested by an `error:` section or by any
+    /// command carrying an inline handler or an output.
+    pub fn generates_handler(&self) -> bool {
+        self.error.is_some()
+            || self
+                .commands
+                .iter()
+                .any(|cmd| cmd.handler.is_some() || cmd.output.is_some())
+    }
+
+    /// Whether any command declares an output, making `handle` go through a generated
+    /// `WithOutput` impl.
+    pub fn has_outputs(&self) -> bool {
+        self.commands.iter().any(|cmd| cmd.output.is_some())
+    }
 }
 
 pub struct CommandVariant {
+    pub attrs: Vec<Attribute>,
+    pub name: Ident,
+    pub fields: Vec<Field>,
+    /// Inline handler (`Deposit { amount: f64 } => |state, cmd, svc| { ... }`); commands
+    /// without one dispatch to an `on_<command>` method.
+    pub handler: Option<syn::ExprClosure>,
+    /// Value handed back to the caller next to the events (`Register { .. } -> ApiKey`).
+    pub output: Option<Type>,
+    /// `#[builder]`: generate a validating builder for this command.
+    pub builder: Option<crate::command_builder::CommandBuilder>,
+    /// `#[create]`: the command opens the stream; with any such command, the others need it
+    /// opened.
+    pub creates: bool,
+}
+
+impl CommandVariant {
+    /// Name of the method a command without inline handler dispatches to:
+    /// `OpenAccount` → `on_open_account`.
+    pub fn handler_method(&self) -> Ident {
+        quote::format_ident!("on_{}", camel_to_snake(&self.name.to_string()))
+    }
+}
+
+pub fn camel_to_snake(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 4);
+    let chars: Vec<char> = s.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev_is_lower = chars[i - 1].is_lowercase() || chars[i - 1].is_ascii_digit();
+            let next_is_lower = i + 1 < chars.len() && chars[i + 1].is_lowercase();
+            if prev_is_lower || (chars[i - 1].is_uppercase() && next_is_lower) {
+                result.push('_');
+            }
+        }
+        result.push(c.to_ascii_lowercase());
+    }
+    result
+}
+
+/// A variant of an enum `state`; the first one, a unit variant, is the initial state.
+pub struct StateVariant {
+    pub attrs: Vec<Attribute>,
     pub name: Ident,
     pub fields: Vec<Field>,
 }
 
 pub struct EventVariant {
+    pub attrs: Vec<Attribute>,
     pub name: Ident,
     pub fields: Vec<Field>,
+    /// `#[entity(field = lines, id = line_id)]`: `apply` routes the event to the child of
+    /// `state.lines` named by its `line_id` field.
+    pub entity: Option<EntityRoute>,
+}
+
+/// Where `apply` routes an `#[entity(..)]` event: the `Entities` field of the state and the
+/// event field holding the child's id.
+pub struct EntityRoute {
+    pub field: Ident,
+    pub id: Ident,
+}
+
+/// Take `#[entity(field = .., id = ..)]` off an event variant; `id` must name one of its fields.
+fn take_entity(attrs: &mut Vec<Attribute>, fields: &[Field]) -> syn::Result<Option<EntityRoute>> {
+    let Some(i) = attrs.iter().position(|attr| attr.path().is_ident("entity")) else {
+        return Ok(None);
+    };
+    let attr = attrs.remove(i);
+
+    let (mut field, mut id) = (None, None);
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("field") {
+            field = Some(meta.value()?.parse::<Ident>()?);
+        } else if meta.path.is_ident("id") {
+            id = Some(meta.value()?.parse::<Ident>()?);
+        } else {
+            return Err(meta.error("unsupported entity argument, expected `field` or `id`"));
+        }
+        Ok(())
+    })?;
+    let (Some(field), Some(id)) = (field, id) else {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "#[entity] needs the state field and the id field, e.g. #[entity(field = lines, id = line_id)]",
+        ));
+    };
+    if !fields.iter().any(|f| f.ident.as_ref() == Some(&id)) {
+        return Err(syn::Error::new_spanned(
+            &id,
+            format!("'{id}' is not a field of this event"),
+        ));
+    }
+    Ok(Some(EntityRoute { field, id }))
+}
+
+impl EventVariant {
+    /// Name of the method an event without `apply` entry dispatches to:
+    /// `AccountOpened` → `apply_account_opened`.
+    pub fn apply_method(&self) -> Ident {
+        quote::format_ident!("apply_{}", camel_to_snake(&self.name.to_string()))
+    }
+}
+
+/// An `apply` section entry: `AccountOpened => |state, e| { ... }`.
+pub struct EventApplier {
+    pub event: Ident,
+    pub closure: syn::ExprClosure,
 }
 
 pub struct ServiceFunction {
+    pub attrs: Vec<Attribute>,
     pub name: Ident,
     pub lifetimes: Vec<syn::LifetimeParam>,
     pub inputs: Vec<FnArg>,
@@ -36,30 +180,143 @@ pub struct ServiceFunction {
 
 impl Parse for AggregateDefinition {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = input.call(Attribute::parse_outer)?;
         let name: Ident = input.parse()?;
+        if input.peek(Token![:]) {
+            return Err(syn::Error::new_spanned(
+                &name,
+                format!(
+                    "expected the aggregate name before its sections, e.g. `BankAccount {{ {name}: ... }}`"
+                ),
+            ));
+        }
 
-        // Parse optional generic parameters
-        let generics: syn::Generics = input.parse()?;
+        // Parse optional generic parameters and where clause
+        let mut generics: syn::Generics = input.parse()?;
+        generics.where_clause = input.parse()?;
 
+        if !input.peek(Brace) {
+            return Err(input.error(format!(
+                "expected `{{ ... }}` with the sections of '{name}' after its name"
+            )));
+        }
         let content;
         syn::braced!(content in input);
 
         let mut namespace = None;
         let mut state_fields = Vec::new();
+        let mut state_variants = None;
+        let mut state_enum_attrs = Vec::new();
         let mut commands = Vec::new();
+        let mut command_attrs = Vec::new();
         let mut events = Vec::new();
+        let mut event_attrs = Vec::new();
         let mut base_service_traits = Vec::new();
+        let mut service_attrs = Vec::new();
+        let mut create_attr = None;
+        let mut transient_state = false;
         let mut service_functions = Vec::new();
+        let mut error = None;
+        let mut appliers = None;
 
+        let mut seen_sections: Vec<Ident> = Vec::new();
         while !content.is_empty() {
+            let mut section_attrs = content.call(Attribute::parse_outer)?;
+            if content.peek(Token![,]) {
+                return Err(content.error("unexpected `,`, expected a section name"));
+            }
             let section_name: Ident = content.parse()?;
+            check_section(&section_name, &seen_sections)?;
+            seen_sections.push(section_name.clone());
+            if !content.peek(Token![:]) {
+                return Err(content.error(format!(
+                    "expected `:` after the '{section_name}' section name, e.g. `{section_name}: {{ ... }}`"
+                )));
+            }
             content.parse::<Token![:]>()?;
 
+            if section_name == "state" {
+                let transient = section_attrs
+                    .iter()
+                    .position(|attr| attr.path().is_ident("transient"));
+                if let Some(i) = transient {
+                    section_attrs.remove(i).meta.require_path_only()?;
+                    transient_state = true;
+                }
+            }
+
             match section_name.to_string().as_str() {
+                "namespace" | "error" | "apply" if !section_attrs.is_empty() => {
+                    return Err(syn::Error::new_spanned(
+                        &section_attrs[0],
+                        format!("attributes are not supported on the '{section_name}' section"),
+                    ));
+                }
                 "namespace" => {
                     namespace = Some(content.parse()?);
                 }
+                "state" if content.peek(Token![enum]) => {
+                    let enum_token = content.parse::<Token![enum]>()?;
+                    state_enum_attrs = section_attrs;
+
+                    let section_content;
+                    syn::braced!(section_content in content);
+                    let mut variants = Vec::new();
+                    while !section_content.is_empty() {
+                        let attrs = section_content.call(Attribute::parse_outer)?;
+                        let (name, fields) = parse_variant(&section_content)?;
+
+                        if section_content.peek(Token![,]) {
+                            section_content.parse::<Token![,]>()?;
+                        } else if !section_content.is_empty() {
+                            return Err(
+                                section_content.error(format!("expected `,` after state '{name}'"))
+                            );
+                        }
+
+                        variants.push(StateVariant {
+                            attrs,
+                            name,
+                            fields,
+                        });
+                    }
+
+                    match variants.first() {
+                        None => {
+                            return Err(syn::Error::new_spanned(
+                                enum_token,
+                                "an enum state needs at least one variant, the initial state",
+                            ))
+                        }
+                        Some(initial) if !initial.fields.is_empty() => {
+                            return Err(syn::Error::new_spanned(
+                                &initial.name,
+                                format!(
+                                    "the first state, '{}', is where every aggregate starts and cannot have fields",
+                                    initial.name
+                                ),
+                            ))
+                        }
+                        Some(_) => {}
+                    }
+                    ensure_unique("state", variants.iter().map(|state| &state.name))?;
+
+                    let state_name = quote::format_ident!("{}State", name);
+                    let state_generics =
+                        generics_used_by(&generics, variants.iter().flat_map(|v| &v.fields));
+                    let (_, state_ty_generics, _) = state_generics.split_for_impl();
+                    state_fields = vec![Field::parse_named.parse2(quote::quote! {
+                        /// Where this aggregate is in its lifecycle.
+                        pub state: #state_name #state_ty_generics
+                    })?];
+                    state_variants = Some(variants);
+                }
+                "error" => {
+                    error = Some(content.parse()?);
+                }
                 "service" => {
+                    service_attrs = section_attrs;
+
                     // Check if there are base traits before the opening brace
                     // service: BaseService { ... } or service: BaseService + OtherService { ... }
                     if !content.peek(Brace) {
@@ -84,49 +341,7 @@ impl Parse for AggregateDefinition {
                     syn::braced!(section_content in content);
 
                     while !section_content.is_empty() {
-                        // Parse optional "async"
-                        let is_async = section_content.peek(Token![async]);
-                        if is_async {
-                            section_content.parse::<Token![async]>()?;
-                        }
-
-                        // Parse "fn"
-                        section_content.parse::<Token![fn]>()?;
-
-                        // Parse function name
-                        let fn_name: Ident = section_content.parse()?;
-
-                        // Parse optional lifetime parameters
-                        let lifetimes = if section_content.peek(Token![<]) {
-                            section_content.parse::<Token![<]>()?;
-                            let lifetimes_content: syn::punctuated::Punctuated<
-                                syn::LifetimeParam,
-                                Token![,],
-                            > = syn::punctuated::Punctuated::parse_separated_nonempty(
-                                &section_content,
-                            )?;
-                            section_content.parse::<Token![>]>()?;
-                            lifetimes_content.into_iter().collect()
-                        } else {
-                            Vec::new()
-                        };
-
-                        // Parse function parameters
-                        let inputs_content;
-                        syn::parenthesized!(inputs_content in section_content);
-                        let inputs: syn::punctuated::Punctuated<FnArg, Token![,]> =
-                            inputs_content.parse_terminated(FnArg::parse, Token![,])?;
-
-                        // Parse return type
-                        let output: ReturnType = section_content.parse()?;
-
-                        service_functions.push(ServiceFunction {
-                            name: fn_name,
-                            lifetimes,
-                            inputs: inputs.into_iter().collect(),
-                            output,
-                            is_async,
-                        });
+                        service_functions.push(parse_service_function(&section_content)?);
 
                         // Optional comma or semicolon
                         if section_content.peek(Token![,]) {
@@ -137,124 +352,121 @@ impl Parse for AggregateDefinition {
                     }
                 }
                 _ => {
+                    if !content.peek(Brace) {
+                        return Err(
+                            content.error(format!("expected `{{ ... }}` after `{section_name}:`"))
+                        );
+                    }
                     let section_content;
                     syn::braced!(section_content in content);
 
                     match section_name.to_string().as_str() {
                         "state" => {
-                            while !section_content.is_empty() {
-                                let field_name: Ident = section_content.parse()?;
-                                section_content.parse::<Token![:]>()?;
-                                let field_type: Type = section_content.parse()?;
-
-                                state_fields.push(Field {
-                                    attrs: Vec::new(),
-                                    vis: syn::Visibility::Public(syn::token::Pub::default()),
-                                    mutability: syn::FieldMutability::None,
-                                    ident: Some(field_name),
-                                    colon_token: Some(Token![:](proc_macro2::Span::call_site())),
-                                    ty: field_type,
-                                });
-
-                                if section_content.peek(Token![,]) {
-                                    section_content.parse::<Token![,]>()?;
-                                }
-                            }
+                            attrs.extend(section_attrs);
+                            state_fields = parse_fields(
+                                &section_content,
+                                syn::Visibility::Public(syn::token::Pub::default()),
+                            )?;
                         }
                         "commands" => {
+                            command_attrs = section_attrs;
                             while !section_content.is_empty() {
-                                let variant_name: Ident = section_content.parse()?;
-
-                                let variant_fields = if section_content.peek(Brace) {
-                                    let fields_content;
-                                    syn::braced!(fields_content in section_content);
-
-                                    let mut fields = Vec::new();
-                                    while !fields_content.is_empty() {
-                                        let field_name: Ident = fields_content.parse()?;
-                                        fields_content.parse::<Token![:]>()?;
-                                        let field_type: Type = fields_content.parse()?;
-
-                                        fields.push(Field {
-                                            attrs: Vec::new(),
-                                            vis: syn::Visibility::Inherited,
-                                            mutability: syn::FieldMutability::None,
-                                            ident: Some(field_name),
-                                            colon_token: Some(Token![:](
-                                                proc_macro2::Span::call_site(),
-                                            )),
-                                            ty: field_type,
-                                        });
-
-                                        if fields_content.peek(Token![,]) {
-                                            fields_content.parse::<Token![,]>()?;
-                                        }
-                                    }
-                                    fields
+                                let mut attrs = section_content.call(Attribute::parse_outer)?;
+                                let (name, mut fields) = parse_variant(&section_content)?;
+                                let builder =
+                                    crate::command_builder::take_builder(&mut attrs, &mut fields)?;
+                                let create = attrs
+                                    .iter()
+                                    .position(|attr| attr.path().is_ident("create"))
+                                    .map(|i| attrs.remove(i));
+                                if let Some(attr) = &create {
+                                    attr.meta.require_path_only()?;
+                                }
+                                let creates = create.is_some();
+                                create_attr = create_attr.or(create);
+
+                                let output = if section_content.peek(Token![->]) {
+                                    section_content.parse::<Token![->]>()?;
+                                    Some(section_content.parse::<Type>()?)
                                 } else {
-                                    Vec::new()
+                                    None
                                 };
 
-                                commands.push(CommandVariant {
-                                    name: variant_name,
-                                    fields: variant_fields,
-                                });
+                                let handler = if section_content.peek(Token![=>]) {
+                                    section_content.parse::<Token![=>]>()?;
+                                    let closure: syn::ExprClosure = section_content.parse()?;
+                                    if closure.inputs.len() != 3 {
+                                        return Err(syn::Error::new_spanned(
+                                            &closure.inputs,
+                                            "command handlers take three parameters: `|state, cmd, svc|`",
+                                        ));
+                                    }
+                                    Some(closure)
+                                } else {
+                                    None
+                                };
 
                                 if section_content.peek(Token![,]) {
                                     section_content.parse::<Token![,]>()?;
+                                } else if !section_content.is_empty() {
+                                    return Err(section_content
+                                        .error(format!("expected `,` after command '{name}'")));
                                 }
+
+                                commands.push(CommandVariant {
+                                    attrs,
+                                    name,
+                                    fields,
+                                    output,
+                                    handler,
+                                    builder,
+                                    creates,
+                                });
                             }
                         }
                         "events" => {
+                            event_attrs = section_attrs;
                             while !section_content.is_empty() {
-                                let variant_name: Ident = section_content.parse()?;
-
-                                let variant_fields = if section_content.peek(Brace) {
-                                    let fields_content;
-                                    syn::braced!(fields_content in section_content);
-
-                                    let mut fields = Vec::new();
-                                    while !fields_content.is_empty() {
-                                        let field_name: Ident = fields_content.parse()?;
-                                        fields_content.parse::<Token![:]>()?;
-                                        let field_type: Type = fields_content.parse()?;
-
-                                        fields.push(Field {
-                                            attrs: Vec::new(),
-                                            vis: syn::Visibility::Inherited,
-                                            mutability: syn::FieldMutability::None,
-                                            ident: Some(field_name),
-                                            colon_token: Some(Token![:](
-                                                proc_macro2::Span::call_site(),
-                                            )),
-                                            ty: field_type,
-                                        });
-
-                                        if fields_content.peek(Token![,]) {
-                                            fields_content.parse::<Token![,]>()?;
-                                        }
-                                    }
-                                    fields
-                                } else {
-                                    Vec::new()
-                                };
+                                let mut attrs = section_content.call(Attribute::parse_outer)?;
+                                let (name, fields) = parse_variant(&section_content)?;
+                                let entity = take_entity(&mut attrs, &fields)?;
+
+                                if section_content.peek(Token![,]) {
+                                    section_content.parse::<Token![,]>()?;
+                                } else if !section_content.is_empty() {
+                                    return Err(section_content
+                                        .error(format!("expected `,` after event '{name}'")));
+                                }
 
                                 events.push(EventVariant {
-                                    name: variant_name,
-                                    fields: variant_fields,
+                                    attrs,
+                                    name,
+                                    fields,
+                                    entity,
                                 });
+                            }
+                        }
+                        "apply" => {
+                            let mut entries = Vec::new();
+                            while !section_content.is_empty() {
+                                let event: Ident = section_content.parse()?;
+                                section_content.parse::<Token![=>]>()?;
+                                let closure: syn::ExprClosure = section_content.parse()?;
+                                if closure.inputs.len() != 2 {
+                                    return Err(syn::Error::new_spanned(
+                                        &closure.inputs,
+                                        "apply handlers take two parameters: `|state, e|`",
+                                    ));
+                                }
+                                entries.push(EventApplier { event, closure });
 
                                 if section_content.peek(Token![,]) {
                                     section_content.parse::<Token![,]>()?;
                                 }
                             }
+                            appliers = Some(entries);
                         }
-                        _ => {
-                            return Err(syn::Error::new_spanned(
-                                section_name,
-                                "Expected 'state', 'commands', 'events', or 'service'",
-                            ));
-                        }
+                        _ => unreachable!("section names are checked before parsing"),
                     }
                 }
             }
@@ -264,15 +476,407 @@ impl Parse for AggregateDefinition {
             }
         }
 
-        Ok(AggregateDefinition {
+        ensure_unique("command", commands.iter().map(|cmd| &cmd.name))?;
+        ensure_unique("event", events.iter().map(|evt| &evt.name))?;
+
+        // Entity routing is part of the generated `apply`, and replaces an entry of its own
+        for evt in &events {
+            let Some(route) = &evt.entity else { continue };
+            let Some(appliers) = &appliers else {
+                return Err(syn::Error::new_spanned(
+                    &route.field,
+                    "#[entity] routing is generated in `apply`; add an `apply` section",
+                ));
+            };
+            if let Some(applier) = appliers.iter().find(|a| a.event == evt.name) {
+                return Err(syn::Error::new_spanned(
+                    &applier.event,
+                    format!(
+                        "'{}' is routed to an entity and can't have an apply handler",
+                        evt.name
+                    ),
+                ));
+            }
+        }
+
+        // Every `apply` entry names a declared event, once
+        for (i, applier) in appliers.iter().flatten().enumerate() {
+            if !events.iter().any(|evt| evt.name == applier.event) {
+                return Err(syn::Error::new_spanned(
+                    &applier.event,
+                    format!("'{}' is not one of the declared events", applier.event),
+                ));
+            }
+            if appliers
+                .iter()
+                .flatten()
+                .take(i)
+                .any(|other| other.event == applier.event)
+            {
+                return Err(syn::Error::new_spanned(
+                    &applier.event,
+                    format!("'{}' already has an apply handler", applier.event),
+                ));
+            }
+        }
+
+        let definition = AggregateDefinition {
+            attrs,
             name,
             generics,
             namespace,
             state_fields,
+            state_variants,
+            state_enum_attrs,
+            transient_state,
             commands,
+            command_attrs,
             events,
+            event_attrs,
             base_service_traits,
+            service_attrs,
             service_functions,
-        })
+            error,
+            appliers,
+        };
+
+        // Creation commands are reported by the generated `Aggregate` impl
+        if let (Some(attr), false) = (&create_attr, definition.generates_handler()) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "#[create] needs the generated `Aggregate` impl: add an `error:` section or inline handlers",
+            ));
+        }
+
+        Ok(definition)
     }
 }
+
+const SECTIONS: [&str; 7] = [
+    "state",
+    "commands",
+    "events",
+    "apply",
+    "service",
+    "namespace",
+    "error",
+];
+
+/// Reject unknown section names (suggesting the closest known one) and repeated sections.
+fn check_section(name: &Ident, seen: &[Ident]) -> syn::Result<()> {
+    let name_str = name.to_string();
+    if !SECTIONS.contains(&name_str.as_str()) {
+        let expected = SECTIONS.join("/");
+        let message = match closest_section(&name_str) {
+            Some(suggestion) => format!(
+                "unknown section '{name_str}', did you mean '{suggestion}'? expected one of {expected}"
+            ),
+            None => format!("unknown section '{name_str}', expected one of {expected}"),
+        };
+        return Err(syn::Error::new_spanned(name, message));
+    }
+    if seen.iter().any(|other| other == name) {
+        return Err(syn::Error::new_spanned(
+            name,
+            format!("the '{name_str}' section is given more than once"),
+        ));
+    }
+    Ok(())
+}
+
+/// The known section within two edits of `name`, if any: `event` → `events`.
+fn closest_section(name: &str) -> Option<&'static str> {
+    SECTIONS
+        .iter()
+        .map(|section| (edit_distance(name, section), *section))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, section)| section)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Commands and events become enum variants, so a name may be declared only once.
+fn ensure_unique<'a>(kind: &str, names: impl Iterator<Item = &'a Ident>) -> syn::Result<()> {
+    let mut seen = HashSet::new();
+    for name in names {
+        if !seen.insert(name) {
+            return Err(syn::Error::new_spanned(
+                name,
+                format!("{kind} '{name}' is declared more than once"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Parse `#[attr] name: Type` fields separated by commas, keeping their attributes.
+fn parse_fields(input: ParseStream, vis: syn::Visibility) -> syn::Result<Vec<Field>> {
+    let mut fields = Vec::new();
+    while !input.is_empty() {
+        let attrs = input.call(Attribute::parse_outer)?;
+        if input.peek(Token![,]) {
+            return Err(input.error("unexpected `,`, expected a field name"));
+        }
+        let field_name: Ident = input.parse()?;
+        if !input.peek(Token![:]) {
+            return Err(syn::Error::new_spanned(
+                &field_name,
+                format!(
+                    "expected `: Type` after field '{field_name}', e.g. `{field_name}: String`"
+                ),
+            ));
+        }
+        input.parse::<Token![:]>()?;
+        let field_type: Type = input.parse()?;
+
+        fields.push(Field {
+            attrs,
+            vis: vis.clone(),
+            mutability: syn::FieldMutability::None,
+            ident: Some(field_name.clone()),
+            colon_token: Some(Token![:](proc_macro2::Span::call_site())),
+            ty: field_type,
+        });
+
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        } else if !input.is_empty() {
+            return Err(input.error(format!("expected `,` after field '{field_name}'")));
+        }
+    }
+    Ok(fields)
+}
+
+/// Parse a command or event variant: its name and optional `{ field: Type, ... }` block.
+fn parse_variant(input: ParseStream) -> syn::Result<(Ident, Vec<Field>)> {
+    if input.peek(Token![,]) {
+        return Err(input.error("unexpected `,`, expected a variant name"));
+    }
+    let variant_name: Ident = input.parse()?;
+    if input.peek(syn::token::Paren) {
+        return Err(input.error(format!(
+            "tuple variants are not supported, name the fields: `{variant_name} {{ value: Type }}`"
+        )));
+    }
+
+    let variant_fields = if input.peek(Brace) {
+        let fields_content;
+        syn::braced!(fields_content in input);
+        parse_fields(&fields_content, syn::Visibility::Inherited)?
+    } else {
+        Vec::new()
+    };
+
+    Ok((variant_name, variant_fields))
+}
+
+/// Parse a service signature: `#[attr] async fn name<'a>(args) -> Output`.
+fn parse_service_function(input: ParseStream) -> syn::Result<ServiceFunction> {
+    let attrs = input.call(Attribute::parse_outer)?;
+
+    // Parse optional "async"
+    let is_async = input.peek(Token![async]);
+    if is_async {
+        input.parse::<Token![async]>()?;
+    }
+
+    // Parse "fn"
+    input.parse::<Token![fn]>()?;
+
+    // Parse function name
+    let fn_name: Ident = input.parse()?;
+
+    // Parse optional lifetime parameters
+    let lifetimes = if input.peek(Token![<]) {
+        input.parse::<Token![<]>()?;
+        let lifetimes_content: syn::punctuated::Punctuated<syn::LifetimeParam, Token![,]> =
+            syn::punctuated::Punctuated::parse_separated_nonempty(input)?;
+        input.parse::<Token![>]>()?;
+        lifetimes_content.into_iter().collect()
+    } else {
+        Vec::new()
+    };
+
+    // Parse function parameters
+    let inputs_content;
+    syn::parenthesized!(inputs_content in input);
+    let inputs: syn::punctuated::Punctuated<FnArg, Token![,]> =
+        inputs_content.parse_terminated(FnArg::parse, Token![,])?;
+
+    // Parse return type
+    let output: ReturnType = input.parse()?;
+
+    Ok(ServiceFunction {
+        attrs,
+        name: fn_name,
+        lifetimes,
+        inputs: inputs.into_iter().collect(),
+        output,
+        is_async,
+    })
+}
+
+/// Collects which of the aggregate's generic parameters (type, const and lifetime) a piece of
+/// syntax mentions.
+struct GenericUsage<'a> {
+    generics: &'a syn::Generics,
+    found: HashSet<Ident>,
+}
+
+impl<'a> GenericUsage<'a> {
+    fn new(generics: &'a syn::Generics) -> Self {
+        GenericUsage {
+            generics,
+            found: HashSet::new(),
+        }
+    }
+
+    fn declares(&self, ident: &Ident) -> bool {
+        self.generics
+            .params
+            .iter()
+            .any(|param| param_ident(param) == ident)
+    }
+}
+
+impl<'ast> Visit<'ast> for GenericUsage<'_> {
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        // The first segment catches both plain `T` and associated types like `A::StreamId`
+        if let Some(first_segment) = path.segments.first() {
+            if self.declares(&first_segment.ident) {
+                self.found.insert(first_segment.ident.clone());
+            }
+        }
+        // Continue visiting nested types (e.g., Vec<T>, Option<T>)
+        syn::visit::visit_path(self, path);
+    }
+
+    fn visit_lifetime(&mut self, lifetime: &'ast syn::Lifetime) {
+        if self.declares(&lifetime.ident) {
+            self.found.insert(lifetime.ident.clone());
+        }
+    }
+}
+
+fn param_ident(param: &GenericParam) -> &Ident {
+    match param {
+        GenericParam::Type(type_param) => &type_param.ident,
+        GenericParam::Lifetime(lifetime) => &lifetime.lifetime.ident,
+        GenericParam::Const(const_param) => &const_param.ident,
+    }
+}
+
+/// Narrow `generics` to the parameters `fields` use, dropping the where-clause bounds that
+/// mention any other.
+///
+/// Generated commands and events declare just the parameters they need: an unused one is a
+/// compile error on an enum. `V: PartialEq + 'a` thus becomes `V: PartialEq` when `'a` isn't
+/// used.
+pub fn generics_used_by<'f>(
+    generics: &syn::Generics,
+    fields: impl IntoIterator<Item = &'f Field>,
+) -> syn::Generics {
+    let mut usage = GenericUsage::new(generics);
+    for field in fields {
+        usage.visit_field(field);
+    }
+    let used = usage.found;
+
+    let only_used = |visit: &dyn Fn(&mut GenericUsage)| {
+        let mut usage = GenericUsage::new(generics);
+        visit(&mut usage);
+        usage.found.is_subset(&used)
+    };
+
+    let mut narrowed = generics.clone();
+    narrowed.params = generics
+        .params
+        .iter()
+        .filter(|param| used.contains(param_ident(param)))
+        .cloned()
+        .collect();
+
+    if let Some(where_clause) = &mut narrowed.where_clause {
+        where_clause.predicates = std::mem::take(&mut where_clause.predicates)
+            .into_iter()
+            .filter_map(|predicate| match predicate {
+                WherePredicate::Type(mut predicate) => {
+                    let lifetimes = &predicate.lifetimes;
+                    let bounded_ty = &predicate.bounded_ty;
+                    if !only_used(&|usage| {
+                        if let Some(lifetimes) = lifetimes {
+                            usage.visit_bound_lifetimes(lifetimes);
+                        }
+                        usage.visit_type(bounded_ty);
+                    }) {
+                        return None;
+                    }
+                    predicate.bounds = std::mem::take(&mut predicate.bounds)
+                        .into_iter()
+                        .filter(|bound| only_used(&|usage| usage.visit_type_param_bound(bound)))
+                        .collect();
+                    (!predicate.bounds.is_empty()).then_some(WherePredicate::Type(predicate))
+                }
+                WherePredicate::Lifetime(mut predicate) => {
+                    if !used.contains(&predicate.lifetime.ident) {
+                        return None;
+                    }
+                    predicate.bounds = std::mem::take(&mut predicate.bounds)
+                        .into_iter()
+                        .filter(|bound| used.contains(&bound.ident))
+                        .collect();
+                    (!predicate.bounds.is_empty()).then_some(WherePredicate::Lifetime(predicate))
+                }
+                predicate => Some(predicate),
+            })
+            .collect();
+        if where_clause.predicates.is_empty() {
+            narrowed.where_clause = None;
+        }
+    }
+
+    narrowed
+}
+
+/// The local struct an inline handler's argument is bound to, mirroring a variant's fields
+/// (`DepositCommand { amount }`), and the expression building it from the bound fields.
+///
+/// It is generic over the aggregate's parameters those fields use.
+pub fn handler_args(
+    args_name: &Ident,
+    fields: &[Field],
+    generics: &syn::Generics,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let mut local_generics = generics_used_by(generics, fields);
+    local_generics.where_clause = None;
+    let local_params = &local_generics.params;
+
+    let field_names: Vec<_> = fields.iter().map(|f| &f.ident).collect();
+    let args_fields = fields.iter().map(|f| {
+        let ident = &f.ident;
+        let ty = &f.ty;
+        quote::quote! { pub #ident: #ty }
+    });
+
+    (
+        quote::quote! {
+            #[allow(dead_code)]
+            struct #args_name <#local_params> { #(#args_fields),* }
+        },
+        quote::quote! { #args_name { #(#field_names),* } },
+    )
+}
@@ -0,0 +1,560 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Attribute, Data, DeriveInput, Field, Fields, LitInt, LitStr};
+
+/// Casing applied to variant (or struct) names by `#[event(rename_all = "...")]`.
+#[derive(Clone, Copy)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+    Dot,
+}
+
+impl RenameRule {
+    fn parse(lit: &LitStr) -> syn::Result<Self> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "snake_case" => RenameRule::Snake,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            "dot.case" => RenameRule::Dot,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    lit,
+                    "unknown rename rule, expected one of \"lowercase\", \"UPPERCASE\", \
+                     \"PascalCase\", \"camelCase\", \"snake_case\", \"SCREAMING_SNAKE_CASE\", \
+                     \"kebab-case\", \"SCREAMING-KEBAB-CASE\", \"dot.case\"",
+                ))
+            }
+        })
+    }
+
+    /// Apply the rule to a PascalCase Rust identifier, keeping acronyms as one word:
+    /// `HTTPRequestSent` in snake_case is `http_request_sent`.
+    fn apply(self, ident: &str) -> String {
+        let words = split_words(ident);
+        let joined = |sep: &str, upper: bool| {
+            let words: Vec<String> = words
+                .iter()
+                .map(|w| {
+                    if upper {
+                        w.to_uppercase()
+                    } else {
+                        w.to_lowercase()
+                    }
+                })
+                .collect();
+            words.join(sep)
+        };
+
+        match self {
+            RenameRule::Lower => ident.to_lowercase(),
+            RenameRule::Upper => ident.to_uppercase(),
+            RenameRule::Pascal => ident.to_string(),
+            RenameRule::Camel => {
+                let mut chars = ident.chars();
+                match chars.next() {
+                    Some(first) => first.to_lowercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            }
+            RenameRule::Snake => joined("_", false),
+            RenameRule::ScreamingSnake => joined("_", true),
+            RenameRule::Kebab => joined("-", false),
+            RenameRule::ScreamingKebab => joined("-", true),
+            RenameRule::Dot => joined(".", false),
+        }
+    }
+}
+
+/// Split a PascalCase identifier into words: `HTTPRequestSent` → `HTTP`, `Request`, `Sent`.
+fn split_words(ident: &str) -> Vec<String> {
+    let chars: Vec<char> = ident.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for (i, &ch) in chars.iter().enumerate() {
+        if ch == '_' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if ch.is_uppercase() && !current.is_empty() {
+            let prev_is_lower = chars[i - 1].is_lowercase() || chars[i - 1].is_ascii_digit();
+            let next_is_lower = i + 1 < chars.len() && chars[i + 1].is_lowercase();
+            if prev_is_lower || next_is_lower {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(ch);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// `#[event(...)]` options on the container (enum or struct).
+#[derive(Default)]
+struct ContainerOptions {
+    rename: Option<LitStr>,
+    rename_all: Option<RenameRule>,
+    /// Schema version of every variant without its own.
+    version: Option<LitInt>,
+    /// Every variant (or the struct) delegates to the event it wraps.
+    transparent: Option<syn::Path>,
+    /// The struct ends its stream.
+    tombstone: Option<syn::Path>,
+}
+
+/// `#[event(...)]` options on an enum variant.
+#[derive(Default)]
+struct VariantOptions {
+    rename: Option<LitStr>,
+    version: Option<LitInt>,
+    /// The variant delegates to the event it wraps.
+    transparent: Option<syn::Path>,
+    /// The variant ends its stream.
+    tombstone: Option<syn::Path>,
+}
+
+fn parse_container_options(attrs: &[Attribute]) -> syn::Result<ContainerOptions> {
+    let mut options = ContainerOptions::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("event")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                options.rename = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("rename_all") {
+                options.rename_all = Some(RenameRule::parse(&meta.value()?.parse()?)?);
+                Ok(())
+            } else if meta.path.is_ident("version") {
+                options.version = Some(parse_version(&meta)?);
+                Ok(())
+            } else if meta.path.is_ident("transparent") {
+                options.transparent = Some(meta.path);
+                Ok(())
+            } else if meta.path.is_ident("tombstone") {
+                options.tombstone = Some(meta.path);
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "unsupported event attribute, expected `rename`, `rename_all`, `version`, `transparent` or `tombstone`",
+                ))
+            }
+        })?;
+    }
+    Ok(options)
+}
+
+fn parse_variant_options(attrs: &[Attribute]) -> syn::Result<VariantOptions> {
+    let mut options = VariantOptions::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("event")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                options.rename = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("version") {
+                options.version = Some(parse_version(&meta)?);
+                Ok(())
+            } else if meta.path.is_ident("transparent") {
+                options.transparent = Some(meta.path);
+                Ok(())
+            } else if meta.path.is_ident("tombstone") {
+                options.tombstone = Some(meta.path);
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "unsupported event variant attribute, expected `rename`, `version`, `transparent` or `tombstone`",
+                ))
+            }
+        })?;
+    }
+    Ok(options)
+}
+
+/// `version = N`: a `u32` of at least 1, the default every event starts at.
+fn parse_version(meta: &syn::meta::ParseNestedMeta) -> syn::Result<LitInt> {
+    let version: LitInt = meta.value()?.parse()?;
+    if version.base10_parse::<u32>()? == 0 {
+        return Err(syn::Error::new_spanned(
+            version,
+            "event versions start at 1",
+        ));
+    }
+    Ok(version)
+}
+
+/// A transparent variant or struct wraps exactly one field: the event it delegates to.
+fn ensure_newtype(fields: &Fields, transparent: &syn::Path) -> syn::Result<()> {
+    if let Some(field) = fields.iter().find(|field| is_pii(field)) {
+        return Err(syn::Error::new_spanned(
+            field,
+            "a transparent event can't be `#[pii]`; mark the fields of the wrapped event",
+        ));
+    }
+    match fields {
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => Ok(()),
+        _ => Err(syn::Error::new_spanned(
+            transparent,
+            "`transparent` requires exactly one unnamed field wrapping another event, e.g. `Banking(BankEvent)`",
+        )),
+    }
+}
+
+/// The stored type string: an explicit `rename`, else the name under `rename_all`, else the
+/// name as written.
+fn type_string(ident: &syn::Ident, rename: Option<LitStr>, rule: Option<RenameRule>) -> String {
+    match (rename, rule) {
+        (Some(rename), _) => rename.value(),
+        (None, Some(rule)) => rule.apply(&ident.to_string()),
+        (None, None) => ident.to_string(),
+    }
+}
+
+/// Whether a field is marked `#[pii]`.
+pub fn is_pii(field: &Field) -> bool {
+    field.attrs.iter().any(|attr| attr.path().is_ident("pii"))
+}
+
+/// Whether any field of the enum or struct is marked `#[pii]`; `define_aggregate!` and
+/// `#[events]` then leave `Debug` to the derive.
+pub fn has_pii<'a>(mut fields: impl Iterator<Item = &'a Field>) -> bool {
+    fields.any(is_pii)
+}
+
+fn all_fields(data: &Data) -> Vec<&Field> {
+    match data {
+        Data::Enum(data_enum) => data_enum
+            .variants
+            .iter()
+            .flat_map(|variant| &variant.fields)
+            .collect(),
+        Data::Struct(data_struct) => data_struct.fields.iter().collect(),
+        Data::Union(_) => Vec::new(),
+    }
+}
+
+/// `Debug` for an event with `#[pii]` fields, printing `[REDACTED]` in their place.
+fn redacting_debug(input: &DeriveInput) -> TokenStream {
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    let type_params: Vec<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let where_clause = generics.make_where_clause();
+    for param in type_params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#param: ::core::fmt::Debug));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Bind every field to `__field_N` and format it, or `[REDACTED]` for `#[pii]` fields
+    let format_fields = |label: String, fields: &Fields| {
+        let bindings: Vec<_> = (0..fields.len())
+            .map(|i| quote::format_ident!("__field_{}", i))
+            .collect();
+        let values = fields.iter().zip(&bindings).map(|(field, binding)| {
+            if is_pii(field) {
+                quote! { &::core::format_args!("[REDACTED]") }
+            } else {
+                quote! { #binding }
+            }
+        });
+        match fields {
+            Fields::Named(named) => {
+                let idents: Vec<_> = named.named.iter().map(|f| f.ident.as_ref()).collect();
+                let names = idents.iter().map(|ident| ident.map(|i| i.to_string()));
+                (
+                    quote! { { #(#idents: #bindings),* } },
+                    quote! {
+                        f.debug_struct(#label)
+                            #(.field(#names, #values))*
+                            .finish()
+                    },
+                )
+            }
+            Fields::Unnamed(_) => (
+                quote! { ( #(#bindings),* ) },
+                quote! {
+                    f.debug_tuple(#label)
+                        #(.field(#values))*
+                        .finish()
+                },
+            ),
+            Fields::Unit => (quote! {}, quote! { f.write_str(#label) }),
+        }
+    };
+
+    let body = match &input.data {
+        Data::Enum(data_enum) => {
+            let arms = data_enum.variants.iter().map(|variant| {
+                let variant_name = &variant.ident;
+                let (pattern, format) = format_fields(variant_name.to_string(), &variant.fields);
+                quote! { #name::#variant_name #pattern => #format, }
+            });
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Struct(data_struct) => {
+            let (pattern, format) = format_fields(name.to_string(), &data_struct.fields);
+            quote! {
+                let #name #pattern = self;
+                #format
+            }
+        }
+        Data::Union(_) => unreachable!("unions are rejected before"),
+    };
+
+    quote! {
+        impl #impl_generics ::core::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+    }
+}
+
+pub fn derive_event(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let container = parse_container_options(&input.attrs)?;
+
+    // `event_version` is only overridden when a version or a transparent delegate asks for it
+    let mut overrides_version = container.version.is_some();
+    // and `is_tombstone` when a tombstone or a transparent delegate does
+    let mut overrides_tombstone = container.tombstone.is_some();
+
+    // Every type string with its version, for schema and contract generation
+    let mut type_entries = Vec::new();
+    // Events wrapped by transparent variants (or the struct), whose `#[pii]` paths are nested
+    let mut wrapped = Vec::new();
+
+    let (event_type_body, event_version_body, is_tombstone_body) = match &input.data {
+        Data::Enum(data_enum) => {
+            if let Some(rename) = &container.rename {
+                return Err(syn::Error::new_spanned(
+                    rename,
+                    "`rename` on an enum is not supported; rename its variants or use `rename_all`",
+                ));
+            }
+            if let Some(tombstone) = &container.tombstone {
+                return Err(syn::Error::new_spanned(
+                    tombstone,
+                    "`tombstone` on an enum is not supported; mark the variants that end the stream",
+                ));
+            }
+
+            let mut type_arms = Vec::new();
+            let mut version_arms = Vec::new();
+            let mut tombstone_arms = Vec::new();
+            for variant in &data_enum.variants {
+                let variant_name = &variant.ident;
+                let options = parse_variant_options(&variant.attrs)?;
+
+                if let Some(transparent) = options
+                    .transparent
+                    .as_ref()
+                    .or(container.transparent.as_ref())
+                {
+                    ensure_newtype(&variant.fields, transparent)?;
+                    if let Some(rename) = &options.rename {
+                        return Err(syn::Error::new_spanned(
+                            rename,
+                            "a transparent variant takes its type from the wrapped event and cannot be renamed",
+                        ));
+                    }
+                    if let Some(version) = &options.version {
+                        return Err(syn::Error::new_spanned(
+                            version,
+                            "a transparent variant takes its version from the wrapped event",
+                        ));
+                    }
+                    if let Some(tombstone) = &options.tombstone {
+                        return Err(syn::Error::new_spanned(
+                            tombstone,
+                            "a transparent variant is a tombstone when the wrapped event is",
+                        ));
+                    }
+                    overrides_version = true;
+                    overrides_tombstone = true;
+                    let inner = &variant.fields.iter().next().expect("checked newtype").ty;
+                    wrapped.push((Some(variant_name), inner));
+                    type_entries.push(quote! {
+                        types.extend(<#inner as replay::Event>::event_types());
+                    });
+                    type_arms.push(quote! {
+                        #name::#variant_name(inner) => replay::Event::event_type(inner),
+                    });
+                    version_arms.push(quote! {
+                        #name::#variant_name(inner) => replay::Event::event_version(inner),
+                    });
+                    tombstone_arms.push(quote! {
+                        #name::#variant_name(inner) => replay::Event::is_tombstone(inner),
+                    });
+                    continue;
+                }
+
+                overrides_version |= options.version.is_some();
+                overrides_tombstone |= options.tombstone.is_some();
+                let tombstone = options.tombstone.is_some();
+                let version = options
+                    .version
+                    .as_ref()
+                    .or(container.version.as_ref())
+                    .map(|version| quote! { #version })
+                    .unwrap_or_else(|| quote! { 1 });
+                let variant_str = type_string(variant_name, options.rename, container.rename_all);
+                type_entries.push(quote! {
+                    types.push((#variant_str, #version));
+                });
+                type_arms.push(quote! {
+                    #name::#variant_name { .. } => #variant_str,
+                });
+                version_arms.push(quote! {
+                    #name::#variant_name { .. } => #version,
+                });
+                tombstone_arms.push(quote! {
+                    #name::#variant_name { .. } => #tombstone,
+                });
+            }
+
+            (
+                quote! {
+                    match self {
+                        #(#type_arms)*
+                    }
+                },
+                quote! {
+                    match self {
+                        #(#version_arms)*
+                    }
+                },
+                quote! {
+                    match self {
+                        #(#tombstone_arms)*
+                    }
+                },
+            )
+        }
+        Data::Struct(data_struct) => match &container.transparent {
+            Some(transparent) => {
+                ensure_newtype(&data_struct.fields, transparent)?;
+                if container.rename.is_some() || container.rename_all.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        transparent,
+                        "a transparent event takes its type from the wrapped event and cannot be renamed",
+                    ));
+                }
+                if let Some(version) = &container.version {
+                    return Err(syn::Error::new_spanned(
+                        version,
+                        "a transparent event takes its version from the wrapped event",
+                    ));
+                }
+                if let Some(tombstone) = &container.tombstone {
+                    return Err(syn::Error::new_spanned(
+                        tombstone,
+                        "a transparent event is a tombstone when the wrapped event is",
+                    ));
+                }
+                overrides_version = true;
+                overrides_tombstone = true;
+                let inner = &data_struct
+                    .fields
+                    .iter()
+                    .next()
+                    .expect("checked newtype")
+                    .ty;
+                wrapped.push((None, inner));
+                type_entries.push(quote! {
+                    types.extend(<#inner as replay::Event>::event_types());
+                });
+                (
+                    quote! { replay::Event::event_type(&self.0) },
+                    quote! { replay::Event::event_version(&self.0) },
+                    quote! { replay::Event::is_tombstone(&self.0) },
+                )
+            }
+            // if it's an struct use the struct name
+            None => {
+                let struct_str = type_string(name, container.rename, container.rename_all);
+                let version = &container.version;
+                let listed_version = version
+                    .as_ref()
+                    .map(|version| quote! { #version })
+                    .unwrap_or_else(|| quote! { 1 });
+                type_entries.push(quote! {
+                    types.push((#struct_str, #listed_version));
+                });
+                (quote! { #struct_str }, quote! { #version }, quote! { true })
+            }
+        },
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "Event can only be derived for enums or structs",
+            ))
+        }
+    };
+
+    let event_version_fn = overrides_version.then(|| {
+        quote! {
+            fn event_version(&self) -> u32 {
+                #event_version_body
+            }
+        }
+    });
+    let is_tombstone_fn = overrides_tombstone.then(|| {
+        quote! {
+            fn is_tombstone(&self) -> bool {
+                #is_tombstone_body
+            }
+        }
+    });
+
+    // `#[pii]` fields are located in the payload for encryption and redacted from `Debug`
+    let pii_paths_fn = crate::pii_paths::pii_paths_fn(&input, &wrapped)?;
+    let debug_impl = all_fields(&input.data)
+        .into_iter()
+        .any(is_pii)
+        .then(|| redacting_debug(&input));
+
+    Ok(quote! {
+        impl #impl_generics replay::Event for #name #ty_generics #where_clause {
+            fn event_type(&self) -> &'static str {
+                #event_type_body
+            }
+
+            #event_version_fn
+
+            #is_tombstone_fn
+
+            #pii_paths_fn
+
+            fn event_types() -> ::std::vec::Vec<(&'static str, u32)> {
+                let mut types = ::std::vec::Vec::new();
+                #(#type_entries)*
+                types
+            }
+        }
+
+        #debug_impl
+    })
+}
@@ -1,6 +1,6 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Fields, Type};
+use syn::{parse_macro_input, DeriveInput, Type};
 
 /// Convert a CamelCase identifier to kebab-case at compile time.
 /// A trailing `Urn` suffix is stripped first.
@@ -17,123 +17,226 @@ fn camel_to_kebab(s: &str) -> String {
     result
 }
 
-mod define_aggregate_macro;
-mod merge_events_macro;
+/// Default URN namespace of an aggregate: its name in kebab-case, keeping acronyms together.
+/// `BankAccount` → `"bank-account"`, `HTTPConnection` → `"http-connection"`.
+fn aggregate_namespace(name: &str) -> String {
+    let mut result = String::new();
+    let chars: Vec<char> = name.chars().collect();
+
+    for i in 0..chars.len() {
+        let ch = chars[i];
+
+        if ch.is_uppercase() {
+            // Add hyphen before uppercase if:
+            // 1. Not at the start (i > 0)
+            // 2. AND one of:
+            //    a. Previous char is lowercase (e.g., "myHTTP" -> "my-HTTP")
+            //    b. Next char exists and is lowercase (end of acronym: "HTTPConnection" -> "HTTP-Connection")
+            if i > 0 {
+                let prev_is_lower = chars[i - 1].is_lowercase();
+                let next_is_lower = i + 1 < chars.len() && chars[i + 1].is_lowercase();
+
+                if prev_is_lower || next_is_lower {
+                    result.push('-');
+                }
+            }
+        }
 
-use define_aggregate_macro::AggregateDefinition;
-use merge_events_macro::QueryEventsDefinition;
+        result.push(ch.to_ascii_lowercase());
+    }
+    result
+}
 
-#[proc_macro_derive(Event)]
-pub fn derive_event(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
-    let name = &input.ident;
-    let generics = &input.generics;
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
-
-    let event_type_impl = match input.data {
-        Data::Enum(data_enum) => {
-            let match_arms = data_enum.variants.iter().map(|variant| {
-                let variant_name = &variant.ident;
-                let variant_str = variant_name.to_string();
-                match &variant.fields {
-                    Fields::Named(_) | Fields::Unnamed(_) | Fields::Unit => {
-                        quote! {
-                            #name::#variant_name { .. } => #variant_str.to_string(),
-                        }
-                    }
-                }
-            });
+/// `#[doc = ...]` for a generated item whose attributes carry no doc comment of their own.
+fn default_doc(attrs: &[syn::Attribute], doc: String) -> Option<proc_macro2::TokenStream> {
+    let documented = attrs.iter().any(|attr| attr.path().is_ident("doc"));
+    (!documented).then(|| quote! { #[doc = #doc] })
+}
 
-            quote! {
-                impl #impl_generics replay::Event for #name #ty_generics #where_clause {
-                    fn event_type(&self) -> String {
-                        match self {
-                            #(#match_arms)*
-                        }
-                    }
-                }
+/// Doc comment of the URN type generated for an aggregate.
+fn urn_doc(aggregate: &syn::Ident, namespace: &str) -> String {
+    format!("Id of a [`{aggregate}`] stream: a URN in the `{namespace}` namespace.")
+}
+
+/// Serialize an aggregate URN as its string form; deserializing validates the namespace.
+fn urn_serde_impl(urn_name: &syn::Ident, namespace: &syn::LitStr) -> proc_macro2::TokenStream {
+    quote! {
+        impl serde::Serialize for #urn_name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.serialize_str(&self.0.to_string())
             }
         }
-        // if it's an struct use the struct name
-        Data::Struct(_data_struct) => {
-            quote! {
-                impl #impl_generics replay::Event for #name #ty_generics #where_clause {
-                    fn event_type(&self) -> String {
-                        stringify!(#name).to_string()
-                    }
+
+        impl<'de> serde::Deserialize<'de> for #urn_name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                use std::str::FromStr;
+                let s = String::deserialize(deserializer)?;
+                let urn = urn::Urn::from_str(&s)
+                    .map_err(|e| serde::de::Error::custom(format!("Invalid URN: {}", e)))?;
+
+                if urn.nid() != #namespace {
+                    return Err(serde::de::Error::custom(
+                        format!("Invalid URN namespace: expected '{}', got '{}'", #namespace, urn.nid())
+                    ));
                 }
+
+                Ok(Self(urn))
             }
         }
-        _ => panic!("Event can only be derived for enums or structs"),
-    };
-
-    TokenStream::from(event_type_impl)
+    }
 }
 
-/*
-Derive `Display`, `From<type> for Urn` and `FromStr` for a type encapsulating a Urn.
+mod aggregate_attribute;
+mod arbitrary_derive;
+mod command_builder;
+mod define_aggregate_macro;
+mod event_derive;
+mod merge_events_macro;
+mod pii_paths;
+mod stream_id_derive;
+mod stream_type_derive;
 
-Example of implementation that need to be derived
+use define_aggregate_macro::{generics_used_by, handler_args, AggregateDefinition};
+use merge_events_macro::{QueryEventsDefinition, Representation};
 
-    #[derive(SerializeDisplay, DeserializeFromStr)]
-    pub struct BankAccountUrn(Urn);
+/// Derive [`replay::Event`], using the variant name (or, for a struct, the type name) as the
+/// stored event type.
+///
+/// `#[event(rename = "...")]` on a variant or struct sets its type string explicitly, and
+/// `#[event(rename_all = "...")]` on the container recases every name; rules follow serde
+/// (`"snake_case"`, `"kebab-case"`, `"camelCase"`, ...) plus `"dot.case"`.
+///
+/// `#[event(transparent)]` on a newtype variant (or on the container, for all of them) takes
+/// the type string from the wrapped event instead.
+///
+/// `#[event(version = N)]` on a variant or the container overrides `Event::event_version`
+/// (default `1`); transparent variants report their wrapped event's version.
+///
+/// `#[event(tombstone)]` on a variant, or on a struct, makes `Event::is_tombstone` true for
+/// it: the event ends its stream. Transparent variants are tombstones when the wrapped
+/// event is.
+///
+/// `#[pii]` on a field keeps it out of logs: the derive then implements `Debug` itself,
+/// printing `[REDACTED]` for the field, so drop `Debug` from the derive list.
+/// `Event::pii_paths` lists where `#[pii]` fields land in the serialized payload, following
+/// `#[serde]` renames and tagging, for field-level encryption.
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Event)]
+/// #[event(rename_all = "dot.case")]
+/// enum AccountEvent {
+///     AccountOpened { owner: String },              // "account.opened"
+///     #[event(rename = "account.closed.v2")]
+///     Closed,                                       // "account.closed.v2"
+/// }
+/// ```
+#[proc_macro_derive(Event, attributes(event, pii))]
+pub fn derive_event(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    event_derive::derive_event(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    impl From<BankAccountUrn> for Urn {
-        fn from(urn: BankAccountUrn) -> Self {
-            urn.0
-        }
-    }
+/// Derive `proptest`'s `Arbitrary` for property-based tests.
+///
+/// Every field is generated with `any::<T>()`; `#[arbitrary(strategy = expr)]` on a field uses
+/// another strategy (for types proptest can't generate, such as URNs), and
+/// `#[arbitrary(skip)]` on a variant never generates it. The impl only exists when `replay` is
+/// built with its `proptest` feature, so the derive can stay on production types.
+///
+/// Types deriving [`Urn`](derive@Urn) (including the URN types the aggregate macros generate)
+/// get an `Arbitrary` impl of their own, with random UUID ids. Add the derive to the command
+/// and event enums that property tests generate; commands also need `Debug`:
+///
+/// ```ignore
+/// #[commands]
+/// #[derive(Debug, replay_macros::Arbitrary)]
+/// pub enum BankAccountCommand {
+///     Deposit {
+///         #[arbitrary(strategy = 1..1_000i64)]
+///         amount: i64,
+///     },
+/// }
+/// ```
+#[proc_macro_derive(Arbitrary, attributes(arbitrary))]
+pub fn derive_arbitrary(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    arbitrary_derive::derive_arbitrary(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    impl Display for BankAccountUrn {
-        fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-            write!(f, "{}", self.0)
+/// Derive a strongly-typed stream id for a newtype around `urn::Urn`.
+///
+/// Besides `Display`, `FromStr`, `From<MyUrn> for Urn`, equality and hashing, the type gets
+/// namespace-checked constructors (`new`, `parse`, `TryFrom<Urn>`), a time-ordered
+/// `new_random` (UUID v7) and the `namespace`, `nid`, `nss` and `to_urn` helpers: the same
+/// API `define_aggregate!` generates for its URN type.
+///
+/// `#[urn(namespace = "...")]` sets the namespace; it defaults to the type name without its
+/// `Urn` suffix in kebab-case.
+///
+/// ```ignore
+/// #[derive(Clone, Debug, Urn)]
+/// #[urn(namespace = "bank-account")]
+/// pub struct AccountUrn(urn::Urn);
+///
+/// let id = AccountUrn::new("acct-1")?; // urn:bank-account:acct-1
+/// ```
+/// Check that `#[derive(Urn)]` is on a single-field tuple struct and read its optional
+/// `#[urn(namespace = "...")]`.
+fn urn_options(input: &DeriveInput) -> syn::Result<Option<syn::LitStr>> {
+    match &input.data {
+        syn::Data::Struct(data) if matches!(&data.fields, syn::Fields::Unnamed(f) if f.unnamed.len() == 1) => {}
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "Urn can only be derived for a newtype around `urn::Urn`, e.g. `struct AccountUrn(Urn);`",
+            ))
         }
     }
 
-    impl FromStr for BankAccountUrn {
-        type Err = urn::Error;
-
-        fn from_str(s: &str) -> Result<Self, Self::Err> {
-            Ok(Self(Urn::from_str(s)?))
-        }
+    let mut namespace = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("urn"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("namespace") {
+                let lit: syn::LitStr = meta.value()?.parse()?;
+                if lit.value().is_empty() {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        "the URN namespace cannot be empty",
+                    ));
+                }
+                namespace = Some(lit);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported urn attribute, expected `namespace`"))
+            }
+        })?;
     }
+    Ok(namespace)
+}
 
-Example of usage:
-
-#[derive(Urn, SerializeDisplay, DeserializeFromStr)]
-pub struct BankAccountUrn(Urn);
-
-*/
 #[proc_macro_derive(Urn, attributes(urn))]
 pub fn derive_urn(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
 
-    // Parse optional #[urn(namespace = "...")] attribute.
-    // When absent the namespace is derived from the type name (CamelCase → kebab-case).
-    let namespace: Option<syn::LitStr> = input
-        .attrs
-        .iter()
-        .find(|attr| attr.path().is_ident("urn"))
-        .and_then(|attr| {
-            attr.parse_args::<syn::MetaNameValue>()
-                .ok()
-                .and_then(|mnv| {
-                    if mnv.path.is_ident("namespace") {
-                        if let syn::Expr::Lit(syn::ExprLit {
-                            lit: syn::Lit::Str(s),
-                            ..
-                        }) = mnv.value
-                        {
-                            Some(s)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                })
-        });
+    let namespace = match urn_options(&input) {
+        Ok(namespace) => namespace,
+        Err(err) => return err.into_compile_error().into(),
+    };
 
     let ns_str = namespace.map(|lit| lit.value()).unwrap_or_else(|| {
         let type_name = name.to_string();
@@ -232,9 +335,11 @@ pub fn derive_urn(input: TokenStream) -> TokenStream {
                 }
             }
 
-            /// Build a URN with a random UUID v4 identifier. Infallible.
+            /// Build a URN with a random, time-ordered UUID v7 identifier. Infallible.
+            ///
+            /// Ids created later sort after earlier ones, keeping index inserts local.
             pub fn new_random() -> Self {
-                let id = uuid::Uuid::new_v4().to_string();
+                let id = uuid::Uuid::now_v7().to_string();
                 Self(
                     urn::UrnBuilder::new(#ns, &id)
                         .build()
@@ -279,14 +384,89 @@ pub fn derive_urn(input: TokenStream) -> TokenStream {
         }
     };
 
+    // UUID-based ids, so URN fields of `#[derive(Arbitrary)]` commands and events need no strategy
+    let arbitrary_impl = quote! {
+        replay::__proptest! {
+            impl replay::__private::proptest::arbitrary::Arbitrary for #name {
+                type Parameters = ();
+                type Strategy = replay::__private::proptest::strategy::BoxedStrategy<Self>;
+
+                fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                    use replay::__private::proptest::strategy::Strategy as _;
+                    replay::__private::proptest::arbitrary::any::<u128>()
+                        .prop_map(|n| {
+                            Self::new(uuid::Uuid::from_u128(n))
+                                .expect("UUID-based URN should always be valid")
+                        })
+                        .boxed()
+                }
+            }
+        }
+    };
+
     let urn_impl = quote! {
         #base_impl
         #namespace_impl
+        #arbitrary_impl
     };
 
     TokenStream::from(urn_impl)
 }
 
+/// Derive a stream id for a newtype around a UUID, string or other key.
+///
+/// Stores address streams by URN, so the derive files the key as the NSS of a fixed
+/// namespace (`OrderId(uuid)` is stored as `urn:order:<uuid>`), percent-encoding what a URN
+/// can't hold. The type gets `new`, which refuses an empty key, `From<OrderId> for Urn`,
+/// `TryFrom<Urn>` (checking the namespace and parsing the key back with `FromStr`), a
+/// `Display` of the bare key, serde impls for the bare key (deserializing through `new`) and
+/// `namespace()`; with `Clone`, `PartialEq` and `Debug` it is a `replay::StreamId`.
+///
+/// `#[stream_id(namespace = "...")]` sets the namespace; it defaults to the type name without
+/// its `Id` suffix in kebab-case. Either must be a valid URN NID, which is checked at compile
+/// time.
+///
+/// ```ignore
+/// #[derive(Clone, Debug, PartialEq, StreamId)]
+/// pub struct OrderId(Uuid);
+/// ```
+#[proc_macro_derive(StreamId, attributes(stream_id))]
+pub fn derive_stream_id(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    stream_id_derive::derive_stream_id(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive the stream type of a hand-written `EventStream` impl at compile time.
+///
+/// The type gets a `STREAM_TYPE` constant holding its name in kebab-case, the way
+/// `#[derive(Urn)]` names namespaces (`BankAccountStream` → `"bank-account-stream"`), for
+/// `stream_type()` to return. `#[stream_type(name = "...")]` sets it instead, e.g. to keep
+/// the stream type of stored events when renaming the type. Without a need for the constant,
+/// [`macro@event_stream`] on the impl fills in the same `stream_type()`.
+///
+/// ```ignore
+/// #[derive(StreamType)]
+/// struct BankAccountStream { id: BankAccountUrn, balance: f64 }
+///
+/// impl EventStream for BankAccountStream {
+///     type Event = BankAccountEvent;
+///
+///     fn stream_type() -> &'static str {
+///         Self::STREAM_TYPE
+///     }
+///     // ...
+/// }
+/// ```
+#[proc_macro_derive(StreamType, attributes(stream_type))]
+pub fn derive_stream_type(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    stream_type_derive::derive_stream_type(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
 #[proc_macro]
 pub fn define_aggregate(input: TokenStream) -> TokenStream {
     let aggregate_def = parse_macro_input!(input as AggregateDefinition);
@@ -328,102 +508,19 @@ pub fn define_aggregate(input: TokenStream) -> TokenStream {
         }
     }
 
-    // Analyze which type parameters are used in events using proper AST traversal
-    let type_param_idents: Vec<_> = generics
-        .params
-        .iter()
-        .filter_map(|param| {
-            if let syn::GenericParam::Type(type_param) = param {
-                Some(type_param.ident.clone())
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    // Visitor to detect type parameter usage in types
-    struct TypeParamVisitor<'a> {
-        type_params: &'a [syn::Ident],
-        found: std::collections::HashSet<syn::Ident>,
-    }
-
-    impl<'ast, 'a> syn::visit::Visit<'ast> for TypeParamVisitor<'a> {
-        fn visit_path(&mut self, path: &'ast syn::Path) {
-            // Check if the first segment of the path matches a type parameter
-            // This handles both simple cases (T) and associated types (A::StreamId)
-            if let Some(first_segment) = path.segments.first() {
-                for type_param in self.type_params {
-                    if first_segment.ident == *type_param {
-                        self.found.insert(type_param.clone());
-                        break;
-                    }
-                }
-            }
-            // Continue visiting nested types (e.g., Vec<T>, Option<T>)
-            syn::visit::visit_path(self, path);
-        }
-    }
-
-    // Check if a type is used in command fields
-    let mut used_in_commands = std::collections::HashSet::new();
-    for command in &aggregate_def.commands {
-        for field in &command.fields {
-            let mut visitor = TypeParamVisitor {
-                type_params: &type_param_idents,
-                found: std::collections::HashSet::new(),
-            };
-            syn::visit::visit_field(&mut visitor, field);
-            used_in_commands.extend(visitor.found);
-        }
-    }
-
-    // Check if a type is used in event fields
-    let mut used_in_events = std::collections::HashSet::new();
-    for event in &aggregate_def.events {
-        for field in &event.fields {
-            let mut visitor = TypeParamVisitor {
-                type_params: &type_param_idents,
-                found: std::collections::HashSet::new(),
-            };
-            syn::visit::visit_field(&mut visitor, field);
-            used_in_events.extend(visitor.found);
-        }
-    }
-
-    // Create generics for commands that only include used type parameters
-    let mut command_generics = command_bounds.clone();
-    command_generics.params = command_generics
-        .params
-        .iter()
-        .filter(|param| {
-            if let syn::GenericParam::Type(type_param) = param {
-                used_in_commands.contains(&type_param.ident)
-            } else {
-                true // Keep lifetime and const parameters
-            }
-        })
-        .cloned()
-        .collect();
-
-    let (_, _, command_where_clause) = command_generics.split_for_impl();
+    // Commands and events only declare the parameters (and where predicates) their fields use
+    let command_generics = generics_used_by(
+        &command_bounds,
+        aggregate_def.commands.iter().flat_map(|cmd| &cmd.fields),
+    );
+    let (_, command_ty_generics, command_where_clause) = command_generics.split_for_impl();
     let command_type_params = &command_generics.params;
 
-    // Create generics for events that only include used type parameters
-    let mut event_generics = generics_with_bounds.clone();
-    event_generics.params = event_generics
-        .params
-        .iter()
-        .filter(|param| {
-            if let syn::GenericParam::Type(type_param) = param {
-                used_in_events.contains(&type_param.ident)
-            } else {
-                true // Keep lifetime and const parameters
-            }
-        })
-        .cloned()
-        .collect();
-
-    let (_, _, event_where_clause) = event_generics.split_for_impl();
+    let event_generics = generics_used_by(
+        &generics_with_bounds,
+        aggregate_def.events.iter().flat_map(|evt| &evt.fields),
+    );
+    let (_, event_ty_generics, event_where_clause) = event_generics.split_for_impl();
     let event_type_params = &event_generics.params;
 
     let command_name = quote::format_ident!("{}Command", name);
@@ -436,95 +533,139 @@ pub fn define_aggregate(input: TokenStream) -> TokenStream {
         .namespace
         .as_ref()
         .map(|lit| lit.value())
-        .unwrap_or_else(|| {
-            // Convert CamelCase to kebab-case with proper acronym handling
-            // e.g., "HTTPConnection" -> "http-connection", "BankAccount" -> "bank-account"
-            let name_str = name.to_string();
-            let mut result = String::new();
-            let chars: Vec<char> = name_str.chars().collect();
-
-            for i in 0..chars.len() {
-                let ch = chars[i];
-
-                if ch.is_uppercase() {
-                    // Add hyphen before uppercase if:
-                    // 1. Not at the start (i > 0)
-                    // 2. AND one of:
-                    //    a. Previous char is lowercase (e.g., "myHTTP" -> "my-HTTP")
-                    //    b. Next char exists and is lowercase (end of acronym: "HTTPConnection" -> "HTTP-Connection")
-                    if i > 0 {
-                        let prev_is_lower = chars[i - 1].is_lowercase();
-                        let next_is_lower = i + 1 < chars.len() && chars[i + 1].is_lowercase();
-
-                        if prev_is_lower || next_is_lower {
-                            result.push('-');
-                        }
-                    }
-                }
-
-                result.push(ch.to_ascii_lowercase());
-            }
-            result
-        });
+        .unwrap_or_else(|| aggregate_namespace(&name.to_string()));
 
     let namespace = syn::LitStr::new(&namespace_str, name.span());
 
     // Generate state fields
     let state_fields = &aggregate_def.state_fields;
 
+    // Attributes written on the aggregate and its sections go to the generated types
+    let state_attrs = &aggregate_def.attrs;
+    let command_attrs = &aggregate_def.command_attrs;
+    let event_attrs = &aggregate_def.event_attrs;
+    let service_attrs = &aggregate_def.service_attrs;
+
+    // Generated items without a doc comment in the DSL get one pointing back to the aggregate
+    let state_doc = default_doc(state_attrs, format!("State of the `{name}` aggregate."));
+    let command_doc = default_doc(command_attrs, format!("Commands for [`{name}`]."));
+    let event_doc = default_doc(event_attrs, format!("Events of [`{name}`]."));
+    let services_doc = if aggregate_def.service_functions.is_empty()
+        && aggregate_def.base_service_traits.is_empty()
+    {
+        default_doc(
+            service_attrs,
+            format!("Services of [`{name}`]; it declares none, so this is an empty placeholder."),
+        )
+    } else {
+        default_doc(
+            service_attrs,
+            format!("External dependencies [`{name}`] commands are handled with."),
+        )
+    };
+    let urn_doc = urn_doc(name, &namespace_str);
+
     // Generate command variants
     let command_variants = aggregate_def.commands.iter().map(|cmd| {
+        let attrs = &cmd.attrs;
         let variant_name = &cmd.name;
         if cmd.fields.is_empty() {
-            quote! { #variant_name }
+            quote! { #(#attrs)* #variant_name }
         } else {
             let fields = &cmd.fields;
-            quote! { #variant_name { #(#fields),* } }
+            quote! { #(#attrs)* #variant_name { #(#fields),* } }
+        }
+    });
+
+    // Generate builders for `#[builder]` commands
+    let command_builders = aggregate_def.commands.iter().filter_map(|cmd| {
+        cmd.builder.as_ref().map(|builder| {
+            command_builder::command_builder(
+                builder,
+                &command_name,
+                &command_generics,
+                &cmd.name,
+                &cmd.fields,
+            )
+        })
+    });
+
+    // Generate serde(bound = "") to prevent serde from adding its own bounds
+    let serde_bound_attr = if !generics.params.is_empty() {
+        quote! { #[serde(bound = "")] }
+    } else {
+        quote! {}
+    };
+
+    // A `#[transient]` state is only rebuilt from events, never serialized
+    let state_serde = if aggregate_def.transient_state {
+        quote! { #[derive(Clone, Debug)] }
+    } else {
+        quote! {
+            #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+            #serde_bound_attr
+        }
+    };
+
+    // `state: enum { ... }` becomes `<Name>State`, starting at its first (unit) variant
+    let state_enum = aggregate_def.state_variants.as_ref().map(|variants| {
+        let state_name = quote::format_ident!("{}State", name);
+        let state_generics =
+            generics_used_by(&generics_with_bounds, variants.iter().flat_map(|v| &v.fields));
+        let (state_impl_generics, state_ty_generics, state_where_clause) =
+            state_generics.split_for_impl();
+        let state_type_params = &state_generics.params;
+        let state_enum_attrs = &aggregate_def.state_enum_attrs;
+        let state_doc = default_doc(state_enum_attrs, format!("Lifecycle states of [`{name}`]."));
+        let initial = &variants[0].name;
+
+        let state_variants = variants.iter().map(|state| {
+            let attrs = &state.attrs;
+            let variant_name = &state.name;
+            if state.fields.is_empty() {
+                quote! { #(#attrs)* #variant_name }
+            } else {
+                let fields = &state.fields;
+                quote! { #(#attrs)* #variant_name { #(#fields),* } }
+            }
+        });
+
+        quote! {
+            #[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug)]
+            #serde_bound_attr
+            #state_doc
+            #(#state_enum_attrs)*
+            pub enum #state_name <#state_type_params> #state_where_clause {
+                #(#state_variants),*
+            }
+
+            impl #state_impl_generics Default for #state_name #state_ty_generics #state_where_clause {
+                fn default() -> Self {
+                    #state_name::#initial
+                }
+            }
         }
     });
 
+    // Events with `#[pii]` fields get a redacting `Debug` from `derive(Event)`
+    let event_debug =
+        (!event_derive::has_pii(aggregate_def.events.iter().flat_map(|evt| &evt.fields)))
+            .then(|| quote! { Debug, });
+
     // Generate event variants
     let event_variants = aggregate_def.events.iter().map(|evt| {
+        let attrs = &evt.attrs;
         let variant_name = &evt.name;
         if evt.fields.is_empty() {
-            quote! { #variant_name }
+            quote! { #(#attrs)* #variant_name }
         } else {
             let fields = &evt.fields;
-            quote! { #variant_name { #(#fields),* } }
+            quote! { #(#attrs)* #variant_name { #(#fields),* } }
         }
     });
 
     // Generate URN serialization implementations with namespace validation
-    let urn_serde_impl = quote! {
-        impl serde::Serialize for #urn_name {
-            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-            where
-                S: serde::Serializer,
-            {
-                serializer.serialize_str(&self.0.to_string())
-            }
-        }
-
-        impl<'de> serde::Deserialize<'de> for #urn_name {
-            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-            where
-                D: serde::Deserializer<'de>,
-            {
-                use std::str::FromStr;
-                let s = String::deserialize(deserializer)?;
-                let urn = urn::Urn::from_str(&s)
-                    .map_err(|e| serde::de::Error::custom(format!("Invalid URN: {}", e)))?;
-
-                if urn.nid() != #namespace {
-                    return Err(serde::de::Error::custom(
-                        format!("Invalid URN namespace: expected '{}', got '{}'", #namespace, urn.nid())
-                    ));
-                }
-
-                Ok(Self(urn))
-            }
-        }
-    };
+    let urn_serde_impl = urn_serde_impl(&urn_name, &namespace);
 
     // Extract field names for initialization
     let state_field_names = aggregate_def.state_fields.iter().map(|f| &f.ident);
@@ -537,6 +678,7 @@ pub fn define_aggregate(input: TokenStream) -> TokenStream {
         let has_async = aggregate_def.service_functions.iter().any(|f| f.is_async);
 
         let trait_methods = aggregate_def.service_functions.iter().map(|func| {
+            let attrs = &func.attrs;
             let func_name = &func.name;
             let lifetimes = &func.lifetimes;
             let inputs = &func.inputs;
@@ -554,6 +696,7 @@ pub fn define_aggregate(input: TokenStream) -> TokenStream {
             };
 
             quote! {
+                #(#attrs)*
                 #async_token fn #func_name #lifetime_params(&self, #(#inputs),*) #output;
             }
         });
@@ -578,29 +721,311 @@ pub fn define_aggregate(input: TokenStream) -> TokenStream {
 
         quote! {
             #async_trait_attr
+            #services_doc
+            #(#service_attrs)*
             pub trait #services_name #trait_bounds {
                 #(#trait_methods)*
             }
         }
     } else {
         quote! {
-            #[derive(Clone, Debug)]
+            #[derive(Clone, Debug, Default)]
+            #services_doc
+            #(#service_attrs)*
             pub struct #services_name;
         }
     };
 
-    // Generate serde(bound = "") to prevent serde from adding its own bounds
-    let serde_bound_attr = if !generics.params.is_empty() {
-        quote! { #[serde(bound = "")] }
+    // Generate `impl Aggregate` whose `handle` runs inline handlers and dispatches every
+    // other command to its `on_<command>` method
+    let aggregate_impl = if aggregate_def.generates_handler() {
+        let error_type = aggregate_def
+            .error
+            .as_ref()
+            .map(|ty| quote! { #ty })
+            .unwrap_or_else(|| quote! { replay::Error });
+
+        let services_type = if !aggregate_def.service_functions.is_empty()
+            || !aggregate_def.base_service_traits.is_empty()
+        {
+            quote! { std::sync::Arc<dyn #services_name> }
+        } else {
+            quote! { #services_name }
+        };
+
+        // With outputs, every arm also yields its command's `<Name>Output` variant
+        let output_name = quote::format_ident!("{}Output", name);
+        let has_outputs = aggregate_def.has_outputs();
+        let handle_arms = aggregate_def.commands.iter().map(|cmd| {
+            let variant_name = &cmd.name;
+            let field_names: Vec<_> = cmd.fields.iter().map(|f| &f.ident).collect();
+            let pattern = quote! { #command_name::#variant_name { #(#field_names),* } };
+
+            let (result_type, to_output) = match (&cmd.output, has_outputs) {
+                (_, false) => (quote! { Vec<Self::Event> }, quote! {}),
+                (Some(output), true) => (
+                    quote! { (Vec<Self::Event>, #output) },
+                    quote! { .map(|(events, value)| (events, #output_name::#variant_name(value))) },
+                ),
+                (None, true) => (
+                    quote! { Vec<Self::Event> },
+                    quote! { .map(|events| (events, #output_name::#variant_name)) },
+                ),
+            };
+
+            let Some(handler) = &cmd.handler else {
+                let method = cmd.handler_method();
+                return quote! {
+                    #pattern => self.#method(#(#field_names,)* services).await #to_output
+                };
+            };
+
+            let args_name = quote::format_ident!("{}Command", variant_name);
+            let (args_struct, args_value) = handler_args(&args_name, &cmd.fields, generics);
+            let inputs: Vec<_> = handler.inputs.iter().collect();
+            let (state_pat, cmd_pat, svc_pat) = (inputs[0], inputs[1], inputs[2]);
+            let body = &handler.body;
+
+            quote! {
+                #pattern => {
+                    #args_struct
+
+                    let #state_pat = self;
+                    let #cmd_pat = #args_value;
+                    let #svc_pat = services;
+                    let result: Result<#result_type, Self::Error> = #body;
+                    result #to_output
+                }
+            }
+        });
+
+        // With `#[create]` commands, every command reports whether it opens the stream
+        let lifecycle_fn = if aggregate_def.commands.iter().any(|cmd| cmd.creates) {
+            let lifecycle_arms = aggregate_def.commands.iter().map(|cmd| {
+                let variant_name = &cmd.name;
+                let lifecycle = if cmd.creates {
+                    quote! { replay::Lifecycle::Creates }
+                } else {
+                    quote! { replay::Lifecycle::Existing }
+                };
+                quote! { #command_name::#variant_name { .. } => #lifecycle }
+            });
+            quote! {
+                fn lifecycle(command: &Self::Command) -> replay::Lifecycle {
+                    match command {
+                        #(#lifecycle_arms),*
+                    }
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        // Bound on the user's `EventStream` impl, so generic aggregates may implement it
+        // for selected type arguments only
+        let where_predicates = where_clause.map(|clause| &clause.predicates);
+
+        if has_outputs {
+            let output_types: Vec<syn::Field> = aggregate_def
+                .commands
+                .iter()
+                .filter_map(|cmd| cmd.output.as_ref())
+                .map(|ty| syn::parse::Parser::parse2(syn::Field::parse_unnamed, quote! { #ty }))
+                .collect::<syn::Result<_>>()
+                .expect("a parsed type is a valid unnamed field");
+            let output_generics = generics_used_by(generics, &output_types);
+            let (output_impl_generics, output_ty_generics, output_where_clause) =
+                output_generics.split_for_impl();
+            let output_type_params = &output_generics.params;
+
+            let output_variants = aggregate_def.commands.iter().map(|cmd| {
+                let variant_name = &cmd.name;
+                match &cmd.output {
+                    Some(output) => quote! { #variant_name(#output) },
+                    None => quote! { #variant_name },
+                }
+            });
+            let output_accessors = aggregate_def.commands.iter().filter_map(|cmd| {
+                let output = cmd.output.as_ref()?;
+                let variant_name = &cmd.name;
+                let accessor = quote::format_ident!(
+                    "into_{}",
+                    define_aggregate_macro::camel_to_snake(&variant_name.to_string())
+                );
+                let doc = format!("The value of a `{variant_name}` command, if this is one.");
+                Some(quote! {
+                    #[doc = #doc]
+                    pub fn #accessor(self) -> Option<#output> {
+                        match self {
+                            #output_name::#variant_name(value) => Some(value),
+                            #[allow(unreachable_patterns)]
+                            _ => None,
+                        }
+                    }
+                })
+            });
+            let output_doc = format!(
+                "What a [`{name}`] command hands back besides its events: one variant per command."
+            );
+
+            quote! {
+                #[doc = #output_doc]
+                pub enum #output_name <#output_type_params> #output_where_clause {
+                    #(#output_variants),*
+                }
+
+                impl #output_impl_generics #output_name #output_ty_generics #output_where_clause {
+                    #(#output_accessors)*
+                }
+
+                impl #impl_generics replay::Aggregate for #name #ty_generics
+                where
+                    Self: replay::EventStream<Event = #event_name #event_ty_generics>,
+                    #where_predicates
+                {
+                    type Command = #command_name #command_ty_generics;
+                    type Error = #error_type;
+                    type Services = #services_type;
+
+                    #lifecycle_fn
+
+                    async fn handle(
+                        &self,
+                        command: Self::Command,
+                        services: &Self::Services,
+                    ) -> Result<Vec<Self::Event>, Self::Error> {
+                        replay::WithOutput::handle_with_output(self, command, services)
+                            .await
+                            .map(|(events, _)| events)
+                    }
+                }
+
+                impl #impl_generics replay::WithOutput for #name #ty_generics
+                where
+                    Self: replay::EventStream<Event = #event_name #event_ty_generics>,
+                    #where_predicates
+                {
+                    type Output = #output_name #output_ty_generics;
+
+                    async fn handle_with_output(
+                        &self,
+                        command: Self::Command,
+                        services: &Self::Services,
+                    ) -> Result<(Vec<Self::Event>, Self::Output), Self::Error> {
+                        match command {
+                            #(#handle_arms),*
+                        }
+                    }
+                }
+            }
+        } else {
+            quote! {
+                impl #impl_generics replay::Aggregate for #name #ty_generics
+                where
+                    Self: replay::EventStream<Event = #event_name #event_ty_generics>,
+                    #where_predicates
+                {
+                    type Command = #command_name #command_ty_generics;
+                    type Error = #error_type;
+                    type Services = #services_type;
+
+                    #lifecycle_fn
+
+                    async fn handle(
+                        &self,
+                        command: Self::Command,
+                        services: &Self::Services,
+                    ) -> Result<Vec<Self::Event>, Self::Error> {
+                        match command {
+                            #(#handle_arms),*
+                        }
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Generate `impl EventStream` from the `apply` section; `#[entity]` events are routed to
+    // their child and events without an entry dispatch to their `apply_<event>` method
+    let event_stream_impl = if let Some(appliers) = &aggregate_def.appliers {
+        let apply_arms = aggregate_def.events.iter().map(|evt| {
+            let variant_name = &evt.name;
+            let field_names: Vec<_> = evt.fields.iter().map(|f| &f.ident).collect();
+            let pattern = quote! { #event_name::#variant_name { #(#field_names),* } };
+
+            if let Some(route) = &evt.entity {
+                let (field, id) = (&route.field, &route.id);
+                return quote! {
+                    #pattern => {
+                        let __entity_id = #id.clone();
+                        let event = #event_name::#variant_name { #(#field_names),* };
+                        self.#field.route(__entity_id, event)
+                    }
+                };
+            }
+
+            let Some(applier) = appliers.iter().find(|a| a.event == *variant_name) else {
+                let method = evt.apply_method();
+                return quote! {
+                    #pattern => self.#method(#(#field_names),*)
+                };
+            };
+
+            let args_name = quote::format_ident!("{}Event", variant_name);
+            let (args_struct, args_value) = handler_args(&args_name, &evt.fields, generics);
+            let inputs: Vec<_> = applier.closure.inputs.iter().collect();
+            let (state_pat, event_pat) = (inputs[0], inputs[1]);
+            let body = &applier.closure.body;
+
+            quote! {
+                #pattern => {
+                    #args_struct
+
+                    let #state_pat = self;
+                    let #event_pat = #args_value;
+                    #body
+                }
+            }
+        });
+
+        let stream_type = name.to_string();
+        let where_predicates = where_clause.map(|clause| &clause.predicates);
+
+        quote! {
+            impl #impl_generics replay::EventStream for #name #ty_generics
+            where
+                #event_name #event_ty_generics: replay::Event,
+                #where_predicates
+            {
+                type Event = #event_name #event_ty_generics;
+
+                fn stream_type() -> &'static str {
+                    #stream_type
+                }
+
+                fn apply(&mut self, event: Self::Event) {
+                    match event {
+                        #(#apply_arms),*
+                    }
+                }
+            }
+        }
     } else {
         quote! {}
     };
 
     let expanded = quote! {
+        // Lifecycle enum of an enum `state`
+        #state_enum
+
         // Aggregate state struct
-        #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
-        #serde_bound_attr
+        #state_serde
+        #state_doc
+        #(#state_attrs)*
         pub struct #name <#type_params> #where_clause {
+            /// Id of the stream this aggregate is rebuilt from.
             pub id: #urn_name,
             #(#state_fields),*
         }
@@ -629,13 +1054,19 @@ pub fn define_aggregate(input: TokenStream) -> TokenStream {
         }
 
         // Command enum
+        #command_doc
+        #(#command_attrs)*
         pub enum #command_name <#command_type_params> #command_where_clause {
             #(#command_variants),*
         }
 
+        #(#command_builders)*
+
         // Event enum with Event derive
-        #[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug, replay_macros::Event)]
+        #[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, #event_debug replay_macros::Event)]
         #serde_bound_attr
+        #event_doc
+        #(#event_attrs)*
         pub enum #event_name <#event_type_params> #event_where_clause {
             #(#event_variants),*
         }
@@ -643,17 +1074,116 @@ pub fn define_aggregate(input: TokenStream) -> TokenStream {
         // URN type — base trait impls + namespace-aware helpers via #[derive(Urn)]
         #[derive(Clone, Debug, replay_macros::Urn)]
         #[urn(namespace = #namespace)]
+        #[doc = #urn_doc]
         pub struct #urn_name(urn::Urn);
 
         #urn_serde_impl
 
         // Generate Services trait
         #services_trait
+
+        // Generate command handler (only when inline handlers or an `error:` section are given)
+        #aggregate_impl
+
+        // Generate event application (only when an `apply` section is given)
+        #event_stream_impl
     };
 
     TokenStream::from(expanded)
 }
 
+/// Attribute-macro flavor of [`define_aggregate!`] for the aggregate state struct.
+///
+/// Adds a leading `pub id: <Name>Urn` field, derives `Serialize`, `Deserialize`, `Clone` and
+/// `Debug`, and generates `WithId` (every other field starts at its `Default`), id-based
+/// `PartialEq` and the `<Name>Urn` type. `namespace` defaults to the name in kebab-case;
+/// `transient` leaves out the serde derives, for a state holding members that can't be
+/// serialized.
+///
+/// ```ignore
+/// #[aggregate(namespace = "bank-account")]
+/// pub struct BankAccount {
+///     pub balance: f64,
+/// }
+///
+/// #[commands]
+/// pub enum BankAccountCommand {
+///     Deposit { amount: f64 },
+/// }
+///
+/// #[events]
+/// pub enum BankAccountEvent {
+///     Deposited { amount: f64 },
+/// }
+///
+/// #[services]
+/// pub trait BankAccountServices {
+///     async fn validate_account_number(&self, account_number: &str) -> bool;
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn aggregate(attr: TokenStream, item: TokenStream) -> TokenStream {
+    aggregate_attribute::aggregate(attr.into(), item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Marks the command enum of an [`macro@aggregate`]; adds `command_name()`, returning the
+/// variant name (handy as an error `operation`).
+#[proc_macro_attribute]
+pub fn commands(attr: TokenStream, item: TokenStream) -> TokenStream {
+    aggregate_attribute::commands(attr.into(), item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Marks the event enum of an [`macro@aggregate`]; derives `Serialize`, `Deserialize`,
+/// `Clone`, `PartialEq`, `Debug` and [`Event`](derive@Event).
+#[proc_macro_attribute]
+pub fn events(attr: TokenStream, item: TokenStream) -> TokenStream {
+    aggregate_attribute::events(attr.into(), item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Marks the services trait of an [`macro@aggregate`]; adds the `Send + Sync` supertraits and,
+/// when it has `async fn`s, the target-appropriate `async_trait` attribute.
+#[proc_macro_attribute]
+pub fn services(attr: TokenStream, item: TokenStream) -> TokenStream {
+    aggregate_attribute::services(attr.into(), item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `EventStream` from the methods of an inherent `impl` tagged `#[apply(Variant)]`,
+/// for hand-written state types. Each method takes `&mut self` and parameters named after the
+/// fields it needs (a leading `_` is ignored); fields it leaves out are skipped. Events without
+/// an `#[apply]` method leave the state unchanged. `stream_type` defaults to the type's name.
+///
+/// On a hand-written `impl EventStream` it only adds the missing `stream_type()`: the type's
+/// name in kebab-case, like [`StreamType`](derive@StreamType), or `stream_type = "..."`.
+///
+/// ```ignore
+/// #[event_stream(event = BankAccountEvent)]
+/// impl BankAccount {
+///     #[apply(Deposited)]
+///     fn on_deposited(&mut self, amount: f64) {
+///         self.balance += amount;
+///     }
+///
+///     #[apply(Closed)]
+///     fn on_closed(&mut self) {
+///         self.open = false;
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn event_stream(attr: TokenStream, item: TokenStream) -> TokenStream {
+    aggregate_attribute::event_stream(attr.into(), item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
 /// Macro to generate a wrapper enum for multiple event types in queries.
 ///
 /// Example usage:
@@ -663,10 +1193,20 @@ pub fn define_aggregate(input: TokenStream) -> TokenStream {
 ///
 /// This will generate:
 /// - An enum with variants for each event type
-/// - From trait implementations for each event type
+/// - From trait implementations for each event type, and `TryFrom` back (returning the
+///   merged event on a mismatch)
+/// - `is_<variant>()` helpers, e.g. `is_user_event()`
 /// - Serialize/Deserialize implementations that delegate to inner types
 /// - replay::Event trait implementation
 /// - PartialEq, Display, and Debug implementations
+///
+/// Naming the aggregate of each event also generates `stream_types()`, so a query can narrow
+/// the store read to those streams:
+/// ```ignore
+/// query_events!(UserHistoryEvent => [UserEvent from User, CatalogEvent from Catalog]);
+///
+/// let filter = StreamFilter::for_stream_types(UserHistoryEvent::stream_types());
+/// ```
 #[proc_macro]
 pub fn query_events(input: TokenStream) -> TokenStream {
     let query_def = parse_macro_input!(input as QueryEventsDefinition);
@@ -705,33 +1245,47 @@ pub fn query_events(input: TokenStream) -> TokenStream {
         }
     });
 
-    // Generate Deserialize match arms (try each type in order)
-    let deserialize_attempts = event_types.iter().enumerate().map(|(i, ty)| {
+    // Generate Deserialize attempts: each type in order, keeping every type's error for the
+    // message when none fits; tagged merges also require `event_type()` to equal the tag
+    let deserialize_attempts = event_types.iter().map(|ty| {
         let variant_name = if let Type::Path(type_path) = ty {
             type_path.path.segments.last().unwrap().ident.clone()
         } else {
             panic!("Expected a type path");
         };
-
-        if i < event_types.len() - 1 {
-            // For all but the last, clone the value for retry
-            quote! {
-                if let Ok(event) = serde_json::from_value::<#ty>(value.clone()) {
+        let type_name = quote!(#ty).to_string();
+        let accept = match &query_def.representation {
+            Representation::Untagged => quote! { return Ok(#enum_name::#variant_name(event)) },
+            Representation::Tagged(_) => quote! {
+                if replay::Event::event_type(&event) == tag {
                     return Ok(#enum_name::#variant_name(event));
+                } else {
+                    errors.push(format!(
+                        "{}: event type is `{}`",
+                        #type_name,
+                        replay::Event::event_type(&event)
+                    ));
                 }
-            }
-        } else {
-            // For the last one, no clone needed
-            quote! {
-                match serde_json::from_value::<#ty>(value) {
-                    Ok(event) => Ok(#enum_name::#variant_name(event)),
-                    Err(_) => Err(serde::de::Error::custom(
-                        format!("Could not deserialize as any of the expected event types")
-                    )),
-                }
+            },
+        };
+
+        quote! {
+            match serde_json::from_value::<#ty>(value.clone()) {
+                Ok(event) => #accept,
+                Err(err) => errors.push(format!("{}: {}", #type_name, err)),
             }
         }
     });
+    let read_tag = match &query_def.representation {
+        Representation::Untagged => quote! {},
+        Representation::Tagged(tag) => quote! {
+            let tag = value
+                .get(#tag)
+                .and_then(serde_json::Value::as_str)
+                .ok_or_else(|| <D::Error as serde::de::Error>::missing_field(#tag))?
+                .to_string();
+        },
+    };
 
     // Generate Serialize match arms
     let serialize_arms = event_types.iter().map(|ty| {
@@ -759,6 +1313,30 @@ pub fn query_events(input: TokenStream) -> TokenStream {
         }
     });
 
+    let event_version_arms = event_types.iter().map(|ty| {
+        let variant_name = if let Type::Path(type_path) = ty {
+            type_path.path.segments.last().unwrap().ident.clone()
+        } else {
+            panic!("Expected a type path");
+        };
+
+        quote! {
+            #enum_name::#variant_name(event) => event.event_version()
+        }
+    });
+
+    let is_tombstone_arms = event_types.iter().map(|ty| {
+        let variant_name = if let Type::Path(type_path) = ty {
+            type_path.path.segments.last().unwrap().ident.clone()
+        } else {
+            panic!("Expected a type path");
+        };
+
+        quote! {
+            #enum_name::#variant_name(event) => event.is_tombstone()
+        }
+    });
+
     // Generate PartialEq match arms
     let partial_eq_arms = event_types.iter().map(|ty| {
         let variant_name = if let Type::Path(type_path) = ty {
@@ -785,6 +1363,62 @@ pub fn query_events(input: TokenStream) -> TokenStream {
         }
     });
 
+    // Generate TryFrom back-conversions, handing the merged event back on a mismatch
+    let try_from_impls = event_types.iter().map(|ty| {
+        let variant_name = if let Type::Path(type_path) = ty {
+            type_path.path.segments.last().unwrap().ident.clone()
+        } else {
+            panic!("Expected a type path");
+        };
+
+        quote! {
+            impl TryFrom<#enum_name> for #ty {
+                type Error = #enum_name;
+
+                fn try_from(event: #enum_name) -> Result<Self, Self::Error> {
+                    match event {
+                        #enum_name::#variant_name(event) => Ok(event),
+                        #[allow(unreachable_patterns)]
+                        other => Err(other),
+                    }
+                }
+            }
+        }
+    });
+
+    // Generate `is_<variant>()` helpers
+    let is_variant_fns = event_types.iter().map(|ty| {
+        let variant_name = if let Type::Path(type_path) = ty {
+            type_path.path.segments.last().unwrap().ident.clone()
+        } else {
+            panic!("Expected a type path");
+        };
+        let fn_name = quote::format_ident!(
+            "is_{}",
+            define_aggregate_macro::camel_to_snake(&variant_name.to_string())
+        );
+        let doc = format!("Whether this is a `{variant_name}`.");
+
+        quote! {
+            #[doc = #doc]
+            pub fn #fn_name(&self) -> bool {
+                matches!(self, #enum_name::#variant_name(_))
+            }
+        }
+    });
+
+    // With `Event from Aggregate` entries, list the stream types to filter on server-side
+    let aggregates = &query_def.aggregates;
+    let stream_types_fn = (!aggregates.is_empty()).then(|| {
+        quote! {
+            /// Stream types of the aggregates these events come from, for
+            /// `StreamFilter::for_stream_types`.
+            pub fn stream_types() -> Vec<&'static str> {
+                vec![#(<#aggregates as replay::EventStream>::stream_type()),*]
+            }
+        }
+    });
+
     let expanded = quote! {
         #[derive(Clone, Debug)]
         pub enum #enum_name {
@@ -794,6 +1428,15 @@ pub fn query_events(input: TokenStream) -> TokenStream {
         // Generate From trait implementations
         #(#from_impls)*
 
+        // Generate TryFrom trait implementations
+        #(#try_from_impls)*
+
+        impl #enum_name {
+            #(#is_variant_fns)*
+
+            #stream_types_fn
+        }
+
         // Deserialize implementation
         impl<'de> serde::Deserialize<'de> for #enum_name {
             fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -801,8 +1444,15 @@ pub fn query_events(input: TokenStream) -> TokenStream {
                 D: serde::Deserializer<'de>,
             {
                 let value = serde_json::Value::deserialize(deserializer)?;
+                #read_tag
 
+                let mut errors: Vec<String> = Vec::new();
                 #(#deserialize_attempts)*
+
+                Err(serde::de::Error::custom(format!(
+                    "Could not deserialize as any of the expected event types ({})",
+                    errors.join("; ")
+                )))
             }
         }
 
@@ -820,11 +1470,23 @@ pub fn query_events(input: TokenStream) -> TokenStream {
 
         // replay::Event implementation
         impl replay::Event for #enum_name {
-            fn event_type(&self) -> String {
+            fn event_type(&self) -> &'static str {
                 match self {
                     #(#event_type_arms),*
                 }
             }
+
+            fn event_version(&self) -> u32 {
+                match self {
+                    #(#event_version_arms),*
+                }
+            }
+
+            fn is_tombstone(&self) -> bool {
+                match self {
+                    #(#is_tombstone_arms),*
+                }
+            }
         }
 
         // PartialEq implementation
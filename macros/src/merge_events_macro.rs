@@ -1,28 +1,85 @@
 use syn::{
     parse::{Parse, ParseStream},
-    Ident, Token, Type,
+    Attribute, Ident, LitStr, Token, Type,
 };
 
+syn::custom_keyword!(from);
+
+/// How stored JSON is matched to one of the merged event types; serializing always writes the
+/// wrapped event as is.
+pub enum Representation {
+    /// `#[serde(untagged)]` (the default): the first type the JSON deserializes into.
+    Untagged,
+    /// `#[serde(tag = "type")]`: the type whose `event_type()` equals the JSON's tag, for
+    /// internally tagged source enums that share a variant shape.
+    Tagged(LitStr),
+}
+
 // Struct to parse the query_events! macro input
 pub struct QueryEventsDefinition {
+    pub representation: Representation,
     pub name: Ident,
     pub event_types: Vec<Type>,
+    /// Aggregates the events come from (`UserEvent from User`); either every event names one
+    /// or none does.
+    pub aggregates: Vec<Type>,
 }
 
 impl Parse for QueryEventsDefinition {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut representation = Representation::Untagged;
+        for attr in input.call(Attribute::parse_outer)? {
+            if !attr.path().is_ident("serde") {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "only `#[serde(untagged)]` or `#[serde(tag = \"...\")]` is supported here",
+                ));
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("untagged") {
+                    representation = Representation::Untagged;
+                    Ok(())
+                } else if meta.path.is_ident("tag") {
+                    representation = Representation::Tagged(meta.value()?.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `untagged` or `tag = \"...\"`"))
+                }
+            })?;
+        }
+
         let name: Ident = input.parse()?;
         input.parse::<Token![=>]>()?;
 
         let content;
         syn::bracketed!(content in input);
 
-        let event_types: syn::punctuated::Punctuated<Type, Token![,]> =
-            content.parse_terminated(Type::parse, Token![,])?;
+        let mut event_types = Vec::new();
+        let mut aggregates = Vec::new();
+        while !content.is_empty() {
+            event_types.push(content.parse()?);
+            if content.peek(from) {
+                content.parse::<from>()?;
+                aggregates.push(content.parse()?);
+            }
+
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+        }
+
+        if !aggregates.is_empty() && aggregates.len() != event_types.len() {
+            return Err(syn::Error::new_spanned(
+                &name,
+                "name the aggregate of every event (`UserEvent from User`) or of none",
+            ));
+        }
 
         Ok(QueryEventsDefinition {
+            representation,
             name,
-            event_types: event_types.into_iter().collect(),
+            event_types,
+            aggregates,
         })
     }
 }
@@ -0,0 +1,332 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, Data, DeriveInput, Field, Fields, Ident, LitStr, Token, Type};
+
+use crate::event_derive::is_pii;
+
+/// A `#[serde(rename_all = "...")]` rule, applied the way serde applies it.
+#[derive(Clone, Copy)]
+enum SerdeCase {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl SerdeCase {
+    fn parse(lit: &LitStr) -> syn::Result<Self> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => SerdeCase::Lower,
+            "UPPERCASE" => SerdeCase::Upper,
+            "PascalCase" => SerdeCase::Pascal,
+            "camelCase" => SerdeCase::Camel,
+            "snake_case" => SerdeCase::Snake,
+            "SCREAMING_SNAKE_CASE" => SerdeCase::ScreamingSnake,
+            "kebab-case" => SerdeCase::Kebab,
+            "SCREAMING-KEBAB-CASE" => SerdeCase::ScreamingKebab,
+            _ => return Err(syn::Error::new_spanned(lit, "unknown serde rename rule")),
+        })
+    }
+
+    /// Rename a PascalCase variant name.
+    fn variant(self, name: &str) -> String {
+        let snake = || {
+            let mut snake = String::new();
+            for (i, ch) in name.char_indices() {
+                if i > 0 && ch.is_uppercase() {
+                    snake.push('_');
+                }
+                snake.push(ch.to_ascii_lowercase());
+            }
+            snake
+        };
+        match self {
+            SerdeCase::Pascal => name.to_string(),
+            SerdeCase::Lower => name.to_ascii_lowercase(),
+            SerdeCase::Upper => name.to_ascii_uppercase(),
+            SerdeCase::Camel => name[..1].to_ascii_lowercase() + &name[1..],
+            SerdeCase::Snake => snake(),
+            SerdeCase::ScreamingSnake => snake().to_ascii_uppercase(),
+            SerdeCase::Kebab => snake().replace('_', "-"),
+            SerdeCase::ScreamingKebab => snake().to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+
+    /// Rename a snake_case field name.
+    fn field(self, name: &str) -> String {
+        let pascal = || {
+            let mut pascal = String::new();
+            let mut capitalize = true;
+            for ch in name.chars() {
+                if ch == '_' {
+                    capitalize = true;
+                } else if capitalize {
+                    pascal.push(ch.to_ascii_uppercase());
+                    capitalize = false;
+                } else {
+                    pascal.push(ch);
+                }
+            }
+            pascal
+        };
+        match self {
+            SerdeCase::Lower | SerdeCase::Snake => name.to_string(),
+            SerdeCase::Upper | SerdeCase::ScreamingSnake => name.to_ascii_uppercase(),
+            SerdeCase::Pascal => pascal(),
+            SerdeCase::Camel => {
+                let pascal = pascal();
+                pascal[..1].to_ascii_lowercase() + &pascal[1..]
+            }
+            SerdeCase::Kebab => name.replace('_', "-"),
+            SerdeCase::ScreamingKebab => name.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
+
+/// The `#[serde(...)]` options that decide where a field lands in the serialized payload.
+#[derive(Default)]
+struct SerdeOptions {
+    rename: Option<String>,
+    rename_all: Option<SerdeCase>,
+    rename_all_fields: Option<SerdeCase>,
+    tag: Option<String>,
+    content: Option<String>,
+    untagged: bool,
+    transparent: bool,
+    flatten: bool,
+    skip: bool,
+}
+
+fn serde_options(attrs: &[Attribute]) -> syn::Result<SerdeOptions> {
+    let mut options = SerdeOptions::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            let path = &meta.path;
+            if path.is_ident("rename") {
+                options.rename = serialized_name(&meta)?.map(|name| name.value());
+            } else if path.is_ident("rename_all") {
+                if let Some(case) = serialized_name(&meta)? {
+                    options.rename_all = Some(SerdeCase::parse(&case)?);
+                }
+            } else if path.is_ident("rename_all_fields") {
+                if let Some(case) = serialized_name(&meta)? {
+                    options.rename_all_fields = Some(SerdeCase::parse(&case)?);
+                }
+            } else if path.is_ident("tag") {
+                options.tag = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if path.is_ident("content") {
+                options.content = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if path.is_ident("untagged") {
+                options.untagged = true;
+            } else if path.is_ident("transparent") {
+                options.transparent = true;
+            } else if path.is_ident("flatten") {
+                options.flatten = true;
+            } else if path.is_ident("skip") || path.is_ident("skip_serializing") {
+                options.skip = true;
+            } else {
+                skip_value(meta)?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+/// The serialized name of `rename = "..."` or `rename(serialize = "...")`.
+fn serialized_name(meta: &ParseNestedMeta) -> syn::Result<Option<LitStr>> {
+    if meta.input.peek(Token![=]) {
+        return Ok(Some(meta.value()?.parse()?));
+    }
+    let mut name = None;
+    meta.parse_nested_meta(|inner| {
+        let value: LitStr = inner.value()?.parse()?;
+        if inner.path.is_ident("serialize") {
+            name = Some(value);
+        }
+        Ok(())
+    })?;
+    Ok(name)
+}
+
+/// Consume a serde option that doesn't move fields, e.g. `default` or `with = "..."`.
+fn skip_value(meta: ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(skip_value)?;
+    }
+    Ok(())
+}
+
+/// A field's name without the `r#` of a raw identifier, as serde writes it.
+fn unraw(ident: &Ident) -> String {
+    let name = ident.to_string();
+    name.strip_prefix("r#").map(str::to_string).unwrap_or(name)
+}
+
+/// `segments` as a JSON Pointer (RFC 6901).
+fn pointer(segments: &[String]) -> String {
+    segments
+        .iter()
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+/// The paths of the `#[pii]` fields among `fields`, serialized under `prefix`.
+fn field_paths(
+    fields: &Fields,
+    prefix: &[String],
+    case: Option<SerdeCase>,
+    paths: &mut Vec<String>,
+) -> syn::Result<()> {
+    let newtype = matches!(fields, Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1);
+    let mut index = 0;
+    for field in fields {
+        let options = serde_options(&field.attrs)?;
+        if options.skip {
+            continue;
+        }
+        let segment = match &field.ident {
+            _ if newtype => None,
+            Some(ident) => Some(options.rename.clone().unwrap_or_else(|| match case {
+                Some(case) => case.field(&unraw(ident)),
+                None => unraw(ident),
+            })),
+            None => Some(index.to_string()),
+        };
+        index += 1;
+        if !is_pii(field) {
+            continue;
+        }
+        if options.flatten {
+            return Err(unlocated(
+                field,
+                "its fields are flattened into the payload",
+            ));
+        }
+        let mut path = prefix.to_vec();
+        path.extend(segment);
+        paths.push(pointer(&path));
+    }
+    Ok(())
+}
+
+fn unlocated(field: &Field, why: &str) -> syn::Error {
+    syn::Error::new_spanned(
+        field,
+        format!("this `#[pii]` field has no path of its own in the payload: {why}"),
+    )
+}
+
+/// How an enum's variants are serialized.
+enum Tagging {
+    /// `{"Variant": {...}}`, serde's default.
+    External,
+    /// `{"tag": "Variant", ...}`.
+    Internal,
+    /// `{"tag": "Variant", "content": {...}}`.
+    Adjacent(String),
+    /// `{...}`.
+    Untagged,
+}
+
+/// `Event::pii_paths` for `derive(Event)`: the JSON Pointers of the `#[pii]` fields in the
+/// serialized payload, following `#[serde]` renames and tagging, and of the `#[pii]` fields
+/// of the events `wrapped` by transparent variants (named) or a transparent struct.
+pub fn pii_paths_fn(
+    input: &DeriveInput,
+    wrapped: &[(Option<&Ident>, &Type)],
+) -> syn::Result<Option<TokenStream>> {
+    let container = serde_options(&input.attrs)?;
+    let mut paths = Vec::new();
+    let mut prefixes = Vec::new();
+
+    match &input.data {
+        Data::Enum(data_enum) => {
+            let tagging = match (&container.tag, &container.content) {
+                _ if container.untagged => Tagging::Untagged,
+                (Some(_), Some(content)) => Tagging::Adjacent(content.clone()),
+                (Some(_), None) => Tagging::Internal,
+                (None, _) => Tagging::External,
+            };
+            for variant in &data_enum.variants {
+                let options = serde_options(&variant.attrs)?;
+                if options.skip {
+                    continue;
+                }
+                let name = options.rename.clone().unwrap_or_else(|| {
+                    let name = variant.ident.to_string();
+                    match container.rename_all {
+                        Some(case) => case.variant(&name),
+                        None => name,
+                    }
+                });
+                let prefix = match &tagging {
+                    _ if options.untagged => vec![],
+                    Tagging::External => vec![name],
+                    Tagging::Adjacent(content) => vec![content.clone()],
+                    Tagging::Internal | Tagging::Untagged => vec![],
+                };
+                if let Some((_, ty)) = wrapped
+                    .iter()
+                    .find(|(ident, _)| *ident == Some(&variant.ident))
+                {
+                    prefixes.push((pointer(&prefix), *ty));
+                    continue;
+                }
+                if matches!(tagging, Tagging::Internal) && !options.untagged {
+                    if let Fields::Unnamed(unnamed) = &variant.fields {
+                        if let Some(field) = unnamed.unnamed.iter().find(|field| is_pii(field)) {
+                            return Err(unlocated(
+                                field,
+                                "an internally tagged variant merges it with its tag",
+                            ));
+                        }
+                    }
+                }
+                let case = options.rename_all.or(container.rename_all_fields);
+                field_paths(&variant.fields, &prefix, case, &mut paths)?;
+            }
+        }
+        Data::Struct(data_struct) => {
+            if let Some((_, ty)) = wrapped.first() {
+                prefixes.push((String::new(), *ty));
+            } else if container.transparent {
+                // The payload is the one serialized field
+                if data_struct.fields.iter().any(is_pii) {
+                    paths.push(String::new());
+                }
+            } else {
+                field_paths(&data_struct.fields, &[], container.rename_all, &mut paths)?;
+            }
+        }
+        Data::Union(_) => {}
+    }
+
+    if paths.is_empty() && prefixes.is_empty() {
+        return Ok(None);
+    }
+    let wrapped = prefixes.iter().map(|(prefix, ty)| {
+        quote! {
+            paths.extend(
+                <#ty as replay::Event>::pii_paths()
+                    .into_iter()
+                    .map(|path| ::std::format!("{}{}", #prefix, path)),
+            );
+        }
+    });
+    Ok(Some(quote! {
+        fn pii_paths() -> ::std::vec::Vec<::std::string::String> {
+            let mut paths: ::std::vec::Vec<::std::string::String> =
+                ::std::vec![#(::std::string::String::from(#paths)),*];
+            #(#wrapped)*
+            paths
+        }
+    }))
+}
@@ -0,0 +1,152 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{parse_quote, Data, DeriveInput, Fields, LitStr, Type};
+
+use crate::camel_to_kebab;
+
+/// Check that `#[derive(StreamId)]` is on a single-field tuple struct, returning the key type,
+/// and read its optional `#[stream_id(namespace = "...")]`.
+fn options(input: &DeriveInput) -> syn::Result<(&Type, Option<LitStr>)> {
+    let key = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+            _ => return Err(not_a_newtype(input)),
+        },
+        _ => return Err(not_a_newtype(input)),
+    };
+
+    let mut namespace = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("stream_id"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("namespace") {
+                let lit: LitStr = meta.value()?.parse()?;
+                if !is_nid(&lit.value()) {
+                    return Err(syn::Error::new_spanned(lit, INVALID_NID));
+                }
+                namespace = Some(lit);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported stream_id attribute, expected `namespace`"))
+            }
+        })?;
+    }
+    Ok((key, namespace))
+}
+
+fn not_a_newtype(input: &DeriveInput) -> syn::Error {
+    syn::Error::new_spanned(
+        &input.ident,
+        "StreamId can only be derived for a newtype around a key, e.g. `struct OrderId(Uuid);`",
+    )
+}
+
+const INVALID_NID: &str = "a stream id namespace is a URN NID: 2 to 32 letters, digits or \
+                           hyphens, starting and ending with a letter or digit";
+
+/// Whether `namespace` is a URN namespace identifier (RFC 8141): 2 to 32 ASCII letters,
+/// digits and hyphens, not starting or ending with a hyphen.
+fn is_nid(namespace: &str) -> bool {
+    let bytes = namespace.as_bytes();
+    (2..=32).contains(&bytes.len())
+        && bytes
+            .iter()
+            .all(|byte| byte.is_ascii_alphanumeric() || *byte == b'-')
+        && bytes[0] != b'-'
+        && bytes[bytes.len() - 1] != b'-'
+}
+
+pub fn derive_stream_id(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let (key, namespace) = options(&input)?;
+    let namespace = match namespace {
+        Some(namespace) => namespace,
+        None => {
+            let type_name = name.to_string();
+            let base = type_name.strip_suffix("Id").unwrap_or(&type_name);
+            let namespace = camel_to_kebab(base);
+            if !is_nid(&namespace) {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    format!(
+                        "`{namespace}` can't be a URN namespace, set one with \
+                         `#[stream_id(namespace = \"...\")]`: {INVALID_NID}"
+                    ),
+                ));
+            }
+            LitStr::new(&namespace, Span::call_site())
+        }
+    };
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut de_generics = input.generics.clone();
+    de_generics.params.insert(0, parse_quote!('de));
+    de_generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(#key: serde::Deserialize<'de>));
+    let (de_impl_generics, _, de_where_clause) = de_generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The namespace (NID) the id's URN is filed under.
+            pub fn namespace() -> &'static str {
+                #namespace
+            }
+
+            /// The id of `key`, which can't be empty: its URN needs an NSS.
+            pub fn new(key: #key) -> replay::Result<Self> {
+                if key.to_string().is_empty() {
+                    return Err(replay::Error::invalid_input("a stream id key cannot be empty")
+                        .with_operation("new")
+                        .with_context("namespace", #namespace));
+                }
+                Ok(Self(key))
+            }
+        }
+
+        impl #impl_generics From<#name #ty_generics> for urn::Urn #where_clause {
+            fn from(id: #name #ty_generics) -> Self {
+                replay::__private::key_urn(#namespace, &id.0.to_string())
+            }
+        }
+
+        impl #impl_generics std::convert::TryFrom<urn::Urn> for #name #ty_generics #where_clause {
+            type Error = replay::Error;
+
+            fn try_from(urn: urn::Urn) -> Result<Self, Self::Error> {
+                let key = replay::__private::urn_key(#namespace, &urn)?;
+                let key = key.parse::<#key>().map_err(|e| {
+                    replay::Error::invalid_input("URN key is not a valid stream id")
+                        .with_operation("try_from")
+                        .with_context("urn", &urn)
+                        .with_context("error", e)
+                })?;
+                Self::new(key)
+            }
+        }
+
+        impl #impl_generics std::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl #impl_generics serde::Serialize for #name #ty_generics #where_clause {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+
+        // Through `new`, so a payload can't smuggle in an empty key.
+        impl #de_impl_generics serde::Deserialize<'de> for #name #ty_generics #de_where_clause {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let key = <#key as serde::Deserialize<'de>>::deserialize(deserializer)?;
+                Self::new(key).map_err(serde::de::Error::custom)
+            }
+        }
+    })
+}
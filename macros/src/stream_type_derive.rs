@@ -0,0 +1,54 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{DeriveInput, LitStr};
+
+use crate::camel_to_kebab;
+
+/// Read the optional `#[stream_type(name = "...")]` of `#[derive(StreamType)]`.
+fn name(input: &DeriveInput) -> syn::Result<Option<LitStr>> {
+    let mut name = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("stream_type"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                let lit: LitStr = meta.value()?.parse()?;
+                if lit.value().is_empty() {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        "the stream type cannot be empty",
+                    ));
+                }
+                name = Some(lit);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported stream_type attribute, expected `name`"))
+            }
+        })?;
+    }
+    Ok(name)
+}
+
+pub fn derive_stream_type(input: DeriveInput) -> syn::Result<TokenStream> {
+    // Every instantiation of a generic type would be stored under the same stream type.
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "StreamType can't be derived for a generic type, whose instantiations would share \
+             one stream type; implement `stream_type()` by hand",
+        ));
+    }
+
+    let name = &input.ident;
+    let stream_type = self::name(&input)?
+        .unwrap_or_else(|| LitStr::new(&camel_to_kebab(&name.to_string()), Span::call_site()));
+
+    Ok(quote! {
+        impl #name {
+            /// The type this stream's events are stored under.
+            pub const STREAM_TYPE: &'static str = #stream_type;
+        }
+    })
+}
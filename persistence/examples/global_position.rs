@@ -59,8 +59,8 @@ define_aggregate! {
 impl EventStream for User {
     type Event = UserEvent;
 
-    fn stream_type() -> String {
-        "User".to_string()
+    fn stream_type() -> &'static str {
+        "User"
     }
 
     fn apply(&mut self, event: Self::Event) {
@@ -122,8 +122,8 @@ define_aggregate! {
 impl EventStream for BankAccount {
     type Event = BankAccountEvent;
 
-    fn stream_type() -> String {
-        "BankAccount".to_string()
+    fn stream_type() -> &'static str {
+        "BankAccount"
     }
 
     fn apply(&mut self, event: Self::Event) {
@@ -229,8 +229,8 @@ define_aggregate! {
 impl EventStream for PolicyFeeLedger {
     type Event = PolicyFeeLedgerEvent;
 
-    fn stream_type() -> String {
-        "PolicyFeeLedger".to_string()
+    fn stream_type() -> &'static str {
+        "PolicyFeeLedger"
     }
 
     fn apply(&mut self, event: Self::Event) {
@@ -0,0 +1,268 @@
+//! AsyncAPI documents describing the events a service publishes, generated from the event
+//! types themselves so consumers get a machine-readable contract that can't drift from code.
+
+use schemars::generate::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
+
+use replay::{Event, EventStream};
+
+/// An [AsyncAPI 3.0](https://www.asyncapi.com/docs/reference/specification/v3.0.0) document
+/// with a channel per registered stream type and a message per event type.
+///
+/// Messages take their names and versions from [`Event::event_types`] and their payload
+/// schemas from the event's [`JsonSchema`], in the JSON form stores write:
+///
+/// ```rust,ignore
+/// let document = AsyncApi::new("Banking events", "1.4.0")
+///     .with_description("Everything the banking service publishes.")
+///     .with_stream::<BankAccount>()
+///     .with_stream::<Transfer>()
+///     .to_json();
+/// std::fs::write("asyncapi.json", serde_json::to_string_pretty(&document)?)?;
+/// ```
+///
+/// A message's payload is the schema of its enum variant when the event type matches the
+/// variant's serialized name, and the whole event's schema otherwise (e.g. when serde and
+/// `#[event]` rename differently). Shared types are emitted once under
+/// `components/schemas`.
+#[derive(Clone, Debug)]
+pub struct AsyncApi {
+    title: String,
+    version: String,
+    description: Option<String>,
+    channels: Map<String, Value>,
+    operations: Map<String, Value>,
+    generator: SchemaGenerator,
+}
+
+impl AsyncApi {
+    /// An empty document; `version` is the version of the contract, not of AsyncAPI.
+    pub fn new(title: impl Into<String>, version: impl Into<String>) -> Self {
+        let settings = SchemaSettings::draft07().for_serialize().with(|settings| {
+            settings.definitions_path = "/components/schemas".into();
+            settings.meta_schema = None;
+        });
+        AsyncApi {
+            title: title.into(),
+            version: version.into(),
+            description: None,
+            channels: Map::new(),
+            operations: Map::new(),
+            generator: settings.into_generator(),
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Add a channel for the events of `S` streams, addressed by its stream type, and an
+    /// operation sending them.
+    pub fn with_stream<S>(mut self) -> Self
+    where
+        S: EventStream,
+        S::Event: JsonSchema,
+    {
+        let stream_type = S::stream_type();
+        let schema = Value::from(self.generator.subschema_for::<S::Event>());
+        let resolved = self.resolve(&schema);
+
+        let mut messages = Map::new();
+        for (event_type, version) in S::Event::event_types() {
+            let payload = variant_schema(&resolved, event_type).unwrap_or_else(|| schema.clone());
+            messages.insert(
+                event_type.to_string(),
+                json!({
+                    "name": event_type,
+                    "title": event_type,
+                    "contentType": "application/json",
+                    "payload": payload,
+                    "x-event-version": version,
+                }),
+            );
+        }
+        if messages.is_empty() {
+            messages.insert(
+                stream_type.to_string(),
+                json!({
+                    "name": stream_type,
+                    "contentType": "application/json",
+                    "payload": schema,
+                }),
+            );
+        }
+
+        let channel = pointer_escape(stream_type);
+        let references: Vec<Value> = messages
+            .keys()
+            .map(|message| {
+                let message = pointer_escape(message);
+                json!({ "$ref": format!("#/channels/{channel}/messages/{message}") })
+            })
+            .collect();
+        self.channels.insert(
+            stream_type.to_string(),
+            json!({
+                "address": stream_type,
+                "description": format!("Events of `{stream_type}` streams."),
+                "messages": messages,
+            }),
+        );
+        self.operations.insert(
+            format!("publish{stream_type}"),
+            json!({
+                "action": "send",
+                "channel": { "$ref": format!("#/channels/{channel}") },
+                "messages": references,
+            }),
+        );
+        self
+    }
+
+    /// The document as JSON, which AsyncAPI tooling reads like YAML.
+    pub fn to_json(&self) -> Value {
+        let mut info = json!({ "title": self.title, "version": self.version });
+        if let Some(description) = &self.description {
+            info["description"] = json!(description);
+        }
+        let schemas = self.generator.clone().take_definitions(true);
+        json!({
+            "asyncapi": "3.0.0",
+            "info": info,
+            "defaultContentType": "application/json",
+            "channels": self.channels,
+            "operations": self.operations,
+            "components": { "schemas": schemas },
+        })
+    }
+
+    /// The schema a `$ref` into `components/schemas` points at, or `schema` itself.
+    fn resolve(&self, schema: &Value) -> Value {
+        schema
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|reference| reference.strip_prefix("#/components/schemas/"))
+            .and_then(|name| self.generator.definitions().get(name))
+            .cloned()
+            .unwrap_or_else(|| schema.clone())
+    }
+}
+
+/// The schema of the externally tagged variant serialized as `name`: an object with `name`
+/// as its only key, or the string `name` for a unit variant.
+fn variant_schema(schema: &Value, name: &str) -> Option<Value> {
+    let variants = schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(Value::as_array);
+    let is_unit = |variant: &Value| {
+        variant.get("const").and_then(Value::as_str) == Some(name)
+            || variant
+                .get("enum")
+                .and_then(Value::as_array)
+                .is_some_and(|symbols| symbols.iter().any(|symbol| symbol == name))
+    };
+
+    if let Some(variants) = variants {
+        for variant in variants {
+            if variant
+                .pointer(&format!("/properties/{}", pointer_escape(name)))
+                .is_some()
+            {
+                return Some(variant.clone());
+            }
+            if is_unit(variant) {
+                return Some(json!({ "type": "string", "const": name }));
+            }
+        }
+    }
+    is_unit(schema).then(|| json!({ "type": "string", "const": name }))
+}
+
+/// `key` escaped for a JSON pointer.
+fn pointer_escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use replay::{EventStream, WithId};
+    use replay_macros::Event;
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::conformance::ConformanceAccountUrn;
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Event, JsonSchema)]
+    enum LedgerEvent {
+        Opened {
+            owner: String,
+        },
+        #[event(version = 2)]
+        Posted {
+            amount: i64,
+            currency: Currency,
+        },
+        Closed,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq, Debug, JsonSchema)]
+    enum Currency {
+        Eur,
+        Usd,
+    }
+
+    struct Ledger {
+        id: ConformanceAccountUrn,
+    }
+
+    impl WithId for Ledger {
+        type StreamId = ConformanceAccountUrn;
+
+        fn with_id(id: Self::StreamId) -> Self {
+            Ledger { id }
+        }
+
+        fn get_id(&self) -> &Self::StreamId {
+            &self.id
+        }
+    }
+
+    impl EventStream for Ledger {
+        type Event = LedgerEvent;
+
+        fn stream_type() -> &'static str {
+            "Ledger"
+        }
+
+        fn apply(&mut self, _event: Self::Event) {}
+    }
+
+    #[test]
+    fn streams_become_channels_with_a_message_per_event_type() {
+        let document = AsyncApi::new("Ledger events", "1.0.0")
+            .with_stream::<Ledger>()
+            .to_json();
+
+        assert_eq!(document["asyncapi"], "3.0.0");
+        let messages = &document["channels"]["Ledger"]["messages"];
+        let mut names: Vec<_> = messages.as_object().unwrap().keys().collect();
+        names.sort();
+        assert_eq!(names, ["Closed", "Opened", "Posted"]);
+        assert_eq!(messages["Posted"]["x-event-version"], 2);
+        assert_eq!(messages["Opened"]["x-event-version"], 1);
+        assert_eq!(messages["Posted"]["payload"]["required"], json!(["Posted"]));
+        assert_eq!(
+            messages["Closed"]["payload"],
+            json!({ "type": "string", "const": "Closed" })
+        );
+        assert!(document["components"]["schemas"]["Currency"].is_object());
+        assert_eq!(
+            document["operations"]["publishLedger"]["channel"]["$ref"],
+            "#/channels/Ledger"
+        );
+    }
+}
@@ -0,0 +1,776 @@
+//! [Apache Avro](https://avro.apache.org) payloads, with writer schemas resolved against the
+//! store's reader schema on read.
+//!
+//! Schemas, the binary encoding and object containers come from the
+//! [`apache-avro`](https://docs.rs/apache-avro) crate. This module maps event payloads between
+//! JSON and Avro values, and frames each payload so it names the schema it was written with:
+//! the single-object encoding (a fingerprint of the schema), the Confluent Schema Registry
+//! wire format (the schema's registry id), or an object container holding the schema itself.
+//!
+//! JSON payloads map onto Avro the way Avro's own JSON encoding does, so serde's forms fit:
+//! records are objects, enums are symbol strings, and a union whose branches are records is
+//! written `{"Branch": {...}}`, an externally tagged enum. Optional fields are unions with
+//! `null`; a missing field takes its default or `null`.
+//!
+//! A payload read with an older writer schema is turned into JSON with that schema and then
+//! mapped onto the reader schema the same way, which follows Avro's resolution rules: fields
+//! are matched by name or alias, added fields take their defaults, `int` and `long` widen to
+//! `float` and `double`, and unknown enum symbols take the enum's default.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use apache_avro::rabin::Rabin;
+use apache_avro::reader::datum::GenericDatumReader;
+use apache_avro::schema::{Name, RecordSchema, ResolvedSchema};
+use apache_avro::types::Value as Datum;
+use apache_avro::writer::datum::GenericDatumWriter;
+use apache_avro::Schema;
+use serde_json::{Map, Value};
+
+use crate::EventSerializer;
+
+/// First bytes of the single-object encoding, followed by the schema's fingerprint.
+const SINGLE_OBJECT_MARKER: [u8; 2] = [0xC3, 0x01];
+/// First byte of the Confluent wire format, followed by the schema id (big endian).
+const CONFLUENT_MAGIC: u8 = 0;
+/// First bytes of an object container.
+const CONTAINER_MAGIC: [u8; 4] = *b"Obj\x01";
+
+/// An Avro schema with its named types, which references to them resolve to.
+#[derive(Clone, Debug)]
+struct AvroSchema {
+    schema: Schema,
+    names: HashMap<Name, Schema>,
+    /// The schema's 64-bit Rabin fingerprint, as the single-object encoding writes it.
+    fingerprint: u64,
+}
+
+fn invalid_schema(e: apache_avro::Error) -> replay::Error {
+    replay::Error::invalid_input("invalid Avro schema")
+        .with_operation("parse_avro_schema")
+        .with_source(e)
+}
+
+fn mismatch(schema: &Schema) -> replay::Error {
+    replay::Error::invalid_input("event payload doesn't match the Avro schema")
+        .with_operation("serialize_event")
+        .with_context("expected", format!("{schema:?}"))
+}
+
+fn malformed(e: apache_avro::Error) -> replay::Error {
+    replay::Error::internal("malformed Avro event payload")
+        .with_operation("deserialize_event")
+        .with_source(e)
+}
+
+fn truncated() -> replay::Error {
+    replay::Error::internal("malformed Avro event payload").with_operation("deserialize_event")
+}
+
+fn incompatible(e: replay::Error) -> replay::Error {
+    replay::Error::internal("Avro writer schema can't be read with the reader schema")
+        .with_operation("deserialize_event")
+        .with_source(e)
+}
+
+impl AvroSchema {
+    fn parse(definition: &str) -> Result<Self, replay::Error> {
+        Self::new(Schema::parse_str(definition).map_err(invalid_schema)?)
+    }
+
+    fn new(schema: Schema) -> Result<Self, replay::Error> {
+        let names = ResolvedSchema::try_from(&schema)
+            .map_err(invalid_schema)?
+            .get_names()
+            .iter()
+            .map(|(name, schema)| (name.clone(), (*schema).clone()))
+            .collect();
+        let fingerprint = schema.fingerprint::<Rabin>().bytes;
+        let fingerprint = u64::from_le_bytes(fingerprint.try_into().unwrap_or_default());
+        let schema = AvroSchema {
+            schema,
+            names,
+            fingerprint,
+        };
+        // apache-avro decodes nested values recursively, with no depth limit, so a payload
+        // of a recursive type could nest deep enough to overflow the stack.
+        if let Some(name) = schema.recursive_type(&schema.schema, &mut Vec::new()) {
+            return Err(
+                replay::Error::invalid_input("recursive Avro schemas aren't supported")
+                    .with_operation("parse_avro_schema")
+                    .with_context("type", name.fullname(None)),
+            );
+        }
+        Ok(schema)
+    }
+
+    /// The first named type in `schema` that contains itself, directly or through other
+    /// types; `path` holds the records being visited.
+    fn recursive_type<'s>(
+        &'s self,
+        schema: &'s Schema,
+        path: &mut Vec<&'s Name>,
+    ) -> Option<&'s Name> {
+        match schema {
+            Schema::Ref { name } if path.contains(&name) => Some(name),
+            Schema::Ref { name } => self
+                .names
+                .get(name)
+                .and_then(|schema| self.recursive_type(schema, path)),
+            Schema::Record(record) if path.contains(&&record.name) => Some(&record.name),
+            Schema::Record(record) => {
+                path.push(&record.name);
+                let found = record
+                    .fields
+                    .iter()
+                    .find_map(|field| self.recursive_type(&field.schema, path));
+                path.pop();
+                found
+            }
+            Schema::Union(union) => union
+                .variants()
+                .iter()
+                .find_map(|branch| self.recursive_type(branch, path)),
+            Schema::Array(array) => self.recursive_type(&array.items, path),
+            Schema::Map(map) => self.recursive_type(&map.types, path),
+            _ => None,
+        }
+    }
+
+    /// `schema`, with a reference to a named type replaced by its definition.
+    fn resolve<'s>(&'s self, schema: &'s Schema) -> &'s Schema {
+        match schema {
+            Schema::Ref { name } => self.names.get(name).unwrap_or(schema),
+            schema => schema,
+        }
+    }
+
+    /// The record `schema` is, if any.
+    fn record<'s>(&'s self, schema: &'s Schema) -> Option<&'s RecordSchema> {
+        match self.resolve(schema) {
+            Schema::Record(record) => Some(record),
+            _ => None,
+        }
+    }
+
+    /// `value` as an Avro value of `schema`. When `reading`, `value` was written with another
+    /// schema, so an unknown enum symbol takes the enum's default instead of failing.
+    fn datum(&self, value: &Value, schema: &Schema, reading: bool) -> Result<Datum, replay::Error> {
+        Ok(match (self.resolve(schema), value) {
+            (Schema::Record(record), Value::Object(object)) => Datum::Record(
+                record
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let value = std::iter::once(&field.name)
+                            .chain(&field.aliases)
+                            .find_map(|name| object.get(name))
+                            .or(field.default.as_ref());
+                        let datum = match value {
+                            Some(value) => self.datum(value, &field.schema, reading)?,
+                            None => self
+                                .datum(&Value::Null, &field.schema, reading)
+                                .map_err(|e| e.with_context("missing_field", &field.name))?,
+                        };
+                        Ok((field.name.clone(), datum))
+                    })
+                    .collect::<Result<_, replay::Error>>()?,
+            ),
+            (Schema::Enum(symbols), Value::String(symbol)) => {
+                let symbol = match &symbols.default {
+                    Some(default) if reading && !symbols.symbols.contains(symbol) => default,
+                    _ => symbol,
+                };
+                let index = symbols
+                    .symbols
+                    .iter()
+                    .position(|s| s == symbol)
+                    .ok_or_else(|| mismatch(schema).with_context("symbol", symbol))?;
+                Datum::Enum(index as u32, symbol.clone())
+            }
+            (Schema::Union(union), value) => {
+                let branches = union.variants();
+                // `{"Record": {...}}` selects a record branch by name, anything else the
+                // first branch it fits.
+                if let Value::Object(object) = value {
+                    if let (1, Some((key, inner))) = (object.len(), object.iter().next()) {
+                        let tagged = branches.iter().position(|branch| {
+                            self.record(branch).is_some_and(|record| {
+                                record.name.name() == key || &record.name.fullname(None) == key
+                            })
+                        });
+                        if let Some(index) = tagged {
+                            let datum = self.datum(inner, &branches[index], reading)?;
+                            return Ok(Datum::Union(index as u32, Box::new(datum)));
+                        }
+                    }
+                }
+                branches
+                    .iter()
+                    .enumerate()
+                    .find_map(|(index, branch)| {
+                        let datum = self.datum(value, branch, reading).ok()?;
+                        Some(Datum::Union(index as u32, Box::new(datum)))
+                    })
+                    .ok_or_else(|| mismatch(schema))?
+            }
+            (Schema::Array(array), Value::Array(items)) => Datum::Array(
+                items
+                    .iter()
+                    .map(|item| self.datum(item, &array.items, reading))
+                    .collect::<Result<_, _>>()?,
+            ),
+            (Schema::Map(map), Value::Object(entries)) => Datum::Map(
+                entries
+                    .iter()
+                    .map(|(key, value)| Ok((key.clone(), self.datum(value, &map.types, reading)?)))
+                    .collect::<Result<_, replay::Error>>()?,
+            ),
+            (Schema::Record(_) | Schema::Enum(_) | Schema::Array(_) | Schema::Map(_), _) => {
+                return Err(mismatch(schema))
+            }
+            // Primitives, fixed and logical types: the library converts the JSON value and
+            // checks it against the schema, widening numbers as resolution allows.
+            (leaf, value) => Datum::try_from(value.clone())
+                .and_then(|datum| datum.resolve_with_names(leaf, &self.names))
+                .map_err(|_| mismatch(leaf))?,
+        })
+    }
+
+    /// `datum`, a value of `schema`, as JSON.
+    fn json(&self, datum: Datum, schema: &Schema) -> Result<Value, replay::Error> {
+        Ok(match (self.resolve(schema), datum) {
+            (Schema::Record(record), Datum::Record(fields)) => Value::Object(
+                record
+                    .fields
+                    .iter()
+                    .zip(fields)
+                    .map(|(field, (name, datum))| Ok((name, self.json(datum, &field.schema)?)))
+                    .collect::<Result<Map<_, _>, replay::Error>>()?,
+            ),
+            (Schema::Union(union), Datum::Union(index, datum)) => {
+                let branches = union.variants();
+                let branch = branches
+                    .get(index as usize)
+                    .ok_or_else(|| truncated().with_context("union_branch", index))?;
+                let value = self.json(*datum, branch)?;
+                let non_null = branches.iter().filter(|b| **b != Schema::Null).count();
+                match self.record(branch) {
+                    Some(record) if non_null > 1 => {
+                        Value::Object(Map::from_iter([(record.name.name().to_string(), value)]))
+                    }
+                    _ => value,
+                }
+            }
+            (Schema::Array(array), Datum::Array(items)) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| self.json(item, &array.items))
+                    .collect::<Result<_, _>>()?,
+            ),
+            (Schema::Map(map), Datum::Map(entries)) => Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, datum)| Ok((key, self.json(datum, &map.types)?)))
+                    .collect::<Result<_, replay::Error>>()?,
+            ),
+            (_, datum) => Value::try_from(datum).map_err(|e| {
+                replay::Error::internal("Avro payload holds a value JSON can't represent")
+                    .with_operation("deserialize_event")
+                    .with_source(e)
+            })?,
+        })
+    }
+}
+
+/// How an [`AvroSerializer`] names the writer schema of the payloads it writes.
+#[derive(Clone, Copy, Debug)]
+enum Framing {
+    /// Avro's single-object encoding: the schema's fingerprint.
+    SingleObject,
+    /// Confluent's wire format: the schema's Schema Registry id.
+    Confluent(u32),
+    /// An object container holding the schema itself.
+    Container,
+}
+
+/// [Apache Avro](https://avro.apache.org), encoding `avro`, for events with an Avro schema.
+///
+/// Payloads are written with the store's schema and name it, by fingerprint, Schema Registry
+/// id or embedded copy. Reads decode with the schema a payload names, which must be the
+/// store's or one registered with [`with_writer_schema`](Self::with_writer_schema) or
+/// [`with_registered_schema`](Self::with_registered_schema), and resolve it to the store's
+/// schema, so events written before a compatible schema change read in the current shape.
+///
+/// A payload that doesn't decode is an error, never a panic. How much a corrupt or hostile
+/// payload can make a read allocate is capped by `apache-avro`'s
+/// [`max_allocation_bytes`](apache_avro::max_allocation_bytes), and recursive schemas are
+/// rejected, so a payload can't nest deep enough to overflow the stack.
+///
+/// ```rust,ignore
+/// let store = PostgresEventStore::new(pool).with_serializer(
+///     AvroSerializer::new(include_str!("../avro/account_event.v2.avsc"))?
+///         .with_writer_schema(include_str!("../avro/account_event.v1.avsc"))?,
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct AvroSerializer {
+    schema: Arc<AvroSchema>,
+    framing: Framing,
+    by_fingerprint: HashMap<u64, Arc<AvroSchema>>,
+    by_id: HashMap<u32, Arc<AvroSchema>>,
+}
+
+impl AvroSerializer {
+    /// Payloads in the single-object encoding, naming `schema` by its fingerprint.
+    pub fn new(schema: &str) -> Result<Self, replay::Error> {
+        Self::with_framing(schema, Framing::SingleObject)
+    }
+
+    /// Payloads in the Confluent Schema Registry wire format, naming `schema` by the id the
+    /// registry assigned it. The application fetches or registers the schema itself.
+    pub fn confluent(schema_id: u32, schema: &str) -> Result<Self, replay::Error> {
+        let mut serializer = Self::with_framing(schema, Framing::Confluent(schema_id))?;
+        serializer
+            .by_id
+            .insert(schema_id, Arc::clone(&serializer.schema));
+        Ok(serializer)
+    }
+
+    /// Payloads in an object container embedding `schema`, readable without any registry at
+    /// the cost of carrying the schema in every event.
+    pub fn embedding(schema: &str) -> Result<Self, replay::Error> {
+        Self::with_framing(schema, Framing::Container)
+    }
+
+    fn with_framing(schema: &str, framing: Framing) -> Result<Self, replay::Error> {
+        let schema = Arc::new(AvroSchema::parse(schema)?);
+        Ok(AvroSerializer {
+            by_fingerprint: HashMap::from([(schema.fingerprint, Arc::clone(&schema))]),
+            by_id: HashMap::new(),
+            schema,
+            framing,
+        })
+    }
+
+    /// Also read payloads written with `schema`, e.g. the previous version of the store's.
+    pub fn with_writer_schema(mut self, schema: &str) -> Result<Self, replay::Error> {
+        let schema = AvroSchema::parse(schema)?;
+        self.by_fingerprint
+            .insert(schema.fingerprint, Arc::new(schema));
+        Ok(self)
+    }
+
+    /// Also read payloads written with `schema` under Schema Registry id `schema_id`.
+    pub fn with_registered_schema(
+        mut self,
+        schema_id: u32,
+        schema: &str,
+    ) -> Result<Self, replay::Error> {
+        let schema = Arc::new(AvroSchema::parse(schema)?);
+        self.by_fingerprint
+            .insert(schema.fingerprint, Arc::clone(&schema));
+        self.by_id.insert(schema_id, schema);
+        Ok(self)
+    }
+
+    fn unknown_schema(&self, key: &'static str, value: impl ToString) -> replay::Error {
+        replay::Error::internal(
+            "Avro event payload was written with an unknown schema; register it with the serializer",
+        )
+        .with_operation("deserialize_event")
+        .with_context(key, value.to_string())
+    }
+
+    /// `datum`, written with `writer`, as JSON in the shape of the store's schema.
+    fn read(&self, writer: &AvroSchema, datum: Datum) -> Result<Value, replay::Error> {
+        let value = writer.json(datum, &writer.schema)?;
+        if writer.fingerprint == self.schema.fingerprint {
+            return Ok(value);
+        }
+        let datum = self
+            .schema
+            .datum(&value, &self.schema.schema, true)
+            .map_err(incompatible)?;
+        self.schema.json(datum, &self.schema.schema)
+    }
+
+    /// `body`, a datum written with `writer`, as JSON in the shape of the store's schema.
+    fn decode(&self, writer: &AvroSchema, mut body: &[u8]) -> Result<Value, replay::Error> {
+        let datum = GenericDatumReader::builder(&writer.schema)
+            .build()
+            .and_then(|reader| reader.read_value(&mut body))
+            .map_err(malformed)?;
+        self.read(writer, datum)
+    }
+}
+
+/// The sync marker of a container written with `schema`: fixed per schema, so a payload's
+/// bytes only depend on its contents.
+fn sync_marker(schema: &AvroSchema) -> [u8; 16] {
+    let mut marker = [0; 16];
+    marker[..8].copy_from_slice(&schema.fingerprint.to_le_bytes());
+    marker[8..].copy_from_slice(&(!schema.fingerprint).to_le_bytes());
+    marker
+}
+
+impl EventSerializer for AvroSerializer {
+    fn encoding(&self) -> &'static str {
+        "avro"
+    }
+
+    fn serialize(&self, data: &Value) -> Result<Vec<u8>, replay::Error> {
+        let datum = self.schema.datum(data, &self.schema.schema, false)?;
+        let encoding_error = |e: apache_avro::Error| {
+            replay::Error::internal("failed to encode event payload as Avro")
+                .with_operation("serialize_event")
+                .with_source(e)
+        };
+
+        let mut out = Vec::new();
+        match self.framing {
+            Framing::SingleObject => {
+                out.extend_from_slice(&SINGLE_OBJECT_MARKER);
+                out.extend_from_slice(&self.schema.fingerprint.to_le_bytes());
+            }
+            Framing::Confluent(schema_id) => {
+                out.push(CONFLUENT_MAGIC);
+                out.extend_from_slice(&schema_id.to_be_bytes());
+            }
+            Framing::Container => {
+                let mut writer = apache_avro::Writer::builder()
+                    .schema(&self.schema.schema)
+                    .writer(out)
+                    .marker(sync_marker(&self.schema))
+                    .build()
+                    .map_err(encoding_error)?;
+                writer.append_value(datum).map_err(encoding_error)?;
+                return writer.into_inner().map_err(encoding_error);
+            }
+        }
+        GenericDatumWriter::builder(&self.schema.schema)
+            .build()
+            .and_then(|writer| writer.write_value(&mut out, datum))
+            .map_err(encoding_error)?;
+        Ok(out)
+    }
+
+    fn deserialize(&self, bytes: &[u8]) -> Result<Value, replay::Error> {
+        if let Some(rest) = bytes.strip_prefix(&SINGLE_OBJECT_MARKER) {
+            let (fingerprint, body) = rest.split_first_chunk::<8>().ok_or_else(truncated)?;
+            let fingerprint = u64::from_le_bytes(*fingerprint);
+            let writer = self
+                .by_fingerprint
+                .get(&fingerprint)
+                .ok_or_else(|| self.unknown_schema("fingerprint", format!("{fingerprint:016x}")))?;
+            self.decode(writer, body)
+        } else if bytes.starts_with(&CONTAINER_MAGIC) {
+            let mut reader = apache_avro::Reader::builder(bytes)
+                .build()
+                .map_err(malformed)?;
+            let writer = AvroSchema::new(reader.writer_schema().clone()).map_err(|e| {
+                replay::Error::internal("Avro container embeds an invalid schema")
+                    .with_operation("deserialize_event")
+                    .with_source(e)
+            })?;
+            let datum = reader.next().ok_or_else(truncated)?.map_err(malformed)?;
+            self.read(&writer, datum)
+        } else if let Some(rest) = bytes.strip_prefix(&[CONFLUENT_MAGIC]) {
+            let (schema_id, body) = rest.split_first_chunk::<4>().ok_or_else(truncated)?;
+            let schema_id = u32::from_be_bytes(*schema_id);
+            let writer = self
+                .by_id
+                .get(&schema_id)
+                .ok_or_else(|| self.unknown_schema("schema_id", schema_id))?;
+            self.decode(writer, body)
+        } else {
+            Err(truncated())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use super::*;
+
+    const ACCOUNT_EVENT_V1: &str = r#"[
+        {
+            "type": "record", "name": "Deposited", "namespace": "bank.v1",
+            "fields": [
+                { "name": "amount", "type": "long" },
+                { "name": "currency", "type": { "type": "enum", "name": "Currency", "symbols": ["EUR", "USD"] } }
+            ]
+        },
+        {
+            "type": "record", "name": "Withdrawn", "namespace": "bank.v1",
+            "fields": [{ "name": "amount", "type": "long" }]
+        }
+    ]"#;
+
+    const ACCOUNT_EVENT_V2: &str = r#"[
+        {
+            "type": "record", "name": "Deposited", "namespace": "bank.v1",
+            "fields": [
+                { "name": "amount", "type": "double" },
+                { "name": "currency", "type": { "type": "enum", "name": "Currency", "symbols": ["EUR", "GBP"], "default": "EUR" } },
+                { "name": "reference", "type": ["null", "string"], "default": null }
+            ]
+        },
+        {
+            "type": "record", "name": "Withdrawn", "namespace": "bank.v1",
+            "fields": [{ "name": "amount", "type": "double" }]
+        }
+    ]"#;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "UPPERCASE")]
+    enum Currency {
+        Eur,
+        Gbp,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum AccountEvent {
+        Deposited {
+            amount: f64,
+            currency: Currency,
+            reference: Option<String>,
+        },
+        Withdrawn {
+            amount: f64,
+        },
+    }
+
+    #[test]
+    fn serde_enums_round_trip() {
+        let serializer = AvroSerializer::new(ACCOUNT_EVENT_V2).unwrap();
+        let events = [
+            AccountEvent::Deposited {
+                amount: 12.5,
+                currency: Currency::Gbp,
+                reference: Some("inv-1".to_string()),
+            },
+            AccountEvent::Deposited {
+                amount: 3.0,
+                currency: Currency::Eur,
+                reference: None,
+            },
+            AccountEvent::Withdrawn { amount: 7.25 },
+        ];
+
+        for event in events {
+            let bytes = serializer
+                .serialize(&serde_json::to_value(&event).unwrap())
+                .unwrap();
+            assert_eq!(&bytes[..2], &SINGLE_OBJECT_MARKER);
+            let value = serializer.deserialize(&bytes).unwrap();
+            assert_eq!(
+                serde_json::from_value::<AccountEvent>(value).unwrap(),
+                event
+            );
+        }
+    }
+
+    #[test]
+    fn older_payloads_resolve_to_the_current_schema() {
+        let v1 = AvroSerializer::new(ACCOUNT_EVENT_V1).unwrap();
+        let old = v1
+            .serialize(&json!({ "Deposited": { "amount": 10, "currency": "USD" } }))
+            .unwrap();
+
+        assert_eq!(
+            AvroSerializer::new(ACCOUNT_EVENT_V2)
+                .unwrap()
+                .deserialize(&old)
+                .unwrap_err()
+                .kind(),
+            replay::ErrorKind::Internal
+        );
+
+        let v2 = AvroSerializer::new(ACCOUNT_EVENT_V2)
+            .unwrap()
+            .with_writer_schema(ACCOUNT_EVENT_V1)
+            .unwrap();
+        assert_eq!(
+            v2.deserialize(&old).unwrap(),
+            json!({ "Deposited": { "amount": 10.0, "currency": "EUR", "reference": null } })
+        );
+
+        // Newer payloads don't read with the older schema: `double` doesn't narrow to `long`.
+        let new = v2
+            .serialize(
+                &json!({ "Deposited": { "amount": 4.0, "currency": "EUR", "reference": "inv-2" } }),
+            )
+            .unwrap();
+        let v1 = v1.with_writer_schema(ACCOUNT_EVENT_V2).unwrap();
+        assert!(
+            v1.deserialize(&new).is_err(),
+            "double doesn't narrow to long"
+        );
+    }
+
+    #[test]
+    fn union_branches_resolve_by_record_name() {
+        // Both records have the same fields, so only their names tell them apart.
+        let v1 = r#"[
+            { "type": "record", "name": "Deposited", "fields": [{ "name": "amount", "type": "long" }] },
+            { "type": "record", "name": "Withdrawn", "fields": [{ "name": "amount", "type": "long" }] }
+        ]"#;
+        let v2 = r#"[
+            { "type": "record", "name": "Deposited", "fields": [{ "name": "amount", "type": "double" }] },
+            { "type": "record", "name": "Withdrawn", "fields": [{ "name": "amount", "type": "double" }] }
+        ]"#;
+        let old = AvroSerializer::new(v1)
+            .unwrap()
+            .serialize(&json!({ "Withdrawn": { "amount": 3 } }))
+            .unwrap();
+
+        let v2 = AvroSerializer::new(v2)
+            .unwrap()
+            .with_writer_schema(v1)
+            .unwrap();
+        assert_eq!(
+            v2.deserialize(&old).unwrap(),
+            json!({ "Withdrawn": { "amount": 3.0 } })
+        );
+    }
+
+    #[test]
+    fn confluent_payloads_name_their_registry_id() {
+        let v1 = AvroSerializer::confluent(7, ACCOUNT_EVENT_V1).unwrap();
+        let bytes = v1
+            .serialize(&json!({ "Withdrawn": { "amount": 5 } }))
+            .unwrap();
+        assert_eq!(&bytes[..5], &[0, 0, 0, 0, 7]);
+
+        let v2 = AvroSerializer::confluent(8, ACCOUNT_EVENT_V2)
+            .unwrap()
+            .with_registered_schema(7, ACCOUNT_EVENT_V1)
+            .unwrap();
+        assert_eq!(
+            v2.deserialize(&bytes).unwrap(),
+            json!({ "Withdrawn": { "amount": 5.0 } })
+        );
+        assert!(AvroSerializer::confluent(8, ACCOUNT_EVENT_V2)
+            .unwrap()
+            .deserialize(&bytes)
+            .is_err());
+    }
+
+    #[test]
+    fn embedded_schemas_need_no_registration() {
+        let v1 = AvroSerializer::embedding(ACCOUNT_EVENT_V1).unwrap();
+        let bytes = v1
+            .serialize(&json!({ "Deposited": { "amount": 1, "currency": "EUR" } }))
+            .unwrap();
+        assert_eq!(&bytes[..4], b"Obj\x01");
+        assert_eq!(
+            bytes,
+            v1.serialize(&json!({ "Deposited": { "amount": 1, "currency": "EUR" } }))
+                .unwrap(),
+            "the sync marker is fixed per schema"
+        );
+
+        let v2 = AvroSerializer::new(ACCOUNT_EVENT_V2).unwrap();
+        assert_eq!(
+            v2.deserialize(&bytes).unwrap(),
+            json!({ "Deposited": { "amount": 1.0, "currency": "EUR", "reference": null } })
+        );
+    }
+
+    #[test]
+    fn payloads_must_match_the_schema() {
+        let serializer = AvroSerializer::new(ACCOUNT_EVENT_V2).unwrap();
+        let err = serializer
+            .serialize(&json!({ "Deposited": { "amount": "ten", "currency": "EUR" } }))
+            .unwrap_err();
+        assert_eq!(err.kind(), replay::ErrorKind::InvalidInput);
+        let err = serializer
+            .serialize(&json!({ "Deposited": { "amount": 1.0, "currency": "USD" } }))
+            .unwrap_err();
+        assert_eq!(err.kind(), replay::ErrorKind::InvalidInput);
+        assert!(AvroSerializer::new(r#"{ "type": "record", "name": "Broken" }"#).is_err());
+    }
+
+    /// A single-object payload of `body` for `serializer`'s schema.
+    fn single_object(serializer: &AvroSerializer, body: &[u8]) -> Vec<u8> {
+        let mut out = SINGLE_OBJECT_MARKER.to_vec();
+        out.extend_from_slice(&serializer.schema.fingerprint.to_le_bytes());
+        out.extend_from_slice(body);
+        out
+    }
+
+    /// `value` zig-zag encoded, as Avro writes longs.
+    fn long(value: i64) -> Vec<u8> {
+        GenericDatumWriter::builder(&Schema::Long)
+            .build()
+            .and_then(|writer| writer.write_value_to_vec(value))
+            .unwrap()
+    }
+
+    #[test]
+    fn corrupt_payloads_are_errors() {
+        let longs = AvroSerializer::new(r#"{ "type": "array", "items": "long" }"#).unwrap();
+        assert!(longs
+            .deserialize(&single_object(&longs, &long(i64::MAX)))
+            .is_err());
+        assert!(longs.deserialize(&single_object(&longs, &[])).is_err());
+
+        // Nulls take no bytes, so a few bytes could otherwise claim billions of them.
+        let nulls = AvroSerializer::new(r#"{ "type": "array", "items": "null" }"#).unwrap();
+        assert!(nulls
+            .deserialize(&single_object(&nulls, &long(1 << 40)))
+            .is_err());
+        let mut body = long(3);
+        body.push(0);
+        assert_eq!(
+            nulls.deserialize(&single_object(&nulls, &body)).unwrap(),
+            json!([null, null, null])
+        );
+
+        assert!(nulls.deserialize(b"Obj\x01").is_err());
+        assert!(nulls.deserialize(&[0xC3, 0x01, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn recursive_schemas_are_rejected() {
+        let list = r#"{
+            "type": "record", "name": "Node",
+            "fields": [{ "name": "next", "type": ["null", "Node"] }]
+        }"#;
+        let err = AvroSerializer::new(list).unwrap_err();
+        assert!(err.context().contains(&("type", "Node".to_string())));
+
+        // An embedded schema can't nest a read without end either.
+        let schema = r#"{
+            "type": "record", "name": "Loop",
+            "fields": [{ "name": "next", "type": "Loop" }]
+        }"#;
+        let serializer = AvroSerializer::new(ACCOUNT_EVENT_V2).unwrap();
+        let mut payload = CONTAINER_MAGIC.to_vec();
+        payload.extend(long(1));
+        for bytes in [&b"avro.schema"[..], schema.as_bytes()] {
+            payload.extend(long(bytes.len() as i64));
+            payload.extend_from_slice(bytes);
+        }
+        payload.push(0);
+        payload.extend_from_slice(&[0; 16]);
+        payload.extend(long(1));
+        payload.extend(long(0));
+        payload.extend_from_slice(&[0; 16]);
+
+        assert!(serializer.deserialize(&payload).is_err());
+    }
+
+    #[test]
+    fn fingerprints_follow_the_specification() {
+        assert_eq!(
+            AvroSchema::parse(r#""null""#).unwrap().fingerprint,
+            0x63dd_24e7_cc25_8f8a
+        );
+    }
+}
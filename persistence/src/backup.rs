@@ -0,0 +1,372 @@
+//! Consistent backups with point-in-time restore.
+//!
+//! A backup is NDJSON like [`export_events`](crate::export_events), led by a
+//! [`BackupManifest`] line and with every event carrying its source `global_position`:
+//!
+//! ```json
+//! {"stream_type":"BankAccount","position":1042,"created":"2024-05-31T12:00:00Z","checkpoints":{"notify-owner":1040}}
+//! {"global_position":7,"id":"0190c5b4-...","stream_id":"urn:bank-account:42","type":"Deposited","version":2,"created":"2024-05-31T11:58:00Z","metadata":{"actor":"ada"},"data":{"Deposited":{"amount":10}}}
+//! ```
+
+use std::collections::BTreeMap;
+use std::future::Future;
+
+use chrono::{DateTime, Utc};
+use futures::{stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufWriter};
+use uuid::Uuid;
+
+use replay::EventStream;
+
+use crate::ndjson::io_error;
+use crate::persisted_event::AnyEvent;
+use crate::{BulkImport, InMemoryEventStore, NdjsonEvent, PostgresEventStore, StreamFilter};
+
+/// Stores [`backup`] reads from and [`restore`] writes to.
+pub trait BackupStore: BulkImport {
+    /// The highest global position below which no event can still appear, so reading up to
+    /// it always returns the same events.
+    fn consistent_position(&self) -> impl Future<Output = Result<i64, replay::Error>> + Send;
+
+    /// The global position each policy has handled events up to, by policy name.
+    fn checkpoints(
+        &self,
+    ) -> impl Future<Output = Result<BTreeMap<String, i64>, replay::Error>> + Send {
+        async { Ok(BTreeMap::new()) }
+    }
+
+    /// Move the named policies back to the given positions; a policy already behind one
+    /// stays where it is.
+    fn restore_checkpoints(
+        &self,
+        checkpoints: &BTreeMap<String, i64>,
+    ) -> impl Future<Output = Result<(), replay::Error>> + Send {
+        let _ = checkpoints;
+        async { Ok(()) }
+    }
+}
+
+impl BackupStore for InMemoryEventStore {
+    async fn consistent_position(&self) -> Result<i64, replay::Error> {
+        Ok(self.head_position())
+    }
+}
+
+/// Checkpoints are the `policy_cursors` of the [`PolicyRunner`](crate::PolicyRunner).
+impl BackupStore for PostgresEventStore {
+    async fn consistent_position(&self) -> Result<i64, replay::Error> {
+        self.contiguous_high_water_mark().await
+    }
+
+    async fn checkpoints(&self) -> Result<BTreeMap<String, i64>, replay::Error> {
+        let rows: Vec<(String, i64)> = sqlx::query_as("SELECT name, position FROM policy_cursors")
+            .fetch_all(self.pool())
+            .await
+            .map_err(|e| self.map_db_error(e).with_operation("checkpoints"))?;
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn restore_checkpoints(
+        &self,
+        checkpoints: &BTreeMap<String, i64>,
+    ) -> Result<(), replay::Error> {
+        let (names, positions): (Vec<String>, Vec<i64>) = checkpoints.clone().into_iter().unzip();
+        sqlx::query(
+            "INSERT INTO policy_cursors (name, position, updated_at)
+             SELECT name, position, now() FROM UNNEST($1::text[], $2::bigint[]) AS c(name, position)
+             ON CONFLICT (name) DO UPDATE
+                SET position = LEAST(policy_cursors.position, EXCLUDED.position), updated_at = now()",
+        )
+        .bind(names)
+        .bind(positions)
+        .execute(self.pool())
+        .await
+        .map_err(|e| self.map_db_error(e).with_operation("restore_checkpoints"))?;
+        Ok(())
+    }
+}
+
+/// The first line of a [`backup`]: the cut it was taken at.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// The stream type whose events follow.
+    pub stream_type: String,
+    /// The source global position the backup was cut at; it holds every event up to it.
+    pub position: i64,
+    pub created: DateTime<Utc>,
+    /// Policy checkpoints at the cut, as source global positions.
+    #[serde(default)]
+    pub checkpoints: BTreeMap<String, i64>,
+}
+
+/// What a [`restore`] did.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RestoreReport {
+    /// Events imported.
+    pub restored: u64,
+    /// Source global position of the last event imported; `0` when none was.
+    pub position: i64,
+    /// The policy checkpoints written to the target, as target global positions.
+    pub checkpoints: BTreeMap<String, i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BackupEvent {
+    global_position: i64,
+    #[serde(flatten)]
+    event: NdjsonEvent,
+}
+
+/// Write a consistent backup of the `S` streams of `store` to `writer`, archived
+/// (compacted) events included, and return its manifest.
+///
+/// The backup is cut at the store's [`consistent_position`](BackupStore::consistent_position)
+/// when it starts: appends made while it runs are left out, and the policy checkpoints it
+/// records never point past the cut. Back up one stream type per file, as for
+/// [`export_events`](crate::export_events).
+///
+/// ```rust,ignore
+/// let file = tokio::fs::File::create("bank-accounts.backup").await?;
+/// let manifest = backup::<BankAccount, _, _>(&store, file).await?;
+/// tracing::info!(position = manifest.position, "backup taken");
+/// ```
+pub async fn backup<S, Store, W>(store: &Store, writer: W) -> Result<BackupManifest, replay::Error>
+where
+    S: EventStream,
+    Store: BackupStore,
+    W: AsyncWrite + Unpin + Send,
+{
+    // Checkpoints first: they only move forward, so none can pass a cut taken after them.
+    let checkpoints = store.checkpoints().await?;
+    let position = store.consistent_position().await?;
+    let manifest = BackupManifest {
+        stream_type: S::stream_type().to_string(),
+        position,
+        created: Utc::now(),
+        checkpoints: checkpoints
+            .into_iter()
+            .map(|(name, checkpoint)| (name, checkpoint.min(position)))
+            .collect(),
+    };
+
+    let mut writer = BufWriter::new(writer);
+    write_line(&mut writer, &manifest).await?;
+    let events = store
+        .stream_events::<AnyEvent>(StreamFilter::for_stream_type::<S>())
+        .into_stream();
+    futures::pin_mut!(events);
+    while let Some(event) = events.try_next().await? {
+        if event.global_position > position {
+            break;
+        }
+        let event = BackupEvent {
+            global_position: event.global_position,
+            event: NdjsonEvent::from_persisted(event),
+        };
+        write_line(&mut writer, &event).await?;
+    }
+    writer.flush().await.map_err(io_error)?;
+    Ok(manifest)
+}
+
+/// Restore a [`backup`] of `S` streams into `store`, up to the source global position
+/// `up_to_position` when given (point-in-time restore), or the whole backup otherwise.
+///
+/// Events keep their ids, versions, timestamps and metadata, and are imported in one
+/// [`bulk_import`](BulkImport::bulk_import), so nothing is restored when a line is invalid
+/// or an event already exists. They get new global positions, and the backed-up policy
+/// checkpoints are translated to them: each policy resumes after the last restored event it
+/// had handled. Restoring several stream types leaves a policy at the earliest of those
+/// points, so it may handle events again but never skips one.
+///
+/// ```rust,ignore
+/// let file = tokio::io::BufReader::new(tokio::fs::File::open("bank-accounts.backup").await?);
+/// let report = restore::<BankAccount, _, _>(&store, file, Some(position_before_incident)).await?;
+/// ```
+pub async fn restore<S, Store, R>(
+    store: &Store,
+    reader: R,
+    up_to_position: Option<i64>,
+) -> Result<RestoreReport, replay::Error>
+where
+    S: EventStream,
+    Store: BackupStore,
+    R: AsyncBufRead + Unpin + Send,
+{
+    let mut lines = reader.lines();
+    let manifest: BackupManifest = match lines.next_line().await.map_err(io_error)? {
+        Some(line) => parse_line(&line, 1)?,
+        None => {
+            return Err(replay::Error::invalid_input("backup is empty").with_operation("restore"))
+        }
+    };
+    if manifest.stream_type != S::stream_type() {
+        return Err(
+            replay::Error::invalid_input("backup is of another stream type")
+                .with_operation("restore")
+                .with_context("stream_type", &manifest.stream_type),
+        );
+    }
+    let up_to = up_to_position.map_or(manifest.position, |p| p.min(manifest.position));
+    let checkpoints: BTreeMap<&str, i64> = manifest
+        .checkpoints
+        .iter()
+        .map(|(name, checkpoint)| (name.as_str(), (*checkpoint).min(up_to)))
+        .collect();
+
+    // For each policy, the id of the last event it had handled: its position in the target.
+    let mut handled: BTreeMap<&str, Uuid> = BTreeMap::new();
+    let mut events = Vec::new();
+    let mut position = 0;
+    let mut number = 1u64;
+    while let Some(line) = lines.next_line().await.map_err(io_error)? {
+        number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event: BackupEvent = parse_line(&line, number)?;
+        if event.global_position > up_to {
+            break;
+        }
+        position = event.global_position;
+        for (name, _) in checkpoints.iter().filter(|(_, c)| position <= **c) {
+            handled.insert(*name, event.event.id);
+        }
+        events.push(
+            event
+                .event
+                .into_persisted::<S::Event>()
+                .map_err(|e| e.with_operation("restore").with_context("line", number))?,
+        );
+    }
+
+    let head = store.consistent_position().await?;
+    let restored = store
+        .bulk_import::<S, _>(stream::iter(events.into_iter().map(Ok)))
+        .await
+        .map_err(|e| e.with_operation("restore"))?;
+
+    let mut targets: BTreeMap<Uuid, i64> = handled.values().map(|id| (*id, head)).collect();
+    if !targets.is_empty() {
+        let filter =
+            StreamFilter::for_stream_type::<S>().and(StreamFilter::after_global_position(head));
+        let imported = store.stream_events::<AnyEvent>(filter).into_stream();
+        futures::pin_mut!(imported);
+        while let Some(event) = imported.try_next().await? {
+            if let Some(target) = targets.get_mut(&event.id) {
+                *target = event.global_position;
+            }
+        }
+    }
+    let checkpoints: BTreeMap<String, i64> = checkpoints
+        .keys()
+        .map(|name| {
+            let target = handled.get(name).map_or(head, |id| targets[id]);
+            (name.to_string(), target)
+        })
+        .collect();
+    store.restore_checkpoints(&checkpoints).await?;
+
+    Ok(RestoreReport {
+        restored,
+        position,
+        checkpoints,
+    })
+}
+
+async fn write_line<W, T>(writer: &mut BufWriter<W>, value: &T) -> Result<(), replay::Error>
+where
+    W: AsyncWrite + Unpin + Send,
+    T: Serialize,
+{
+    let mut line = serde_json::to_vec(value).map_err(crate::ser_error)?;
+    line.push(b'\n');
+    writer.write_all(&line).await.map_err(io_error)
+}
+
+fn parse_line<T: serde::de::DeserializeOwned>(line: &str, number: u64) -> Result<T, replay::Error> {
+    serde_json::from_str(line).map_err(|e| {
+        replay::Error::wrap_permanent(
+            replay::ErrorKind::InvalidInput,
+            "invalid backup line",
+            crate::deser_error(e),
+        )
+        .with_operation("restore")
+        .with_context("line", number)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use replay::Metadata;
+    use tokio::io::BufReader;
+
+    use super::*;
+    use crate::conformance::{ConformanceAccount, ConformanceAccountUrn, ConformanceEvent};
+    use crate::EventStore;
+
+    async fn deposit(store: &InMemoryEventStore, id: &ConformanceAccountUrn, amount: i64) {
+        store
+            .store_events::<ConformanceAccount>(
+                id,
+                ConformanceAccount::stream_type(),
+                Metadata::default(),
+                &[ConformanceEvent::Deposited { amount }],
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn amounts(store: &InMemoryEventStore) -> Vec<i64> {
+        store
+            .stream_events::<ConformanceEvent>(StreamFilter::All)
+            .map_ok(|event| match event.data {
+                ConformanceEvent::Deposited { amount } => amount,
+                _ => 0,
+            })
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn restores_up_to_a_point_in_time() {
+        let source = InMemoryEventStore::new();
+        let id = ConformanceAccountUrn::new_random();
+        for amount in [1, 2, 3] {
+            deposit(&source, &id, amount).await;
+        }
+        let mut file = Vec::new();
+        let manifest = backup::<ConformanceAccount, _, _>(&source, &mut file)
+            .await
+            .unwrap();
+        assert_eq!(manifest.position, 3);
+
+        let target = InMemoryEventStore::new();
+        let report =
+            restore::<ConformanceAccount, _, _>(&target, BufReader::new(file.as_slice()), Some(2))
+                .await
+                .unwrap();
+
+        assert_eq!((report.restored, report.position), (2, 2));
+        assert_eq!(amounts(&target).await, [1, 2]);
+    }
+
+    #[tokio::test]
+    async fn restores_refuse_another_stream_type() {
+        let backup =
+            b"{\"stream_type\":\"Cart\",\"position\":0,\"created\":\"2024-05-31T12:00:00Z\"}\n";
+
+        let err = restore::<ConformanceAccount, _, _>(
+            &InMemoryEventStore::new(),
+            BufReader::new(&backup[..]),
+            None,
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(err.kind(), replay::ErrorKind::InvalidInput);
+    }
+}
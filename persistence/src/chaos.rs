@@ -0,0 +1,380 @@
+//! Fault injection for testing how an application copes with a misbehaving [`EventStore`].
+//!
+//! [`ChaosEventStore`] wraps a real store and fails or slows down chosen operations with the
+//! same [`replay::Error`] kinds the built-in stores report, so retry loops, projections and
+//! policies can be exercised without breaking a database.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{TryStream, TryStreamExt};
+use urn::Urn;
+
+use replay::{Compactable, ErrorKind, Event, Snapshottable, StreamId};
+
+use crate::{
+    CompactionOutcome, DeletionMode, EventSink, EventStore, PersistedEvent, StoreHealth,
+    StreamFilter, StreamListing, StreamMetadata, StreamPage, StreamRetention, StreamState,
+};
+
+/// An [`EventStore`] wrapper that injects failures and latency.
+///
+/// - **Appends** are numbered from 1 across the store (and its clones); the ones registered
+///   with [`fail_append`](Self::fail_append) fail before reaching the inner store, so nothing
+///   is written.
+/// - **Reads** can be slowed down per event ([`slow_reads`](Self::slow_reads)) or cut off with
+///   an `Unavailable` error after a number of events ([`fail_reads_after`](Self::fail_reads_after)).
+///
+/// Compaction and health checks are passed through untouched.
+///
+/// ```rust,ignore
+/// let store = ChaosEventStore::new(InMemoryEventStore::new())
+///     .unavailable_on_append(1)
+///     .conflict_on_append(3);
+/// let cqrs = Cqrs::new(store);
+/// ```
+pub struct ChaosEventStore<ES> {
+    inner: Arc<ES>,
+    append_faults: Arc<Vec<(usize, ErrorKind)>>,
+    read_delay: Option<Duration>,
+    fail_reads_after: Option<usize>,
+    appends: Arc<AtomicUsize>,
+}
+
+impl<ES: EventStore> ChaosEventStore<ES> {
+    pub fn new(inner: impl Into<Arc<ES>>) -> Self {
+        Self {
+            inner: inner.into(),
+            append_faults: Arc::new(Vec::new()),
+            read_delay: None,
+            fail_reads_after: None,
+            appends: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Fail the `n`th append (1-based) with a temporary error of `kind`.
+    pub fn fail_append(mut self, n: usize, kind: ErrorKind) -> Self {
+        Arc::make_mut(&mut self.append_faults).push((n, kind));
+        self
+    }
+
+    /// Fail the `n`th append as if another writer got there first.
+    pub fn conflict_on_append(self, n: usize) -> Self {
+        self.fail_append(n, ErrorKind::Conflict)
+    }
+
+    /// Fail the `n`th append as if the database were down.
+    pub fn unavailable_on_append(self, n: usize) -> Self {
+        self.fail_append(n, ErrorKind::Unavailable)
+    }
+
+    /// Wait `delay` before yielding each event read.
+    pub fn slow_reads(mut self, delay: Duration) -> Self {
+        self.read_delay = Some(delay);
+        self
+    }
+
+    /// End every read with an `Unavailable` error once it has yielded `count` events and
+    /// another one is due; shorter reads complete normally.
+    pub fn fail_reads_after(mut self, count: usize) -> Self {
+        self.fail_reads_after = Some(count);
+        self
+    }
+
+    /// Number of appends attempted so far, failed ones included.
+    pub fn appends(&self) -> usize {
+        self.appends.load(Ordering::SeqCst)
+    }
+
+    /// The wrapped store, for checking what was actually written.
+    pub fn inner(&self) -> &ES {
+        &self.inner
+    }
+}
+
+impl<ES> Clone for ChaosEventStore<ES> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            append_faults: self.append_faults.clone(),
+            read_delay: self.read_delay,
+            fail_reads_after: self.fail_reads_after,
+            appends: self.appends.clone(),
+        }
+    }
+}
+
+impl<ES: EventStore> EventStore for ChaosEventStore<ES> {
+    async fn store_events_stream<S, Events, Sink>(
+        &self,
+        stream_id: &S::StreamId,
+        stream_type: &str,
+        metadata: replay::Metadata,
+        domain_events: Events,
+        expected_version: Option<i64>,
+        sink: Sink,
+    ) -> Result<(), replay::Error>
+    where
+        S: replay::EventStream,
+        Events: TryStream<Ok = S::Event, Error = replay::Error> + Send,
+        Sink: EventSink<S::Event> + Send,
+    {
+        let append = self.appends.fetch_add(1, Ordering::SeqCst) + 1;
+        if let Some((_, kind)) = self.append_faults.iter().find(|(n, _)| *n == append) {
+            let stream_id: Urn = stream_id.clone().into();
+            return Err(replay::Error::temporary(*kind, "injected append failure")
+                .with_operation("store_events")
+                .with_context("stream_id", stream_id)
+                .with_context("append", append));
+        }
+
+        self.inner
+            .store_events_stream::<S, _, _>(
+                stream_id,
+                stream_type,
+                metadata,
+                domain_events,
+                expected_version,
+                sink,
+            )
+            .await
+    }
+
+    fn stream_events<E: Event>(
+        &self,
+        filter: StreamFilter,
+    ) -> impl TryStream<Ok = PersistedEvent<E>, Error = replay::Error> + Send {
+        async_stream::try_stream! {
+            let events = self.inner.stream_events::<E>(filter).into_stream();
+            futures::pin_mut!(events);
+
+            let mut read = 0;
+            while let Some(event) = events.try_next().await? {
+                if self.fail_reads_after == Some(read) {
+                    Err(replay::Error::unavailable("injected read failure")
+                        .with_operation("stream_events")
+                        .with_context("events_read", read))?;
+                }
+                if let Some(delay) = self.read_delay {
+                    tokio::time::sleep(delay).await;
+                }
+                read += 1;
+                yield event;
+            }
+        }
+    }
+
+    async fn needs_compaction(&self, stream_id: &impl StreamId) -> Result<bool, replay::Error> {
+        self.inner.needs_compaction(stream_id).await
+    }
+
+    async fn delete_stream(
+        &self,
+        stream_id: &impl StreamId,
+        mode: DeletionMode,
+    ) -> Result<(), replay::Error> {
+        self.inner.delete_stream(stream_id, mode).await
+    }
+
+    async fn is_deleted(&self, stream_id: &impl StreamId) -> Result<bool, replay::Error> {
+        self.inner.is_deleted(stream_id).await
+    }
+
+    async fn truncate_stream(
+        &self,
+        stream_id: &impl StreamId,
+        before_version: i64,
+    ) -> Result<u64, replay::Error> {
+        self.inner.truncate_stream(stream_id, before_version).await
+    }
+
+    async fn set_stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+        retention: StreamRetention,
+    ) -> Result<(), replay::Error> {
+        self.inner.set_stream_retention(stream_id, retention).await
+    }
+
+    async fn stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> Result<StreamRetention, replay::Error> {
+        self.inner.stream_retention(stream_id).await
+    }
+
+    async fn enforce_stream_retention(&self) -> Result<u64, replay::Error> {
+        self.inner.enforce_stream_retention().await
+    }
+
+    async fn set_stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+        metadata: StreamMetadata,
+    ) -> Result<(), replay::Error> {
+        self.inner.set_stream_metadata(stream_id, metadata).await
+    }
+
+    async fn stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> Result<StreamMetadata, replay::Error> {
+        self.inner.stream_metadata(stream_id).await
+    }
+
+    async fn list_streams(
+        &self,
+        stream_type: Option<&str>,
+        page: StreamPage,
+    ) -> Result<StreamListing, replay::Error> {
+        self.inner.list_streams(stream_type, page).await
+    }
+
+    async fn stream_info(&self, stream_id: &impl StreamId) -> Result<StreamState, replay::Error> {
+        self.inner.stream_info(stream_id).await
+    }
+
+    async fn rename_stream(
+        &self,
+        old_id: &impl StreamId,
+        new_id: &impl StreamId,
+    ) -> Result<(), replay::Error> {
+        self.inner.rename_stream(old_id, new_id).await
+    }
+
+    async fn health_check(&self) -> Result<StoreHealth, replay::Error> {
+        self.inner.health_check().await
+    }
+
+    async fn compact<A>(
+        &self,
+        aggregate: &A,
+        metadata: replay::Metadata,
+    ) -> Result<CompactionOutcome, replay::Error>
+    where
+        A: replay::Aggregate + Compactable + Sync,
+    {
+        self.inner.compact(aggregate, metadata).await
+    }
+
+    async fn compact_stream<S>(
+        &self,
+        stream_id: &S::StreamId,
+        up_to_version: i64,
+        metadata: replay::Metadata,
+    ) -> Result<u64, replay::Error>
+    where
+        S: Snapshottable,
+    {
+        self.inner
+            .compact_stream::<S>(stream_id, up_to_version, metadata)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance::{ConformanceAccount, ConformanceAccountUrn, ConformanceEvent};
+    use crate::InMemoryEventStore;
+    use replay::Metadata;
+
+    async fn deposit(
+        store: &impl EventStore,
+        stream_id: &ConformanceAccountUrn,
+        amount: i64,
+    ) -> replay::Result<()> {
+        store
+            .store_events::<ConformanceAccount>(
+                stream_id,
+                "ConformanceAccount",
+                Metadata::default(),
+                &[ConformanceEvent::Deposited { amount }],
+                None,
+            )
+            .await
+    }
+
+    async fn read_all(
+        store: &impl EventStore,
+        stream_id: &ConformanceAccountUrn,
+    ) -> replay::Result<Vec<PersistedEvent<ConformanceEvent>>> {
+        store
+            .stream_events::<ConformanceEvent>(StreamFilter::with_stream_id::<ConformanceAccount>(
+                stream_id,
+            ))
+            .try_collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn injected_append_failures_write_nothing() {
+        let store = ChaosEventStore::new(InMemoryEventStore::new())
+            .unavailable_on_append(1)
+            .conflict_on_append(3);
+        let stream_id = ConformanceAccountUrn::new_random();
+
+        let err = deposit(&store, &stream_id, 1).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unavailable);
+        assert_eq!(err.status(), replay::ErrorStatus::Temporary);
+
+        deposit(&store, &stream_id, 2).await.unwrap();
+
+        let err = deposit(&store.clone(), &stream_id, 3).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Conflict);
+
+        deposit(&store, &stream_id, 4).await.unwrap();
+        assert_eq!(store.appends(), 4);
+
+        let amounts: Vec<_> = read_all(store.inner(), &stream_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|event| event.data)
+            .collect();
+        assert_eq!(
+            amounts,
+            vec![
+                ConformanceEvent::Deposited { amount: 2 },
+                ConformanceEvent::Deposited { amount: 4 }
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn reads_fail_after_the_configured_number_of_events() {
+        let store = ChaosEventStore::new(InMemoryEventStore::new()).fail_reads_after(2);
+        let stream_id = ConformanceAccountUrn::new_random();
+        for amount in [1, 2] {
+            deposit(&store, &stream_id, amount).await.unwrap();
+        }
+        assert_eq!(read_all(&store, &stream_id).await.unwrap().len(), 2);
+
+        deposit(&store, &stream_id, 3).await.unwrap();
+        let events = store
+            .stream_events::<ConformanceEvent>(StreamFilter::with_stream_id::<ConformanceAccount>(
+                &stream_id,
+            ))
+            .into_stream();
+        futures::pin_mut!(events);
+        assert!(events.try_next().await.unwrap().is_some());
+        assert!(events.try_next().await.unwrap().is_some());
+        let err = events.try_next().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unavailable);
+    }
+
+    #[tokio::test]
+    async fn slow_reads_delay_each_event() {
+        let store =
+            ChaosEventStore::new(InMemoryEventStore::new()).slow_reads(Duration::from_millis(5));
+        let stream_id = ConformanceAccountUrn::new_random();
+        for amount in [1, 2, 3] {
+            deposit(&store, &stream_id, amount).await.unwrap();
+        }
+
+        let started = std::time::Instant::now();
+        assert_eq!(read_all(&store, &stream_id).await.unwrap().len(), 3);
+        assert!(started.elapsed() >= Duration::from_millis(15));
+    }
+}
@@ -0,0 +1,90 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+/// Source of the `created` timestamps the in-memory store gives appended events.
+///
+/// The default, [`SystemClock`], reads the wall clock. [`SteppingClock`] makes timestamps
+/// reproducible for snapshot-style assertions. Any `Fn() -> DateTime<Utc> + Send + Sync` closure
+/// is a clock.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+impl<F> Clock for F
+where
+    F: Fn() -> DateTime<Utc> + Send + Sync,
+{
+    fn now(&self) -> DateTime<Utc> {
+        self()
+    }
+}
+
+/// The wall clock ([`Utc::now`]).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that starts at a fixed instant and moves forward by a fixed step on every reading.
+#[derive(Debug)]
+pub struct SteppingClock {
+    next: Mutex<DateTime<Utc>>,
+    step: Duration,
+}
+
+impl SteppingClock {
+    pub fn new(start: DateTime<Utc>, step: Duration) -> Self {
+        SteppingClock {
+            next: Mutex::new(start),
+            step,
+        }
+    }
+}
+
+impl Default for SteppingClock {
+    /// Starts at 2024-01-01T00:00:00Z and moves a second per reading.
+    fn default() -> Self {
+        SteppingClock::new(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            Duration::seconds(1),
+        )
+    }
+}
+
+impl Clock for SteppingClock {
+    fn now(&self) -> DateTime<Utc> {
+        let mut next = self.next.lock().unwrap();
+        let now = *next;
+        *next = now + self.step;
+        now
+    }
+}
+
+pub(crate) type SharedClock = Arc<dyn Clock>;
+
+pub(crate) fn default_clock() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stepping_clock_moves_by_its_step() {
+        let start = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        let clock = SteppingClock::new(start, Duration::milliseconds(10));
+
+        assert_eq!(clock.now(), start);
+        assert_eq!(clock.now(), start + Duration::milliseconds(10));
+        assert_eq!(
+            SteppingClock::default().now(),
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        );
+    }
+}
@@ -0,0 +1,430 @@
+//! HTTP command endpoints for aggregates, served by [axum](https://docs.rs/axum).
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::de::DeserializeOwned;
+use urn::{Urn, UrnBuilder};
+
+use replay::{Aggregate, ErrorKind, Metadata};
+
+use crate::{Cqrs, EventStore};
+
+/// Header naming who issued the command, stored as the events' actor.
+pub const ACTOR_HEADER: &str = "x-actor";
+/// Header carrying the workflow's correlation id.
+pub const CORRELATION_ID_HEADER: &str = "x-correlation-id";
+/// Header carrying the id of what caused the command.
+pub const CAUSATION_ID_HEADER: &str = "x-causation-id";
+/// Header naming the tenant the command is issued for.
+pub const TENANT_ID_HEADER: &str = "x-tenant-id";
+
+/// An axum router executing the commands of aggregate `A` at
+/// `POST /{segment}/{id}/commands`, where the segment defaults to the kebab-cased stream type
+/// (`BankAccount` serves `/bank-account/{id}/commands`).
+///
+/// ```rust,ignore
+/// let app = CommandRouter::<BankAccount, _>::new(cqrs.clone(), services)
+///     .router()
+///     .merge(CommandRouter::<Transfer, _>::new(cqrs, ()).router());
+/// axum::serve(listener, app).await?;
+/// ```
+///
+/// - The body is the command as JSON.
+/// - `{id}` is the stream id: a full URN, or the part after the namespace, which is the
+///   segment unless [`with_namespace`](Self::with_namespace) says otherwise.
+/// - [`ACTOR_HEADER`], [`CORRELATION_ID_HEADER`], [`CAUSATION_ID_HEADER`],
+///   [`TENANT_ID_HEADER`] and W3C `traceparent`/`tracestate` become event metadata.
+/// - `If-Match: "3"` executes only if the stream is at version 3.
+///
+/// A successful command answers `204 No Content`. Errors answer with the status of their
+/// [`ErrorKind`] (`404`, `409`, `422` for business rules, `429` with `Retry-After`, ...) and
+/// a body like `{"error": "conflict", "message": "..."}`; an aggregate error that doesn't wrap
+/// a `replay::Error` is a rejected command, `422`.
+pub struct CommandRouter<A: Aggregate, ES: EventStore> {
+    cqrs: Cqrs<ES>,
+    services: A::Services,
+    segment: String,
+    namespace: Option<String>,
+}
+
+impl<A, ES> CommandRouter<A, ES>
+where
+    A: Aggregate + 'static,
+    A::Command: DeserializeOwned + 'static,
+    A::Event: 'static,
+    A::Error: 'static,
+    A::Services: 'static,
+    ES: EventStore + 'static,
+{
+    pub fn new(cqrs: Cqrs<ES>, services: A::Services) -> Self {
+        CommandRouter {
+            cqrs,
+            services,
+            segment: kebab_case(A::stream_type()),
+            namespace: None,
+        }
+    }
+
+    /// Serve the commands under `/{segment}/{id}/commands` instead.
+    pub fn with_path_segment(mut self, segment: impl Into<String>) -> Self {
+        self.segment = segment.into();
+        self
+    }
+
+    /// The URN namespace of ids given without one, when it isn't the path segment.
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    pub fn router<S>(self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let path = format!("/{}/{{id}}/commands", self.segment);
+        Router::new()
+            .route(&path, post(execute::<A, ES>))
+            .with_state(Arc::new(self))
+    }
+
+    fn stream_id(&self, id: &str) -> Result<A::StreamId, Box<Response>> {
+        let namespace = self.namespace.as_deref().unwrap_or(&self.segment);
+        let urn = if id.starts_with("urn:") {
+            Urn::from_str(id)
+        } else {
+            UrnBuilder::new(namespace, id).build()
+        };
+        urn.map_err(|e| e.to_string())
+            .and_then(|urn| A::StreamId::try_from(urn).map_err(|e| format!("{e:?}")))
+            .map_err(|message| {
+                Box::new(error_response(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_id",
+                    message,
+                ))
+            })
+    }
+}
+
+async fn execute<A, ES>(
+    State(endpoint): State<Arc<CommandRouter<A, ES>>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(command): Json<A::Command>,
+) -> Response
+where
+    A: Aggregate + 'static,
+    A::Command: DeserializeOwned + 'static,
+    A::Event: 'static,
+    A::Error: 'static,
+    A::Services: 'static,
+    ES: EventStore + 'static,
+{
+    let stream_id = match endpoint.stream_id(&id) {
+        Ok(stream_id) => stream_id,
+        Err(response) => return *response,
+    };
+    let expected_version = match expected_version(&headers) {
+        Ok(version) => version,
+        Err(response) => return *response,
+    };
+
+    match endpoint
+        .cqrs
+        .execute::<A>(
+            &stream_id,
+            metadata(&headers),
+            command,
+            &endpoint.services,
+            expected_version,
+        )
+        .await
+    {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => command_error_response(&err),
+    }
+}
+
+/// Event metadata from the request headers.
+fn metadata(headers: &HeaderMap) -> Metadata {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let mut metadata = Metadata::default();
+    if let Some(actor) = header(ACTOR_HEADER) {
+        metadata = metadata.with_actor(actor);
+    }
+    if let Some(id) = header(CORRELATION_ID_HEADER) {
+        metadata = metadata.with_correlation_id(id);
+    }
+    if let Some(id) = header(CAUSATION_ID_HEADER) {
+        metadata = metadata.with_causation_id(id);
+    }
+    if let Some(tenant_id) = header(TENANT_ID_HEADER) {
+        metadata = metadata.with_tenant_id(tenant_id);
+    }
+    if let Some(traceparent) = header("traceparent") {
+        metadata = metadata.with_trace_context(traceparent, header("tracestate").unwrap_or(""));
+    }
+    metadata
+}
+
+/// The stream version required by `If-Match`, quoted like an ETag or bare.
+fn expected_version(headers: &HeaderMap) -> Result<Option<i64>, Box<Response>> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(None);
+    };
+    value
+        .to_str()
+        .ok()
+        .map(|value| value.trim().trim_start_matches("W/").trim_matches('"'))
+        .and_then(|version| version.parse().ok())
+        .map(Some)
+        .ok_or_else(|| {
+            Box::new(error_response(
+                StatusCode::BAD_REQUEST,
+                "invalid_input",
+                "If-Match must be a stream version",
+            ))
+        })
+}
+
+/// The response for a failed command: the status of the `replay::Error` it wraps, or `422`
+/// for a domain error.
+fn command_error_response<E: std::error::Error + 'static>(error: &E) -> Response {
+    let mut current: Option<&(dyn std::error::Error + 'static)> = Some(error);
+    while let Some(error) = current {
+        if let Some(error) = error.downcast_ref::<replay::Error>() {
+            return replay_error_response(error);
+        }
+        current = error.source();
+    }
+    error_response(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "command_rejected",
+        error.to_string(),
+    )
+}
+
+pub(crate) fn replay_error_response(error: &replay::Error) -> Response {
+    let (status, code) = match error.kind() {
+        ErrorKind::NotFound => (StatusCode::NOT_FOUND, "not_found"),
+        ErrorKind::InvalidInput => (StatusCode::BAD_REQUEST, "invalid_input"),
+        ErrorKind::Conflict => (StatusCode::CONFLICT, "conflict"),
+        ErrorKind::Unavailable => (StatusCode::SERVICE_UNAVAILABLE, "unavailable"),
+        ErrorKind::Internal => (StatusCode::INTERNAL_SERVER_ERROR, "internal"),
+        ErrorKind::BusinessRuleViolation => {
+            (StatusCode::UNPROCESSABLE_ENTITY, "business_rule_violation")
+        }
+        ErrorKind::Unauthorized => (StatusCode::UNAUTHORIZED, "unauthorized"),
+        ErrorKind::Forbidden => (StatusCode::FORBIDDEN, "forbidden"),
+        ErrorKind::RateLimited => (StatusCode::TOO_MANY_REQUESTS, "rate_limited"),
+        ErrorKind::Unsupported => (StatusCode::NOT_IMPLEMENTED, "unsupported"),
+    };
+    let mut response = error_response(status, code, error.to_string());
+    if let Some(retry_after) = error.retry_after() {
+        let seconds = retry_after.as_secs_f64().ceil() as u64;
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+    }
+    response
+}
+
+fn error_response(status: StatusCode, code: &str, message: impl Into<String>) -> Response {
+    let body = serde_json::json!({ "error": code, "message": message.into() });
+    (status, Json(body)).into_response()
+}
+
+/// `BankAccount` as `bank-account`.
+fn kebab_case(name: &str) -> String {
+    let mut kebab = String::with_capacity(name.len() + 4);
+    let chars: Vec<char> = name.chars().collect();
+    for (i, &ch) in chars.iter().enumerate() {
+        if ch.is_uppercase() {
+            let previous = i.checked_sub(1).map(|i| chars[i]);
+            let after_lower = previous.is_some_and(|c| c.is_lowercase() || c.is_ascii_digit());
+            let acronym_end = previous.is_some_and(char::is_uppercase)
+                && chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            if after_lower || acronym_end {
+                kebab.push('-');
+            }
+            kebab.extend(ch.to_lowercase());
+        } else if ch == '_' || ch == ' ' {
+            kebab.push('-');
+        } else {
+            kebab.push(ch);
+        }
+    }
+    kebab
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use futures::TryStreamExt;
+    use replay::{EventStream, WithId};
+    use replay_macros::Event;
+    use serde::{Deserialize, Serialize};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{InMemoryEventStore, PersistedEvent, StreamFilter};
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Event)]
+    enum TallyEvent {
+        Added { amount: i64 },
+    }
+
+    #[derive(Deserialize)]
+    enum TallyCommand {
+        Add { amount: i64 },
+    }
+
+    #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+    struct TallyUrn(Urn);
+
+    impl From<TallyUrn> for Urn {
+        fn from(urn: TallyUrn) -> Self {
+            urn.0
+        }
+    }
+
+    impl TryFrom<Urn> for TallyUrn {
+        type Error = String;
+
+        fn try_from(urn: Urn) -> Result<Self, Self::Error> {
+            match urn.nid() {
+                "tally-sheet" => Ok(TallyUrn(urn)),
+                nid => Err(format!("expected a tally-sheet URN, got {nid}")),
+            }
+        }
+    }
+
+    struct TallySheet {
+        id: TallyUrn,
+    }
+
+    impl WithId for TallySheet {
+        type StreamId = TallyUrn;
+
+        fn with_id(id: Self::StreamId) -> Self {
+            TallySheet { id }
+        }
+
+        fn get_id(&self) -> &Self::StreamId {
+            &self.id
+        }
+    }
+
+    impl EventStream for TallySheet {
+        type Event = TallyEvent;
+
+        fn stream_type() -> &'static str {
+            "TallySheet"
+        }
+
+        fn apply(&mut self, _event: Self::Event) {}
+    }
+
+    impl Aggregate for TallySheet {
+        type Command = TallyCommand;
+        type Error = replay::Error;
+        type Services = ();
+
+        async fn handle(
+            &self,
+            command: Self::Command,
+            _services: &Self::Services,
+        ) -> Result<Vec<Self::Event>, Self::Error> {
+            match command {
+                TallyCommand::Add { amount } if amount <= 0 => Err(
+                    replay::Error::business_rule_violation("amounts must be positive"),
+                ),
+                TallyCommand::Add { amount } => Ok(vec![TallyEvent::Added { amount }]),
+            }
+        }
+    }
+
+    fn post(uri: &str) -> axum::http::request::Builder {
+        Request::post(uri).header(header::CONTENT_TYPE, "application/json")
+    }
+
+    #[tokio::test]
+    async fn commands_execute_with_header_metadata() {
+        let cqrs = Cqrs::new(InMemoryEventStore::new());
+        let app: Router = CommandRouter::<TallySheet, _>::new(cqrs.clone(), ()).router();
+        let body = r#"{"Add":{"amount":3}}"#;
+
+        let response = app
+            .oneshot(
+                post("/tally-sheet/42/commands")
+                    .header(ACTOR_HEADER, "ada")
+                    .header(header::IF_MATCH, "\"0\"")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let events: Vec<PersistedEvent<TallyEvent>> = cqrs
+            .store()
+            .stream_events(StreamFilter::All)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].stream_id.to_string(), "urn:tally-sheet:42");
+        assert_eq!(events[0].actor(), Some("ada"));
+    }
+
+    #[tokio::test]
+    async fn errors_map_to_statuses() {
+        let app: Router =
+            CommandRouter::<TallySheet, _>::new(Cqrs::new(InMemoryEventStore::new()), ()).router();
+        let send = |uri: &str, body: &'static str, if_match: Option<&str>| {
+            let mut request = post(uri);
+            if let Some(version) = if_match {
+                request = request.header(header::IF_MATCH, version);
+            }
+            app.clone().oneshot(request.body(Body::from(body)).unwrap())
+        };
+
+        let rejected = send("/tally-sheet/1/commands", r#"{"Add":{"amount":0}}"#, None)
+            .await
+            .unwrap();
+        assert_eq!(rejected.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = to_bytes(rejected.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "business_rule_violation");
+        assert_eq!(body["message"], "amounts must be positive");
+
+        let stale = send(
+            "/tally-sheet/1/commands",
+            r#"{"Add":{"amount":1}}"#,
+            Some("5"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(stale.status(), StatusCode::CONFLICT);
+
+        let other_namespace = "/tally-sheet/urn:other:1/commands";
+        let wrong_namespace = send(other_namespace, r#"{"Add":{"amount":1}}"#, None)
+            .await
+            .unwrap();
+        assert_eq!(wrong_namespace.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn stream_types_kebab_case() {
+        assert_eq!(kebab_case("BankAccount"), "bank-account");
+        assert_eq!(kebab_case("HTTPRequestLog"), "http-request-log");
+        assert_eq!(kebab_case("Order2Line"), "order2-line");
+    }
+}
@@ -0,0 +1,108 @@
+//! Compression of large event payloads in the Postgres store.
+//!
+//! A compressed event keeps its serialized payload in `events.data_binary` (`bytea`) and
+//! records `<format>+<codec>` in `events.data_encoding` (e.g. `json+zstd`). Reads decode
+//! compressed and plain events alike, so compression can be switched on or off at any time.
+
+/// Codec name in `events.data_encoding` for zstd-compressed payloads.
+#[cfg(feature = "compression")]
+pub(crate) const ZSTD: &str = "zstd";
+
+/// zstd compression for payloads larger than a threshold once serialized, installed with
+/// [`PostgresEventStore::with_compression`](crate::PostgresEventStore::with_compression).
+///
+/// Small payloads are stored as the serializer writes them: compressing them saves little
+/// and costs CPU on every read. Compressed payloads can't be queried from SQL (`data` is
+/// `NULL` for them), so keep compression for domains whose payloads are large and only read
+/// through the store, such as document contents.
+///
+/// ```rust,ignore
+/// let store = PostgresEventStore::new(pool)
+///     .with_compression(Compression::zstd().with_threshold(8 * 1024));
+/// ```
+#[cfg(feature = "compression")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Compression {
+    pub(crate) threshold: usize,
+    pub(crate) level: i32,
+}
+
+#[cfg(feature = "compression")]
+impl Compression {
+    /// zstd at level 3 for payloads over 1 KiB once serialized.
+    pub fn zstd() -> Self {
+        Compression {
+            threshold: 1024,
+            level: 3,
+        }
+    }
+
+    /// Compress payloads whose serialized form is longer than `bytes`.
+    pub fn with_threshold(mut self, bytes: usize) -> Self {
+        self.threshold = bytes;
+        self
+    }
+
+    /// zstd level, from 1 (fastest) to 22 (smallest).
+    pub fn with_level(mut self, level: i32) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// The compressed form of `payload`, or `None` when it is under the threshold or
+    /// doesn't get smaller.
+    pub(crate) fn compress(&self, payload: &[u8]) -> Result<Option<Vec<u8>>, replay::Error> {
+        if payload.len() <= self.threshold {
+            return Ok(None);
+        }
+        let compressed = zstd::bulk::compress(payload, self.level).map_err(|e| {
+            replay::Error::internal("failed to compress event payload")
+                .with_operation("compress_event")
+                .with_source(e)
+        })?;
+        Ok((compressed.len() < payload.len()).then_some(compressed))
+    }
+}
+
+/// Undo `codec` on a stored payload.
+pub(crate) fn decompress(codec: &str, bytes: &[u8]) -> Result<Vec<u8>, replay::Error> {
+    match codec {
+        #[cfg(feature = "compression")]
+        ZSTD => zstd::stream::decode_all(bytes).map_err(|e| {
+            replay::Error::internal("failed to decompress event payload")
+                .with_operation("decompress_event")
+                .with_context("codec", codec)
+                .with_source(e)
+        }),
+        _ => Err(replay::Error::internal(
+            "unsupported event payload compression; is the `compression` feature enabled?",
+        )
+        .with_operation("decompress_event")
+        .with_context("codec", codec)
+        .with_context("bytes", bytes.len())),
+    }
+}
+
+#[cfg(all(test, feature = "compression"))]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn only_payloads_over_the_threshold_are_compressed() {
+        let compression = Compression::zstd().with_threshold(64);
+        assert_eq!(compression.compress(br#"{"n":1}"#).unwrap(), None);
+
+        let document = serde_json::to_vec(&json!({ "body": "lorem ipsum ".repeat(100) })).unwrap();
+        let compressed = compression.compress(&document).unwrap().unwrap();
+        assert!(compressed.len() < document.len());
+        assert_eq!(decompress(ZSTD, &compressed).unwrap(), document);
+    }
+
+    #[test]
+    fn unknown_encodings_are_rejected() {
+        let err = decompress("lz4", &[]).unwrap_err();
+        assert_eq!(err.kind(), replay::ErrorKind::Internal);
+    }
+}
@@ -0,0 +1,1140 @@
+//! Conformance checks every [`EventStore`] implementation should pass.
+//!
+//! Each check takes a store, writes to fresh random streams (so a shared database is fine) and
+//! panics on the first deviation. Run them one test each with
+//! [`event_store_conformance_tests!`](crate::event_store_conformance_tests), or all at once with
+//! [`run_all`] when the store needs setup the macro can't express (e.g. a container that must
+//! outlive the store).
+
+use std::future::Future;
+use std::time::Duration;
+
+use futures::TryStreamExt;
+use replay::{ErrorKind, EventStream, Metadata, Snapshottable, WithId};
+use replay_macros::Event;
+use serde::{Deserialize, Serialize};
+use urn::{Urn, UrnBuilder};
+
+use crate::{
+    DeletionMode, EventStore, PersistedEvent, StreamFilter, StreamMetadata, StreamPage,
+    StreamRetention, StreamState, StreamSummary,
+};
+
+/// Events of the stream the checks write.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Event)]
+pub enum ConformanceEvent {
+    Opened { owner: String },
+    Deposited { amount: i64 },
+    Withdrawn { amount: i64 },
+}
+
+/// Id of a [`ConformanceAccount`] stream.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct ConformanceAccountUrn(Urn);
+
+impl ConformanceAccountUrn {
+    /// A fresh stream id, `urn:conformance-account:<uuid>`.
+    pub fn new_random() -> Self {
+        let urn = UrnBuilder::new("conformance-account", &uuid::Uuid::now_v7().to_string())
+            .build()
+            .expect("a uuid is a valid URN NSS");
+        ConformanceAccountUrn(urn)
+    }
+}
+
+impl From<ConformanceAccountUrn> for Urn {
+    fn from(urn: ConformanceAccountUrn) -> Self {
+        urn.0
+    }
+}
+
+impl TryFrom<Urn> for ConformanceAccountUrn {
+    type Error = String;
+
+    fn try_from(urn: Urn) -> Result<Self, Self::Error> {
+        Ok(ConformanceAccountUrn(urn))
+    }
+}
+
+/// Stream the checks write to.
+#[derive(Debug)]
+pub struct ConformanceAccount {
+    pub id: ConformanceAccountUrn,
+    pub balance: i64,
+}
+
+impl WithId for ConformanceAccount {
+    type StreamId = ConformanceAccountUrn;
+
+    fn with_id(id: Self::StreamId) -> Self {
+        ConformanceAccount { id, balance: 0 }
+    }
+
+    fn get_id(&self) -> &Self::StreamId {
+        &self.id
+    }
+}
+
+impl EventStream for ConformanceAccount {
+    type Event = ConformanceEvent;
+
+    fn stream_type() -> &'static str {
+        "ConformanceAccount"
+    }
+
+    fn apply(&mut self, event: Self::Event) {
+        match event {
+            ConformanceEvent::Opened { .. } => {}
+            ConformanceEvent::Deposited { amount } => self.balance += amount,
+            ConformanceEvent::Withdrawn { amount } => self.balance -= amount,
+        }
+    }
+}
+
+impl Snapshottable for ConformanceAccount {
+    fn snapshot_event(&self) -> ConformanceEvent {
+        ConformanceEvent::Deposited {
+            amount: self.balance,
+        }
+    }
+}
+
+async fn append(
+    store: &impl EventStore,
+    stream_id: &ConformanceAccountUrn,
+    metadata: Metadata,
+    events: &[ConformanceEvent],
+    expected_version: Option<i64>,
+) -> replay::Result<()> {
+    store
+        .store_events::<ConformanceAccount>(
+            stream_id,
+            ConformanceAccount::stream_type(),
+            metadata,
+            events,
+            expected_version,
+        )
+        .await
+}
+
+async fn read(
+    store: &impl EventStore,
+    filter: StreamFilter,
+) -> Vec<PersistedEvent<ConformanceEvent>> {
+    store
+        .stream_events::<ConformanceEvent>(filter)
+        .try_collect()
+        .await
+        .expect("reading events back failed")
+}
+
+async fn read_stream(
+    store: &impl EventStore,
+    stream_id: &ConformanceAccountUrn,
+) -> Vec<PersistedEvent<ConformanceEvent>> {
+    read(
+        store,
+        StreamFilter::with_stream_id::<ConformanceAccount>(stream_id),
+    )
+    .await
+}
+
+fn opened() -> ConformanceEvent {
+    ConformanceEvent::Opened {
+        owner: "conformance".to_string(),
+    }
+}
+
+/// Every event from `from_position` on, following `read_all` pages of `limit` until it is
+/// caught up. Panics unless positions rise strictly across pages.
+async fn read_all_from(
+    store: &impl EventStore,
+    from_position: i64,
+    limit: usize,
+) -> Vec<PersistedEvent<ConformanceEvent>> {
+    let mut events: Vec<PersistedEvent<ConformanceEvent>> = Vec::new();
+    let mut position = from_position;
+    for _ in 0..1_000 {
+        let page = store
+            .read_all::<ConformanceEvent>(position, limit)
+            .await
+            .expect("read_all failed");
+        assert!(page.events.len() <= limit, "a page over its limit");
+        if page.next_position == position {
+            assert!(page.events.is_empty(), "a page that doesn't advance");
+            return events;
+        }
+        for event in page.events {
+            assert!(
+                event.global_position >= position && event.global_position < page.next_position,
+                "position {} outside the page {position}..{}",
+                event.global_position,
+                page.next_position
+            );
+            events.push(event);
+        }
+        position = page.next_position;
+    }
+    panic!("read_all never caught up from {from_position}");
+}
+
+/// Whether the store implements the operation `probe` calls. The stream-management
+/// operations default to `Unsupported`, and their checks pass a store without them.
+async fn supports<T>(probe: impl Future<Output = replay::Result<T>>) -> bool {
+    !matches!(probe.await, Err(err) if err.kind() == ErrorKind::Unsupported)
+}
+
+/// Retry `operation` while it fails with a `Conflict`: a store may refuse to remove events
+/// while appends to other streams are still settling.
+async fn settled<T, F>(mut operation: impl FnMut() -> F) -> replay::Result<T>
+where
+    F: Future<Output = replay::Result<T>>,
+{
+    let mut attempts = 0;
+    loop {
+        match operation().await {
+            Err(err) if err.kind() == ErrorKind::Conflict && attempts < 50 => {
+                attempts += 1;
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Every stream of `stream_type` carrying `page`'s labels, following the pages to the last.
+/// Also returns how many pages that took.
+async fn list_all(
+    store: &impl EventStore,
+    stream_type: Option<&str>,
+    page: StreamPage,
+) -> (Vec<StreamSummary>, usize) {
+    let mut streams = Vec::new();
+    let mut pages = 0;
+    let mut page = Some(page);
+    while let Some(next) = page {
+        let limit = next.limit();
+        let listing = store
+            .list_streams(stream_type, next)
+            .await
+            .expect("listing streams failed");
+        assert!(listing.streams.len() <= limit, "a page over its limit");
+        streams.extend(listing.streams);
+        page = listing.next_page;
+        pages += 1;
+    }
+    (streams, pages)
+}
+
+/// The ids of `streams`, sorted.
+fn stream_ids(streams: Vec<StreamSummary>) -> Vec<Urn> {
+    sorted(streams.into_iter().map(|stream| stream.stream_id))
+}
+
+fn sorted(ids: impl IntoIterator<Item = Urn>) -> Vec<Urn> {
+    let mut ids: Vec<Urn> = ids.into_iter().collect();
+    ids.sort_by_key(ToString::to_string);
+    ids
+}
+
+/// Appended events read back with their data, type, stream id and versions `1..=n`.
+pub async fn append_and_read_back(store: &impl EventStore) {
+    let stream_id = ConformanceAccountUrn::new_random();
+    let events = vec![
+        opened(),
+        ConformanceEvent::Deposited { amount: 100 },
+        ConformanceEvent::Withdrawn { amount: 30 },
+    ];
+    append(store, &stream_id, Metadata::default(), &events, None)
+        .await
+        .expect("append failed");
+
+    let persisted = read_stream(store, &stream_id).await;
+    let data: Vec<_> = persisted.iter().map(|e| e.data.clone()).collect();
+    assert_eq!(data, events);
+
+    let versions: Vec<_> = persisted.iter().map(|e| e.version).collect();
+    assert_eq!(versions, vec![1, 2, 3]);
+
+    let types: Vec<_> = persisted.iter().map(|e| e.r#type.as_str()).collect();
+    assert_eq!(types, vec!["Opened", "Deposited", "Withdrawn"]);
+
+    let expected_stream: Urn = stream_id.into();
+    assert!(persisted.iter().all(|e| e.stream_id == expected_stream));
+    assert!(persisted.iter().all(|e| e.aggregate_version.is_none()));
+}
+
+/// Later appends continue the stream's version, and reads return events in version order.
+pub async fn appends_continue_the_stream(store: &impl EventStore) {
+    let stream_id = ConformanceAccountUrn::new_random();
+    append(store, &stream_id, Metadata::default(), &[opened()], None)
+        .await
+        .expect("first append failed");
+    for amount in [10, 20, 30] {
+        append(
+            store,
+            &stream_id,
+            Metadata::default(),
+            &[ConformanceEvent::Deposited { amount }],
+            None,
+        )
+        .await
+        .expect("append failed");
+    }
+
+    let persisted = read_stream(store, &stream_id).await;
+    let versions: Vec<_> = persisted.iter().map(|e| e.version).collect();
+    assert_eq!(versions, vec![1, 2, 3, 4]);
+
+    let mut account = ConformanceAccount::with_id(stream_id);
+    account.apply_all(persisted.into_iter().map(|e| e.data).collect());
+    assert_eq!(account.balance, 60);
+}
+
+/// A stale `expected_version` fails with a `Conflict` and writes nothing; the right one appends.
+pub async fn expected_version_conflicts(store: &impl EventStore) {
+    let stream_id = ConformanceAccountUrn::new_random();
+    append(store, &stream_id, Metadata::default(), &[opened()], Some(0))
+        .await
+        .expect("append to a new stream expecting version 0 failed");
+
+    let deposit = [
+        ConformanceEvent::Deposited { amount: 5 },
+        ConformanceEvent::Deposited { amount: 7 },
+    ];
+    for stale in [0, 2] {
+        let err = append(
+            store,
+            &stream_id,
+            Metadata::default(),
+            &deposit,
+            Some(stale),
+        )
+        .await
+        .expect_err("a stale expected version must conflict");
+        assert_eq!(err.kind(), ErrorKind::Conflict, "{err:?}");
+    }
+    assert_eq!(read_stream(store, &stream_id).await.len(), 1);
+
+    append(store, &stream_id, Metadata::default(), &deposit, Some(1))
+        .await
+        .expect("append with the current version failed");
+    let versions: Vec<_> = read_stream(store, &stream_id)
+        .await
+        .iter()
+        .map(|e| e.version)
+        .collect();
+    assert_eq!(versions, vec![1, 2, 3]);
+}
+
+/// Stream id, stream type, version and metadata filters select the matching events only.
+pub async fn filters_select_events(store: &impl EventStore) {
+    let first = ConformanceAccountUrn::new_random();
+    let second = ConformanceAccountUrn::new_random();
+    let correlation_id = uuid::Uuid::new_v4().to_string();
+
+    append(store, &first, Metadata::default(), &[opened()], None)
+        .await
+        .expect("append failed");
+    append(
+        store,
+        &first,
+        Metadata::default().with_correlation_id(correlation_id.clone()),
+        &[
+            ConformanceEvent::Deposited { amount: 1 },
+            ConformanceEvent::Deposited { amount: 2 },
+        ],
+        None,
+    )
+    .await
+    .expect("append failed");
+    append(store, &second, Metadata::default(), &[opened()], None)
+        .await
+        .expect("append failed");
+
+    let first_only = StreamFilter::with_stream_id::<ConformanceAccount>(&first);
+    assert_eq!(read(store, first_only.clone()).await.len(), 3);
+
+    let by_type = read(
+        store,
+        StreamFilter::for_stream_type::<ConformanceAccount>().and(first_only.clone()),
+    )
+    .await;
+    assert_eq!(by_type.len(), 3);
+
+    let after = read(store, first_only.clone().and(StreamFilter::AfterVersion(1))).await;
+    assert_eq!(after.iter().map(|e| e.version).collect::<Vec<_>>(), [2, 3]);
+
+    let up_to = read(store, first_only.clone().and(StreamFilter::UpToVersion(2))).await;
+    assert_eq!(up_to.iter().map(|e| e.version).collect::<Vec<_>>(), [1, 2]);
+
+    let correlated = read(store, StreamFilter::WithCorrelationId(correlation_id)).await;
+    assert_eq!(
+        correlated.iter().map(|e| &e.data).collect::<Vec<_>>(),
+        [
+            &ConformanceEvent::Deposited { amount: 1 },
+            &ConformanceEvent::Deposited { amount: 2 }
+        ]
+    );
+
+    let either = read(
+        store,
+        first_only.or(StreamFilter::with_stream_id::<ConformanceAccount>(&second)),
+    )
+    .await;
+    assert_eq!(either.len(), 4);
+}
+
+/// Well-known and custom metadata entries read back unchanged.
+pub async fn metadata_round_trips(store: &impl EventStore) {
+    let stream_id = ConformanceAccountUrn::new_random();
+    let metadata = Metadata::default()
+        .with_correlation_id("corr-1")
+        .with_causation_id("cause-1")
+        .with_actor("alice")
+        .into_builder()
+        .insert("channel", "conformance")
+        .insert("attempt", 3)
+        .build();
+    append(store, &stream_id, metadata, &[opened()], None)
+        .await
+        .expect("append failed");
+
+    let persisted = read_stream(store, &stream_id).await;
+    let metadata = &persisted[0].metadata;
+    assert_eq!(metadata.correlation_id(), Some("corr-1"));
+    assert_eq!(metadata.causation_id(), Some("cause-1"));
+    assert_eq!(metadata.actor(), Some("alice"));
+    assert_eq!(
+        metadata.get::<String>("channel").as_deref(),
+        Some("conformance")
+    );
+    assert_eq!(metadata.get::<i64>("attempt"), Some(3));
+}
+
+/// A soft-deleted stream leaves reads unless they include deleted streams, and a
+/// hard-deleted one is gone; deleting a stream that doesn't exist is a `NotFound`.
+pub async fn deleted_streams_leave_reads(store: &impl EventStore) {
+    let stream_id = ConformanceAccountUrn::new_random();
+    if !supports(store.delete_stream(&stream_id, DeletionMode::Soft)).await {
+        return;
+    }
+    let err = store
+        .delete_stream(&stream_id, DeletionMode::Soft)
+        .await
+        .expect_err("deleting a missing stream succeeded");
+    assert_eq!(err.kind(), ErrorKind::NotFound, "{err:?}");
+
+    append(store, &stream_id, Metadata::default(), &[opened()], None)
+        .await
+        .expect("append failed");
+    store
+        .delete_stream(&stream_id, DeletionMode::Soft)
+        .await
+        .expect("soft delete failed");
+    assert!(store
+        .is_deleted(&stream_id)
+        .await
+        .expect("is_deleted failed"));
+    assert!(read_stream(store, &stream_id).await.is_empty());
+    let including_deleted =
+        StreamFilter::with_stream_id::<ConformanceAccount>(&stream_id).including_deleted();
+    assert_eq!(read(store, including_deleted.clone()).await.len(), 1);
+
+    settled(|| store.delete_stream(&stream_id, DeletionMode::Hard))
+        .await
+        .expect("hard delete failed");
+    assert!(read(store, including_deleted).await.is_empty());
+    assert!(!store
+        .is_deleted(&stream_id)
+        .await
+        .expect("is_deleted failed"));
+}
+
+/// Compacting a stream replaces its live events up to a version with one snapshot event that
+/// takes the last one's version, position and time, so later versions, expected versions and
+/// the folded state are unchanged. Fewer than two events are left as they are; compacting a
+/// stream that doesn't exist is a `NotFound`.
+pub async fn compact_stream_keeps_versions(store: &impl EventStore) {
+    let stream_id = ConformanceAccountUrn::new_random();
+    let compact = store.compact_stream::<ConformanceAccount>(&stream_id, 1, Metadata::default());
+    if !supports(compact).await {
+        return;
+    }
+    let err = store
+        .compact_stream::<ConformanceAccount>(&stream_id, 1, Metadata::default())
+        .await
+        .expect_err("compacting a missing stream succeeded");
+    assert_eq!(err.kind(), ErrorKind::NotFound, "{err:?}");
+
+    let events = [
+        opened(),
+        ConformanceEvent::Deposited { amount: 10 },
+        ConformanceEvent::Deposited { amount: 20 },
+        ConformanceEvent::Withdrawn { amount: 5 },
+    ];
+    append(store, &stream_id, Metadata::default(), &events, None)
+        .await
+        .expect("append failed");
+    let before = read_stream(store, &stream_id).await;
+
+    let replaced =
+        settled(|| store.compact_stream::<ConformanceAccount>(&stream_id, 3, Metadata::default()))
+            .await
+            .expect("compact_stream failed");
+    assert_eq!(replaced, 3);
+    let after = read_stream(store, &stream_id).await;
+    assert_eq!(after.len(), 2);
+    let (snapshot, head) = (&after[0], &after[1]);
+    assert_eq!(snapshot.data, ConformanceEvent::Deposited { amount: 30 });
+    assert_eq!(
+        (snapshot.version, snapshot.global_position, snapshot.created),
+        (
+            before[2].version,
+            before[2].global_position,
+            before[2].created
+        )
+    );
+    assert_eq!(head.id, before[3].id);
+    assert_eq!(
+        (head.version, head.global_position),
+        (before[3].version, before[3].global_position)
+    );
+    let mut account = ConformanceAccount::with_id(stream_id.clone());
+    account.apply_all(after.into_iter().map(|e| e.data).collect());
+    assert_eq!(account.balance, 25);
+
+    // Only the snapshot is left up to version 3.
+    let replaced =
+        settled(|| store.compact_stream::<ConformanceAccount>(&stream_id, 3, Metadata::default()))
+            .await
+            .expect("compact_stream failed");
+    assert_eq!(replaced, 0);
+
+    append(
+        store,
+        &stream_id,
+        Metadata::default(),
+        &[ConformanceEvent::Deposited { amount: 1 }],
+        Some(4),
+    )
+    .await
+    .expect("append after compacting failed");
+    let versions: Vec<_> = read_stream(store, &stream_id)
+        .await
+        .iter()
+        .map(|e| e.version)
+        .collect();
+    assert_eq!(versions, [3, 4, 5]);
+}
+
+/// Truncating deletes the live events below a version but never the stream's last one, so a
+/// version past the head stops there and a version already truncated deletes nothing. Reads
+/// and appends carry on from what is left; truncating a stream that doesn't exist is a
+/// `NotFound`.
+pub async fn truncated_streams_keep_their_head(store: &impl EventStore) {
+    if !supports(store.truncate_stream(&ConformanceAccountUrn::new_random(), 1)).await {
+        return;
+    }
+    let stream_id = ConformanceAccountUrn::new_random();
+    let err = store
+        .truncate_stream(&stream_id, 1)
+        .await
+        .expect_err("truncating a missing stream succeeded");
+    assert_eq!(err.kind(), ErrorKind::NotFound, "{err:?}");
+
+    let deposits: Vec<_> = (1..=4)
+        .map(|amount| ConformanceEvent::Deposited { amount })
+        .collect();
+    append(store, &stream_id, Metadata::default(), &[opened()], None)
+        .await
+        .expect("append failed");
+    append(store, &stream_id, Metadata::default(), &deposits, Some(1))
+        .await
+        .expect("append failed");
+    let versions = |events: Vec<PersistedEvent<ConformanceEvent>>| -> Vec<i64> {
+        events.iter().map(|e| e.version).collect()
+    };
+
+    let truncated = settled(|| store.truncate_stream(&stream_id, 3))
+        .await
+        .expect("truncate failed");
+    assert_eq!(truncated, 2);
+    assert_eq!(versions(read_stream(store, &stream_id).await), [3, 4, 5]);
+
+    // Below what is already gone there is nothing left to delete.
+    let truncated = settled(|| store.truncate_stream(&stream_id, 2))
+        .await
+        .expect("truncate below the horizon failed");
+    assert_eq!(truncated, 0);
+    assert_eq!(versions(read_stream(store, &stream_id).await), [3, 4, 5]);
+
+    // Past the head, truncation stops at the head.
+    let truncated = settled(|| store.truncate_stream(&stream_id, 100))
+        .await
+        .expect("truncate past the head failed");
+    assert_eq!(truncated, 2);
+    let left = read_stream(store, &stream_id).await;
+    assert_eq!(left[0].data, ConformanceEvent::Deposited { amount: 4 });
+    assert_eq!(versions(left), [5]);
+
+    let err = append(store, &stream_id, Metadata::default(), &[opened()], Some(4))
+        .await
+        .expect_err("truncation must not move the head back");
+    assert_eq!(err.kind(), ErrorKind::Conflict, "{err:?}");
+    append(
+        store,
+        &stream_id,
+        Metadata::default(),
+        &[ConformanceEvent::Withdrawn { amount: 1 }],
+        Some(5),
+    )
+    .await
+    .expect("append after truncating failed");
+    let later = read(
+        store,
+        StreamFilter::with_stream_id::<ConformanceAccount>(&stream_id)
+            .and(StreamFilter::AfterVersion(4)),
+    )
+    .await;
+    assert_eq!(versions(later), [5, 6]);
+}
+
+/// Retention limits read back as set and hide the live events past either of them right
+/// away, except the stream's last one; enforcing them deletes those events, and appends carry
+/// on from the head. Limits on a stream that doesn't exist are a `NotFound`.
+pub async fn stream_retention_expires_events(store: &impl EventStore) {
+    if !supports(store.stream_retention(&ConformanceAccountUrn::new_random())).await {
+        return;
+    }
+    let missing = ConformanceAccountUrn::new_random();
+    let err = store
+        .set_stream_retention(&missing, StreamRetention::default().with_max_count(1))
+        .await
+        .expect_err("limiting a missing stream succeeded");
+    assert_eq!(err.kind(), ErrorKind::NotFound, "{err:?}");
+    let err = store
+        .stream_retention(&missing)
+        .await
+        .expect_err("the limits of a missing stream were found");
+    assert_eq!(err.kind(), ErrorKind::NotFound, "{err:?}");
+
+    let stream_id = ConformanceAccountUrn::new_random();
+    let deposits: Vec<_> = (1..=5)
+        .map(|amount| ConformanceEvent::Deposited { amount })
+        .collect();
+    append(store, &stream_id, Metadata::default(), &[opened()], None)
+        .await
+        .expect("append failed");
+    append(store, &stream_id, Metadata::default(), &deposits, None)
+        .await
+        .expect("append failed");
+    let limits = || async {
+        store
+            .stream_retention(&stream_id)
+            .await
+            .expect("reading the limits failed")
+    };
+    let versions = || async {
+        read_stream(store, &stream_id)
+            .await
+            .iter()
+            .map(|e| e.version)
+            .collect::<Vec<_>>()
+    };
+    assert!(limits().await.is_unlimited());
+
+    // Both limits apply: an hour doesn't expire anything yet, two events do.
+    let retention = StreamRetention::default()
+        .with_max_age(Duration::from_secs(60 * 60))
+        .with_max_count(2);
+    store
+        .set_stream_retention(&stream_id, retention)
+        .await
+        .expect("setting the limits failed");
+    assert_eq!(limits().await, retention);
+    assert_eq!(versions().await, [5, 6]);
+
+    // An age every event is past leaves only the head, however many events may be kept.
+    let retention = StreamRetention::default()
+        .with_max_age(Duration::ZERO)
+        .with_max_count(10);
+    store
+        .set_stream_retention(&stream_id, retention)
+        .await
+        .expect("setting the limits failed");
+    assert_eq!(versions().await, [6]);
+
+    // Other streams in the store may have expired events too, so only a lower bound holds.
+    let removed = store
+        .enforce_stream_retention()
+        .await
+        .expect("enforcing the limits failed");
+    assert!(removed >= 5, "{removed} events removed");
+    let kept = read(
+        store,
+        StreamFilter::with_stream_id::<ConformanceAccount>(&stream_id).including_deleted(),
+    )
+    .await;
+    assert_eq!(kept.len(), 1);
+
+    store
+        .set_stream_retention(&stream_id, StreamRetention::default())
+        .await
+        .expect("lifting the limits failed");
+    assert!(limits().await.is_unlimited());
+    append(
+        store,
+        &stream_id,
+        Metadata::default(),
+        &[ConformanceEvent::Withdrawn { amount: 1 }],
+        Some(6),
+    )
+    .await
+    .expect("append after enforcing the limits failed");
+    assert_eq!(versions().await, [6, 7]);
+}
+
+/// `read_all` pages through every event in position order, each once, however concurrent
+/// appends interleave, and steps over the positions a truncation removed.
+pub async fn read_all_pages_in_position_order(store: &impl EventStore) {
+    let streams: Vec<_> = (0..4)
+        .map(|_| ConformanceAccountUrn::new_random())
+        .collect();
+    for stream_id in &streams {
+        append(store, stream_id, Metadata::default(), &[opened()], None)
+            .await
+            .expect("append failed");
+    }
+    let from_position = read_stream(store, &streams[0]).await[0].global_position;
+
+    let deposits: Vec<_> = (1..=4)
+        .map(|amount| [ConformanceEvent::Deposited { amount }])
+        .collect();
+    let appends = streams.iter().flat_map(|stream_id| {
+        deposits
+            .iter()
+            .map(move |deposit| append(store, stream_id, Metadata::default(), deposit, None))
+    });
+    let ours = |events: &[PersistedEvent<ConformanceEvent>]| -> Vec<Vec<i64>> {
+        streams
+            .iter()
+            .map(|stream_id| {
+                let stream_id = Urn::from(stream_id.clone());
+                events
+                    .iter()
+                    .filter(|event| event.stream_id == stream_id)
+                    .map(|event| event.version)
+                    .collect()
+            })
+            .collect()
+    };
+    // A reader tailing the log while the appends commit must not step past any of them.
+    let tail = async {
+        let mut tailed = Vec::new();
+        let mut position = from_position;
+        for _ in 0..1_000 {
+            let page = store
+                .read_all::<ConformanceEvent>(position, 3)
+                .await
+                .expect("read_all failed");
+            position = page.next_position;
+            tailed.extend(page.events);
+            if ours(&tailed).iter().map(Vec::len).sum::<usize>() == 20 {
+                return tailed;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("tailing read_all skipped appended events");
+    };
+    let (appended, tailed) =
+        futures::future::join(futures::future::try_join_all(appends), tail).await;
+    appended.expect("concurrent appends failed");
+    assert!(
+        tailed
+            .windows(2)
+            .all(|pair| pair[0].global_position < pair[1].global_position),
+        "read_all out of position order"
+    );
+    assert_eq!(ours(&tailed), vec![vec![1, 2, 3, 4, 5]; 4]);
+    assert_eq!(
+        ours(&read_all_from(store, from_position, 3).await),
+        ours(&tailed)
+    );
+
+    // Truncated events leave the log; the rest of the check needs `truncate_stream`.
+    match settled(|| store.truncate_stream(&streams[0], 4)).await {
+        Err(err) if err.kind() == ErrorKind::Unsupported => return,
+        result => result.expect("truncate failed"),
+    };
+    let mut expected = vec![vec![1, 2, 3, 4, 5]; 4];
+    expected[0] = vec![4, 5];
+    assert_eq!(
+        ours(&read_all_from(store, from_position, 3).await),
+        expected
+    );
+}
+
+/// `stream_info` describes a missing stream as not existing, and keeps the head version of a
+/// truncated stream, the creation time of its oldest kept event and the soft deletion mark;
+/// a hard-deleted stream no longer exists.
+pub async fn stream_info_describes_streams(store: &impl EventStore) {
+    if !supports(store.stream_info(&ConformanceAccountUrn::new_random())).await {
+        return;
+    }
+    let stream_id = ConformanceAccountUrn::new_random();
+    let info = || async {
+        store
+            .stream_info(&stream_id)
+            .await
+            .expect("stream_info failed")
+    };
+    assert_eq!(info().await, StreamState::default());
+
+    append(store, &stream_id, Metadata::default(), &[opened()], None)
+        .await
+        .expect("append failed");
+    for amount in [1, 2] {
+        append(
+            store,
+            &stream_id,
+            Metadata::default(),
+            &[ConformanceEvent::Deposited { amount }],
+            None,
+        )
+        .await
+        .expect("append failed");
+    }
+    let events = read_stream(store, &stream_id).await;
+    let live = info().await;
+    assert!(live.exists && !live.deleted, "{live:?}");
+    assert_eq!(live.version, 3);
+    assert_eq!(live.created, Some(events[0].created));
+    assert_eq!(live.updated, Some(events[2].created));
+
+    settled(|| store.truncate_stream(&stream_id, 3))
+        .await
+        .expect("truncate failed");
+    let truncated = info().await;
+    assert!(truncated.exists && !truncated.deleted, "{truncated:?}");
+    assert_eq!(truncated.version, 3);
+    assert_eq!(truncated.created, Some(events[2].created));
+    assert_eq!(truncated.updated, live.updated);
+
+    store
+        .delete_stream(&stream_id, DeletionMode::Soft)
+        .await
+        .expect("soft delete failed");
+    let deleted = info().await;
+    assert!(deleted.exists && deleted.deleted, "{deleted:?}");
+    assert_eq!(deleted.version, 3);
+
+    settled(|| store.delete_stream(&stream_id, DeletionMode::Hard))
+        .await
+        .expect("hard delete failed");
+    assert_eq!(info().await, StreamState::default());
+}
+
+/// Renaming moves a stream's events, versions, positions and settings to the new id and frees
+/// the old one. A missing source is a `NotFound`, checked before an existing target, which
+/// is a `Conflict` that leaves both streams as they were.
+pub async fn renamed_streams_move_their_events(store: &impl EventStore) {
+    let (old_id, new_id) = (
+        ConformanceAccountUrn::new_random(),
+        ConformanceAccountUrn::new_random(),
+    );
+    if !supports(store.rename_stream(&old_id, &new_id)).await {
+        return;
+    }
+    let taken = ConformanceAccountUrn::new_random();
+    let rename = |from: &ConformanceAccountUrn, to: &ConformanceAccountUrn| {
+        let (from, to) = (from.clone(), to.clone());
+        async move { store.rename_stream(&from, &to).await }
+    };
+
+    let err = rename(&old_id, &new_id)
+        .await
+        .expect_err("renaming a missing stream succeeded");
+    assert_eq!(err.kind(), ErrorKind::NotFound, "{err:?}");
+
+    append(store, &taken, Metadata::default(), &[opened()], None)
+        .await
+        .expect("append failed");
+    let err = rename(&old_id, &taken)
+        .await
+        .expect_err("renaming a missing stream onto a taken id succeeded");
+    assert_eq!(err.kind(), ErrorKind::NotFound, "{err:?}");
+
+    let events = [
+        opened(),
+        ConformanceEvent::Deposited { amount: 10 },
+        ConformanceEvent::Withdrawn { amount: 3 },
+    ];
+    append(store, &old_id, Metadata::default(), &events, None)
+        .await
+        .expect("append failed");
+    let retention = StreamRetention::default().with_max_count(100);
+    store
+        .set_stream_retention(&old_id, retention)
+        .await
+        .expect("setting the limits failed");
+    let metadata = StreamMetadata::default().with_owner("ada");
+    store
+        .set_stream_metadata(&old_id, metadata.clone())
+        .await
+        .expect("setting stream metadata failed");
+    let before = read_stream(store, &old_id).await;
+
+    let err = rename(&old_id, &taken)
+        .await
+        .expect_err("renaming onto a taken id succeeded");
+    assert_eq!(err.kind(), ErrorKind::Conflict, "{err:?}");
+    assert_eq!(read_stream(store, &old_id).await.len(), 3);
+    assert_eq!(read_stream(store, &taken).await.len(), 1);
+
+    rename(&old_id, &new_id).await.expect("rename failed");
+    let moved = read_stream(store, &new_id).await;
+    let expected_stream = Urn::from(new_id.clone());
+    assert!(moved.iter().all(|e| e.stream_id == expected_stream));
+    let summary = |events: &[PersistedEvent<ConformanceEvent>]| -> Vec<_> {
+        events
+            .iter()
+            .map(|e| (e.id, e.version, e.global_position, e.data.clone()))
+            .collect()
+    };
+    assert_eq!(summary(&moved), summary(&before));
+    assert_eq!(
+        store
+            .stream_retention(&new_id)
+            .await
+            .expect("reading the limits failed"),
+        retention
+    );
+    assert_eq!(
+        store
+            .stream_metadata(&new_id)
+            .await
+            .expect("reading stream metadata failed"),
+        metadata
+    );
+
+    assert!(read_stream(store, &old_id).await.is_empty());
+    assert!(
+        !store
+            .stream_info(&old_id)
+            .await
+            .expect("stream_info failed")
+            .exists
+    );
+    append(
+        store,
+        &new_id,
+        Metadata::default(),
+        &[ConformanceEvent::Deposited { amount: 1 }],
+        Some(3),
+    )
+    .await
+    .expect("append to the renamed stream failed");
+    append(store, &old_id, Metadata::default(), &[opened()], Some(0))
+        .await
+        .expect("the old id isn't free again");
+}
+
+/// Listing streams of a type pages through every live one of that type, each once, with its
+/// type and version; other types and soft-deleted streams are left out.
+pub async fn list_streams_pages_by_stream_type(store: &impl EventStore) {
+    if !supports(store.list_streams(None, StreamPage::first(1))).await {
+        return;
+    }
+    // Types unique to this run keep other checks' streams out of the listings.
+    let run = uuid::Uuid::new_v4();
+    let listed_type = format!("ConformanceListed-{run}");
+    let other_type = format!("ConformanceOther-{run}");
+    let mut listed = Vec::new();
+    for (stream_type, count) in [(&listed_type, 5), (&other_type, 2)] {
+        for _ in 0..count {
+            let stream_id = ConformanceAccountUrn::new_random();
+            store
+                .store_events::<ConformanceAccount>(
+                    &stream_id,
+                    stream_type,
+                    Metadata::default(),
+                    &[opened(), ConformanceEvent::Deposited { amount: 1 }],
+                    None,
+                )
+                .await
+                .expect("append failed");
+            if stream_type == &listed_type {
+                listed.push(stream_id);
+            }
+        }
+    }
+    let deleted = listed.pop().expect("five listed streams");
+    store
+        .delete_stream(&deleted, DeletionMode::Soft)
+        .await
+        .expect("soft delete failed");
+
+    let (streams, pages) = list_all(store, Some(&listed_type), StreamPage::first(2)).await;
+    assert_eq!(pages, 2, "four streams take two pages of two");
+    assert!(streams
+        .iter()
+        .all(|stream| stream.stream_type == listed_type && stream.version == 2));
+    assert_eq!(
+        stream_ids(streams),
+        sorted(listed.into_iter().map(Urn::from))
+    );
+
+    let (streams, pages) = list_all(store, Some(&other_type), StreamPage::first(1)).await;
+    assert_eq!((streams.len(), pages), (2, 2));
+}
+
+/// Stream metadata reads back as set, and a page's labels select the streams carrying all
+/// of them; setting metadata on a stream that doesn't exist is a `NotFound`.
+pub async fn stream_metadata_selects_streams_by_label(store: &impl EventStore) {
+    if !supports(store.stream_metadata(&ConformanceAccountUrn::new_random())).await {
+        return;
+    }
+    let missing = ConformanceAccountUrn::new_random();
+    let err = store
+        .set_stream_metadata(&missing, StreamMetadata::default())
+        .await
+        .expect_err("setting metadata on a missing stream succeeded");
+    assert_eq!(err.kind(), ErrorKind::NotFound, "{err:?}");
+
+    // A label unique to this run keeps other checks' streams out of the listings.
+    let run = uuid::Uuid::new_v4().to_string();
+    let mut labelled = Vec::new();
+    for region in ["eu", "eu", "us"] {
+        let stream_id = ConformanceAccountUrn::new_random();
+        append(store, &stream_id, Metadata::default(), &[opened()], None)
+            .await
+            .expect("append failed");
+        let metadata = StreamMetadata::default()
+            .with_owner("ada")
+            .with_acl("read", ["team-billing"])
+            .with_label("run", run.clone())
+            .with_label("region", region);
+        store
+            .set_stream_metadata(&stream_id, metadata.clone())
+            .await
+            .expect("setting stream metadata failed");
+        let read_back = store
+            .stream_metadata(&stream_id)
+            .await
+            .expect("reading stream metadata failed");
+        assert_eq!(read_back, metadata);
+        labelled.push((Urn::from(stream_id), region));
+    }
+    let unlabelled = ConformanceAccountUrn::new_random();
+    append(store, &unlabelled, Metadata::default(), &[opened()], None)
+        .await
+        .expect("append failed");
+    assert_eq!(
+        store
+            .stream_metadata(&unlabelled)
+            .await
+            .expect("reading stream metadata failed"),
+        StreamMetadata::default()
+    );
+
+    let (in_eu, pages) = list_all(
+        store,
+        None,
+        StreamPage::first(1)
+            .with_label("run", run.clone())
+            .with_label("region", "eu"),
+    )
+    .await;
+    assert_eq!(pages, 2, "following pages must keep the labels");
+    let expected = labelled
+        .iter()
+        .filter(|(_, region)| *region == "eu")
+        .map(|(id, _)| id.clone());
+    assert_eq!(stream_ids(in_eu), sorted(expected));
+
+    let (in_run, _) = list_all(store, None, StreamPage::first(10).with_label("run", run)).await;
+    let expected = labelled.into_iter().map(|(id, _)| id);
+    assert_eq!(stream_ids(in_run), sorted(expected));
+}
+
+/// A reachable store reports healthy, with pool figures that add up when it has a pool.
+pub async fn reports_healthy(store: &impl EventStore) {
+    let health = store.health_check().await.expect("health check failed");
+    if let (Some(connections), Some(idle)) = (health.connections, health.idle_connections) {
+        assert!(
+            idle <= connections,
+            "{idle} idle of {connections} connections"
+        );
+    }
+    if let (Some(connections), Some(max)) = (health.connections, health.max_connections) {
+        assert!(
+            connections <= max,
+            "{connections} connections over a cap of {max}"
+        );
+    }
+}
+
+/// Run every check against `store`.
+pub async fn run_all(store: &impl EventStore) {
+    append_and_read_back(store).await;
+    appends_continue_the_stream(store).await;
+    expected_version_conflicts(store).await;
+    filters_select_events(store).await;
+    metadata_round_trips(store).await;
+    deleted_streams_leave_reads(store).await;
+    compact_stream_keeps_versions(store).await;
+    truncated_streams_keep_their_head(store).await;
+    stream_info_describes_streams(store).await;
+    renamed_streams_move_their_events(store).await;
+    stream_retention_expires_events(store).await;
+    read_all_pages_in_position_order(store).await;
+    list_streams_pages_by_stream_type(store).await;
+    stream_metadata_selects_streams_by_label(store).await;
+    reports_healthy(store).await;
+}
+
+/// Generate one `#[tokio::test]` per [`conformance`](crate::conformance) check for an
+/// [`EventStore`].
+///
+/// `$factory` is called for each test and awaited for a fresh store: an `async fn` or a
+/// closure returning a future. The calling crate needs `tokio` (with `macros` and `rt`) as a
+/// dev-dependency.
+///
+/// ```rust,ignore
+/// mod conformance {
+///     replay_persistence::event_store_conformance_tests!(|| async { MyStore::connect().await });
+/// }
+/// ```
+#[macro_export]
+macro_rules! event_store_conformance_tests {
+    ($factory:expr) => {
+        $crate::event_store_conformance_tests!(@tests $factory;
+            append_and_read_back,
+            appends_continue_the_stream,
+            expected_version_conflicts,
+            filters_select_events,
+            metadata_round_trips,
+            deleted_streams_leave_reads,
+            compact_stream_keeps_versions,
+            truncated_streams_keep_their_head,
+            stream_info_describes_streams,
+            renamed_streams_move_their_events,
+            stream_retention_expires_events,
+            read_all_pages_in_position_order,
+            list_streams_pages_by_stream_type,
+            stream_metadata_selects_streams_by_label,
+            reports_healthy,
+        );
+    };
+
+    (@tests $factory:expr; $($check:ident),* $(,)?) => {
+        $(
+            #[tokio::test]
+            async fn $check() {
+                let store = ($factory)().await;
+                $crate::conformance::$check(&store).await;
+            }
+        )*
+    };
+}
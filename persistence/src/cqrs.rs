@@ -1,24 +1,208 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
 use futures::{StreamExt, TryStreamExt};
 
-use replay::{Aggregate, Event};
+use replay::{Aggregate, CommandContext, Event, StreamId, WithOutput};
 use urn::Urn;
 
-use super::{AggregateVersion, CompactionOutcome, EventStore, PersistedEvent};
+use super::{
+    AggregateVersion, CompactionOutcome, EventStore, ExecutionTimeline, PersistedEvent,
+    RateLimitRequest, RateLimiter, StreamFilter,
+};
 
-#[derive(Clone)]
+/// Check run on the final metadata of every command and compaction issued through a
+/// [`Cqrs`] handle; an error rejects the write before anything is appended.
+pub type MetadataValidator =
+    Arc<dyn Fn(&replay::Metadata) -> Result<(), replay::Error> + Send + Sync>;
+
+/// Entry point for reading and writing aggregates through an [`EventStore`].
+///
+/// Errors returned from `Cqrs` are [recorded](replay::Error::record) as structured
+/// `tracing` events on the current span before they reach the caller, so failed commands
+/// and store outages show up in traces without application-side logging.
 pub struct Cqrs<ES: EventStore> {
     store: Arc<ES>,
+    /// Default actor stamped on commands whose metadata names none.
+    actor: Option<Arc<str>>,
+    /// Triggering event whose identity is chained into the metadata of issued commands.
+    causation: Option<Arc<Causation>>,
+    /// Rejects writes whose metadata doesn't meet the application's requirements.
+    metadata_validator: Option<MetadataValidator>,
+    /// Rejects commands arriving faster than the application allows.
+    rate_limiter: Option<Arc<dyn RateLimiter>>,
+    /// Commands slower than this are logged as warnings.
+    slow_command_threshold: Option<Duration>,
+}
+
+// Manual impl: the store sits behind an `Arc`, so cloning a handle never requires `ES: Clone`.
+impl<ES: EventStore> Clone for Cqrs<ES> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            actor: self.actor.clone(),
+            causation: self.causation.clone(),
+            metadata_validator: self.metadata_validator.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            slow_command_threshold: self.slow_command_threshold,
+        }
+    }
+}
+
+/// The timeline of a command whose events are committed, still running its post-commit phase.
+struct Committed {
+    timeline: ExecutionTimeline,
+    at: Instant,
+}
+
+impl Committed {
+    fn at(timeline: ExecutionTimeline) -> Self {
+        Self {
+            timeline,
+            at: Instant::now(),
+        }
+    }
+
+    fn finish(mut self) -> ExecutionTimeline {
+        self.timeline.post_commit = self.at.elapsed();
+        self.timeline
+    }
+}
+
+/// Causal identity inherited from a triggering event, see [`Cqrs::caused_by`].
+#[derive(Debug)]
+struct Causation {
+    event_id: String,
+    correlation_id: String,
 }
 
 impl<ES: EventStore> Cqrs<ES> {
     pub fn new(event_store: ES) -> Self {
         Self {
             store: Arc::new(event_store),
+            actor: None,
+            causation: None,
+            metadata_validator: None,
+            rate_limiter: None,
+            slow_command_threshold: None,
+        }
+    }
+
+    /// A handle over the same store that issues commands on behalf of `actor`.
+    ///
+    /// [`execute`](Self::execute) stamps the actor (a user id, a service name, ...) into the
+    /// metadata of every persisted event unless the caller's metadata already names one.
+    /// Typically created per request:
+    ///
+    /// ```rust,ignore
+    /// let cqrs = cqrs.with_actor(format!("user:{}", session.user_id));
+    /// cqrs.execute::<BankAccount>(&id, Metadata::default(), command, &(), None).await?;
+    /// ```
+    pub fn with_actor(&self, actor: impl Into<String>) -> Self {
+        Self {
+            actor: Some(Arc::from(actor.into())),
+            ..self.clone()
         }
     }
 
+    /// A handle over the same store that issues commands in reaction to `event`.
+    ///
+    /// [`execute`](Self::execute) stamps the event's id as `causation_id` and carries its
+    /// correlation id forward (see [`PersistedEvent::chained_correlation_id`]), so the causal
+    /// graph of a saga or post-commit hook can be rebuilt from the store. Ids already present
+    /// in the caller's metadata are kept.
+    ///
+    /// ```rust,ignore
+    /// while let Some(event) = events.try_next().await? {
+    ///     cqrs.caused_by(&event)
+    ///         .execute::<Invoice>(&invoice_id, Metadata::default(), Issue, &(), None)
+    ///         .await?;
+    /// }
+    /// ```
+    pub fn caused_by<E>(&self, event: &PersistedEvent<E>) -> Self {
+        Self {
+            causation: Some(Arc::new(Causation {
+                event_id: event.id.to_string(),
+                correlation_id: event.chained_correlation_id(),
+            })),
+            ..self.clone()
+        }
+    }
+
+    /// A handle over the same store that rejects writes whose metadata fails `validator`.
+    ///
+    /// The validator sees the metadata after this handle's actor and causal ids are stamped
+    /// in. A rejected [`execute`](Self::execute) fails before the aggregate is loaded, a
+    /// rejected [`compact`](Self::compact) before the store is touched. See
+    /// [`require_metadata`](Self::require_metadata) for the common case.
+    pub fn with_metadata_validator<F>(&self, validator: F) -> Self
+    where
+        F: Fn(&replay::Metadata) -> Result<(), replay::Error> + Send + Sync + 'static,
+    {
+        Self {
+            metadata_validator: Some(Arc::new(validator)),
+            ..self.clone()
+        }
+    }
+
+    /// A handle over the same store that rejects writes missing any of `keys`, failing with
+    /// `InvalidInput` (see [`Metadata::require`](replay::Metadata::require)).
+    ///
+    /// ```rust,ignore
+    /// let cqrs = Cqrs::new(store).require_metadata(&[
+    ///     Metadata::TENANT_ID_KEY,
+    ///     Metadata::ACTOR_KEY,
+    ///     Metadata::CORRELATION_ID_KEY,
+    /// ]);
+    /// ```
+    pub fn require_metadata(&self, keys: &[&str]) -> Self {
+        let keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+        self.with_metadata_validator(move |metadata| {
+            let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+            metadata.require(&keys)
+        })
+    }
+
+    /// A handle over the same store that admits commands through `limiter` (see
+    /// [`RateLimiter`]).
+    ///
+    /// The limiter sees the command's stream and its actor once the metadata is validated,
+    /// before the aggregate is loaded. A rejected [`execute`](Self::execute) fails with
+    /// `RateLimited`, its [`retry_after`](replay::Error::retry_after) set to the limiter's
+    /// wait.
+    ///
+    /// ```rust,ignore
+    /// let cqrs = Cqrs::new(store)
+    ///     .with_rate_limiter(TokenBucketRateLimiter::per_stream(20, Duration::from_millis(200)));
+    /// ```
+    pub fn with_rate_limiter(&self, limiter: impl RateLimiter + 'static) -> Self {
+        Self {
+            rate_limiter: Some(Arc::new(limiter)),
+            ..self.clone()
+        }
+    }
+
+    /// A handle over the same store that logs commands taking longer than `threshold`.
+    ///
+    /// A slow [`execute`](Self::execute) or [`execute_with_output`](Self::execute_with_output),
+    /// successful or not, emits a warning on the `replay_persistence::slow` tracing target with
+    /// the aggregate type, stream id and `elapsed_ms`, covering the load, the command handler
+    /// and the append. Successful commands also carry their [`ExecutionTimeline`] as
+    /// `hydrate_ms`, `handle_ms` and `append_ms`.
+    pub fn with_slow_command_threshold(&self, threshold: Duration) -> Self {
+        Self {
+            slow_command_threshold: Some(threshold),
+            ..self.clone()
+        }
+    }
+
+    /// The default actor of this handle, if any.
+    pub fn actor(&self) -> Option<&str> {
+        self.actor.as_deref()
+    }
+
     /// Shared handle to the underlying event store.
     pub(crate) fn store(&self) -> &Arc<ES> {
         &self.store
@@ -33,6 +217,11 @@ impl<ES: EventStore> Cqrs<ES> {
     ///   (events with version ≤ n are included).  Use this for time-travel reads.
     /// - `at_timestamp`: optional inclusive upper bound on the event creation timestamp
     ///   (events created at or before the given instant are included).
+    ///
+    /// A [soft-deleted](crate::DeletionMode::Soft) stream fails with `NotFound`, and so do
+    /// commands sent to it; so does a stream holding a
+    /// [tombstone](replay::Event::is_tombstone) event, unless `at_stream_version` or
+    /// `at_timestamp` stop before it.
     pub async fn fetch_aggregate_at<A: Aggregate + Sync>(
         &self,
         id: &A::StreamId,
@@ -40,20 +229,62 @@ impl<ES: EventStore> Cqrs<ES> {
         at_stream_version: Option<i64>,
         at_timestamp: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Result<A, A::Error> {
+        let (aggregate, head) = self
+            .hydrate_at::<A>(id, aggregate_version, at_stream_version, at_timestamp)
+            .await?;
+        // Reads leave soft-deleted streams out, so only an empty stream can be one.
+        if head == 0 {
+            self.ensure_not_deleted(id)
+                .await
+                .map_err(|e| A::Error::from(e.recorded()))?;
+        }
+        Ok(aggregate)
+    }
+
+    /// [`fetch_aggregate_at`](Self::fetch_aggregate_at), also returning the version of the
+    /// last event applied (`0` for a stream without events). A soft-deleted stream reads as
+    /// empty here; commands don't look it up, as the store refuses to append to it.
+    async fn hydrate_at<A: Aggregate + Sync>(
+        &self,
+        id: &A::StreamId,
+        aggregate_version: AggregateVersion,
+        at_stream_version: Option<i64>,
+        at_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<(A, i64), A::Error> {
         let events = self
             .store
             .stream_events_by_stream_id::<A>(id, aggregate_version, at_stream_version, at_timestamp)
-            .map_err(A::Error::from);
+            .map_err(|e| A::Error::from(e.recorded()));
 
         let mut stream = A::with_id(id.clone());
+        let mut head = 0;
+        let mut tombstone = None;
 
         futures::pin_mut!(events);
 
         while let Some(event) = events.try_next().await? {
-            stream.apply(event.data);
+            if event.data.is_tombstone() {
+                tombstone.get_or_insert(event.version);
+            }
+            let (stream_id, version) = (event.stream_id.clone(), event.version);
+            event
+                .apply_to(&mut stream)
+                .map_err(|e| A::Error::from(apply_error(e, &stream_id, version)))?;
+            head = version;
+        }
+
+        if let Some(version) = tombstone {
+            let stream_id: Urn = id.clone().into();
+            return Err(A::Error::from(
+                replay::Error::not_found("stream was ended by a tombstone event")
+                    .with_operation("fetch_aggregate")
+                    .with_context("stream_id", stream_id)
+                    .with_context("version", version)
+                    .recorded(),
+            ));
         }
 
-        Ok(stream)
+        Ok((stream, head))
     }
 
     /// Reconstruct an aggregate at its latest state.
@@ -76,6 +307,68 @@ impl<ES: EventStore> Cqrs<ES> {
             .await
     }
 
+    /// Reconstruct several aggregates at their latest state with one read, keyed by stream id.
+    ///
+    /// The events of every stream in `ids` are fetched by a single filtered query and folded
+    /// into their aggregates, instead of one read per aggregate. Ids without events map to
+    /// a fresh aggregate, as with [`fetch_aggregate`](Self::fetch_aggregate), and
+    /// soft-deleted streams and streams ended by a tombstone are left out.
+    ///
+    /// ```rust,ignore
+    /// let accounts = cqrs.fetch_aggregates::<BankAccount>(&account_ids).await?;
+    /// let balance = accounts[&account_ids[0].clone().into()].balance;
+    /// ```
+    pub async fn fetch_aggregates<A: Aggregate + Sync>(
+        &self,
+        ids: &[A::StreamId],
+    ) -> Result<HashMap<Urn, A>, A::Error> {
+        let mut aggregates: HashMap<Urn, A> = ids
+            .iter()
+            .map(|id| (id.clone().into(), A::with_id(id.clone())))
+            .collect();
+        if aggregates.is_empty() {
+            return Ok(aggregates);
+        }
+
+        let filter = StreamFilter::with_stream_ids::<A>(ids)
+            .and_aggregate_version(AggregateVersion::Latest.as_option());
+        let events = self
+            .store
+            .stream_events::<A::Event>(filter)
+            .map_err(|e| A::Error::from(e.recorded()));
+
+        futures::pin_mut!(events);
+
+        let mut empty: HashSet<Urn> = aggregates.keys().cloned().collect();
+        let mut ended = HashSet::new();
+        while let Some(event) = events.try_next().await? {
+            if let Some(aggregate) = aggregates.get_mut(&event.stream_id) {
+                let (stream_id, version) = (event.stream_id.clone(), event.version);
+                if event.data.is_tombstone() {
+                    ended.insert(stream_id.clone());
+                }
+                event
+                    .apply_to(aggregate)
+                    .map_err(|e| A::Error::from(apply_error(e, &stream_id, version)))?;
+                empty.remove(&stream_id);
+            }
+        }
+        aggregates.retain(|stream_id, _| !ended.contains(stream_id));
+
+        for stream_id in empty {
+            let deleted = self
+                .store
+                .is_deleted(&stream_id)
+                .await
+                .map_err(|e| A::Error::from(e.recorded()))?;
+            if deleted {
+                aggregates.remove(&stream_id);
+            }
+        }
+
+        Ok(aggregates)
+    }
+
     pub async fn execute<A: Aggregate>(
         &self,
         id: &A::StreamId,
@@ -88,10 +381,73 @@ impl<ES: EventStore> Cqrs<ES> {
         A::Event: 'static,
         A::Error: 'static,
     {
+        self.execute_timed::<A>(id, metadata, command, services, expected_version)
+            .await
+            .map(|(aggregate, _)| aggregate)
+    }
+
+    /// Like [`execute`](Self::execute), also returning how long each phase of the command
+    /// took (see [`ExecutionTimeline`]).
+    ///
+    /// Every `execute` reports its timeline as a `debug` event on the
+    /// `replay_persistence::timeline` tracing target, with one `*_us` field per phase; this
+    /// method hands it to the caller as well.
+    ///
+    /// ```rust,ignore
+    /// let (account, timeline) = cqrs
+    ///     .execute_timed::<BankAccount>(&id, Metadata::default(), command, &(), None)
+    ///     .await?;
+    /// if timeline.append > timeline.handle {
+    ///     // the database, not the domain logic, dominates
+    /// }
+    /// ```
+    pub async fn execute_timed<A: Aggregate>(
+        &self,
+        id: &A::StreamId,
+        metadata: replay::Metadata,
+        command: A::Command,
+        services: &A::Services,
+        expected_version: Option<i64>,
+    ) -> Result<(A, ExecutionTimeline), A::Error>
+    where
+        A::Event: 'static,
+        A::Error: 'static,
+    {
+        let started = Instant::now();
+        let result = self
+            .execute_command::<A>(id, metadata, command, services, expected_version)
+            .await;
+        self.finish::<A, _>(id, started, result)
+    }
+
+    async fn execute_command<A: Aggregate>(
+        &self,
+        id: &A::StreamId,
+        metadata: replay::Metadata,
+        command: A::Command,
+        services: &A::Services,
+        expected_version: Option<i64>,
+    ) -> Result<(A, Committed), A::Error>
+    where
+        A::Event: 'static,
+        A::Error: 'static,
+    {
+        let metadata = self
+            .validate(self.stamp(metadata))
+            .and_then(|metadata| self.admit::<A>(id, metadata))
+            .map_err(|e| A::Error::from(e.recorded()))?;
+
+        let mut timeline = ExecutionTimeline::default();
+
         // Always load the latest (current) event stream for command handling.
-        let mut aggregate = self
-            .fetch_aggregate_at::<A>(id, AggregateVersion::Latest, expected_version, None)
+        let phase = Instant::now();
+        let (mut aggregate, head) = self
+            .hydrate_at::<A>(id, AggregateVersion::Latest, expected_version, None)
             .await?;
+        timeline.hydrate = phase.elapsed();
+        A::lifecycle(&command)
+            .check(&id.clone().into(), head)
+            .map_err(|e| A::Error::from(e.recorded()))?;
 
         let stream_type = A::stream_type();
 
@@ -102,11 +458,22 @@ impl<ES: EventStore> Cqrs<ES> {
         //
         // A producer error is fatal and rolls back the whole append; it is surfaced to the
         // store as a `replay::Error` so the streaming contract (`Error = replay::Error`) holds.
+        let phase = Instant::now();
+        let context =
+            CommandContext::new(id.clone().into(), head, metadata.clone(), SystemTime::now());
         let event_stream = aggregate
-            .handle_stream(command, services)
-            .await?
+            .handle_in_context(command, services, &context)
+            .await
+            .inspect_err(record_domain_error)?;
+        timeline.handle = phase.elapsed();
+
+        // The producer runs inside the append; its share of the time is moved to `handle`.
+        let producing = AtomicU64::new(0);
+        let event_stream = crate::timeline::timed(event_stream, &producing)
             .map_err(|e| replay::Error::internal("aggregate event producer failed").with_source(e));
 
+        let phase = Instant::now();
+        let mut failed = None;
         self.store
             .store_events_stream::<A, _, _>(
                 id,
@@ -114,12 +481,131 @@ impl<ES: EventStore> Cqrs<ES> {
                 metadata,
                 event_stream,
                 expected_version,
-                |event: &PersistedEvent<A::Event>| aggregate.apply(event.data.clone()),
+                |event: &PersistedEvent<A::Event>| {
+                    fold_appended(&mut aggregate, &mut failed, event)
+                },
             )
             .await
-            .map_err(A::Error::from)?;
+            .map_err(|e| A::Error::from(e.recorded()))?;
+        // The events are stored; the aggregate in hand just doesn't reflect them.
+        if let Some(error) = failed {
+            return Err(A::Error::from(error.recorded()));
+        }
+        let producing = Duration::from_nanos(producing.into_inner());
+        timeline.handle += producing;
+        timeline.append = phase.elapsed().saturating_sub(producing);
 
-        Ok(aggregate)
+        Ok((aggregate, Committed::at(timeline)))
+    }
+
+    /// Like [`execute`](Self::execute) for a command that also produces a value: the events
+    /// of [`handle_with_output`](WithOutput::handle_with_output) are appended and the output
+    /// is returned together with the updated aggregate.
+    ///
+    /// ```rust,ignore
+    /// let (user, output) = cqrs
+    ///     .execute_with_output::<User>(&id, metadata, UserCommand::Register { email }, &services, None)
+    ///     .await?;
+    /// let api_key = output.into_register().expect("Register outputs an api key");
+    /// ```
+    ///
+    /// The command goes to `handle_with_output`, so a
+    /// [`handle_in_context`](Aggregate::handle_in_context) override isn't consulted.
+    pub async fn execute_with_output<A: WithOutput>(
+        &self,
+        id: &A::StreamId,
+        metadata: replay::Metadata,
+        command: A::Command,
+        services: &A::Services,
+        expected_version: Option<i64>,
+    ) -> Result<(A, A::Output), A::Error>
+    where
+        A::Event: 'static,
+        A::Error: 'static,
+    {
+        self.execute_with_output_timed::<A>(id, metadata, command, services, expected_version)
+            .await
+            .map(|(result, _)| result)
+    }
+
+    /// Like [`execute_with_output`](Self::execute_with_output), also returning the command's
+    /// [`ExecutionTimeline`]; see [`execute_timed`](Self::execute_timed).
+    pub async fn execute_with_output_timed<A: WithOutput>(
+        &self,
+        id: &A::StreamId,
+        metadata: replay::Metadata,
+        command: A::Command,
+        services: &A::Services,
+        expected_version: Option<i64>,
+    ) -> Result<((A, A::Output), ExecutionTimeline), A::Error>
+    where
+        A::Event: 'static,
+        A::Error: 'static,
+    {
+        let started = Instant::now();
+        let result = self
+            .execute_command_with_output::<A>(id, metadata, command, services, expected_version)
+            .await;
+        self.finish::<A, _>(id, started, result)
+    }
+
+    async fn execute_command_with_output<A: WithOutput>(
+        &self,
+        id: &A::StreamId,
+        metadata: replay::Metadata,
+        command: A::Command,
+        services: &A::Services,
+        expected_version: Option<i64>,
+    ) -> Result<((A, A::Output), Committed), A::Error>
+    where
+        A::Event: 'static,
+        A::Error: 'static,
+    {
+        let metadata = self
+            .validate(self.stamp(metadata))
+            .and_then(|metadata| self.admit::<A>(id, metadata))
+            .map_err(|e| A::Error::from(e.recorded()))?;
+
+        let mut timeline = ExecutionTimeline::default();
+
+        let phase = Instant::now();
+        let (mut aggregate, head) = self
+            .hydrate_at::<A>(id, AggregateVersion::Latest, expected_version, None)
+            .await?;
+        timeline.hydrate = phase.elapsed();
+        A::lifecycle(&command)
+            .check(&id.clone().into(), head)
+            .map_err(|e| A::Error::from(e.recorded()))?;
+
+        let phase = Instant::now();
+        let (events, output) = aggregate
+            .handle_with_output(command, services)
+            .await
+            .inspect_err(record_domain_error)?;
+        timeline.handle = phase.elapsed();
+
+        let phase = Instant::now();
+        let mut failed = None;
+        self.store
+            .store_events_stream::<A, _, _>(
+                id,
+                A::stream_type(),
+                metadata,
+                futures::stream::iter(events.into_iter().map(Ok)),
+                expected_version,
+                |event: &PersistedEvent<A::Event>| {
+                    fold_appended(&mut aggregate, &mut failed, event)
+                },
+            )
+            .await
+            .map_err(|e| A::Error::from(e.recorded()))?;
+        // The events are stored; the aggregate in hand just doesn't reflect them.
+        if let Some(error) = failed {
+            return Err(A::Error::from(error.recorded()));
+        }
+        timeline.append = phase.elapsed();
+
+        Ok(((aggregate, output), Committed::at(timeline)))
     }
 
     /// Compact the event stream for an aggregate.
@@ -141,7 +627,38 @@ impl<ES: EventStore> Cqrs<ES> {
     where
         A: replay::Aggregate + replay::Compactable + Sync,
     {
-        self.store.compact(aggregate, metadata).await
+        let metadata = self
+            .validate(self.stamp(metadata))
+            .inspect_err(replay::Error::record)?;
+
+        self.store
+            .compact(aggregate, metadata)
+            .await
+            .inspect_err(replay::Error::record)
+    }
+
+    /// Replace the events of `id`'s stream up to version `up_to_version` with one
+    /// [`Snapshottable::snapshot_event`](replay::Snapshottable::snapshot_event), deleting
+    /// them. Returns how many events were replaced.
+    ///
+    /// See [`EventStore::compact_stream`](crate::EventStore::compact_stream).
+    pub async fn compact_stream<A>(
+        &self,
+        id: &A::StreamId,
+        up_to_version: i64,
+        metadata: replay::Metadata,
+    ) -> Result<u64, replay::Error>
+    where
+        A: replay::Aggregate + replay::Snapshottable,
+    {
+        let metadata = self
+            .validate(self.stamp(metadata))
+            .inspect_err(replay::Error::record)?;
+
+        self.store
+            .compact_stream::<A>(id, up_to_version, metadata)
+            .await
+            .inspect_err(replay::Error::record)
     }
 
     /// Whether `id`'s stream has changed since it was last compacted.
@@ -153,8 +670,122 @@ impl<ES: EventStore> Cqrs<ES> {
     where
         A: replay::Aggregate,
     {
+        self.store
+            .needs_compaction(id)
+            .await
+            .inspect_err(replay::Error::record)
+    }
+
+    /// Fail with `NotFound` when `stream_id` was soft-deleted.
+    async fn ensure_not_deleted(&self, stream_id: &impl StreamId) -> Result<(), replay::Error> {
+        if self.store.is_deleted(stream_id).await? {
+            return Err(replay::Error::not_found("stream was deleted")
+                .with_operation("fetch_aggregate")
+                .with_context("stream_id", stream_id.stream_urn()));
+        }
+        Ok(())
+    }
+
+    /// Fill in the actor and causal ids this handle carries, keeping any the caller set.
+    fn stamp(&self, mut metadata: replay::Metadata) -> replay::Metadata {
+        if let (Some(actor), None) = (&self.actor, metadata.actor()) {
+            metadata = metadata.with_actor(actor.as_ref());
+        }
+        if let Some(causation) = &self.causation {
+            if metadata.causation_id().is_none() {
+                metadata = metadata.with_causation_id(causation.event_id.as_str());
+            }
+            if metadata.correlation_id().is_none() {
+                metadata = metadata.with_correlation_id(causation.correlation_id.as_str());
+            }
+        }
+        #[cfg(feature = "opentelemetry")]
+        let metadata = crate::inject_trace_context(metadata);
+        metadata
+    }
+
+    /// Bookkeeping shared by the `execute` variants: failure metrics, the timeline and
+    /// slow-command warnings.
+    fn finish<A: Aggregate, T>(
+        &self,
+        id: &A::StreamId,
+        started: Instant,
+        result: Result<(T, Committed), A::Error>,
+    ) -> Result<(T, ExecutionTimeline), A::Error>
+    where
+        A::Error: 'static,
+    {
+        let result = result
+            .inspect_err(|e| crate::metrics::record_command_failure(A::stream_type(), e))
+            .map(|(value, committed)| (value, committed.finish()));
+        let timeline = result.as_ref().ok().map(|(_, timeline)| timeline);
+        let elapsed = started.elapsed();
+        let stream_id: Urn = id.clone().into();
+
+        if let Some(timeline) = timeline {
+            tracing::debug!(
+                target: "replay_persistence::timeline",
+                aggregate = A::stream_type(),
+                stream_id = %stream_id,
+                hydrate_us = timeline.hydrate.as_micros() as u64,
+                handle_us = timeline.handle.as_micros() as u64,
+                append_us = timeline.append.as_micros() as u64,
+                post_commit_us = timeline.post_commit.as_micros() as u64,
+                "command executed",
+            );
+        }
+        if self
+            .slow_command_threshold
+            .is_some_and(|threshold| elapsed > threshold)
+        {
+            tracing::warn!(
+                target: "replay_persistence::slow",
+                operation = "execute",
+                aggregate = A::stream_type(),
+                stream_id = %stream_id,
+                succeeded = timeline.is_some(),
+                elapsed_ms = elapsed.as_millis() as u64,
+                hydrate_ms = timeline.map(|t| t.hydrate.as_millis() as u64),
+                handle_ms = timeline.map(|t| t.handle.as_millis() as u64),
+                append_ms = timeline.map(|t| t.append.as_millis() as u64),
+                "slow command",
+            );
+        }
+        result
+    }
+
+    fn validate(&self, metadata: replay::Metadata) -> Result<replay::Metadata, replay::Error> {
+        match &self.metadata_validator {
+            Some(validator) => validator(&metadata).map(|()| metadata),
+            None => Ok(metadata),
+        }
+    }
+
+    /// Ask the rate limiter, if any, to let a command on `id` through.
+    fn admit<A: Aggregate>(
+        &self,
+        id: &A::StreamId,
+        metadata: replay::Metadata,
+    ) -> Result<replay::Metadata, replay::Error> {
+        let Some(limiter) = &self.rate_limiter else {
+            return Ok(metadata);
+        };
         let stream_id: Urn = id.clone().into();
-        self.store.needs_compaction(&stream_id).await
+        let request = RateLimitRequest {
+            stream_type: A::stream_type(),
+            stream_id: &stream_id,
+            actor: metadata.actor(),
+        };
+        match limiter.check(&request) {
+            Ok(()) => Ok(metadata),
+            Err(retry_after) => Err(replay::Error::rate_limited(
+                "too many commands, try again later",
+                retry_after,
+            )
+            .with_operation("execute")
+            .with_context("stream_id", &stream_id)
+            .with_context("actor", metadata.actor().unwrap_or("none"))),
+        }
     }
 
     pub async fn run_query<'a, Q, E>(&'a self, query: &'a mut Q) -> Result<(), replay::Error>
@@ -176,3 +807,627 @@ impl<ES: EventStore> Cqrs<ES> {
         Ok(())
     }
 }
+
+/// Record a command-handling error: structured when the aggregate's error type is
+/// [`replay::Error`] itself, otherwise via its `Display` form.
+fn record_domain_error<E: std::error::Error + 'static>(error: &E) {
+    match (error as &dyn std::error::Error).downcast_ref::<replay::Error>() {
+        Some(error) => error.record(),
+        None => tracing::debug!(target: "replay::error", error = %error, "command rejected"),
+    }
+}
+
+/// The error loading a stream fails with when its event at `version` doesn't apply: the
+/// store holds events the aggregate can't make sense of.
+fn apply_error(error: replay::Error, stream_id: &Urn, version: i64) -> replay::Error {
+    replay::Error::internal("a stored event could not be applied")
+        .with_operation("fetch_aggregate")
+        .with_context("stream_id", stream_id)
+        .with_context("version", version)
+        .with_source(error)
+        .recorded()
+}
+
+/// Fold an event the command just appended into the aggregate returned to the caller. The
+/// first event the aggregate refuses is kept in `failed`, and the events after it are left
+/// unapplied, so the command can fail rather than return a half-applied aggregate.
+fn fold_appended<A: Aggregate>(
+    aggregate: &mut A,
+    failed: &mut Option<replay::Error>,
+    event: &PersistedEvent<A::Event>,
+) {
+    if failed.is_some() {
+        return;
+    }
+    if let Err(error) = aggregate.apply_with_context(event.data.clone(), &event.context()) {
+        *failed = Some(
+            replay::Error::internal("an appended event could not be applied")
+                .with_operation("execute")
+                .with_context("stream_id", &event.stream_id)
+                .with_context("version", event.version)
+                .with_context("committed", true)
+                .with_source(error),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use futures::stream::BoxStream;
+    use futures::TryStreamExt;
+    use replay::{CommandContext, ErrorKind, EventContext, Metadata, WithId};
+    use replay_macros::Event;
+    use serde::{Deserialize, Serialize};
+    use urn::{Urn, UrnBuilder};
+
+    use super::Cqrs;
+    use crate::{
+        AggregateVersion, DeletionMode, EventStore, InMemoryEventStore, PersistedEvent,
+        StreamFilter,
+    };
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Event)]
+    enum CounterEvent {
+        Incremented,
+        Decremented,
+        Counted {
+            count: usize,
+        },
+        #[event(tombstone)]
+        Retired,
+    }
+
+    #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+    struct CounterUrn(Urn);
+
+    impl From<CounterUrn> for Urn {
+        fn from(urn: CounterUrn) -> Self {
+            urn.0
+        }
+    }
+
+    impl TryFrom<Urn> for CounterUrn {
+        type Error = String;
+
+        fn try_from(urn: Urn) -> Result<Self, Self::Error> {
+            Ok(CounterUrn(urn))
+        }
+    }
+
+    #[derive(Debug)]
+    struct Counter {
+        id: CounterUrn,
+        count: usize,
+        version: i64,
+        last_updated: Option<SystemTime>,
+    }
+
+    impl WithId for Counter {
+        type StreamId = CounterUrn;
+
+        fn with_id(id: Self::StreamId) -> Self {
+            Counter {
+                id,
+                count: 0,
+                version: 0,
+                last_updated: None,
+            }
+        }
+
+        fn get_id(&self) -> &Self::StreamId {
+            &self.id
+        }
+    }
+
+    impl replay::EventStream for Counter {
+        type Event = CounterEvent;
+
+        fn stream_type() -> &'static str {
+            "Counter"
+        }
+
+        fn apply(&mut self, event: Self::Event) {
+            match event {
+                CounterEvent::Decremented => self.count = self.count.saturating_sub(1),
+                CounterEvent::Counted { count } => self.count = count,
+                _ => self.count += 1,
+            }
+        }
+
+        fn try_apply(&mut self, event: Self::Event) -> replay::Result<()> {
+            if event == CounterEvent::Decremented && self.count == 0 {
+                return Err(replay::Error::business_rule_violation(
+                    "counter decremented below zero",
+                ));
+            }
+            self.apply(event);
+            Ok(())
+        }
+
+        fn apply_with_context(
+            &mut self,
+            event: Self::Event,
+            context: &EventContext<'_>,
+        ) -> replay::Result<()> {
+            self.try_apply(event)?;
+            self.version = context.version();
+            self.last_updated = Some(context.created());
+            Ok(())
+        }
+    }
+
+    impl replay::Snapshottable for Counter {
+        fn snapshot_event(&self) -> CounterEvent {
+            CounterEvent::Counted { count: self.count }
+        }
+    }
+
+    impl replay::Aggregate for Counter {
+        type Command = ();
+        type Error = replay::Error;
+        type Services = ();
+
+        async fn handle(
+            &self,
+            _command: Self::Command,
+            _services: &Self::Services,
+        ) -> Result<Vec<Self::Event>, Self::Error> {
+            Ok(vec![CounterEvent::Incremented])
+        }
+
+        async fn handle_in_context(
+            &self,
+            command: Self::Command,
+            services: &Self::Services,
+            context: &CommandContext,
+        ) -> Result<BoxStream<'static, Result<Self::Event, Self::Error>>, Self::Error> {
+            // An optional `limit` caps the number of events the stream may hold.
+            match context.metadata().get::<i64>("limit") {
+                Some(limit) if context.version() >= limit => Err(
+                    replay::Error::business_rule_violation("counter is at its limit")
+                        .with_context("stream_id", context.stream_id()),
+                ),
+                // `decrement` emits an event the counter may refuse to apply.
+                _ if context.metadata().get::<bool>("decrement") == Some(true) => Ok(Box::pin(
+                    futures::stream::iter([Ok(CounterEvent::Decremented)]),
+                )),
+                _ => replay::Aggregate::handle_stream(self, command, services).await,
+            }
+        }
+    }
+
+    impl replay::WithOutput for Counter {
+        type Output = &'static str;
+
+        async fn handle_with_output(
+            &self,
+            command: Self::Command,
+            services: &Self::Services,
+        ) -> Result<(Vec<Self::Event>, Self::Output), Self::Error> {
+            let events = replay::Aggregate::handle(self, command, services).await?;
+            Ok((events, "ticket-1"))
+        }
+    }
+
+    fn counter_id() -> CounterUrn {
+        CounterUrn(UrnBuilder::new("counter", "1").build().unwrap())
+    }
+
+    #[tokio::test]
+    async fn caused_by_chains_causation_and_correlation() {
+        let cqrs = Cqrs::new(InMemoryEventStore::new());
+
+        cqrs.execute::<Counter>(&counter_id(), Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+        let root = events(&cqrs).await.remove(0);
+
+        cqrs.caused_by(&root)
+            .execute::<Counter>(&counter_id(), Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+        let child = events(&cqrs).await.remove(1);
+
+        cqrs.caused_by(&child)
+            .execute::<Counter>(&counter_id(), Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+        let grandchild = events(&cqrs).await.remove(2);
+
+        let root_id = root.id.to_string();
+        assert_eq!(child.metadata.causation_id(), Some(root_id.as_str()));
+        assert_eq!(child.metadata.correlation_id(), Some(root_id.as_str()));
+        assert_eq!(
+            grandchild.metadata.causation_id(),
+            Some(child.id.to_string().as_str())
+        );
+        assert_eq!(grandchild.metadata.correlation_id(), Some(root_id.as_str()));
+    }
+
+    #[tokio::test]
+    async fn soft_deleted_streams_are_not_found_until_hard_deleted() {
+        let cqrs = Cqrs::new(InMemoryEventStore::new());
+        let id = counter_id();
+        cqrs.execute::<Counter>(&id, Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+
+        cqrs.store()
+            .delete_stream(&id, DeletionMode::Soft)
+            .await
+            .unwrap();
+        let err = cqrs
+            .execute::<Counter>(&id, Metadata::default(), (), &(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert!(events(&cqrs).await.is_empty());
+        let deleted: Vec<PersistedEvent<CounterEvent>> = cqrs
+            .store()
+            .stream_events(StreamFilter::all().including_deleted())
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(deleted.len(), 1);
+
+        // A hard-deleted id starts over.
+        cqrs.store()
+            .delete_stream(&id, DeletionMode::Hard)
+            .await
+            .unwrap();
+        let counter = cqrs
+            .execute::<Counter>(&id, Metadata::default(), (), &(), Some(0))
+            .await
+            .unwrap();
+        assert_eq!(counter.count, 1);
+    }
+
+    #[tokio::test]
+    async fn streams_ended_by_a_tombstone_take_no_more_commands() {
+        let cqrs = Cqrs::new(InMemoryEventStore::new());
+        let id = counter_id();
+        cqrs.execute::<Counter>(&id, Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+        cqrs.store()
+            .store_events::<Counter>(
+                &id,
+                "Counter",
+                Metadata::default(),
+                &[CounterEvent::Retired],
+                Some(1),
+            )
+            .await
+            .unwrap();
+
+        let err = cqrs
+            .execute::<Counter>(&id, Metadata::default(), (), &(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert_eq!(events(&cqrs).await.len(), 2);
+        assert!(cqrs
+            .fetch_aggregates::<Counter>(std::slice::from_ref(&id))
+            .await
+            .unwrap()
+            .is_empty());
+        // Its state before the tombstone can still be read.
+        let counter = cqrs
+            .fetch_aggregate_at::<Counter>(&id, AggregateVersion::Latest, Some(1), None)
+            .await
+            .unwrap();
+        assert_eq!(counter.count, 1);
+    }
+
+    #[tokio::test]
+    async fn handlers_see_the_loaded_version_and_metadata() {
+        let cqrs = Cqrs::new(InMemoryEventStore::new());
+        let id = counter_id();
+        let limited = || Metadata::builder().insert("limit", 2).build();
+        for _ in 0..2 {
+            cqrs.execute::<Counter>(&id, limited(), (), &(), None)
+                .await
+                .unwrap();
+        }
+
+        let err = cqrs
+            .execute::<Counter>(&id, limited(), (), &(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BusinessRuleViolation);
+        assert_eq!(events(&cqrs).await.len(), 2);
+        // Without the limit in its metadata the command goes through.
+        let counter = cqrs
+            .execute::<Counter>(&id, Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+        assert_eq!(counter.count, 3);
+    }
+
+    #[tokio::test]
+    async fn hydration_applies_events_with_their_envelope() {
+        let cqrs = Cqrs::new(InMemoryEventStore::new());
+        let id = counter_id();
+        let counter = cqrs
+            .execute::<Counter>(&id, Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+        assert_eq!(counter.version, 1);
+        cqrs.execute::<Counter>(&id, Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+
+        let counter = cqrs.fetch_aggregate::<Counter>(&id).await.unwrap();
+        let last = events(&cqrs).await.pop().unwrap();
+        assert_eq!(counter.version, 2);
+        assert_eq!(counter.last_updated, Some(last.created.into()));
+    }
+
+    #[tokio::test]
+    async fn events_that_do_not_apply_fail_the_load_with_their_position() {
+        let cqrs = Cqrs::new(InMemoryEventStore::new());
+        let id = counter_id();
+        cqrs.store()
+            .store_events::<Counter>(
+                &id,
+                "Counter",
+                Metadata::default(),
+                &[CounterEvent::Decremented],
+                None,
+            )
+            .await
+            .unwrap();
+
+        let err = cqrs.fetch_aggregate::<Counter>(&id).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Internal);
+        assert!(err.context().contains(&("version", "1".to_string())));
+        let err = cqrs
+            .execute::<Counter>(&id, Metadata::default(), (), &(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Internal);
+        assert!(cqrs
+            .fetch_aggregates::<Counter>(std::slice::from_ref(&id))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn commands_whose_events_do_not_apply_fail_after_the_append() {
+        let cqrs = Cqrs::new(InMemoryEventStore::new());
+        let id = counter_id();
+        let decrement = Metadata::builder().insert("decrement", true).build();
+
+        let err = cqrs
+            .execute::<Counter>(&id, decrement, (), &(), None)
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::Internal);
+        assert!(err.context().contains(&("version", "1".to_string())));
+        assert!(err.context().contains(&("committed", "true".to_string())));
+        assert_eq!(events(&cqrs).await.len(), 1);
+    }
+
+    async fn events(cqrs: &Cqrs<InMemoryEventStore>) -> Vec<PersistedEvent<CounterEvent>> {
+        cqrs.store()
+            .stream_events::<CounterEvent>(StreamFilter::all())
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    async fn actors(cqrs: &Cqrs<InMemoryEventStore>) -> Vec<Option<String>> {
+        cqrs.store()
+            .stream_events::<CounterEvent>(StreamFilter::all())
+            .map_ok(|e| e.actor().map(str::to_string))
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn with_actor_stamps_events_unless_metadata_names_one() {
+        let cqrs = Cqrs::new(InMemoryEventStore::new());
+        let as_alice = cqrs.with_actor("user:alice");
+
+        as_alice
+            .execute::<Counter>(&counter_id(), Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+        as_alice
+            .execute::<Counter>(
+                &counter_id(),
+                Metadata::default().with_actor("support:bob"),
+                (),
+                &(),
+                None,
+            )
+            .await
+            .unwrap();
+        cqrs.execute::<Counter>(&counter_id(), Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            actors(&cqrs).await,
+            vec![
+                Some("user:alice".to_string()),
+                Some("support:bob".to_string()),
+                None
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn require_metadata_rejects_writes_missing_keys() {
+        let cqrs = Cqrs::new(InMemoryEventStore::new())
+            .require_metadata(&[Metadata::TENANT_ID_KEY, Metadata::ACTOR_KEY]);
+
+        let err = cqrs
+            .execute::<Counter>(&counter_id(), Metadata::default(), (), &(), None)
+            .await
+            .expect_err("metadata without tenant or actor must be rejected");
+        assert_eq!(err.kind(), replay::ErrorKind::InvalidInput);
+        assert!(err
+            .context()
+            .contains(&("missing_keys", "tenant_id, actor".to_string())));
+        assert!(events(&cqrs).await.is_empty());
+
+        // The handle's actor is stamped before validation runs.
+        cqrs.with_actor("user:alice")
+            .execute::<Counter>(
+                &counter_id(),
+                Metadata::default().with_tenant_id("acme"),
+                (),
+                &(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(events(&cqrs).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn compaction_metadata_is_stamped_before_validation() {
+        let cqrs = Cqrs::new(InMemoryEventStore::new()).require_metadata(&[Metadata::ACTOR_KEY]);
+        let as_ops = cqrs.with_actor("ops");
+        for _ in 0..3 {
+            as_ops
+                .execute::<Counter>(&counter_id(), Metadata::default(), (), &(), None)
+                .await
+                .unwrap();
+        }
+
+        let err = cqrs
+            .compact_stream::<Counter>(&counter_id(), 2, Metadata::default())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), replay::ErrorKind::InvalidInput);
+
+        let replaced = as_ops
+            .compact_stream::<Counter>(&counter_id(), 2, Metadata::default())
+            .await
+            .unwrap();
+        assert_eq!(replaced, 2);
+        assert_eq!(actors(&cqrs).await, vec![Some("ops".to_string()); 2]);
+        let counter = cqrs
+            .fetch_aggregate::<Counter>(&counter_id())
+            .await
+            .unwrap();
+        assert_eq!(counter.count, 3);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_commands_fail_with_retry_after() {
+        let cqrs = Cqrs::new(InMemoryEventStore::new()).with_rate_limiter(
+            crate::TokenBucketRateLimiter::per_actor(1, std::time::Duration::from_secs(60)),
+        );
+
+        cqrs.with_actor("user:alice")
+            .execute::<Counter>(&counter_id(), Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+        let err = cqrs
+            .with_actor("user:alice")
+            .execute_with_output::<Counter>(&counter_id(), Metadata::default(), (), &(), None)
+            .await
+            .expect_err("alice's second command must be rate limited");
+        assert_eq!(err.kind(), replay::ErrorKind::RateLimited);
+        assert!(err.is_temporary());
+        assert!(err.retry_after().unwrap() > std::time::Duration::from_secs(59));
+        assert!(err.context().contains(&("actor", "user:alice".to_string())));
+
+        cqrs.with_actor("user:bob")
+            .execute::<Counter>(&counter_id(), Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+        assert_eq!(events(&cqrs).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn execute_with_output_returns_the_output_and_persists_the_events() {
+        let cqrs = Cqrs::new(InMemoryEventStore::new());
+
+        let (counter, ticket) = cqrs
+            .execute_with_output::<Counter>(&counter_id(), Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(ticket, "ticket-1");
+        assert_eq!(counter.id, counter_id());
+        assert_eq!(events(&cqrs).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn fetch_aggregates_folds_each_stream() {
+        let cqrs = Cqrs::new(InMemoryEventStore::new());
+        let other = CounterUrn(UrnBuilder::new("counter", "2").build().unwrap());
+        let missing = CounterUrn(UrnBuilder::new("counter", "3").build().unwrap());
+        for id in [&counter_id(), &counter_id(), &other] {
+            cqrs.execute::<Counter>(id, Metadata::default(), (), &(), None)
+                .await
+                .unwrap();
+        }
+
+        let counters = cqrs
+            .fetch_aggregates::<Counter>(&[counter_id(), other.clone(), missing.clone()])
+            .await
+            .unwrap();
+
+        let count = |id: &CounterUrn| counters[&Urn::from(id.clone())].count;
+        assert_eq!(counters.len(), 3);
+        assert_eq!(count(&counter_id()), 2);
+        assert_eq!(count(&other), 1);
+        assert_eq!(count(&missing), 0);
+        assert!(cqrs
+            .fetch_aggregates::<Counter>(&[])
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn commands_over_the_slow_threshold_are_logged() {
+        let cqrs = Cqrs::new(InMemoryEventStore::new());
+
+        cqrs.with_slow_command_threshold(std::time::Duration::from_secs(3600))
+            .execute::<Counter>(&counter_id(), Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+        assert!(!logs_contain("slow command"));
+
+        cqrs.with_slow_command_threshold(std::time::Duration::ZERO)
+            .execute::<Counter>(&counter_id(), Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+        assert!(logs_contain("slow command"));
+        assert!(logs_contain("aggregate=\"Counter\""));
+        assert!(logs_contain("stream_id=urn:counter:1"));
+    }
+
+    #[tokio::test]
+    async fn execute_timed_attributes_slow_reads_to_hydration() {
+        let delay = std::time::Duration::from_millis(20);
+        let store = crate::ChaosEventStore::new(InMemoryEventStore::new()).slow_reads(delay);
+        let cqrs = Cqrs::new(store);
+        for _ in 0..2 {
+            cqrs.execute::<Counter>(&counter_id(), Metadata::default(), (), &(), None)
+                .await
+                .unwrap();
+        }
+
+        let (counter, timeline) = cqrs
+            .execute_timed::<Counter>(&counter_id(), Metadata::default(), (), &(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(counter.count, 3);
+        assert!(timeline.hydrate >= delay * 2, "{timeline:?}");
+        assert!(timeline.append < timeline.hydrate, "{timeline:?}");
+        assert!(timeline.handle < timeline.hydrate, "{timeline:?}");
+    }
+}
@@ -0,0 +1,122 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use sqlx::postgres::PgConnectOptions;
+use sqlx::{Pool, Postgres};
+
+/// A database user name and password, e.g. a short-lived IAM token or a Vault lease.
+#[derive(Clone)]
+pub struct PostgresCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl PostgresCredentials {
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        PostgresCredentials {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+
+    /// `options` logging in with these credentials.
+    pub(crate) fn apply(&self, options: PgConnectOptions) -> PgConnectOptions {
+        options.username(&self.username).password(&self.password)
+    }
+}
+
+impl std::fmt::Debug for PostgresCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PostgresCredentials")
+            .field("username", &self.username)
+            .field("password", &"[REDACTED]")
+            .finish()
+    }
+}
+
+/// Fetches the credentials new connections log in with, for databases whose credentials
+/// rotate (see [`PostgresPoolOptions::with_credential_provider`](crate::PostgresPoolOptions::with_credential_provider)).
+///
+/// Implemented for async closures returning [`PostgresCredentials`]:
+///
+/// ```rust,ignore
+/// let options = PostgresPoolOptions::new("postgres://db.internal/events")?
+///     .with_credential_provider(move || {
+///         let vault = vault.clone();
+///         async move {
+///             let lease = vault.database_credentials("events").await?;
+///             Ok(PostgresCredentials::new(lease.username, lease.password))
+///         }
+///     });
+/// ```
+pub trait CredentialProvider: Send + Sync {
+    fn credentials(&self) -> BoxFuture<'_, Result<PostgresCredentials, replay::Error>>;
+}
+
+impl<F, Fut> CredentialProvider for F
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<PostgresCredentials, replay::Error>> + Send + 'static,
+{
+    fn credentials(&self) -> BoxFuture<'_, Result<PostgresCredentials, replay::Error>> {
+        Box::pin(self())
+    }
+}
+
+/// Replaces the credentials of a pool's future connections with fresh ones from its
+/// provider. Connections already open stay as they are: the database only checks
+/// credentials when a connection logs in.
+pub(crate) struct CredentialRefresh {
+    provider: Arc<dyn CredentialProvider>,
+    connect_options: PgConnectOptions,
+    pool: Pool<Postgres>,
+    refreshing: AtomicBool,
+}
+
+impl CredentialRefresh {
+    pub(crate) fn new(
+        provider: Arc<dyn CredentialProvider>,
+        connect_options: PgConnectOptions,
+        pool: Pool<Postgres>,
+    ) -> Self {
+        CredentialRefresh {
+            provider,
+            connect_options,
+            pool,
+            refreshing: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) async fn refresh(&self) -> Result<(), replay::Error> {
+        let credentials = self.provider.credentials().await?;
+        self.pool
+            .set_connect_options(credentials.apply(self.connect_options.clone()));
+        tracing::info!(username = %credentials.username, "refreshed database credentials");
+        Ok(())
+    }
+
+    /// Refresh in a background task, unless a refresh is already running.
+    pub(crate) fn refresh_in_background(self: &Arc<Self>) {
+        if self.refreshing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let refresh = Arc::clone(self);
+        tokio::spawn(async move {
+            if let Err(error) = refresh.refresh().await {
+                tracing::warn!(error = %error, "failed to refresh database credentials");
+            }
+            refresh.refreshing.store(false, Ordering::Release);
+        });
+    }
+}
+
+/// Whether the database rejected a login: `28P01` invalid password or `28000` invalid
+/// authorization, as when a rotated credential expires.
+pub(crate) fn is_auth_failure(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Database(db) => matches!(db.code().as_deref(), Some("28P01" | "28000")),
+        _ => false,
+    }
+}
@@ -0,0 +1,781 @@
+//! Application-level encryption of event payloads with per-stream data keys.
+//!
+//! Each stream gets its own data key, created on its first encrypted append and kept in the
+//! `stream_keys` table (migration `0018_stream_keys`) in the form returned by
+//! [`EventCrypto::generate_data_key`], normally wrapped by a master key that never reaches
+//! the database. Encrypted values are stored as `{"$encrypted": "<base64>"}` in place of the
+//! original, so they survive any [`EventSerializer`](crate::EventSerializer) and
+//! compression, and the rest of a field-encrypted payload stays queryable from SQL. A
+//! payload's own single-key objects named like the marker (`$encrypted`, `$$encrypted`, ...)
+//! are stored with one more `$` and restored on reads, so they never read as ciphertext.
+//!
+//! Each ciphertext is bound to the stream and the JSON Pointer of the value it replaces, so an
+//! encrypted value copied to another stream or field fails to decrypt.
+//!
+//! Reads decrypt before events are deserialized; inline projections see plaintext.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde_json::{Map, Value};
+
+/// Key of the object that replaces an encrypted value.
+const ENCRYPTED: &str = "$encrypted";
+
+/// How many `$` a key named like [`ENCRYPTED`] starts with, `0` for any other key.
+fn marker_dollars(key: &str) -> usize {
+    let name = key.trim_start_matches('$');
+    if name == &ENCRYPTED[1..] {
+        key.len() - name.len()
+    } else {
+        0
+    }
+}
+
+/// The cipher behind [`Encryption`]: generates per-stream data keys and unwraps them for use.
+///
+/// Data keys are opaque to the store, which keeps them as returned by
+/// [`generate_data_key`](Self::generate_data_key) and hands them back to
+/// [`data_key`](Self::data_key) once per stream of an append, read or maintenance operation.
+/// Implementations backed by a KMS return the KMS-wrapped key and unwrap it there;
+/// [`AesGcmCrypto`] wraps keys locally.
+pub trait EventCrypto: Send + Sync {
+    /// A new data key for a stream, in the form to store.
+    fn generate_data_key(&self) -> Result<Vec<u8>, replay::Error>;
+
+    /// Unwrap a stored data key for the values of one operation.
+    fn data_key(&self, data_key: &[u8]) -> Result<Box<dyn DataKey>, replay::Error>;
+}
+
+/// A stream's unwrapped data key, from [`EventCrypto::data_key`].
+///
+/// `aad` is the associated data a ciphertext is bound to: decrypting must fail unless it gets
+/// the `aad` the value was encrypted with.
+pub trait DataKey: Send + Sync {
+    fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, replay::Error>;
+
+    fn decrypt(&self, ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>, replay::Error>;
+}
+
+/// What [`PostgresEventStore::with_encryption`](crate::PostgresEventStore::with_encryption)
+/// encrypts, and with which [`EventCrypto`].
+///
+/// ```rust,ignore
+/// let crypto = AesGcmCrypto::new(&master_key)?;
+/// // The fields `CustomerEvent` marks `#[pii]`:
+/// let encryption = Encryption::for_pii::<CustomerEvent>(crypto)?;
+/// // Only these fields, wherever they appear in a payload:
+/// let encryption = Encryption::fields(crypto, ["email", "iban"]);
+/// // Or every payload as a whole:
+/// let encryption = Encryption::payloads(crypto);
+/// ```
+#[derive(Clone)]
+pub struct Encryption {
+    crypto: Arc<dyn EventCrypto>,
+    targets: Arc<Targets>,
+}
+
+/// What an [`Encryption`] encrypts.
+enum Targets {
+    Payloads,
+    /// The values of these object keys, at any depth.
+    Fields(BTreeSet<String>),
+    /// The values at these JSON Pointers, as their unescaped segments.
+    Paths(Vec<Vec<String>>),
+}
+
+impl Encryption {
+    /// Encrypt every payload as a whole.
+    pub fn payloads(crypto: impl EventCrypto + 'static) -> Self {
+        Encryption {
+            crypto: Arc::new(crypto),
+            targets: Arc::new(Targets::Payloads),
+        }
+    }
+
+    /// Encrypt the values of object keys named in `fields`, at any depth of a payload.
+    pub fn fields<I>(crypto: impl EventCrypto + 'static, fields: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Encryption {
+            crypto: Arc::new(crypto),
+            targets: Arc::new(Targets::Fields(
+                fields.into_iter().map(Into::into).collect(),
+            )),
+        }
+    }
+
+    /// Encrypt the fields `E` marks `#[pii]`, at the paths of its serialized payload (see
+    /// [`Event::pii_paths`](replay::Event::pii_paths)). Fails with `InvalidInput` when a path
+    /// isn't a JSON Pointer.
+    pub fn for_pii<E: replay::Event>(
+        crypto: impl EventCrypto + 'static,
+    ) -> Result<Self, replay::Error> {
+        let paths = E::pii_paths()
+            .iter()
+            .map(|path| {
+                let Some(segments) = path.strip_prefix('/') else {
+                    return match path.as_str() {
+                        "" => Ok(Vec::new()),
+                        _ => Err(
+                            replay::Error::invalid_input("pii path is not a JSON Pointer")
+                                .with_operation("configure_encryption")
+                                .with_context("path", path),
+                        ),
+                    };
+                };
+                Ok(segments
+                    .split('/')
+                    .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+                    .collect())
+            })
+            .collect::<Result<_, replay::Error>>()?;
+        Ok(Encryption {
+            crypto: Arc::new(crypto),
+            targets: Arc::new(Targets::Paths(paths)),
+        })
+    }
+
+    pub(crate) fn generate_data_key(&self) -> Result<Vec<u8>, replay::Error> {
+        self.crypto.generate_data_key()
+    }
+
+    /// Unwrap a stream's stored `data_key`, for the values of one operation on it.
+    pub(crate) fn data_key(&self, data_key: &[u8]) -> Result<Box<dyn DataKey>, replay::Error> {
+        self.crypto.data_key(data_key)
+    }
+
+    /// A cache of the data keys unwrapped during one read.
+    pub(crate) fn keys(&self) -> DataKeys<'_> {
+        DataKeys {
+            encryption: self,
+            unwrapped: Mutex::default(),
+        }
+    }
+
+    /// `payload` of `stream_id` with the configured parts encrypted under `key`.
+    pub(crate) fn encrypt(
+        &self,
+        stream_id: &str,
+        key: &dyn DataKey,
+        payload: &Value,
+    ) -> Result<Value, replay::Error> {
+        let sealing = Sealing { stream_id, key };
+        match &*self.targets {
+            Targets::Payloads => sealing.seal("", payload),
+            Targets::Fields(fields) => Self::encrypt_fields(&sealing, fields, "", payload),
+            Targets::Paths(paths) => {
+                let paths: Vec<&[String]> = paths.iter().map(Vec::as_slice).collect();
+                Self::encrypt_paths(&sealing, &paths, "", payload)
+            }
+        }
+    }
+
+    fn encrypt_fields(
+        sealing: &Sealing<'_>,
+        fields: &BTreeSet<String>,
+        pointer: &str,
+        value: &Value,
+    ) -> Result<Value, replay::Error> {
+        Self::encrypt_children(pointer, value, |key, pointer, value| {
+            if fields.contains(key) {
+                sealing.seal(pointer, value)
+            } else {
+                Self::encrypt_fields(sealing, fields, pointer, value)
+            }
+        })
+    }
+
+    /// `value` with the values at `paths`, relative to it, encrypted.
+    fn encrypt_paths(
+        sealing: &Sealing<'_>,
+        paths: &[&[String]],
+        pointer: &str,
+        value: &Value,
+    ) -> Result<Value, replay::Error> {
+        if paths.iter().any(|path| path.is_empty()) {
+            return sealing.seal(pointer, value);
+        }
+        Self::encrypt_children(pointer, value, |key, pointer, value| {
+            let below: Vec<&[String]> = paths
+                .iter()
+                .filter(|path| path[0] == key)
+                .map(|path| &path[1..])
+                .collect();
+            Self::encrypt_paths(sealing, &below, pointer, value)
+        })
+    }
+
+    /// `value`, at `pointer`, with each object value or array item (keyed by its index)
+    /// replaced by `encrypt`, escaping objects named like the marker.
+    fn encrypt_children(
+        pointer: &str,
+        value: &Value,
+        mut encrypt: impl FnMut(&str, &str, &Value) -> Result<Value, replay::Error>,
+    ) -> Result<Value, replay::Error> {
+        Ok(match value {
+            Value::Object(object) => {
+                let escape = object.len() == 1;
+                let mut encrypted = Map::with_capacity(object.len());
+                for (key, value) in object {
+                    let value = encrypt(key, &child_pointer(pointer, key), value)?;
+                    let key = match marker_dollars(key) {
+                        dollars if escape && dollars > 0 => format!("${key}"),
+                        _ => key.clone(),
+                    };
+                    encrypted.insert(key, value);
+                }
+                Value::Object(encrypted)
+            }
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| {
+                        let index = index.to_string();
+                        encrypt(&index, &child_pointer(pointer, &index), item)
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            other => other.clone(),
+        })
+    }
+
+    /// `payload` of `stream_id` with every encrypted value decrypted, whatever this
+    /// configuration encrypts, so payloads written under an earlier configuration still read.
+    ///
+    /// `key` is the stream's key, `None` when the stream was forgotten: encrypted fields
+    /// then read as `null`, and an encrypted whole payload fails with `NotFound`.
+    pub(crate) fn decrypt(
+        &self,
+        stream_id: &str,
+        key: Option<&dyn DataKey>,
+        payload: Value,
+    ) -> Result<Value, replay::Error> {
+        match (&payload, key) {
+            (Value::Object(object), None) if sealed(object).is_some() => Err(
+                replay::Error::not_found("event payload belongs to a forgotten stream")
+                    .with_operation("decrypt_event"),
+            ),
+            _ => Self::decrypt_fields(
+                key.map(|key| Sealing { stream_id, key }).as_ref(),
+                "",
+                payload,
+            ),
+        }
+    }
+
+    fn decrypt_fields(
+        sealing: Option<&Sealing<'_>>,
+        pointer: &str,
+        value: Value,
+    ) -> Result<Value, replay::Error> {
+        match value {
+            Value::Object(object) => match (sealed(&object), sealing) {
+                (Some(ciphertext), Some(sealing)) => sealing.open(pointer, ciphertext),
+                (Some(_), None) => Ok(Value::Null),
+                (None, _) => {
+                    let unescape = object.len() == 1;
+                    Ok(Value::Object(
+                        object
+                            .into_iter()
+                            .map(|(key, value)| {
+                                let key = match marker_dollars(&key) {
+                                    dollars if unescape && dollars > 1 => key[1..].to_string(),
+                                    _ => key,
+                                };
+                                let pointer = child_pointer(pointer, &key);
+                                Ok((key, Self::decrypt_fields(sealing, &pointer, value)?))
+                            })
+                            .collect::<Result<_, replay::Error>>()?,
+                    ))
+                }
+            },
+            Value::Array(items) => Ok(Value::Array(
+                items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, item)| {
+                        let pointer = child_pointer(pointer, &index.to_string());
+                        Self::decrypt_fields(sealing, &pointer, item)
+                    })
+                    .collect::<Result<_, _>>()?,
+            )),
+            other => Ok(other),
+        }
+    }
+
+    /// The stored `payload` of stream `from` with its encrypted values bound to stream `to`
+    /// instead, for renaming a stream; the rest of the payload is kept as stored.
+    pub(crate) fn rebind(
+        &self,
+        key: &dyn DataKey,
+        from: &str,
+        to: &str,
+        payload: Value,
+    ) -> Result<Value, replay::Error> {
+        let from = Sealing {
+            stream_id: from,
+            key,
+        };
+        let to = Sealing { stream_id: to, key };
+        Self::rebind_values(&from, &to, "", payload)
+    }
+
+    fn rebind_values(
+        from: &Sealing<'_>,
+        to: &Sealing<'_>,
+        pointer: &str,
+        value: Value,
+    ) -> Result<Value, replay::Error> {
+        match value {
+            Value::Object(object) => match sealed(&object) {
+                Some(ciphertext) => to.seal(pointer, &from.open(pointer, ciphertext)?),
+                None => {
+                    let unescape = object.len() == 1;
+                    Ok(Value::Object(
+                        object
+                            .into_iter()
+                            .map(|(key, value)| {
+                                let pointer = match marker_dollars(&key) {
+                                    dollars if unescape && dollars > 1 => {
+                                        child_pointer(pointer, &key[1..])
+                                    }
+                                    _ => child_pointer(pointer, &key),
+                                };
+                                Ok((key, Self::rebind_values(from, to, &pointer, value)?))
+                            })
+                            .collect::<Result<_, replay::Error>>()?,
+                    ))
+                }
+            },
+            Value::Array(items) => Ok(Value::Array(
+                items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, item)| {
+                        let pointer = child_pointer(pointer, &index.to_string());
+                        Self::rebind_values(from, to, &pointer, item)
+                    })
+                    .collect::<Result<_, _>>()?,
+            )),
+            other => Ok(other),
+        }
+    }
+}
+
+/// The data keys unwrapped during one read, by their stored form, so a stream's key is
+/// unwrapped once however many of its events the read decrypts.
+pub(crate) struct DataKeys<'a> {
+    encryption: &'a Encryption,
+    unwrapped: Mutex<HashMap<Vec<u8>, Arc<dyn DataKey>>>,
+}
+
+impl DataKeys<'_> {
+    /// `payload` of `stream_id` decrypted with the stream's stored `data_key`; see
+    /// [`Encryption::decrypt`].
+    pub(crate) fn decrypt(
+        &self,
+        stream_id: &str,
+        data_key: Option<&[u8]>,
+        payload: Value,
+    ) -> Result<Value, replay::Error> {
+        let key = data_key.map(|data_key| self.unwrap(data_key)).transpose()?;
+        self.encryption.decrypt(stream_id, key.as_deref(), payload)
+    }
+
+    fn unwrap(&self, data_key: &[u8]) -> Result<Arc<dyn DataKey>, replay::Error> {
+        let mut unwrapped = self
+            .unwrapped
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(key) = unwrapped.get(data_key) {
+            return Ok(key.clone());
+        }
+        let key: Arc<dyn DataKey> = self.encryption.data_key(data_key)?.into();
+        unwrapped.insert(data_key.to_vec(), key.clone());
+        Ok(key)
+    }
+}
+
+/// A stream's data key, sealing values at their JSON Pointer in its payloads.
+struct Sealing<'a> {
+    stream_id: &'a str,
+    key: &'a dyn DataKey,
+}
+
+impl Sealing<'_> {
+    /// The associated data binding a ciphertext to the stream and the value's pointer.
+    fn aad(&self, pointer: &str) -> Vec<u8> {
+        let mut aad = Vec::with_capacity(self.stream_id.len() + 1 + pointer.len());
+        aad.extend_from_slice(self.stream_id.as_bytes());
+        aad.push(0);
+        aad.extend_from_slice(pointer.as_bytes());
+        aad
+    }
+
+    fn seal(&self, pointer: &str, value: &Value) -> Result<Value, replay::Error> {
+        let plaintext = serde_json::to_vec(value).map_err(crate::ser_error)?;
+        let ciphertext = self.key.encrypt(&plaintext, &self.aad(pointer))?;
+        let mut sealed = Map::with_capacity(1);
+        sealed.insert(
+            ENCRYPTED.to_string(),
+            Value::String(BASE64.encode(ciphertext)),
+        );
+        Ok(Value::Object(sealed))
+    }
+
+    fn open(&self, pointer: &str, ciphertext: &str) -> Result<Value, replay::Error> {
+        let ciphertext = BASE64.decode(ciphertext).map_err(|e| {
+            replay::Error::internal("encrypted event payload is not valid base64")
+                .with_operation("decrypt_event")
+                .with_source(e)
+        })?;
+        let plaintext = self.key.decrypt(&ciphertext, &self.aad(pointer))?;
+        serde_json::from_slice(&plaintext).map_err(crate::deser_error)
+    }
+}
+
+/// The JSON Pointer of `key` under `pointer`.
+fn child_pointer(pointer: &str, key: &str) -> String {
+    format!("{pointer}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+/// The ciphertext of an `{"$encrypted": "..."}` object.
+fn sealed(object: &Map<String, Value>) -> Option<&str> {
+    match (object.len(), object.get(ENCRYPTED)) {
+        (1, Some(Value::String(ciphertext))) => Some(ciphertext),
+        _ => None,
+    }
+}
+
+/// AES-256-GCM with data keys wrapped by a 256-bit master key (feature `encryption`).
+///
+/// Data keys are random and stored encrypted under the master key; ciphertexts are
+/// `nonce || ciphertext || tag` with a random 96-bit nonce, authenticating the associated
+/// data. Rotating the master key means re-wrapping the `stream_keys` rows; the events
+/// themselves don't change.
+#[cfg(feature = "encryption")]
+pub struct AesGcmCrypto {
+    master_key: AesGcmKey,
+}
+
+#[cfg(feature = "encryption")]
+impl AesGcmCrypto {
+    const KEY_LEN: usize = 32;
+
+    /// Fails with `InvalidInput` unless `master_key` is 32 bytes.
+    pub fn new(master_key: &[u8]) -> Result<Self, replay::Error> {
+        Ok(AesGcmCrypto {
+            master_key: AesGcmKey::new(master_key).map_err(|_| {
+                replay::Error::invalid_input("AES-256-GCM master key must be 32 bytes")
+                    .with_operation("configure_encryption")
+            })?,
+        })
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl EventCrypto for AesGcmCrypto {
+    fn generate_data_key(&self) -> Result<Vec<u8>, replay::Error> {
+        use ring::rand::SecureRandom;
+
+        let mut key = [0u8; Self::KEY_LEN];
+        self.master_key.rng.fill(&mut key).map_err(|_| {
+            replay::Error::internal("failed to generate a data key").with_operation("encrypt_event")
+        })?;
+        self.master_key.seal(&key, &[])
+    }
+
+    fn data_key(&self, data_key: &[u8]) -> Result<Box<dyn DataKey>, replay::Error> {
+        let bytes = self.master_key.open(data_key, &[])?;
+        let key = AesGcmKey::new(&bytes).map_err(|_| {
+            replay::Error::internal("stored data key is not an AES-256 key")
+                .with_operation("decrypt_event")
+        })?;
+        Ok(Box::new(key))
+    }
+}
+
+/// An AES-256-GCM key of [`AesGcmCrypto`]: the master key, or an unwrapped data key.
+#[cfg(feature = "encryption")]
+struct AesGcmKey {
+    key: ring::aead::LessSafeKey,
+    rng: ring::rand::SystemRandom,
+}
+
+#[cfg(feature = "encryption")]
+impl AesGcmKey {
+    fn new(bytes: &[u8]) -> Result<Self, ring::error::Unspecified> {
+        let key = ring::aead::UnboundKey::new(&ring::aead::AES_256_GCM, bytes)?;
+        Ok(AesGcmKey {
+            key: ring::aead::LessSafeKey::new(key),
+            rng: ring::rand::SystemRandom::new(),
+        })
+    }
+
+    fn seal(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, replay::Error> {
+        use ring::aead::{Aad, Nonce, NONCE_LEN};
+        use ring::rand::SecureRandom;
+
+        let failed = |_| {
+            replay::Error::internal("failed to encrypt event payload")
+                .with_operation("encrypt_event")
+        };
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng.fill(&mut nonce).map_err(failed)?;
+        let mut in_out = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(aad),
+                &mut in_out,
+            )
+            .map_err(failed)?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&in_out);
+        Ok(sealed)
+    }
+
+    fn open(&self, sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>, replay::Error> {
+        use ring::aead::{Aad, Nonce, NONCE_LEN};
+
+        let failed = || {
+            replay::Error::internal("failed to decrypt event payload; wrong key or tampered data")
+                .with_operation("decrypt_event")
+        };
+        if sealed.len() < NONCE_LEN {
+            return Err(failed());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| failed())?;
+        let mut in_out = ciphertext.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::from(aad), &mut in_out)
+            .map_err(|_| failed())?;
+        Ok(plaintext.to_vec())
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl DataKey for AesGcmKey {
+    fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, replay::Error> {
+        self.seal(plaintext, aad)
+    }
+
+    fn decrypt(&self, ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>, replay::Error> {
+        self.open(ciphertext, aad)
+    }
+}
+
+#[cfg(all(test, feature = "encryption"))]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const STREAM: &str = "urn:customer:ada";
+
+    fn crypto() -> AesGcmCrypto {
+        AesGcmCrypto::new(&[7; 32]).unwrap()
+    }
+
+    fn data_key(encryption: &Encryption) -> Box<dyn DataKey> {
+        let stored = encryption.generate_data_key().unwrap();
+        encryption.data_key(&stored).unwrap()
+    }
+
+    fn payload() -> Value {
+        json!({ "Registered": { "email": "ada@example.com", "plan": "pro", "contacts": [{ "email": "bob@example.com" }] } })
+    }
+
+    #[test]
+    fn fields_are_encrypted_wherever_they_appear() {
+        let encryption = Encryption::fields(crypto(), ["email"]);
+        let key = data_key(&encryption);
+
+        let stored = encryption.encrypt(STREAM, &*key, &payload()).unwrap();
+
+        assert_eq!(stored["Registered"]["plan"], "pro");
+        assert!(stored["Registered"]["email"][ENCRYPTED].is_string());
+        assert!(stored["Registered"]["contacts"][0]["email"][ENCRYPTED].is_string());
+        assert!(!stored.to_string().contains("example.com"));
+        assert_eq!(
+            encryption.decrypt(STREAM, Some(&*key), stored).unwrap(),
+            payload()
+        );
+    }
+
+    #[test]
+    fn pii_fields_of_an_event_are_encrypted_at_their_serialized_paths() {
+        #[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, replay_macros::Event)]
+        #[serde(rename_all = "snake_case")]
+        enum CustomerEvent {
+            #[serde(rename_all = "camelCase")]
+            Registered {
+                #[pii]
+                #[serde(rename = "email")]
+                contact_email: String,
+                plan: String,
+                contacts: Vec<serde_json::Value>,
+            },
+        }
+
+        let encryption = Encryption::for_pii::<CustomerEvent>(crypto()).unwrap();
+        let key = data_key(&encryption);
+        let payload = json!({ "registered": {
+            "email": "ada@example.com",
+            "plan": "pro",
+            "contacts": [{ "email": "bob@example.com" }],
+        } });
+
+        let stored = encryption.encrypt(STREAM, &*key, &payload).unwrap();
+
+        assert!(stored["registered"]["email"][ENCRYPTED].is_string());
+        assert_eq!(stored["registered"]["plan"], "pro");
+        // Only the marked field: a same-named key elsewhere is not personal data
+        assert_eq!(
+            stored["registered"]["contacts"][0]["email"],
+            "bob@example.com"
+        );
+        assert_eq!(
+            encryption.decrypt(STREAM, Some(&*key), stored).unwrap(),
+            payload
+        );
+    }
+
+    #[test]
+    fn payloads_imitating_ciphertext_read_back_as_written() {
+        let encryption = Encryption::fields(crypto(), ["email"]);
+        let key = data_key(&encryption);
+        let payload = json!({
+            "email": "ada@example.com",
+            "note": { "$encrypted": "AAAA" },
+            "nested": { "$$encrypted": [1] },
+        });
+
+        let stored = encryption.encrypt(STREAM, &*key, &payload).unwrap();
+
+        assert_eq!(stored["note"], json!({ "$$encrypted": "AAAA" }));
+        assert_eq!(stored["nested"], json!({ "$$$encrypted": [1] }));
+        assert_eq!(
+            encryption
+                .decrypt(STREAM, Some(&*key), stored.clone())
+                .unwrap(),
+            payload
+        );
+        assert_eq!(
+            encryption.decrypt(STREAM, None, stored).unwrap(),
+            json!({
+                "email": null,
+                "note": { "$encrypted": "AAAA" },
+                "nested": { "$$encrypted": [1] },
+            })
+        );
+    }
+
+    #[test]
+    fn whole_payloads_need_their_stream_key() {
+        let encryption = Encryption::payloads(crypto());
+        let key = data_key(&encryption);
+        let other_key = data_key(&encryption);
+
+        let stored = encryption.encrypt(STREAM, &*key, &payload()).unwrap();
+
+        assert_eq!(stored.as_object().unwrap().len(), 1);
+        assert_eq!(
+            encryption
+                .decrypt(STREAM, Some(&*key), stored.clone())
+                .unwrap(),
+            payload()
+        );
+        assert!(encryption
+            .decrypt(STREAM, Some(&*other_key), stored.clone())
+            .is_err());
+        let forgotten = encryption.decrypt(STREAM, None, stored).unwrap_err();
+        assert_eq!(forgotten.kind(), replay::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn forgotten_fields_read_as_null() {
+        let encryption = Encryption::fields(crypto(), ["email"]);
+        let key = data_key(&encryption);
+        let stored = encryption.encrypt(STREAM, &*key, &payload()).unwrap();
+
+        assert_eq!(
+            encryption.decrypt(STREAM, None, stored).unwrap(),
+            json!({ "Registered": { "email": null, "plan": "pro", "contacts": [{ "email": null }] } })
+        );
+    }
+
+    #[test]
+    fn master_keys_must_be_256_bits() {
+        let err = AesGcmCrypto::new(&[0; 16]).err().unwrap();
+        assert_eq!(err.kind(), replay::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn encrypted_values_only_decrypt_in_their_stream_and_field() {
+        let encryption = Encryption::fields(crypto(), ["email"]);
+        let key = data_key(&encryption);
+        let stored = encryption.encrypt(STREAM, &*key, &payload()).unwrap();
+
+        assert!(encryption
+            .decrypt("urn:customer:bob", Some(&*key), stored.clone())
+            .is_err());
+        let mut moved = stored.clone();
+        moved["Registered"]["plan"] = stored["Registered"]["email"].clone();
+        assert!(encryption.decrypt(STREAM, Some(&*key), moved).is_err());
+
+        let renamed = encryption
+            .rebind(&*key, STREAM, "urn:customer:ada-2", stored)
+            .unwrap();
+        assert_eq!(
+            encryption
+                .decrypt("urn:customer:ada-2", Some(&*key), renamed)
+                .unwrap(),
+            payload()
+        );
+    }
+
+    #[test]
+    fn a_read_unwraps_each_stream_key_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Counting(AesGcmCrypto, Arc<AtomicUsize>);
+
+        impl EventCrypto for Counting {
+            fn generate_data_key(&self) -> Result<Vec<u8>, replay::Error> {
+                self.0.generate_data_key()
+            }
+
+            fn data_key(&self, data_key: &[u8]) -> Result<Box<dyn DataKey>, replay::Error> {
+                self.1.fetch_add(1, Ordering::SeqCst);
+                self.0.data_key(data_key)
+            }
+        }
+
+        let unwraps = Arc::new(AtomicUsize::new(0));
+        let encryption = Encryption::fields(Counting(crypto(), unwraps.clone()), ["email"]);
+        let stored_key = encryption.generate_data_key().unwrap();
+        let key = encryption.data_key(&stored_key).unwrap();
+        let stored = encryption.encrypt(STREAM, &*key, &payload()).unwrap();
+        unwraps.store(0, Ordering::SeqCst);
+
+        let keys = encryption.keys();
+        for _ in 0..3 {
+            let read = keys
+                .decrypt(STREAM, Some(&stored_key), stored.clone())
+                .unwrap();
+            assert_eq!(read, payload());
+        }
+
+        assert_eq!(unwraps.load(Ordering::SeqCst), 1);
+    }
+}
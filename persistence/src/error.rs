@@ -1,5 +1,15 @@
+use std::sync::Arc;
+
 use urn::Urn;
 
+/// A hook that translates a [`sqlx::Error`] into a [`replay::Error`].
+///
+/// The default is [`db_error`]. Install a custom mapper on a store (e.g.
+/// [`PostgresEventStore::with_db_error_mapper`](crate::PostgresEventStore::with_db_error_mapper))
+/// to classify driver errors differently — a custom mapper typically matches the cases it
+/// cares about and falls back to [`db_error`] for the rest.
+pub type DbErrorMapper = Arc<dyn Fn(sqlx::Error) -> replay::Error + Send + Sync>;
+
 /// Convert a deserialization error to replay::Error
 pub fn deser_error(error: serde_json::Error) -> replay::Error {
     replay::Error::internal(format!("Deserialization failed: {}", error))
@@ -12,6 +22,23 @@ pub fn ser_error(error: serde_json::Error) -> replay::Error {
 }
 
 /// Convert a sqlx error to replay::Error
+///
+/// Errors are classified by what the caller can do about them:
+///
+/// | sqlx error                                              | replay kind             |
+/// |---------------------------------------------------------|-------------------------|
+/// | `RowNotFound`                                           | `NotFound`              |
+/// | `P0002` no data found (appending to a deleted stream)   | `NotFound`              |
+/// | `42501` insufficient privilege (a foreign tenant)       | `Forbidden`             |
+/// | unique / exclusion violation                            | `Conflict`              |
+/// | `40001` serialization failure, `40P01` deadlock         | `Conflict`              |
+/// | foreign key / not-null / check violation                | `InvalidInput`          |
+/// | `08xxx` connection exception, `57P0x` shutdown, `53300` | `Unavailable`           |
+/// | `PoolTimedOut`, `PoolClosed`, I/O, TLS, worker crash    | `Unavailable`           |
+/// | anything else                                           | `Internal`              |
+///
+/// `Conflict` and `Unavailable` are temporary, so callers that retry on
+/// [`replay::Error::is_temporary`] pick up genuine transient failures.
 pub fn db_error(error: sqlx::Error) -> replay::Error {
     match error {
         sqlx::Error::RowNotFound => {
@@ -21,11 +48,57 @@ pub fn db_error(error: sqlx::Error) -> replay::Error {
             replay::Error::unavailable("Database connection pool timed out")
                 .with_operation("database_connect")
         }
+        sqlx::Error::PoolClosed => replay::Error::unavailable("Database connection pool closed")
+            .with_operation("database_connect"),
+        sqlx::Error::Io(_) | sqlx::Error::Tls(_) | sqlx::Error::WorkerCrashed => {
+            replay::Error::unavailable(format!("Database connection error: {}", error))
+                .with_operation("database_connect")
+                .with_source(error)
+        }
+        sqlx::Error::Database(ref db) => {
+            let code = db.code().map(|c| c.into_owned()).unwrap_or_default();
+            let constraint = db.constraint().map(str::to_string);
+
+            let mapped = match (db.kind(), code.as_str()) {
+                (sqlx::error::ErrorKind::UniqueViolation, _)
+                | (sqlx::error::ErrorKind::ExclusionViolation, _) => {
+                    replay::Error::conflict(format!("Constraint violation: {}", db.message()))
+                }
+                (_, "P0002") => replay::Error::not_found(db.message().to_string()),
+                (_, "42501") => replay::Error::forbidden(db.message().to_string()),
+                (_, "40001") | (_, "40P01") => {
+                    replay::Error::conflict(format!("Transaction conflict: {}", db.message()))
+                }
+                (sqlx::error::ErrorKind::ForeignKeyViolation, _)
+                | (sqlx::error::ErrorKind::NotNullViolation, _)
+                | (sqlx::error::ErrorKind::CheckViolation, _) => {
+                    replay::Error::invalid_input(format!("Constraint violation: {}", db.message()))
+                }
+                (_, c) if c.starts_with("08") || c.starts_with("57P0") || c == "53300" => {
+                    replay::Error::unavailable(format!("Database unavailable: {}", db.message()))
+                }
+                _ => replay::Error::internal(format!("Database error: {}", error)),
+            };
+
+            let mapped = mapped
+                .with_operation("database_operation")
+                .with_context("sqlstate", code);
+            let mapped = match constraint {
+                Some(constraint) => mapped.with_context("constraint", constraint),
+                None => mapped,
+            };
+            mapped.with_source(error)
+        }
         _ => replay::Error::internal(format!("Database error: {}", error))
             .with_operation("database_operation"),
     }
 }
 
+/// The default [`DbErrorMapper`], wrapping [`db_error`].
+pub(crate) fn default_db_error_mapper() -> DbErrorMapper {
+    Arc::new(db_error)
+}
+
 /// Create a concurrency conflict error
 pub fn concurrency_error(
     stream_id: Urn,
@@ -38,3 +111,138 @@ pub fn concurrency_error(
         .with_context("expected_version", expected_version)
         .with_context("actual_version", actual_version)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::fmt;
+
+    use replay::ErrorKind;
+
+    use super::db_error;
+
+    /// Minimal driver error carrying a SQLSTATE, used to exercise the mapping table.
+    #[derive(Debug)]
+    struct FakeDbError {
+        code: &'static str,
+        kind: sqlx::error::ErrorKind,
+    }
+
+    impl fmt::Display for FakeDbError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "fake database error {}", self.code)
+        }
+    }
+
+    impl std::error::Error for FakeDbError {}
+
+    impl sqlx::error::DatabaseError for FakeDbError {
+        fn message(&self) -> &str {
+            "fake database error"
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.code))
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            match self.kind {
+                sqlx::error::ErrorKind::UniqueViolation => sqlx::error::ErrorKind::UniqueViolation,
+                sqlx::error::ErrorKind::ForeignKeyViolation => {
+                    sqlx::error::ErrorKind::ForeignKeyViolation
+                }
+                _ => sqlx::error::ErrorKind::Other,
+            }
+        }
+    }
+
+    fn database(code: &'static str, kind: sqlx::error::ErrorKind) -> sqlx::Error {
+        sqlx::Error::Database(Box::new(FakeDbError { code, kind }))
+    }
+
+    #[test]
+    fn unique_violation_is_a_temporary_conflict() {
+        let err = db_error(database("23505", sqlx::error::ErrorKind::UniqueViolation));
+        assert_eq!(err.kind(), ErrorKind::Conflict);
+        assert!(err.is_temporary());
+        assert!(err
+            .context()
+            .iter()
+            .any(|(k, v)| *k == "sqlstate" && v == "23505"));
+    }
+
+    #[test]
+    fn serialization_failure_and_deadlock_are_conflicts() {
+        for code in ["40001", "40P01"] {
+            let err = db_error(database(code, sqlx::error::ErrorKind::Other));
+            assert_eq!(err.kind(), ErrorKind::Conflict, "sqlstate {code}");
+        }
+    }
+
+    #[test]
+    fn no_data_found_is_not_found() {
+        let err = db_error(database("P0002", sqlx::error::ErrorKind::Other));
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn insufficient_privilege_is_forbidden() {
+        let err = db_error(database("42501", sqlx::error::ErrorKind::Other));
+        assert_eq!(err.kind(), ErrorKind::Forbidden);
+    }
+
+    #[test]
+    fn foreign_key_violation_is_invalid_input() {
+        let err = db_error(database(
+            "23503",
+            sqlx::error::ErrorKind::ForeignKeyViolation,
+        ));
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert!(err.is_permanent());
+    }
+
+    #[test]
+    fn connection_exceptions_are_unavailable() {
+        for code in ["08006", "57P01", "53300"] {
+            let err = db_error(database(code, sqlx::error::ErrorKind::Other));
+            assert_eq!(err.kind(), ErrorKind::Unavailable, "sqlstate {code}");
+            assert!(err.is_temporary());
+        }
+    }
+
+    #[test]
+    fn io_and_pool_errors_are_unavailable() {
+        let io = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset by peer");
+        assert_eq!(db_error(sqlx::Error::Io(io)).kind(), ErrorKind::Unavailable);
+        assert_eq!(
+            db_error(sqlx::Error::PoolClosed).kind(),
+            ErrorKind::Unavailable
+        );
+        assert_eq!(
+            db_error(sqlx::Error::PoolTimedOut).kind(),
+            ErrorKind::Unavailable
+        );
+    }
+
+    #[test]
+    fn unknown_errors_stay_internal() {
+        let err = db_error(database("XX000", sqlx::error::ErrorKind::Other));
+        assert_eq!(err.kind(), ErrorKind::Internal);
+        assert_eq!(
+            db_error(sqlx::Error::Protocol("bad".into())).kind(),
+            ErrorKind::Internal
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use std::ops::Not;
 
 use chrono::Utc;
+use replay::StreamId;
 use serde::Serialize;
 use urn::Urn;
 
@@ -11,8 +12,18 @@ pub enum StreamFilter {
     #[default]
     All,
     WithStreamId(Urn),
+    /// Matches events of any of the given streams.
+    WithStreamIds(Vec<Urn>),
     ForStreamTypes(Vec<String>),
     WithMetadata(replay::Metadata),
+    /// Matches events whose metadata carries the given `correlation_id`.
+    WithCorrelationId(String),
+    /// Matches events whose metadata carries the given `causation_id`.
+    WithCausationId(String),
+    /// Matches events whose metadata carries the given `actor`.
+    WithActor(String),
+    /// Matches events whose metadata carries the given `tenant_id`.
+    WithTenantId(String),
     /// Matches events whose sequence version is strictly greater than the given value.
     AfterVersion(i64),
     /// Matches events whose sequence version is less than or equal to the given value.
@@ -24,23 +35,64 @@ pub enum StreamFilter {
     /// Matches events whose `aggregate_version` equals the given value.
     /// `None` selects current (non-archived) events; `Some(n)` selects version n.
     WithAggregateVersion(Option<i32>),
+    /// Matches events whose global position is strictly greater than the given value.
+    AfterGlobalPosition(i64),
+    /// Matches every event, and lets the read include the events of soft-deleted streams
+    /// (see [`DeletionMode::Soft`](crate::DeletionMode::Soft)) when part of a conjunction.
+    IncludingDeleted,
     And(Box<StreamFilter>, Box<StreamFilter>),
     Or(Box<StreamFilter>, Box<StreamFilter>),
     Not(Box<StreamFilter>),
 }
 
 impl StreamFilter {
+    /// The first `WithStreamId` URN in a conjunction of filters, if any.
+    pub(crate) fn stream_id(&self) -> Option<&Urn> {
+        match self {
+            StreamFilter::WithStreamId(id) => Some(id),
+            StreamFilter::And(left, right) => left.stream_id().or_else(|| right.stream_id()),
+            _ => None,
+        }
+    }
+
+    /// Whether a conjunction of filters holds `IncludingDeleted`.
+    pub(crate) fn includes_deleted(&self) -> bool {
+        match self {
+            StreamFilter::IncludingDeleted => true,
+            StreamFilter::And(left, right) => left.includes_deleted() || right.includes_deleted(),
+            _ => false,
+        }
+    }
+
+    /// The first `WithTenantId` tenant in a conjunction of filters, if any.
+    pub(crate) fn tenant_id(&self) -> Option<&str> {
+        match self {
+            StreamFilter::WithTenantId(tenant) => Some(tenant),
+            StreamFilter::And(left, right) => left.tenant_id().or_else(|| right.tenant_id()),
+            _ => None,
+        }
+    }
+
     pub fn passes<S: replay::EventStream>(&self, event: &PersistedEvent<S::Event>) -> bool {
         match self {
             StreamFilter::All => true,
             StreamFilter::WithStreamId(stream_id) => event.stream_id == *stream_id,
-            StreamFilter::ForStreamTypes(stream_types) => stream_types.contains(&S::stream_type()),
+            StreamFilter::WithStreamIds(stream_ids) => stream_ids.contains(&event.stream_id),
+            StreamFilter::ForStreamTypes(stream_types) => {
+                stream_types.iter().any(|t| t == S::stream_type())
+            }
             StreamFilter::WithMetadata(metadata) => event.metadata == *metadata,
+            StreamFilter::WithCorrelationId(id) => event.metadata.correlation_id() == Some(id),
+            StreamFilter::WithCausationId(id) => event.metadata.causation_id() == Some(id),
+            StreamFilter::WithActor(actor) => event.metadata.actor() == Some(actor),
+            StreamFilter::WithTenantId(tenant) => event.metadata.tenant_id() == Some(tenant),
             StreamFilter::AfterVersion(version) => event.version > *version,
             StreamFilter::UpToVersion(version) => event.version <= *version,
             StreamFilter::CreatedAfter(timestamp) => event.created > *timestamp,
             StreamFilter::CreatedBefore(timestamp) => event.created <= *timestamp,
             StreamFilter::WithAggregateVersion(v) => event.aggregate_version == *v,
+            StreamFilter::AfterGlobalPosition(position) => event.global_position > *position,
+            StreamFilter::IncludingDeleted => true,
             StreamFilter::And(left, right) => left.passes::<S>(event) && right.passes::<S>(event),
             StreamFilter::Or(left, right) => left.passes::<S>(event) || right.passes::<S>(event),
             StreamFilter::Not(filter) => !filter.passes::<S>(event),
@@ -52,17 +104,54 @@ impl StreamFilter {
     }
 
     pub fn with_stream_id<S: replay::EventStream>(stream_id: &S::StreamId) -> StreamFilter {
-        StreamFilter::WithStreamId(stream_id.clone().into())
+        StreamFilter::WithStreamId(stream_id.stream_urn())
+    }
+
+    /// Events of any of `stream_ids`, read together, e.g. to load several aggregates at once.
+    pub fn with_stream_ids<'a, S: replay::EventStream>(
+        stream_ids: impl IntoIterator<Item = &'a S::StreamId>,
+    ) -> StreamFilter
+    where
+        S::StreamId: 'a,
+    {
+        StreamFilter::WithStreamIds(stream_ids.into_iter().map(StreamId::stream_urn).collect())
     }
 
     pub fn for_stream_type<S: replay::EventStream>() -> StreamFilter {
-        StreamFilter::ForStreamTypes(vec![S::stream_type()])
+        StreamFilter::ForStreamTypes(vec![S::stream_type().to_string()])
+    }
+
+    /// Events of any of the given stream types, e.g. a `query_events!` enum's `stream_types()`.
+    pub fn for_stream_types<T: Into<String>>(
+        stream_types: impl IntoIterator<Item = T>,
+    ) -> StreamFilter {
+        StreamFilter::ForStreamTypes(stream_types.into_iter().map(Into::into).collect())
     }
 
     pub fn with_metadata(metadata: impl Serialize) -> StreamFilter {
         StreamFilter::WithMetadata(replay::Metadata::new(metadata))
     }
 
+    /// Events belonging to the workflow identified by `id` (see [`replay::Metadata::correlation_id`]).
+    pub fn with_correlation_id(id: impl Into<String>) -> StreamFilter {
+        StreamFilter::WithCorrelationId(id.into())
+    }
+
+    /// Events directly caused by `id` (see [`replay::Metadata::causation_id`]).
+    pub fn with_causation_id(id: impl Into<String>) -> StreamFilter {
+        StreamFilter::WithCausationId(id.into())
+    }
+
+    /// Events produced by commands issued by `actor` (see [`replay::Metadata::actor`]).
+    pub fn with_actor(actor: impl Into<String>) -> StreamFilter {
+        StreamFilter::WithActor(actor.into())
+    }
+
+    /// Events belonging to `tenant` (see [`replay::Metadata::tenant_id`]).
+    pub fn with_tenant_id(tenant: impl Into<String>) -> StreamFilter {
+        StreamFilter::WithTenantId(tenant.into())
+    }
+
     pub fn after_version(version: i64) -> StreamFilter {
         StreamFilter::AfterVersion(version)
     }
@@ -71,6 +160,12 @@ impl StreamFilter {
         StreamFilter::UpToVersion(version)
     }
 
+    /// Events appended after the one at `position` (see
+    /// [`PersistedEvent::global_position`]), in any stream; used to tail the store.
+    pub fn after_global_position(position: i64) -> StreamFilter {
+        StreamFilter::AfterGlobalPosition(position)
+    }
+
     pub fn created_after(timestamp: chrono::DateTime<Utc>) -> StreamFilter {
         StreamFilter::CreatedAfter(timestamp)
     }
@@ -127,6 +222,11 @@ impl StreamFilter {
     pub fn and_aggregate_version(self, aggregate_version: Option<i32>) -> StreamFilter {
         self.and(StreamFilter::WithAggregateVersion(aggregate_version))
     }
+
+    /// Also read the events of soft-deleted streams, which reads leave out otherwise.
+    pub fn including_deleted(self) -> StreamFilter {
+        self.and(StreamFilter::IncludingDeleted)
+    }
 }
 
 impl Not for StreamFilter {
@@ -174,8 +274,8 @@ mod tests {
     impl replay::EventStream for BankAccountStream {
         type Event = BankAccountEvent;
 
-        fn stream_type() -> String {
-            "BankAccount".to_string()
+        fn stream_type() -> &'static str {
+            "BankAccount"
         }
 
         fn apply(&mut self, event: Self::Event) {
@@ -233,6 +333,7 @@ mod tests {
             }
             .into(),
             aggregate_version: None,
+            global_position: 1,
         };
         assert!(filter.passes::<BankAccountStream>(&persisted_event));
     }
@@ -257,6 +358,7 @@ mod tests {
             }
             .into(),
             aggregate_version: None,
+            global_position: 1,
         };
         assert!(filter.passes::<BankAccountStream>(&persisted_event));
     }
@@ -281,10 +383,23 @@ mod tests {
             }
             .into(),
             aggregate_version: None,
+            global_position: 1,
         };
         assert!(filter.passes::<BankAccountStream>(&persisted_event));
     }
 
+    #[test]
+    fn test_for_stream_types() {
+        let filter = super::StreamFilter::for_stream_types(["Other", "BankAccount"]);
+        assert_eq!(
+            filter,
+            super::StreamFilter::ForStreamTypes(vec![
+                "Other".to_string(),
+                "BankAccount".to_string()
+            ])
+        );
+    }
+
     // test an event pass filter `StreamFilter::WithMetadata`
     #[test]
     fn test_with_metadata() {
@@ -303,10 +418,66 @@ mod tests {
             created: chrono::Utc::now(),
             metadata: metadata.into(),
             aggregate_version: None,
+            global_position: 1,
         };
         assert!(filter.passes::<BankAccountStream>(&persisted_event));
     }
 
+    // test an event pass filter `StreamFilter::WithCorrelationId` / `WithCausationId`
+    #[test]
+    fn test_with_correlation_and_causation_id() {
+        let event = BankAccountEvent::Deposited { amount: 123f64 };
+        let bank_account_urn =
+            BankAccountUrn(UrnBuilder::new("bank-account", "123").build().unwrap());
+
+        let persisted_event = crate::PersistedEvent {
+            id: uuid::Uuid::new_v4(),
+            data: event,
+            stream_id: bank_account_urn.into(),
+            r#type: "BankAccountEvent".to_string(),
+            version: 1,
+            created: chrono::Utc::now(),
+            metadata: Metadata::default()
+                .with_correlation_id("req-1")
+                .with_causation_id("evt-1"),
+            aggregate_version: None,
+            global_position: 1,
+        };
+
+        assert!(super::StreamFilter::with_correlation_id("req-1")
+            .passes::<BankAccountStream>(&persisted_event));
+        assert!(super::StreamFilter::with_causation_id("evt-1")
+            .passes::<BankAccountStream>(&persisted_event));
+        assert!(!super::StreamFilter::with_correlation_id("req-2")
+            .passes::<BankAccountStream>(&persisted_event));
+    }
+
+    // test an event pass filter `StreamFilter::WithActor`
+    #[test]
+    fn test_with_actor() {
+        let event = BankAccountEvent::Deposited { amount: 123f64 };
+        let bank_account_urn =
+            BankAccountUrn(UrnBuilder::new("bank-account", "123").build().unwrap());
+
+        let persisted_event = crate::PersistedEvent {
+            id: uuid::Uuid::new_v4(),
+            data: event,
+            stream_id: bank_account_urn.into(),
+            r#type: "BankAccountEvent".to_string(),
+            version: 1,
+            created: chrono::Utc::now(),
+            metadata: Metadata::default().with_actor("user:42"),
+            aggregate_version: None,
+            global_position: 1,
+        };
+
+        assert_eq!(persisted_event.actor(), Some("user:42"));
+        assert!(super::StreamFilter::with_actor("user:42")
+            .passes::<BankAccountStream>(&persisted_event));
+        assert!(!super::StreamFilter::with_actor("billing-service")
+            .passes::<BankAccountStream>(&persisted_event));
+    }
+
     // test an event pass filter `StreamFilter::AfterVersion`
     #[test]
     fn test_after_version() {
@@ -327,6 +498,7 @@ mod tests {
             }
             .into(),
             aggregate_version: None,
+            global_position: 1,
         };
         assert!(filter.passes::<BankAccountStream>(&persisted_event));
     }
@@ -352,6 +524,7 @@ mod tests {
             }
             .into(),
             aggregate_version: None,
+            global_position: 1,
         };
         assert!(filter.passes::<BankAccountStream>(&persisted_event));
     }
@@ -377,6 +550,7 @@ mod tests {
             }
             .into(),
             aggregate_version: None,
+            global_position: 1,
         };
         assert!(filter.passes::<BankAccountStream>(&persisted_event));
     }
@@ -402,6 +576,7 @@ mod tests {
             }
             .into(),
             aggregate_version: None,
+            global_position: 1,
         };
         assert!(filter.passes::<BankAccountStream>(&persisted_event));
     }
@@ -427,8 +602,71 @@ mod tests {
             }
             .into(),
             aggregate_version: None,
+            global_position: 1,
         };
 
         assert!(filter.passes::<BankAccountStream>(&persisted_event));
     }
+
+    // test an event pass filter `StreamFilter::AfterGlobalPosition`
+    #[test]
+    fn test_after_global_position() {
+        let bank_account_urn =
+            BankAccountUrn(UrnBuilder::new("bank-account", "123").build().unwrap());
+        let event = BankAccountEvent::Deposited { amount: 123f64 };
+
+        let persisted_event = crate::PersistedEvent {
+            id: uuid::Uuid::new_v4(),
+            data: event,
+            stream_id: bank_account_urn.clone().into(),
+            r#type: "BankAccountEvent".to_string(),
+            version: 1,
+            created: chrono::Utc::now(),
+            metadata: BankAccountMetadata {
+                bank_account: bank_account_urn,
+            }
+            .into(),
+            aggregate_version: None,
+            global_position: 42,
+        };
+
+        assert!(super::StreamFilter::after_global_position(41)
+            .passes::<BankAccountStream>(&persisted_event));
+        assert!(!super::StreamFilter::after_global_position(42)
+            .passes::<BankAccountStream>(&persisted_event));
+    }
+
+    // test an event pass filter `StreamFilter::WithStreamIds`
+    #[test]
+    fn test_with_stream_ids() {
+        let bank_account_urn =
+            BankAccountUrn(UrnBuilder::new("bank-account", "123").build().unwrap());
+        let other_urn = BankAccountUrn(UrnBuilder::new("bank-account", "456").build().unwrap());
+        let event = BankAccountEvent::Deposited { amount: 123f64 };
+
+        let persisted_event = crate::PersistedEvent {
+            id: uuid::Uuid::new_v4(),
+            data: event,
+            stream_id: bank_account_urn.clone().into(),
+            r#type: "BankAccountEvent".to_string(),
+            version: 1,
+            created: chrono::Utc::now(),
+            metadata: BankAccountMetadata {
+                bank_account: bank_account_urn.clone(),
+            }
+            .into(),
+            aggregate_version: None,
+            global_position: 1,
+        };
+
+        assert!(super::StreamFilter::with_stream_ids::<BankAccountStream>([
+            &other_urn,
+            &bank_account_urn
+        ])
+        .passes::<BankAccountStream>(&persisted_event));
+        assert!(
+            !super::StreamFilter::with_stream_ids::<BankAccountStream>([&other_urn])
+                .passes::<BankAccountStream>(&persisted_event)
+        );
+    }
 }
@@ -0,0 +1,235 @@
+//! Cross-region replication: a [`Follower`] tails a primary store and copies new events to a
+//! replica, so a region can serve reads from its own copy.
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use replay::EventStream;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::{replicate, BackupStore, BulkImport, ReplicationOptions, StreamFilter};
+
+/// Where a [`Follower`] stands, as of its last pass.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FollowerStatus {
+    /// Primary global position the replica holds every event up to.
+    pub position: i64,
+    /// The primary's gap-free head when the last pass started.
+    pub head: i64,
+    /// Primary positions the replica was behind when the last pass started (`head` minus
+    /// the position then).
+    pub lag: i64,
+    /// When the last pass finished; `None` before the first one.
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+/// Copies the events of `S` streams from a primary store to a replica as they are appended,
+/// e.g. from the region that takes writes to every region that serves reads.
+///
+/// Each pass copies what the primary appended since the last one, up to its
+/// [`consistent_position`](BackupStore::consistent_position), so an append still in flight
+/// is picked up by a later pass rather than skipped. Events keep their ids, versions and
+/// metadata, so a [`Cqrs`](crate::Cqrs) over the replica loads the same aggregates as one
+/// over the primary, only [`lag`](FollowerStatus::lag) behind. Send commands to the primary.
+///
+/// ```rust,ignore
+/// let follower = Follower::<BankAccount, _, _>::new(primary, replica.clone())
+///     .with_poll_interval(Duration::from_millis(500))
+///     .start();
+/// let reads = Cqrs::new(replica);
+///
+/// tracing::info!(lag = follower.status().lag, "replica lag");
+/// ```
+pub struct Follower<S, Primary, Replica> {
+    primary: Arc<Primary>,
+    replica: Arc<Replica>,
+    batch_size: usize,
+    poll_interval: Duration,
+    status: Arc<watch::Sender<FollowerStatus>>,
+    _stream: PhantomData<fn() -> S>,
+}
+
+impl<S, Primary, Replica> Follower<S, Primary, Replica>
+where
+    S: EventStream + 'static,
+    Primary: BackupStore + 'static,
+    Replica: BulkImport + 'static,
+{
+    pub fn new(primary: impl Into<Arc<Primary>>, replica: impl Into<Arc<Replica>>) -> Self {
+        Self {
+            primary: primary.into(),
+            replica: replica.into(),
+            batch_size: 1_000,
+            poll_interval: Duration::from_secs(1),
+            status: Arc::new(watch::Sender::new(FollowerStatus::default())),
+            _stream: PhantomData,
+        }
+    }
+
+    /// Start after the primary global position `position`, already held by the replica
+    /// (e.g. the [`position`](FollowerStatus::position) saved before a restart).
+    pub fn with_resume_from(self, position: i64) -> Self {
+        self.status.send_modify(|status| status.position = position);
+        self
+    }
+
+    /// Import `size` events per replica write (at least 1).
+    pub fn with_batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Wait `interval` between passes of a [started](Self::start) follower (1 second by
+    /// default).
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    pub fn status(&self) -> FollowerStatus {
+        *self.status.borrow()
+    }
+
+    /// Receive every status update, e.g. to wait until the replica reached a position.
+    pub fn subscribe(&self) -> watch::Receiver<FollowerStatus> {
+        self.status.subscribe()
+    }
+
+    /// Copy what the primary appended since the last pass.
+    pub async fn catch_up(&self) -> Result<FollowerStatus, replay::Error> {
+        let head = self.primary.consistent_position().await?;
+        let from = self.status().position.min(head);
+        self.status.send_modify(|status| {
+            status.head = head;
+            status.lag = head - from;
+        });
+        crate::metrics::record_replication_lag(S::stream_type(), head - from);
+
+        let status = self.status.clone();
+        let options = ReplicationOptions::default()
+            .with_resume_from(from)
+            .with_up_to(head)
+            .with_batch_size(self.batch_size)
+            .with_progress(move |progress| {
+                status.send_modify(|status| status.position = progress.position)
+            });
+        replicate::<S, _, _>(&*self.primary, &*self.replica, StreamFilter::All, options)
+            .await
+            .map_err(|e| e.with_operation("follow"))?;
+
+        // Nothing of `S` is left before the head, whether or not the last copied event was.
+        self.status.send_modify(|status| {
+            status.position = status.position.max(head);
+            status.synced_at = Some(Utc::now());
+        });
+        crate::metrics::record_replication_lag(S::stream_type(), 0);
+        Ok(self.status())
+    }
+
+    /// Run passes in the background every [poll interval](Self::with_poll_interval) until
+    /// [`FollowerDaemon::shutdown`]. A failed pass is logged and retried on the next one.
+    pub fn start(self) -> FollowerDaemon {
+        let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+        let status = self.subscribe();
+
+        let task = tokio::spawn(async move {
+            loop {
+                if let Err(error) = self.catch_up().await {
+                    tracing::warn!(
+                        stream_type = S::stream_type(),
+                        error = %error,
+                        "replication pass failed; retrying next interval"
+                    );
+                }
+
+                tokio::select! {
+                    changed = shutdown_rx.changed() => {
+                        if changed.is_err() || *shutdown_rx.borrow() {
+                            return;
+                        }
+                    }
+                    _ = tokio::time::sleep(self.poll_interval) => {}
+                }
+            }
+        });
+
+        FollowerDaemon {
+            shutdown_tx,
+            task,
+            status,
+        }
+    }
+}
+
+/// Handle to a [started](Follower::start) [`Follower`].
+pub struct FollowerDaemon {
+    shutdown_tx: watch::Sender<bool>,
+    task: JoinHandle<()>,
+    status: watch::Receiver<FollowerStatus>,
+}
+
+impl FollowerDaemon {
+    pub fn status(&self) -> FollowerStatus {
+        *self.status.borrow()
+    }
+
+    /// Stop the task, letting a pass in progress finish.
+    pub async fn shutdown(self) {
+        let _ = self.shutdown_tx.send(true);
+        let _ = self.task.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+    use replay::Metadata;
+
+    use super::*;
+    use crate::conformance::{ConformanceAccount, ConformanceAccountUrn, ConformanceEvent};
+    use crate::{EventStore, InMemoryEventStore, PersistedEvent};
+
+    async fn deposit(store: &InMemoryEventStore, id: &ConformanceAccountUrn, amount: i64) {
+        store
+            .store_events::<ConformanceAccount>(
+                id,
+                ConformanceAccount::stream_type(),
+                Metadata::default(),
+                &[ConformanceEvent::Deposited { amount }],
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn passes_copy_only_new_events_and_report_lag() {
+        let primary = Arc::new(InMemoryEventStore::new());
+        let replica = Arc::new(InMemoryEventStore::new());
+        let follower = Follower::<ConformanceAccount, InMemoryEventStore, InMemoryEventStore>::new(
+            primary.clone(),
+            replica.clone(),
+        );
+        let id = ConformanceAccountUrn::new_random();
+
+        deposit(&primary, &id, 1).await;
+        deposit(&primary, &id, 2).await;
+        let status = follower.catch_up().await.unwrap();
+        assert_eq!((status.position, status.lag), (2, 2));
+
+        deposit(&primary, &id, 3).await;
+        let status = follower.catch_up().await.unwrap();
+        assert_eq!((status.position, status.lag), (3, 1));
+
+        let versions: Vec<i64> = replica
+            .stream_events::<ConformanceEvent>(StreamFilter::All)
+            .map_ok(|event: PersistedEvent<ConformanceEvent>| event.version)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(versions, [1, 2, 3]);
+    }
+}
@@ -0,0 +1,254 @@
+//! [async-graphql](https://docs.rs/async-graphql) helpers (feature `graphql`), to serve read
+//! models and live events to frontends without hand-written resolvers.
+//!
+//! Put the application's [`Cqrs`] in the schema data, then delegate fields to
+//! [`query_projection`] and [`event_subscription`]:
+//!
+//! ```rust,ignore
+//! use replay_persistence::graphql::{event_subscription, query_projection, GraphQlEvent};
+//!
+//! struct QueryRoot;
+//!
+//! #[Object]
+//! impl QueryRoot {
+//!     async fn balances(&self, ctx: &Context<'_>) -> async_graphql::Result<Balances> {
+//!         query_projection::<Balances, PostgresEventStore>(ctx).await
+//!     }
+//! }
+//!
+//! struct SubscriptionRoot;
+//!
+//! #[Subscription]
+//! impl SubscriptionRoot {
+//!     async fn account_events<'ctx>(
+//!         &self,
+//!         ctx: &Context<'ctx>,
+//!         after: Option<i64>,
+//!     ) -> async_graphql::Result<impl Stream<Item = async_graphql::Result<GraphQlEvent>> + 'ctx> {
+//!         let filter = StreamFilter::for_stream_type::<BankAccount>();
+//!         event_subscription::<PostgresEventStore>(ctx, filter, after.unwrap_or(0))
+//!     }
+//! }
+//!
+//! let schema = Schema::build(QueryRoot, EmptyMutation, SubscriptionRoot)
+//!     .data(cqrs)
+//!     .finish();
+//! ```
+//!
+//! Errors carry their [`replay::ErrorKind`] as the `code` extension, e.g. `NOT_FOUND`.
+
+use std::time::Duration;
+
+use async_graphql::{Context, ErrorExtensions, Json, SimpleObject};
+use chrono::{DateTime, Utc};
+use futures::{Stream, TryStreamExt};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::persisted_event::AnyEvent;
+use crate::{Cqrs, EventStore, PersistedEvent, Query, StreamFilter};
+
+/// How long an [`event_subscription`] waits before looking for new events once it has caught
+/// up.
+pub const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// An event as served over GraphQL, named `PersistedEvent` in the schema.
+#[derive(SimpleObject, Clone, Debug, PartialEq)]
+#[graphql(name = "PersistedEvent")]
+pub struct GraphQlEvent {
+    pub id: Uuid,
+    pub stream_id: String,
+    #[graphql(name = "type")]
+    pub event_type: String,
+    pub version: i64,
+    pub created: DateTime<Utc>,
+    pub data: Json<Value>,
+    pub metadata: Json<Value>,
+    /// Pass the last one received as `after` to resume a subscription.
+    pub global_position: i64,
+}
+
+impl GraphQlEvent {
+    fn from_persisted(event: PersistedEvent<AnyEvent>) -> Self {
+        GraphQlEvent {
+            id: event.id,
+            stream_id: event.stream_id.to_string(),
+            event_type: event.r#type,
+            version: event.version,
+            created: event.created,
+            data: Json(event.data.0),
+            metadata: Json(event.metadata.to_json()),
+            global_position: event.global_position,
+        }
+    }
+}
+
+/// Resolve a field to the read model `Q`, folded from the events of the schema's
+/// `Cqrs<ES>` with [`Cqrs::run_query`].
+pub async fn query_projection<Q, ES>(ctx: &Context<'_>) -> async_graphql::Result<Q>
+where
+    Q: Query + Default,
+    ES: EventStore + 'static,
+{
+    let cqrs = ctx.data::<Cqrs<ES>>()?;
+    let mut query = Q::default();
+    cqrs.run_query(&mut query).await.map_err(graphql_error)?;
+    Ok(query)
+}
+
+/// Resolve a subscription field to the events matching `filter` past the global position
+/// `after` (`0` for the whole history), then to new ones as they are appended.
+///
+/// New events are found by polling every [`EVENT_POLL_INTERVAL`]. A Postgres append that
+/// commits after a later one may be skipped, so clients that can't miss an event should
+/// reconcile with a query on reconnect.
+pub fn event_subscription<'ctx, ES>(
+    ctx: &Context<'ctx>,
+    filter: StreamFilter,
+    after: i64,
+) -> async_graphql::Result<impl Stream<Item = async_graphql::Result<GraphQlEvent>> + 'ctx>
+where
+    ES: EventStore + 'static,
+{
+    let cqrs = ctx.data::<Cqrs<ES>>()?;
+    Ok(async_stream::stream! {
+        let mut position = after;
+        loop {
+            let unseen = filter.clone().and(StreamFilter::after_global_position(position));
+            let events = cqrs.store().stream_events::<AnyEvent>(unseen).into_stream();
+            futures::pin_mut!(events);
+            loop {
+                match events.try_next().await {
+                    Ok(Some(event)) => {
+                        position = event.global_position;
+                        yield Ok(GraphQlEvent::from_persisted(event));
+                    }
+                    Ok(None) => break,
+                    Err(err) => {
+                        yield Err(graphql_error(err));
+                        return;
+                    }
+                }
+            }
+            tokio::time::sleep(EVENT_POLL_INTERVAL).await;
+        }
+    })
+}
+
+/// A GraphQL error with the message of `error` and its kind as the `code` extension.
+pub fn graphql_error(error: replay::Error) -> async_graphql::Error {
+    use replay::ErrorKind;
+
+    let code = match error.kind() {
+        ErrorKind::NotFound => "NOT_FOUND",
+        ErrorKind::InvalidInput => "INVALID_INPUT",
+        ErrorKind::Conflict => "CONFLICT",
+        ErrorKind::Unavailable => "UNAVAILABLE",
+        ErrorKind::Internal => "INTERNAL",
+        ErrorKind::BusinessRuleViolation => "BUSINESS_RULE_VIOLATION",
+        ErrorKind::Unauthorized => "UNAUTHORIZED",
+        ErrorKind::Forbidden => "FORBIDDEN",
+        ErrorKind::RateLimited => "RATE_LIMITED",
+        ErrorKind::Unsupported => "UNSUPPORTED",
+    };
+    async_graphql::Error::new(error.to_string()).extend_with(|_, extensions| {
+        extensions.set("code", code);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::{EmptyMutation, Object, Schema, Subscription};
+    use futures::StreamExt;
+    use replay::{EventStream, Metadata};
+
+    use super::*;
+    use crate::conformance::{ConformanceAccount, ConformanceAccountUrn, ConformanceEvent};
+    use crate::InMemoryEventStore;
+
+    #[derive(Default, SimpleObject)]
+    struct Deposits {
+        total: i64,
+    }
+
+    impl Query for Deposits {
+        type Event = ConformanceEvent;
+
+        fn update(&mut self, event: PersistedEvent<ConformanceEvent>) {
+            if let ConformanceEvent::Deposited { amount } = event.data {
+                self.total += amount;
+            }
+        }
+    }
+
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {
+        async fn deposits(&self, ctx: &Context<'_>) -> async_graphql::Result<Deposits> {
+            query_projection::<Deposits, InMemoryEventStore>(ctx).await
+        }
+    }
+
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn events<'ctx>(
+            &self,
+            ctx: &Context<'ctx>,
+            after: Option<i64>,
+        ) -> async_graphql::Result<impl Stream<Item = async_graphql::Result<GraphQlEvent>> + 'ctx>
+        {
+            event_subscription::<InMemoryEventStore>(ctx, StreamFilter::All, after.unwrap_or(0))
+        }
+    }
+
+    async fn schema() -> Schema<QueryRoot, EmptyMutation, SubscriptionRoot> {
+        let store = InMemoryEventStore::new();
+        store
+            .store_events::<ConformanceAccount>(
+                &ConformanceAccountUrn::new_random(),
+                ConformanceAccount::stream_type(),
+                Metadata::default(),
+                &[
+                    ConformanceEvent::Deposited { amount: 10 },
+                    ConformanceEvent::Deposited { amount: 5 },
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+        Schema::build(QueryRoot, EmptyMutation, SubscriptionRoot)
+            .data(Cqrs::new(store))
+            .finish()
+    }
+
+    #[tokio::test]
+    async fn projections_resolve_as_queries() {
+        let response = schema().await.execute("{ deposits { total } }").await;
+
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({ "deposits": { "total": 15 } })
+        );
+    }
+
+    #[tokio::test]
+    async fn subscriptions_resume_after_a_position() {
+        let schema = schema().await;
+        let subscription = "subscription { events(after: 1) { type version globalPosition } }";
+        let mut events = schema.execute_stream(subscription);
+
+        let response = events.next().await.unwrap();
+
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({
+                "events": { "type": "Deposited", "version": 2, "globalPosition": 2 }
+            })
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+/// Source of the ids the stores give appended events.
+///
+/// The default, [`UuidV7`], makes ids sort by creation time. Install another generator on a
+/// store (e.g. [`InMemoryEventStore::with_id_generator`](crate::InMemoryEventStore::with_id_generator))
+/// for deterministic ids in golden tests, or to derive ids from something the caller already
+/// knows so a retried append produces the same ids. Any `Fn() -> Uuid + Send + Sync` closure
+/// is a generator.
+pub trait IdGenerator: Send + Sync {
+    /// Id for the next appended event.
+    fn next_id(&self) -> Uuid;
+}
+
+impl<F> IdGenerator for F
+where
+    F: Fn() -> Uuid + Send + Sync,
+{
+    fn next_id(&self) -> Uuid {
+        self()
+    }
+}
+
+/// Time-ordered random ids ([`Uuid::now_v7`]); the stores' default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UuidV7;
+
+impl IdGenerator for UuidV7 {
+    fn next_id(&self) -> Uuid {
+        Uuid::now_v7()
+    }
+}
+
+/// Ids `00000000-0000-0000-0000-000000000001`, `…-000000000002`, … for reproducible tests.
+#[derive(Debug, Default)]
+pub struct SequentialIds {
+    last: AtomicU64,
+}
+
+impl SequentialIds {
+    /// Start counting after `last`: the first id is `last + 1`.
+    pub fn starting_after(last: u64) -> Self {
+        SequentialIds {
+            last: AtomicU64::new(last),
+        }
+    }
+}
+
+impl IdGenerator for SequentialIds {
+    fn next_id(&self) -> Uuid {
+        Uuid::from_u128(u128::from(self.last.fetch_add(1, Ordering::Relaxed) + 1))
+    }
+}
+
+pub(crate) type SharedIdGenerator = Arc<dyn IdGenerator>;
+
+pub(crate) fn default_id_generator() -> SharedIdGenerator {
+    Arc::new(UuidV7)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequential_ids_count_up_from_one() {
+        let ids = SequentialIds::default();
+        assert_eq!(ids.next_id(), Uuid::from_u128(1));
+        assert_eq!(ids.next_id(), Uuid::from_u128(2));
+
+        let ids = SequentialIds::starting_after(41);
+        assert_eq!(ids.next_id(), Uuid::from_u128(42));
+    }
+
+    #[test]
+    fn uuid_v7_ids_are_time_ordered() {
+        let first = UuidV7.next_id();
+        let second = UuidV7.next_id();
+        assert_eq!(first.get_version_num(), 7);
+        assert!(first < second);
+    }
+}
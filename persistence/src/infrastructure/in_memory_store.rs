@@ -1,17 +1,32 @@
-use std::{collections::HashMap, sync::RwLock};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
 
-use chrono::Utc;
 use futures::{TryStream, TryStreamExt};
 use serde_json::Value;
 use tokio::sync::Mutex;
+use tracing::Instrument;
 use urn::Urn;
 use uuid::Uuid;
 
+use crate::clock::{default_clock, SharedClock};
+use crate::id_generator::{default_id_generator, SharedIdGenerator};
 use crate::inline_projection::ErasedInlineProjection;
+use crate::persisted_event::versioned_metadata;
 use crate::{
-    CompactionOutcome, EventSink, EventStore, InlineProjection, PersistedEvent, StreamFilter,
+    Clock, CompactionOutcome, DeletionMode, EventSink, EventStore, IdGenerator, InlineProjection,
+    PersistedEvent, SequentialIds, SteppingClock, StoreHealth, StoreStatistics, StreamFilter,
+    StreamListing, StreamMetadata, StreamPage, StreamRetention, StreamState, StreamSummary,
 };
-use replay::{Compactable, Event};
+use replay::{Compactable, Event, Snapshottable, StreamId};
+
+/// The `store` label of this store's [metrics](crate::metrics).
+const STORE: &str = "in_memory";
 
 /// In-memory event store implementation, only for testing purpose.
 ///
@@ -44,6 +59,25 @@ pub struct InMemoryEventStore {
     /// unchanged stream is skipped. Absent means "never compacted" (eligible if it has
     /// events). Mirrors `streams.last_compacted_version` in the Postgres store.
     last_compacted_version: RwLock<HashMap<Urn, i64>>,
+    /// Soft-deleted streams, left out of reads. Mirrors `streams.deleted` in the Postgres
+    /// store.
+    deleted: RwLock<HashSet<Urn>>,
+    /// The tenant each stream was created for, absent for streams created without one.
+    /// Mirrors `streams.tenant_id` in the Postgres store.
+    stream_tenants: RwLock<HashMap<Urn, String>>,
+    /// Per-stream limits set with `set_stream_retention`. Mirrors `streams.max_age` and
+    /// `streams.max_count` in the Postgres store.
+    retention: RwLock<HashMap<Urn, StreamRetention>>,
+    /// Metadata set with `set_stream_metadata`. Mirrors `streams.metadata` in the Postgres
+    /// store.
+    stream_metadata: RwLock<HashMap<Urn, StreamMetadata>>,
+    /// Ids for appended events; [`UuidV7`](crate::UuidV7) unless replaced.
+    id_generator: SharedIdGenerator,
+    /// `created` timestamps for appended events; the wall clock unless replaced.
+    clock: SharedClock,
+    /// Last [`PersistedEvent::global_position`] handed out. Like the Postgres sequence, a
+    /// position taken by an append whose producer fails is not reused.
+    last_position: AtomicI64,
 }
 
 impl InMemoryEventStore {
@@ -53,9 +87,37 @@ impl InMemoryEventStore {
             stream_types: RwLock::new(HashMap::new()),
             projections: Vec::new(),
             last_compacted_version: RwLock::new(HashMap::new()),
+            deleted: RwLock::new(HashSet::new()),
+            stream_tenants: RwLock::new(HashMap::new()),
+            retention: RwLock::new(HashMap::new()),
+            stream_metadata: RwLock::new(HashMap::new()),
+            id_generator: default_id_generator(),
+            clock: default_clock(),
+            last_position: AtomicI64::new(0),
         }
     }
 
+    /// A store whose output is the same on every run: ids count up from
+    /// [`SequentialIds`] and timestamps from a [`SteppingClock`] (2024-01-01T00:00:00Z, one
+    /// second per event). Reads across streams come back in append order.
+    pub fn deterministic() -> Self {
+        Self::new()
+            .with_id_generator(SequentialIds::default())
+            .with_clock(SteppingClock::default())
+    }
+
+    /// Replace the source of `created` timestamps (the wall clock by default).
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Replace the source of event ids (time-ordered UUIDv7 by default).
+    pub fn with_id_generator(mut self, id_generator: impl IdGenerator + 'static) -> Self {
+        self.id_generator = Arc::new(id_generator);
+        self
+    }
+
     /// Register a best-effort inline projection (test-only).
     ///
     /// After each successful append, the store routes the newly-appended events to this
@@ -76,6 +138,102 @@ impl InMemoryEventStore {
         self
     }
 
+    /// Count the streams and events held, like
+    /// [`PostgresEventStore::statistics`](crate::PostgresEventStore::statistics). There is no
+    /// on-disk size, so `size_bytes` is `None`.
+    pub fn statistics(&self) -> StoreStatistics {
+        let events = self.events.read().unwrap();
+        let stream_types = self.stream_types.read().unwrap();
+
+        let mut statistics = StoreStatistics::default();
+        for (stream_id, stream) in events.iter().filter(|(_, stream)| !stream.is_empty()) {
+            statistics.streams += 1;
+            let live = stream
+                .iter()
+                .filter(|e| e.aggregate_version.is_none())
+                .count() as u64;
+            statistics.events += live;
+            statistics.archived_events += stream.len() as u64 - live;
+            if let Some(stream_type) = stream_types.get(stream_id).filter(|_| live > 0) {
+                *statistics
+                    .events_by_stream_type
+                    .entry(stream_type.clone())
+                    .or_default() += live;
+            }
+        }
+        let created = || events.values().flatten().map(|event| event.created);
+        statistics.oldest_event = created().min();
+        statistics.newest_event = created().max();
+        statistics
+    }
+
+    /// Load already-persisted events of `S` streams, like
+    /// [`PostgresEventStore::bulk_import`](crate::PostgresEventStore::bulk_import): events keep
+    /// their id, version, timestamp, metadata and `aggregate_version`, and get new global
+    /// positions in the order they arrive.
+    ///
+    /// Nothing is imported when the producer fails or an event id already exists (a
+    /// `Conflict`). Registered projections are not run. Returns the number of imported events.
+    pub async fn bulk_import<S, Events>(&self, events: Events) -> Result<u64, replay::Error>
+    where
+        S: replay::EventStream,
+        Events: TryStream<Ok = PersistedEvent<S::Event>, Error = replay::Error> + Send,
+    {
+        let mut events = std::pin::pin!(events.into_stream());
+        let mut staged: Vec<PersistedEvent<Value>> = Vec::new();
+        while let Some(event) = events.try_next().await? {
+            staged.push(PersistedEvent {
+                id: event.id,
+                data: serde_json::to_value(&event.data).map_err(crate::ser_error)?,
+                stream_id: event.stream_id,
+                r#type: event.r#type,
+                version: event.version,
+                created: event.created,
+                metadata: event.metadata,
+                aggregate_version: event.aggregate_version,
+                global_position: 0,
+            });
+        }
+
+        let mut store = self.events.write().unwrap();
+        let mut ids: std::collections::HashSet<_> =
+            store.values().flatten().map(|event| event.id).collect();
+        if let Some(event) = staged.iter().find(|event| !ids.insert(event.id)) {
+            return Err(replay::Error::conflict("event already exists")
+                .with_operation("bulk_import")
+                .with_context("event_id", event.id));
+        }
+
+        let mut stream_types = self.stream_types.write().unwrap();
+        let mut stream_tenants = self.stream_tenants.write().unwrap();
+        let imported = staged.len() as u64;
+        for mut event in staged {
+            event.global_position = self.next_position();
+            if !store.contains_key(&event.stream_id) {
+                if let Some(tenant) = event.metadata.tenant_id() {
+                    stream_tenants.insert(event.stream_id.clone(), tenant.to_string());
+                }
+            }
+            stream_types.insert(event.stream_id.clone(), S::stream_type().to_string());
+            store
+                .entry(event.stream_id.clone())
+                .or_default()
+                .push(event);
+        }
+        Ok(imported)
+    }
+
+    /// Restore an NDJSON backup of `S` streams written by
+    /// [`export_events`](crate::export_events), through [`bulk_import`](Self::bulk_import).
+    pub async fn import_events<S, R>(&self, reader: R) -> Result<u64, replay::Error>
+    where
+        S: replay::EventStream,
+        R: tokio::io::AsyncBufRead + Unpin + Send,
+    {
+        self.bulk_import::<S, _>(crate::read_events::<S::Event, _>(reader))
+            .await
+    }
+
     /// Drive every registered projection over the just-appended events, best-effort.
     ///
     /// Each projection routes the batch by deserialize-or-skip and runs at most once with the
@@ -92,25 +250,76 @@ impl InMemoryEventStore {
         let mut exec = ();
         for projection in self.projections.iter() {
             let mut projection = projection.lock().await;
-            projection.handle(&mut exec, events).await?;
+            let span = tracing::info_span!("inline_projection", projection = projection.name());
+            #[cfg(feature = "opentelemetry")]
+            events.iter().for_each(|event| event.link_trace(&span));
+            projection
+                .handle(&mut exec, events)
+                .instrument(span)
+                .await?;
         }
 
         Ok(())
     }
 
-    /// Walk a filter tree and return the first `WithStreamId` URN found (used for fast lookup).
-    fn extract_stream_id(filter: &StreamFilter) -> Option<Urn> {
-        match filter {
-            StreamFilter::WithStreamId(id) => Some(id.clone()),
-            StreamFilter::And(left, right) => {
-                Self::extract_stream_id(left).or_else(|| Self::extract_stream_id(right))
-            }
-            _ => None,
+    /// The last global position handed out, `0` before the first append.
+    pub(crate) fn head_position(&self) -> i64 {
+        self.last_position.load(Ordering::SeqCst)
+    }
+
+    /// The version the next append to each stream of `stream_type` continues from.
+    pub(crate) fn stream_heads(&self, stream_type: &str) -> HashMap<Urn, i64> {
+        let store = self.events.read().unwrap();
+        let stream_types = self.stream_types.read().unwrap();
+        stream_types
+            .iter()
+            .filter(|(_, st)| *st == stream_type)
+            .map(|(stream_id, _)| {
+                let head = store
+                    .get(stream_id)
+                    .and_then(|events| events.iter().rfind(|e| e.aggregate_version.is_none()))
+                    .map_or(0, |e| e.version);
+                (stream_id.clone(), head)
+            })
+            .collect()
+    }
+
+    /// Delete the events with `ids`, returning how many there were.
+    pub(crate) fn remove_events(&self, ids: &HashSet<Uuid>) -> u64 {
+        let mut store = self.events.write().unwrap();
+        let mut removed = 0;
+        for stream in store.values_mut() {
+            let before = stream.len();
+            stream.retain(|event| !ids.contains(&event.id));
+            removed += (before - stream.len()) as u64;
         }
+        removed
+    }
+
+    fn next_position(&self) -> i64 {
+        self.last_position.fetch_add(1, Ordering::SeqCst) + 1
     }
 
     /// Apply a filter to a raw (un-typed) persisted event.
     ///
+    /// The first live version of `stream` kept under `retention`: every live event below it
+    /// expired. Ages are measured against the wall clock, like Postgres's `now()`.
+    fn retained_from(stream: &[PersistedEvent<Value>], retention: &StreamRetention) -> i64 {
+        let now = chrono::Utc::now();
+        let head = stream
+            .iter()
+            .rfind(|e| e.aggregate_version.is_none())
+            .map_or(0, |e| e.version);
+        stream
+            .iter()
+            .filter(|e| {
+                e.aggregate_version.is_none() && retention.expires(e.version, e.created, head, now)
+            })
+            .map(|e| e.version + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
     /// Every [`StreamFilter`] variant is evaluated as a per-event predicate. `stream_type` is
     /// the type of the stream the event belongs to (resolved from the store's side map); it is
     /// only needed by [`StreamFilter::ForStreamTypes`].
@@ -122,15 +331,22 @@ impl InMemoryEventStore {
         match filter {
             StreamFilter::All => true,
             StreamFilter::WithStreamId(stream_id) => event.stream_id == *stream_id,
+            StreamFilter::WithStreamIds(stream_ids) => stream_ids.contains(&event.stream_id),
             StreamFilter::ForStreamTypes(stream_types) => {
                 stream_type.is_some_and(|st| stream_types.iter().any(|t| t == st))
             }
             StreamFilter::WithMetadata(metadata) => event.metadata == *metadata,
+            StreamFilter::WithCorrelationId(id) => event.metadata.correlation_id() == Some(id),
+            StreamFilter::WithCausationId(id) => event.metadata.causation_id() == Some(id),
+            StreamFilter::WithActor(actor) => event.metadata.actor() == Some(actor),
+            StreamFilter::WithTenantId(tenant) => event.metadata.tenant_id() == Some(tenant),
             StreamFilter::AfterVersion(version) => event.version > *version,
             StreamFilter::UpToVersion(version) => event.version <= *version,
             StreamFilter::CreatedAfter(timestamp) => event.created > *timestamp,
             StreamFilter::CreatedBefore(timestamp) => event.created <= *timestamp,
             StreamFilter::WithAggregateVersion(v) => event.aggregate_version == *v,
+            StreamFilter::AfterGlobalPosition(position) => event.global_position > *position,
+            StreamFilter::IncludingDeleted => true,
             StreamFilter::And(left, right) => {
                 Self::evaluate(left, event, stream_type)
                     && Self::evaluate(right, event, stream_type)
@@ -150,11 +366,12 @@ impl Default for InMemoryEventStore {
     }
 }
 
-impl EventStore for InMemoryEventStore {
-    async fn store_events_stream<S, ES, Sink>(
+impl InMemoryEventStore {
+    /// The append behind [`EventStore::store_events_stream`], which adds metrics.
+    async fn append_stream<S, ES, Sink>(
         &self,
         stream_id: &S::StreamId,
-        stream_type: String,
+        stream_type: &str,
         metadata: replay::Metadata,
         domain_events: ES,
         expected_version: Option<i64>,
@@ -165,13 +382,20 @@ impl EventStore for InMemoryEventStore {
         ES: TryStream<Ok = S::Event, Error = replay::Error> + Send,
         Sink: EventSink<S::Event> + Send,
     {
-        let stream_id: Urn = stream_id.clone().into();
+        let stream_id = stream_id.stream_urn();
+
+        // Like `append_events` in Postgres: a soft-deleted stream takes no more events.
+        if self.deleted.read().unwrap().contains(&stream_id) {
+            return Err(replay::Error::not_found("stream was deleted")
+                .with_operation("store_events")
+                .with_context("stream_id", &stream_id));
+        }
 
         // Record the stream's type so `ForStreamTypes` filters can be evaluated per-event.
         self.stream_types
             .write()
             .unwrap()
-            .insert(stream_id.clone(), stream_type);
+            .insert(stream_id.clone(), stream_type.to_string());
 
         // Determine the starting version and check optimistic concurrency once, up front
         // (mirrors the Postgres "check expected_version at the head of the transaction").
@@ -208,13 +432,15 @@ impl EventStore for InMemoryEventStore {
         let mut staged: Vec<PersistedEvent<Value>> = Vec::new();
 
         while let Some(event) = domain_events.try_next().await? {
-            let id = Uuid::new_v4();
-            let created = Utc::now();
+            let id = self.id_generator.next_id();
+            let created = self.clock.now();
             let r#type = event.event_type();
             let version = last_version + 1;
             last_version = version;
+            let global_position = self.next_position();
 
             let data = serde_json::to_value(&event).map_err(crate::ser_error)?;
+            let event_metadata = versioned_metadata(&metadata, &event);
 
             // Notify the sink with the typed event as it is appended, instead of accumulating a
             // parallel `Vec<PersistedEvent<S::Event>>` to replay afterwards. The JSON-encoded
@@ -223,22 +449,24 @@ impl EventStore for InMemoryEventStore {
                 id,
                 data: event,
                 stream_id: stream_id.clone(),
-                r#type: r#type.clone(),
+                r#type: r#type.to_string(),
                 version,
                 created,
-                metadata: metadata.clone(),
+                metadata: event_metadata.clone(),
                 aggregate_version: None,
+                global_position,
             });
 
             staged.push(PersistedEvent {
                 id,
                 data,
                 stream_id: stream_id.clone(),
-                r#type,
+                r#type: r#type.to_string(),
                 version,
                 created,
-                metadata: metadata.clone(),
+                metadata: event_metadata,
                 aggregate_version: None,
+                global_position,
             });
         }
 
@@ -246,6 +474,23 @@ impl EventStore for InMemoryEventStore {
         // driving any async projections (the `RwLockWriteGuard` is not held across an await).
         {
             let mut store = self.events.write().unwrap();
+            // Like `append_events` in Postgres, the tenant of the append that creates a stream
+            // owns it, claimed under the lock that publishes the events.
+            let mut stream_tenants = self.stream_tenants.write().unwrap();
+            match metadata.tenant_id() {
+                Some(tenant) if !store.contains_key(&stream_id) => {
+                    stream_tenants.insert(stream_id.clone(), tenant.to_string());
+                }
+                Some(tenant)
+                    if stream_tenants.get(&stream_id).map(String::as_str) != Some(tenant) =>
+                {
+                    return Err(replay::Error::forbidden("stream belongs to another tenant")
+                        .with_operation("store_events")
+                        .with_context("stream_id", &stream_id)
+                        .with_context("tenant", tenant));
+                }
+                _ => {}
+            }
             let stream = store.entry(stream_id.clone()).or_default();
             stream.extend(staged.iter().cloned());
         }
@@ -258,6 +503,41 @@ impl EventStore for InMemoryEventStore {
 
         Ok(())
     }
+}
+
+impl EventStore for InMemoryEventStore {
+    async fn store_events_stream<S, ES, Sink>(
+        &self,
+        stream_id: &S::StreamId,
+        stream_type: &str,
+        metadata: replay::Metadata,
+        domain_events: ES,
+        expected_version: Option<i64>,
+        mut sink: Sink,
+    ) -> Result<(), replay::Error>
+    where
+        S: replay::EventStream,
+        ES: TryStream<Ok = S::Event, Error = replay::Error> + Send,
+        Sink: EventSink<S::Event> + Send,
+    {
+        let started = std::time::Instant::now();
+        let mut appended = 0;
+        let result = self
+            .append_stream::<S, _, _>(
+                stream_id,
+                stream_type,
+                metadata,
+                domain_events,
+                expected_version,
+                |event: &PersistedEvent<S::Event>| {
+                    appended += 1;
+                    sink.on_event(event);
+                },
+            )
+            .await;
+        crate::metrics::record_append(STORE, stream_type, appended, started.elapsed(), &result);
+        result
+    }
 
     fn stream_events<E: Event>(
         &self,
@@ -267,11 +547,34 @@ impl EventStore for InMemoryEventStore {
         let (candidate_events, stream_types): (Vec<PersistedEvent<Value>>, HashMap<Urn, String>) = {
             let store = self.events.read().unwrap();
             let stream_types = self.stream_types.read().unwrap().clone();
-            let events = if let Some(stream_id) = Self::extract_stream_id(&filter) {
-                store.get(&stream_id).cloned().unwrap_or_default()
+            let mut events = if let Some(stream_id) = filter.stream_id() {
+                store.get(stream_id).cloned().unwrap_or_default()
             } else {
-                store.values().flatten().cloned().collect()
+                // Append order, like the Postgres `ORDER BY global_position`.
+                let mut events: Vec<_> = store.values().flatten().cloned().collect();
+                events.sort_by_key(|event| event.global_position);
+                events
             };
+            if !filter.includes_deleted() {
+                let deleted = self.deleted.read().unwrap();
+                events.retain(|event| !deleted.contains(&event.stream_id));
+            }
+            let retention = self.retention.read().unwrap();
+            if !retention.is_empty() {
+                let retained_from: HashMap<&Urn, i64> = retention
+                    .iter()
+                    .filter_map(|(stream_id, retention)| {
+                        let stream = store.get(stream_id)?;
+                        Some((stream_id, Self::retained_from(stream, retention)))
+                    })
+                    .collect();
+                events.retain(|event| {
+                    event.aggregate_version.is_some()
+                        || retained_from
+                            .get(&event.stream_id)
+                            .is_none_or(|from| event.version >= *from)
+                });
+            }
             (events, stream_types)
         };
 
@@ -282,6 +585,7 @@ impl EventStore for InMemoryEventStore {
                     continue;
                 }
                 let data: E = serde_json::from_value(event.data).map_err(crate::deser_error)?;
+                crate::metrics::record_streamed(STORE);
                 yield Ok(PersistedEvent {
                     id: event.id,
                     data,
@@ -291,6 +595,7 @@ impl EventStore for InMemoryEventStore {
                     created: event.created,
                     metadata: event.metadata,
                     aggregate_version: event.aggregate_version,
+                    global_position: event.global_position,
                 });
             }
         }
@@ -304,7 +609,7 @@ impl EventStore for InMemoryEventStore {
     where
         A: replay::Aggregate + Compactable + Sync,
     {
-        let stream_id: Urn = aggregate.get_id().clone().into();
+        let stream_id = aggregate.get_id().stream_urn();
 
         // 1. Collect current live events and the stream head while holding the read
         //    lock (brief, sync). Deriving the watermark head from this same snapshot —
@@ -392,14 +697,15 @@ impl EventStore for InMemoryEventStore {
                 let seq = seq + 1;
                 let data = serde_json::to_value(event).map_err(crate::ser_error)?;
                 stream.push(PersistedEvent {
-                    id: Uuid::new_v4(),
+                    id: self.id_generator.next_id(),
                     data,
                     stream_id: stream_id.clone(),
-                    r#type: event.event_type(),
+                    r#type: event.event_type().to_string(),
                     version: seq,
-                    created: Utc::now(),
-                    metadata: metadata.clone(),
+                    created: self.clock.now(),
+                    metadata: versioned_metadata(&metadata, event),
                     aggregate_version: None,
+                    global_position: self.next_position(),
                 });
             }
 
@@ -416,7 +722,321 @@ impl EventStore for InMemoryEventStore {
         }
     }
 
-    async fn needs_compaction(&self, stream_id: &Urn) -> Result<bool, replay::Error> {
+    async fn compact_stream<S>(
+        &self,
+        stream_id: &S::StreamId,
+        up_to_version: i64,
+        metadata: replay::Metadata,
+    ) -> Result<u64, replay::Error>
+    where
+        S: Snapshottable,
+    {
+        let urn = stream_id.stream_urn();
+        let replaced =
+            |e: &PersistedEvent<Value>| e.aggregate_version.is_none() && e.version <= up_to_version;
+
+        // Fold and rewrite under one write lock, so no append lands in between.
+        let mut store = self.events.write().unwrap();
+        let Some(stream) = store.get_mut(&urn) else {
+            return Err(replay::Error::not_found("Stream not found")
+                .with_operation("compact_stream")
+                .with_context("stream_id", urn.to_string()));
+        };
+        let prefix: Vec<usize> = (0..stream.len())
+            .filter(|&i| replaced(&stream[i]))
+            .collect();
+        let (Some(&first), Some(&last), true) = (prefix.first(), prefix.last(), prefix.len() > 1)
+        else {
+            return Ok(0);
+        };
+
+        let mut state = S::with_id(stream_id.clone());
+        for &i in &prefix {
+            state
+                .apply(serde_json::from_value(stream[i].data.clone()).map_err(crate::deser_error)?);
+        }
+        let snapshot = state.snapshot_event();
+        let last = &stream[last];
+        let snapshot = PersistedEvent {
+            id: self.id_generator.next_id(),
+            data: serde_json::to_value(&snapshot).map_err(crate::ser_error)?,
+            stream_id: urn,
+            r#type: snapshot.event_type().to_string(),
+            version: last.version,
+            created: last.created,
+            metadata: versioned_metadata(&metadata, &snapshot),
+            aggregate_version: None,
+            global_position: last.global_position,
+        };
+
+        stream.retain(|e| !replaced(e));
+        stream.insert(first, snapshot);
+        Ok(prefix.len() as u64)
+    }
+
+    async fn delete_stream(
+        &self,
+        stream_id: &impl StreamId,
+        mode: DeletionMode,
+    ) -> Result<(), replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        let mut store = self.events.write().unwrap();
+        if !store.contains_key(stream_id) {
+            return Err(replay::Error::not_found("Stream not found")
+                .with_operation("delete_stream")
+                .with_context("stream_id", stream_id));
+        }
+
+        match mode {
+            DeletionMode::Soft => {
+                self.deleted.write().unwrap().insert(stream_id.clone());
+            }
+            DeletionMode::Hard => {
+                store.remove(stream_id);
+                self.stream_types.write().unwrap().remove(stream_id);
+                self.last_compacted_version
+                    .write()
+                    .unwrap()
+                    .remove(stream_id);
+                self.deleted.write().unwrap().remove(stream_id);
+                self.stream_tenants.write().unwrap().remove(stream_id);
+                self.retention.write().unwrap().remove(stream_id);
+                self.stream_metadata.write().unwrap().remove(stream_id);
+            }
+        }
+        Ok(())
+    }
+
+    async fn is_deleted(&self, stream_id: &impl StreamId) -> Result<bool, replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        Ok(self.deleted.read().unwrap().contains(stream_id))
+    }
+
+    async fn truncate_stream(
+        &self,
+        stream_id: &impl StreamId,
+        before_version: i64,
+    ) -> Result<u64, replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        let mut store = self.events.write().unwrap();
+        let Some(stream) = store.get_mut(stream_id) else {
+            return Err(replay::Error::not_found("Stream not found")
+                .with_operation("truncate_stream")
+                .with_context("stream_id", stream_id));
+        };
+        // Appends continue from the last live event, so it is never truncated.
+        let head = stream
+            .iter()
+            .rfind(|e| e.aggregate_version.is_none())
+            .map_or(0, |e| e.version);
+        let before_version = before_version.min(head);
+
+        let before = stream.len();
+        stream.retain(|e| e.aggregate_version.is_some() || e.version >= before_version);
+        Ok((before - stream.len()) as u64)
+    }
+
+    async fn set_stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+        retention: StreamRetention,
+    ) -> Result<(), replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        let store = self.events.read().unwrap();
+        if !store.contains_key(stream_id) {
+            return Err(replay::Error::not_found("Stream not found")
+                .with_operation("set_stream_retention")
+                .with_context("stream_id", stream_id));
+        }
+        let mut limits = self.retention.write().unwrap();
+        if retention.is_unlimited() {
+            limits.remove(stream_id);
+        } else {
+            limits.insert(stream_id.clone(), retention);
+        }
+        Ok(())
+    }
+
+    async fn stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> Result<StreamRetention, replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        let store = self.events.read().unwrap();
+        if !store.contains_key(stream_id) {
+            return Err(replay::Error::not_found("Stream not found")
+                .with_operation("stream_retention")
+                .with_context("stream_id", stream_id));
+        }
+        let limits = self.retention.read().unwrap();
+        Ok(limits.get(stream_id).copied().unwrap_or_default())
+    }
+
+    async fn enforce_stream_retention(&self) -> Result<u64, replay::Error> {
+        let mut store = self.events.write().unwrap();
+        let limits = self.retention.read().unwrap();
+        let mut removed = 0;
+        for (stream_id, retention) in limits.iter() {
+            let Some(stream) = store.get_mut(stream_id) else {
+                continue;
+            };
+            let retained_from = Self::retained_from(stream, retention);
+            let before = stream.len();
+            stream.retain(|e| e.aggregate_version.is_some() || e.version >= retained_from);
+            removed += (before - stream.len()) as u64;
+        }
+        Ok(removed)
+    }
+
+    async fn set_stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+        metadata: StreamMetadata,
+    ) -> Result<(), replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        let store = self.events.read().unwrap();
+        if !store.contains_key(stream_id) {
+            return Err(replay::Error::not_found("Stream not found")
+                .with_operation("set_stream_metadata")
+                .with_context("stream_id", stream_id));
+        }
+        let mut stream_metadata = self.stream_metadata.write().unwrap();
+        if metadata == StreamMetadata::default() {
+            stream_metadata.remove(stream_id);
+        } else {
+            stream_metadata.insert(stream_id.clone(), metadata);
+        }
+        Ok(())
+    }
+
+    async fn stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> Result<StreamMetadata, replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        let store = self.events.read().unwrap();
+        if !store.contains_key(stream_id) {
+            return Err(replay::Error::not_found("Stream not found")
+                .with_operation("stream_metadata")
+                .with_context("stream_id", stream_id));
+        }
+        let stream_metadata = self.stream_metadata.read().unwrap();
+        Ok(stream_metadata.get(stream_id).cloned().unwrap_or_default())
+    }
+
+    async fn list_streams(
+        &self,
+        stream_type: Option<&str>,
+        page: StreamPage,
+    ) -> Result<StreamListing, replay::Error> {
+        let store = self.events.read().unwrap();
+        let stream_types = self.stream_types.read().unwrap();
+        let deleted = self.deleted.read().unwrap();
+        let stream_metadata = self.stream_metadata.read().unwrap();
+        let no_metadata = StreamMetadata::default();
+        let after = page.cursor().map(ToString::to_string);
+        let mut streams: Vec<StreamSummary> = store
+            .iter()
+            .filter(|(stream_id, _)| !deleted.contains(*stream_id))
+            .filter(|(stream_id, _)| {
+                stream_metadata
+                    .get(*stream_id)
+                    .unwrap_or(&no_metadata)
+                    .has_labels(page.labels())
+            })
+            .filter(|(stream_id, _)| {
+                after
+                    .as_deref()
+                    .is_none_or(|after| stream_id.to_string().as_str() > after)
+            })
+            .filter_map(|(stream_id, events)| {
+                let recorded = stream_types.get(stream_id)?;
+                if stream_type.is_some_and(|stream_type| stream_type != recorded.as_str()) {
+                    return None;
+                }
+                Some(StreamSummary {
+                    stream_id: stream_id.clone(),
+                    stream_type: recorded.clone(),
+                    version: events
+                        .iter()
+                        .rfind(|e| e.aggregate_version.is_none())
+                        .map_or(0, |e| e.version),
+                    updated: events.iter().map(|e| e.created).max()?,
+                })
+            })
+            .collect();
+        streams.sort_by_key(|stream| stream.stream_id.to_string());
+        let more = streams.len() > page.limit();
+        streams.truncate(page.limit());
+        Ok(StreamListing {
+            next_page: page.next(&streams, more),
+            streams,
+        })
+    }
+
+    async fn stream_info(&self, stream_id: &impl StreamId) -> Result<StreamState, replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        let store = self.events.read().unwrap();
+        let Some(events) = store.get(stream_id) else {
+            return Ok(StreamState::default());
+        };
+        Ok(StreamState {
+            exists: true,
+            version: events
+                .iter()
+                .rfind(|e| e.aggregate_version.is_none())
+                .map_or(0, |e| e.version),
+            created: events.iter().map(|e| e.created).min(),
+            updated: events.iter().map(|e| e.created).max(),
+            deleted: self.deleted.read().unwrap().contains(stream_id),
+        })
+    }
+
+    async fn rename_stream(
+        &self,
+        old_id: &impl StreamId,
+        new_id: &impl StreamId,
+    ) -> Result<(), replay::Error> {
+        let old_id = &old_id.stream_urn();
+        let new_id = &new_id.stream_urn();
+        let mut store = self.events.write().unwrap();
+        if !store.contains_key(old_id) {
+            return Err(replay::Error::not_found("Stream not found")
+                .with_operation("rename_stream")
+                .with_context("stream_id", old_id));
+        }
+        if store.contains_key(new_id) {
+            return Err(replay::Error::conflict("target stream already exists")
+                .with_operation("rename_stream")
+                .with_context("stream_id", old_id)
+                .with_context("new_stream_id", new_id));
+        }
+        let mut events = store.remove(old_id).unwrap_or_default();
+        for event in &mut events {
+            event.stream_id = new_id.clone();
+        }
+        store.insert(new_id.clone(), events);
+
+        fn carry_over<V>(map: &RwLock<HashMap<Urn, V>>, old_id: &Urn, new_id: &Urn) {
+            let mut map = map.write().unwrap();
+            if let Some(value) = map.remove(old_id) {
+                map.insert(new_id.clone(), value);
+            }
+        }
+        carry_over(&self.stream_types, old_id, new_id);
+        carry_over(&self.last_compacted_version, old_id, new_id);
+        carry_over(&self.retention, old_id, new_id);
+        carry_over(&self.stream_metadata, old_id, new_id);
+        carry_over(&self.stream_tenants, old_id, new_id);
+        let mut deleted = self.deleted.write().unwrap();
+        if deleted.remove(old_id) {
+            deleted.insert(new_id.clone());
+        }
+        Ok(())
+    }
+
+    async fn needs_compaction(&self, stream_id: &impl StreamId) -> Result<bool, replay::Error> {
+        let stream_id = &stream_id.stream_urn();
         // Current live head version (max version among un-archived events), 0 if none.
         let head = {
             let store = self.events.read().unwrap();
@@ -441,6 +1061,20 @@ impl EventStore for InMemoryEventStore {
 
         Ok(head > watermark)
     }
+
+    async fn health_check(&self) -> Result<StoreHealth, replay::Error> {
+        // Nothing to reach; the store only breaks when a writer panicked holding its lock.
+        if self.events.is_poisoned() {
+            return Err(replay::Error::unavailable("in-memory store lock poisoned")
+                .with_operation("health_check"));
+        }
+        Ok(StoreHealth {
+            latency: Duration::ZERO,
+            connections: None,
+            idle_connections: None,
+            max_connections: None,
+        })
+    }
 }
 
 // tests
@@ -448,6 +1082,7 @@ impl EventStore for InMemoryEventStore {
 mod tests {
 
     use super::*;
+    use crate::AllEvents;
     use replay::{EventStream, WithId};
 
     use std::sync::atomic::{AtomicUsize, Ordering};
@@ -459,6 +1094,10 @@ mod tests {
     use serde::{Deserialize, Serialize};
     use urn::{Urn, UrnBuilder};
 
+    mod conformance {
+        crate::event_store_conformance_tests!(|| async { crate::InMemoryEventStore::new() });
+    }
+
     //  bank account stream (id of stream is not part of the model)
     struct BankAccountStream {
         pub id: BankAccountUrn,
@@ -468,8 +1107,13 @@ mod tests {
     // create bank account events enum: Deposited and Withdrawn
     #[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Event)]
     enum BankAccountEvent {
-        Deposited { amount: f64 },
-        Withdrawn { amount: f64 },
+        Deposited {
+            amount: f64,
+        },
+        #[event(version = 2)]
+        Withdrawn {
+            amount: f64,
+        },
     }
 
     // bank account urn
@@ -509,8 +1153,8 @@ mod tests {
     impl replay::EventStream for BankAccountStream {
         type Event = BankAccountEvent;
 
-        fn stream_type() -> String {
-            "BankAccount".to_string()
+        fn stream_type() -> &'static str {
+            "BankAccount"
         }
 
         fn apply(&mut self, event: Self::Event) {
@@ -589,8 +1233,8 @@ mod tests {
     impl replay::EventStream for SnapshotStream {
         type Event = SnapshotEvent;
 
-        fn stream_type() -> String {
-            "Snapshot".to_string()
+        fn stream_type() -> &'static str {
+            "Snapshot"
         }
 
         fn apply(&mut self, event: Self::Event) {
@@ -641,6 +1285,12 @@ mod tests {
         }
     }
 
+    impl Snapshottable for SnapshotStream {
+        fn snapshot_event(&self) -> SnapshotEvent {
+            SnapshotEvent::Snapshot { total: self.total }
+        }
+    }
+
     async fn add_snapshot_events(
         store: &InMemoryEventStore,
         id: &BankAccountUrn,
@@ -649,7 +1299,7 @@ mod tests {
         store
             .store_events::<SnapshotStream>(
                 id,
-                "Snapshot".to_string(),
+                "Snapshot",
                 replay::Metadata::default(),
                 events,
                 None,
@@ -687,7 +1337,7 @@ mod tests {
         store
             .store_events::<BankAccountStream>(
                 id,
-                "BankAccount".to_string(),
+                "BankAccount",
                 replay::Metadata::default(),
                 events,
                 None,
@@ -800,7 +1450,7 @@ mod tests {
         store
             .store_events::<BankAccountStream>(
                 &id,
-                "BankAccount".to_string(),
+                "BankAccount",
                 replay::Metadata::default(),
                 &[BankAccountEvent::Withdrawn { amount: 30.0 }],
                 None,
@@ -853,14 +1503,57 @@ mod tests {
         assert_eq!(err.kind(), replay::ErrorKind::NotFound);
     }
 
+    #[tokio::test]
+    async fn statistics_count_live_and_archived_events() {
+        let store = InMemoryEventStore::new();
+        assert_eq!(store.statistics(), StoreStatistics::default());
+
+        let compacted = make_stream_id("stats-compacted");
+        let untouched = make_stream_id("stats-untouched");
+        add_events(
+            &store,
+            &compacted,
+            &[
+                BankAccountEvent::Deposited { amount: 100.0 },
+                BankAccountEvent::Withdrawn { amount: 40.0 },
+            ],
+        )
+        .await;
+        add_events(
+            &store,
+            &untouched,
+            &[BankAccountEvent::Deposited { amount: 5.0 }],
+        )
+        .await;
+
+        let mut account = BankAccountStream::with_id(compacted.clone());
+        for ev in live_events(&store, &compacted).await {
+            account.apply(ev);
+        }
+        store
+            .compact(&account, replay::Metadata::default())
+            .await
+            .unwrap();
+
+        let statistics = store.statistics();
+        assert_eq!(statistics.streams, 2);
+        assert_eq!(statistics.events, 2);
+        assert_eq!(statistics.archived_events, 2);
+        assert_eq!(
+            statistics.events_by_stream_type,
+            [("BankAccount".to_string(), 2)].into()
+        );
+        assert_eq!(statistics.size_bytes, None);
+        assert!(statistics.oldest_event.unwrap() <= statistics.newest_event.unwrap());
+    }
+
     #[tokio::test]
     async fn needs_compaction_tracks_the_watermark() {
         let store = InMemoryEventStore::new();
         let id = make_stream_id("needs-compaction");
-        let urn: Urn = id.clone().into();
 
         // A stream that does not exist has nothing to compact.
-        assert!(!store.needs_compaction(&urn).await.unwrap());
+        assert!(!store.needs_compaction(&id).await.unwrap());
 
         // A never-compacted stream with events is eligible.
         add_events(
@@ -872,7 +1565,7 @@ mod tests {
             ],
         )
         .await;
-        assert!(store.needs_compaction(&urn).await.unwrap());
+        assert!(store.needs_compaction(&id).await.unwrap());
 
         // Compaction advances the watermark to the live head, so an unchanged stream
         // is skipped — and stays skipped on repeated reads with no intervening append.
@@ -884,12 +1577,12 @@ mod tests {
             .compact(&account, replay::Metadata::default())
             .await
             .unwrap();
-        assert!(!store.needs_compaction(&urn).await.unwrap());
-        assert!(!store.needs_compaction(&urn).await.unwrap());
+        assert!(!store.needs_compaction(&id).await.unwrap());
+        assert!(!store.needs_compaction(&id).await.unwrap());
 
         // A new append past the watermark makes it eligible again.
         add_events(&store, &id, &[BankAccountEvent::Deposited { amount: 10.0 }]).await;
-        assert!(store.needs_compaction(&urn).await.unwrap());
+        assert!(store.needs_compaction(&id).await.unwrap());
 
         // Compacting again settles it once more.
         let mut account2 = BankAccountStream::with_id(id.clone());
@@ -900,7 +1593,7 @@ mod tests {
             .compact(&account2, replay::Metadata::default())
             .await
             .unwrap();
-        assert!(!store.needs_compaction(&urn).await.unwrap());
+        assert!(!store.needs_compaction(&id).await.unwrap());
     }
 
     #[tokio::test]
@@ -910,7 +1603,6 @@ mod tests {
         // post-compaction count (1), not the pre-compaction version (4).
         let store = InMemoryEventStore::new();
         let id = make_stream_id("version-coincidence");
-        let urn: Urn = id.clone().into();
 
         add_events(
             &store,
@@ -928,7 +1620,7 @@ mod tests {
             4,
             "four live events — version 4"
         );
-        assert!(store.needs_compaction(&urn).await.unwrap());
+        assert!(store.needs_compaction(&id).await.unwrap());
 
         // Compact 4 → 1: the watermark becomes 1.
         let mut account = BankAccountStream::with_id(id.clone());
@@ -945,7 +1637,7 @@ mod tests {
             1,
             "compacted to a single event"
         );
-        assert!(!store.needs_compaction(&urn).await.unwrap());
+        assert!(!store.needs_compaction(&id).await.unwrap());
 
         // Three more events climb the live head version back to 4.
         add_events(
@@ -967,7 +1659,7 @@ mod tests {
         // The key assertion: version 4 equals the pre-compaction version, but the
         // watermark is 1, so the three new events are detected.
         assert!(
-            store.needs_compaction(&urn).await.unwrap(),
+            store.needs_compaction(&id).await.unwrap(),
             "version 4 is compared against watermark 1, not the stale pre-compaction 4"
         );
 
@@ -981,18 +1673,17 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(v2, CompactionOutcome::Compacted { archive_version: 2 });
-        assert!(!store.needs_compaction(&urn).await.unwrap());
+        assert!(!store.needs_compaction(&id).await.unwrap());
     }
 
     #[tokio::test]
     async fn already_compacted_skips_and_writes_nothing() {
         let store = InMemoryEventStore::new();
         let id = make_stream_id("already-compacted");
-        let urn: Urn = id.clone().into();
 
         // Born minimal: a single Snapshot event, never compacted.
         add_snapshot_events(&store, &id, &[SnapshotEvent::Snapshot { total: 5 }]).await;
-        assert!(store.needs_compaction(&urn).await.unwrap());
+        assert!(store.needs_compaction(&id).await.unwrap());
 
         // compact → the author reports AlreadyCompacted → nothing written.
         let account = SnapshotStream::with_id(id.clone());
@@ -1020,7 +1711,7 @@ mod tests {
 
         // The watermark advanced, so a re-check skips it, and a direct re-compaction
         // is still Skipped (fixpoint).
-        assert!(!store.needs_compaction(&urn).await.unwrap());
+        assert!(!store.needs_compaction(&id).await.unwrap());
         let outcome2 = store
             .compact(&account, replay::Metadata::default())
             .await
@@ -1032,7 +1723,6 @@ mod tests {
     async fn compact_folds_then_reports_already_compacted() {
         let store = InMemoryEventStore::new();
         let id = make_stream_id("fold-then-fixpoint");
-        let urn: Urn = id.clone().into();
 
         add_snapshot_events(&store, &id, &[SnapshotEvent::Bumped, SnapshotEvent::Bumped]).await;
 
@@ -1047,7 +1737,7 @@ mod tests {
             live_snapshot_events(&store, &id).await,
             vec![SnapshotEvent::Snapshot { total: 2 }]
         );
-        assert!(!store.needs_compaction(&urn).await.unwrap());
+        assert!(!store.needs_compaction(&id).await.unwrap());
 
         // The live stream is now a lone Snapshot, so a direct re-compaction is a
         // no-op skip — the guards do not over-fold a settled stream.
@@ -1058,6 +1748,361 @@ mod tests {
         assert_eq!(outcome2, CompactionOutcome::Skipped);
     }
 
+    #[tokio::test]
+    async fn compact_stream_replaces_a_prefix_keeping_later_versions() {
+        let store = InMemoryEventStore::new();
+        let id = make_stream_id("compact-prefix");
+        let bumps = [(); 4].map(|_| SnapshotEvent::Bumped);
+        add_snapshot_events(&store, &id, &bumps).await;
+
+        let replaced = store
+            .compact_stream::<SnapshotStream>(&id, 3, replay::Metadata::default())
+            .await
+            .unwrap();
+
+        assert_eq!(replaced, 3);
+        assert_eq!(
+            live_snapshot_events(&store, &id).await,
+            vec![SnapshotEvent::Snapshot { total: 3 }, SnapshotEvent::Bumped]
+        );
+        // Appends continue from the untouched head.
+        store
+            .store_events::<SnapshotStream>(
+                &id,
+                "Snapshot",
+                replay::Metadata::default(),
+                &[SnapshotEvent::Bumped],
+                Some(4),
+            )
+            .await
+            .unwrap();
+        // A lone event is left as it is.
+        let replaced = store
+            .compact_stream::<SnapshotStream>(&id, 3, replay::Metadata::default())
+            .await
+            .unwrap();
+        assert_eq!(replaced, 0);
+    }
+
+    #[tokio::test]
+    async fn truncate_stream_keeps_the_head_and_later_appends_continue() {
+        let store = InMemoryEventStore::new();
+        let id = make_stream_id("truncate");
+        add_events(
+            &store,
+            &id,
+            &[
+                BankAccountEvent::Deposited { amount: 10.0 },
+                BankAccountEvent::Deposited { amount: 20.0 },
+                BankAccountEvent::Deposited { amount: 30.0 },
+            ],
+        )
+        .await;
+
+        assert_eq!(store.truncate_stream(&id, 10).await.unwrap(), 2);
+        store
+            .store_events::<BankAccountStream>(
+                &id,
+                "BankAccount",
+                replay::Metadata::default(),
+                &[BankAccountEvent::Withdrawn { amount: 5.0 }],
+                Some(3),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            live_events(&store, &id).await,
+            [
+                BankAccountEvent::Deposited { amount: 30.0 },
+                BankAccountEvent::Withdrawn { amount: 5.0 },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_retention_hides_then_deletes_events_past_max_count() {
+        let store = InMemoryEventStore::new();
+        let id = make_stream_id("retention");
+        let amounts = [10.0, 20.0, 30.0, 40.0];
+        let events = amounts.map(|amount| BankAccountEvent::Deposited { amount });
+        add_events(&store, &id, &events).await;
+        let retention = StreamRetention::default().with_max_count(2);
+
+        store.set_stream_retention(&id, retention).await.unwrap();
+
+        assert_eq!(store.stream_retention(&id).await.unwrap(), retention);
+        assert_eq!(live_events(&store, &id).await, events[2..]);
+        assert_eq!(store.enforce_stream_retention().await.unwrap(), 2);
+        assert_eq!(store.enforce_stream_retention().await.unwrap(), 0);
+        assert_eq!(live_events(&store, &id).await, events[2..]);
+    }
+
+    #[tokio::test]
+    async fn list_streams_pages_through_streams_in_id_order() {
+        let store = InMemoryEventStore::new();
+        for n in ["c", "a", "b"] {
+            add_events(
+                &store,
+                &make_stream_id(n),
+                &[BankAccountEvent::Deposited { amount: 1.0 }],
+            )
+            .await;
+        }
+
+        let first = store
+            .list_streams(Some("BankAccount"), StreamPage::first(2))
+            .await
+            .unwrap();
+        let next_page = first.next_page.clone().expect("a second page");
+        let second = store.list_streams(None, next_page).await.unwrap();
+
+        let ids = |listing: &StreamListing| -> Vec<String> {
+            listing
+                .streams
+                .iter()
+                .map(|stream| stream.stream_id.to_string())
+                .collect()
+        };
+        assert_eq!(ids(&first), ["urn:bank-account:a", "urn:bank-account:b"]);
+        assert_eq!(ids(&second), ["urn:bank-account:c"]);
+        assert_eq!(second.next_page, None);
+        assert_eq!(second.streams[0].version, 1);
+        assert!(store
+            .list_streams(Some("Other"), StreamPage::first(2))
+            .await
+            .unwrap()
+            .streams
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn stream_metadata_is_kept_and_selects_streams_by_label() {
+        let store = InMemoryEventStore::new();
+        for n in ["eu-1", "eu-2", "us-1"] {
+            add_events(
+                &store,
+                &make_stream_id(n),
+                &[BankAccountEvent::Deposited { amount: 1.0 }],
+            )
+            .await;
+        }
+        let missing = make_stream_id("missing");
+        let error = store
+            .set_stream_metadata(&missing, StreamMetadata::default())
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), replay::ErrorKind::NotFound);
+
+        for n in ["eu-1", "eu-2"] {
+            let metadata = StreamMetadata::default()
+                .with_owner("ada")
+                .with_acl("read", ["team-billing"])
+                .with_label("region", "eu");
+            store
+                .set_stream_metadata(&make_stream_id(n), metadata)
+                .await
+                .unwrap();
+        }
+        let metadata = store
+            .stream_metadata(&make_stream_id("eu-1"))
+            .await
+            .unwrap();
+        assert!(metadata.allows("team-billing", "read"));
+        assert!(!metadata.allows("team-billing", "write"));
+        assert_eq!(
+            store
+                .stream_metadata(&make_stream_id("us-1"))
+                .await
+                .unwrap(),
+            StreamMetadata::default()
+        );
+
+        let first = store
+            .list_streams(None, StreamPage::first(1).with_label("region", "eu"))
+            .await
+            .unwrap();
+        let next_page = first.next_page.expect("a second page");
+        assert_eq!(
+            next_page.labels().get("region").map(String::as_str),
+            Some("eu")
+        );
+        let second = store.list_streams(None, next_page).await.unwrap();
+        assert_eq!(
+            first.streams[0].stream_id.to_string(),
+            "urn:bank-account:eu-1"
+        );
+        assert_eq!(
+            second.streams[0].stream_id.to_string(),
+            "urn:bank-account:eu-2"
+        );
+        assert_eq!(second.next_page, None);
+    }
+
+    #[tokio::test]
+    async fn read_all_pages_through_every_stream_in_append_order() {
+        let store = InMemoryEventStore::new();
+        for n in ["x", "y", "x"] {
+            add_events(
+                &store,
+                &make_stream_id(n),
+                &[BankAccountEvent::Deposited { amount: 1.0 }],
+            )
+            .await;
+        }
+
+        let first = store.read_all::<BankAccountEvent>(0, 2).await.unwrap();
+        let second = store
+            .read_all::<BankAccountEvent>(first.next_position, 2)
+            .await
+            .unwrap();
+        let positions = |page: &AllEvents<BankAccountEvent>| -> Vec<(i64, String)> {
+            page.events
+                .iter()
+                .map(|event| (event.global_position, event.stream_id.to_string()))
+                .collect()
+        };
+        assert_eq!(
+            positions(&first),
+            [
+                (1, "urn:bank-account:x".into()),
+                (2, "urn:bank-account:y".into())
+            ]
+        );
+        assert_eq!(positions(&second), [(3, "urn:bank-account:x".into())]);
+
+        let caught_up = store
+            .read_all::<BankAccountEvent>(second.next_position, 2)
+            .await
+            .unwrap();
+        assert!(caught_up.events.is_empty());
+        assert_eq!(caught_up.next_position, second.next_position);
+    }
+
+    #[tokio::test]
+    async fn stream_info_describes_missing_live_and_deleted_streams() {
+        let store = InMemoryEventStore::new();
+        let id = make_stream_id("info");
+        assert_eq!(
+            store.stream_info(&id).await.unwrap(),
+            StreamState::default()
+        );
+
+        add_events(
+            &store,
+            &id,
+            &[
+                BankAccountEvent::Deposited { amount: 1.0 },
+                BankAccountEvent::Deposited { amount: 2.0 },
+            ],
+        )
+        .await;
+        let state = store.stream_info(&id).await.unwrap();
+        assert!(state.exists && !state.deleted);
+        assert_eq!(state.version, 2);
+        assert!(state.created <= state.updated);
+
+        store.delete_stream(&id, DeletionMode::Soft).await.unwrap();
+        assert!(store.stream_info(&id).await.unwrap().deleted);
+    }
+
+    #[tokio::test]
+    async fn appending_to_a_soft_deleted_stream_is_not_found() {
+        let store = InMemoryEventStore::new();
+        let id = make_stream_id("closed");
+        let deposit = BankAccountEvent::Deposited { amount: 1.0 };
+        add_events(&store, &id, std::slice::from_ref(&deposit)).await;
+        store.delete_stream(&id, DeletionMode::Soft).await.unwrap();
+
+        let err = store
+            .store_events::<BankAccountStream>(
+                &id,
+                "BankAccount",
+                replay::Metadata::default(),
+                std::slice::from_ref(&deposit),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), replay::ErrorKind::NotFound);
+
+        store.delete_stream(&id, DeletionMode::Hard).await.unwrap();
+        add_events(&store, &id, std::slice::from_ref(&deposit)).await;
+        assert_eq!(live_events(&store, &id).await, [deposit]);
+    }
+
+    #[tokio::test]
+    async fn the_tenant_of_the_first_append_owns_the_stream() {
+        let store = InMemoryEventStore::new();
+        let id = make_stream_id("tenanted");
+        let deposit = BankAccountEvent::Deposited { amount: 1.0 };
+        let append = |tenant: Option<&str>| {
+            let metadata = match tenant {
+                Some(tenant) => replay::Metadata::default().with_tenant_id(tenant),
+                None => replay::Metadata::default(),
+            };
+            store.store_events::<BankAccountStream>(
+                &id,
+                "BankAccount",
+                metadata,
+                std::slice::from_ref(&deposit),
+                None,
+            )
+        };
+
+        append(Some("acme")).await.unwrap();
+        append(Some("acme")).await.unwrap();
+        let err = append(Some("globex")).await.unwrap_err();
+        assert_eq!(err.kind(), replay::ErrorKind::Forbidden);
+        append(None).await.unwrap();
+
+        let untenanted = make_stream_id("untenanted");
+        add_events(&store, &untenanted, std::slice::from_ref(&deposit)).await;
+        let err = store
+            .store_events::<BankAccountStream>(
+                &untenanted,
+                "BankAccount",
+                replay::Metadata::default().with_tenant_id("acme"),
+                &[deposit],
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), replay::ErrorKind::Forbidden);
+    }
+
+    #[tokio::test]
+    async fn rename_stream_moves_events_and_appends_continue_under_the_new_id() {
+        let store = InMemoryEventStore::new();
+        let old_id = make_stream_id("old-number");
+        let new_id = make_stream_id("new-number");
+        let taken_id = make_stream_id("taken-number");
+        let deposit = BankAccountEvent::Deposited { amount: 10.0 };
+        add_events(&store, &old_id, std::slice::from_ref(&deposit)).await;
+        add_events(&store, &taken_id, std::slice::from_ref(&deposit)).await;
+
+        let taken = store.rename_stream(&old_id, &taken_id).await.unwrap_err();
+        assert_eq!(taken.kind(), replay::ErrorKind::Conflict);
+        store.rename_stream(&old_id, &new_id).await.unwrap();
+
+        assert!(!store.stream_info(&old_id).await.unwrap().exists);
+        store
+            .store_events::<BankAccountStream>(
+                &new_id,
+                "BankAccount",
+                replay::Metadata::default(),
+                &[BankAccountEvent::Withdrawn { amount: 5.0 }],
+                Some(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            live_events(&store, &new_id).await,
+            [deposit, BankAccountEvent::Withdrawn { amount: 5.0 }]
+        );
+        let missing = store.rename_stream(&old_id, &new_id).await.unwrap_err();
+        assert_eq!(missing.kind(), replay::ErrorKind::NotFound);
+    }
+
     #[tokio::test]
     async fn empty_rewrite_archives_to_empty_distinct_from_skip() {
         let store = InMemoryEventStore::new();
@@ -1101,7 +2146,7 @@ mod tests {
         store
             .store_events::<BankAccountStream>(
                 &stream_id,
-                "BankAccount".to_string(),
+                "BankAccount",
                 replay::Metadata::default(),
                 &events,
                 None,
@@ -1142,7 +2187,7 @@ mod tests {
         store
             .store_events_stream::<BankAccountStream, _, _>(
                 &stream_id,
-                "BankAccount".to_string(),
+                "BankAccount",
                 replay::Metadata::default(),
                 futures::stream::iter(events.clone().into_iter().map(Ok::<_, replay::Error>)),
                 None,
@@ -1162,6 +2207,87 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn appended_events_take_ids_from_the_id_generator() {
+        let store = InMemoryEventStore::new().with_id_generator(crate::SequentialIds::default());
+        let stream_id = make_stream_id("sequential-ids");
+
+        for amount in [10.0, 20.0] {
+            store
+                .store_events::<BankAccountStream>(
+                    &stream_id,
+                    "BankAccount",
+                    replay::Metadata::default(),
+                    &[BankAccountEvent::Deposited { amount }],
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+
+        let ids = store
+            .stream_events::<BankAccountEvent>(StreamFilter::with_stream_id::<BankAccountStream>(
+                &stream_id,
+            ))
+            .map_ok(|event| event.id)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            ids,
+            vec![uuid::Uuid::from_u128(1), uuid::Uuid::from_u128(2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn deterministic_store_reproduces_ids_timestamps_and_order() {
+        async fn run() -> Vec<(uuid::Uuid, chrono::DateTime<chrono::Utc>, String, i64)> {
+            let store = InMemoryEventStore::deterministic();
+            let streams: Vec<_> = (1..=4)
+                .map(|n| make_stream_id(&format!("deterministic-{n}")))
+                .collect();
+            for round in 0..2 {
+                for stream_id in &streams {
+                    store
+                        .store_events::<BankAccountStream>(
+                            stream_id,
+                            "BankAccount",
+                            replay::Metadata::default(),
+                            &[BankAccountEvent::Deposited {
+                                amount: f64::from(round),
+                            }],
+                            None,
+                        )
+                        .await
+                        .unwrap();
+                }
+            }
+
+            store
+                .stream_events::<BankAccountEvent>(StreamFilter::all())
+                .map_ok(|e| (e.id, e.created, e.stream_id.to_string(), e.version))
+                .try_collect()
+                .await
+                .unwrap()
+        }
+
+        let first = run().await;
+        assert_eq!(first, run().await);
+
+        let start = chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2024, 1, 1, 0, 0, 0).unwrap();
+        let expected: Vec<_> = (0..8)
+            .map(|n| {
+                (
+                    uuid::Uuid::from_u128(n as u128 + 1),
+                    start + chrono::Duration::seconds(n),
+                    format!("urn:bank-account:deterministic-{}", n % 4 + 1),
+                    n / 4 + 1,
+                )
+            })
+            .collect();
+        assert_eq!(first, expected);
+    }
+
     #[tokio::test]
     async fn store_events_stream_multi_event_with_expected_version_succeeds() {
         // Parity with the Postgres regression guard: a multi-event append with a correct
@@ -1178,7 +2304,7 @@ mod tests {
         store
             .store_events_stream::<BankAccountStream, _, _>(
                 &stream_id,
-                "BankAccount".to_string(),
+                "BankAccount",
                 replay::Metadata::default(),
                 futures::stream::iter(events.into_iter().map(Ok::<_, replay::Error>)),
                 Some(0),
@@ -1199,7 +2325,7 @@ mod tests {
         store
             .store_events_stream::<BankAccountStream, _, _>(
                 &stream_id,
-                "BankAccount".to_string(),
+                "BankAccount",
                 replay::Metadata::default(),
                 futures::stream::iter(
                     vec![Ok::<_, replay::Error>(BankAccountEvent::Deposited {
@@ -1217,7 +2343,7 @@ mod tests {
         let result = store
             .store_events_stream::<BankAccountStream, _, _>(
                 &stream_id,
-                "BankAccount".to_string(),
+                "BankAccount",
                 replay::Metadata::default(),
                 futures::stream::iter(
                     vec![Ok::<_, replay::Error>(BankAccountEvent::Deposited {
@@ -1255,7 +2381,7 @@ mod tests {
         let result = store
             .store_events_stream::<BankAccountStream, _, _>(
                 &stream_id,
-                "BankAccount".to_string(),
+                "BankAccount",
                 replay::Metadata::default(),
                 futures::stream::iter(vec![
                     Ok(BankAccountEvent::Deposited { amount: 100.0 }),
@@ -1295,7 +2421,7 @@ mod tests {
         store
             .store_events::<BankAccountStream>(
                 &checking,
-                "Checking".to_string(),
+                "Checking",
                 replay::Metadata::default(),
                 &[BankAccountEvent::Deposited { amount: 100.0 }],
                 None,
@@ -1305,7 +2431,7 @@ mod tests {
         store
             .store_events::<BankAccountStream>(
                 &savings,
-                "Savings".to_string(),
+                "Savings",
                 replay::Metadata::default(),
                 &[BankAccountEvent::Deposited { amount: 50.0 }],
                 None,
@@ -1454,7 +2580,7 @@ mod tests {
         store
             .store_events::<BankAccountStream>(
                 &stream_id,
-                "bank-account".to_string(),
+                "bank-account",
                 replay::Metadata::default(),
                 &[
                     BankAccountEvent::Deposited { amount: 100.0 },
@@ -1485,7 +2611,7 @@ mod tests {
         store
             .store_events::<BankAccountStream>(
                 &stream_id,
-                "bank-account".to_string(),
+                "bank-account",
                 replay::Metadata::default(),
                 &[BankAccountEvent::Deposited { amount: 100.0 }],
                 None,
@@ -1515,7 +2641,7 @@ mod tests {
         store
             .store_events::<BankAccountStream>(
                 &stream_id,
-                "bank-account".to_string(),
+                "bank-account",
                 replay::Metadata::default(),
                 &[BankAccountEvent::Deposited { amount: 10.0 }],
                 None,
@@ -1526,7 +2652,7 @@ mod tests {
         store
             .store_events::<BankAccountStream>(
                 &stream_id,
-                "bank-account".to_string(),
+                "bank-account",
                 replay::Metadata::default(),
                 &[BankAccountEvent::Deposited { amount: 5.0 }],
                 Some(1),
@@ -1537,4 +2663,122 @@ mod tests {
         assert_eq!(*deposits.lock().unwrap(), vec![10.0, 5.0]);
         assert_eq!(handle_calls.load(Ordering::SeqCst), 2);
     }
+
+    #[tokio::test]
+    async fn stream_events_with_metadata_deserializes_each_event() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Audit {
+            actor: String,
+        }
+
+        let store = InMemoryEventStore::new();
+        let id = make_stream_id("typed-metadata");
+        store
+            .store_events::<BankAccountStream>(
+                &id,
+                "BankAccount",
+                replay::Metadata::default().with_actor("user:alice"),
+                &[BankAccountEvent::Deposited { amount: 1.0 }],
+                None,
+            )
+            .await
+            .unwrap();
+
+        let events: Vec<PersistedEvent<BankAccountEvent, Audit>> = store
+            .stream_events_with_metadata(StreamFilter::with_stream_id::<BankAccountStream>(&id))
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            events[0].metadata,
+            Audit {
+                actor: "user:alice".to_string()
+            }
+        );
+
+        add_events(&store, &id, &[BankAccountEvent::Withdrawn { amount: 1.0 }]).await;
+        let err = store
+            .stream_events_with_metadata::<BankAccountEvent, Audit>(StreamFilter::with_stream_id::<
+                BankAccountStream,
+            >(&id))
+            .try_collect::<Vec<_>>()
+            .await
+            .expect_err("an event without an actor doesn't fit Audit");
+        assert_eq!(err.kind(), replay::ErrorKind::Internal);
+    }
+
+    #[tokio::test]
+    async fn event_versions_are_recorded_per_event() {
+        let store = InMemoryEventStore::new();
+        let id = make_stream_id("versioned");
+        store
+            .store_events::<BankAccountStream>(
+                &id,
+                "BankAccount",
+                replay::Metadata::default().with_actor("user:alice"),
+                &[
+                    BankAccountEvent::Deposited { amount: 2.0 },
+                    BankAccountEvent::Withdrawn { amount: 1.0 },
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+
+        let events: Vec<PersistedEvent<BankAccountEvent>> = store
+            .stream_events(StreamFilter::with_stream_id::<BankAccountStream>(&id))
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(events[0].event_version(), 1);
+        assert!(!events[0]
+            .metadata
+            .contains_key(replay::Metadata::EVENT_VERSION_KEY));
+        assert_eq!(events[1].event_version(), 2);
+        assert_eq!(events[1].metadata.actor(), Some("user:alice"));
+    }
+
+    #[tokio::test]
+    async fn global_positions_order_reads_across_streams() {
+        let store = InMemoryEventStore::new();
+        let first = make_stream_id("position-1");
+        let second = make_stream_id("position-2");
+        add_events(
+            &store,
+            &first,
+            &[BankAccountEvent::Deposited { amount: 1.0 }],
+        )
+        .await;
+        add_events(
+            &store,
+            &second,
+            &[BankAccountEvent::Deposited { amount: 2.0 }],
+        )
+        .await;
+        add_events(
+            &store,
+            &first,
+            &[BankAccountEvent::Withdrawn { amount: 1.0 }],
+        )
+        .await;
+
+        let events: Vec<PersistedEvent<BankAccountEvent>> = store
+            .stream_events(StreamFilter::all())
+            .try_collect()
+            .await
+            .unwrap();
+        let positions: Vec<_> = events.iter().map(|e| e.global_position).collect();
+        assert_eq!(positions, vec![1, 2, 3]);
+
+        let tail: Vec<PersistedEvent<BankAccountEvent>> = store
+            .stream_events(StreamFilter::after_global_position(1))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(tail.len(), 2);
+        assert_eq!(tail[0].stream_id, Urn::from(second));
+        assert_eq!(tail[1].data, BankAccountEvent::Withdrawn { amount: 1.0 });
+    }
 }
@@ -1,5 +1,7 @@
 mod in_memory_store;
 mod postgres;
+mod postgres_copy;
 
 pub use in_memory_store::InMemoryEventStore;
+pub(crate) use postgres::event_from_row;
 pub use postgres::{PostgresEventStore, PostgresInlineProjection};
@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use futures::future::BoxFuture;
 use futures::{StreamExt, TryStream, TryStreamExt};
@@ -10,13 +11,29 @@ use sqlx::{
     Pool, Postgres, QueryBuilder, Row,
 };
 use tokio::sync::Mutex;
+use tracing::Instrument;
 
 use urn::Urn;
 use uuid::Uuid;
 
+use super::postgres_copy::{CopyEncoder, EVENT_COLUMNS};
+#[cfg(feature = "compression")]
+use crate::compression::{self, Compression};
+use crate::credentials::{self, CredentialRefresh};
+use crate::encryption::DataKeys;
+use crate::error::default_db_error_mapper;
+use crate::id_generator::{default_id_generator, SharedIdGenerator};
 use crate::inline_projection::{ErasedInlineProjection, InlineProjection};
-use crate::{CompactionOutcome, EventSink, EventStore, PersistedEvent, StreamFilter};
-use replay::{Compactable, Event, Metadata};
+use crate::persisted_event::versioned_metadata;
+use crate::serializer::{self, default_serializer, SharedSerializer};
+use crate::signing;
+use crate::{
+    AllEvents, CompactionOutcome, DataKey, DbErrorMapper, DeletionMode, Encryption,
+    EventSerializer, EventSigner, EventSink, EventStore, IdGenerator, JsonSerializer,
+    PersistedEvent, ReadOptions, StoreHealth, StoreStatistics, StreamFilter, StreamListing,
+    StreamMetadata, StreamPage, StreamRetention, StreamState, StreamSummary, StreamVerification,
+};
+use replay::{Compactable, ErrorKind, Event, Metadata, Snapshottable, StreamId};
 
 /// Convenience marker trait for inline projections that run on Postgres.
 ///
@@ -76,11 +93,82 @@ where
 /// `handle`/`init` methods can be driven through the shared (`&self`) store.
 type RegisteredProjection = Mutex<Box<dyn ErasedInlineProjection<Exec = sqlx::PgConnection>>>;
 
+/// Most events written by one `append_events` call; longer appends are split into batches
+/// of this size within the same transaction.
+const APPEND_BATCH_SIZE: usize = 1_000;
+
+/// How a producer's batch ended, see [`next_batch`].
+enum BatchEnd {
+    /// The batch holds [`APPEND_BATCH_SIZE`] events; more may follow.
+    Full,
+    /// The producer is done.
+    Exhausted,
+    /// The producer failed after the events in the batch.
+    Failed(replay::Error),
+}
+
+/// Move events from `events` into `batch` until it is full or the producer ends.
+async fn next_batch<E>(
+    events: &mut (impl futures::Stream<Item = Result<E, replay::Error>> + Unpin),
+    batch: &mut Vec<E>,
+) -> BatchEnd {
+    while batch.len() < APPEND_BATCH_SIZE {
+        match events.next().await {
+            Some(Ok(event)) => batch.push(event),
+            Some(Err(e)) => return BatchEnd::Failed(e),
+            None => return BatchEnd::Exhausted,
+        }
+    }
+    BatchEnd::Full
+}
+
+/// Bytes of `COPY` data buffered before they are sent by [`PostgresEventStore::bulk_import`].
+const COPY_CHUNK_BYTES: usize = 1 << 20;
+
+/// Each event's stream data key, selected next to the event when the store encrypts.
+const DATA_KEY_COLUMN: &str =
+    ", (SELECT key FROM stream_keys WHERE stream_keys.stream_id = events.stream_id) AS data_key";
+
+/// Each event's signature, selected next to the event when the store signs.
+const SIGNATURE_COLUMN: &str = ", signature";
+
+/// The `store` label of this store's [metrics](crate::metrics).
+const STORE: &str = "postgres";
+
+/// How long [`EventStore::health_check`] waits for the database before reporting it
+/// unavailable. Shorter than the pool's acquire timeout, so a probe answers while the pool is
+/// exhausted or the database is down.
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
 pub struct PostgresEventStore {
     pool: Pool<Postgres>,
     /// Builder-fixed, immutable set of inline projections. The `Vec` itself never
     /// changes after `build()`; each projection is individually locked while applied.
     projections: Arc<Vec<RegisteredProjection>>,
+    /// Translates driver errors into [`replay::Error`]; defaults to [`crate::db_error`].
+    db_error_mapper: DbErrorMapper,
+    /// Ids for appended events; [`UuidV7`](crate::UuidV7) unless replaced.
+    id_generator: SharedIdGenerator,
+    /// Tuning for `stream_events`.
+    read_options: ReadOptions,
+    /// Payload format for appends; [`JsonSerializer`](crate::JsonSerializer) unless replaced.
+    serializer: SharedSerializer,
+    /// Compression of large payloads on append; off unless installed.
+    #[cfg(feature = "compression")]
+    compression: Option<Compression>,
+    /// Appends and reads slower than this are logged as warnings; off unless set.
+    slow_operation_threshold: Option<Duration>,
+    /// Encryption of payloads with per-stream data keys; off unless installed.
+    encryption: Option<Encryption>,
+    /// Signs appended events and verifies them on read; off unless installed.
+    signer: Option<Arc<dyn EventSigner>>,
+    /// Set `app.tenant_id` for appends, reads and compactions; off unless enabled.
+    row_level_security: bool,
+    /// Read correlation ids through their link streams; off unless enabled.
+    correlation_links: bool,
+    /// Refreshes the pool's credentials when the database rejects them; set by
+    /// [`connect`](Self::connect) with a credential provider.
+    credentials: Option<Arc<CredentialRefresh>>,
 }
 
 impl PostgresEventStore {
@@ -88,6 +176,46 @@ impl PostgresEventStore {
         PostgresEventStore {
             pool,
             projections: Arc::new(Vec::new()),
+            db_error_mapper: default_db_error_mapper(),
+            id_generator: default_id_generator(),
+            read_options: ReadOptions::default(),
+            serializer: default_serializer(),
+            #[cfg(feature = "compression")]
+            compression: None,
+            slow_operation_threshold: None,
+            encryption: None,
+            signer: None,
+            row_level_security: false,
+            correlation_links: false,
+            credentials: None,
+        }
+    }
+
+    /// Open a connection pool tuned by `options` and begin configuring a store on it.
+    ///
+    /// With a [credential provider](crate::PostgresPoolOptions::with_credential_provider), the
+    /// store fetches fresh credentials whenever the database rejects a login. The operation
+    /// that hit the rejection fails as `Unavailable`, so a retry picks up the new credentials.
+    ///
+    /// ```rust,ignore
+    /// let options = PostgresPoolOptions::new(&database_url)?.with_max_connections(32);
+    /// let store = PostgresEventStore::connect(options).await?.build().await?;
+    /// ```
+    pub async fn connect(
+        options: crate::PostgresPoolOptions,
+    ) -> Result<PostgresEventStoreBuilder, replay::Error> {
+        let pool = options.connect().await?;
+        let mut builder = Self::builder(pool.clone());
+        builder.credentials = options.credential_refresh(&pool).map(Arc::new);
+        Ok(builder)
+    }
+
+    /// Fetch fresh credentials from the pool's provider for the connections opened from now
+    /// on, e.g. ahead of a known expiry. Does nothing for a store without a provider.
+    pub async fn refresh_credentials(&self) -> Result<(), replay::Error> {
+        match &self.credentials {
+            Some(credentials) => credentials.refresh().await,
+            None => Ok(()),
         }
     }
 
@@ -99,7 +227,178 @@ impl PostgresEventStore {
         PostgresEventStoreBuilder {
             pool,
             projections: Vec::new(),
+            db_error_mapper: default_db_error_mapper(),
+            id_generator: default_id_generator(),
+            read_options: ReadOptions::default(),
+            serializer: default_serializer(),
+            #[cfg(feature = "compression")]
+            compression: None,
+            slow_operation_threshold: None,
+            encryption: None,
+            signer: None,
+            row_level_security: false,
+            correlation_links: false,
+            credentials: None,
+        }
+    }
+
+    /// Replace the mapping from [`sqlx::Error`] to [`replay::Error`] used by every store
+    /// operation.
+    ///
+    /// The default, [`crate::db_error`], already treats connection failures as
+    /// `Unavailable` and serialization failures as `Conflict`; install a mapper to
+    /// reclassify driver errors for your deployment (e.g. a pooler's custom SQLSTATEs),
+    /// falling back to [`crate::db_error`] for everything else.
+    pub fn with_db_error_mapper<F>(mut self, mapper: F) -> Self
+    where
+        F: Fn(sqlx::Error) -> replay::Error + Send + Sync + 'static,
+    {
+        self.db_error_mapper = Arc::new(mapper);
+        self
+    }
+
+    /// Replace the source of event ids (time-ordered UUIDv7 by default).
+    pub fn with_id_generator(mut self, id_generator: impl IdGenerator + 'static) -> Self {
+        self.id_generator = Arc::new(id_generator);
+        self
+    }
+
+    /// Replace the default tuning of [`stream_events`](EventStore::stream_events).
+    pub fn with_read_options(mut self, read_options: ReadOptions) -> Self {
+        self.read_options = read_options;
+        self
+    }
+
+    /// Write payloads with `serializer` instead of as `jsonb` (see [`EventSerializer`]).
+    pub fn with_serializer(mut self, serializer: impl EventSerializer + 'static) -> Self {
+        self.serializer = Arc::new(serializer);
+        self
+    }
+
+    /// Compress large payloads on append (see [`Compression`]). Reads decode compressed and
+    /// plain events alike, whatever this store's setting.
+    #[cfg(feature = "compression")]
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Encrypt payloads, or some of their fields, with per-stream data keys (see
+    /// [`Encryption`]). Needs the `0018_stream_keys` migration.
+    ///
+    /// Reads decrypt whatever is encrypted, so existing plaintext events stay readable and a
+    /// store can start encrypting at any time. [`bulk_import`](Self::bulk_import) is refused
+    /// while encryption is on, since it writes payloads as given.
+    pub fn with_encryption(mut self, encryption: Encryption) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+
+    /// Sign every appended event with `signer` and reject events whose signature doesn't
+    /// match on read (see [`EventSigner`]). Needs the `0020_event_signatures` migration.
+    ///
+    /// Events written before signing was enabled have no signature and still read; they
+    /// show up as unsigned in [`verify_stream`](Self::verify_stream). Appends run in a
+    /// transaction, which signs the events once the database has given them their versions,
+    /// and [`bulk_import`](Self::bulk_import) is refused.
+    pub fn with_signer(mut self, signer: impl EventSigner + 'static) -> Self {
+        self.signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// Run appends, reads and compactions with the `app.tenant_id` setting that the
+    /// `0021_row_level_security` and `0029_row_level_security_streams` policies check, so the
+    /// database itself keeps tenants apart.
+    ///
+    /// The tenant comes from the metadata of appends and compactions, which must name one,
+    /// and from the [`StreamFilter::with_tenant_id`] of reads; a read without one sees no
+    /// events. [`TenantScopedEventStore`](crate::TenantScopedEventStore) provides both. The
+    /// policies only bind roles that are neither the tables' owner nor `BYPASSRLS`, so connect
+    /// this store as such a role, and run migrations, policy runners and maintenance
+    /// (statistics, imports, audits) as another.
+    pub fn with_row_level_security(mut self) -> Self {
+        self.row_level_security = true;
+        self
+    }
+
+    /// Read [`StreamFilter::with_correlation_id`] through the `'$bc-<correlation_id>'` link
+    /// streams of the `0025_link_streams` migration, an indexed read in append order instead
+    /// of a scan of the events' metadata. Call `replay_enable_correlation_links()` in the
+    /// database first: it starts linking appends and links the events already stored.
+    pub fn with_correlation_links(mut self) -> Self {
+        self.correlation_links = true;
+        self
+    }
+
+    /// Log appends and reads that take longer than `threshold` as warnings.
+    ///
+    /// Warnings go to the `replay_persistence::slow` tracing target with the stream id, the operation's
+    /// shape (stream type and event count for appends; the SQL, without bound values, and
+    /// filter for reads) and `elapsed_ms`. A read is timed over the queries only, not the time
+    /// its consumer spends between events, and is reported once the stream is exhausted.
+    /// Individual statements are logged by the pool instead, see
+    /// [`PostgresPoolOptions::with_slow_statement_threshold`](crate::PostgresPoolOptions::with_slow_statement_threshold).
+    pub fn with_slow_operation_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_operation_threshold = Some(threshold);
+        self
+    }
+
+    /// Whether appends need a transaction besides the `append_events` statement: to run
+    /// projections, write signatures or scope the tenant.
+    fn appends_in_transaction(&self) -> bool {
+        !self.projections.is_empty() || self.signer.is_some() || self.row_level_security
+    }
+
+    /// Scope the rest of `conn`'s transaction to the tenant named by `metadata`, under
+    /// row-level security. Writes without a tenant fail with `InvalidInput`.
+    async fn set_write_tenant(
+        &self,
+        conn: &mut sqlx::PgConnection,
+        metadata: &Metadata,
+        operation: &'static str,
+    ) -> Result<(), replay::Error> {
+        if !self.row_level_security {
+            return Ok(());
+        }
+        match metadata.tenant_id() {
+            Some(tenant) => self.set_tenant(conn, tenant).await,
+            None => Err(replay::Error::invalid_input(
+                "row-level security needs a tenant_id in the event metadata",
+            )
+            .with_operation(operation)),
+        }
+    }
+
+    /// Set `app.tenant_id` until the end of `conn`'s transaction.
+    async fn set_tenant(
+        &self,
+        conn: &mut sqlx::PgConnection,
+        tenant: &str,
+    ) -> Result<(), replay::Error> {
+        sqlx::query("SELECT set_config('app.tenant_id', $1, true)")
+            .bind(tenant)
+            .execute(conn)
+            .await
+            .map_err(|e| self.map_db_error(e).with_operation("set_tenant"))?;
+        Ok(())
+    }
+
+    /// Whether an operation that took `elapsed` should be logged as slow.
+    fn is_slow(&self, elapsed: Duration) -> bool {
+        self.slow_operation_threshold
+            .is_some_and(|threshold| elapsed > threshold)
+    }
+
+    pub(crate) fn map_db_error(&self, error: sqlx::Error) -> replay::Error {
+        if let Some(refresh) = &self.credentials {
+            if credentials::is_auth_failure(&error) {
+                refresh.refresh_in_background();
+                return replay::Error::unavailable("database rejected the pool's credentials")
+                    .with_operation("database_connect")
+                    .with_source(error);
+            }
         }
+        (self.db_error_mapper)(error)
     }
 
     /// Accessor for the underlying connection pool (used by the policy runner to
@@ -124,31 +423,372 @@ impl PostgresEventStore {
     ///
     /// This is the scalar counterpart of the contiguous-prefix scan the policy
     /// runner uses to avoid skipping in-flight events. Returns `0` when the log
-    /// is empty or its very first position has not yet committed.
+    /// is empty or its very first position has not yet committed. Holes left by
+    /// [retention](Self::apply_retention) up to its horizon don't count as gaps.
     pub async fn contiguous_high_water_mark(&self) -> Result<i64, replay::Error> {
-        // Number the rows in global order: the first position whose row number
-        // diverges from the value marks the first gap, so the contiguous prefix
-        // ends at that row number minus one. A single scan over the numbered
-        // rows yields both the first gap (a FILTERed MIN, NULL when there is
-        // none) and the total row count; with no gaps every position lines up
-        // and the prefix spans the whole table (COUNT(*)).
+        let horizon = crate::retention::retention_horizon(&self.pool)
+            .await
+            .map_err(|e| self.map_db_error(e))?;
+
+        // Number the rows past the horizon in global order: the first position
+        // whose row number diverges from the value marks the first gap, so the
+        // contiguous prefix ends at that row number minus one. A single scan over
+        // the numbered rows yields both the first gap (a FILTERed MIN, NULL when
+        // there is none) and the row count; with no gaps every position lines up
+        // and the prefix spans every row (COUNT(*)).
         let hwm: i64 = sqlx::query_scalar(
-            "SELECT COALESCE( \
-                 MIN(rn) FILTER (WHERE global_position <> rn) - 1, \
+            "SELECT $1 + COALESCE( \
+                 MIN(rn) FILTER (WHERE global_position <> $1 + rn) - 1, \
                  COUNT(*) \
              ) \
                FROM (SELECT global_position, \
                             ROW_NUMBER() OVER (ORDER BY global_position) AS rn \
-                       FROM events) numbered",
+                       FROM events WHERE global_position > $1) numbered",
         )
+        .bind(horizon)
         .fetch_one(&self.pool)
         .await
-        .map_err(crate::db_error)?;
+        .map_err(|e| self.map_db_error(e))?;
 
         Ok(hwm)
     }
 
-    pub(crate) fn add_filters(query_builder: &mut QueryBuilder<Postgres>, filter: StreamFilter) {
+    /// Crypto-shred `stream_id`: destroy its data key so the encrypted parts of its events
+    /// can never be read again, while the events themselves stay in place.
+    ///
+    /// Afterwards, reads return the stream's events with every encrypted field as `null`
+    /// (so declare such fields `Option` or `#[serde(default)]`); a whole-payload encrypted
+    /// event fails to load with `NotFound`. Appends and compaction through an encrypting
+    /// store fail with `Forbidden`. Forgetting is recorded (`stream_keys.forgotten`) even for
+    /// a stream that had no key yet, and forgetting twice is a no-op. Needs the
+    /// `0019_forgotten_streams` migration.
+    ///
+    /// ```rust,ignore
+    /// // GDPR erasure request for a customer
+    /// store.forget_stream(&customer_id.into()).await?;
+    /// ```
+    pub async fn forget_stream(&self, stream_id: &Urn) -> Result<(), replay::Error> {
+        sqlx::query(
+            "INSERT INTO stream_keys (stream_id, key, forgotten) VALUES ($1, NULL, now()) \
+             ON CONFLICT (stream_id) DO UPDATE \
+                SET key = NULL, forgotten = COALESCE(stream_keys.forgotten, now())",
+        )
+        .bind(stream_id.to_string())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            self.map_db_error(e)
+                .with_operation("forget_stream")
+                .with_context("stream_id", stream_id)
+        })?;
+        Ok(())
+    }
+
+    /// Check the signature of every event of `stream_id`, archived ones included, and that
+    /// no live version below the stream head is missing. Needs a signer (see
+    /// [`with_signer`](Self::with_signer)); a verify-only one is enough.
+    ///
+    /// ```rust,ignore
+    /// let auditor = PostgresEventStore::new(pool).with_signer(Ed25519Signer::verifying(&public_key));
+    /// let report = auditor.verify_stream(&account_id.into()).await?;
+    /// assert!(report.is_intact(), "{report:?}");
+    /// ```
+    pub async fn verify_stream(
+        &self,
+        stream_id: &Urn,
+    ) -> Result<StreamVerification, replay::Error> {
+        let Some(signer) = self.signer.as_deref() else {
+            return Err(replay::Error::invalid_input(
+                "verify_stream needs a signer, see `with_signer`",
+            )
+            .with_operation("verify_stream"));
+        };
+        let stream_id_str = stream_id.to_string();
+        let map_error = |e| {
+            self.map_db_error(e)
+                .with_operation("verify_stream")
+                .with_context("stream_id", stream_id)
+        };
+
+        let mut report = StreamVerification::default();
+        let mut next_live_version = 1;
+        let mut rows = sqlx::query(
+            "SELECT id, data, data_binary, data_encoding, metadata, stream_id, type, version, \
+             aggregate_version, signature FROM events WHERE stream_id = $1 \
+             ORDER BY aggregate_version NULLS LAST, version",
+        )
+        .bind(&stream_id_str)
+        .fetch(&self.pool);
+        while let Some(row) = rows.try_next().await.map_err(map_error)? {
+            let id: Uuid = row.get("id");
+            // A payload that no longer decodes was tampered with too.
+            let matches = stored_data(&row, &*self.serializer)
+                .and_then(|payload| signature_matches(&row, &payload, signer));
+            match matches {
+                Ok(Some(true)) => report.verified += 1,
+                Ok(None) => report.unsigned.push(id),
+                Ok(Some(false)) | Err(_) => report.tampered.push(id),
+            }
+
+            if row.get::<Option<i32>, _>("aggregate_version").is_none() {
+                let version: i64 = row.get("version");
+                report.missing_versions.extend(next_live_version..version);
+                next_live_version = next_live_version.max(version + 1);
+            }
+        }
+        drop(rows);
+
+        let head: Option<i64> = sqlx::query_scalar("SELECT version FROM streams WHERE id = $1")
+            .bind(&stream_id_str)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(map_error)?;
+        report
+            .missing_versions
+            .extend(next_live_version..=head.unwrap_or(0));
+
+        Ok(report)
+    }
+
+    /// Load already-persisted events of `S` streams with `COPY ... (FORMAT BINARY)`, for
+    /// initial data loads and store-to-store migrations.
+    ///
+    /// Events keep their id, version, timestamp, metadata and `aggregate_version`, and are
+    /// given `global_position`s in the order they arrive. Each stream's head moves to its
+    /// highest live version; streams are created with `S::stream_type()`. Everything is
+    /// loaded in one transaction, so a failing producer or a clash with an existing event
+    /// (a `Conflict`) imports nothing.
+    ///
+    /// Inline projections are not run; bump their version afterwards to rebuild them from
+    /// the imported history. Returns the number of imported events.
+    ///
+    /// ```rust,ignore
+    /// let events = source.stream_events::<BankAccountEvent>(
+    ///     StreamFilter::for_stream_type::<BankAccount>(),
+    /// );
+    /// let imported = target.bulk_import::<BankAccount, _>(events).await?;
+    /// ```
+    pub async fn bulk_import<S, Events>(&self, events: Events) -> Result<u64, replay::Error>
+    where
+        S: replay::EventStream,
+        Events: TryStream<Ok = PersistedEvent<S::Event>, Error = replay::Error> + Send,
+    {
+        if self.encryption.is_some() {
+            return Err(replay::Error::invalid_input(
+                "bulk_import writes payloads as given and can't encrypt them",
+            )
+            .with_operation("bulk_import"));
+        }
+        if self.signer.is_some() {
+            return Err(replay::Error::invalid_input(
+                "bulk_import writes events as given and can't sign them",
+            )
+            .with_operation("bulk_import"));
+        }
+
+        let stream_type = S::stream_type();
+        let mut tx = self.pool.begin().await.map_err(|e| self.map_db_error(e))?;
+
+        // Rows land in a staging table first: `events.stream_id` references `streams`, whose
+        // rows can only be written once every imported stream is known.
+        sqlx::query(
+            "CREATE TEMP TABLE replay_import (
+                import_order BIGINT GENERATED ALWAYS AS IDENTITY,
+                id uuid NOT NULL,
+                data jsonb NOT NULL,
+                metadata jsonb NOT NULL,
+                stream_id text NOT NULL,
+                type text NOT NULL,
+                version bigint NOT NULL,
+                created timestamp with time zone NOT NULL,
+                aggregate_version integer
+            ) ON COMMIT DROP",
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| self.map_db_error(e))?;
+
+        let mut copy = tx
+            .copy_in_raw(&format!(
+                "COPY replay_import ({EVENT_COLUMNS}) FROM STDIN (FORMAT BINARY)"
+            ))
+            .await
+            .map_err(|e| self.map_db_error(e))?;
+
+        let mut encoder = CopyEncoder::new();
+        let mut events = std::pin::pin!(events.into_stream());
+        let sent: Result<(), replay::Error> = async {
+            while let Some(event) = events.try_next().await? {
+                encoder.push_event(&event)?;
+                if encoder.len() >= COPY_CHUNK_BYTES {
+                    copy.send(encoder.take())
+                        .await
+                        .map_err(|e| self.map_db_error(e))?;
+                }
+            }
+            Ok(())
+        }
+        .await;
+        if let Err(err) = sent {
+            let _ = copy.abort("bulk import producer failed").await;
+            return Err(err.with_operation("bulk_import"));
+        }
+
+        copy.send(encoder.finish())
+            .await
+            .map_err(|e| self.map_db_error(e))?;
+        let imported = copy.finish().await.map_err(|e| self.map_db_error(e))?;
+
+        sqlx::query(
+            "INSERT INTO streams (id, type, version, tenant_id)
+             SELECT stream_id, $1,
+                    COALESCE(MAX(version) FILTER (WHERE aggregate_version IS NULL), 0),
+                    (ARRAY_AGG(metadata ->> 'tenant_id' ORDER BY version))[1]
+               FROM replay_import
+              GROUP BY stream_id
+             ON CONFLICT (id) DO UPDATE SET version = GREATEST(streams.version, EXCLUDED.version)",
+        )
+        .bind(stream_type)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| self.map_db_error(e))?;
+
+        sqlx::query(
+            "INSERT INTO events (id, data, metadata, stream_id, type, version, created, aggregate_version)
+             SELECT id, data, metadata, stream_id, type, version, created, aggregate_version
+               FROM replay_import
+              ORDER BY import_order",
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| self.map_db_error(e).with_operation("bulk_import"))?;
+
+        tx.commit().await.map_err(|e| self.map_db_error(e))?;
+
+        // Best-effort, as after an append: polling catches a missed notification.
+        if imported > 0 {
+            let _ = sqlx::query("SELECT pg_notify($1, $2)")
+                .bind(crate::REPLAY_NOTIFY_CHANNEL)
+                .bind(stream_type)
+                .execute(&self.pool)
+                .await;
+        }
+
+        Ok(imported)
+    }
+
+    /// Restore an NDJSON backup of `S` streams written by
+    /// [`export_events`](crate::export_events), through [`bulk_import`](Self::bulk_import):
+    /// events keep their ids, versions and timestamps, and nothing is restored when a line is
+    /// invalid or an event already exists.
+    ///
+    /// ```rust,ignore
+    /// let file = tokio::io::BufReader::new(tokio::fs::File::open("bank-accounts.ndjson").await?);
+    /// let restored = store.import_events::<BankAccount, _>(file).await?;
+    /// ```
+    pub async fn import_events<S, R>(&self, reader: R) -> Result<u64, replay::Error>
+    where
+        S: replay::EventStream,
+        R: tokio::io::AsyncBufRead + Unpin + Send,
+    {
+        self.bulk_import::<S, _>(crate::read_events::<S::Event, _>(reader))
+            .await
+    }
+
+    /// Create the missing monthly partitions of an `events` table partitioned by
+    /// `created_month` (see the `0017_event_partitioning` migration), from the current month
+    /// through the month of `through`. Returns how many were created.
+    ///
+    /// Appends fail once they reach a month without a partition, so run this well ahead,
+    /// e.g. from a monthly maintenance job.
+    pub async fn create_event_partitions(
+        &self,
+        through: chrono::DateTime<Utc>,
+    ) -> Result<i32, replay::Error> {
+        sqlx::query_scalar("SELECT replay_create_event_partitions(now(), $1)")
+            .bind(through)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| {
+                self.map_db_error(e)
+                    .with_operation("create_event_partitions")
+            })
+    }
+
+    /// Drop the monthly `events` partitions that end on or before `before`, returning their
+    /// names. Their events are deleted for good, so only drop months that are archived or
+    /// whose streams have been compacted since.
+    pub async fn drop_event_partitions(
+        &self,
+        before: chrono::DateTime<Utc>,
+    ) -> Result<Vec<String>, replay::Error> {
+        sqlx::query_scalar("SELECT replay_drop_event_partitions($1)")
+            .bind(before)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| self.map_db_error(e).with_operation("drop_event_partitions"))
+    }
+
+    /// Count the store's streams and events and measure its size on disk.
+    ///
+    /// Reads one consistent snapshot. The counts scan the `events` table, so call this from
+    /// admin tooling or a periodic job rather than a request path.
+    pub async fn statistics(&self) -> Result<StoreStatistics, replay::Error> {
+        let mut tx = self.pool.begin().await.map_err(|e| self.map_db_error(e))?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| self.map_db_error(e).with_operation("statistics"))?;
+
+        // A partitioned table reports no size of its own, so sum its partitions; for a plain
+        // table `pg_partition_tree` returns nothing and the table is measured directly.
+        let totals = sqlx::query(
+            "SELECT \
+               (SELECT count(*) FROM streams) AS streams, \
+               count(*) FILTER (WHERE aggregate_version IS NULL) AS events, \
+               count(*) FILTER (WHERE aggregate_version IS NOT NULL) AS archived_events, \
+               min(created) AS oldest_event, \
+               max(created) AS newest_event, \
+               COALESCE( \
+                 (SELECT sum(pg_total_relation_size(relid)) FROM pg_partition_tree('events')), \
+                 pg_total_relation_size('events') \
+               )::bigint + pg_total_relation_size('streams') AS size_bytes \
+             FROM events",
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| self.map_db_error(e).with_operation("statistics"))?;
+
+        let by_type: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT s.type, count(*) FROM events e JOIN streams s ON s.id = e.stream_id \
+             WHERE e.aggregate_version IS NULL GROUP BY s.type",
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| self.map_db_error(e).with_operation("statistics"))?;
+
+        Ok(StoreStatistics {
+            streams: totals.get::<i64, _>("streams") as u64,
+            events: totals.get::<i64, _>("events") as u64,
+            archived_events: totals.get::<i64, _>("archived_events") as u64,
+            events_by_stream_type: by_type
+                .into_iter()
+                .map(|(stream_type, count)| (stream_type, count as u64))
+                .collect(),
+            size_bytes: Some(totals.get::<i64, _>("size_bytes") as u64),
+            oldest_event: totals.get("oldest_event"),
+            newest_event: totals.get("newest_event"),
+        })
+    }
+
+    /// Render `filter` as a condition on `events`. Stream types are matched through their
+    /// `'$ce-'` link streams, and correlation ids through their `'$bc-'` ones when
+    /// `correlation_links` is set (see [`with_correlation_links`](Self::with_correlation_links)).
+    pub(crate) fn add_filters(
+        query_builder: &mut QueryBuilder<Postgres>,
+        filter: StreamFilter,
+        correlation_links: bool,
+    ) {
         match filter {
             StreamFilter::All => {
                 query_builder.push(" 1 = 1");
@@ -158,22 +798,55 @@ impl PostgresEventStore {
                     .push(" stream_id = ")
                     .push_bind(stream_id.to_string());
             }
+            StreamFilter::WithStreamIds(stream_ids) => {
+                let stream_ids: Vec<String> = stream_ids.iter().map(Urn::to_string).collect();
+                query_builder
+                    .push(" stream_id = ANY(")
+                    .push_bind(stream_ids)
+                    .push(")");
+            }
             StreamFilter::ForStreamTypes(stream_types) => {
-                query_builder.push(" stream_id IN (select id from streams where type IN (");
-
-                let mut separated = query_builder.separated(", ");
-
-                for stream_type in stream_types.clone().into_iter() {
-                    separated.push_bind(stream_type);
-                }
-
-                separated.push_unseparated("))");
+                let links: Vec<String> = stream_types
+                    .iter()
+                    .map(|stream_type| format!("$ce-{stream_type}"))
+                    .collect();
+                query_builder
+                    .push(" global_position IN (SELECT position FROM event_links WHERE link = ANY(")
+                    .push_bind(links)
+                    .push("))");
             }
             StreamFilter::WithMetadata(metadata) => {
                 query_builder
                     .push(" metadata @> ")
                     .push_bind(metadata.to_json());
             }
+            StreamFilter::WithCorrelationId(id) if correlation_links => {
+                query_builder
+                    .push(" global_position IN (SELECT position FROM event_links WHERE link = ")
+                    .push_bind(format!("$bc-{id}"))
+                    .push(")");
+            }
+            StreamFilter::WithCorrelationId(id) => {
+                query_builder
+                    .push(" metadata ->> 'correlation_id' = ")
+                    .push_bind(id);
+            }
+            StreamFilter::WithCausationId(id) => {
+                query_builder
+                    .push(" metadata ->> 'causation_id' = ")
+                    .push_bind(id);
+            }
+            StreamFilter::WithActor(actor) => {
+                query_builder
+                    .push(" metadata ->> 'actor' = ")
+                    .push_bind(actor);
+            }
+            StreamFilter::WithTenantId(tenant) => {
+                // Never NULL, so `NOT` over it also matches untenanted events.
+                query_builder
+                    .push(" metadata ->> 'tenant_id' IS NOT DISTINCT FROM ")
+                    .push_bind(tenant);
+            }
             StreamFilter::AfterVersion(version) => {
                 query_builder.push(" version > ").push_bind(version);
             }
@@ -196,31 +869,39 @@ impl PostgresEventStore {
                         .push_bind(version);
                 }
             },
+            StreamFilter::AfterGlobalPosition(position) => {
+                query_builder
+                    .push(" global_position > ")
+                    .push_bind(position);
+            }
+            StreamFilter::IncludingDeleted => {
+                query_builder.push(" 1 = 1");
+            }
             StreamFilter::And(left, right) => {
                 query_builder.push(" (");
-                Self::add_filters(query_builder, *left);
+                Self::add_filters(query_builder, *left, correlation_links);
                 query_builder.push(")");
 
                 query_builder.push(" AND ");
 
                 query_builder.push(" (");
-                Self::add_filters(query_builder, *right);
+                Self::add_filters(query_builder, *right, correlation_links);
                 query_builder.push(")");
             }
             StreamFilter::Or(left, right) => {
                 query_builder.push(" (");
-                Self::add_filters(query_builder, *left);
+                Self::add_filters(query_builder, *left, correlation_links);
                 query_builder.push(")");
 
                 query_builder.push(" OR ");
 
                 query_builder.push(" (");
-                Self::add_filters(query_builder, *right);
+                Self::add_filters(query_builder, *right, correlation_links);
                 query_builder.push(")");
             }
             StreamFilter::Not(filter) => {
                 query_builder.push(" NOT (");
-                Self::add_filters(query_builder, *filter);
+                Self::add_filters(query_builder, *filter, correlation_links);
                 query_builder.push(")");
             }
         }
@@ -234,6 +915,18 @@ impl PostgresEventStore {
 pub struct PostgresEventStoreBuilder {
     pool: Pool<Postgres>,
     projections: Vec<Box<dyn ErasedInlineProjection<Exec = sqlx::PgConnection>>>,
+    db_error_mapper: DbErrorMapper,
+    id_generator: SharedIdGenerator,
+    read_options: ReadOptions,
+    serializer: SharedSerializer,
+    #[cfg(feature = "compression")]
+    compression: Option<Compression>,
+    slow_operation_threshold: Option<Duration>,
+    encryption: Option<Encryption>,
+    signer: Option<Arc<dyn EventSigner>>,
+    row_level_security: bool,
+    correlation_links: bool,
+    credentials: Option<Arc<CredentialRefresh>>,
 }
 
 impl PostgresEventStoreBuilder {
@@ -284,6 +977,81 @@ impl PostgresEventStoreBuilder {
         self
     }
 
+    /// Replace the mapping from [`sqlx::Error`] to [`replay::Error`].
+    ///
+    /// Applies to the projection setup run by [`build`](Self::build) and to the built
+    /// store; see [`PostgresEventStore::with_db_error_mapper`].
+    pub fn with_db_error_mapper<F>(mut self, mapper: F) -> Self
+    where
+        F: Fn(sqlx::Error) -> replay::Error + Send + Sync + 'static,
+    {
+        self.db_error_mapper = Arc::new(mapper);
+        self
+    }
+
+    /// Replace the source of event ids for the built store; see
+    /// [`PostgresEventStore::with_id_generator`].
+    pub fn with_id_generator(mut self, id_generator: impl IdGenerator + 'static) -> Self {
+        self.id_generator = Arc::new(id_generator);
+        self
+    }
+
+    /// Replace the default read tuning of the built store; see
+    /// [`PostgresEventStore::with_read_options`].
+    pub fn with_read_options(mut self, read_options: ReadOptions) -> Self {
+        self.read_options = read_options;
+        self
+    }
+
+    /// Write payloads of the built store with `serializer`; see
+    /// [`PostgresEventStore::with_serializer`].
+    pub fn with_serializer(mut self, serializer: impl EventSerializer + 'static) -> Self {
+        self.serializer = Arc::new(serializer);
+        self
+    }
+
+    /// Compress large payloads in the built store; see
+    /// [`PostgresEventStore::with_compression`].
+    #[cfg(feature = "compression")]
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Encrypt payloads in the built store; see [`PostgresEventStore::with_encryption`].
+    /// Inline projections replayed by [`build`](Self::build) see plaintext.
+    pub fn with_encryption(mut self, encryption: Encryption) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+
+    /// Sign events of the built store; see [`PostgresEventStore::with_signer`].
+    pub fn with_signer(mut self, signer: impl EventSigner + 'static) -> Self {
+        self.signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// Set the tenant for row-level security in the built store; see
+    /// [`PostgresEventStore::with_row_level_security`].
+    pub fn with_row_level_security(mut self) -> Self {
+        self.row_level_security = true;
+        self
+    }
+
+    /// Read correlation ids through their link streams in the built store; see
+    /// [`PostgresEventStore::with_correlation_links`].
+    pub fn with_correlation_links(mut self) -> Self {
+        self.correlation_links = true;
+        self
+    }
+
+    /// Log slow appends and reads of the built store; see
+    /// [`PostgresEventStore::with_slow_operation_threshold`].
+    pub fn with_slow_operation_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_operation_threshold = Some(threshold);
+        self
+    }
+
     /// Run setup for the registered projections and freeze the store.
     ///
     /// For each projection, compares the stored registry version against the code
@@ -301,7 +1069,8 @@ impl PostgresEventStoreBuilder {
     /// Emits startup logs across the lifecycle (init, drift detection, reset, replay) so
     /// operators can see what happens at startup.
     pub async fn build(self) -> Result<PostgresEventStore, replay::Error> {
-        let mut tx = self.pool.begin().await.map_err(crate::db_error)?;
+        let map_db_error = self.db_error_mapper.clone();
+        let mut tx = self.pool.begin().await.map_err(&*map_db_error)?;
 
         let mut registered: Vec<RegisteredProjection> = Vec::with_capacity(self.projections.len());
 
@@ -312,7 +1081,7 @@ impl PostgresEventStoreBuilder {
                     .bind(&name)
                     .fetch_optional(&mut *tx)
                     .await
-                    .map_err(crate::db_error)?;
+                    .map_err(&*map_db_error)?;
 
             let code = projection.version();
 
@@ -333,8 +1102,15 @@ impl PostgresEventStoreBuilder {
                     // a store that already contains events catches up to the full backlog
                     // (not just events appended after registration). The projection's
                     // stream_filter narrows which events are scanned.
-                    let events =
-                        Self::load_events_for_replay(&mut tx, projection.stream_filter()).await?;
+                    let events = Self::load_events_for_replay(
+                        &mut tx,
+                        projection.stream_filter(),
+                        &*map_db_error,
+                        &*self.serializer,
+                        self.encryption.as_ref(),
+                        self.signer.as_deref(),
+                    )
+                    .await?;
                     tracing::info!(
                         projection = %name,
                         events = events.len(),
@@ -347,7 +1123,7 @@ impl PostgresEventStoreBuilder {
                         .bind(code)
                         .execute(&mut *tx)
                         .await
-                        .map_err(crate::db_error)?;
+                        .map_err(&*map_db_error)?;
                 }
                 // Version drift: the code is newer than the stored view. Reset and rebuild
                 // from history, then record the new version LAST so a crash mid-rebuild
@@ -367,8 +1143,15 @@ impl PostgresEventStoreBuilder {
                     // stream_filter narrows which events are scanned. Loaded in one batch
                     // for now; large histories can be chunked later without changing the
                     // batch-handling semantics seen by `handle`.
-                    let events =
-                        Self::load_events_for_replay(&mut tx, projection.stream_filter()).await?;
+                    let events = Self::load_events_for_replay(
+                        &mut tx,
+                        projection.stream_filter(),
+                        &*map_db_error,
+                        &*self.serializer,
+                        self.encryption.as_ref(),
+                        self.signer.as_deref(),
+                    )
+                    .await?;
                     tracing::info!(
                         projection = %name,
                         events = events.len(),
@@ -381,7 +1164,7 @@ impl PostgresEventStoreBuilder {
                         .bind(code)
                         .execute(&mut *tx)
                         .await
-                        .map_err(crate::db_error)?;
+                        .map_err(&*map_db_error)?;
                 }
                 // Stored version is newer than the code: refuse to start. A rolled-back or
                 // older deploy must not run against a view built by newer code.
@@ -407,11 +1190,23 @@ impl PostgresEventStoreBuilder {
             registered.push(Mutex::new(projection));
         }
 
-        tx.commit().await.map_err(crate::db_error)?;
+        tx.commit().await.map_err(&*map_db_error)?;
 
         Ok(PostgresEventStore {
             pool: self.pool,
             projections: Arc::new(registered),
+            db_error_mapper: self.db_error_mapper,
+            id_generator: self.id_generator,
+            read_options: self.read_options,
+            serializer: self.serializer,
+            #[cfg(feature = "compression")]
+            compression: self.compression,
+            slow_operation_threshold: self.slow_operation_threshold,
+            encryption: self.encryption,
+            signer: self.signer,
+            row_level_security: self.row_level_security,
+            correlation_links: self.correlation_links,
+            credentials: self.credentials,
         })
     }
 
@@ -422,22 +1217,34 @@ impl PostgresEventStoreBuilder {
     async fn load_events_for_replay(
         tx: &mut sqlx::PgConnection,
         filter: StreamFilter,
+        map_db_error: &(dyn Fn(sqlx::Error) -> replay::Error + Send + Sync),
+        serializer: &dyn EventSerializer,
+        encryption: Option<&Encryption>,
+        signer: Option<&dyn EventSigner>,
     ) -> Result<Vec<PersistedEvent<Value>>, replay::Error> {
         let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
-            "SELECT id, data, metadata, stream_id, type, version, created, aggregate_version \
-             FROM events WHERE ",
+            "SELECT id, data, data_binary, data_encoding, metadata, stream_id, type, version, \
+             created, aggregate_version, global_position",
         );
-        PostgresEventStore::add_filters(&mut query_builder, filter);
-        query_builder.push(" ORDER BY created, version ASC");
+        if encryption.is_some() {
+            query_builder.push(DATA_KEY_COLUMN);
+        }
+        if signer.is_some() {
+            query_builder.push(SIGNATURE_COLUMN);
+        }
+        query_builder.push(" FROM events WHERE ");
+        PostgresEventStore::add_filters(&mut query_builder, filter, false);
+        query_builder.push(" ORDER BY global_position");
 
         let rows = query_builder
             .build()
             .fetch_all(&mut *tx)
             .await
-            .map_err(crate::db_error)?;
+            .map_err(map_db_error)?;
 
+        let keys = encryption.map(Encryption::keys);
         rows.into_iter()
-            .map(PersistedEvent::<Value>::try_from)
+            .map(|row| event_from_row(row, serializer, keys.as_ref(), signer))
             .collect()
     }
 }
@@ -459,181 +1266,692 @@ impl PostgresEventStore {
 
         for projection in self.projections.iter() {
             let mut projection = projection.lock().await;
-            projection.handle(&mut *conn, events).await?;
+            let span = tracing::info_span!("inline_projection", projection = projection.name());
+            #[cfg(feature = "opentelemetry")]
+            events.iter().for_each(|event| event.link_trace(&span));
+            projection
+                .handle(&mut *conn, events)
+                .instrument(span)
+                .await?;
         }
 
         Ok(())
     }
 }
 
-impl EventStore for PostgresEventStore {
-    async fn store_events_stream<S, ES, Sink>(
-        &self,
-        stream_id: &S::StreamId,
-        stream_type: String,
-        metadata: replay::Metadata,
-        domain_events: ES,
-        expected_version: Option<i64>,
-        mut sink: Sink,
-    ) -> Result<(), replay::Error>
-    where
-        S: replay::EventStream,
-        ES: TryStream<Ok = S::Event, Error = replay::Error> + Send,
-        Sink: EventSink<S::Event> + Send,
-    {
-        let mut transaction = self.pool.begin().await.map_err(crate::db_error)?;
-        let stream_id: Urn = stream_id.clone().into();
-
-        // Track the appended events so registered inline projections can be applied
-        // inside this same transaction. This is the only buffer in the loop and it is
-        // populated *only* when projections are registered; bulk producers without
-        // projections stream straight through without materialising the batch.
-        let has_projections = !self.projections.is_empty();
-        let mut appended: Vec<PersistedEvent<Value>> = Vec::new();
-        let mut appended_count: usize = 0;
+impl PostgresEventStore {
+    /// The payload format used for appends.
+    pub(crate) fn serializer(&self) -> &dyn EventSerializer {
+        &*self.serializer
+    }
 
-        // Consume the producer one event at a time so a large append (e.g. 160k rows)
-        // never has to live fully in memory.
-        let mut domain_events = std::pin::pin!(domain_events.into_stream());
+    /// The payload encryption, if installed.
+    pub(crate) fn encryption(&self) -> Option<&Encryption> {
+        self.encryption.as_ref()
+    }
 
-        let mut is_first = true;
-        while let Some(event) = domain_events.try_next().await? {
-            let event_type = event.event_type().clone();
-            let event_data = serde_json::to_value(&event).map_err(crate::ser_error)?;
-            let id = Uuid::new_v4();
-
-            // Optimistic concurrency is checked once, on the first append only. The caller's
-            // expected version is matched against the stream head inside `append_event`,
-            // whose `SELECT ... FOR UPDATE` locks the stream row for the rest of the
-            // transaction. Subsequent appends pass a NULL expected version: the version has
-            // been incremented under the lock we already hold, so re-checking it against the
-            // original expectation would spuriously conflict.
-            let expected = if is_first { expected_version } else { None };
-
-            let row = sqlx::query(
-                "SELECT id, version, created FROM append_event($1, $2, $3, $4, $5, $6, $7)",
-            )
-            .bind(id)
-            .bind(&event_data)
-            .bind(metadata.to_json())
-            .bind(&event_type)
-            .bind(stream_id.to_string())
-            .bind(&stream_type)
-            .bind(expected)
-            .fetch_optional(&mut *transaction)
+    /// The select-list entry for each event's data key, empty when the store doesn't encrypt.
+    pub(crate) fn data_key_column(&self) -> &'static str {
+        match self.encryption {
+            Some(_) => DATA_KEY_COLUMN,
+            None => "",
+        }
+    }
+
+    /// The event signer, if installed.
+    pub(crate) fn signer(&self) -> Option<&dyn EventSigner> {
+        self.signer.as_deref()
+    }
+
+    /// The select-list entry for each event's signature, empty when the store doesn't sign.
+    pub(crate) fn signature_column(&self) -> &'static str {
+        match self.signer {
+            Some(_) => SIGNATURE_COLUMN,
+            None => "",
+        }
+    }
+
+    /// Whether correlation ids are read through their link streams.
+    pub(crate) fn correlation_links(&self) -> bool {
+        self.correlation_links
+    }
+
+    /// `stream_id`'s data key, created on first use and unwrapped for the operation, when the
+    /// store encrypts. A [forgotten](Self::forget_stream) stream fails with `Forbidden`.
+    async fn data_key(
+        &self,
+        conn: &mut sqlx::PgConnection,
+        stream_id: &str,
+    ) -> Result<Option<Box<dyn DataKey>>, replay::Error> {
+        let Some(encryption) = &self.encryption else {
+            return Ok(None);
+        };
+        let forgotten = || {
+            replay::Error::forbidden("stream was forgotten; it can't take new encrypted events")
+                .with_operation("encrypt_event")
+                .with_context("stream_id", stream_id)
+        };
+        let select = "SELECT key FROM stream_keys WHERE stream_id = $1";
+        match sqlx::query_scalar::<_, Option<Vec<u8>>>(select)
+            .bind(stream_id)
+            .fetch_optional(&mut *conn)
             .await
-            .map_err(crate::db_error)?;
-
-            // No row means an optimistic-concurrency mismatch in `append_event` (only
-            // possible on the first append). Surface it as a concurrency_error and let the
-            // transaction roll back so no partial append commits.
-            let Some(row) = row else {
-                let actual_version: i64 =
-                    sqlx::query_scalar("SELECT version FROM streams WHERE id = $1")
-                        .bind(stream_id.to_string())
-                        .fetch_optional(&mut *transaction)
-                        .await
-                        .map_err(crate::db_error)?
-                        .unwrap_or(0);
-
-                return Err(crate::concurrency_error(
-                    stream_id.clone(),
-                    expected_version.unwrap_or(actual_version),
-                    actual_version,
-                ));
+            .map_err(|e| self.map_db_error(e))?
+        {
+            Some(Some(key)) => return encryption.data_key(&key).map(Some),
+            Some(None) => return Err(forgotten()),
+            None => {}
+        }
+
+        // A concurrent first append may insert its own key; whichever lands wins, and the
+        // second SELECT (a fresh snapshot) returns it.
+        sqlx::query(
+            "INSERT INTO stream_keys (stream_id, key) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(stream_id)
+        .bind(encryption.generate_data_key()?)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| self.map_db_error(e))?;
+        let key = sqlx::query_scalar::<_, Option<Vec<u8>>>(select)
+            .bind(stream_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| self.map_db_error(e))?
+            .ok_or_else(forgotten)?;
+        encryption.data_key(&key).map(Some)
+    }
+
+    /// `data` of `stream_id` as it is stored: encrypted under `data_key` when there is one.
+    fn encrypt_payload<'a>(
+        &self,
+        stream_id: &str,
+        data_key: Option<&dyn DataKey>,
+        data: &'a Value,
+    ) -> Result<std::borrow::Cow<'a, Value>, replay::Error> {
+        match (&self.encryption, data_key) {
+            (Some(encryption), Some(data_key)) => Ok(std::borrow::Cow::Owned(
+                encryption.encrypt(stream_id, data_key, data)?,
+            )),
+            _ => Ok(std::borrow::Cow::Borrowed(data)),
+        }
+    }
+
+    /// `data` as it goes into `data_binary`, with its `data_encoding`; `None` for a plain
+    /// `jsonb` payload.
+    fn encode_payload(&self, data: &Value) -> Result<Option<(String, Vec<u8>)>, replay::Error> {
+        let format = self.serializer.encoding();
+        let bytes = (format != serializer::JSON)
+            .then(|| self.serializer.serialize(data))
+            .transpose()?;
+
+        #[cfg(feature = "compression")]
+        if let Some(compression) = &self.compression {
+            let payload = match &bytes {
+                Some(bytes) => std::borrow::Cow::Borrowed(bytes),
+                None => std::borrow::Cow::Owned(self.serializer.serialize(data)?),
             };
+            if let Some(compressed) = compression.compress(&payload)? {
+                return Ok(Some((
+                    format!("{format}+{}", compression::ZSTD),
+                    compressed,
+                )));
+            }
+        }
+
+        Ok(bytes.map(|bytes| (format.to_string(), bytes)))
+    }
 
-            is_first = false;
+    /// Append `events` to `stream_id` with a single `append_events` call.
+    ///
+    /// The same statement notifies [`REPLAY_NOTIFY_CHANNEL`](crate::REPLAY_NOTIFY_CHANNEL)
+    /// with the stream type, delivered when the append commits, so waiting policy runners
+    /// wake without an extra round trip. Postgres folds the identical notifications of one
+    /// transaction into one.
+    ///
+    /// Returns each appended event with its JSON payload, in version order, or `None` when
+    /// `expected_version` doesn't match the stream head (nothing is written then).
+    async fn append_batch<E: Event>(
+        &self,
+        conn: &mut sqlx::PgConnection,
+        stream_id: &Urn,
+        stream_type: &str,
+        metadata: &Metadata,
+        events: Vec<E>,
+        expected_version: Option<i64>,
+    ) -> Result<Option<Vec<(PersistedEvent<E>, Value)>>, replay::Error> {
+        let mut ids = Vec::with_capacity(events.len());
+        let mut data = Vec::with_capacity(events.len());
+        let mut stored_data = Vec::with_capacity(events.len());
+        let mut binary_data = Vec::with_capacity(events.len());
+        let mut encodings = Vec::with_capacity(events.len());
+        let mut metadata_json = Vec::with_capacity(events.len());
+        let mut event_metadata = Vec::with_capacity(events.len());
+        let mut types = Vec::with_capacity(events.len());
+        // Payloads as stored, before any binary encoding, kept for signing.
+        let mut signed_data = Vec::new();
+        let data_key = self.data_key(conn, stream_id.as_ref()).await?;
+        for event in &events {
+            ids.push(self.id_generator.next_id());
+            let json = serde_json::to_value(event).map_err(crate::ser_error)?;
+            let stored = self.encrypt_payload(stream_id.as_ref(), data_key.as_deref(), &json)?;
+            if self.signer.is_some() {
+                signed_data.push(stored.clone().into_owned());
+            }
+            match self.encode_payload(&stored)? {
+                Some((encoding, bytes)) => {
+                    stored_data.push(None);
+                    binary_data.push(Some(bytes));
+                    encodings.push(Some(encoding));
+                }
+                None => {
+                    stored_data.push(Some(stored.into_owned()));
+                    binary_data.push(None);
+                    encodings.push(None);
+                }
+            }
+            data.push(json);
+            let versioned = versioned_metadata(metadata, event);
+            metadata_json.push(versioned.to_json());
+            event_metadata.push(versioned);
+            types.push(event.event_type());
+        }
 
-            let persisted_id: Uuid = row.get("id");
-            let version: i64 = row.get("version");
-            let created: chrono::DateTime<Utc> = row.get("created");
+        let mut rows = sqlx::query(
+            "SELECT id, version, created, global_position, pg_notify($10, $6)::text \
+             FROM append_events($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        )
+        .bind(&ids)
+        .bind(&stored_data)
+        .bind(&metadata_json)
+        .bind(&types)
+        .bind(stream_id.to_string())
+        .bind(stream_type)
+        .bind(expected_version)
+        .bind(&binary_data)
+        .bind(&encodings)
+        .bind(crate::REPLAY_NOTIFY_CHANNEL)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| self.map_db_error(e))?;
 
-            // Notify the sink as each event is appended (inside the transaction) so a
-            // consumer can fold events as they stream, without the store retaining the
-            // whole batch. The sink is an infallible observer and cannot abort the txn.
-            sink.on_event(&PersistedEvent {
-                id: persisted_id,
-                data: event,
-                stream_id: stream_id.clone(),
-                r#type: event_type.clone(),
-                version,
-                created,
-                metadata: metadata.clone(),
-                aggregate_version: None,
-            });
+        if rows.is_empty() {
+            return Ok(None);
+        }
+        rows.sort_by_key(|row| row.get::<i64, _>("version"));
+
+        // Versions are only known once the database assigned them, so signatures follow
+        // in the same transaction.
+        if let Some(signer) = &self.signer {
+            let stream_id_str = stream_id.to_string();
+            let mut signatures = Vec::with_capacity(rows.len());
+            for (((row, payload), metadata), r#type) in rows
+                .iter()
+                .zip(&signed_data)
+                .zip(&metadata_json)
+                .zip(&types)
+            {
+                let message = signing::message(
+                    &stream_id_str,
+                    row.get("version"),
+                    r#type,
+                    payload,
+                    metadata,
+                )?;
+                signatures.push(signer.sign(&message)?);
+            }
+            let ids: Vec<Uuid> = rows.iter().map(|row| row.get("id")).collect();
+            sqlx::query(
+                "UPDATE events SET signature = signed.signature \
+                 FROM UNNEST($1::uuid[], $2::bytea[]) AS signed(id, signature) \
+                 WHERE events.id = signed.id",
+            )
+            .bind(&ids)
+            .bind(&signatures)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| self.map_db_error(e))?;
+        }
 
-            if has_projections {
-                appended.push(PersistedEvent {
-                    id: persisted_id,
-                    data: event_data,
+        let persisted = events
+            .into_iter()
+            .zip(data)
+            .zip(event_metadata.into_iter().zip(types))
+            .zip(rows)
+            .map(|(((event, event_data), (metadata, r#type)), row)| {
+                let persisted = PersistedEvent {
+                    id: row.get("id"),
+                    data: event,
                     stream_id: stream_id.clone(),
-                    r#type: event_type,
-                    version,
-                    created,
-                    metadata: metadata.clone(),
+                    r#type: r#type.to_string(),
+                    version: row.get("version"),
+                    created: row.get("created"),
+                    metadata,
                     aggregate_version: None,
-                });
+                    global_position: row.get("global_position"),
+                };
+                (persisted, event_data)
+            })
+            .collect();
+
+        Ok(Some(persisted))
+    }
+
+    /// The error for an append whose `expected_version` missed the stream head.
+    async fn concurrency_error(
+        &self,
+        conn: &mut sqlx::PgConnection,
+        stream_id: &Urn,
+        expected_version: Option<i64>,
+    ) -> replay::Error {
+        let actual_version: i64 =
+            match sqlx::query_scalar("SELECT version FROM streams WHERE id = $1")
+                .bind(stream_id.to_string())
+                .fetch_optional(&mut *conn)
+                .await
+            {
+                Ok(version) => version.unwrap_or(0),
+                Err(e) => return self.map_db_error(e),
+            };
+
+        crate::concurrency_error(
+            stream_id.clone(),
+            expected_version.unwrap_or(actual_version),
+            actual_version,
+        )
+    }
+}
+
+impl PostgresEventStore {
+    /// [`stream_events`](EventStore::stream_events) with per-call [`ReadOptions`] instead of
+    /// the store's defaults.
+    pub fn stream_events_with<E: Event>(
+        &self,
+        filter: StreamFilter,
+        options: ReadOptions,
+    ) -> impl TryStream<Ok = PersistedEvent<E>, Error = replay::Error> + Send + use<'_, E> {
+        async_stream::try_stream! {
+            // Keyset cursor: the global position of the last row returned. Positions are
+            // unique, so a page never repeats or skips rows.
+            let mut after: Option<i64> = None;
+            let mut count = 0;
+            // Time spent waiting on Postgres, and the last query's SQL, for slow-read warnings.
+            let mut elapsed = Duration::ZERO;
+            let mut sql = String::new();
+
+            loop {
+                let page_size = options.next_page_size(count);
+                if page_size == Some(0) {
+                    break;
+                }
+
+                let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+                    "SELECT id, data, data_binary, data_encoding, metadata, stream_id, type, \
+                     version, created, aggregate_version, global_position",
+                );
+                query_builder.push(self.data_key_column());
+                query_builder.push(self.signature_column());
+                query_builder.push(" FROM events WHERE (");
+                Self::add_filters(&mut query_builder, filter.clone(), self.correlation_links);
+                query_builder.push(")");
+                if !filter.includes_deleted() {
+                    query_builder.push(
+                        " AND NOT EXISTS (SELECT 1 FROM streams s \
+                         WHERE s.id = events.stream_id AND s.deleted IS NOT NULL)",
+                    );
+                }
+                // Live events past their stream's retention limits, except its head.
+                query_builder.push(
+                    " AND NOT EXISTS (SELECT 1 FROM streams s \
+                     WHERE s.id = events.stream_id AND events.aggregate_version IS NULL \
+                       AND events.version < s.version \
+                       AND (events.version <= s.version - s.max_count \
+                            OR events.created < now() - s.max_age))",
+                );
+                if let Some(position) = after {
+                    query_builder.push(" AND global_position > ").push_bind(position);
+                }
+                query_builder.push(" ORDER BY global_position");
+                if let Some(page_size) = page_size {
+                    query_builder.push(" LIMIT ").push_bind(page_size as i64);
+                }
+
+                sql.clear();
+                sql.push_str(query_builder.sql().as_str());
+                // Under row-level security each page reads in a transaction scoped to the
+                // filter's tenant, which ends (and unsets it) when the page is dropped.
+                let mut tenant_tx = None;
+                if self.row_level_security {
+                    let mut tx = self.pool.begin().await.map_err(|e| self.map_db_error(e))?;
+                    if let Some(tenant) = filter.tenant_id() {
+                        self.set_tenant(&mut tx, tenant).await?;
+                    }
+                    tenant_tx = Some(tx);
+                }
+                let keys = self.encryption().map(Encryption::keys);
+                let query = query_builder.build();
+                let rows = match tenant_tx.as_mut() {
+                    Some(tx) => query.fetch(&mut **tx).left_stream(),
+                    None => query.fetch(&self.pool).right_stream(),
+                };
+                let mut rows = rows
+                    .map_err(|e: sqlx::Error| {
+                        self.map_db_error(e)
+                            .with_operation("fetching events from Postgres")
+                            .with_context("filter", format!("{:?}", filter))
+                    })
+                    .map(|result| async { result.and_then(|row| event_from_row(row, &*self.serializer, keys.as_ref(), self.signer())) })
+                    .buffered(options.buffer_size);
+
+                let mut fetched = 0;
+                loop {
+                    let started = Instant::now();
+                    let next = rows.try_next().await?;
+                    elapsed += started.elapsed();
+                    let Some(event) = next else { break };
+                    fetched += 1;
+                    after = Some(event.global_position);
+                    crate::metrics::record_streamed(STORE);
+                    yield event;
+                }
+                count += fetched;
+
+                // Without a batch size the single query returned every match (up to the
+                // limit); with one, a short page means the matches ran out.
+                match (options.fetch_batch_size, page_size) {
+                    (Some(_), Some(page_size)) if fetched == page_size => continue,
+                    _ => break,
+                }
             }
 
-            appended_count += 1;
+            tracing::debug!("Streamed {} events from Postgres", count);
+            if self.is_slow(elapsed) {
+                tracing::warn!(
+                    target: "replay_persistence::slow",
+                    operation = "stream_events",
+                    stream_id = filter.stream_id().map(tracing::field::display),
+                    sql = %sql,
+                    events = count,
+                    elapsed_ms = elapsed.as_millis() as u64,
+                    "slow read",
+                );
+            }
         }
+    }
+}
 
-        if has_projections {
-            self.apply_projections(&mut transaction, &appended).await?;
+impl PostgresEventStore {
+    /// The append behind [`EventStore::store_events_stream`], which adds metrics.
+    async fn append_stream<S, ES, Sink>(
+        &self,
+        stream_id: &S::StreamId,
+        stream_type: &str,
+        metadata: replay::Metadata,
+        domain_events: ES,
+        expected_version: Option<i64>,
+        mut sink: Sink,
+    ) -> Result<(), replay::Error>
+    where
+        S: replay::EventStream,
+        ES: TryStream<Ok = S::Event, Error = replay::Error> + Send,
+        Sink: EventSink<S::Event> + Send,
+    {
+        let stream_id = stream_id.stream_urn();
+
+        // Track the appended events so registered inline projections can be applied
+        // inside this same transaction. This is the only buffer that outlives a batch and
+        // it is populated *only* when projections are registered; bulk producers without
+        // projections stream straight through.
+        let has_projections = !self.projections.is_empty();
+        let mut appended: Vec<PersistedEvent<Value>> = Vec::new();
+        let mut appended_count: usize = 0;
+
+        // Consume the producer in batches of at most `APPEND_BATCH_SIZE` events, each
+        // written with a single `append_events` call, so a large append (e.g. 160k rows)
+        // never has to live fully in memory nor pay a round trip per event.
+        let mut domain_events = std::pin::pin!(domain_events.into_stream());
+        let mut batch = Vec::new();
+        let mut end = next_batch(&mut domain_events, &mut batch).await;
+
+        // Fast path: a command's events usually fit one batch, and one `append_events`
+        // statement is atomic on its own. With nothing else to run in its transaction, the
+        // append is a single round trip instead of BEGIN, append and COMMIT.
+        if matches!(end, BatchEnd::Exhausted) && !self.appends_in_transaction() {
+            if batch.is_empty() {
+                return Ok(());
+            }
+            let mut conn = self
+                .pool
+                .acquire()
+                .await
+                .map_err(|e| self.map_db_error(e))?;
+            let Some(persisted) = self
+                .append_batch(
+                    &mut conn,
+                    &stream_id,
+                    stream_type,
+                    &metadata,
+                    batch,
+                    expected_version,
+                )
+                .await?
+            else {
+                return Err(self
+                    .concurrency_error(&mut conn, &stream_id, expected_version)
+                    .await);
+            };
+            for (event, _) in persisted {
+                sink.on_event(&event);
+            }
+            return Ok(());
         }
 
-        transaction.commit().await.map_err(crate::db_error)?;
+        let mut transaction = self.pool.begin().await.map_err(|e| self.map_db_error(e))?;
+        self.set_write_tenant(&mut transaction, &metadata, "store_events")
+            .await?;
+
+        loop {
+            if !batch.is_empty() {
+                // Optimistic concurrency is checked once, on the first batch only. The
+                // caller's expected version is matched against the stream head inside
+                // `append_events`, whose `SELECT ... FOR UPDATE` locks the stream row for the
+                // rest of the transaction. Later batches pass a NULL expected version: the
+                // version has been incremented under the lock we already hold, so re-checking
+                // it against the original expectation would spuriously conflict.
+                let expected = if appended_count == 0 {
+                    expected_version
+                } else {
+                    None
+                };
+
+                let Some(persisted) = self
+                    .append_batch(
+                        &mut transaction,
+                        &stream_id,
+                        stream_type,
+                        &metadata,
+                        std::mem::take(&mut batch),
+                        expected,
+                    )
+                    .await?
+                else {
+                    // Zero rows means an optimistic-concurrency mismatch (only possible on the
+                    // first batch); the transaction rolls back so no partial append commits.
+                    return Err(self
+                        .concurrency_error(&mut transaction, &stream_id, expected_version)
+                        .await);
+                };
+
+                for (event, event_data) in persisted {
+                    // Notify the sink as each batch is appended (inside the transaction) so a
+                    // consumer can fold events as they stream, without the store retaining
+                    // the whole append. The sink is an infallible observer and cannot abort
+                    // the txn.
+                    sink.on_event(&event);
+
+                    if has_projections {
+                        appended.push(PersistedEvent {
+                            id: event.id,
+                            data: event_data,
+                            stream_id: event.stream_id,
+                            r#type: event.r#type,
+                            version: event.version,
+                            created: event.created,
+                            metadata: event.metadata,
+                            aggregate_version: None,
+                            global_position: event.global_position,
+                        });
+                    }
+
+                    appended_count += 1;
+                }
+            }
 
-        // Best-effort NOTIFY: wake any waiting policy tasks immediately so they
-        // react without waiting for the next poll interval.  Errors are silently
-        // swallowed — polling remains the correctness baseline and a missed
-        // notification is caught on the next interval.
-        if appended_count > 0 {
-            let _ = sqlx::query("SELECT pg_notify($1, $2)")
-                .bind(crate::REPLAY_NOTIFY_CHANNEL)
-                .bind(&stream_type)
-                .execute(&self.pool)
-                .await;
+            match end {
+                BatchEnd::Full => end = next_batch(&mut domain_events, &mut batch).await,
+                BatchEnd::Exhausted => break,
+                // The events read before the error were appended, so the sink observed
+                // them; dropping the transaction rolls them back.
+                BatchEnd::Failed(e) => return Err(e),
+            }
         }
 
+        if has_projections {
+            self.apply_projections(&mut transaction, &appended).await?;
+        }
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| self.map_db_error(e))?;
+
         Ok(())
     }
+}
+
+impl EventStore for PostgresEventStore {
+    async fn store_events_stream<S, ES, Sink>(
+        &self,
+        stream_id: &S::StreamId,
+        stream_type: &str,
+        metadata: replay::Metadata,
+        domain_events: ES,
+        expected_version: Option<i64>,
+        mut sink: Sink,
+    ) -> Result<(), replay::Error>
+    where
+        S: replay::EventStream,
+        ES: TryStream<Ok = S::Event, Error = replay::Error> + Send,
+        Sink: EventSink<S::Event> + Send,
+    {
+        let started = Instant::now();
+        let mut appended = 0;
+        let result = self
+            .append_stream::<S, _, _>(
+                stream_id,
+                stream_type,
+                metadata,
+                domain_events,
+                expected_version,
+                |event: &PersistedEvent<S::Event>| {
+                    appended += 1;
+                    sink.on_event(event);
+                },
+            )
+            .await;
+        let elapsed = started.elapsed();
+        crate::metrics::record_append(STORE, stream_type, appended, elapsed, &result);
+        if self.is_slow(elapsed) {
+            tracing::warn!(
+                target: "replay_persistence::slow",
+                operation = "store_events",
+                stream_id = %Into::<Urn>::into(stream_id.clone()),
+                stream_type,
+                events = appended,
+                elapsed_ms = elapsed.as_millis() as u64,
+                "slow append",
+            );
+        }
+        result
+    }
 
     fn stream_events<E: Event>(
         &self,
         filter: StreamFilter,
     ) -> impl TryStream<Ok = PersistedEvent<E>, Error = replay::Error> + Send {
-        async_stream::stream! {
-            let sql = "SELECT id, data, metadata, stream_id, type, version, created, aggregate_version
-                FROM events 
-                WHERE " ;
-
-            let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(sql);
-            Self::add_filters(&mut query_builder, filter.clone());
-
-            let query_builder = query_builder.push(" ORDER BY created, version ASC");
-
-            let mut rows = query_builder
-                .build()
-                .fetch(&self.pool)
-                .map_err(|e: sqlx::Error| crate::db_error(e).with_operation("fetching events from Postgres").with_context("filter", format!("{:?}", filter)))
-                .map(|result| async {
-                    result.and_then(PersistedEvent::<E>::try_from)
-                }).buffered(4);
-
-            let mut count = 0;
+        self.stream_events_with(filter, self.read_options)
+    }
 
-            while let Some(row) = rows.try_next().await? {
-                count += 1;
-                yield Ok(row);
+    /// Finds where the committed prefix of the page ends from the positions alone, so the
+    /// events of deleted streams don't read as gaps, then reads the events up to there.
+    async fn read_all<E: Event>(
+        &self,
+        from_position: i64,
+        limit: usize,
+    ) -> Result<AllEvents<E>, replay::Error> {
+        let map_error = |e: sqlx::Error| {
+            self.map_db_error(e)
+                .with_operation("read_all")
+                .with_context("from_position", from_position)
+        };
+        let positions: Vec<i64> = sqlx::query_scalar(
+            "SELECT global_position FROM events WHERE global_position >= $1 \
+             ORDER BY global_position LIMIT $2",
+        )
+        .bind(from_position)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_error)?;
+
+        // As in the policy feed: holes up to the retention horizon are deleted events, any
+        // other is an append still in flight, which ends the page.
+        let mut through = None;
+        let mut expected = from_position;
+        let mut horizon = None;
+        for position in positions {
+            if position != expected {
+                let horizon = match horizon {
+                    Some(horizon) => horizon,
+                    None => *horizon.insert(
+                        crate::retention::retention_horizon(&self.pool)
+                            .await
+                            .map_err(map_error)?,
+                    ),
+                };
+                if position - 1 > horizon {
+                    break;
+                }
             }
-
-            tracing::debug!("Streamed {} events from Postgres", count);
+            through = Some(position);
+            expected = position + 1;
         }
+        let Some(through) = through else {
+            return Ok(AllEvents {
+                events: Vec::new(),
+                next_position: from_position,
+            });
+        };
+
+        let events = self
+            .stream_events_with::<E>(
+                StreamFilter::AfterGlobalPosition(from_position - 1),
+                ReadOptions {
+                    limit: Some(limit),
+                    ..self.read_options
+                },
+            )
+            .try_take_while(|event| futures::future::ready(Ok(event.global_position <= through)))
+            .try_collect()
+            .await?;
+        Ok(AllEvents {
+            events,
+            next_position: through + 1,
+        })
     }
 
-    async fn needs_compaction(&self, stream_id: &Urn) -> Result<bool, replay::Error> {
+    async fn needs_compaction(&self, stream_id: &impl StreamId) -> Result<bool, replay::Error> {
+        let stream_id = &stream_id.stream_urn();
         let stream_id_str = stream_id.to_string();
 
         // Pure streams-row scalar test, no lock: has anything been appended past the
@@ -644,7 +1962,7 @@ impl EventStore for PostgresEventStore {
         .bind(&stream_id_str)
         .fetch_optional(&self.pool)
         .await
-        .map_err(crate::db_error)?;
+        .map_err(|e| self.map_db_error(e))?;
 
         Ok(needs.unwrap_or(false))
     }
@@ -657,10 +1975,11 @@ impl EventStore for PostgresEventStore {
     where
         A: replay::Aggregate + Compactable + Sync,
     {
-        let stream_id: Urn = aggregate.get_id().clone().into();
+        let stream_id = aggregate.get_id().stream_urn();
         let stream_id_str = stream_id.to_string();
 
-        let mut tx = self.pool.begin().await.map_err(crate::db_error)?;
+        let mut tx = self.pool.begin().await.map_err(|e| self.map_db_error(e))?;
+        self.set_write_tenant(&mut tx, &metadata, "compact").await?;
 
         // 1. Lock the stream row for the duration of this transaction.
         //    Any concurrent `append_event` call that updates (or inserts into) this stream
@@ -672,7 +1991,7 @@ impl EventStore for PostgresEventStore {
             .bind(&stream_id_str)
             .execute(&mut *tx)
             .await
-            .map_err(crate::db_error)?;
+            .map_err(|e| self.map_db_error(e))?;
 
         if lock_result.rows_affected() == 0 {
             return Err(replay::Error::not_found("Stream not found")
@@ -680,16 +1999,24 @@ impl EventStore for PostgresEventStore {
                 .with_context("stream_id", stream_id_str));
         }
 
+        let data_key = self.data_key(&mut tx, &stream_id_str).await?;
+        let data_key = data_key.as_deref();
+        let stream_id = stream_id_str.as_str();
+
         // 2. Stream the current live events inside the transaction (now protected by the lock),
         //    processing rows one at a time so the full history is never held in memory.
         let event_stream = sqlx::query(
-            "SELECT data FROM events WHERE stream_id = $1 AND aggregate_version IS NULL ORDER BY version",
+            "SELECT data, data_binary, data_encoding FROM events \
+             WHERE stream_id = $1 AND aggregate_version IS NULL ORDER BY version",
         )
         .bind(&stream_id_str)
         .fetch(&mut *tx)
-        .map_err(crate::db_error)
+        .map_err(|e| self.map_db_error(e))
         .and_then(|row: PgRow| async move {
-            let data: serde_json::Value = row.get("data");
+            let mut data = stored_data(&row, &*self.serializer)?;
+            if let Some(encryption) = &self.encryption {
+                data = encryption.decrypt(stream_id, data_key, data)?;
+            }
             serde_json::from_value::<A::Event>(data).map_err(crate::deser_error)
         });
 
@@ -703,8 +2030,8 @@ impl EventStore for PostgresEventStore {
                     .bind(&stream_id_str)
                     .execute(&mut *tx)
                     .await
-                    .map_err(crate::db_error)?;
-                tx.commit().await.map_err(crate::db_error)?;
+                    .map_err(|e| self.map_db_error(e))?;
+                tx.commit().await.map_err(|e| self.map_db_error(e))?;
                 return Ok(CompactionOutcome::Skipped);
             }
         };
@@ -716,7 +2043,7 @@ impl EventStore for PostgresEventStore {
         .bind(&stream_id_str)
         .fetch_one(&mut *tx)
         .await
-        .map_err(crate::db_error)?;
+        .map_err(|e| self.map_db_error(e))?;
 
         // 4. Archive all current (un-versioned) events for this stream.
         sqlx::query(
@@ -726,38 +2053,54 @@ impl EventStore for PostgresEventStore {
         .bind(&stream_id_str)
         .execute(&mut *tx)
         .await
-        .map_err(crate::db_error)?;
+        .map_err(|e| self.map_db_error(e))?;
 
         // 5. Reset the stream's version counter so compacted events start from 1.
         sqlx::query("UPDATE streams SET version = 0 WHERE id = $1")
             .bind(&stream_id_str)
             .execute(&mut *tx)
             .await
-            .map_err(crate::db_error)?;
+            .map_err(|e| self.map_db_error(e))?;
 
         // 6. Insert compacted events as the new current stream (aggregate_version = NULL).
         //    These synthetic rows are marked compacted_snapshot = TRUE so the Policy feed
         //    can skip them; the archived originals (above) carry the true history.
         let stream_type = A::stream_type();
-        let meta_json = metadata.to_json();
         for (seq, event) in compacted.iter().enumerate() {
             let event_type = event.event_type();
             let data = serde_json::to_value(event).map_err(crate::ser_error)?;
             let version = (seq as i64) + 1;
+            let data = self.encrypt_payload(&stream_id_str, data_key, &data)?;
+            let encoded = self.encode_payload(&data)?;
+            let event_metadata = versioned_metadata(&metadata, event).to_json();
+            let signature = match &self.signer {
+                Some(signer) => Some(signer.sign(&signing::message(
+                    &stream_id_str,
+                    version,
+                    event_type,
+                    &data,
+                    &event_metadata,
+                )?)?),
+                None => None,
+            };
 
             sqlx::query(
-                "INSERT INTO events (id, data, metadata, stream_id, type, version, aggregate_version, compacted_snapshot)
-                 VALUES ($1, $2, $3, $4, $5, $6, NULL, TRUE)",
+                "INSERT INTO events (id, data, metadata, stream_id, type, version, aggregate_version, compacted_snapshot,
+                                     data_binary, data_encoding, signature)
+                 VALUES ($1, $2, $3, $4, $5, $6, NULL, TRUE, $7, $8, $9)",
             )
-            .bind(Uuid::new_v4())
-            .bind(&data)
-            .bind(&meta_json)
+            .bind(self.id_generator.next_id())
+            .bind(encoded.is_none().then_some(&data))
+            .bind(event_metadata)
             .bind(&stream_id_str)
-            .bind(&event_type)
+            .bind(event_type)
             .bind(version)
+            .bind(encoded.as_ref().map(|(_, bytes)| bytes))
+            .bind(encoded.as_ref().map(|(encoding, _)| encoding))
+            .bind(signature)
             .execute(&mut *tx)
             .await
-            .map_err(crate::db_error)?;
+            .map_err(|e| self.map_db_error(e))?;
         }
 
         // 7. Update the stream version to the count of compacted events, and advance the
@@ -768,18 +2111,748 @@ impl EventStore for PostgresEventStore {
             "UPDATE streams SET version = $1, type = $2, last_compacted_version = $1 WHERE id = $3",
         )
         .bind(new_stream_version)
-        .bind(&stream_type)
+        .bind(stream_type)
         .bind(&stream_id_str)
         .execute(&mut *tx)
         .await
-        .map_err(crate::db_error)?;
+        .map_err(|e| self.map_db_error(e))?;
 
-        tx.commit().await.map_err(crate::db_error)?;
+        tx.commit().await.map_err(|e| self.map_db_error(e))?;
 
         Ok(CompactionOutcome::Compacted {
             archive_version: next_version,
         })
     }
+
+    /// Deletes the replaced rows, so like [`apply_retention`](Self::apply_retention) it needs
+    /// the `0022_event_retention` migration: their global positions become holes below the
+    /// retention horizon, which readers don't wait for.
+    async fn compact_stream<S>(
+        &self,
+        stream_id: &S::StreamId,
+        up_to_version: i64,
+        metadata: replay::Metadata,
+    ) -> Result<u64, replay::Error>
+    where
+        S: Snapshottable,
+    {
+        let urn = stream_id.stream_urn();
+        let stream_id_str = urn.to_string();
+        let map_error = |e| {
+            self.map_db_error(e)
+                .with_operation("compact_stream")
+                .with_context("stream_id", &stream_id_str)
+        };
+        // Every replaced position must be settled, or its hole would read as an
+        // in-flight append below the new horizon.
+        let horizon = self.contiguous_high_water_mark().await?;
+
+        let mut tx = self.pool.begin().await.map_err(map_error)?;
+        self.set_write_tenant(&mut tx, &metadata, "compact_stream")
+            .await?;
+
+        // Lock the stream so no append or compaction interleaves with the rewrite.
+        let locked = sqlx::query("SELECT id FROM streams WHERE id = $1 FOR UPDATE")
+            .bind(&stream_id_str)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_error)?;
+        if locked.rows_affected() == 0 {
+            return Err(replay::Error::not_found("Stream not found")
+                .with_operation("compact_stream")
+                .with_context("stream_id", &stream_id_str));
+        }
+
+        let data_key = self.data_key(&mut tx, &stream_id_str).await?;
+        let data_key = data_key.as_deref();
+        let rows = sqlx::query(
+            "SELECT data, data_binary, data_encoding, version, created, global_position FROM events \
+             WHERE stream_id = $1 AND aggregate_version IS NULL AND version <= $2 ORDER BY version",
+        )
+        .bind(&stream_id_str)
+        .bind(up_to_version)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(map_error)?;
+        let Some(last) = rows.last().filter(|_| rows.len() > 1) else {
+            return Ok(0);
+        };
+        let version: i64 = last.get("version");
+        let created: chrono::DateTime<Utc> = last.get("created");
+        let global_position: i64 = last.get("global_position");
+        if global_position > horizon {
+            return Err(replay::Error::conflict(
+                "events to replace are not settled yet; retry shortly",
+            )
+            .with_operation("compact_stream")
+            .with_context("stream_id", &stream_id_str));
+        }
+
+        // Folded in a block so the state, which needn't be `Send`, isn't held across an await.
+        let snapshot = {
+            let mut state = S::with_id(stream_id.clone());
+            for row in &rows {
+                let mut data = stored_data(row, &*self.serializer)?;
+                if let Some(encryption) = &self.encryption {
+                    data = encryption.decrypt(&stream_id_str, data_key, data)?;
+                }
+                state.apply(serde_json::from_value(data).map_err(crate::deser_error)?);
+            }
+            state.snapshot_event()
+        };
+        let event_type = snapshot.event_type();
+        let data = serde_json::to_value(&snapshot).map_err(crate::ser_error)?;
+        let data = self.encrypt_payload(&stream_id_str, data_key, &data)?;
+        let encoded = self.encode_payload(&data)?;
+        let event_metadata = versioned_metadata(&metadata, &snapshot).to_json();
+        let signature = match &self.signer {
+            Some(signer) => Some(signer.sign(&signing::message(
+                &stream_id_str,
+                version,
+                event_type,
+                &data,
+                &event_metadata,
+            )?)?),
+            None => None,
+        };
+
+        sqlx::query(
+            "INSERT INTO event_retention (horizon) VALUES ($1) \
+             ON CONFLICT (id) DO UPDATE \
+                SET horizon = GREATEST(event_retention.horizon, EXCLUDED.horizon)",
+        )
+        .bind(horizon)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_error)?;
+        sqlx::query(
+            "DELETE FROM events WHERE stream_id = $1 AND aggregate_version IS NULL AND version <= $2",
+        )
+        .bind(&stream_id_str)
+        .bind(up_to_version)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_error)?;
+        // The snapshot takes the place of the last replaced event; like compacted rows it is
+        // marked `compacted_snapshot` so policies don't react to it.
+        sqlx::query(
+            "INSERT INTO events (id, data, metadata, stream_id, type, version, created, global_position,
+                                 aggregate_version, compacted_snapshot, data_binary, data_encoding, signature)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NULL, TRUE, $9, $10, $11)",
+        )
+        .bind(self.id_generator.next_id())
+        .bind(encoded.is_none().then_some(&data))
+        .bind(event_metadata)
+        .bind(&stream_id_str)
+        .bind(event_type)
+        .bind(version)
+        .bind(created)
+        .bind(global_position)
+        .bind(encoded.as_ref().map(|(_, bytes)| bytes))
+        .bind(encoded.as_ref().map(|(encoding, _)| encoding))
+        .bind(signature)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_error)?;
+
+        tx.commit().await.map_err(map_error)?;
+        Ok(rows.len() as u64)
+    }
+
+    /// Soft deletion sets `streams.deleted` (migration `0023_stream_deletion`). Hard deletion
+    /// also drops the stream's data key, and its holes in `global_position` are recorded
+    /// like [retention](Self::apply_retention) ones. Like the other maintenance operations,
+    /// run it as a role not bound by row-level security.
+    async fn delete_stream(
+        &self,
+        stream_id: &impl StreamId,
+        mode: DeletionMode,
+    ) -> Result<(), replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        let stream_id_str = stream_id.to_string();
+        let map_error = |e| {
+            self.map_db_error(e)
+                .with_operation("delete_stream")
+                .with_context("stream_id", &stream_id_str)
+        };
+        let not_found = || {
+            replay::Error::not_found("Stream not found")
+                .with_operation("delete_stream")
+                .with_context("stream_id", &stream_id_str)
+        };
+
+        if mode == DeletionMode::Soft {
+            let marked =
+                sqlx::query("UPDATE streams SET deleted = COALESCE(deleted, now()) WHERE id = $1")
+                    .bind(&stream_id_str)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(map_error)?;
+            return match marked.rows_affected() {
+                0 => Err(not_found()),
+                _ => Ok(()),
+            };
+        }
+
+        // Every removed position must be settled, or its hole would read as an in-flight
+        // append below the new horizon.
+        let horizon = self.contiguous_high_water_mark().await?;
+        let mut tx = self.pool.begin().await.map_err(map_error)?;
+        let locked = sqlx::query("SELECT id FROM streams WHERE id = $1 FOR UPDATE")
+            .bind(&stream_id_str)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_error)?;
+        if locked.rows_affected() == 0 {
+            return Err(not_found());
+        }
+        let last: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(global_position) FROM events WHERE stream_id = $1")
+                .bind(&stream_id_str)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(map_error)?;
+        if last.is_some_and(|last| last > horizon) {
+            return Err(replay::Error::conflict(
+                "events to delete are not settled yet; retry shortly",
+            )
+            .with_operation("delete_stream")
+            .with_context("stream_id", &stream_id_str));
+        }
+
+        sqlx::query(
+            "INSERT INTO event_retention (horizon) VALUES ($1) \
+             ON CONFLICT (id) DO UPDATE \
+                SET horizon = GREATEST(event_retention.horizon, EXCLUDED.horizon)",
+        )
+        .bind(horizon)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_error)?;
+        for statement in [
+            "DELETE FROM events WHERE stream_id = $1",
+            "DELETE FROM streams WHERE id = $1",
+            "DELETE FROM stream_keys WHERE stream_id = $1",
+        ] {
+            sqlx::query(statement)
+                .bind(&stream_id_str)
+                .execute(&mut *tx)
+                .await
+                .map_err(map_error)?;
+        }
+        tx.commit().await.map_err(map_error)?;
+        Ok(())
+    }
+
+    async fn is_deleted(&self, stream_id: &impl StreamId) -> Result<bool, replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        let deleted: Option<bool> =
+            sqlx::query_scalar("SELECT deleted IS NOT NULL FROM streams WHERE id = $1")
+                .bind(stream_id.to_string())
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| {
+                    self.map_db_error(e)
+                        .with_operation("is_deleted")
+                        .with_context("stream_id", stream_id)
+                })?;
+        Ok(deleted.unwrap_or(false))
+    }
+
+    /// Truncated positions are recorded like [retention](Self::apply_retention) ones; the
+    /// stream's head stays in `streams.version`.
+    async fn truncate_stream(
+        &self,
+        stream_id: &impl StreamId,
+        before_version: i64,
+    ) -> Result<u64, replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        let stream_id_str = stream_id.to_string();
+        let map_error = |e| {
+            self.map_db_error(e)
+                .with_operation("truncate_stream")
+                .with_context("stream_id", &stream_id_str)
+        };
+        // Every removed position must be settled, or its hole would read as an in-flight
+        // append below the new horizon.
+        let horizon = self.contiguous_high_water_mark().await?;
+        let mut tx = self.pool.begin().await.map_err(map_error)?;
+        let head: Option<i64> =
+            sqlx::query_scalar("SELECT version FROM streams WHERE id = $1 FOR UPDATE")
+                .bind(&stream_id_str)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(map_error)?;
+        let Some(head) = head else {
+            return Err(replay::Error::not_found("Stream not found")
+                .with_operation("truncate_stream")
+                .with_context("stream_id", &stream_id_str));
+        };
+        // Appends continue from the head, so its event is never truncated.
+        let before_version = before_version.min(head);
+
+        let last: Option<i64> = sqlx::query_scalar(
+            "SELECT MAX(global_position) FROM events \
+             WHERE stream_id = $1 AND aggregate_version IS NULL AND version < $2",
+        )
+        .bind(&stream_id_str)
+        .bind(before_version)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(map_error)?;
+        let Some(last) = last else {
+            return Ok(0);
+        };
+        if last > horizon {
+            return Err(replay::Error::conflict(
+                "events to truncate are not settled yet; retry shortly",
+            )
+            .with_operation("truncate_stream")
+            .with_context("stream_id", &stream_id_str));
+        }
+
+        sqlx::query(
+            "INSERT INTO event_retention (horizon) VALUES ($1) \
+             ON CONFLICT (id) DO UPDATE \
+                SET horizon = GREATEST(event_retention.horizon, EXCLUDED.horizon)",
+        )
+        .bind(horizon)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_error)?;
+        let truncated = sqlx::query(
+            "DELETE FROM events WHERE stream_id = $1 AND aggregate_version IS NULL AND version < $2",
+        )
+        .bind(&stream_id_str)
+        .bind(before_version)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_error)?;
+        tx.commit().await.map_err(map_error)?;
+        Ok(truncated.rows_affected())
+    }
+
+    /// Limits are `streams.max_age` and `streams.max_count` (migration
+    /// `0024_stream_retention`).
+    async fn set_stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+        retention: StreamRetention,
+    ) -> Result<(), replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        let updated = sqlx::query(
+            "UPDATE streams SET max_age = make_interval(secs => $2), max_count = $3 WHERE id = $1",
+        )
+        .bind(stream_id.to_string())
+        .bind(retention.max_age().map(|max_age| max_age.as_secs_f64()))
+        .bind(
+            retention
+                .max_count()
+                .map(|max_count| max_count.min(i64::MAX as u64) as i64),
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            self.map_db_error(e)
+                .with_operation("set_stream_retention")
+                .with_context("stream_id", stream_id)
+        })?;
+        if updated.rows_affected() == 0 {
+            return Err(replay::Error::not_found("Stream not found")
+                .with_operation("set_stream_retention")
+                .with_context("stream_id", stream_id));
+        }
+        Ok(())
+    }
+
+    async fn stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> Result<StreamRetention, replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        let limits: Option<(Option<f64>, Option<i64>)> = sqlx::query_as(
+            "SELECT EXTRACT(EPOCH FROM max_age)::float8, max_count FROM streams WHERE id = $1",
+        )
+        .bind(stream_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            self.map_db_error(e)
+                .with_operation("stream_retention")
+                .with_context("stream_id", stream_id)
+        })?;
+        let Some((max_age, max_count)) = limits else {
+            return Err(replay::Error::not_found("Stream not found")
+                .with_operation("stream_retention")
+                .with_context("stream_id", stream_id));
+        };
+        let mut retention = StreamRetention::default();
+        if let Some(max_age) = max_age {
+            retention = retention.with_max_age(Duration::from_secs_f64(max_age.max(0.0)));
+        }
+        if let Some(max_count) = max_count {
+            retention = retention.with_max_count(max_count.max(0) as u64);
+        }
+        Ok(retention)
+    }
+
+    /// Truncates each stream with expired events in its own transaction; a stream with
+    /// events still committing around it (a `Conflict`) is left for the next run.
+    async fn enforce_stream_retention(&self) -> Result<u64, replay::Error> {
+        let expired: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT e.stream_id, MAX(e.version) + 1 FROM events e JOIN streams s ON s.id = e.stream_id \
+             WHERE e.aggregate_version IS NULL AND e.version < s.version \
+               AND (e.version <= s.version - s.max_count OR e.created < now() - s.max_age) \
+             GROUP BY e.stream_id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            self.map_db_error(e)
+                .with_operation("enforce_stream_retention")
+        })?;
+
+        let mut removed = 0;
+        for (stream_id, retained_from) in expired {
+            let stream_id = Urn::try_from(stream_id.clone()).map_err(|e| {
+                replay::Error::internal("failed to parse persisted stream_id as URN")
+                    .with_operation("enforce_stream_retention")
+                    .with_context("stream_id", &stream_id)
+                    .with_source(e)
+            })?;
+            match self.truncate_stream(&stream_id, retained_from).await {
+                Ok(truncated) => removed += truncated,
+                Err(error) if matches!(error.kind(), ErrorKind::Conflict | ErrorKind::NotFound) => {
+                    tracing::debug!(%stream_id, error = %error, "stream retention deferred");
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(removed)
+    }
+
+    async fn set_stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+        metadata: StreamMetadata,
+    ) -> Result<(), replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        let updated = sqlx::query("UPDATE streams SET metadata = $2 WHERE id = $1")
+            .bind(stream_id.to_string())
+            .bind(sqlx::types::Json(&metadata))
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                self.map_db_error(e)
+                    .with_operation("set_stream_metadata")
+                    .with_context("stream_id", stream_id)
+            })?;
+        if updated.rows_affected() == 0 {
+            return Err(replay::Error::not_found("Stream not found")
+                .with_operation("set_stream_metadata")
+                .with_context("stream_id", stream_id));
+        }
+        Ok(())
+    }
+
+    async fn stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> Result<StreamMetadata, replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        let metadata: Option<sqlx::types::Json<StreamMetadata>> =
+            sqlx::query_scalar("SELECT metadata FROM streams WHERE id = $1")
+                .bind(stream_id.to_string())
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| {
+                    self.map_db_error(e)
+                        .with_operation("stream_metadata")
+                        .with_context("stream_id", stream_id)
+                })?;
+        match metadata {
+            Some(sqlx::types::Json(metadata)) => Ok(metadata),
+            None => Err(replay::Error::not_found("Stream not found")
+                .with_operation("stream_metadata")
+                .with_context("stream_id", stream_id)),
+        }
+    }
+
+    /// Streams are ordered by `streams.id` under the database's collation.
+    async fn list_streams(
+        &self,
+        stream_type: Option<&str>,
+        page: StreamPage,
+    ) -> Result<StreamListing, replay::Error> {
+        let map_error = |e| self.map_db_error(e).with_operation("list_streams");
+        // One row past the limit tells whether another page follows.
+        let rows = sqlx::query(
+            "SELECT s.id, s.type, s.version, MAX(e.created) AS updated \
+             FROM streams s JOIN events e ON e.stream_id = s.id \
+             WHERE s.deleted IS NULL AND ($1::text IS NULL OR s.type = $1) \
+               AND ($2::text IS NULL OR s.id > $2) \
+               AND COALESCE(s.metadata -> 'labels', '{}'::jsonb) @> $4 \
+             GROUP BY s.id ORDER BY s.id LIMIT $3",
+        )
+        .bind(stream_type)
+        .bind(page.cursor().map(ToString::to_string))
+        .bind(page.limit().saturating_add(1).min(i64::MAX as usize) as i64)
+        .bind(sqlx::types::Json(page.labels()))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_error)?;
+        let more = rows.len() > page.limit();
+
+        let mut streams = Vec::with_capacity(rows.len().min(page.limit()));
+        for row in rows.into_iter().take(page.limit()) {
+            let id: String = row.get("id");
+            let stream_id = Urn::try_from(id.clone()).map_err(|e| {
+                replay::Error::internal("failed to parse persisted stream_id as URN")
+                    .with_operation("list_streams")
+                    .with_context("stream_id", id)
+                    .with_source(e)
+            })?;
+            streams.push(StreamSummary {
+                stream_id,
+                stream_type: row.get("type"),
+                version: row.get("version"),
+                updated: row.get("updated"),
+            });
+        }
+        Ok(StreamListing {
+            next_page: page.next(&streams, more),
+            streams,
+        })
+    }
+
+    async fn stream_info(&self, stream_id: &impl StreamId) -> Result<StreamState, replay::Error> {
+        let stream_id = &stream_id.stream_urn();
+        let row = sqlx::query(
+            "SELECT s.version, s.deleted IS NOT NULL AS deleted, \
+                    MIN(e.created) AS created, MAX(e.created) AS updated \
+             FROM streams s LEFT JOIN events e ON e.stream_id = s.id \
+             WHERE s.id = $1 GROUP BY s.id",
+        )
+        .bind(stream_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            self.map_db_error(e)
+                .with_operation("stream_info")
+                .with_context("stream_id", stream_id)
+        })?;
+        Ok(row.map_or_else(StreamState::default, |row| StreamState {
+            exists: true,
+            version: row.get("version"),
+            created: row.get("created"),
+            updated: row.get("updated"),
+            deleted: row.get("deleted"),
+        }))
+    }
+
+    /// Moves the `streams` row, the events and the data key in one transaction. Encrypted
+    /// values are sealed again for the new id, and with a signer, signed events are checked
+    /// and signed again for it, since ciphertexts and signatures both cover it. Like the other
+    /// maintenance operations, run it as a role not bound by row-level security.
+    async fn rename_stream(
+        &self,
+        old_id: &impl StreamId,
+        new_id: &impl StreamId,
+    ) -> Result<(), replay::Error> {
+        let old_id = &old_id.stream_urn();
+        let new_id = &new_id.stream_urn();
+        let old_id_str = old_id.to_string();
+        let new_id_str = new_id.to_string();
+        let map_error = |e| {
+            self.map_db_error(e)
+                .with_operation("rename_stream")
+                .with_context("stream_id", &old_id_str)
+                .with_context("new_stream_id", &new_id_str)
+        };
+
+        let mut tx = self.pool.begin().await.map_err(map_error)?;
+        let locked = sqlx::query("SELECT id FROM streams WHERE id = $1 FOR UPDATE")
+            .bind(&old_id_str)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_error)?;
+        if locked.rows_affected() == 0 {
+            return Err(replay::Error::not_found("Stream not found")
+                .with_operation("rename_stream")
+                .with_context("stream_id", &old_id_str));
+        }
+        // Events reference `streams`, so the new row goes in before they move and the old
+        // one comes out after.
+        let created = sqlx::query(
+            "INSERT INTO streams (id, type, version, last_compacted_version, deleted, max_age, max_count, \
+                                  metadata, tenant_id) \
+             SELECT $2, type, version, last_compacted_version, deleted, max_age, max_count, \
+                    metadata, tenant_id \
+             FROM streams WHERE id = $1 \
+             ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(&old_id_str)
+        .bind(&new_id_str)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_error)?;
+        if created.rows_affected() == 0 {
+            return Err(replay::Error::conflict("target stream already exists")
+                .with_operation("rename_stream")
+                .with_context("stream_id", &old_id_str)
+                .with_context("new_stream_id", &new_id_str));
+        }
+
+        // Encrypted values are bound to their stream id, so they are sealed again for the
+        // new one. A forgotten stream's values can't be opened and stay as they are.
+        let data_key = match &self.encryption {
+            Some(encryption) => sqlx::query_scalar::<_, Option<Vec<u8>>>(
+                "SELECT key FROM stream_keys WHERE stream_id = $1",
+            )
+            .bind(&old_id_str)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(map_error)?
+            .flatten()
+            .map(|key| encryption.data_key(&key))
+            .transpose()?,
+            None => None,
+        };
+
+        if self.signer.is_some() || data_key.is_some() {
+            let rows = sqlx::query(
+                "SELECT id, data, data_binary, data_encoding, metadata, stream_id, type, version, \
+                        signature \
+                 FROM events WHERE stream_id = $1 AND (signature IS NOT NULL OR $2)",
+            )
+            .bind(&old_id_str)
+            .bind(data_key.is_some())
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(map_error)?;
+            let mut signed_ids = Vec::with_capacity(rows.len());
+            let mut signatures = Vec::with_capacity(rows.len());
+            let mut sealed_ids = Vec::with_capacity(rows.len());
+            let mut json_data = Vec::with_capacity(rows.len());
+            let mut binary_data = Vec::with_capacity(rows.len());
+            let mut encodings = Vec::with_capacity(rows.len());
+            for row in &rows {
+                let id: Uuid = row.get("id");
+                let mut payload = stored_data(row, &*self.serializer)?;
+                let signer = self
+                    .signer
+                    .as_deref()
+                    .filter(|_| row.get::<Option<&[u8]>, _>("signature").is_some());
+                if let Some(signer) = signer {
+                    if signature_matches(row, &payload, signer)? == Some(false) {
+                        return Err(replay::Error::internal(
+                            "event signature doesn't match its contents",
+                        )
+                        .with_operation("rename_stream")
+                        .with_context("event_id", id)
+                        .with_context("stream_id", &old_id_str));
+                    }
+                }
+                if let (Some(encryption), Some(data_key)) = (&self.encryption, &data_key) {
+                    payload = encryption.rebind(&**data_key, &old_id_str, &new_id_str, payload)?;
+                    sealed_ids.push(id);
+                    match self.encode_payload(&payload)? {
+                        Some((encoding, bytes)) => {
+                            json_data.push(None);
+                            binary_data.push(Some(bytes));
+                            encodings.push(Some(encoding));
+                        }
+                        None => {
+                            json_data.push(Some(payload.clone()));
+                            binary_data.push(None);
+                            encodings.push(None);
+                        }
+                    }
+                }
+                if let Some(signer) = signer {
+                    let message = signing::message(
+                        &new_id_str,
+                        row.get("version"),
+                        row.get("type"),
+                        &payload,
+                        &row.get::<Value, _>("metadata"),
+                    )?;
+                    signed_ids.push(id);
+                    signatures.push(signer.sign(&message)?);
+                }
+            }
+            sqlx::query(
+                "UPDATE events SET data = sealed.data, data_binary = sealed.data_binary, \
+                        data_encoding = sealed.data_encoding \
+                 FROM UNNEST($1::uuid[], $2::jsonb[], $3::bytea[], $4::text[]) \
+                      AS sealed(id, data, data_binary, data_encoding) \
+                 WHERE events.id = sealed.id",
+            )
+            .bind(&sealed_ids)
+            .bind(&json_data)
+            .bind(&binary_data)
+            .bind(&encodings)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_error)?;
+            sqlx::query(
+                "UPDATE events SET signature = signed.signature \
+                 FROM UNNEST($1::uuid[], $2::bytea[]) AS signed(id, signature) \
+                 WHERE events.id = signed.id",
+            )
+            .bind(&signed_ids)
+            .bind(&signatures)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_error)?;
+        }
+
+        for statement in [
+            "UPDATE events SET stream_id = $2 WHERE stream_id = $1",
+            "UPDATE stream_keys SET stream_id = $2 WHERE stream_id = $1",
+            "DELETE FROM streams WHERE id = $1",
+        ] {
+            sqlx::query(statement)
+                .bind(&old_id_str)
+                .bind(&new_id_str)
+                .execute(&mut *tx)
+                .await
+                .map_err(map_error)?;
+        }
+        tx.commit().await.map_err(map_error)?;
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<StoreHealth, replay::Error> {
+        let started = Instant::now();
+        // `to_regclass` resolves through the search path, so this also catches a store
+        // pointed at a schema the migrations never ran in.
+        let probe = sqlx::query_scalar::<_, bool>("SELECT to_regclass('events') IS NOT NULL")
+            .fetch_one(&self.pool);
+        let migrated = tokio::time::timeout(HEALTH_CHECK_TIMEOUT, probe)
+            .await
+            .map_err(|_| {
+                replay::Error::unavailable("health check timed out")
+                    .with_operation("health_check")
+                    .with_context("timeout", format!("{HEALTH_CHECK_TIMEOUT:?}"))
+            })?
+            .map_err(|e| self.map_db_error(e).with_operation("health_check"))?;
+        let latency = started.elapsed();
+
+        if !migrated {
+            return Err(replay::Error::unavailable(
+                "the events table does not exist; run the migrations",
+            )
+            .with_operation("health_check"));
+        }
+
+        Ok(StoreHealth {
+            latency,
+            connections: Some(self.pool.size()),
+            idle_connections: Some(self.pool.num_idle() as u32),
+            max_connections: Some(self.pool.options().get_max_connections()),
+        })
+    }
 }
 
 impl Clone for PostgresEventStore {
@@ -787,46 +2860,115 @@ impl Clone for PostgresEventStore {
         Self {
             pool: self.pool.clone(),
             projections: self.projections.clone(),
+            db_error_mapper: self.db_error_mapper.clone(),
+            id_generator: self.id_generator.clone(),
+            read_options: self.read_options,
+            serializer: self.serializer.clone(),
+            #[cfg(feature = "compression")]
+            compression: self.compression,
+            slow_operation_threshold: self.slow_operation_threshold,
+            encryption: self.encryption.clone(),
+            signer: self.signer.clone(),
+            row_level_security: self.row_level_security,
+            correlation_links: self.correlation_links,
+            credentials: self.credentials.clone(),
         }
     }
 }
 
+/// The payload of an `events` row, decoded from `data_binary` when it has an encoding.
+fn stored_data(row: &PgRow, serializer: &dyn EventSerializer) -> Result<Value, replay::Error> {
+    match row.get::<Option<&str>, _>("data_encoding") {
+        None => Ok(row.get("data")),
+        Some(encoding) => serializer::decode(encoding, row.get("data_binary"), serializer),
+    }
+}
+
+/// Whether the signature of an `events` row matches its stored `payload`; `None` when the
+/// row is unsigned.
+fn signature_matches(
+    row: &PgRow,
+    payload: &Value,
+    signer: &dyn EventSigner,
+) -> Result<Option<bool>, replay::Error> {
+    let Some(signature) = row.get::<Option<&[u8]>, _>("signature") else {
+        return Ok(None);
+    };
+    let message = signing::message(
+        row.get("stream_id"),
+        row.get("version"),
+        row.get("type"),
+        payload,
+        &row.get::<Value, _>("metadata"),
+    )?;
+    Ok(Some(signer.verify(&message, signature)))
+}
+
+/// Decodes rows written in JSON or one of the enabled built-in formats.
 impl<D: DeserializeOwned> TryFrom<PgRow> for PersistedEvent<D> {
     type Error = replay::Error;
 
     fn try_from(value: PgRow) -> Result<Self, replay::Error> {
-        let id: Uuid = value.get("id");
-
-        let data_raw: Value = value.get("data");
-        let data: D = serde_json::from_value(data_raw.clone()).map_err(|e| {
-            crate::deser_error(e)
-                .with_context("operation", "serde json from store")
-                .with_context("stored_json", data_raw.clone())
-        })?;
+        event_from_row(value, &JsonSerializer, None, None)
+    }
+}
 
-        let stream_id_string: String = value.get("stream_id");
-        let stream_id: Urn = Urn::try_from(stream_id_string.clone()).map_err(|e| {
-            replay::Error::internal("failed to parse persisted stream_id as URN")
-                .with_operation("postgres_row_to_persisted_event")
-                .with_context("stream_id", stream_id_string)
-                .with_source(e)
-        })?;
-        let r#type: String = value.get("type");
-        let version: i64 = value.get("version");
-        let created: chrono::DateTime<Utc> = value.get("created");
-        let metadata: Value = value.get("metadata");
-        let metadata: Metadata = Metadata::new(metadata);
-        let aggregate_version: Option<i32> = value.get("aggregate_version");
-
-        Ok(PersistedEvent {
-            id,
-            data,
-            stream_id,
-            r#type,
-            version,
-            created,
-            metadata,
-            aggregate_version,
-        })
+/// An `events` row as a [`PersistedEvent`], trying `serializer` for binary payloads before
+/// the built-in formats. With `keys`, the row must select its `data_key` (see
+/// [`PostgresEventStore::data_key_column`]); with `signer`, its `signature` (see
+/// [`PostgresEventStore::signature_column`]), and a signature that doesn't match fails.
+pub(crate) fn event_from_row<D: DeserializeOwned>(
+    value: PgRow,
+    serializer: &dyn EventSerializer,
+    keys: Option<&DataKeys<'_>>,
+    signer: Option<&dyn EventSigner>,
+) -> Result<PersistedEvent<D>, replay::Error> {
+    let id: Uuid = value.get("id");
+
+    let mut data_raw = stored_data(&value, serializer)?;
+    if let Some(signer) = signer {
+        if signature_matches(&value, &data_raw, signer)? == Some(false) {
+            return Err(
+                replay::Error::internal("event signature doesn't match its contents")
+                    .with_operation("verify_event")
+                    .with_context("event_id", id)
+                    .with_context("stream_id", value.get::<&str, _>("stream_id")),
+            );
+        }
+    }
+    if let Some(keys) = keys {
+        data_raw = keys.decrypt(value.get("stream_id"), value.get("data_key"), data_raw)?;
     }
+    let data: D = serde_json::from_value(data_raw.clone()).map_err(|e| {
+        crate::deser_error(e)
+            .with_context("operation", "serde json from store")
+            .with_context("stored_json", data_raw.clone())
+    })?;
+
+    let stream_id_string: String = value.get("stream_id");
+    let stream_id: Urn = Urn::try_from(stream_id_string.clone()).map_err(|e| {
+        replay::Error::internal("failed to parse persisted stream_id as URN")
+            .with_operation("postgres_row_to_persisted_event")
+            .with_context("stream_id", stream_id_string)
+            .with_source(e)
+    })?;
+    let r#type: String = value.get("type");
+    let version: i64 = value.get("version");
+    let created: chrono::DateTime<Utc> = value.get("created");
+    let metadata: Value = value.get("metadata");
+    let metadata: Metadata = Metadata::new(metadata);
+    let aggregate_version: Option<i32> = value.get("aggregate_version");
+    let global_position: i64 = value.get("global_position");
+
+    Ok(PersistedEvent {
+        id,
+        data,
+        stream_id,
+        r#type,
+        version,
+        created,
+        metadata,
+        aggregate_version,
+        global_position,
+    })
 }
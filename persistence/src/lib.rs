@@ -1,22 +1,86 @@
 mod aggregate_version;
+#[cfg(feature = "asyncapi")]
+mod asyncapi;
+#[cfg(feature = "avro")]
+mod avro;
+mod backup;
+mod chaos;
+mod clock;
+#[cfg(feature = "axum")]
+mod command_router;
+mod compression;
+pub mod conformance;
 mod cqrs;
+mod credentials;
+mod encryption;
 mod error;
 mod filters;
+mod follower;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+mod id_generator;
 mod infrastructure;
 mod inline_projection;
+pub mod metrics;
+mod migrating;
+mod ndjson;
+mod offline_sync;
+#[cfg(feature = "parquet")]
+mod parquet_export;
 mod persisted_event;
 mod policy;
 mod policy_runner;
 mod policy_status;
+mod pool_options;
 mod query;
+mod query_bus;
+mod rate_limit;
+mod read_options;
+mod replication;
+mod retention;
+mod serializer;
+mod sharded;
+mod signing;
+mod statistics;
 mod store;
+mod tenant;
+pub mod testing;
+mod tiering;
+mod timeline;
+#[cfg(feature = "opentelemetry")]
+mod trace_context;
+mod verify;
 
 pub use aggregate_version::AggregateVersion;
-pub use cqrs::Cqrs;
-pub use error::{concurrency_error, db_error, deser_error, ser_error};
+#[cfg(feature = "asyncapi")]
+pub use asyncapi::AsyncApi;
+#[cfg(feature = "avro")]
+pub use avro::AvroSerializer;
+pub use backup::{backup, restore, BackupManifest, BackupStore, RestoreReport};
+pub use chaos::ChaosEventStore;
+pub use clock::{Clock, SteppingClock, SystemClock};
+#[cfg(feature = "axum")]
+pub use command_router::{
+    CommandRouter, ACTOR_HEADER, CAUSATION_ID_HEADER, CORRELATION_ID_HEADER, TENANT_ID_HEADER,
+};
+#[cfg(feature = "compression")]
+pub use compression::Compression;
+pub use cqrs::{Cqrs, MetadataValidator};
+pub use credentials::{CredentialProvider, PostgresCredentials};
+#[cfg(feature = "encryption")]
+pub use encryption::AesGcmCrypto;
+pub use encryption::{DataKey, Encryption, EventCrypto};
+pub use error::{concurrency_error, db_error, deser_error, ser_error, DbErrorMapper};
 pub use filters::StreamFilter;
+pub use follower::{Follower, FollowerDaemon, FollowerStatus};
+pub use id_generator::{IdGenerator, SequentialIds, UuidV7};
 pub use infrastructure::{InMemoryEventStore, PostgresEventStore, PostgresInlineProjection};
 pub use inline_projection::InlineProjection;
+pub use migrating::{MigratingEventStore, MigrationPrimary};
+pub use ndjson::{export_events, read_events, NdjsonEvent};
+pub use offline_sync::{OfflineSync, SyncConflict, SyncCursor, SyncReport, SyncResolution};
+#[cfg(feature = "parquet")]
+pub use parquet_export::{ParquetExport, ParquetExporter, ParquetPartitioning};
 pub use persisted_event::PersistedEvent;
 pub use policy::{Dispatch, Policy, StartAt};
 pub use policy_runner::{
@@ -24,8 +88,35 @@ pub use policy_runner::{
     PolicyRunnerDaemon, REPLAY_NOTIFY_CHANNEL,
 };
 pub use policy_status::{PolicyCondition, PolicyStatus, PolicyStatusStore};
+pub use pool_options::PostgresPoolOptions;
 pub use query::Query;
-pub use store::{CompactionOutcome, EventSink, EventStore, NoSink};
+pub use query_bus::QueryBus;
+pub use rate_limit::{RateLimitRequest, RateLimiter, TokenBucketRateLimiter};
+pub use read_options::ReadOptions;
+pub use replication::{replicate, BulkImport, ReplicationOptions, ReplicationProgress};
+pub use retention::{ColdStorage, RetentionDaemon, RetentionPolicy};
+#[cfg(feature = "cbor")]
+pub use serializer::CborSerializer;
+#[cfg(feature = "msgpack")]
+pub use serializer::MessagePackSerializer;
+#[cfg(feature = "protobuf")]
+pub use serializer::ProtobufSerializer;
+pub use serializer::{EventSerializer, JsonSerializer};
+pub use sharded::ShardedEventStore;
+#[cfg(feature = "signing")]
+pub use signing::{Ed25519Signer, HmacSigner};
+pub use signing::{EventSigner, StreamVerification};
+pub use statistics::StoreStatistics;
+pub use store::{
+    AllEvents, CompactionOutcome, DeletionMode, EventSink, EventStore, NoSink, StoreHealth,
+    StreamListing, StreamMetadata, StreamPage, StreamRetention, StreamState, StreamSummary,
+};
+pub use tenant::{TenantId, TenantScopedEventStore};
+pub use tiering::{HotTier, TieredEventStore, TieringDaemon, TieringPolicy};
+pub use timeline::ExecutionTimeline;
+#[cfg(feature = "opentelemetry")]
+pub use trace_context::{extract_trace_context, inject_trace_context};
+pub use verify::{verify, Anomaly, ConsistencyReport, VerifiableStore};
 
 /// Convenience re-exports of the most commonly used types and traits across
 /// `replay`, `replay_macros`, and `replay_persistence`.
@@ -38,18 +129,22 @@ pub use store::{CompactionOutcome, EventSink, EventStore, NoSink};
 pub mod prelude {
     // Core traits from es-replay
     pub use replay::{
-        Aggregate, Compactable, Error, Event, EventStream, Result, ScopedUrn, WithId,
+        Aggregate, Compactable, Error, Event, EventStream, Result, ScopedUrn, StreamId, WithId,
     };
 
     // Macros from es-replay-macros
-    pub use replay_macros::{define_aggregate, query_events, Event as EventDerive, Urn};
+    pub use replay_macros::{
+        aggregate, commands, define_aggregate, event_stream, events, query_events, services,
+        Event as EventDerive, StreamId, StreamType, Urn,
+    };
 
     // Persistence types from this crate
     pub use super::{
         AggregateVersion, CompactionOutcome, Cqrs, DeadLetterDiscard, DeadLetterRetry,
-        DeadLetterRetrySummary, Dispatch, EventSink, EventStore, InMemoryEventStore,
-        InlineProjection, NoSink, PersistedEvent, Policy, PolicyCondition, PolicyRunner,
-        PolicyRunnerBuilder, PolicyRunnerDaemon, PolicyStatus, PolicyStatusStore,
-        PostgresEventStore, PostgresInlineProjection, Query, StartAt, StreamFilter,
+        DeadLetterRetrySummary, DeletionMode, Dispatch, EventSink, EventStore, IdGenerator,
+        InMemoryEventStore, InlineProjection, NoSink, PersistedEvent, Policy, PolicyCondition,
+        PolicyRunner, PolicyRunnerBuilder, PolicyRunnerDaemon, PolicyStatus, PolicyStatusStore,
+        PostgresEventStore, PostgresInlineProjection, PostgresPoolOptions, Query, QueryBus,
+        ReadOptions, StartAt, StreamFilter, StreamRetention, TenantId, TenantScopedEventStore,
     };
 }
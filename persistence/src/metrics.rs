@@ -0,0 +1,223 @@
+//! Store, command and policy metrics (feature `metrics`).
+//!
+//! Recorded through the [`metrics`](https://docs.rs/metrics) facade, so any installed
+//! recorder receives them, e.g. `metrics-exporter-prometheus`. Without the feature the
+//! recording functions compile to nothing.
+//!
+//! | Metric | Type | Labels |
+//! |--------|------|--------|
+//! | [`EVENTS_APPENDED`] | counter | `store`, `stream_type` |
+//! | [`APPEND_DURATION`] | histogram, seconds | `store`, `stream_type` |
+//! | [`APPEND_CONFLICTS`] | counter | `store`, `stream_type` |
+//! | [`EVENTS_STREAMED`] | counter | `store` |
+//! | [`COMMAND_FAILURES`] | counter | `aggregate`, `kind` |
+//! | [`POLICY_LAG`] | gauge, events | `policy` |
+//! | [`REPLICATION_LAG`] | gauge, positions | `stream_type` |
+//!
+//! `store` is `postgres` or `in_memory`. `kind` is the snake-cased [`replay::ErrorKind`] of
+//! the failure, or `domain` for an aggregate error that doesn't wrap a `replay::Error`.
+
+use std::time::Duration;
+
+/// Events committed by `store_events_stream`.
+pub const EVENTS_APPENDED: &str = "replay_events_appended_total";
+/// Time spent in `store_events_stream`, successful or not.
+pub const APPEND_DURATION: &str = "replay_append_duration_seconds";
+/// Appends rejected by the optimistic concurrency check.
+pub const APPEND_CONFLICTS: &str = "replay_append_conflicts_total";
+/// Events yielded by `stream_events` and the reads built on it.
+pub const EVENTS_STREAMED: &str = "replay_events_streamed_total";
+/// `Cqrs::execute` and `Cqrs::execute_with_output` calls that returned an error.
+pub const COMMAND_FAILURES: &str = "replay_command_failures_total";
+/// Events between a policy's cursor and the head of the log, refreshed by
+/// [`PolicyStatusStore::list`](crate::PolicyStatusStore::list).
+pub const POLICY_LAG: &str = "replay_policy_lag";
+/// Global positions between a [`Follower`](crate::Follower)'s replica and its primary,
+/// refreshed after every pass.
+pub const REPLICATION_LAG: &str = "replay_replication_lag";
+
+/// Record one `store_events_stream` call that appended `appended` events.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_append(
+    store: &'static str,
+    stream_type: &str,
+    appended: u64,
+    elapsed: Duration,
+    result: &Result<(), replay::Error>,
+) {
+    #[cfg(feature = "metrics")]
+    {
+        let stream_type = stream_type.to_string();
+        metrics::histogram!(APPEND_DURATION, "store" => store, "stream_type" => stream_type.clone())
+            .record(elapsed);
+        match result {
+            Ok(()) => {
+                metrics::counter!(EVENTS_APPENDED, "store" => store, "stream_type" => stream_type)
+                    .increment(appended)
+            }
+            Err(e) if e.kind() == replay::ErrorKind::Conflict => {
+                metrics::counter!(APPEND_CONFLICTS, "store" => store, "stream_type" => stream_type)
+                    .increment(1)
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+/// Record one event read from `store`.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_streamed(store: &'static str) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(EVENTS_STREAMED, "store" => store).increment(1);
+}
+
+/// Record a failed command against `aggregate`.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_command_failure<E: std::error::Error + 'static>(aggregate: &str, error: &E) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(
+        COMMAND_FAILURES,
+        "aggregate" => aggregate.to_string(),
+        "kind" => error_kind_label(error),
+    )
+    .increment(1);
+}
+
+/// Record how far `policy` trails the head of the log.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_policy_lag(policy: &str, lag: i64) {
+    #[cfg(feature = "metrics")]
+    metrics::gauge!(POLICY_LAG, "policy" => policy.to_string()).set(lag as f64);
+}
+
+/// Record how far the replica of `stream_type` trails its primary.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_replication_lag(stream_type: &str, lag: i64) {
+    #[cfg(feature = "metrics")]
+    metrics::gauge!(REPLICATION_LAG, "stream_type" => stream_type.to_string()).set(lag as f64);
+}
+
+/// The kind of the first `replay::Error` in `error`'s source chain.
+#[cfg(feature = "metrics")]
+fn error_kind_label(error: &(dyn std::error::Error + 'static)) -> &'static str {
+    use replay::ErrorKind;
+
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(error) = error.downcast_ref::<replay::Error>() {
+            return match error.kind() {
+                ErrorKind::NotFound => "not_found",
+                ErrorKind::InvalidInput => "invalid_input",
+                ErrorKind::Conflict => "conflict",
+                ErrorKind::Unavailable => "unavailable",
+                ErrorKind::Internal => "internal",
+                ErrorKind::BusinessRuleViolation => "business_rule_violation",
+                ErrorKind::Unauthorized => "unauthorized",
+                ErrorKind::Forbidden => "forbidden",
+                ErrorKind::RateLimited => "rate_limited",
+                ErrorKind::Unsupported => "unsupported",
+            };
+        }
+        current = error.source();
+    }
+    "domain"
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use metrics_util::{CompositeKey, MetricKind};
+
+    use super::*;
+
+    fn counter(snapshot: &[(CompositeKey, DebugValue)], name: &str) -> Vec<(Vec<String>, u64)> {
+        snapshot
+            .iter()
+            .filter(|(key, _)| key.kind() == MetricKind::Counter && key.key().name() == name)
+            .map(|(key, value)| {
+                let labels = key
+                    .key()
+                    .labels()
+                    .map(|l| format!("{}={}", l.key(), l.value()))
+                    .collect();
+                match value {
+                    DebugValue::Counter(n) => (labels, *n),
+                    other => panic!("{name} is not a counter: {other:?}"),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn appends_count_events_and_conflicts() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, || {
+            record_append("postgres", "Account", 3, Duration::from_millis(2), &Ok(()));
+            let conflict = Err(replay::Error::conflict("stale"));
+            record_append(
+                "postgres",
+                "Account",
+                0,
+                Duration::from_millis(1),
+                &conflict,
+            );
+        });
+        let snapshot: Vec<_> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| (key, value))
+            .collect();
+
+        let labels = vec![
+            "store=postgres".to_string(),
+            "stream_type=Account".to_string(),
+        ];
+        assert_eq!(counter(&snapshot, EVENTS_APPENDED), [(labels.clone(), 3)]);
+        assert_eq!(counter(&snapshot, APPEND_CONFLICTS), [(labels, 1)]);
+    }
+
+    #[test]
+    fn command_failures_are_labelled_by_kind() {
+        #[derive(Debug, thiserror::Error)]
+        enum AccountError {
+            #[error("store failed")]
+            Store(#[source] replay::Error),
+            #[error("overdrawn")]
+            Overdrawn,
+        }
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, || {
+            let unavailable = replay::Error::unavailable("down");
+            record_command_failure("Account", &unavailable);
+            record_command_failure(
+                "Account",
+                &AccountError::Store(replay::Error::conflict("x")),
+            );
+            record_command_failure("Account", &AccountError::Overdrawn);
+        });
+        let snapshot: Vec<_> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| (key, value))
+            .collect();
+
+        let mut kinds: Vec<_> = counter(&snapshot, COMMAND_FAILURES)
+            .into_iter()
+            .map(|(labels, n)| (labels[1].clone(), n))
+            .collect();
+        kinds.sort();
+        assert_eq!(
+            kinds,
+            [
+                ("kind=conflict".to_string(), 1),
+                ("kind=domain".to_string(), 1),
+                ("kind=unavailable".to_string(), 1),
+            ]
+        );
+    }
+}
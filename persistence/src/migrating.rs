@@ -0,0 +1,595 @@
+//! Zero-downtime migration between two stores: [`MigratingEventStore`] writes every append to
+//! both and serves reads from the one chosen as primary, optionally checking the other agrees.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use futures::future::Either;
+use futures::{stream, TryStream, TryStreamExt};
+use urn::Urn;
+
+use replay::{Compactable, Event, Snapshottable, StreamId};
+
+use crate::{
+    AllEvents, BulkImport, CompactionOutcome, DeletionMode, EventSink, EventStore, PersistedEvent,
+    StoreHealth, StreamFilter, StreamListing, StreamMetadata, StreamPage, StreamRetention,
+    StreamState,
+};
+
+/// Which store of a [`MigratingEventStore`] appends go to first and reads come from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MigrationPrimary {
+    /// The store being migrated from; the new one is being filled.
+    #[default]
+    Old,
+    /// The store being migrated to; the old one is kept up to date as a fallback.
+    New,
+}
+
+/// An [`EventStore`] decorator that writes to an old and a new store while an application
+/// moves from one to the other.
+///
+/// Appends go to the primary store, and the events it persisted are then
+/// [bulk imported](BulkImport) into the secondary one, so both hold the same ids, versions
+/// and metadata. A failing secondary write doesn't fail the append: it is logged on the
+/// `replay_persistence::migration` target and counted in
+/// [`secondary_failures`](Self::secondary_failures), to be repaired with
+/// [`replicate`](crate::replicate).
+///
+/// Reads come from the primary. With [`with_verification`](Self::with_verification), the
+/// secondary is read alongside and each read whose events differ (by id, stream, version or
+/// payload) is logged and counted in [`mismatches`](Self::mismatches). Global positions
+/// differ between the stores, so reads filtered by position are only meaningful on the
+/// primary.
+///
+/// A typical migration backfills history with `replicate`, runs with `Old` as primary and
+/// verification on until no mismatches show up, switches to `New`, and finally drops the
+/// decorator.
+///
+/// ```rust,ignore
+/// let store = MigratingEventStore::new(postgres_store, new_store)
+///     .with_primary(MigrationPrimary::Old)
+///     .with_verification(true);
+/// let cqrs = Cqrs::new(store);
+/// ```
+pub struct MigratingEventStore<Old, New> {
+    old: Arc<Old>,
+    new: Arc<New>,
+    primary: MigrationPrimary,
+    verify: bool,
+    mismatches: Arc<AtomicU64>,
+    secondary_failures: Arc<AtomicU64>,
+}
+
+impl<Old: BulkImport, New: BulkImport> MigratingEventStore<Old, New> {
+    pub fn new(old: impl Into<Arc<Old>>, new: impl Into<Arc<New>>) -> Self {
+        Self {
+            old: old.into(),
+            new: new.into(),
+            primary: MigrationPrimary::Old,
+            verify: false,
+            mismatches: Arc::new(AtomicU64::new(0)),
+            secondary_failures: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Choose the store appends go to first and reads come from (`Old` by default).
+    pub fn with_primary(mut self, primary: MigrationPrimary) -> Self {
+        self.primary = primary;
+        self
+    }
+
+    /// Compare every read with the secondary store.
+    pub fn with_verification(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Reads whose secondary events differed from the primary ones.
+    pub fn mismatches(&self) -> u64 {
+        self.mismatches.load(Ordering::Relaxed)
+    }
+
+    /// Appends and compactions the secondary store failed to apply.
+    pub fn secondary_failures(&self) -> u64 {
+        self.secondary_failures.load(Ordering::Relaxed)
+    }
+
+    pub fn old_store(&self) -> &Old {
+        &self.old
+    }
+
+    pub fn new_store(&self) -> &New {
+        &self.new
+    }
+
+    fn secondary_failed(&self, operation: &str, error: &replay::Error) {
+        self.secondary_failures.fetch_add(1, Ordering::Relaxed);
+        tracing::warn!(
+            target: "replay_persistence::migration",
+            operation,
+            error = %error,
+            "secondary store write failed"
+        );
+    }
+}
+
+impl<Old, New> Clone for MigratingEventStore<Old, New> {
+    fn clone(&self) -> Self {
+        Self {
+            old: self.old.clone(),
+            new: self.new.clone(),
+            primary: self.primary,
+            verify: self.verify,
+            mismatches: self.mismatches.clone(),
+            secondary_failures: self.secondary_failures.clone(),
+        }
+    }
+}
+
+impl<Old: BulkImport, New: BulkImport> EventStore for MigratingEventStore<Old, New> {
+    async fn store_events_stream<S, Events, Sink>(
+        &self,
+        stream_id: &S::StreamId,
+        stream_type: &str,
+        metadata: replay::Metadata,
+        domain_events: Events,
+        expected_version: Option<i64>,
+        mut sink: Sink,
+    ) -> Result<(), replay::Error>
+    where
+        S: replay::EventStream,
+        Events: TryStream<Ok = S::Event, Error = replay::Error> + Send,
+        Sink: EventSink<S::Event> + Send,
+    {
+        let mut persisted = Vec::new();
+        let tee = |event: &PersistedEvent<S::Event>| {
+            sink.on_event(event);
+            persisted.push(event.clone());
+        };
+        let append = match self.primary {
+            MigrationPrimary::Old => Either::Left(self.old.store_events_stream::<S, _, _>(
+                stream_id,
+                stream_type,
+                metadata,
+                domain_events,
+                expected_version,
+                tee,
+            )),
+            MigrationPrimary::New => Either::Right(self.new.store_events_stream::<S, _, _>(
+                stream_id,
+                stream_type,
+                metadata,
+                domain_events,
+                expected_version,
+                tee,
+            )),
+        };
+        append.await?;
+
+        let events = stream::iter(persisted.into_iter().map(Ok));
+        let mirrored = match self.primary {
+            MigrationPrimary::Old => self.new.bulk_import::<S, _>(events).await,
+            MigrationPrimary::New => self.old.bulk_import::<S, _>(events).await,
+        };
+        if let Err(error) = mirrored {
+            self.secondary_failed("store_events", &error);
+        }
+        Ok(())
+    }
+
+    fn stream_events<E: Event>(
+        &self,
+        filter: StreamFilter,
+    ) -> impl TryStream<Ok = PersistedEvent<E>, Error = replay::Error> + Send {
+        match self.primary {
+            MigrationPrimary::Old => Either::Left(
+                verified_read(
+                    &*self.old,
+                    &*self.new,
+                    self.verify,
+                    &self.mismatches,
+                    filter,
+                )
+                .into_stream(),
+            ),
+            MigrationPrimary::New => Either::Right(
+                verified_read(
+                    &*self.new,
+                    &*self.old,
+                    self.verify,
+                    &self.mismatches,
+                    filter,
+                )
+                .into_stream(),
+            ),
+        }
+    }
+
+    /// From the primary alone: positions differ between the two stores.
+    async fn read_all<E: Event>(
+        &self,
+        from_position: i64,
+        limit: usize,
+    ) -> Result<AllEvents<E>, replay::Error> {
+        match self.primary {
+            MigrationPrimary::Old => self.old.read_all(from_position, limit).await,
+            MigrationPrimary::New => self.new.read_all(from_position, limit).await,
+        }
+    }
+
+    async fn needs_compaction(&self, stream_id: &impl StreamId) -> Result<bool, replay::Error> {
+        match self.primary {
+            MigrationPrimary::Old => self.old.needs_compaction(stream_id).await,
+            MigrationPrimary::New => self.new.needs_compaction(stream_id).await,
+        }
+    }
+
+    async fn health_check(&self) -> Result<StoreHealth, replay::Error> {
+        match self.primary {
+            MigrationPrimary::Old => self.old.health_check().await,
+            MigrationPrimary::New => self.new.health_check().await,
+        }
+    }
+
+    /// Compacts both stores, each from its own events; the compacted events get their own
+    /// ids in each store, so verified reads of compacted streams report mismatches.
+    async fn compact<A>(
+        &self,
+        aggregate: &A,
+        metadata: replay::Metadata,
+    ) -> Result<CompactionOutcome, replay::Error>
+    where
+        A: replay::Aggregate + Compactable + Sync,
+    {
+        let (outcome, mirrored) = match self.primary {
+            MigrationPrimary::Old => {
+                let outcome = self.old.compact(aggregate, metadata.clone()).await?;
+                (outcome, self.new.compact(aggregate, metadata).await)
+            }
+            MigrationPrimary::New => {
+                let outcome = self.new.compact(aggregate, metadata.clone()).await?;
+                (outcome, self.old.compact(aggregate, metadata).await)
+            }
+        };
+        if let Err(error) = mirrored {
+            self.secondary_failed("compact", &error);
+        }
+        Ok(outcome)
+    }
+
+    /// Compacts both stores; each writes its snapshot with its own id.
+    async fn compact_stream<S>(
+        &self,
+        stream_id: &S::StreamId,
+        up_to_version: i64,
+        metadata: replay::Metadata,
+    ) -> Result<u64, replay::Error>
+    where
+        S: Snapshottable,
+    {
+        let (replaced, mirrored) = match self.primary {
+            MigrationPrimary::Old => {
+                let replaced = self
+                    .old
+                    .compact_stream::<S>(stream_id, up_to_version, metadata.clone())
+                    .await?;
+                let mirrored = self
+                    .new
+                    .compact_stream::<S>(stream_id, up_to_version, metadata)
+                    .await;
+                (replaced, mirrored)
+            }
+            MigrationPrimary::New => {
+                let replaced = self
+                    .new
+                    .compact_stream::<S>(stream_id, up_to_version, metadata.clone())
+                    .await?;
+                let mirrored = self
+                    .old
+                    .compact_stream::<S>(stream_id, up_to_version, metadata)
+                    .await;
+                (replaced, mirrored)
+            }
+        };
+        if let Err(error) = mirrored {
+            self.secondary_failed("compact_stream", &error);
+        }
+        Ok(replaced)
+    }
+
+    async fn delete_stream(
+        &self,
+        stream_id: &impl StreamId,
+        mode: DeletionMode,
+    ) -> Result<(), replay::Error> {
+        let mirrored = match self.primary {
+            MigrationPrimary::Old => {
+                self.old.delete_stream(stream_id, mode).await?;
+                self.new.delete_stream(stream_id, mode).await
+            }
+            MigrationPrimary::New => {
+                self.new.delete_stream(stream_id, mode).await?;
+                self.old.delete_stream(stream_id, mode).await
+            }
+        };
+        if let Err(error) = mirrored {
+            self.secondary_failed("delete_stream", &error);
+        }
+        Ok(())
+    }
+
+    async fn is_deleted(&self, stream_id: &impl StreamId) -> Result<bool, replay::Error> {
+        match self.primary {
+            MigrationPrimary::Old => self.old.is_deleted(stream_id).await,
+            MigrationPrimary::New => self.new.is_deleted(stream_id).await,
+        }
+    }
+
+    /// Returns what the primary truncated.
+    async fn truncate_stream(
+        &self,
+        stream_id: &impl StreamId,
+        before_version: i64,
+    ) -> Result<u64, replay::Error> {
+        let (truncated, mirrored) = match self.primary {
+            MigrationPrimary::Old => (
+                self.old.truncate_stream(stream_id, before_version).await?,
+                self.new.truncate_stream(stream_id, before_version).await,
+            ),
+            MigrationPrimary::New => (
+                self.new.truncate_stream(stream_id, before_version).await?,
+                self.old.truncate_stream(stream_id, before_version).await,
+            ),
+        };
+        if let Err(error) = mirrored {
+            self.secondary_failed("truncate_stream", &error);
+        }
+        Ok(truncated)
+    }
+
+    async fn set_stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+        retention: StreamRetention,
+    ) -> Result<(), replay::Error> {
+        let mirrored = match self.primary {
+            MigrationPrimary::Old => {
+                self.old.set_stream_retention(stream_id, retention).await?;
+                self.new.set_stream_retention(stream_id, retention).await
+            }
+            MigrationPrimary::New => {
+                self.new.set_stream_retention(stream_id, retention).await?;
+                self.old.set_stream_retention(stream_id, retention).await
+            }
+        };
+        if let Err(error) = mirrored {
+            self.secondary_failed("set_stream_retention", &error);
+        }
+        Ok(())
+    }
+
+    async fn stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> Result<StreamRetention, replay::Error> {
+        match self.primary {
+            MigrationPrimary::Old => self.old.stream_retention(stream_id).await,
+            MigrationPrimary::New => self.new.stream_retention(stream_id).await,
+        }
+    }
+
+    /// Returns what the primary deleted.
+    async fn enforce_stream_retention(&self) -> Result<u64, replay::Error> {
+        let (removed, mirrored) = match self.primary {
+            MigrationPrimary::Old => (
+                self.old.enforce_stream_retention().await?,
+                self.new.enforce_stream_retention().await,
+            ),
+            MigrationPrimary::New => (
+                self.new.enforce_stream_retention().await?,
+                self.old.enforce_stream_retention().await,
+            ),
+        };
+        if let Err(error) = mirrored {
+            self.secondary_failed("enforce_stream_retention", &error);
+        }
+        Ok(removed)
+    }
+
+    async fn set_stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+        metadata: StreamMetadata,
+    ) -> Result<(), replay::Error> {
+        let mirrored = match self.primary {
+            MigrationPrimary::Old => {
+                self.old
+                    .set_stream_metadata(stream_id, metadata.clone())
+                    .await?;
+                self.new.set_stream_metadata(stream_id, metadata).await
+            }
+            MigrationPrimary::New => {
+                self.new
+                    .set_stream_metadata(stream_id, metadata.clone())
+                    .await?;
+                self.old.set_stream_metadata(stream_id, metadata).await
+            }
+        };
+        if let Err(error) = mirrored {
+            self.secondary_failed("set_stream_metadata", &error);
+        }
+        Ok(())
+    }
+
+    async fn stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> Result<StreamMetadata, replay::Error> {
+        match self.primary {
+            MigrationPrimary::Old => self.old.stream_metadata(stream_id).await,
+            MigrationPrimary::New => self.new.stream_metadata(stream_id).await,
+        }
+    }
+
+    async fn list_streams(
+        &self,
+        stream_type: Option<&str>,
+        page: StreamPage,
+    ) -> Result<StreamListing, replay::Error> {
+        match self.primary {
+            MigrationPrimary::Old => self.old.list_streams(stream_type, page).await,
+            MigrationPrimary::New => self.new.list_streams(stream_type, page).await,
+        }
+    }
+
+    async fn stream_info(&self, stream_id: &impl StreamId) -> Result<StreamState, replay::Error> {
+        match self.primary {
+            MigrationPrimary::Old => self.old.stream_info(stream_id).await,
+            MigrationPrimary::New => self.new.stream_info(stream_id).await,
+        }
+    }
+
+    async fn rename_stream(
+        &self,
+        old_id: &impl StreamId,
+        new_id: &impl StreamId,
+    ) -> Result<(), replay::Error> {
+        let mirrored = match self.primary {
+            MigrationPrimary::Old => {
+                self.old.rename_stream(old_id, new_id).await?;
+                self.new.rename_stream(old_id, new_id).await
+            }
+            MigrationPrimary::New => {
+                self.new.rename_stream(old_id, new_id).await?;
+                self.old.rename_stream(old_id, new_id).await
+            }
+        };
+        if let Err(error) = mirrored {
+            self.secondary_failed("rename_stream", &error);
+        }
+        Ok(())
+    }
+}
+
+/// The events of `primary` matching `filter`; with `verify`, compared one by one with those
+/// of `secondary`, counting the read in `mismatches` at the first difference.
+fn verified_read<'a, E, P, Q>(
+    primary: &'a P,
+    secondary: &'a Q,
+    verify: bool,
+    mismatches: &'a AtomicU64,
+    filter: StreamFilter,
+) -> impl TryStream<Ok = PersistedEvent<E>, Error = replay::Error> + Send + use<'a, E, P, Q>
+where
+    E: Event,
+    P: EventStore,
+    Q: EventStore,
+{
+    async_stream::try_stream! {
+        let shadow = verify.then(|| secondary.stream_events::<E>(filter.clone()).into_stream());
+        let events = primary.stream_events::<E>(filter).into_stream();
+        futures::pin_mut!(events);
+        futures::pin_mut!(shadow);
+
+        let mut agreeing = verify;
+        while let Some(event) = events.try_next().await? {
+            if let Some(mut shadow) = shadow.as_mut().as_pin_mut().filter(|_| agreeing) {
+                let mirrored = shadow.try_next().await;
+                if !matches!(&mirrored, Ok(Some(other)) if same_event(&event, other)) {
+                    agreeing = false;
+                    mismatch(mismatches, &event.stream_id, event.version);
+                }
+            }
+            yield event;
+        }
+        if let Some(mut shadow) = shadow.as_mut().as_pin_mut().filter(|_| agreeing) {
+            if let Ok(Some(extra)) = shadow.try_next().await {
+                mismatch(mismatches, &extra.stream_id, extra.version);
+            }
+        }
+    }
+}
+
+fn same_event<E: Event>(event: &PersistedEvent<E>, other: &PersistedEvent<E>) -> bool {
+    event.id == other.id
+        && event.stream_id == other.stream_id
+        && event.version == other.version
+        && event.data == other.data
+}
+
+fn mismatch(mismatches: &AtomicU64, stream_id: &Urn, version: i64) {
+    mismatches.fetch_add(1, Ordering::Relaxed);
+    tracing::warn!(
+        target: "replay_persistence::migration",
+        stream_id = %stream_id,
+        version,
+        "secondary store differs from primary"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use replay::{EventStream, Metadata};
+
+    use super::*;
+    use crate::conformance::{ConformanceAccount, ConformanceAccountUrn, ConformanceEvent};
+    use crate::InMemoryEventStore;
+
+    async fn deposit(store: &impl EventStore, id: &ConformanceAccountUrn, amount: i64) {
+        store
+            .store_events::<ConformanceAccount>(
+                id,
+                ConformanceAccount::stream_type(),
+                Metadata::default(),
+                &[ConformanceEvent::Deposited { amount }],
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn read_all(store: &impl EventStore) -> Vec<PersistedEvent<ConformanceEvent>> {
+        store
+            .stream_events::<ConformanceEvent>(StreamFilter::All)
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn appends_reach_both_stores_with_the_same_ids() {
+        let store = MigratingEventStore::new(InMemoryEventStore::new(), InMemoryEventStore::new())
+            .with_verification(true);
+        let id = ConformanceAccountUrn::new_random();
+
+        deposit(&store, &id, 10).await;
+        deposit(&store, &id, 5).await;
+
+        let (old, new) = (
+            read_all(store.old_store()).await,
+            read_all(store.new_store()).await,
+        );
+        assert_eq!(
+            old.iter().map(|e| (e.id, e.version)).collect::<Vec<_>>(),
+            new.iter().map(|e| (e.id, e.version)).collect::<Vec<_>>()
+        );
+        assert_eq!(read_all(&store).await.len(), 2);
+        assert_eq!(store.mismatches(), 0);
+        assert_eq!(store.secondary_failures(), 0);
+    }
+
+    #[tokio::test]
+    async fn verified_reads_count_a_diverging_secondary() {
+        let store = MigratingEventStore::new(InMemoryEventStore::new(), InMemoryEventStore::new())
+            .with_primary(MigrationPrimary::New)
+            .with_verification(true);
+        let id = ConformanceAccountUrn::new_random();
+
+        deposit(&store, &id, 10).await;
+        deposit(store.old_store(), &id, 99).await;
+
+        assert_eq!(read_all(&store).await.len(), 1);
+        assert_eq!(store.mismatches(), 1);
+    }
+}
@@ -0,0 +1,239 @@
+//! Backup and restore of events as newline-delimited JSON, one event per line:
+//!
+//! ```json
+//! {"id":"0190c5b4-...","stream_id":"urn:bank-account:42","type":"Deposited","version":2,"created":"2024-05-31T12:00:00Z","metadata":{"actor":"ada"},"data":{"Deposited":{"amount":10}}}
+//! ```
+//!
+//! `aggregate_version` is added for archived events. Lines are written in global order, so a
+//! restore replays every stream in the order it was appended.
+
+use chrono::{DateTime, Utc};
+use futures::{TryStream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufWriter};
+use urn::Urn;
+use uuid::Uuid;
+
+use replay::{Event, Metadata};
+
+use crate::persisted_event::AnyEvent;
+use crate::{EventStore, PersistedEvent, StreamFilter};
+
+/// One line of an NDJSON backup.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NdjsonEvent {
+    pub id: Uuid,
+    pub stream_id: Urn,
+    pub r#type: String,
+    pub version: i64,
+    pub created: DateTime<Utc>,
+    pub metadata: Value,
+    pub data: Value,
+    /// The compaction that archived the event; absent for live events.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregate_version: Option<i32>,
+}
+
+impl NdjsonEvent {
+    pub(crate) fn from_persisted(event: PersistedEvent<AnyEvent>) -> Self {
+        NdjsonEvent {
+            id: event.id,
+            stream_id: event.stream_id,
+            r#type: event.r#type,
+            version: event.version,
+            created: event.created,
+            metadata: event.metadata.to_json(),
+            data: event.data.0,
+            aggregate_version: event.aggregate_version,
+        }
+    }
+
+    /// The event with its payload deserialized into `E`. Global positions aren't backed up;
+    /// the importing store assigns new ones.
+    pub fn into_persisted<E: Event>(self) -> Result<PersistedEvent<E>, replay::Error> {
+        Ok(PersistedEvent {
+            id: self.id,
+            data: serde_json::from_value(self.data).map_err(crate::deser_error)?,
+            stream_id: self.stream_id,
+            r#type: self.r#type,
+            version: self.version,
+            created: self.created,
+            metadata: Metadata::new(self.metadata),
+            aggregate_version: self.aggregate_version,
+            global_position: 0,
+        })
+    }
+}
+
+/// Write the events of `store` matching `filter` to `writer` as NDJSON, archived ones
+/// included unless the filter excludes them. Returns the number of events written.
+///
+/// ```rust,ignore
+/// let file = tokio::fs::File::create("bank-accounts.ndjson").await?;
+/// export_events(&store, StreamFilter::for_stream_type::<BankAccount>(), file).await?;
+/// ```
+///
+/// Payloads are written as the store reads them, so decrypted when the store has
+/// encryption.
+pub async fn export_events<W>(
+    store: &impl EventStore,
+    filter: StreamFilter,
+    writer: W,
+) -> Result<u64, replay::Error>
+where
+    W: AsyncWrite + Unpin + Send,
+{
+    let mut writer = BufWriter::new(writer);
+    let mut exported = 0;
+    let events = store.stream_events::<AnyEvent>(filter).into_stream();
+    futures::pin_mut!(events);
+
+    while let Some(event) = events.try_next().await? {
+        let mut line =
+            serde_json::to_vec(&NdjsonEvent::from_persisted(event)).map_err(crate::ser_error)?;
+        line.push(b'\n');
+        writer.write_all(&line).await.map_err(io_error)?;
+        exported += 1;
+    }
+    writer.flush().await.map_err(io_error)?;
+    Ok(exported)
+}
+
+/// Read an NDJSON backup as events of type `E`, in the order they were written. Blank lines
+/// are skipped; a line that isn't an event ends the stream with an `InvalidInput` error
+/// naming the line.
+///
+/// Stores restore a backup with `import_events`, e.g.
+/// [`PostgresEventStore::import_events`](crate::PostgresEventStore::import_events).
+pub fn read_events<E, R>(reader: R) -> impl TryStream<Ok = PersistedEvent<E>, Error = replay::Error>
+where
+    E: Event,
+    R: AsyncBufRead + Unpin + Send,
+{
+    async_stream::try_stream! {
+        let mut lines = reader.lines();
+        let mut number = 0u64;
+        while let Some(line) = lines.next_line().await.map_err(io_error)? {
+            number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = serde_json::from_str::<NdjsonEvent>(&line)
+                .map_err(crate::deser_error)
+                .and_then(NdjsonEvent::into_persisted)
+                .map_err(|e| {
+                    replay::Error::wrap_permanent(
+                        replay::ErrorKind::InvalidInput,
+                        "invalid NDJSON backup line",
+                        e,
+                    )
+                    .with_operation("import_events")
+                    .with_context("line", number)
+                })?;
+            yield event;
+        }
+    }
+}
+
+pub(crate) fn io_error(error: std::io::Error) -> replay::Error {
+    replay::Error::unavailable("failed to transfer NDJSON backup")
+        .with_operation("ndjson")
+        .with_source(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use replay::{EventStream, Metadata};
+    use tokio::io::BufReader;
+
+    use super::*;
+    use crate::conformance::{ConformanceAccount, ConformanceAccountUrn, ConformanceEvent};
+    use crate::InMemoryEventStore;
+
+    #[tokio::test]
+    async fn backups_restore_with_ids_and_versions() {
+        let source = InMemoryEventStore::new();
+        let account = ConformanceAccountUrn::new_random();
+        source
+            .store_events::<ConformanceAccount>(
+                &account,
+                ConformanceAccount::stream_type(),
+                Metadata::default().with_actor("ada"),
+                &[
+                    ConformanceEvent::Opened {
+                        owner: "ada".to_string(),
+                    },
+                    ConformanceEvent::Deposited { amount: 10 },
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+
+        let mut backup = Vec::new();
+        let exported = export_events(&source, StreamFilter::All, &mut backup)
+            .await
+            .unwrap();
+        assert_eq!(exported, 2);
+        let first_line = String::from_utf8(backup.clone())
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .to_string();
+        let line: Value = serde_json::from_str(&first_line).unwrap();
+        assert_eq!(line["type"], "Opened");
+        assert_eq!(line["version"], 1);
+        assert_eq!(line["metadata"]["actor"], "ada");
+
+        let target = InMemoryEventStore::new();
+        let imported = target
+            .import_events::<ConformanceAccount, _>(BufReader::new(backup.as_slice()))
+            .await
+            .unwrap();
+        assert_eq!(imported, 2);
+
+        let original: Vec<PersistedEvent<ConformanceEvent>> = source
+            .stream_events(StreamFilter::All)
+            .try_collect()
+            .await
+            .unwrap();
+        let restored: Vec<PersistedEvent<ConformanceEvent>> = target
+            .stream_events(StreamFilter::All)
+            .try_collect()
+            .await
+            .unwrap();
+        let summary = |events: &[PersistedEvent<ConformanceEvent>]| {
+            events
+                .iter()
+                .map(|e| (e.id, e.version, e.created, e.data.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summary(&restored), summary(&original));
+
+        // Appends continue after the restored head.
+        target
+            .store_events::<ConformanceAccount>(
+                &account,
+                ConformanceAccount::stream_type(),
+                Metadata::default(),
+                &[ConformanceEvent::Withdrawn { amount: 4 }],
+                Some(2),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn invalid_lines_name_the_line() {
+        let backup = b"\n{\"id\": 1}\n";
+        let events = read_events::<ConformanceEvent, _>(BufReader::new(&backup[..])).into_stream();
+        futures::pin_mut!(events);
+
+        let err = events.try_next().await.unwrap_err();
+
+        assert_eq!(err.kind(), replay::ErrorKind::InvalidInput);
+        assert!(err.context().contains(&("line", "2".to_string())));
+    }
+}
@@ -0,0 +1,328 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use futures::TryStreamExt;
+use replay::local::{LocalEvent, LocalEventStore};
+use replay::EventStream;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use urn::Urn;
+
+use crate::{AggregateVersion, EventStore};
+
+/// What [`OfflineSync`] does with local events whose stream moved on remotely since they were
+/// recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncResolution {
+    /// Append them after the remote head anyway.
+    Rebase,
+    /// Never push them; the local stream should be reloaded from the remote.
+    Discard,
+    /// Keep them pending and try again on the next push.
+    Defer,
+}
+
+/// Local events rejected by the remote store because their stream has other events there.
+#[derive(Debug)]
+pub struct SyncConflict<'a> {
+    pub stream_id: &'a Urn,
+    /// The events still to push, oldest first, in the local store's JSON form.
+    pub pending: &'a [LocalEvent<Value>],
+    /// The version of the stream in the remote store.
+    pub remote_version: i64,
+}
+
+/// How far [`OfflineSync`] has pushed one stream.
+///
+/// Save the [`cursors`](OfflineSync::cursors) alongside the local store to resume after a
+/// restart without pushing events twice.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncCursor {
+    /// Local version of the last event pushed (or discarded).
+    pub pushed_version: i64,
+    /// Remote version minus local version of the stream's events, moved by rebases.
+    pub version_offset: i64,
+}
+
+/// Outcome of an [`OfflineSync::push`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub pushed: usize,
+    pub discarded: usize,
+    pub deferred: usize,
+}
+
+type ConflictResolver = Box<dyn Fn(&SyncConflict<'_>) -> SyncResolution>;
+
+/// Pushes the events recorded in a [`LocalEventStore`] while offline to a remote
+/// [`EventStore`] once connectivity returns.
+///
+/// Every batch is appended with the version the remote stream had when the local events were
+/// recorded as expected version, so events appended remotely in the meantime are detected
+/// and handed to the conflict resolver, which [defers](SyncResolution::Defer) them unless
+/// configured otherwise. Local streams start at the version of their remote counterpart:
+/// either new, or loaded from the remote before going offline.
+///
+/// ```rust,ignore
+/// let local = LocalCqrs::new(LocalInMemoryEventStore::new());
+/// let sync = OfflineSync::new(local.store().clone(), Arc::new(remote_store))
+///     .with_conflict_resolver(|_| SyncResolution::Rebase);
+///
+/// // ... commands run against `local` while offline ...
+///
+/// let report = sync.push::<BankAccount>().await?;
+/// ```
+pub struct OfflineSync<L, R> {
+    local: Rc<L>,
+    remote: Arc<R>,
+    resolver: Option<ConflictResolver>,
+    cursors: RefCell<HashMap<Urn, SyncCursor>>,
+}
+
+impl<L: LocalEventStore, R: EventStore> OfflineSync<L, R> {
+    pub fn new(local: Rc<L>, remote: Arc<R>) -> Self {
+        Self {
+            local,
+            remote,
+            resolver: None,
+            cursors: RefCell::default(),
+        }
+    }
+
+    /// Decide what happens to local events that conflict with remote ones.
+    pub fn with_conflict_resolver<F>(mut self, resolver: F) -> Self
+    where
+        F: Fn(&SyncConflict<'_>) -> SyncResolution + 'static,
+    {
+        self.resolver = Some(Box::new(resolver));
+        self
+    }
+
+    /// Resume from cursors saved by a previous run.
+    pub fn with_cursors(self, cursors: HashMap<Urn, SyncCursor>) -> Self {
+        *self.cursors.borrow_mut() = cursors;
+        self
+    }
+
+    /// How far each stream has been pushed.
+    pub fn cursors(&self) -> HashMap<Urn, SyncCursor> {
+        self.cursors.borrow().clone()
+    }
+
+    /// Push the events of `S` streams not pushed yet, one stream after the other.
+    ///
+    /// Consecutive events of a stream recorded with the same metadata are appended together.
+    /// A failing append other than a conflict stops the push; what was pushed before it is
+    /// kept in the cursors.
+    pub async fn push<S: EventStream>(&self) -> Result<SyncReport, replay::Error> {
+        let mut streams: Vec<(Urn, Vec<LocalEvent<Value>>)> = Vec::new();
+        for event in self.local.read_all(0).await? {
+            if event.stream_type != S::stream_type() {
+                continue;
+            }
+            match streams.iter_mut().find(|(id, _)| *id == event.stream_id) {
+                Some((_, events)) => events.push(event),
+                None => streams.push((event.stream_id.clone(), vec![event])),
+            }
+        }
+
+        let mut report = SyncReport::default();
+        for (stream_id, events) in streams {
+            let mut cursor = self.cursor(&stream_id);
+            let pending: Vec<_> = events
+                .into_iter()
+                .filter(|event| event.version > cursor.pushed_version)
+                .collect();
+            let pushed = self
+                .push_stream::<S>(&stream_id, &pending, &mut cursor, &mut report)
+                .await;
+            self.cursors.borrow_mut().insert(stream_id, cursor);
+            pushed?;
+        }
+        Ok(report)
+    }
+
+    async fn push_stream<S: EventStream>(
+        &self,
+        stream_id: &Urn,
+        pending: &[LocalEvent<Value>],
+        cursor: &mut SyncCursor,
+        report: &mut SyncReport,
+    ) -> Result<(), replay::Error> {
+        let id = S::StreamId::try_from(stream_id.clone()).map_err(|e| {
+            replay::Error::invalid_input("local stream id is not a valid stream id")
+                .with_operation("push")
+                .with_context("stream_id", stream_id.to_string())
+                .with_context("error", format!("{e:?}"))
+        })?;
+
+        let mut from = 0;
+        for batch in pending.chunk_by(|a, b| a.metadata == b.metadata) {
+            let events = batch
+                .iter()
+                .map(|event| event.clone().decode::<S::Event>().map(|event| event.data))
+                .collect::<Result<Vec<_>, _>>()?;
+            loop {
+                let expected_version = batch[0].version - 1 + cursor.version_offset;
+                let appended = self
+                    .remote
+                    .store_events::<S>(
+                        &id,
+                        S::stream_type(),
+                        batch[0].metadata.clone(),
+                        &events,
+                        Some(expected_version),
+                    )
+                    .await;
+                match appended {
+                    Ok(()) => {
+                        cursor.pushed_version = batch[batch.len() - 1].version;
+                        report.pushed += batch.len();
+                        break;
+                    }
+                    Err(err) if err.kind() == replay::ErrorKind::Conflict => {
+                        let remote_version = self.remote_version::<S>(&id).await?;
+                        let conflict = SyncConflict {
+                            stream_id,
+                            pending: &pending[from..],
+                            remote_version,
+                        };
+                        let resolution = self
+                            .resolver
+                            .as_ref()
+                            .map_or(SyncResolution::Defer, |resolver| resolver(&conflict));
+                        match resolution {
+                            SyncResolution::Rebase => {
+                                cursor.version_offset = remote_version - (batch[0].version - 1);
+                            }
+                            SyncResolution::Discard => {
+                                cursor.pushed_version = pending[pending.len() - 1].version;
+                                report.discarded += pending.len() - from;
+                                return Ok(());
+                            }
+                            SyncResolution::Defer => {
+                                report.deferred += pending.len() - from;
+                                return Ok(());
+                            }
+                        }
+                    }
+                    Err(err) => return Err(err.with_operation("push")),
+                }
+            }
+            from += batch.len();
+        }
+        Ok(())
+    }
+
+    fn cursor(&self, stream_id: &Urn) -> SyncCursor {
+        self.cursors
+            .borrow()
+            .get(stream_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    async fn remote_version<S: EventStream>(&self, id: &S::StreamId) -> Result<i64, replay::Error> {
+        self.remote
+            .stream_events_by_stream_id::<S>(id, AggregateVersion::Latest, None, None)
+            .try_fold(0, |_, event| async move { Ok(event.version) })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use replay::local::LocalInMemoryEventStore;
+    use replay::Metadata;
+
+    use super::*;
+    use crate::conformance::{ConformanceAccount, ConformanceAccountUrn, ConformanceEvent};
+    use crate::InMemoryEventStore;
+
+    async fn record_deposit(
+        local: &LocalInMemoryEventStore,
+        id: &ConformanceAccountUrn,
+        version: i64,
+        amount: i64,
+    ) {
+        let event = LocalEvent {
+            stream_id: id.clone().into(),
+            stream_type: ConformanceAccount::stream_type().to_string(),
+            r#type: "Deposited".to_string(),
+            version,
+            global_position: 0,
+            metadata: Metadata::default(),
+            data: serde_json::to_value(ConformanceEvent::Deposited { amount }).unwrap(),
+        };
+        local.append(vec![event]).await.unwrap();
+    }
+
+    async fn remote_deposits(remote: &InMemoryEventStore, id: &ConformanceAccountUrn) -> Vec<i64> {
+        remote
+            .stream_events_by_stream_id::<ConformanceAccount>(
+                id,
+                AggregateVersion::Latest,
+                None,
+                None,
+            )
+            .map_ok(|event| match event.data {
+                ConformanceEvent::Deposited { amount } => amount,
+                _ => 0,
+            })
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn offline_events_are_pushed_once() {
+        let local = Rc::new(LocalInMemoryEventStore::new());
+        let remote = Arc::new(InMemoryEventStore::new());
+        let sync = OfflineSync::new(local.clone(), remote.clone());
+        let id = ConformanceAccountUrn::new_random();
+
+        record_deposit(&local, &id, 1, 10).await;
+        record_deposit(&local, &id, 2, 5).await;
+        let report = sync.push::<ConformanceAccount>().await.unwrap();
+        let again = sync.push::<ConformanceAccount>().await.unwrap();
+
+        assert_eq!(report.pushed, 2);
+        assert_eq!(again, SyncReport::default());
+        assert_eq!(remote_deposits(&remote, &id).await, [10, 5]);
+        let stream_id: Urn = id.into();
+        assert_eq!(sync.cursors()[&stream_id].pushed_version, 2);
+    }
+
+    #[tokio::test]
+    async fn conflicts_are_deferred_unless_resolved() {
+        let local = Rc::new(LocalInMemoryEventStore::new());
+        let remote = Arc::new(InMemoryEventStore::new());
+        let id = ConformanceAccountUrn::new_random();
+
+        record_deposit(&local, &id, 1, 10).await;
+        remote
+            .store_events::<ConformanceAccount>(
+                &id,
+                ConformanceAccount::stream_type(),
+                Metadata::default(),
+                &[ConformanceEvent::Deposited { amount: 1 }],
+                None,
+            )
+            .await
+            .unwrap();
+
+        let deferring = OfflineSync::new(local.clone(), remote.clone());
+        let report = deferring.push::<ConformanceAccount>().await.unwrap();
+        assert_eq!(report.deferred, 1);
+
+        let rebasing = OfflineSync::new(local, remote.clone()).with_conflict_resolver(|conflict| {
+            assert_eq!(conflict.remote_version, 1);
+            SyncResolution::Rebase
+        });
+        let report = rebasing.push::<ConformanceAccount>().await.unwrap();
+        assert_eq!(report.pushed, 1);
+        assert_eq!(remote_deposits(&remote, &id).await, [1, 10]);
+    }
+}
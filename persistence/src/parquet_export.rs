@@ -0,0 +1,368 @@
+//! Export of events to Parquet files, for data lakes and analytics engines (DuckDB, Spark,
+//! Athena) to query the event log without a custom ETL.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Int64Array, RecordBatch, StringArray, TimestampMicrosecondArray};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use futures::TryStreamExt;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+
+use crate::persisted_event::AnyEvent;
+use crate::{EventStore, PersistedEvent, StreamFilter};
+
+/// Partitions written to at once; writing to another closes the least recently used, and a
+/// later event for it starts a new file in the same partition.
+const MAX_OPEN_PARTITIONS: usize = 16;
+
+/// How a [`ParquetExporter`] lays out its files under the export directory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParquetPartitioning {
+    /// All files directly in the export directory.
+    Unpartitioned,
+    /// A Hive-style directory per creation day, `created_date=2024-05-31/`.
+    #[default]
+    ByDay,
+    /// A Hive-style directory per creation month, `created_month=2024-05/`.
+    ByMonth,
+}
+
+impl ParquetPartitioning {
+    fn directory(&self, event: &PersistedEvent<AnyEvent>) -> Option<String> {
+        match self {
+            ParquetPartitioning::Unpartitioned => None,
+            ParquetPartitioning::ByDay => {
+                Some(format!("created_date={}", event.created.format("%Y-%m-%d")))
+            }
+            ParquetPartitioning::ByMonth => {
+                Some(format!("created_month={}", event.created.format("%Y-%m")))
+            }
+        }
+    }
+}
+
+/// What a [`ParquetExporter::export`] wrote.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParquetExport {
+    pub events: u64,
+    pub files: Vec<PathBuf>,
+}
+
+/// Streams the events matching a filter into Parquet files with the columns `stream_id`,
+/// `type`, `version`, `created` (UTC microseconds), and `data` and `metadata` as JSON
+/// strings.
+///
+/// Events are read in global order and written in row groups, so memory stays bounded by
+/// the row groups being filled. Every run writes new, uniquely named files, so exports of
+/// successive time ranges can share a directory:
+///
+/// ```rust,ignore
+/// let export = ParquetExporter::new("/lake/events")
+///     .export(&store, StreamFilter::CreatedAfter(last_export))
+///     .await?;
+/// ```
+///
+/// Payloads are exported as the store reads them, so decrypted when the store has
+/// encryption. File writes block the calling thread, so run exports on a multi-threaded
+/// runtime.
+#[derive(Clone, Debug)]
+pub struct ParquetExporter {
+    directory: PathBuf,
+    partitioning: ParquetPartitioning,
+    row_group_size: usize,
+    max_rows_per_file: usize,
+}
+
+impl ParquetExporter {
+    /// An exporter into `directory`, partitioned by day, with row groups of 10,000 events
+    /// and files of at most a million.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        ParquetExporter {
+            directory: directory.into(),
+            partitioning: ParquetPartitioning::default(),
+            row_group_size: 10_000,
+            max_rows_per_file: 1_000_000,
+        }
+    }
+
+    pub fn with_partitioning(mut self, partitioning: ParquetPartitioning) -> Self {
+        self.partitioning = partitioning;
+        self
+    }
+
+    pub fn with_row_group_size(mut self, row_group_size: usize) -> Self {
+        self.row_group_size = row_group_size.max(1);
+        self
+    }
+
+    pub fn with_max_rows_per_file(mut self, max_rows_per_file: usize) -> Self {
+        self.max_rows_per_file = max_rows_per_file.max(1);
+        self
+    }
+
+    /// Write the events of `store` matching `filter`, archived ones included unless the
+    /// filter excludes them.
+    pub async fn export(
+        &self,
+        store: &impl EventStore,
+        filter: StreamFilter,
+    ) -> Result<ParquetExport, replay::Error> {
+        let mut export = ParquetExport::default();
+        let mut open: Vec<PartitionFile> = Vec::new();
+        let events = store.stream_events::<AnyEvent>(filter).into_stream();
+        futures::pin_mut!(events);
+
+        while let Some(event) = events.try_next().await? {
+            let partition = self.partitioning.directory(&event);
+            let index = match open.iter().position(|file| file.partition == partition) {
+                Some(index) => index,
+                None => {
+                    if open.len() >= MAX_OPEN_PARTITIONS {
+                        let oldest = (0..open.len())
+                            .min_by_key(|&i| open[i].last_written)
+                            .unwrap_or(0);
+                        export.files.push(open.swap_remove(oldest).close()?);
+                    }
+                    open.push(self.create(partition)?);
+                    open.len() - 1
+                }
+            };
+
+            let file = &mut open[index];
+            file.push(&event)?;
+            file.last_written = export.events;
+            export.events += 1;
+            if file.buffered() >= self.row_group_size {
+                file.flush()?;
+            }
+            if file.rows >= self.max_rows_per_file {
+                export.files.push(open.swap_remove(index).close()?);
+            }
+        }
+
+        for file in open {
+            export.files.push(file.close()?);
+        }
+        export.files.sort();
+        tracing::info!(
+            events = export.events,
+            files = export.files.len(),
+            directory = %self.directory.display(),
+            "exported events to Parquet"
+        );
+        Ok(export)
+    }
+
+    fn create(&self, partition: Option<String>) -> Result<PartitionFile, replay::Error> {
+        let directory = match &partition {
+            Some(partition) => self.directory.join(partition),
+            None => self.directory.clone(),
+        };
+        let path = directory.join(format!("events-{}.parquet", uuid::Uuid::now_v7()));
+        let file = std::fs::create_dir_all(&directory)
+            .and_then(|_| File::create(&path))
+            .map_err(|e| export_error(&path, e))?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .set_max_row_group_size(self.row_group_size)
+            .build();
+        let writer = ArrowWriter::try_new(file, schema(), Some(properties))
+            .map_err(|e| export_error(&path, e))?;
+        Ok(PartitionFile {
+            partition,
+            path,
+            writer,
+            columns: Columns::default(),
+            rows: 0,
+            last_written: 0,
+        })
+    }
+}
+
+fn schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("stream_id", DataType::Utf8, false),
+        Field::new("type", DataType::Utf8, false),
+        Field::new("version", DataType::Int64, false),
+        Field::new(
+            "created",
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            false,
+        ),
+        Field::new("data", DataType::Utf8, false),
+        Field::new("metadata", DataType::Utf8, false),
+    ]))
+}
+
+fn export_error(
+    path: &Path,
+    error: impl std::error::Error + Send + Sync + 'static,
+) -> replay::Error {
+    replay::Error::internal("failed to write Parquet export")
+        .with_operation("export_parquet")
+        .with_context("path", path.display())
+        .with_source(error)
+}
+
+/// Rows of the row group being filled.
+#[derive(Default)]
+struct Columns {
+    stream_id: Vec<String>,
+    r#type: Vec<String>,
+    version: Vec<i64>,
+    created: Vec<i64>,
+    data: Vec<String>,
+    metadata: Vec<String>,
+}
+
+struct PartitionFile {
+    partition: Option<String>,
+    path: PathBuf,
+    writer: ArrowWriter<File>,
+    columns: Columns,
+    /// Rows written or buffered.
+    rows: usize,
+    /// When the last event was added, in events exported so far.
+    last_written: u64,
+}
+
+impl PartitionFile {
+    fn push(&mut self, event: &PersistedEvent<AnyEvent>) -> Result<(), replay::Error> {
+        let columns = &mut self.columns;
+        columns.stream_id.push(event.stream_id.to_string());
+        columns.r#type.push(event.r#type.clone());
+        columns.version.push(event.version);
+        columns.created.push(event.created.timestamp_micros());
+        columns
+            .data
+            .push(serde_json::to_string(&event.data.0).map_err(crate::ser_error)?);
+        columns
+            .metadata
+            .push(serde_json::to_string(&event.metadata.to_json()).map_err(crate::ser_error)?);
+        self.rows += 1;
+        Ok(())
+    }
+
+    fn buffered(&self) -> usize {
+        self.columns.version.len()
+    }
+
+    /// Write the buffered rows as a row group.
+    fn flush(&mut self) -> Result<(), replay::Error> {
+        if self.buffered() == 0 {
+            return Ok(());
+        }
+        let columns = std::mem::take(&mut self.columns);
+        let arrays: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(columns.stream_id)),
+            Arc::new(StringArray::from(columns.r#type)),
+            Arc::new(Int64Array::from(columns.version)),
+            Arc::new(TimestampMicrosecondArray::from(columns.created).with_timezone("UTC")),
+            Arc::new(StringArray::from(columns.data)),
+            Arc::new(StringArray::from(columns.metadata)),
+        ];
+        let batch =
+            RecordBatch::try_new(schema(), arrays).map_err(|e| export_error(&self.path, e))?;
+        self.writer
+            .write(&batch)
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| export_error(&self.path, e))
+    }
+
+    fn close(mut self) -> Result<PathBuf, replay::Error> {
+        self.flush()?;
+        self.writer
+            .close()
+            .map_err(|e| export_error(&self.path, e))?;
+        Ok(self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use replay::{EventStream, Metadata};
+    use urn::Urn;
+
+    use super::*;
+    use crate::conformance::{ConformanceAccount, ConformanceAccountUrn, ConformanceEvent};
+    use crate::InMemoryEventStore;
+
+    /// The number of row groups in the file at `path`, and its rows read back as batches.
+    fn read_back(path: &Path) -> (usize, Vec<RecordBatch>) {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap()).unwrap();
+        let row_groups = builder.metadata().num_row_groups();
+        let batches = builder.build().unwrap().collect::<Result<_, _>>().unwrap();
+        (row_groups, batches)
+    }
+
+    #[tokio::test]
+    async fn events_export_to_day_partitions() {
+        let store = InMemoryEventStore::new();
+        let account = ConformanceAccountUrn::new_random();
+        store
+            .store_events::<ConformanceAccount>(
+                &account,
+                ConformanceAccount::stream_type(),
+                Metadata::default().with_actor("ada"),
+                &[
+                    ConformanceEvent::Opened {
+                        owner: "ada".to_string(),
+                    },
+                    ConformanceEvent::Deposited { amount: 10 },
+                    ConformanceEvent::Withdrawn { amount: 4 },
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+        let directory =
+            std::env::temp_dir().join(format!("replay-parquet-{}", uuid::Uuid::now_v7()));
+
+        let export = ParquetExporter::new(&directory)
+            .with_row_group_size(2)
+            .export(&store, StreamFilter::All)
+            .await
+            .unwrap();
+
+        assert_eq!(export.events, 3);
+        assert_eq!(export.files.len(), 1);
+        let file = &export.files[0];
+        let partition = format!("created_date={}", Utc::now().format("%Y-%m-%d"));
+        assert_eq!(file.parent().unwrap(), directory.join(partition));
+
+        let (row_groups, batches) = read_back(file);
+        assert_eq!(row_groups, 2);
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 3);
+        let column = |name: &str| {
+            batch
+                .column_by_name(name)
+                .unwrap()
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .unwrap()
+                .value(1)
+                .to_string()
+        };
+        assert_eq!(column("stream_id"), Urn::from(account).to_string());
+        assert_eq!(column("type"), "Deposited");
+        assert_eq!(column("data"), r#"{"Deposited":{"amount":10}}"#);
+        assert_eq!(column("metadata"), r#"{"actor":"ada"}"#);
+        let versions = batch
+            .column_by_name("version")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(versions.values(), &[1, 2, 3]);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
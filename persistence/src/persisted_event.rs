@@ -1,11 +1,19 @@
 use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use urn::Urn;
 use uuid::Uuid;
 
-use replay::{Event, Metadata};
+use replay::{Event, EventContext, EventStream, Metadata};
 
+/// An event as read back from the store.
+///
+/// Metadata is raw [`Metadata`] by default; [`with_typed_metadata`](Self::with_typed_metadata)
+/// (or [`EventStore::stream_events_with_metadata`](crate::EventStore::stream_events_with_metadata))
+/// deserializes it into an application struct `M` instead.
 #[derive(Debug, Clone)]
-pub struct PersistedEvent<E> {
+pub struct PersistedEvent<E, M = Metadata> {
     pub id: Uuid,
     pub data: E,
     pub stream_id: Urn,
@@ -13,15 +21,76 @@ pub struct PersistedEvent<E> {
     /// Monotonic position of this event within the aggregate's current stream.
     pub version: i64,
     pub created: DateTime<Utc>,
-    pub metadata: Metadata,
+    pub metadata: M,
     /// `None` identifies events belonging to the current (latest) stream.
     /// `Some(n)` identifies events that were archived during the nth compaction.
     /// Matches the `INTEGER` column type in the database.
     pub aggregate_version: Option<i32>,
+    /// Store-wide append order: strictly increasing across every stream, so it can be used to
+    /// resume a read where the last one stopped. Numbers may be skipped.
+    pub global_position: i64,
 }
 
 impl<E> PersistedEvent<E> {
-    pub fn wrap_data_with<Other: From<E>>(self) -> PersistedEvent<Other> {
+    /// Who issued the command that produced this event, as recorded in its metadata.
+    pub fn actor(&self) -> Option<&str> {
+        self.metadata.actor()
+    }
+
+    /// Schema version of the payload as it was written, from [`Event::event_version`].
+    ///
+    /// Events written before a type declared a version, or at version `1`, carry no
+    /// `event_version` metadata and report `1`.
+    pub fn event_version(&self) -> u32 {
+        self.metadata.event_version().unwrap_or(1)
+    }
+
+    /// The correlation id carried by whatever this event causes.
+    ///
+    /// That is the event's own `correlation_id`, or — for an event with none, which starts
+    /// a new workflow — its id.
+    pub fn chained_correlation_id(&self) -> String {
+        self.metadata
+            .correlation_id()
+            .map(str::to_string)
+            .unwrap_or_else(|| self.id.to_string())
+    }
+
+    /// Deserialize the metadata into `M`.
+    ///
+    /// Empty metadata deserializes like an empty object, so a struct whose fields are all
+    /// optional or defaulted accepts events written without metadata.
+    pub fn with_typed_metadata<M: DeserializeOwned>(
+        self,
+    ) -> Result<PersistedEvent<E, M>, replay::Error> {
+        let metadata = match self.metadata.to_json() {
+            Value::Null => Value::Object(Map::new()),
+            metadata => metadata,
+        };
+        let metadata = serde_json::from_value(metadata)
+            .map_err(|e| crate::deser_error(e).with_context("event_id", self.id))?;
+
+        Ok(self.map_metadata(|_| metadata))
+    }
+}
+
+impl<E, M> PersistedEvent<E, M> {
+    /// Replace the metadata with `f` applied to it, keeping everything else.
+    pub fn map_metadata<Other>(self, f: impl FnOnce(M) -> Other) -> PersistedEvent<E, Other> {
+        PersistedEvent {
+            id: self.id,
+            data: self.data,
+            stream_id: self.stream_id,
+            r#type: self.r#type,
+            version: self.version,
+            created: self.created,
+            metadata: f(self.metadata),
+            aggregate_version: self.aggregate_version,
+            global_position: self.global_position,
+        }
+    }
+
+    pub fn wrap_data_with<Other: From<E>>(self) -> PersistedEvent<Other, M> {
         PersistedEvent {
             id: self.id,
             data: Other::from(self.data),
@@ -31,10 +100,11 @@ impl<E> PersistedEvent<E> {
             created: self.created,
             metadata: self.metadata,
             aggregate_version: self.aggregate_version,
+            global_position: self.global_position,
         }
     }
 
-    pub fn with_data<Other: Event>(self, data: Other) -> PersistedEvent<Other> {
+    pub fn with_data<Other: Event>(self, data: Other) -> PersistedEvent<Other, M> {
         PersistedEvent {
             id: self.id,
             data,
@@ -44,6 +114,114 @@ impl<E> PersistedEvent<E> {
             created: self.created,
             metadata: self.metadata,
             aggregate_version: self.aggregate_version,
+            global_position: self.global_position,
+        }
+    }
+}
+
+impl<E> PersistedEvent<E> {
+    /// The envelope of this event, as [`EventStream::apply_with_context`] sees it.
+    pub fn context(&self) -> EventContext<'_> {
+        EventContext::new(
+            &self.stream_id,
+            self.version,
+            self.global_position,
+            self.created.into(),
+            &self.metadata,
+        )
+    }
+
+    /// Apply this event to `stream` along with its envelope.
+    pub fn apply_to<S: EventStream<Event = E>>(self, stream: &mut S) -> replay::Result<()> {
+        let context = EventContext::new(
+            &self.stream_id,
+            self.version,
+            self.global_position,
+            self.created.into(),
+            &self.metadata,
+        );
+        stream.apply_with_context(self.data, &context)
+    }
+}
+
+/// The metadata stored with `event`: the batch metadata plus its schema version, unless `1`.
+pub(crate) fn versioned_metadata<E: Event>(metadata: &Metadata, event: &E) -> Metadata {
+    match event.event_version() {
+        1 => metadata.clone(),
+        version => metadata.clone().with_event_version(version),
+    }
+}
+
+/// Payload-agnostic event, to read events of any stream type as their stored JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct AnyEvent(pub(crate) Value);
+
+impl Event for AnyEvent {
+    fn event_type(&self) -> &'static str {
+        ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct RequestMetadata {
+        tenant_id: String,
+        #[serde(default)]
+        client_ip: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Optional {
+        #[serde(default)]
+        actor: Option<String>,
+    }
+
+    fn event(metadata: Metadata) -> PersistedEvent<()> {
+        PersistedEvent {
+            id: Uuid::new_v4(),
+            data: (),
+            stream_id: "urn:test:1".parse().unwrap(),
+            r#type: "Test".to_string(),
+            version: 1,
+            created: Utc::now(),
+            metadata,
+            aggregate_version: None,
+            global_position: 1,
         }
     }
+
+    #[test]
+    fn with_typed_metadata_deserializes_into_the_target_struct() {
+        let typed = event(Metadata::new(json!({ "tenant_id": "acme", "extra": true })))
+            .with_typed_metadata::<RequestMetadata>()
+            .unwrap();
+
+        assert_eq!(
+            typed.metadata,
+            RequestMetadata {
+                tenant_id: "acme".to_string(),
+                client_ip: None,
+            }
+        );
+    }
+
+    #[test]
+    fn with_typed_metadata_treats_empty_metadata_as_an_empty_object() {
+        let typed = event(Metadata::default())
+            .with_typed_metadata::<Optional>()
+            .unwrap();
+        assert_eq!(typed.metadata, Optional { actor: None });
+
+        let err = event(Metadata::default())
+            .with_typed_metadata::<RequestMetadata>()
+            .unwrap_err();
+        assert_eq!(err.kind(), replay::ErrorKind::Internal);
+    }
 }
@@ -21,11 +21,13 @@ use serde_json::{Map, Value};
 use sqlx::{Pool, Postgres, QueryBuilder, Row};
 use tokio::sync::{broadcast, watch};
 use tokio::task::JoinHandle;
+use tracing::Instrument;
 
 use replay::{Aggregate, Metadata};
 
+use crate::infrastructure::event_from_row;
 use crate::policy::{Dispatch, ErasedPolicy, Policy, StartAt};
-use crate::{Cqrs, PersistedEvent, PostgresEventStore, StreamFilter};
+use crate::{Cqrs, Encryption, PersistedEvent, PostgresEventStore, StreamFilter};
 
 /// Erased, services-bound execution path for one aggregate type.
 ///
@@ -353,7 +355,7 @@ impl PolicyRunner {
                 .with_context("policy", &policy_name)
             })?;
 
-        let raw = load_event_by_id(&self.pool, event_id)
+        let raw = load_event_by_id(&self.cqrs, event_id)
             .await?
             .ok_or_else(|| {
                 replay::Error::not_found("triggering event for dead letter no longer exists")
@@ -935,7 +937,7 @@ async fn drain_policy_once(
     let name = policy.name().to_string();
     let checkpoint_size = resolve_checkpoint_batch_size(policy);
     let read_batch = resolve_read_batch_size(policy, checkpoint_size);
-    let feed = read_feed(pool, policy.stream_filter(), *cursor, read_batch).await?;
+    let feed = read_feed(cqrs, policy.stream_filter(), *cursor, read_batch).await?;
 
     let mut executed = 0;
     let mut events_since_checkpoint = 0u32;
@@ -1141,20 +1143,30 @@ async fn move_dead_letter_to_archive(
 /// Load a single event by its primary key, shaped exactly like [`read_feed`]
 /// so it can be fed back into a policy's erased reaction during retry.
 async fn load_event_by_id(
-    pool: &Pool<Postgres>,
+    cqrs: &Cqrs<PostgresEventStore>,
     event_id: uuid::Uuid,
 ) -> Result<Option<PersistedEvent<Value>>, replay::Error> {
-    let row = sqlx::query(
-        "SELECT id, data, metadata, stream_id, type, version, created, aggregate_version, \
-         global_position, compacted_snapshot FROM events WHERE id = $1",
-    )
-    .bind(event_id)
-    .fetch_optional(pool)
-    .await
-    .map_err(crate::db_error)?;
+    let mut qb: QueryBuilder<Postgres> = QueryBuilder::new(
+        "SELECT id, data, data_binary, data_encoding, metadata, stream_id, type, version, \
+         created, aggregate_version, global_position, compacted_snapshot",
+    );
+    qb.push(cqrs.store().data_key_column());
+    qb.push(cqrs.store().signature_column());
+    qb.push(" FROM events WHERE id = ").push_bind(event_id);
+    let row = qb
+        .build()
+        .fetch_optional(cqrs.store().pool())
+        .await
+        .map_err(crate::db_error)?;
 
+    let keys = cqrs.store().encryption().map(Encryption::keys);
     match row {
-        Some(row) => Ok(Some(PersistedEvent::<Value>::try_from(row)?)),
+        Some(row) => Ok(Some(event_from_row(
+            row,
+            cqrs.store().serializer(),
+            keys.as_ref(),
+            cqrs.store().signer(),
+        )?)),
         None => Ok(None),
     }
 }
@@ -1170,37 +1182,60 @@ async fn load_event_by_id(
 /// `None` the row is a synthetic compaction snapshot (`compacted_snapshot =
 /// TRUE`): the cursor must still advance past it, but no reaction is fired.
 async fn read_feed(
-    pool: &Pool<Postgres>,
+    cqrs: &Cqrs<PostgresEventStore>,
     filter: StreamFilter,
     cursor: i64,
     limit: u32,
 ) -> Result<Vec<(i64, Option<PersistedEvent<Value>>)>, replay::Error> {
+    let store = cqrs.store();
     let mut qb: QueryBuilder<Postgres> = QueryBuilder::new(
-        "SELECT id, data, metadata, stream_id, type, version, created, aggregate_version, \
-         global_position, compacted_snapshot FROM events WHERE global_position > ",
+        "SELECT id, data, data_binary, data_encoding, metadata, stream_id, type, version, \
+         created, aggregate_version, global_position, compacted_snapshot",
     );
+    qb.push(store.data_key_column());
+    qb.push(store.signature_column());
+    qb.push(" FROM events WHERE global_position > ");
     qb.push_bind(cursor);
     qb.push(" AND ");
-    PostgresEventStore::add_filters(&mut qb, filter);
+    PostgresEventStore::add_filters(&mut qb, filter, store.correlation_links());
     qb.push(" ORDER BY global_position ASC LIMIT ");
     qb.push_bind(limit as i64);
 
-    let rows = qb.build().fetch_all(pool).await.map_err(crate::db_error)?;
+    let rows = qb
+        .build()
+        .fetch_all(store.pool())
+        .await
+        .map_err(|e| store.map_db_error(e))?;
 
     let mut feed = Vec::with_capacity(rows.len());
-    for (expected, row) in (cursor + 1..).zip(rows) {
+    let mut expected = cursor + 1;
+    let keys = store.encryption().map(Encryption::keys);
+    let mut horizon = None;
+    for row in rows {
         let global_position: i64 = row.get("global_position");
         if global_position != expected {
-            // Gap: stop here and let the hole fill on a later poll.
-            break;
+            // Holes up to the retention horizon are expired events, not appends in flight.
+            let horizon = match horizon {
+                Some(horizon) => horizon,
+                None => *horizon.insert(
+                    crate::retention::retention_horizon(store.pool())
+                        .await
+                        .map_err(crate::db_error)?,
+                ),
+            };
+            if global_position - 1 > horizon {
+                // Gap: stop here and let the hole fill on a later poll.
+                break;
+            }
         }
+        expected = global_position + 1;
 
         let is_snapshot: bool = row.get("compacted_snapshot");
         if is_snapshot {
             // Synthetic row: advance the cursor past it, but deliver nothing.
             feed.push((global_position, None));
         } else {
-            let event = PersistedEvent::<Value>::try_from(row)?;
+            let event = event_from_row(row, store.serializer(), keys.as_ref(), store.signer())?;
             feed.push((global_position, Some(event)));
         }
     }
@@ -1238,8 +1273,29 @@ async fn execute_dispatch(
             .with_context("aggregate", aggregate_name)
     })?;
 
+    // An explicit correlation id on the dispatch wins over the inherited one.
+    let metadata = match metadata.correlation_id() {
+        Some(_) => metadata,
+        None => metadata.with_correlation_id(raw.chained_correlation_id()),
+    };
+    // Policy-issued commands act as the policy unless the dispatch names an actor.
+    let metadata = match metadata.actor() {
+        Some(_) => metadata,
+        None => metadata.with_actor(format!("policy:{policy_name}")),
+    };
+
+    // The command runs as a child of the span that appended the triggering event.
+    let span = tracing::info_span!(
+        "policy_dispatch",
+        policy = policy_name,
+        aggregate = aggregate_name
+    );
+    #[cfg(feature = "opentelemetry")]
+    raw.follow_trace(&span);
+
     executor
         .execute(cqrs, dispatch.payload, metadata, dispatch.expected_version)
+        .instrument(span)
         .await
 }
 
@@ -1332,6 +1388,8 @@ struct CausationInfo {
 /// Top-level metadata payload written by the runner for each policy-issued command.
 #[derive(Debug, Serialize)]
 struct CausationPayload {
+    /// The triggering event's id, under the well-known [`Metadata::CAUSATION_ID_KEY`].
+    causation_id: String,
     causation: CausationInfo,
 }
 
@@ -1345,6 +1403,7 @@ fn causation_metadata(
     raw: &PersistedEvent<Value>,
 ) -> Metadata {
     Metadata::new(CausationPayload {
+        causation_id: raw.id.to_string(),
         causation: CausationInfo {
             policy: policy_name.to_string(),
             event_id: raw.id.to_string(),
@@ -1490,7 +1549,41 @@ mod tests {
     use replay::Metadata;
     use serde_json::json;
 
-    use super::merge_dispatch_metadata;
+    use super::{causation_metadata, merge_dispatch_metadata};
+    use crate::PersistedEvent;
+
+    fn raw_event(metadata: Metadata) -> PersistedEvent<serde_json::Value> {
+        PersistedEvent {
+            id: uuid::Uuid::new_v4(),
+            data: json!({}),
+            stream_id: "urn:order:1".parse().unwrap(),
+            r#type: "OrderPlaced".to_string(),
+            version: 1,
+            created: chrono::Utc::now(),
+            metadata,
+            aggregate_version: None,
+            global_position: 1,
+        }
+    }
+
+    #[test]
+    fn causation_metadata_stamps_causation_id() {
+        let raw = raw_event(Metadata::default());
+
+        let metadata = causation_metadata("p", 7, &raw);
+
+        assert_eq!(metadata.causation_id(), Some(raw.id.to_string().as_str()));
+        assert_eq!(metadata.to_json()["causation"]["global_position"], 7);
+    }
+
+    #[test]
+    fn correlation_id_is_inherited_or_started_from_the_root_event() {
+        let root = raw_event(Metadata::default());
+        assert_eq!(root.chained_correlation_id(), root.id.to_string());
+
+        let reaction = raw_event(Metadata::default().with_correlation_id("req-1"));
+        assert_eq!(reaction.chained_correlation_id(), "req-1");
+    }
 
     #[test]
     fn merges_dispatch_metadata_without_collisions() {
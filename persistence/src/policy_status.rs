@@ -120,6 +120,10 @@ impl PolicyStatusStore {
     /// `LATERAL` aggregate over `policy_dead_letters`.  The lateral is filtered
     /// by `pc.name`, so it uses the `(policy_name, created_at)` index instead of
     /// aggregating the whole dead-letter table.  The event log is never scanned.
+    ///
+    /// With the `metrics` feature, each call also sets the
+    /// [`POLICY_LAG`](crate::metrics::POLICY_LAG) gauge of every listed policy, so polling
+    /// `list` keeps the exported lag current.
     pub async fn list(&self) -> Result<Vec<PolicyStatus>, replay::Error> {
         let rows = sqlx::query(
             r#"
@@ -161,6 +165,7 @@ impl PolicyStatusStore {
                 let last_dead_letter_at: Option<DateTime<Utc>> = r.get("last_dead_letter_at");
                 let lag = head - position;
                 let condition = PolicyCondition::from_fields(lag, dead_letter_count);
+                crate::metrics::record_policy_lag(&name, lag);
                 PolicyStatus {
                     name,
                     position,
@@ -0,0 +1,234 @@
+//! Named queries, dispatched by name with JSON parameters, to expose read models generically.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{Cqrs, EventStore, Query};
+
+type QueryHandler<ES> =
+    Arc<dyn Fn(Cqrs<ES>, Value) -> BoxFuture<'static, replay::Result<Value>> + Send + Sync>;
+
+/// A registry of named queries: each name deserializes its parameters, builds a [`Query`]
+/// from them, runs it through [`Cqrs::run_query`] and answers with the query serialized.
+///
+/// A transport only needs the name and the parameters, so one endpoint serves every read
+/// model; with the `axum` feature, [`router`](Self::router) is that endpoint over HTTP.
+///
+/// ```rust,ignore
+/// let queries = QueryBus::new(cqrs)
+///     .with_query("balance", |params: BalanceParams| Balance::of(params.account))
+///     .with_query("open-accounts", |()| OpenAccounts::default());
+///
+/// let balance = queries.dispatch("balance", json!({ "account": "urn:bank-account:42" })).await?;
+/// ```
+pub struct QueryBus<ES: EventStore> {
+    cqrs: Cqrs<ES>,
+    handlers: BTreeMap<String, QueryHandler<ES>>,
+}
+
+impl<ES: EventStore + 'static> QueryBus<ES> {
+    pub fn new(cqrs: Cqrs<ES>) -> Self {
+        QueryBus {
+            cqrs,
+            handlers: BTreeMap::new(),
+        }
+    }
+
+    /// Register `name`, replacing an earlier query of that name: its parameters deserialize
+    /// as `P` and `build` turns them into the query to run.
+    pub fn with_query<P, Q>(
+        mut self,
+        name: impl Into<String>,
+        build: impl Fn(P) -> Q + Send + Sync + 'static,
+    ) -> Self
+    where
+        P: DeserializeOwned,
+        Q: Query + Serialize + 'static,
+        Q::Event: 'static,
+    {
+        let name = name.into();
+        let query_name = name.clone();
+        let handler: QueryHandler<ES> = Arc::new(move |cqrs, params| {
+            let query = serde_json::from_value::<P>(params)
+                .map(&build)
+                .map_err(|e| {
+                    replay::Error::invalid_input("query parameters do not match the query")
+                        .with_operation("dispatch_query")
+                        .with_context("query", &query_name)
+                        .with_context("error", e)
+                });
+            async move {
+                let mut query = query?;
+                cqrs.run_query(&mut query).await?;
+                serde_json::to_value(&query).map_err(crate::ser_error)
+            }
+            .boxed()
+        });
+        self.handlers.insert(name, handler);
+        self
+    }
+
+    /// The registered query names, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.handlers.keys().map(String::as_str)
+    }
+
+    /// Run the query `name` with `params` and return its serialized result. Fails with
+    /// `NotFound` for an unknown name and `InvalidInput` for parameters that don't fit.
+    pub async fn dispatch(&self, name: &str, params: Value) -> replay::Result<Value> {
+        let Some(handler) = self.handlers.get(name) else {
+            return Err(
+                replay::Error::not_found("no query is registered under this name")
+                    .with_operation("dispatch_query")
+                    .with_context("query", name)
+                    .recorded(),
+            );
+        };
+        handler(self.cqrs.clone(), params)
+            .await
+            .map_err(replay::Error::recorded)
+    }
+}
+
+#[cfg(feature = "axum")]
+impl<ES: EventStore + 'static> QueryBus<ES> {
+    /// An axum router answering `POST /queries/{name}`, whose JSON body holds the parameters,
+    /// with `200 OK` and the result. Errors answer like the
+    /// [`CommandRouter`](crate::CommandRouter)'s: the status of their `ErrorKind` and a body
+    /// like `{"error": "not_found", "message": "..."}`.
+    pub fn router<S>(self) -> axum::Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        axum::Router::new()
+            .route("/queries/{name}", axum::routing::post(dispatch::<ES>))
+            .with_state(Arc::new(self))
+    }
+}
+
+#[cfg(feature = "axum")]
+async fn dispatch<ES: EventStore + 'static>(
+    axum::extract::State(bus): axum::extract::State<Arc<QueryBus<ES>>>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+    axum::Json(params): axum::Json<Value>,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+
+    match bus.dispatch(&name, params).await {
+        Ok(result) => axum::Json(result).into_response(),
+        Err(err) => crate::command_router::replay_error_response(&err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use replay::{ErrorKind, EventStream, Metadata};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+    use crate::conformance::{ConformanceAccount, ConformanceAccountUrn, ConformanceEvent};
+    use crate::{InMemoryEventStore, PersistedEvent};
+
+    #[derive(Deserialize)]
+    struct DepositsParams {
+        at_least: i64,
+    }
+
+    #[derive(Default, Serialize)]
+    struct Deposits {
+        #[serde(skip)]
+        at_least: i64,
+        count: usize,
+        total: i64,
+    }
+
+    impl Query for Deposits {
+        type Event = ConformanceEvent;
+
+        fn update(&mut self, event: PersistedEvent<ConformanceEvent>) {
+            match event.data {
+                ConformanceEvent::Deposited { amount } if amount >= self.at_least => {
+                    self.count += 1;
+                    self.total += amount;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    async fn bus() -> QueryBus<InMemoryEventStore> {
+        let store = InMemoryEventStore::new();
+        store
+            .store_events::<ConformanceAccount>(
+                &ConformanceAccountUrn::new_random(),
+                ConformanceAccount::stream_type(),
+                Metadata::default(),
+                &[
+                    ConformanceEvent::Deposited { amount: 10 },
+                    ConformanceEvent::Deposited { amount: 5 },
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+        QueryBus::new(Cqrs::new(store)).with_query("deposits", |params: DepositsParams| Deposits {
+            at_least: params.at_least,
+            ..Deposits::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn queries_are_dispatched_by_name() {
+        let bus = bus().await;
+
+        let result = bus
+            .dispatch("deposits", json!({ "at_least": 6 }))
+            .await
+            .unwrap();
+
+        assert_eq!(result, json!({ "count": 1, "total": 10 }));
+        assert_eq!(bus.names().collect::<Vec<_>>(), ["deposits"]);
+    }
+
+    #[tokio::test]
+    async fn unknown_queries_and_bad_parameters_are_rejected() {
+        let bus = bus().await;
+
+        let err = bus.dispatch("withdrawals", json!({})).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let err = bus
+            .dispatch("deposits", json!({ "at_least": "six" }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[cfg(feature = "axum")]
+    #[tokio::test]
+    async fn the_router_serves_queries_over_http() {
+        use axum::body::{to_bytes, Body};
+        use axum::http::{header, Request, StatusCode};
+        use tower::ServiceExt;
+
+        let app: axum::Router = bus().await.router();
+        let request = Request::post("/queries/deposits")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"at_least":0}"#))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({ "count": 2, "total": 15 }));
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use urn::Urn;
+
+/// Buckets kept by a [`TokenBucketRateLimiter`] before the full ones are dropped.
+const MAX_IDLE_BUCKETS: usize = 10_000;
+
+/// A command about to run through [`Cqrs::execute`](crate::Cqrs::execute), as seen by a
+/// [`RateLimiter`].
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimitRequest<'a> {
+    pub stream_type: &'static str,
+    pub stream_id: &'a Urn,
+    /// The actor of the command's metadata, after the handle's default is stamped in.
+    pub actor: Option<&'a str>,
+}
+
+/// Admits or rejects commands before their aggregate is loaded, to protect hot aggregates
+/// from command storms (see [`Cqrs::with_rate_limiter`](crate::Cqrs::with_rate_limiter)).
+///
+/// [`TokenBucketRateLimiter`] limits per stream or per actor in memory. Any
+/// `Fn(&RateLimitRequest) -> Result<(), Duration> + Send + Sync` closure is a limiter, e.g.
+/// to consult a counter shared by every instance of the service.
+pub trait RateLimiter: Send + Sync {
+    /// `Ok` admits the command; `Err` rejects it with how long to wait before retrying.
+    fn check(&self, request: &RateLimitRequest<'_>) -> Result<(), Duration>;
+}
+
+impl<F> RateLimiter for F
+where
+    F: Fn(&RateLimitRequest<'_>) -> Result<(), Duration> + Send + Sync,
+{
+    fn check(&self, request: &RateLimitRequest<'_>) -> Result<(), Duration> {
+        self(request)
+    }
+}
+
+/// What a [`TokenBucketRateLimiter`] keeps a bucket for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BucketKey {
+    Stream,
+    Actor,
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+/// Allows bursts of up to `burst` commands, then one command per `refill_every`, for each
+/// stream or each actor. Buckets live in this process's memory.
+///
+/// ```rust,ignore
+/// // 20 commands at once per account, then 5 per second
+/// let cqrs = cqrs.with_rate_limiter(TokenBucketRateLimiter::per_stream(20, Duration::from_millis(200)));
+/// ```
+pub struct TokenBucketRateLimiter {
+    burst: f64,
+    refill_every: Duration,
+    key: BucketKey,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl TokenBucketRateLimiter {
+    /// A bucket per stream id.
+    pub fn per_stream(burst: u32, refill_every: Duration) -> Self {
+        Self::new(BucketKey::Stream, burst, refill_every)
+    }
+
+    /// A bucket per actor; commands without an actor share one.
+    pub fn per_actor(burst: u32, refill_every: Duration) -> Self {
+        Self::new(BucketKey::Actor, burst, refill_every)
+    }
+
+    fn new(key: BucketKey, burst: u32, refill_every: Duration) -> Self {
+        TokenBucketRateLimiter {
+            burst: f64::from(burst.max(1)),
+            refill_every: refill_every.max(Duration::from_nanos(1)),
+            key,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn refill(&self, bucket: &mut Bucket, now: Instant) {
+        let earned =
+            now.duration_since(bucket.refilled).as_secs_f64() / self.refill_every.as_secs_f64();
+        bucket.tokens = (bucket.tokens + earned).min(self.burst);
+        bucket.refilled = now;
+    }
+}
+
+impl RateLimiter for TokenBucketRateLimiter {
+    fn check(&self, request: &RateLimitRequest<'_>) -> Result<(), Duration> {
+        let key = match self.key {
+            BucketKey::Stream => request.stream_id.to_string(),
+            BucketKey::Actor => request.actor.unwrap_or_default().to_string(),
+        };
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if buckets.len() >= MAX_IDLE_BUCKETS {
+            // A full bucket behaves like a missing one, so forgetting it changes nothing.
+            buckets.retain(|_, bucket| {
+                self.refill(bucket, now);
+                bucket.tokens < self.burst
+            });
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: self.burst,
+            refilled: now,
+        });
+        self.refill(bucket, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(self.refill_every.mul_f64(1.0 - bucket.tokens))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn request<'a>(stream_id: &'a Urn, actor: Option<&'a str>) -> RateLimitRequest<'a> {
+        RateLimitRequest {
+            stream_type: "Counter",
+            stream_id,
+            actor,
+        }
+    }
+
+    #[test]
+    fn buckets_admit_a_burst_then_ask_to_wait() {
+        let limiter = TokenBucketRateLimiter::per_stream(2, Duration::from_secs(60));
+        let hot = Urn::from_str("urn:counter:hot").unwrap();
+        let cold = Urn::from_str("urn:counter:cold").unwrap();
+
+        assert!(limiter.check(&request(&hot, None)).is_ok());
+        assert!(limiter.check(&request(&hot, None)).is_ok());
+        let retry_after = limiter.check(&request(&hot, None)).unwrap_err();
+        assert!(retry_after > Duration::from_secs(59) && retry_after <= Duration::from_secs(60));
+        assert!(limiter.check(&request(&cold, None)).is_ok());
+    }
+
+    #[test]
+    fn actor_buckets_span_streams() {
+        let limiter = TokenBucketRateLimiter::per_actor(1, Duration::from_secs(60));
+        let first = Urn::from_str("urn:counter:1").unwrap();
+        let second = Urn::from_str("urn:counter:2").unwrap();
+
+        assert!(limiter.check(&request(&first, Some("ada"))).is_ok());
+        assert!(limiter.check(&request(&second, Some("ada"))).is_err());
+        assert!(limiter.check(&request(&second, Some("grace"))).is_ok());
+    }
+}
@@ -0,0 +1,88 @@
+/// Tuning for how [`PostgresEventStore`](crate::PostgresEventStore) reads events.
+///
+/// - **buffer size**: rows decoded ahead of the consumer (default 4). Raise it for long
+///   replays whose consumer does async work per event.
+/// - **fetch batch size**: rows per query. By default one query streams every match; with a
+///   batch size, the read is split into keyset-paginated queries of that many rows, so a long
+///   replay doesn't hold one connection and one server-side result for its whole duration.
+/// - **limit**: most rows a read returns, for "first N" reads that shouldn't pull more.
+///
+/// Install defaults with
+/// [`PostgresEventStore::with_read_options`](crate::PostgresEventStore::with_read_options), or
+/// pass options per call to
+/// [`PostgresEventStore::stream_events_with`](crate::PostgresEventStore::stream_events_with).
+/// A limit in the store defaults also caps the reads that rebuild aggregates, so keep limits
+/// per call.
+///
+/// ```rust,ignore
+/// let replay = ReadOptions::default().with_buffer_size(64).with_fetch_batch_size(10_000);
+/// let latest = ReadOptions::default().with_limit(10);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReadOptions {
+    pub(crate) buffer_size: usize,
+    pub(crate) fetch_batch_size: Option<usize>,
+    pub(crate) limit: Option<usize>,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        ReadOptions {
+            buffer_size: 4,
+            fetch_batch_size: None,
+            limit: None,
+        }
+    }
+}
+
+impl ReadOptions {
+    /// Decode up to `size` rows ahead of the consumer (at least 1).
+    pub fn with_buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = size.max(1);
+        self
+    }
+
+    /// Fetch `size` rows per query (at least 1).
+    pub fn with_fetch_batch_size(mut self, size: usize) -> Self {
+        self.fetch_batch_size = Some(size.max(1));
+        self
+    }
+
+    /// Return at most `limit` rows.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Rows the next query should ask for, given `read` rows returned so far; `None` for no
+    /// `LIMIT`.
+    pub(crate) fn next_page_size(&self, read: usize) -> Option<usize> {
+        let remaining = self.limit.map(|limit| limit.saturating_sub(read));
+        match (self.fetch_batch_size, remaining) {
+            (Some(batch), Some(remaining)) => Some(batch.min(remaining)),
+            (batch, remaining) => batch.or(remaining),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_shrink_to_the_remaining_limit() {
+        let options = ReadOptions::default()
+            .with_fetch_batch_size(100)
+            .with_limit(250);
+        assert_eq!(options.next_page_size(0), Some(100));
+        assert_eq!(options.next_page_size(200), Some(50));
+        assert_eq!(options.next_page_size(250), Some(0));
+
+        assert_eq!(ReadOptions::default().next_page_size(0), None);
+        assert_eq!(
+            ReadOptions::default().with_limit(3).next_page_size(1),
+            Some(2)
+        );
+        assert_eq!(ReadOptions::default().with_buffer_size(0).buffer_size, 1);
+    }
+}
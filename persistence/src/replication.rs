@@ -0,0 +1,285 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use futures::{stream, TryStream, TryStreamExt};
+use replay::EventStream;
+
+use crate::{EventStore, InMemoryEventStore, PersistedEvent, PostgresEventStore, StreamFilter};
+
+/// Stores that can load already-persisted events as they are, keeping their ids, versions,
+/// timestamps and metadata; the target side of [`replicate`].
+pub trait BulkImport: EventStore {
+    /// Import `events` of `S` streams, returning how many were imported. Nothing is imported
+    /// when an event id already exists (a `Conflict`).
+    fn bulk_import<S, Events>(
+        &self,
+        events: Events,
+    ) -> impl Future<Output = Result<u64, replay::Error>> + Send
+    where
+        S: EventStream,
+        Events: TryStream<Ok = PersistedEvent<S::Event>, Error = replay::Error> + Send;
+}
+
+impl BulkImport for InMemoryEventStore {
+    fn bulk_import<S, Events>(
+        &self,
+        events: Events,
+    ) -> impl Future<Output = Result<u64, replay::Error>> + Send
+    where
+        S: EventStream,
+        Events: TryStream<Ok = PersistedEvent<S::Event>, Error = replay::Error> + Send,
+    {
+        InMemoryEventStore::bulk_import::<S, Events>(self, events)
+    }
+}
+
+impl BulkImport for PostgresEventStore {
+    fn bulk_import<S, Events>(
+        &self,
+        events: Events,
+    ) -> impl Future<Output = Result<u64, replay::Error>> + Send
+    where
+        S: EventStream,
+        Events: TryStream<Ok = PersistedEvent<S::Event>, Error = replay::Error> + Send,
+    {
+        PostgresEventStore::bulk_import::<S, Events>(self, events)
+    }
+}
+
+/// How far a [`replicate`] run got.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReplicationProgress {
+    /// Events copied by this run.
+    pub replicated: u64,
+    /// Source global position of the last event copied; pass it to
+    /// [`ReplicationOptions::with_resume_from`] to continue an interrupted run.
+    pub position: i64,
+}
+
+type ProgressCallback = Arc<dyn Fn(ReplicationProgress) + Send + Sync>;
+
+/// Tuning for [`replicate`].
+///
+/// ```rust,ignore
+/// let options = ReplicationOptions::default()
+///     .with_resume_from(checkpoint)
+///     .with_batch_size(5_000)
+///     .with_progress(|progress| tracing::info!(?progress, "replicating"));
+/// ```
+#[derive(Clone)]
+pub struct ReplicationOptions {
+    resume_from: i64,
+    up_to: Option<i64>,
+    batch_size: usize,
+    progress: Option<ProgressCallback>,
+}
+
+impl Default for ReplicationOptions {
+    fn default() -> Self {
+        ReplicationOptions {
+            resume_from: 0,
+            up_to: None,
+            batch_size: 1_000,
+            progress: None,
+        }
+    }
+}
+
+impl ReplicationOptions {
+    /// Copy only the events past the source global position `position`.
+    pub fn with_resume_from(mut self, position: i64) -> Self {
+        self.resume_from = position;
+        self
+    }
+
+    /// Copy only the events up to the source global position `position`, e.g. a
+    /// gap-free cut of a store that is still being written to.
+    pub fn with_up_to(mut self, position: i64) -> Self {
+        self.up_to = Some(position);
+        self
+    }
+
+    /// Import `size` events per target write (at least 1).
+    pub fn with_batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Call `progress` after every batch written to the target.
+    pub fn with_progress<F>(mut self, progress: F) -> Self
+    where
+        F: Fn(ReplicationProgress) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+}
+
+/// Copy the events of `S` streams matching `filter` from `source` to `target` in global
+/// order, keeping their ids, versions, timestamps and metadata; the building block for
+/// migrating between backends.
+///
+/// Events are imported in batches with [`BulkImport::bulk_import`], so they get new global
+/// positions in the target. A failed run leaves every batch before the failure imported;
+/// resume it from the last reported [`ReplicationProgress::position`].
+///
+/// ```rust,ignore
+/// let progress = replicate::<BankAccount, _, _>(
+///     &old_store,
+///     &new_store,
+///     StreamFilter::All,
+///     ReplicationOptions::default().with_resume_from(checkpoint),
+/// )
+/// .await?;
+/// ```
+pub async fn replicate<S, Source, Target>(
+    source: &Source,
+    target: &Target,
+    filter: StreamFilter,
+    options: ReplicationOptions,
+) -> Result<ReplicationProgress, replay::Error>
+where
+    S: EventStream,
+    Source: EventStore,
+    Target: BulkImport,
+{
+    let filter = StreamFilter::for_stream_type::<S>()
+        .and(filter)
+        .and(StreamFilter::after_global_position(options.resume_from));
+    let events = source.stream_events::<S::Event>(filter).into_stream();
+    futures::pin_mut!(events);
+
+    let mut progress = ReplicationProgress {
+        replicated: 0,
+        position: options.resume_from,
+    };
+    let mut batch: Vec<PersistedEvent<S::Event>> = Vec::with_capacity(options.batch_size);
+    loop {
+        let event = events.try_next().await?.filter(|event| {
+            options
+                .up_to
+                .is_none_or(|up_to| event.global_position <= up_to)
+        });
+        let done = event.is_none();
+        batch.extend(event);
+        if !batch.is_empty() && (done || batch.len() >= options.batch_size) {
+            let position = batch[batch.len() - 1].global_position;
+            let events = std::mem::take(&mut batch).into_iter().map(Ok);
+            let imported = target
+                .bulk_import::<S, _>(stream::iter(events))
+                .await
+                .map_err(|e| {
+                    e.with_operation("replicate")
+                        .with_context("resume_from", progress.position)
+                })?;
+            progress.replicated += imported;
+            progress.position = position;
+            if let Some(report) = &options.progress {
+                report(progress);
+            }
+        }
+        if done {
+            break;
+        }
+    }
+    Ok(progress)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use replay::Metadata;
+
+    use super::*;
+    use crate::conformance::{ConformanceAccount, ConformanceAccountUrn, ConformanceEvent};
+
+    async fn deposit(store: &InMemoryEventStore, id: &ConformanceAccountUrn, amount: i64) {
+        store
+            .store_events::<ConformanceAccount>(
+                id,
+                ConformanceAccount::stream_type(),
+                Metadata::default().with_actor("ada"),
+                &[ConformanceEvent::Deposited { amount }],
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn all_events(store: &InMemoryEventStore) -> Vec<PersistedEvent<ConformanceEvent>> {
+        store
+            .stream_events::<ConformanceEvent>(StreamFilter::All)
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn events_are_copied_as_they_are_in_batches() {
+        let (source, target) = (InMemoryEventStore::new(), InMemoryEventStore::new());
+        let (a, b) = (
+            ConformanceAccountUrn::new_random(),
+            ConformanceAccountUrn::new_random(),
+        );
+        for (id, amount) in [(&a, 1), (&b, 2), (&a, 3)] {
+            deposit(&source, id, amount).await;
+        }
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let sink = reported.clone();
+
+        let options = ReplicationOptions::default()
+            .with_batch_size(2)
+            .with_progress(move |progress| sink.lock().unwrap().push(progress.position));
+        let progress =
+            replicate::<ConformanceAccount, _, _>(&source, &target, StreamFilter::All, options)
+                .await
+                .unwrap();
+
+        assert_eq!(progress.replicated, 3);
+        assert_eq!(*reported.lock().unwrap(), [2, 3]);
+        let (copied, original) = (all_events(&target).await, all_events(&source).await);
+        let identity = |e: &PersistedEvent<ConformanceEvent>| {
+            (
+                e.id,
+                e.stream_id.clone(),
+                e.version,
+                e.metadata.clone(),
+                e.data.clone(),
+            )
+        };
+        assert_eq!(
+            copied.iter().map(identity).collect::<Vec<_>>(),
+            original.iter().map(identity).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn runs_resume_after_a_position() {
+        let (source, target) = (InMemoryEventStore::new(), InMemoryEventStore::new());
+        let id = ConformanceAccountUrn::new_random();
+        for amount in [1, 2, 3] {
+            deposit(&source, &id, amount).await;
+        }
+
+        let options = ReplicationOptions::default().with_resume_from(1);
+        let progress =
+            replicate::<ConformanceAccount, _, _>(&source, &target, StreamFilter::All, options)
+                .await
+                .unwrap();
+
+        assert_eq!(
+            progress,
+            ReplicationProgress {
+                replicated: 2,
+                position: 3
+            }
+        );
+        let versions: Vec<_> = all_events(&target)
+            .await
+            .iter()
+            .map(|e| e.version)
+            .collect();
+        assert_eq!(versions, [2, 3]);
+    }
+}
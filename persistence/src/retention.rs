@@ -0,0 +1,265 @@
+//! Retention of superseded event history in the Postgres store.
+//!
+//! Compaction keeps the history it replaces as archived events (`aggregate_version` set)
+//! while the live stream starts over from a compacted snapshot. Retention expires those
+//! archived events by age or by count per stream, handing them to a [`ColdStorage`] first
+//! or simply deleting them. Live events are never touched, so a stream that was never
+//! compacted, and therefore has no snapshot to load from, keeps its whole history.
+//!
+//! Deleting leaves holes in `global_position`. Retention only deletes below the contiguous
+//! high-water mark and the slowest policy cursor, and records the high-water mark as the
+//! retention horizon (migration `0022_event_retention`): holes up to it are expired events,
+//! which the policy feed steps over instead of waiting for them to commit.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use futures::future::BoxFuture;
+use serde_json::Value;
+use sqlx::{PgExecutor, Row};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::infrastructure::event_from_row;
+use crate::{PersistedEvent, PostgresEventStore};
+
+/// Expired events removed per transaction by [`PostgresEventStore::apply_retention`].
+const RETENTION_BATCH_SIZE: i64 = 1_000;
+
+/// Where a [`RetentionPolicy`] moves expired events before deleting them, e.g. an object
+/// store bucket.
+///
+/// Events are handed over in their stored form: encrypted payloads stay encrypted. A batch
+/// is deleted once `archive` returns `Ok`, and a failed batch is retried on the next pass, so
+/// archiving the same events twice must be harmless.
+pub trait ColdStorage: Send + Sync {
+    fn archive<'a>(
+        &'a self,
+        events: &'a [PersistedEvent<Value>],
+    ) -> BoxFuture<'a, Result<(), replay::Error>>;
+}
+
+/// How long the archived history of one stream type is kept.
+///
+/// ```rust,ignore
+/// let policy = RetentionPolicy::for_stream_type(BankAccount::stream_type())
+///     .with_max_age(Duration::from_secs(365 * 24 * 60 * 60))
+///     .with_max_events(10_000)
+///     .archive_to(bucket);
+/// ```
+#[derive(Clone)]
+pub struct RetentionPolicy {
+    stream_type: String,
+    max_age: Option<Duration>,
+    max_events: Option<u64>,
+    /// `None` deletes expired events outright.
+    cold_storage: Option<Arc<dyn ColdStorage>>,
+}
+
+impl RetentionPolicy {
+    /// A policy for the streams of `stream_type` that expires nothing until given a limit.
+    pub fn for_stream_type(stream_type: impl Into<String>) -> Self {
+        RetentionPolicy {
+            stream_type: stream_type.into(),
+            max_age: None,
+            max_events: None,
+            cold_storage: None,
+        }
+    }
+
+    /// Expire archived events created longer than `max_age` ago.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Keep at most the newest `max_events` archived events of each stream.
+    pub fn with_max_events(mut self, max_events: u64) -> Self {
+        self.max_events = Some(max_events);
+        self
+    }
+
+    /// Hand expired events to `cold_storage` before deleting them.
+    pub fn archive_to(mut self, cold_storage: impl ColdStorage + 'static) -> Self {
+        self.cold_storage = Some(Arc::new(cold_storage));
+        self
+    }
+
+    pub fn stream_type(&self) -> &str {
+        &self.stream_type
+    }
+}
+
+/// Handle for the background task spawned by [`PostgresEventStore::start_retention`].
+pub struct RetentionDaemon {
+    shutdown_tx: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+impl RetentionDaemon {
+    /// Stop the task, letting a pass in progress finish its current batch.
+    pub async fn shutdown(self) {
+        let _ = self.shutdown_tx.send(true);
+        let _ = self.task.await;
+    }
+}
+
+impl PostgresEventStore {
+    /// Remove the archived events of `policy`'s stream type that expired, archiving them to
+    /// its [`ColdStorage`] first if it has one. Returns how many were removed.
+    ///
+    /// Only events every policy has already processed expire, so a lagging policy still
+    /// sees the history it reacts to. Runs in batches of a thousand events, one transaction
+    /// each. Like the other maintenance operations, run it as a role not bound by
+    /// row-level security.
+    pub async fn apply_retention(&self, policy: &RetentionPolicy) -> Result<u64, replay::Error> {
+        if policy.max_age.is_none() && policy.max_events.is_none() {
+            return Ok(0);
+        }
+        let cutoff = policy.max_age.map(|max_age| {
+            Utc::now() - chrono::Duration::from_std(max_age).unwrap_or(chrono::Duration::MAX)
+        });
+        let max_events = policy.max_events.map(|max| max.min(i64::MAX as u64) as i64);
+        let map_error = |e| {
+            crate::db_error(e)
+                .with_operation("apply_retention")
+                .with_context("stream_type", &policy.stream_type)
+        };
+
+        let horizon = self.contiguous_high_water_mark().await?;
+
+        let mut removed = 0;
+        loop {
+            let mut tx = self.pool().begin().await.map_err(map_error)?;
+            // A NULL limit compares as unknown, so it never expires anything.
+            let rows = sqlx::query(
+                "SELECT id, data, data_binary, data_encoding, metadata, stream_id, type, version, \
+                        created, aggregate_version, global_position \
+                 FROM ( \
+                    SELECT e.*, row_number() OVER ( \
+                        PARTITION BY e.stream_id \
+                        ORDER BY e.aggregate_version DESC, e.version DESC \
+                    ) AS newest \
+                    FROM events e JOIN streams s ON s.id = e.stream_id \
+                    WHERE s.type = $1 AND e.aggregate_version IS NOT NULL \
+                 ) archived \
+                 WHERE (created < $2 OR newest > $3) \
+                   AND global_position <= LEAST($4, (SELECT MIN(position) FROM policy_cursors)) \
+                 ORDER BY global_position \
+                 LIMIT $5",
+            )
+            .bind(&policy.stream_type)
+            .bind(cutoff)
+            .bind(max_events)
+            .bind(horizon)
+            .bind(RETENTION_BATCH_SIZE)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(map_error)?;
+            let batch = rows.len() as i64;
+            if batch == 0 {
+                break;
+            }
+
+            let ids: Vec<Uuid> = rows.iter().map(|row| row.get("id")).collect();
+            if let Some(cold_storage) = &policy.cold_storage {
+                let events = rows
+                    .into_iter()
+                    .map(|row| event_from_row(row, self.serializer(), None, None))
+                    .collect::<Result<Vec<PersistedEvent<Value>>, _>>()?;
+                cold_storage.archive(&events).await?;
+            }
+
+            sqlx::query(
+                "INSERT INTO event_retention (horizon) VALUES ($1) \
+                 ON CONFLICT (id) DO UPDATE \
+                    SET horizon = GREATEST(event_retention.horizon, EXCLUDED.horizon)",
+            )
+            .bind(horizon)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_error)?;
+            sqlx::query("DELETE FROM events WHERE id = ANY($1)")
+                .bind(&ids)
+                .execute(&mut *tx)
+                .await
+                .map_err(map_error)?;
+            tx.commit().await.map_err(map_error)?;
+            removed += batch as u64;
+
+            if batch < RETENTION_BATCH_SIZE {
+                break;
+            }
+        }
+
+        if removed > 0 {
+            tracing::info!(
+                stream_type = %policy.stream_type,
+                removed,
+                archived = policy.cold_storage.is_some(),
+                "retention removed expired events"
+            );
+        }
+        Ok(removed)
+    }
+
+    /// Apply `policies` every `interval` in a background task, starting now. A failing pass
+    /// is logged and retried on the next tick.
+    ///
+    /// Use [`RetentionDaemon::shutdown`] to stop it.
+    pub fn start_retention(
+        &self,
+        policies: Vec<RetentionPolicy>,
+        interval: Duration,
+    ) -> RetentionDaemon {
+        let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+        let store = self.clone();
+
+        let task = tokio::spawn(async move {
+            loop {
+                for policy in &policies {
+                    if *shutdown_rx.borrow() {
+                        return;
+                    }
+                    if let Err(error) = store.apply_retention(policy).await {
+                        tracing::warn!(
+                            stream_type = %policy.stream_type,
+                            error = %error,
+                            "retention pass failed; retrying next interval"
+                        );
+                    }
+                }
+
+                tokio::select! {
+                    changed = shutdown_rx.changed() => {
+                        if changed.is_err() || *shutdown_rx.borrow() {
+                            return;
+                        }
+                    }
+                    _ = tokio::time::sleep(interval) => {}
+                }
+            }
+        });
+
+        RetentionDaemon { shutdown_tx, task }
+    }
+}
+
+/// The retention horizon: every position up to it had committed when retention deleted
+/// events below it, so a hole there is an expired event. `0` before the first deletion, and
+/// without the `0022_event_retention` migration.
+pub(crate) async fn retention_horizon<'e>(
+    executor: impl PgExecutor<'e>,
+) -> Result<i64, sqlx::Error> {
+    match sqlx::query_scalar("SELECT horizon FROM event_retention")
+        .fetch_optional(executor)
+        .await
+    {
+        Ok(horizon) => Ok(horizon.unwrap_or(0)),
+        // undefined_table: retention was never set up
+        Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("42P01") => Ok(0),
+        Err(e) => Err(e),
+    }
+}
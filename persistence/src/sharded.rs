@@ -0,0 +1,418 @@
+//! Horizontal write scaling: [`ShardedEventStore`] spreads streams over several stores by a
+//! consistent hash of their URN.
+
+use std::sync::Arc;
+
+use futures::{TryStream, TryStreamExt};
+use urn::Urn;
+
+use replay::{Compactable, Event, Snapshottable, StreamId};
+
+use crate::{
+    AllEvents, CompactionOutcome, DeletionMode, EventSink, EventStore, PersistedEvent, StoreHealth,
+    StreamFilter, StreamListing, StreamMetadata, StreamPage, StreamRetention, StreamState,
+};
+
+/// Points each shard gets on the hash ring; more points even out the share of streams.
+const POINTS_PER_SHARD: u32 = 64;
+
+/// An [`EventStore`] that routes every stream to one of several stores, so appends scale
+/// past what one database can take.
+///
+/// A stream lives on the shard its URN hashes to on a consistent-hash ring, so all of its
+/// events, versions and compactions stay on one store and optimistic concurrency works as
+/// before. Adding a shard moves about `1/N` of the streams; copy them over (e.g. with
+/// [`replicate`](crate::replicate) and a [`StreamFilter::WithStreamIds`] filter) before
+/// switching.
+///
+/// Reads of a single stream go to its shard. Other reads fan out to every shard and merge
+/// the results by `created`. Global positions are per shard, so
+/// [`StreamFilter::AfterGlobalPosition`] cursors don't carry across a fan-out read; run
+/// position-based consumers against each shard instead.
+///
+/// ```rust,ignore
+/// let store = ShardedEventStore::new(vec![
+///     PostgresEventStore::new(pool_a),
+///     PostgresEventStore::new(pool_b),
+///     PostgresEventStore::new(pool_c),
+/// ]);
+/// let cqrs = Cqrs::new(store);
+/// ```
+pub struct ShardedEventStore<ES> {
+    shards: Arc<[ES]>,
+    ring: Arc<[(u64, usize)]>,
+}
+
+impl<ES: EventStore> ShardedEventStore<ES> {
+    /// Shard over `shards`, in a fixed order: the position of a store in the list is part of
+    /// where streams hash to.
+    ///
+    /// # Panics
+    ///
+    /// If `shards` is empty.
+    pub fn new(shards: Vec<ES>) -> Self {
+        assert!(
+            !shards.is_empty(),
+            "a sharded store needs at least one shard"
+        );
+        let mut ring: Vec<(u64, usize)> = (0..shards.len())
+            .flat_map(|shard| {
+                (0..POINTS_PER_SHARD).map(move |point| (fnv1a(&format!("{shard}#{point}")), shard))
+            })
+            .collect();
+        ring.sort_unstable();
+        Self {
+            shards: shards.into(),
+            ring: ring.into(),
+        }
+    }
+
+    pub fn shards(&self) -> &[ES] {
+        &self.shards
+    }
+
+    /// The index of the shard holding `stream_id`.
+    pub fn shard_for(&self, stream_id: &Urn) -> usize {
+        let hash = fnv1a(stream_id.as_ref());
+        let point = self.ring.partition_point(|(at, _)| *at < hash);
+        self.ring[point % self.ring.len()].1
+    }
+
+    fn shard(&self, stream_id: &Urn) -> &ES {
+        &self.shards[self.shard_for(stream_id)]
+    }
+}
+
+impl<ES> Clone for ShardedEventStore<ES> {
+    fn clone(&self) -> Self {
+        Self {
+            shards: self.shards.clone(),
+            ring: self.ring.clone(),
+        }
+    }
+}
+
+impl<ES: EventStore> EventStore for ShardedEventStore<ES> {
+    async fn store_events_stream<S, Events, Sink>(
+        &self,
+        stream_id: &S::StreamId,
+        stream_type: &str,
+        metadata: replay::Metadata,
+        domain_events: Events,
+        expected_version: Option<i64>,
+        sink: Sink,
+    ) -> Result<(), replay::Error>
+    where
+        S: replay::EventStream,
+        Events: TryStream<Ok = S::Event, Error = replay::Error> + Send,
+        Sink: EventSink<S::Event> + Send,
+    {
+        self.shard(&stream_id.clone().into())
+            .store_events_stream::<S, _, _>(
+                stream_id,
+                stream_type,
+                metadata,
+                domain_events,
+                expected_version,
+                sink,
+            )
+            .await
+    }
+
+    fn stream_events<E: Event>(
+        &self,
+        filter: StreamFilter,
+    ) -> impl TryStream<Ok = PersistedEvent<E>, Error = replay::Error> + Send {
+        async_stream::try_stream! {
+            let shards: Vec<&ES> = match filter.stream_id() {
+                Some(stream_id) => vec![self.shard(stream_id)],
+                None => self.shards.iter().collect(),
+            };
+            let mut reads: Vec<_> = shards
+                .into_iter()
+                .map(|shard| Box::pin(shard.stream_events::<E>(filter.clone()).into_stream()))
+                .collect();
+            let mut heads = Vec::with_capacity(reads.len());
+            for read in reads.iter_mut() {
+                heads.push(read.try_next().await?);
+            }
+
+            // Each shard reads in its own order; yield the earliest head until all run dry.
+            while let Some(next) = heads
+                .iter()
+                .enumerate()
+                .filter_map(|(shard, head)| head.as_ref().map(|event| (event.created, shard)))
+                .min()
+                .map(|(_, shard)| shard)
+            {
+                let following = reads[next].try_next().await?;
+                if let Some(event) = std::mem::replace(&mut heads[next], following) {
+                    yield event;
+                }
+            }
+        }
+    }
+
+    /// Global positions are per shard, so only a store of one shard has a single log to read.
+    async fn read_all<E: Event>(
+        &self,
+        from_position: i64,
+        limit: usize,
+    ) -> Result<AllEvents<E>, replay::Error> {
+        match &*self.shards {
+            [shard] => shard.read_all(from_position, limit).await,
+            _ => Err(replay::Error::invalid_input(
+                "global positions are per shard; read each of the shards instead",
+            )
+            .with_operation("read_all")),
+        }
+    }
+
+    async fn needs_compaction(&self, stream_id: &impl StreamId) -> Result<bool, replay::Error> {
+        self.shard(&stream_id.stream_urn())
+            .needs_compaction(stream_id)
+            .await
+    }
+
+    async fn compact<A>(
+        &self,
+        aggregate: &A,
+        metadata: replay::Metadata,
+    ) -> Result<CompactionOutcome, replay::Error>
+    where
+        A: replay::Aggregate + Compactable + Sync,
+    {
+        self.shard(&aggregate.get_id().clone().into())
+            .compact(aggregate, metadata)
+            .await
+    }
+
+    async fn compact_stream<S>(
+        &self,
+        stream_id: &S::StreamId,
+        up_to_version: i64,
+        metadata: replay::Metadata,
+    ) -> Result<u64, replay::Error>
+    where
+        S: Snapshottable,
+    {
+        self.shard(&stream_id.clone().into())
+            .compact_stream::<S>(stream_id, up_to_version, metadata)
+            .await
+    }
+
+    async fn delete_stream(
+        &self,
+        stream_id: &impl StreamId,
+        mode: DeletionMode,
+    ) -> Result<(), replay::Error> {
+        self.shard(&stream_id.stream_urn())
+            .delete_stream(stream_id, mode)
+            .await
+    }
+
+    async fn is_deleted(&self, stream_id: &impl StreamId) -> Result<bool, replay::Error> {
+        self.shard(&stream_id.stream_urn())
+            .is_deleted(stream_id)
+            .await
+    }
+
+    async fn truncate_stream(
+        &self,
+        stream_id: &impl StreamId,
+        before_version: i64,
+    ) -> Result<u64, replay::Error> {
+        self.shard(&stream_id.stream_urn())
+            .truncate_stream(stream_id, before_version)
+            .await
+    }
+
+    async fn set_stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+        retention: StreamRetention,
+    ) -> Result<(), replay::Error> {
+        self.shard(&stream_id.stream_urn())
+            .set_stream_retention(stream_id, retention)
+            .await
+    }
+
+    async fn stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> Result<StreamRetention, replay::Error> {
+        self.shard(&stream_id.stream_urn())
+            .stream_retention(stream_id)
+            .await
+    }
+
+    /// Enforces on every shard and returns the total.
+    async fn enforce_stream_retention(&self) -> Result<u64, replay::Error> {
+        let passes = self
+            .shards
+            .iter()
+            .map(|shard| shard.enforce_stream_retention());
+        let removed = futures::future::try_join_all(passes).await?;
+        Ok(removed.into_iter().sum())
+    }
+
+    async fn set_stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+        metadata: StreamMetadata,
+    ) -> Result<(), replay::Error> {
+        self.shard(&stream_id.stream_urn())
+            .set_stream_metadata(stream_id, metadata)
+            .await
+    }
+
+    async fn stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> Result<StreamMetadata, replay::Error> {
+        self.shard(&stream_id.stream_urn())
+            .stream_metadata(stream_id)
+            .await
+    }
+
+    /// Merges a page from every shard, keeping the first `limit` streams in id order.
+    async fn list_streams(
+        &self,
+        stream_type: Option<&str>,
+        page: StreamPage,
+    ) -> Result<StreamListing, replay::Error> {
+        let pages = self
+            .shards
+            .iter()
+            .map(|shard| shard.list_streams(stream_type, page.clone()));
+        let listings = futures::future::try_join_all(pages).await?;
+        let mut more = listings.iter().any(|listing| listing.next_page.is_some());
+        let mut streams: Vec<_> = listings
+            .into_iter()
+            .flat_map(|listing| listing.streams)
+            .collect();
+        streams.sort_by_key(|stream| stream.stream_id.to_string());
+        more |= streams.len() > page.limit();
+        streams.truncate(page.limit());
+        Ok(StreamListing {
+            next_page: page.next(&streams, more),
+            streams,
+        })
+    }
+
+    async fn stream_info(&self, stream_id: &impl StreamId) -> Result<StreamState, replay::Error> {
+        self.shard(&stream_id.stream_urn())
+            .stream_info(stream_id)
+            .await
+    }
+
+    /// Only within a shard: moving a stream between shards couldn't be atomic, so a new id
+    /// that hashes elsewhere is an `InvalidInput`.
+    async fn rename_stream(
+        &self,
+        old_id: &impl StreamId,
+        new_id: &impl StreamId,
+    ) -> Result<(), replay::Error> {
+        let (old_urn, new_urn) = (old_id.stream_urn(), new_id.stream_urn());
+        let shard = self.shard_for(&old_urn);
+        if shard != self.shard_for(&new_urn) {
+            return Err(replay::Error::invalid_input(
+                "streams on different shards can't be renamed",
+            )
+            .with_operation("rename_stream")
+            .with_context("stream_id", old_urn)
+            .with_context("new_stream_id", new_urn));
+        }
+        self.shards[shard].rename_stream(old_id, new_id).await
+    }
+
+    /// Probes every shard and reports the slowest; fails when any shard does.
+    async fn health_check(&self) -> Result<StoreHealth, replay::Error> {
+        let probes = self.shards.iter().map(|shard| shard.health_check());
+        let healths = futures::future::try_join_all(probes).await?;
+        Ok(healths
+            .into_iter()
+            .max_by_key(|health| health.latency)
+            .expect("a sharded store has at least one shard"))
+    }
+}
+
+/// 64-bit FNV-1a: stable across builds and platforms, unlike `std`'s hasher, so streams stay
+/// on their shard through upgrades.
+fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use replay::{EventStream, Metadata};
+
+    use super::*;
+    use crate::conformance::{ConformanceAccount, ConformanceAccountUrn, ConformanceEvent};
+    use crate::InMemoryEventStore;
+
+    fn sharded() -> ShardedEventStore<InMemoryEventStore> {
+        ShardedEventStore::new((0..3).map(|_| InMemoryEventStore::new()).collect())
+    }
+
+    async fn open(store: &impl EventStore, id: &ConformanceAccountUrn) {
+        store
+            .store_events::<ConformanceAccount>(
+                id,
+                ConformanceAccount::stream_type(),
+                Metadata::default(),
+                &[ConformanceEvent::Deposited { amount: 1 }],
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn streams_are_spread_and_read_back_across_shards() {
+        let store = sharded();
+        let ids: Vec<_> = (0..30)
+            .map(|_| ConformanceAccountUrn::new_random())
+            .collect();
+        for id in &ids {
+            open(&store, id).await;
+        }
+
+        let used: HashSet<usize> = ids
+            .iter()
+            .map(|id| store.shard_for(&id.clone().into()))
+            .collect();
+        assert!(used.len() > 1);
+
+        let all: Vec<PersistedEvent<ConformanceEvent>> = store
+            .stream_events(StreamFilter::All)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(all.len(), ids.len());
+        assert!(all
+            .windows(2)
+            .all(|pair| pair[0].created <= pair[1].created));
+    }
+
+    #[tokio::test]
+    async fn a_stream_is_only_written_to_its_shard() {
+        let store = sharded();
+        let id = ConformanceAccountUrn::new_random();
+        open(&store, &id).await;
+
+        let shard = store.shard_for(&id.clone().into());
+        for (index, inner) in store.shards().iter().enumerate() {
+            let events: Vec<PersistedEvent<ConformanceEvent>> = inner
+                .stream_events(StreamFilter::All)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(events.len(), usize::from(index == shard));
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Tamper evidence for the event log: signatures over each stored event.
+//!
+//! A store with a signer (see
+//! [`PostgresEventStore::with_signer`](crate::PostgresEventStore::with_signer)) signs every
+//! event it writes over its stream id, version, type, payload as stored (so after any
+//! encryption) and metadata, and keeps the signature in `events.signature` (migration
+//! `0020_event_signatures`). Reads reject events whose signature doesn't match, and
+//! [`verify_stream`](crate::PostgresEventStore::verify_stream) audits a whole stream.
+
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+/// Signs stored events and checks their signatures.
+///
+/// [`HmacSigner`] shares one secret between writers and auditors; [`Ed25519Signer`] lets
+/// auditors verify with the public key alone.
+pub trait EventSigner: Send + Sync {
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, replay::Error>;
+
+    /// Whether `signature` was made by [`sign`](Self::sign) over `message`.
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool;
+}
+
+/// What [`verify_stream`](crate::PostgresEventStore::verify_stream) found in one stream,
+/// archived events included.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StreamVerification {
+    /// Events whose signature matches.
+    pub verified: u64,
+    /// Events without a signature, written before signing was enabled or stripped of it.
+    pub unsigned: Vec<Uuid>,
+    /// Events whose signature doesn't match their contents.
+    pub tampered: Vec<Uuid>,
+    /// Live versions below the stream head with no event.
+    pub missing_versions: Vec<i64>,
+}
+
+impl StreamVerification {
+    /// Every event is signed and intact, and none is missing.
+    pub fn is_intact(&self) -> bool {
+        self.unsigned.is_empty() && self.tampered.is_empty() && self.missing_versions.is_empty()
+    }
+}
+
+/// The bytes signed for an event: a JSON array of its identity and contents, with object
+/// keys sorted so the message doesn't depend on how `jsonb` orders them.
+pub(crate) fn message(
+    stream_id: &str,
+    version: i64,
+    event_type: &str,
+    payload: &Value,
+    metadata: &Value,
+) -> Result<Vec<u8>, replay::Error> {
+    serde_json::to_vec(&(
+        stream_id,
+        version,
+        event_type,
+        sorted(payload),
+        sorted(metadata),
+    ))
+    .map_err(crate::ser_error)
+}
+
+fn sorted(value: &Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut keys: Vec<_> = object.keys().collect();
+            keys.sort();
+            let mut sorted_object = Map::with_capacity(object.len());
+            for key in keys {
+                sorted_object.insert(key.clone(), sorted(&object[key]));
+            }
+            Value::Object(sorted_object)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sorted).collect()),
+        other => other.clone(),
+    }
+}
+
+/// HMAC-SHA256 signatures under a shared secret.
+#[cfg(feature = "signing")]
+pub struct HmacSigner {
+    key: ring::hmac::Key,
+}
+
+#[cfg(feature = "signing")]
+impl HmacSigner {
+    /// A signer keyed by `secret`, which must be at least 32 bytes.
+    pub fn new(secret: &[u8]) -> Result<Self, replay::Error> {
+        if secret.len() < 32 {
+            return Err(
+                replay::Error::invalid_input("HMAC secrets must be at least 32 bytes")
+                    .with_operation("hmac_signer")
+                    .with_context("length", secret.len()),
+            );
+        }
+        Ok(HmacSigner {
+            key: ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret),
+        })
+    }
+}
+
+#[cfg(feature = "signing")]
+impl EventSigner for HmacSigner {
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, replay::Error> {
+        Ok(ring::hmac::sign(&self.key, message).as_ref().to_vec())
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        ring::hmac::verify(&self.key, message, signature).is_ok()
+    }
+}
+
+/// Ed25519 signatures. Writers hold the key pair; auditors can verify with
+/// [`Ed25519Signer::verifying`] and the public key only.
+#[cfg(feature = "signing")]
+pub struct Ed25519Signer {
+    key_pair: Option<ring::signature::Ed25519KeyPair>,
+    public_key: Vec<u8>,
+}
+
+#[cfg(feature = "signing")]
+impl Ed25519Signer {
+    /// A new PKCS#8-encoded key pair, to keep in a secret store.
+    pub fn generate_pkcs8() -> Result<Vec<u8>, replay::Error> {
+        ring::signature::Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new())
+            .map(|pkcs8| pkcs8.as_ref().to_vec())
+            .map_err(|_| {
+                replay::Error::internal("failed to generate an Ed25519 key pair")
+                    .with_operation("ed25519_signer")
+            })
+    }
+
+    /// A signer from a PKCS#8-encoded key pair.
+    pub fn from_pkcs8(pkcs8: &[u8]) -> Result<Self, replay::Error> {
+        use ring::signature::KeyPair;
+
+        let key_pair = ring::signature::Ed25519KeyPair::from_pkcs8(pkcs8).map_err(|e| {
+            replay::Error::invalid_input("invalid Ed25519 PKCS#8 key pair")
+                .with_operation("ed25519_signer")
+                .with_context("reason", e)
+        })?;
+        Ok(Ed25519Signer {
+            public_key: key_pair.public_key().as_ref().to_vec(),
+            key_pair: Some(key_pair),
+        })
+    }
+
+    /// A signer that only verifies, for audits; signing fails with `Forbidden`.
+    pub fn verifying(public_key: &[u8]) -> Self {
+        Ed25519Signer {
+            key_pair: None,
+            public_key: public_key.to_vec(),
+        }
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+}
+
+#[cfg(feature = "signing")]
+impl EventSigner for Ed25519Signer {
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, replay::Error> {
+        match &self.key_pair {
+            Some(key_pair) => Ok(key_pair.sign(message).as_ref().to_vec()),
+            None => Err(replay::Error::forbidden(
+                "this Ed25519 signer only verifies; it has no private key",
+            )
+            .with_operation("sign_event")),
+        }
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, &self.public_key)
+            .verify(message, signature)
+            .is_ok()
+    }
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn signed_message() -> Vec<u8> {
+        message(
+            "urn:account:1",
+            3,
+            "Deposited",
+            &json!({ "Deposited": { "amount": 10, "currency": "EUR" } }),
+            &json!({ "user": "ada" }),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn messages_do_not_depend_on_key_order() {
+        let reordered = message(
+            "urn:account:1",
+            3,
+            "Deposited",
+            &serde_json::from_str(r#"{ "Deposited": { "currency": "EUR", "amount": 10 } }"#)
+                .unwrap(),
+            &json!({ "user": "ada" }),
+        )
+        .unwrap();
+
+        assert_eq!(reordered, signed_message());
+    }
+
+    #[test]
+    fn hmac_signatures_catch_changes() {
+        let signer = HmacSigner::new(&[1; 32]).unwrap();
+        let signature = signer.sign(&signed_message()).unwrap();
+
+        assert!(signer.verify(&signed_message(), &signature));
+        let moved = message(
+            "urn:account:1",
+            4,
+            "Deposited",
+            &json!({ "Deposited": { "amount": 10, "currency": "EUR" } }),
+            &json!({ "user": "ada" }),
+        )
+        .unwrap();
+        assert!(!signer.verify(&moved, &signature));
+        assert!(!HmacSigner::new(&[2; 32])
+            .unwrap()
+            .verify(&signed_message(), &signature));
+        assert!(HmacSigner::new(&[1; 16]).is_err());
+    }
+
+    #[test]
+    fn ed25519_signatures_verify_with_the_public_key_alone() {
+        let signer = Ed25519Signer::from_pkcs8(&Ed25519Signer::generate_pkcs8().unwrap()).unwrap();
+        let signature = signer.sign(&signed_message()).unwrap();
+
+        let auditor = Ed25519Signer::verifying(signer.public_key());
+        assert!(auditor.verify(&signed_message(), &signature));
+        assert!(!auditor.verify(b"something else", &signature));
+        assert_eq!(
+            auditor.sign(&signed_message()).unwrap_err().kind(),
+            replay::ErrorKind::Forbidden
+        );
+    }
+}
@@ -0,0 +1,26 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+
+/// Counts and sizes of a store's contents, for admin dashboards and capacity planning.
+///
+/// Returned by [`PostgresEventStore::statistics`](crate::PostgresEventStore::statistics) and
+/// [`InMemoryEventStore::statistics`](crate::InMemoryEventStore::statistics). Live events are
+/// the ones aggregates load; archived events are the copies kept by compaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StoreStatistics {
+    /// Streams with at least one event.
+    pub streams: u64,
+    /// Live events across all streams.
+    pub events: u64,
+    /// Events archived by compaction.
+    pub archived_events: u64,
+    /// Live events per stream type.
+    pub events_by_stream_type: BTreeMap<String, u64>,
+    /// Bytes on disk of the events and streams tables with their indexes; `None` in memory.
+    pub size_bytes: Option<u64>,
+    /// Creation time of the oldest event, live or archived.
+    pub oldest_event: Option<DateTime<Utc>>,
+    /// Creation time of the newest event, live or archived.
+    pub newest_event: Option<DateTime<Utc>>,
+}
@@ -1,12 +1,18 @@
+use std::collections::BTreeMap;
 use std::future::Future;
+use std::time::{Duration, Instant};
 
-use futures::stream;
-use futures::TryStream;
+use chrono::{DateTime, Utc};
+use futures::{future, stream};
+use futures::{StreamExt, TryStream, TryStreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 
-use replay::{Compactable, Event};
+use replay::{Compactable, Event, Snapshottable, StreamId};
 use urn::Urn;
 
 use super::{AggregateVersion, PersistedEvent};
+use crate::persisted_event::AnyEvent;
 
 pub trait EventSink<E: Event>: Send {
     fn on_event(&mut self, event: &PersistedEvent<E>);
@@ -29,11 +35,23 @@ impl<E: Event> EventSink<E> for NoSink {
     fn on_event(&mut self, _event: &PersistedEvent<E>) {}
 }
 
+/// Storage for event streams.
+///
+/// Only appending, reading and compaction are required. The stream-management methods
+/// (snapshot compaction, deleting, truncating, retention, metadata, listing, renaming, ...)
+/// default to an [`Unsupported`](replay::ErrorKind::Unsupported) error, so a backend can
+/// implement the ones it supports, and [`health_check`](Self::health_check) defaults to a
+/// probing read.
+///
+/// A stream belongs to the tenant named by the metadata of the append that created it.
+/// Appends whose metadata names another tenant fail with
+/// [`Forbidden`](replay::ErrorKind::Forbidden), checked in the same step that appends, so two
+/// tenants can't both claim a new stream; appends without a tenant aren't checked.
 pub trait EventStore: Send + Sync {
     fn store_events_stream<S, ES, Sink>(
         &self,
         stream_id: &S::StreamId,
-        stream_type: String,
+        stream_type: &str,
         metadata: replay::Metadata,
         domain_events: ES,
         expected_version: Option<i64>,
@@ -47,7 +65,7 @@ pub trait EventStore: Send + Sync {
     fn store_events<S: replay::EventStream>(
         &self,
         stream_id: &S::StreamId,
-        stream_type: String,
+        stream_type: &str,
         metadata: replay::Metadata,
         domain_events: &[S::Event],
         expected_version: Option<i64>,
@@ -68,6 +86,25 @@ pub trait EventStore: Send + Sync {
         filter: crate::StreamFilter,
     ) -> impl TryStream<Ok = PersistedEvent<E>, Error = replay::Error> + Send;
 
+    /// [`stream_events`](Self::stream_events) with each event's metadata deserialized into
+    /// `M` (see [`PersistedEvent::with_typed_metadata`]).
+    ///
+    /// Metadata that doesn't fit `M` ends the stream with an internal deserialization error.
+    ///
+    /// ```rust,ignore
+    /// #[derive(Deserialize)]
+    /// struct Audit { actor: String, correlation_id: Option<String> }
+    ///
+    /// let events = store.stream_events_with_metadata::<BankAccountEvent, Audit>(filter);
+    /// ```
+    fn stream_events_with_metadata<E: Event, M: DeserializeOwned + Send>(
+        &self,
+        filter: crate::StreamFilter,
+    ) -> impl TryStream<Ok = PersistedEvent<E, M>, Error = replay::Error> + Send {
+        self.stream_events::<E>(filter)
+            .and_then(|event| future::ready(event.with_typed_metadata()))
+    }
+
     /// Stream the events for a specific aggregate stream, optionally scoped to a particular
     /// compaction version.
     ///
@@ -82,9 +119,9 @@ pub trait EventStore: Send + Sync {
         stream_id: &S::StreamId,
         aggregate_version: AggregateVersion,
         at_stream_version: Option<i64>,
-        at_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+        at_timestamp: Option<DateTime<Utc>>,
     ) -> impl TryStream<Ok = PersistedEvent<S::Event>, Error = replay::Error> + Send {
-        let filter = crate::StreamFilter::WithStreamId(stream_id.clone().into())
+        let filter = crate::StreamFilter::WithStreamId(stream_id.stream_urn())
             .and_aggregate_version(aggregate_version.as_option())
             .and_at_stream_version_optional(at_stream_version)
             .and_at_timestamp_optional(at_timestamp);
@@ -92,6 +129,47 @@ pub trait EventStore: Send + Sync {
         self.stream_events::<S::Event>(filter)
     }
 
+    /// Up to `limit` events of every stream, from global position `from_position` on, in
+    /// the order they were committed (see [`PersistedEvent::global_position`]). Pass the
+    /// page's [`next_position`](AllEvents::next_position) to read on; a reader that is caught
+    /// up gets an empty page and the same position back.
+    ///
+    /// Only events that can no longer be overtaken are returned: on Postgres, where a
+    /// position is taken at insert but shows at commit, a page stops before an append that
+    /// is still in flight. Archived events are included; soft-deleted streams and expired
+    /// events are not, as with [`stream_events`](Self::stream_events).
+    ///
+    /// ```rust,ignore
+    /// let mut position = checkpoint.load().await?;
+    /// loop {
+    ///     let page = store.read_all::<serde_json::Value>(position, 500).await?;
+    ///     replica.apply(&page.events).await?;
+    ///     position = page.next_position;
+    ///     checkpoint.save(position).await?;
+    /// }
+    /// ```
+    fn read_all<E: Event>(
+        &self,
+        from_position: i64,
+        limit: usize,
+    ) -> impl Future<Output = Result<AllEvents<E>, replay::Error>> + Send {
+        let events = self
+            .stream_events::<E>(crate::StreamFilter::AfterGlobalPosition(from_position - 1))
+            .into_stream()
+            .take(limit)
+            .try_collect::<Vec<_>>();
+        async move {
+            let events = events.await?;
+            let next_position = events
+                .last()
+                .map_or(from_position, |event| event.global_position + 1);
+            Ok(AllEvents {
+                events,
+                next_position,
+            })
+        }
+    }
+
     /// Compact the event stream for an aggregate.
     ///
     /// The method performs the following steps atomically (where the store supports it):
@@ -125,7 +203,7 @@ pub trait EventStore: Send + Sync {
     /// does not exist reports `false` (nothing to compact).
     fn needs_compaction(
         &self,
-        stream_id: &Urn,
+        stream_id: &impl StreamId,
     ) -> impl Future<Output = Result<bool, replay::Error>> + Send;
 
     /// Compacts the aggregate's live stream and reports the outcome as a
@@ -141,6 +219,489 @@ pub trait EventStore: Send + Sync {
     ) -> impl Future<Output = Result<CompactionOutcome, replay::Error>> + Send
     where
         A: replay::Aggregate + Compactable + Sync;
+
+    /// Replaces the live events of a stream up to version `up_to_version` with a single
+    /// [`Snapshottable::snapshot_event`] of the state they build, for streams such as
+    /// telemetry whose full history is not worth keeping. Unlike [`compact`](Self::compact),
+    /// the replaced events are deleted, not archived.
+    ///
+    /// The snapshot takes the version, timestamp and global position of the last event it
+    /// replaces, so later events, expected versions and time-travel reads past it are
+    /// unaffected. Returns how many events were replaced: `0` when fewer than two fall
+    /// within `up_to_version`. A stream that does not exist is a `NotFound`.
+    ///
+    /// ```rust,ignore
+    /// // keep the last day of readings as events, fold everything older into one
+    /// store
+    ///     .compact_stream::<Thermostat>(&id, last_version_before_yesterday, metadata)
+    ///     .await?;
+    /// ```
+    fn compact_stream<S>(
+        &self,
+        stream_id: &S::StreamId,
+        up_to_version: i64,
+        metadata: replay::Metadata,
+    ) -> impl Future<Output = Result<u64, replay::Error>> + Send
+    where
+        S: Snapshottable,
+    {
+        let _ = (stream_id, up_to_version, metadata);
+        future::ready(Err(unsupported("compact_stream")))
+    }
+
+    /// Delete a stream, either by marking it deleted or by removing its events for good (see
+    /// [`DeletionMode`]). A stream that does not exist is a `NotFound`; soft-deleting a
+    /// soft-deleted stream again is a no-op, and hard-deleting it removes it.
+    ///
+    /// ```rust,ignore
+    /// // closing an account hides it; a GDPR erasure request removes it
+    /// store.delete_stream(&account_id, DeletionMode::Soft).await?;
+    /// store.delete_stream(&customer_id, DeletionMode::Hard).await?;
+    /// ```
+    fn delete_stream(
+        &self,
+        stream_id: &impl StreamId,
+        mode: DeletionMode,
+    ) -> impl Future<Output = Result<(), replay::Error>> + Send {
+        let _ = (stream_id, mode);
+        future::ready(Err(unsupported("delete_stream")))
+    }
+
+    /// Whether `stream_id` was [soft-deleted](DeletionMode::Soft). A stream that does not
+    /// exist, hard-deleted ones included, reports `false`. By default no stream is: a store
+    /// without [`delete_stream`](Self::delete_stream) never deletes one.
+    fn is_deleted(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> impl Future<Output = Result<bool, replay::Error>> + Send {
+        let _ = stream_id;
+        future::ready(Ok(false))
+    }
+
+    /// Delete the live events of a stream below version `before_version`, e.g. once a
+    /// snapshot event carries their state, and return how many were deleted.
+    ///
+    /// The stream's last live event is always kept, so appends continue from the current
+    /// version and expected versions are unaffected. Archived events are left to
+    /// [retention](crate::RetentionPolicy). A stream that does not exist is a `NotFound`.
+    ///
+    /// ```rust,ignore
+    /// // version 500 is a snapshot of everything before it
+    /// store.truncate_stream(&id, 500).await?;
+    /// ```
+    fn truncate_stream(
+        &self,
+        stream_id: &impl StreamId,
+        before_version: i64,
+    ) -> impl Future<Output = Result<u64, replay::Error>> + Send {
+        let _ = (stream_id, before_version);
+        future::ready(Err(unsupported("truncate_stream")))
+    }
+
+    /// Limit how many live events of a stream are kept, like EventStoreDB's `$maxAge` and
+    /// `$maxCount`, replacing the stream's previous limits.
+    ///
+    /// Reads leave out expired events right away, and
+    /// [`enforce_stream_retention`](Self::enforce_stream_retention) deletes them. The
+    /// stream's last live event never expires, so appends continue from the current version.
+    /// Loading an aggregate replays only what is left, so limit streams whose newer events
+    /// stand on their own, e.g. with a snapshot event. A stream that does not exist is a
+    /// `NotFound`.
+    ///
+    /// ```rust,ignore
+    /// let retention = StreamRetention::default()
+    ///     .with_max_age(Duration::from_secs(30 * 24 * 60 * 60))
+    ///     .with_max_count(1_000);
+    /// store.set_stream_retention(&id, retention).await?;
+    /// ```
+    fn set_stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+        retention: StreamRetention,
+    ) -> impl Future<Output = Result<(), replay::Error>> + Send {
+        let _ = (stream_id, retention);
+        future::ready(Err(unsupported("set_stream_retention")))
+    }
+
+    /// The limits set on a stream, unlimited if none were; a stream that does not exist is a
+    /// `NotFound`.
+    fn stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> impl Future<Output = Result<StreamRetention, replay::Error>> + Send {
+        let _ = stream_id;
+        future::ready(Err(unsupported("stream_retention")))
+    }
+
+    /// Delete the events that expired under their stream's [`StreamRetention`], as with
+    /// [`truncate_stream`](Self::truncate_stream), and return how many were deleted. Run it
+    /// periodically; a stream that can't be truncated yet is left for the next run.
+    fn enforce_stream_retention(&self) -> impl Future<Output = Result<u64, replay::Error>> + Send {
+        future::ready(Err(unsupported("enforce_stream_retention")))
+    }
+
+    /// Record who owns a stream, who may do what with it and how it is labelled, replacing
+    /// its previous metadata. The store only keeps it: enforcing the ACL is up to the
+    /// application. Labels can select streams in [`list_streams`](Self::list_streams). A
+    /// stream that does not exist is a `NotFound`.
+    ///
+    /// ```rust,ignore
+    /// let metadata = StreamMetadata::default()
+    ///     .with_owner("ada")
+    ///     .with_acl("read", ["team-billing"])
+    ///     .with_label("region", "eu");
+    /// store.set_stream_metadata(&id, metadata).await?;
+    /// ```
+    fn set_stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+        metadata: StreamMetadata,
+    ) -> impl Future<Output = Result<(), replay::Error>> + Send {
+        let _ = (stream_id, metadata);
+        future::ready(Err(unsupported("set_stream_metadata")))
+    }
+
+    /// The metadata set on a stream, empty if none was; a stream that does not exist is a
+    /// `NotFound`.
+    fn stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> impl Future<Output = Result<StreamMetadata, replay::Error>> + Send {
+        let _ = stream_id;
+        future::ready(Err(unsupported("stream_metadata")))
+    }
+
+    /// A page of the streams that exist, of `stream_type` if given and carrying the page's
+    /// [labels](StreamPage::with_label), in stream id order. Soft-deleted streams are left
+    /// out.
+    ///
+    /// ```rust,ignore
+    /// let mut page = Some(StreamPage::first(100));
+    /// while let Some(next) = page {
+    ///     let listing = store.list_streams(Some("BankAccount"), next).await?;
+    ///     for stream in &listing.streams {
+    ///         println!("{} at version {}", stream.stream_id, stream.version);
+    ///     }
+    ///     page = listing.next_page;
+    /// }
+    /// ```
+    fn list_streams(
+        &self,
+        stream_type: Option<&str>,
+        page: StreamPage,
+    ) -> impl Future<Output = Result<StreamListing, replay::Error>> + Send {
+        let _ = (stream_type, page);
+        future::ready(Err(unsupported("list_streams")))
+    }
+
+    /// What the store holds of a stream; a stream that does not exist is not an error but a
+    /// state with `exists: false`.
+    ///
+    /// ```rust,ignore
+    /// let state = store.stream_info(&id).await?;
+    /// if state.exists && !state.deleted {
+    ///     println!("at version {} since {:?}", state.version, state.updated);
+    /// }
+    /// ```
+    fn stream_info(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> impl Future<Output = Result<StreamState, replay::Error>> + Send {
+        let _ = stream_id;
+        future::ready(Err(unsupported("stream_info")))
+    }
+
+    /// Move a stream and all its events, archived ones included, from `old_id` to `new_id`,
+    /// e.g. when an account is renumbered. Versions, positions and settings carry over, and
+    /// the old id is free again afterwards.
+    ///
+    /// The move is atomic: readers see the stream under one id or the other. Read models
+    /// keyed by the old id aren't told; policies don't see the moved events again. A missing
+    /// `old_id` is a `NotFound`, an existing `new_id` a `Conflict`.
+    ///
+    /// ```rust,ignore
+    /// store.rename_stream(&old_account, &new_account).await?;
+    /// ```
+    fn rename_stream(
+        &self,
+        old_id: &impl StreamId,
+        new_id: &impl StreamId,
+    ) -> impl Future<Output = Result<(), replay::Error>> + Send {
+        let _ = (old_id, new_id);
+        future::ready(Err(unsupported("rename_stream")))
+    }
+
+    /// Probe the store for a readiness or liveness check.
+    ///
+    /// Succeeds with the probe's latency and, for pooled stores, the pool's occupancy. A store
+    /// that can't be reached, or that isn't set up (e.g. migrations haven't run), fails with
+    /// an `Unavailable` error.
+    ///
+    /// ```rust,ignore
+    /// async fn ready(store: &PostgresEventStore) -> StatusCode {
+    ///     match store.health_check().await {
+    ///         Ok(_) => StatusCode::OK,
+    ///         Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// By default the probe reads past the last event, which reaches the storage without
+    /// returning anything, and reports no pool.
+    fn health_check(&self) -> impl Future<Output = Result<StoreHealth, replay::Error>> + Send {
+        let started = Instant::now();
+        let probe = self.read_all::<AnyEvent>(i64::MAX, 1);
+        async move {
+            probe.await.map_err(|e| {
+                replay::Error::unavailable("the event store did not answer its probe")
+                    .with_operation("health_check")
+                    .with_source(e)
+            })?;
+            Ok(StoreHealth {
+                latency: started.elapsed(),
+                connections: None,
+                idle_connections: None,
+                max_connections: None,
+            })
+        }
+    }
+}
+
+/// The error of an [`EventStore`] method the store doesn't implement.
+fn unsupported(operation: &'static str) -> replay::Error {
+    replay::Error::unsupported("the event store does not support this operation")
+        .with_operation(operation)
+}
+
+/// How [`EventStore::delete_stream`] deletes a stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeletionMode {
+    /// Keep the events but mark the stream deleted: reads leave it out unless their filter
+    /// is [`including_deleted`](crate::StreamFilter::including_deleted), and
+    /// [`Cqrs`](crate::Cqrs) fails to load it with `NotFound`. Appending to it fails with
+    /// `NotFound` too.
+    Soft,
+    /// Remove the stream and its events, archived ones included. Appending to the stream id
+    /// afterwards starts a new stream.
+    Hard,
+}
+
+/// Per-stream limits on the live events kept, set with
+/// [`EventStore::set_stream_retention`]. Both limits apply when both are set.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StreamRetention {
+    max_age: Option<Duration>,
+    max_count: Option<u64>,
+}
+
+impl StreamRetention {
+    /// Expire events created longer than `max_age` ago.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Keep at most the newest `max_count` events.
+    pub fn with_max_count(mut self, max_count: u64) -> Self {
+        self.max_count = Some(max_count);
+        self
+    }
+
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
+    pub fn max_count(&self) -> Option<u64> {
+        self.max_count
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_age.is_none() && self.max_count.is_none()
+    }
+
+    /// Whether the live event at `version`, created at `created`, expired in a stream whose
+    /// last live version is `head`.
+    pub(crate) fn expires(
+        &self,
+        version: i64,
+        created: DateTime<Utc>,
+        head: i64,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let too_many = self.max_count.is_some_and(|max_count| {
+            version <= head.saturating_sub(max_count.min(i64::MAX as u64) as i64)
+        });
+        let too_old = self.max_age.is_some_and(|max_age| {
+            chrono::Duration::from_std(max_age)
+                .ok()
+                .and_then(|max_age| now.checked_sub_signed(max_age))
+                .is_some_and(|cutoff| created < cutoff)
+        });
+        version < head && (too_many || too_old)
+    }
+}
+
+/// Which streams [`EventStore::list_streams`] returns: at most `limit`, starting after a
+/// stream id, of those carrying every label of the page.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StreamPage {
+    after: Option<Urn>,
+    limit: usize,
+    labels: BTreeMap<String, String>,
+}
+
+impl StreamPage {
+    /// The first `limit` streams.
+    pub fn first(limit: usize) -> Self {
+        StreamPage {
+            after: None,
+            limit,
+            labels: BTreeMap::new(),
+        }
+    }
+
+    /// Start after the stream `stream_id` instead.
+    pub fn after(mut self, stream_id: Urn) -> Self {
+        self.after = Some(stream_id);
+        self
+    }
+
+    /// Only list streams whose [metadata](StreamMetadata) has the label `key` set to
+    /// `value`. Following pages keep the labels.
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn cursor(&self) -> Option<&Urn> {
+        self.after.as_ref()
+    }
+
+    pub fn labels(&self) -> &BTreeMap<String, String> {
+        &self.labels
+    }
+
+    /// The page following `streams`, this page's results, unless they were the last.
+    pub(crate) fn next(&self, streams: &[StreamSummary], more: bool) -> Option<StreamPage> {
+        let last = streams.last().filter(|_| more)?;
+        Some(StreamPage {
+            after: Some(last.stream_id.clone()),
+            ..self.clone()
+        })
+    }
+}
+
+/// What is recorded about a stream besides its events, set with
+/// [`EventStore::set_stream_metadata`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// The principals granted each permission, e.g. `"read"`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub acl: BTreeMap<String, Vec<String>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+impl StreamMetadata {
+    pub fn with_owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner = Some(owner.into());
+        self
+    }
+
+    /// Grant `permission` to `principals`, replacing whom it was granted to.
+    pub fn with_acl<P: Into<String>>(
+        mut self,
+        permission: impl Into<String>,
+        principals: impl IntoIterator<Item = P>,
+    ) -> Self {
+        self.acl.insert(
+            permission.into(),
+            principals.into_iter().map(Into::into).collect(),
+        );
+        self
+    }
+
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Whether the principal may `permission` the stream, as its owner or by the ACL.
+    pub fn allows(&self, principal: &str, permission: &str) -> bool {
+        self.owner.as_deref() == Some(principal)
+            || self
+                .acl
+                .get(permission)
+                .is_some_and(|principals| principals.iter().any(|p| p == principal))
+    }
+
+    /// Whether every one of `labels` is set to the same value here.
+    pub(crate) fn has_labels(&self, labels: &BTreeMap<String, String>) -> bool {
+        labels
+            .iter()
+            .all(|(key, value)| self.labels.get(key) == Some(value))
+    }
+}
+
+/// A stream listed by [`EventStore::list_streams`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StreamSummary {
+    pub stream_id: Urn,
+    pub stream_type: String,
+    /// The version of its last live event, which appends continue from.
+    pub version: i64,
+    /// When its last event was appended.
+    pub updated: DateTime<Utc>,
+}
+
+/// A page of [`EventStore::list_streams`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StreamListing {
+    pub streams: Vec<StreamSummary>,
+    /// `None` once there are no more streams.
+    pub next_page: Option<StreamPage>,
+}
+
+/// A page of [`EventStore::read_all`].
+#[derive(Clone, Debug)]
+pub struct AllEvents<E> {
+    pub events: Vec<PersistedEvent<E>>,
+    /// Where the next page starts.
+    pub next_position: i64,
+}
+
+/// A stream as described by [`EventStore::stream_info`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StreamState {
+    pub exists: bool,
+    /// The version of its last live event, which appends continue from; `0` for a stream
+    /// that does not exist.
+    pub version: i64,
+    /// When its oldest kept event was appended.
+    pub created: Option<DateTime<Utc>>,
+    /// When its last event was appended.
+    pub updated: Option<DateTime<Utc>>,
+    /// Whether it was soft-deleted.
+    pub deleted: bool,
+}
+
+/// The outcome of a successful [`EventStore::health_check`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StoreHealth {
+    /// Round trip of the probe.
+    pub latency: Duration,
+    /// Open connections, idle ones included; `None` for stores without a pool.
+    pub connections: Option<u32>,
+    /// Open connections not currently in use.
+    pub idle_connections: Option<u32>,
+    /// The most connections the pool will open.
+    pub max_connections: Option<u32>,
 }
 
 /// The outcome of [`EventStore::compact`].
@@ -153,3 +714,107 @@ pub enum CompactionOutcome {
     /// stream is skipped by [`EventStore::needs_compaction`] until a new event is appended.
     Skipped,
 }
+
+#[cfg(test)]
+mod tests {
+    use replay::{ErrorKind, EventStream, Metadata, WithId};
+
+    use super::*;
+    use crate::conformance::{ConformanceAccount, ConformanceAccountUrn, ConformanceEvent};
+    use crate::{InMemoryEventStore, StreamFilter};
+
+    /// A backend implementing only the required methods, as one outside this crate might.
+    struct MinimalStore(InMemoryEventStore);
+
+    impl EventStore for MinimalStore {
+        fn store_events_stream<S, ES, Sink>(
+            &self,
+            stream_id: &S::StreamId,
+            stream_type: &str,
+            metadata: replay::Metadata,
+            domain_events: ES,
+            expected_version: Option<i64>,
+            sink: Sink,
+        ) -> impl Future<Output = Result<(), replay::Error>> + Send
+        where
+            S: replay::EventStream,
+            ES: TryStream<Ok = S::Event, Error = replay::Error> + Send,
+            Sink: EventSink<S::Event> + Send,
+        {
+            self.0.store_events_stream::<S, ES, Sink>(
+                stream_id,
+                stream_type,
+                metadata,
+                domain_events,
+                expected_version,
+                sink,
+            )
+        }
+
+        fn stream_events<E: Event>(
+            &self,
+            filter: StreamFilter,
+        ) -> impl TryStream<Ok = PersistedEvent<E>, Error = replay::Error> + Send {
+            self.0.stream_events(filter)
+        }
+
+        fn needs_compaction(
+            &self,
+            stream_id: &impl StreamId,
+        ) -> impl Future<Output = Result<bool, replay::Error>> + Send {
+            self.0.needs_compaction(stream_id)
+        }
+
+        fn compact<A>(
+            &self,
+            aggregate: &A,
+            metadata: replay::Metadata,
+        ) -> impl Future<Output = Result<CompactionOutcome, replay::Error>> + Send
+        where
+            A: replay::Aggregate + Compactable + Sync,
+        {
+            self.0.compact(aggregate, metadata)
+        }
+    }
+
+    #[tokio::test]
+    async fn stores_implementing_the_required_methods_only_still_work() {
+        let store = MinimalStore(InMemoryEventStore::new());
+        let id = ConformanceAccountUrn::new_random();
+        store
+            .store_events::<ConformanceAccount>(
+                &id,
+                ConformanceAccount::stream_type(),
+                Metadata::default(),
+                &[ConformanceEvent::Deposited { amount: 10 }],
+                None,
+            )
+            .await
+            .unwrap();
+
+        let mut account = ConformanceAccount::with_id(id.clone());
+        let events: Vec<PersistedEvent<ConformanceEvent>> = store
+            .stream_events_by_stream_id::<ConformanceAccount>(
+                &id,
+                AggregateVersion::Latest,
+                None,
+                None,
+            )
+            .try_collect()
+            .await
+            .unwrap();
+        for event in events {
+            account.apply(event.data);
+        }
+        assert_eq!(account.balance, 10);
+        assert!(store.health_check().await.is_ok());
+        assert!(!store.is_deleted(&id).await.unwrap());
+        let err = store
+            .delete_stream(&id, DeletionMode::Soft)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        crate::conformance::run_all(&store).await;
+    }
+}
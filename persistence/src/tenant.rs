@@ -0,0 +1,616 @@
+//! Tenant-scoped access to a shared [`EventStore`].
+//!
+//! Every event written through a [`TenantScopedEventStore`] carries its tenant in the
+//! well-known `tenant_id` metadata key, every read is narrowed to that tenant, and touching
+//! a stream that holds another tenant's events fails with [`replay::ErrorKind::Forbidden`].
+
+use std::fmt;
+use std::sync::Arc;
+
+use futures::{TryStream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use urn::Urn;
+
+use replay::{Compactable, Event, Metadata, Snapshottable, StreamId};
+
+use crate::persisted_event::AnyEvent;
+use crate::{
+    AggregateVersion, CompactionOutcome, DeletionMode, EventSink, EventStore, PersistedEvent,
+    StoreHealth, StreamFilter, StreamListing, StreamMetadata, StreamPage, StreamRetention,
+    StreamState,
+};
+
+/// Identifies a tenant; stored in event metadata under [`Metadata::TENANT_ID_KEY`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TenantId(String);
+
+impl TenantId {
+    pub fn new(id: impl Into<String>) -> Self {
+        TenantId(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for TenantId {
+    fn from(id: String) -> Self {
+        TenantId(id)
+    }
+}
+
+impl From<&str> for TenantId {
+    fn from(id: &str) -> Self {
+        TenantId(id.to_string())
+    }
+}
+
+/// An [`EventStore`] wrapper that confines every operation to one tenant.
+///
+/// - **Writes** stamp the tenant into the metadata; metadata naming a different tenant is
+///   rejected with `Forbidden`, and so is appending to a stream created for another tenant
+///   (or without one), which the inner store checks as it appends.
+/// - **Reads** through [`stream_events`](EventStore::stream_events) only see the tenant's
+///   events; loading a foreign stream by id fails with `Forbidden` rather than coming back
+///   empty.
+/// - **Stream management** (deleting, truncating, retention, metadata, compaction, listing)
+///   only reaches streams holding the tenant's events and no others.
+///   [`enforce_stream_retention`](EventStore::enforce_stream_retention) spans every tenant,
+///   so it fails with `Forbidden` here and runs on the [`inner`](Self::inner) store.
+///
+/// The inner store is shared, so scoping a request is cheap:
+///
+/// ```rust,ignore
+/// let store = TenantScopedEventStore::new(PostgresEventStore::new(pool), "acme");
+/// let cqrs = Cqrs::new(store.for_tenant(request.tenant_id()));
+/// ```
+pub struct TenantScopedEventStore<ES> {
+    inner: Arc<ES>,
+    tenant: TenantId,
+}
+
+impl<ES: EventStore> TenantScopedEventStore<ES> {
+    pub fn new(inner: impl Into<Arc<ES>>, tenant: impl Into<TenantId>) -> Self {
+        Self {
+            inner: inner.into(),
+            tenant: tenant.into(),
+        }
+    }
+
+    /// A view of the same inner store scoped to another tenant.
+    pub fn for_tenant(&self, tenant: impl Into<TenantId>) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            tenant: tenant.into(),
+        }
+    }
+
+    pub fn tenant(&self) -> &TenantId {
+        &self.tenant
+    }
+
+    /// The unscoped store, for cross-tenant maintenance.
+    pub fn inner(&self) -> &ES {
+        &self.inner
+    }
+
+    fn tenant_filter(&self) -> StreamFilter {
+        StreamFilter::with_tenant_id(self.tenant.as_str())
+    }
+
+    /// Stamp the tenant, refusing metadata that already names another one or that can't
+    /// carry it: the inner store only checks stream ownership for appends with a tenant.
+    fn stamp(&self, metadata: Metadata) -> Result<Metadata, replay::Error> {
+        let metadata = match metadata.tenant_id() {
+            Some(tenant) if tenant == self.tenant.as_str() => metadata,
+            Some(tenant) => {
+                return Err(replay::Error::forbidden(
+                    "metadata names a different tenant than the store scope",
+                )
+                .with_operation("tenant_scope")
+                .with_context("tenant", &self.tenant)
+                .with_context("metadata_tenant", tenant))
+            }
+            None => metadata.with_tenant_id(self.tenant.as_str()),
+        };
+        if metadata.tenant_id() != Some(self.tenant.as_str()) {
+            return Err(replay::Error::invalid_input(
+                "metadata must be a JSON object to carry the tenant",
+            )
+            .with_operation("tenant_scope")
+            .with_context("tenant", &self.tenant));
+        }
+        Ok(metadata)
+    }
+
+    /// Fail with `Forbidden` unless `stream_id` is this tenant's: it holds the tenant's
+    /// events and no others.
+    async fn ensure_owned(&self, stream_id: &Urn) -> Result<(), replay::Error> {
+        if self.owns(stream_id).await? {
+            Ok(())
+        } else {
+            Err(self.not_owned(stream_id))
+        }
+    }
+
+    /// Fail with `Forbidden` when `stream_id` holds any event outside this tenant. A stream
+    /// without events passes: it's free to create, and reads of it come back empty.
+    async fn ensure_not_foreign(&self, stream_id: &Urn) -> Result<(), replay::Error> {
+        if self.holds_any(stream_id, !self.tenant_filter()).await? {
+            Err(self.not_owned(stream_id))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn not_owned(&self, stream_id: &Urn) -> replay::Error {
+        replay::Error::forbidden("stream belongs to another tenant")
+            .with_operation("tenant_scope")
+            .with_context("tenant", &self.tenant)
+            .with_context("stream_id", stream_id)
+    }
+
+    /// Whether `stream_id` holds events, and all of them are this tenant's.
+    async fn owns(&self, stream_id: &Urn) -> Result<bool, replay::Error> {
+        Ok(self.holds_any(stream_id, self.tenant_filter()).await?
+            && !self.holds_any(stream_id, !self.tenant_filter()).await?)
+    }
+
+    /// Whether `stream_id` holds an event matching `filter`, deleted or not.
+    async fn holds_any(
+        &self,
+        stream_id: &Urn,
+        filter: StreamFilter,
+    ) -> Result<bool, replay::Error> {
+        let events = self
+            .inner
+            .stream_events::<AnyEvent>(
+                StreamFilter::WithStreamId(stream_id.clone())
+                    .and(filter)
+                    .including_deleted(),
+            )
+            .into_stream();
+        futures::pin_mut!(events);
+        Ok(events.try_next().await?.is_some())
+    }
+}
+
+impl<ES> Clone for TenantScopedEventStore<ES> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            tenant: self.tenant.clone(),
+        }
+    }
+}
+
+impl<ES: EventStore> EventStore for TenantScopedEventStore<ES> {
+    async fn store_events_stream<S, Events, Sink>(
+        &self,
+        stream_id: &S::StreamId,
+        stream_type: &str,
+        metadata: replay::Metadata,
+        domain_events: Events,
+        expected_version: Option<i64>,
+        sink: Sink,
+    ) -> Result<(), replay::Error>
+    where
+        S: replay::EventStream,
+        Events: TryStream<Ok = S::Event, Error = replay::Error> + Send,
+        Sink: EventSink<S::Event> + Send,
+    {
+        // The inner store refuses appends of one tenant to a stream created by another, in the
+        // same step as the append, so a new stream can't be claimed twice.
+        let metadata = self.stamp(metadata)?;
+
+        self.inner
+            .store_events_stream::<S, _, _>(
+                stream_id,
+                stream_type,
+                metadata,
+                domain_events,
+                expected_version,
+                sink,
+            )
+            .await
+    }
+
+    fn stream_events<E: Event>(
+        &self,
+        filter: StreamFilter,
+    ) -> impl TryStream<Ok = PersistedEvent<E>, Error = replay::Error> + Send {
+        self.inner
+            .stream_events::<E>(filter.and(self.tenant_filter()))
+    }
+
+    fn stream_events_by_stream_id<S: replay::EventStream>(
+        &self,
+        stream_id: &S::StreamId,
+        aggregate_version: AggregateVersion,
+        at_stream_version: Option<i64>,
+        at_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> impl TryStream<Ok = PersistedEvent<S::Event>, Error = replay::Error> + Send {
+        let stream_id: Urn = stream_id.clone().into();
+        let filter = StreamFilter::WithStreamId(stream_id.clone())
+            .and_aggregate_version(aggregate_version.as_option())
+            .and_at_stream_version_optional(at_stream_version)
+            .and_at_timestamp_optional(at_timestamp);
+
+        async_stream::try_stream! {
+            self.ensure_not_foreign(&stream_id).await?;
+
+            let events = self.stream_events::<S::Event>(filter).into_stream();
+            futures::pin_mut!(events);
+
+            while let Some(event) = events.try_next().await? {
+                yield event;
+            }
+        }
+    }
+
+    async fn needs_compaction(&self, stream_id: &impl StreamId) -> Result<bool, replay::Error> {
+        self.ensure_owned(&stream_id.stream_urn()).await?;
+        self.inner.needs_compaction(stream_id).await
+    }
+
+    async fn health_check(&self) -> Result<StoreHealth, replay::Error> {
+        self.inner.health_check().await
+    }
+
+    async fn compact<A>(
+        &self,
+        aggregate: &A,
+        metadata: replay::Metadata,
+    ) -> Result<CompactionOutcome, replay::Error>
+    where
+        A: replay::Aggregate + Compactable + Sync,
+    {
+        // Compacted events are rewritten with this metadata, so it must keep the tenant.
+        let metadata = self.stamp(metadata)?;
+        self.ensure_owned(&aggregate.get_id().clone().into())
+            .await?;
+
+        self.inner.compact(aggregate, metadata).await
+    }
+
+    async fn compact_stream<S>(
+        &self,
+        stream_id: &S::StreamId,
+        up_to_version: i64,
+        metadata: replay::Metadata,
+    ) -> Result<u64, replay::Error>
+    where
+        S: Snapshottable,
+    {
+        // The snapshot is written with this metadata, so it must keep the tenant.
+        let metadata = self.stamp(metadata)?;
+        self.ensure_owned(&stream_id.clone().into()).await?;
+
+        self.inner
+            .compact_stream::<S>(stream_id, up_to_version, metadata)
+            .await
+    }
+
+    async fn delete_stream(
+        &self,
+        stream_id: &impl StreamId,
+        mode: DeletionMode,
+    ) -> Result<(), replay::Error> {
+        self.ensure_owned(&stream_id.stream_urn()).await?;
+        self.inner.delete_stream(stream_id, mode).await
+    }
+
+    async fn is_deleted(&self, stream_id: &impl StreamId) -> Result<bool, replay::Error> {
+        // Checked before a stream's first append too, so an empty stream passes.
+        self.ensure_not_foreign(&stream_id.stream_urn()).await?;
+        self.inner.is_deleted(stream_id).await
+    }
+
+    async fn truncate_stream(
+        &self,
+        stream_id: &impl StreamId,
+        before_version: i64,
+    ) -> Result<u64, replay::Error> {
+        self.ensure_owned(&stream_id.stream_urn()).await?;
+        self.inner.truncate_stream(stream_id, before_version).await
+    }
+
+    async fn set_stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+        retention: StreamRetention,
+    ) -> Result<(), replay::Error> {
+        self.ensure_owned(&stream_id.stream_urn()).await?;
+        self.inner.set_stream_retention(stream_id, retention).await
+    }
+
+    async fn stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> Result<StreamRetention, replay::Error> {
+        self.ensure_owned(&stream_id.stream_urn()).await?;
+        self.inner.stream_retention(stream_id).await
+    }
+
+    /// Fails with `Forbidden`: enforcing retention expires events of every tenant, so it
+    /// runs on the [`inner`](Self::inner) store.
+    async fn enforce_stream_retention(&self) -> Result<u64, replay::Error> {
+        Err(replay::Error::forbidden(
+            "retention is enforced for every tenant at once, on the inner store",
+        )
+        .with_operation("enforce_stream_retention")
+        .with_context("tenant", &self.tenant))
+    }
+
+    async fn set_stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+        metadata: StreamMetadata,
+    ) -> Result<(), replay::Error> {
+        self.ensure_owned(&stream_id.stream_urn()).await?;
+        self.inner.set_stream_metadata(stream_id, metadata).await
+    }
+
+    async fn stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> Result<StreamMetadata, replay::Error> {
+        self.ensure_owned(&stream_id.stream_urn()).await?;
+        self.inner.stream_metadata(stream_id).await
+    }
+
+    /// Pages through the inner store, keeping the tenant's streams; a page may come back
+    /// short, even empty, before the last.
+    async fn list_streams(
+        &self,
+        stream_type: Option<&str>,
+        page: StreamPage,
+    ) -> Result<StreamListing, replay::Error> {
+        let listing = self.inner.list_streams(stream_type, page).await?;
+        let mut streams = Vec::with_capacity(listing.streams.len());
+        for stream in listing.streams {
+            if self.owns(&stream.stream_id).await? {
+                streams.push(stream);
+            }
+        }
+        Ok(StreamListing {
+            streams,
+            next_page: listing.next_page,
+        })
+    }
+
+    async fn stream_info(&self, stream_id: &impl StreamId) -> Result<StreamState, replay::Error> {
+        self.ensure_owned(&stream_id.stream_urn()).await?;
+        self.inner.stream_info(stream_id).await
+    }
+
+    async fn rename_stream(
+        &self,
+        old_id: &impl StreamId,
+        new_id: &impl StreamId,
+    ) -> Result<(), replay::Error> {
+        self.ensure_owned(&old_id.stream_urn()).await?;
+        self.ensure_not_foreign(&new_id.stream_urn()).await?;
+        self.inner.rename_stream(old_id, new_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+    use replay_macros::Event;
+    use serde::{Deserialize, Serialize};
+    use urn::{Urn, UrnBuilder};
+
+    use replay::{ErrorKind, EventStream, Metadata, WithId};
+
+    use super::TenantScopedEventStore;
+    use crate::{AggregateVersion, DeletionMode, EventStore, InMemoryEventStore, StreamFilter};
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Event)]
+    enum NoteEvent {
+        Written { text: String },
+    }
+
+    #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+    struct NoteUrn(Urn);
+
+    impl From<NoteUrn> for Urn {
+        fn from(urn: NoteUrn) -> Self {
+            urn.0
+        }
+    }
+
+    impl TryFrom<Urn> for NoteUrn {
+        type Error = String;
+
+        fn try_from(urn: Urn) -> Result<Self, Self::Error> {
+            Ok(NoteUrn(urn))
+        }
+    }
+
+    struct Note {
+        id: NoteUrn,
+    }
+
+    impl WithId for Note {
+        type StreamId = NoteUrn;
+
+        fn with_id(id: Self::StreamId) -> Self {
+            Note { id }
+        }
+
+        fn get_id(&self) -> &Self::StreamId {
+            &self.id
+        }
+    }
+
+    impl replay::EventStream for Note {
+        type Event = NoteEvent;
+
+        fn stream_type() -> &'static str {
+            "Note"
+        }
+
+        fn apply(&mut self, _event: Self::Event) {}
+    }
+
+    fn note_id(id: &str) -> NoteUrn {
+        NoteUrn(UrnBuilder::new("note", id).build().unwrap())
+    }
+
+    fn written(text: &str) -> NoteEvent {
+        NoteEvent::Written {
+            text: text.to_string(),
+        }
+    }
+
+    async fn write(
+        store: &TenantScopedEventStore<InMemoryEventStore>,
+        id: &str,
+        metadata: Metadata,
+    ) -> Result<(), replay::Error> {
+        store
+            .store_events::<Note>(
+                &note_id(id),
+                Note::stream_type(),
+                metadata,
+                &[written(id)],
+                None,
+            )
+            .await
+    }
+
+    #[tokio::test]
+    async fn writes_are_stamped_and_reads_are_scoped() {
+        let acme = TenantScopedEventStore::new(InMemoryEventStore::new(), "acme");
+        let globex = acme.for_tenant("globex");
+
+        write(&acme, "a", Metadata::default()).await.unwrap();
+        write(&globex, "g", Metadata::default()).await.unwrap();
+
+        let seen: Vec<_> = acme
+            .stream_events::<NoteEvent>(StreamFilter::all())
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].data, written("a"));
+        assert_eq!(seen[0].metadata.tenant_id(), Some("acme"));
+    }
+
+    #[tokio::test]
+    async fn cross_tenant_stream_access_is_forbidden() {
+        let acme = TenantScopedEventStore::new(InMemoryEventStore::new(), "acme");
+        let globex = acme.for_tenant("globex");
+
+        write(&acme, "shared", Metadata::default()).await.unwrap();
+
+        let err = write(&globex, "shared", Metadata::default())
+            .await
+            .expect_err("appending to another tenant's stream must fail");
+        assert_eq!(err.kind(), ErrorKind::Forbidden);
+
+        let err = globex
+            .stream_events_by_stream_id::<Note>(
+                &note_id("shared"),
+                AggregateVersion::Latest,
+                None,
+                None,
+            )
+            .try_collect::<Vec<_>>()
+            .await
+            .expect_err("loading another tenant's stream must fail");
+        assert_eq!(err.kind(), ErrorKind::Forbidden);
+    }
+
+    #[tokio::test]
+    async fn only_one_tenant_claims_a_new_stream() {
+        let acme = TenantScopedEventStore::new(InMemoryEventStore::new(), "acme");
+        let globex = acme.for_tenant("globex");
+
+        let (a, g) = futures::join!(
+            write(&acme, "contested", Metadata::default()),
+            write(&globex, "contested", Metadata::default()),
+        );
+
+        let err = a.and(g).expect_err("the second claim must fail");
+        assert_eq!(err.kind(), ErrorKind::Forbidden);
+        let events: Vec<_> = acme
+            .inner()
+            .stream_events::<NoteEvent>(StreamFilter::WithStreamId(note_id("contested").into()))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn streams_without_the_tenants_events_are_not_managed() {
+        let acme = TenantScopedEventStore::new(InMemoryEventStore::new(), "acme");
+        let empty = note_id("empty");
+
+        let err = acme
+            .delete_stream(&empty, DeletionMode::Hard)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Forbidden);
+        let err = acme.stream_info(&empty).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Forbidden);
+
+        // Still free to read and create.
+        assert!(!acme.is_deleted(&empty).await.unwrap());
+        write(&acme, "empty", Metadata::default()).await.unwrap();
+        acme.stream_info(&empty).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn retention_is_not_enforced_through_a_tenant_scope() {
+        let acme = TenantScopedEventStore::new(InMemoryEventStore::new(), "acme");
+
+        let err = acme.enforce_stream_retention().await.unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::Forbidden);
+        acme.inner().enforce_stream_retention().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn metadata_naming_another_tenant_is_forbidden() {
+        let acme = TenantScopedEventStore::new(InMemoryEventStore::new(), "acme");
+
+        let err = write(&acme, "a", Metadata::default().with_tenant_id("globex"))
+            .await
+            .expect_err("must fail");
+
+        assert_eq!(err.kind(), ErrorKind::Forbidden);
+    }
+
+    #[tokio::test]
+    async fn metadata_that_cannot_carry_the_tenant_is_rejected() {
+        let acme = TenantScopedEventStore::new(InMemoryEventStore::new(), "acme");
+        let globex = acme.for_tenant("globex");
+        write(&acme, "a", Metadata::default()).await.unwrap();
+
+        let err = write(&globex, "a", Metadata::new("x"))
+            .await
+            .expect_err("scalar metadata must not skip the ownership check");
+
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let events: Vec<_> = acme
+            .inner()
+            .stream_events::<NoteEvent>(StreamFilter::WithStreamId(note_id("a").into()))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+    }
+}
@@ -0,0 +1,209 @@
+//! Synthetic load: fill a store with many streams of generated events, optionally at a fixed
+//! rate, to benchmark stores, queries and policy runners on realistic volumes.
+
+use std::time::{Duration, Instant};
+
+use replay::{EventStream, Metadata};
+
+use crate::EventStore;
+
+type StreamIdFn<S> = Box<dyn Fn(usize) -> <S as replay::WithId>::StreamId + Send + Sync>;
+type EventFn<S> = Box<dyn Fn(usize, usize) -> <S as EventStream>::Event + Send + Sync>;
+
+/// Appends `streams × events_per_stream` generated events of the stream type `S`.
+///
+/// Streams are written round-robin, `batch_size` events per append, so their events interleave
+/// in the log as they would under concurrent writers. With a [`rate`](Self::rate) the
+/// generator paces itself to that many events per second; without one it writes as fast as the
+/// store accepts.
+///
+/// ```rust,ignore
+/// let report = LoadGenerator::<BankAccount>::new(
+///     |stream| BankAccountUrn::new(&format!("load-{stream}")).unwrap(),
+///     |_stream, n| BankAccountEvent::Deposited { amount: n as f64 },
+/// )
+/// .streams(1_000)
+/// .events_per_stream(100)
+/// .batch_size(10)
+/// .rate(5_000)
+/// .run(&store)
+/// .await?;
+/// println!("{} events/s", report.events_per_second());
+/// ```
+pub struct LoadGenerator<S: EventStream> {
+    stream_id: StreamIdFn<S>,
+    event: EventFn<S>,
+    streams: usize,
+    events_per_stream: usize,
+    batch_size: usize,
+    rate: Option<u32>,
+    metadata: Metadata,
+}
+
+/// What a [`LoadGenerator::run`] wrote.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadReport {
+    pub events: usize,
+    pub appends: usize,
+    pub elapsed: Duration,
+}
+
+impl LoadReport {
+    /// Throughput achieved over the whole run.
+    pub fn events_per_second(&self) -> f64 {
+        self.events as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+impl<S: EventStream> LoadGenerator<S> {
+    /// `stream_id(stream)` names stream number `stream`; `event(stream, n)` is its `n`th event,
+    /// both counting from 0. Defaults to 10 streams of 10 events, one event per append.
+    pub fn new(
+        stream_id: impl Fn(usize) -> S::StreamId + Send + Sync + 'static,
+        event: impl Fn(usize, usize) -> S::Event + Send + Sync + 'static,
+    ) -> Self {
+        LoadGenerator {
+            stream_id: Box::new(stream_id),
+            event: Box::new(event),
+            streams: 10,
+            events_per_stream: 10,
+            batch_size: 1,
+            rate: None,
+            metadata: Metadata::default(),
+        }
+    }
+
+    pub fn streams(mut self, streams: usize) -> Self {
+        self.streams = streams;
+        self
+    }
+
+    pub fn events_per_stream(mut self, events_per_stream: usize) -> Self {
+        self.events_per_stream = events_per_stream;
+        self
+    }
+
+    /// Events written per append (at least 1).
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Pace the run to about `events_per_second`.
+    pub fn rate(mut self, events_per_second: u32) -> Self {
+        self.rate = Some(events_per_second.max(1));
+        self
+    }
+
+    /// Metadata written with every event.
+    pub fn metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Write every event to `store`, stopping at the first failed append.
+    pub async fn run(&self, store: &impl EventStore) -> replay::Result<LoadReport> {
+        let started = Instant::now();
+        let stream_ids: Vec<_> = (0..self.streams).map(&self.stream_id).collect();
+        let mut report = LoadReport {
+            events: 0,
+            appends: 0,
+            elapsed: Duration::ZERO,
+        };
+
+        for first in (0..self.events_per_stream).step_by(self.batch_size) {
+            let last = (first + self.batch_size).min(self.events_per_stream);
+            for (stream, stream_id) in stream_ids.iter().enumerate() {
+                let events: Vec<_> = (first..last).map(|n| (self.event)(stream, n)).collect();
+                store
+                    .store_events::<S>(
+                        stream_id,
+                        S::stream_type(),
+                        self.metadata.clone(),
+                        &events,
+                        None,
+                    )
+                    .await?;
+                report.events += events.len();
+                report.appends += 1;
+
+                if let Some(rate) = self.rate {
+                    let due = Duration::from_secs_f64(report.events as f64 / f64::from(rate));
+                    if let Some(wait) = due.checked_sub(started.elapsed()) {
+                        tokio::time::sleep(wait).await;
+                    }
+                }
+            }
+        }
+
+        report.elapsed = started.elapsed();
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::conformance::{ConformanceAccount, ConformanceAccountUrn, ConformanceEvent};
+    use crate::{InMemoryEventStore, StreamFilter};
+
+    fn generator(ids: Vec<ConformanceAccountUrn>) -> LoadGenerator<ConformanceAccount> {
+        LoadGenerator::new(
+            move |stream| ids[stream].clone(),
+            |stream, n| ConformanceEvent::Deposited {
+                amount: (stream * 100 + n) as i64,
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn writes_every_stream_in_round_robin_batches() {
+        let store = InMemoryEventStore::new();
+        let ids: Vec<_> = (0..3)
+            .map(|_| ConformanceAccountUrn::new_random())
+            .collect();
+
+        let report = generator(ids.clone())
+            .streams(3)
+            .events_per_stream(5)
+            .batch_size(2)
+            .run(&store)
+            .await
+            .unwrap();
+        assert_eq!(report.events, 15);
+        assert_eq!(report.appends, 9);
+
+        let events: Vec<_> = store
+            .stream_events::<ConformanceEvent>(StreamFilter::with_stream_id::<ConformanceAccount>(
+                &ids[2],
+            ))
+            .map_ok(|event| (event.version, event.data))
+            .try_collect()
+            .await
+            .unwrap();
+        let expected: Vec<_> = (0..5)
+            .map(|n| (n + 1, ConformanceEvent::Deposited { amount: 200 + n }))
+            .collect();
+        assert_eq!(events, expected);
+    }
+
+    #[tokio::test]
+    async fn paces_itself_to_the_rate() {
+        let store = InMemoryEventStore::new();
+        let ids: Vec<_> = (0..2)
+            .map(|_| ConformanceAccountUrn::new_random())
+            .collect();
+
+        let report = generator(ids)
+            .streams(2)
+            .events_per_stream(5)
+            .rate(200)
+            .run(&store)
+            .await
+            .unwrap();
+        assert_eq!(report.events, 10);
+        assert!(report.elapsed >= Duration::from_millis(50), "{report:?}");
+    }
+}
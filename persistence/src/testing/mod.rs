@@ -0,0 +1,234 @@
+//! Hand-built event histories for testing queries and projections without a store, and a
+//! [`generator`] of synthetic load.
+
+pub mod generator;
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use urn::Urn;
+
+use replay::{Event, Metadata};
+
+use crate::{IdGenerator, InlineProjection, PersistedEvent, Query, SequentialIds};
+
+/// A list of [`PersistedEvent`]s built by hand, to feed a [`Query`] or an [`InlineProjection`]
+/// in a test.
+///
+/// Events get sequential ids, per-stream versions counting from 1, and timestamps one second
+/// apart from 2024-01-01T00:00:00Z. Each of these, the stream and the metadata can be set
+/// before an event and carry over to the ones after it.
+///
+/// ```rust,ignore
+/// let summary = CannedEvents::for_stream(account_id)
+///     .with_metadata(Metadata::default().with_actor("alice"))
+///     .event(BankAccountEvent::Deposited { amount: 100.0 })
+///     .at(month_end)
+///     .event(BankAccountEvent::Withdrawn { amount: 30.0 })
+///     .replay_into(AccountSummary::default());
+/// ```
+pub struct CannedEvents<E> {
+    events: Vec<PersistedEvent<E>>,
+    stream_id: Urn,
+    versions: HashMap<Urn, i64>,
+    created: DateTime<Utc>,
+    metadata: Metadata,
+    ids: SequentialIds,
+}
+
+impl<E: Event> CannedEvents<E> {
+    /// Start a history whose events belong to `stream_id`.
+    pub fn for_stream(stream_id: impl Into<Urn>) -> Self {
+        CannedEvents {
+            events: Vec::new(),
+            stream_id: stream_id.into(),
+            versions: HashMap::new(),
+            created: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            metadata: Metadata::default(),
+            ids: SequentialIds::default(),
+        }
+    }
+
+    /// Add the next events to `stream_id`, continuing its version count.
+    pub fn on_stream(mut self, stream_id: impl Into<Urn>) -> Self {
+        self.stream_id = stream_id.into();
+        self
+    }
+
+    /// Give the next event `version` in its stream; later ones count on from it.
+    pub fn at_version(mut self, version: i64) -> Self {
+        self.versions.insert(self.stream_id.clone(), version - 1);
+        self
+    }
+
+    /// Create the next event at `created`; later ones follow a second apart.
+    pub fn at(mut self, created: DateTime<Utc>) -> Self {
+        self.created = created;
+        self
+    }
+
+    /// Metadata for the next events.
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Append an event.
+    pub fn event(mut self, data: E) -> Self {
+        let version = self.versions.entry(self.stream_id.clone()).or_insert(0);
+        *version += 1;
+
+        self.events.push(PersistedEvent {
+            id: self.ids.next_id(),
+            r#type: data.event_type().to_string(),
+            data,
+            stream_id: self.stream_id.clone(),
+            version: *version,
+            created: self.created,
+            metadata: self.metadata.clone(),
+            aggregate_version: None,
+            global_position: self.events.len() as i64 + 1,
+        });
+        self.created += Duration::seconds(1);
+        self
+    }
+
+    /// The events built so far.
+    pub fn events(&self) -> &[PersistedEvent<E>] {
+        &self.events
+    }
+
+    /// Feed every event to `query`, in order, and return it.
+    pub fn replay_into<Q: Query<Event = E>>(&self, mut query: Q) -> Q {
+        for event in &self.events {
+            query.update(event.clone());
+        }
+        query
+    }
+
+    /// Hand every event to `projection` as one batch, as a store does after an append, and
+    /// wait for it on the current thread; nothing happens for an empty history.
+    pub fn project<P: InlineProjection<Event = E>>(
+        &self,
+        projection: &mut P,
+        exec: &mut P::Exec,
+    ) -> replay::Result<()> {
+        if self.events.is_empty() {
+            return Ok(());
+        }
+        futures::executor::block_on(projection.handle(exec, &self.events))
+    }
+}
+
+impl<E> From<CannedEvents<E>> for Vec<PersistedEvent<E>> {
+    fn from(canned: CannedEvents<E>) -> Self {
+        canned.events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance::{ConformanceAccountUrn, ConformanceEvent};
+
+    #[derive(Default)]
+    struct Balances {
+        totals: HashMap<Urn, i64>,
+        seen: Vec<(i64, DateTime<Utc>, Option<String>)>,
+    }
+
+    impl Query for Balances {
+        type Event = ConformanceEvent;
+
+        fn update(&mut self, event: PersistedEvent<Self::Event>) {
+            let total = self.totals.entry(event.stream_id.clone()).or_default();
+            match event.data {
+                ConformanceEvent::Deposited { amount } => *total += amount,
+                ConformanceEvent::Withdrawn { amount } => *total -= amount,
+                ConformanceEvent::Opened { .. } => {}
+            }
+            self.seen.push((
+                event.version,
+                event.created,
+                event.actor().map(str::to_string),
+            ));
+        }
+    }
+
+    impl InlineProjection for Balances {
+        type Exec = ();
+        type Event = ConformanceEvent;
+
+        fn name(&self) -> &str {
+            "balances"
+        }
+
+        fn version(&self) -> i32 {
+            1
+        }
+
+        async fn init(&mut self, _conn: &mut ()) -> replay::Result<()> {
+            Ok(())
+        }
+
+        async fn handle(
+            &mut self,
+            _conn: &mut (),
+            events: &[PersistedEvent<Self::Event>],
+        ) -> replay::Result<()> {
+            events.iter().cloned().for_each(|event| self.update(event));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn canned_events_drive_a_query() {
+        let first: Urn = ConformanceAccountUrn::new_random().into();
+        let second: Urn = ConformanceAccountUrn::new_random().into();
+        let month_end = Utc.with_ymd_and_hms(2024, 1, 31, 23, 59, 0).unwrap();
+
+        let canned = CannedEvents::for_stream(first.clone())
+            .event(ConformanceEvent::Deposited { amount: 100 })
+            .on_stream(second.clone())
+            .at_version(5)
+            .with_metadata(Metadata::default().with_actor("alice"))
+            .event(ConformanceEvent::Deposited { amount: 7 })
+            .on_stream(first.clone())
+            .at(month_end)
+            .event(ConformanceEvent::Withdrawn { amount: 30 });
+        let balances = canned.replay_into(Balances::default());
+
+        assert_eq!(balances.totals[&first], 70);
+        assert_eq!(balances.totals[&second], 7);
+        assert_eq!(
+            balances.seen,
+            vec![
+                (1, Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(), None),
+                (
+                    5,
+                    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 1).unwrap(),
+                    Some("alice".to_string())
+                ),
+                (2, month_end, Some("alice".to_string())),
+            ]
+        );
+
+        let events: Vec<PersistedEvent<ConformanceEvent>> = canned.into();
+        assert_eq!(events[2].id, uuid::Uuid::from_u128(3));
+        assert_eq!(events[2].r#type, "Withdrawn");
+    }
+
+    #[test]
+    fn canned_events_drive_a_projection() {
+        let account: Urn = ConformanceAccountUrn::new_random().into();
+        let mut balances = Balances::default();
+
+        CannedEvents::for_stream(account.clone())
+            .event(ConformanceEvent::Deposited { amount: 10 })
+            .event(ConformanceEvent::Deposited { amount: 5 })
+            .project(&mut balances, &mut ())
+            .unwrap();
+
+        assert_eq!(balances.totals[&account], 15);
+    }
+}
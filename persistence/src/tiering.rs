@@ -0,0 +1,707 @@
+//! Hot/cold tiering: [`TieredEventStore`] moves aged events from a hot store to a cold one
+//! and reads across both, so the hot database stays small without losing replayability.
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use futures::{stream, StreamExt, TryStream, TryStreamExt};
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
+use urn::Urn;
+use uuid::Uuid;
+
+use replay::{Compactable, ErrorKind, Event, EventStream, Snapshottable, StreamId};
+
+use crate::persisted_event::AnyEvent;
+use crate::{
+    BulkImport, CompactionOutcome, DeletionMode, EventSink, EventStore, InMemoryEventStore,
+    PersistedEvent, PostgresEventStore, StoreHealth, StreamFilter, StreamListing, StreamMetadata,
+    StreamPage, StreamRetention, StreamState,
+};
+
+/// Stores that can be the hot tier of a [`TieredEventStore`]: they can give up events that
+/// were moved elsewhere.
+pub trait HotTier: EventStore {
+    /// The global position up to which events may leave the store: every event up to it
+    /// has committed and been processed by every policy.
+    fn tiering_horizon(&self) -> impl Future<Output = Result<i64, replay::Error>> + Send;
+
+    /// Delete the events with `ids`, all at or below the [`tiering_horizon`](Self::tiering_horizon),
+    /// returning how many were deleted.
+    fn remove_events(
+        &self,
+        ids: &HashSet<Uuid>,
+    ) -> impl Future<Output = Result<u64, replay::Error>> + Send;
+}
+
+impl HotTier for InMemoryEventStore {
+    async fn tiering_horizon(&self) -> Result<i64, replay::Error> {
+        Ok(self.head_position())
+    }
+
+    async fn remove_events(&self, ids: &HashSet<Uuid>) -> Result<u64, replay::Error> {
+        Ok(InMemoryEventStore::remove_events(self, ids))
+    }
+}
+
+/// Events leave below the contiguous high-water mark and the slowest policy cursor, and the
+/// holes they leave are recorded like [retention](PostgresEventStore::apply_retention) ones
+/// (migration `0022_event_retention`).
+impl HotTier for PostgresEventStore {
+    async fn tiering_horizon(&self) -> Result<i64, replay::Error> {
+        let hwm = self.contiguous_high_water_mark().await?;
+        let slowest: Option<i64> = sqlx::query_scalar("SELECT MIN(position) FROM policy_cursors")
+            .fetch_one(self.pool())
+            .await
+            .map_err(|e| self.map_db_error(e).with_operation("tiering_horizon"))?;
+        Ok(slowest.map_or(hwm, |slowest| slowest.min(hwm)))
+    }
+
+    async fn remove_events(&self, ids: &HashSet<Uuid>) -> Result<u64, replay::Error> {
+        let map_error = |e| self.map_db_error(e).with_operation("remove_events");
+        let horizon = self.contiguous_high_water_mark().await?;
+        let ids: Vec<Uuid> = ids.iter().copied().collect();
+
+        let mut tx = self.pool().begin().await.map_err(map_error)?;
+        sqlx::query(
+            "INSERT INTO event_retention (horizon) VALUES ($1) \
+             ON CONFLICT (id) DO UPDATE \
+                SET horizon = GREATEST(event_retention.horizon, EXCLUDED.horizon)",
+        )
+        .bind(horizon)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_error)?;
+        let removed =
+            sqlx::query("DELETE FROM events WHERE id = ANY($1) AND global_position <= $2")
+                .bind(&ids)
+                .bind(horizon)
+                .execute(&mut *tx)
+                .await
+                .map_err(map_error)?
+                .rows_affected();
+        tx.commit().await.map_err(map_error)?;
+        Ok(removed)
+    }
+}
+
+/// Which events of a stream type a [tiering pass](TieredEventStore::apply_tiering) moves.
+///
+/// ```rust,ignore
+/// let policy = TieringPolicy::older_than(Duration::from_secs(90 * 24 * 60 * 60))
+///     .with_batch_size(5_000);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TieringPolicy {
+    older_than: Duration,
+    batch_size: usize,
+}
+
+impl TieringPolicy {
+    /// Move events created longer than `older_than` ago.
+    pub fn older_than(older_than: Duration) -> Self {
+        TieringPolicy {
+            older_than,
+            batch_size: 1_000,
+        }
+    }
+
+    /// Move `size` events per cold write and hot delete (at least 1).
+    pub fn with_batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+}
+
+/// An [`EventStore`] over a hot store that takes appends and a cold store that keeps aged
+/// history, e.g. a small Postgres database in front of a cheap archive one.
+///
+/// Appends go to the hot store. [`apply_tiering`](Self::apply_tiering) moves the events a
+/// [`TieringPolicy`] selects to the cold store, keeping their ids, versions, timestamps and
+/// metadata, and then deletes them from the hot one. The newest live event of each stream
+/// always stays hot, so the hot store keeps checking expected versions, and only events up
+/// to the [`tiering_horizon`](HotTier::tiering_horizon) move, so policies see every event
+/// before it leaves.
+///
+/// Reads return the matching cold events first, then the hot ones, so a stream loads in
+/// version order across both tiers. An event caught in both by an interrupted pass is
+/// returned once. Global positions are per tier, so position-based consumers run against
+/// the hot store. A read waits for a pass to finish deleting a batch and a pass waits for
+/// the reads in progress, so no event is missed mid-move; this only holds within one
+/// `TieredEventStore` and its clones, so run tiering in the process serving the reads.
+///
+/// Compaction runs on the hot store and is refused (`InvalidInput`) for a stream that
+/// already has events in the cold one: compact streams before they age out.
+///
+/// ```rust,ignore
+/// let store = TieredEventStore::new(hot_store, archive_store);
+/// let daemon = store.start_tiering::<BankAccount>(policy, Duration::from_secs(60 * 60));
+/// let cqrs = Cqrs::new(store);
+/// ```
+pub struct TieredEventStore<Hot, Cold> {
+    hot: Arc<Hot>,
+    cold: Arc<Cold>,
+    moving: Arc<RwLock<()>>,
+}
+
+impl<Hot: HotTier + 'static, Cold: BulkImport + 'static> TieredEventStore<Hot, Cold> {
+    pub fn new(hot: impl Into<Arc<Hot>>, cold: impl Into<Arc<Cold>>) -> Self {
+        Self {
+            hot: hot.into(),
+            cold: cold.into(),
+            moving: Arc::new(RwLock::new(())),
+        }
+    }
+
+    pub fn hot_store(&self) -> &Hot {
+        &self.hot
+    }
+
+    pub fn cold_store(&self) -> &Cold {
+        &self.cold
+    }
+
+    /// Move the events of `S` streams that `policy` selects from the hot store to the cold
+    /// one, returning how many were moved.
+    ///
+    /// Each batch is imported into the cold store, then deleted from the hot one. A batch
+    /// interrupted in between is finished by the next pass.
+    pub async fn apply_tiering<S: EventStream>(
+        &self,
+        policy: &TieringPolicy,
+    ) -> Result<u64, replay::Error> {
+        let map_error = |e: replay::Error| {
+            e.with_operation("apply_tiering")
+                .with_context("stream_type", S::stream_type())
+        };
+        let cutoff = Utc::now()
+            - chrono::Duration::from_std(policy.older_than).unwrap_or(chrono::Duration::MAX);
+        let horizon = self.hot.tiering_horizon().await.map_err(map_error)?;
+
+        let mut moved = 0;
+        let mut after = 0;
+        loop {
+            let filter = StreamFilter::for_stream_type::<S>()
+                .and(StreamFilter::created_before(cutoff))
+                .and(StreamFilter::after_global_position(after));
+            let batch: Vec<PersistedEvent<S::Event>> = self
+                .hot
+                .stream_events::<S::Event>(filter)
+                .into_stream()
+                .try_take_while(|event| {
+                    futures::future::ready(Ok(event.global_position <= horizon))
+                })
+                .take(policy.batch_size)
+                .try_collect()
+                .await
+                .map_err(map_error)?;
+            let Some(last) = batch.last() else {
+                break;
+            };
+            after = last.global_position;
+            let full = batch.len() == policy.batch_size;
+
+            let batch = self.without_stream_heads(batch).await.map_err(map_error)?;
+            if !batch.is_empty() {
+                self.import_cold::<S>(&batch).await.map_err(map_error)?;
+                let ids = batch.iter().map(|event| event.id).collect();
+                let _moving = self.moving.write().await;
+                moved += self.hot.remove_events(&ids).await.map_err(map_error)?;
+            }
+
+            if !full {
+                break;
+            }
+        }
+
+        if moved > 0 {
+            tracing::info!(
+                stream_type = S::stream_type(),
+                moved,
+                "tiering moved events to the cold store"
+            );
+        }
+        Ok(moved)
+    }
+
+    /// Run [`apply_tiering`](Self::apply_tiering) for `S` every `interval` in a background
+    /// task, starting now. A failing pass is logged and retried on the next tick.
+    ///
+    /// Use [`TieringDaemon::shutdown`] to stop it.
+    pub fn start_tiering<S: EventStream + 'static>(
+        &self,
+        policy: TieringPolicy,
+        interval: Duration,
+    ) -> TieringDaemon {
+        let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+        let store = self.clone();
+
+        let task = tokio::spawn(async move {
+            loop {
+                if let Err(error) = store.apply_tiering::<S>(&policy).await {
+                    tracing::warn!(
+                        stream_type = S::stream_type(),
+                        error = %error,
+                        "tiering pass failed; retrying next interval"
+                    );
+                }
+
+                tokio::select! {
+                    changed = shutdown_rx.changed() => {
+                        if changed.is_err() || *shutdown_rx.borrow() {
+                            return;
+                        }
+                    }
+                    _ = tokio::time::sleep(interval) => {}
+                }
+            }
+        });
+
+        TieringDaemon { shutdown_tx, task }
+    }
+
+    /// `batch` without the newest live event of each stream, unless the hot store holds a
+    /// later one.
+    async fn without_stream_heads<E: Event>(
+        &self,
+        mut batch: Vec<PersistedEvent<E>>,
+    ) -> Result<Vec<PersistedEvent<E>>, replay::Error> {
+        let mut heads: HashMap<Urn, i64> = HashMap::new();
+        for event in batch
+            .iter()
+            .filter(|event| event.aggregate_version.is_none())
+        {
+            let version = heads.entry(event.stream_id.clone()).or_default();
+            *version = (*version).max(event.version);
+        }
+
+        for (stream_id, version) in heads {
+            let later = StreamFilter::WithStreamId(stream_id.clone())
+                .and(StreamFilter::WithAggregateVersion(None))
+                .and(StreamFilter::after_version(version));
+            let later = self.hot.stream_events::<AnyEvent>(later).into_stream();
+            futures::pin_mut!(later);
+            let has_later = later.try_next().await?.is_some();
+            if !has_later {
+                batch.retain(|event| {
+                    event.stream_id != stream_id
+                        || event.aggregate_version.is_some()
+                        || event.version != version
+                });
+            }
+        }
+        Ok(batch)
+    }
+
+    /// Import `batch` into the cold store, skipping the events a pass interrupted before
+    /// deleting them from the hot store already put there.
+    async fn import_cold<S: EventStream>(
+        &self,
+        batch: &[PersistedEvent<S::Event>],
+    ) -> Result<(), replay::Error> {
+        let events = batch.iter().cloned().map(Ok);
+        match self.cold.bulk_import::<S, _>(stream::iter(events)).await {
+            Err(error) if error.kind() == ErrorKind::Conflict => {}
+            result => return result.map(|_| ()),
+        }
+
+        let stream_ids: HashSet<Urn> = batch.iter().map(|event| event.stream_id.clone()).collect();
+        let present: HashSet<Uuid> = self
+            .cold
+            .stream_events::<AnyEvent>(StreamFilter::WithStreamIds(
+                stream_ids.into_iter().collect(),
+            ))
+            .map_ok(|event| event.id)
+            .try_collect()
+            .await?;
+        let missing = batch
+            .iter()
+            .filter(|event| !present.contains(&event.id))
+            .cloned()
+            .map(Ok);
+        self.cold.bulk_import::<S, _>(stream::iter(missing)).await?;
+        Ok(())
+    }
+
+    /// Refuse compacting a stream that already has events in the cold store.
+    async fn ensure_hot_only(&self, stream_id: Urn) -> Result<(), replay::Error> {
+        let cold = self
+            .cold
+            .stream_events::<AnyEvent>(StreamFilter::WithStreamId(stream_id.clone()))
+            .into_stream();
+        futures::pin_mut!(cold);
+        let in_cold = cold.try_next().await?.is_some();
+        if in_cold {
+            return Err(
+                replay::Error::invalid_input("stream has events in the cold store")
+                    .with_context("stream_id", stream_id),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<Hot, Cold> Clone for TieredEventStore<Hot, Cold> {
+    fn clone(&self) -> Self {
+        Self {
+            hot: self.hot.clone(),
+            cold: self.cold.clone(),
+            moving: self.moving.clone(),
+        }
+    }
+}
+
+impl<Hot: HotTier + 'static, Cold: BulkImport + 'static> EventStore
+    for TieredEventStore<Hot, Cold>
+{
+    async fn store_events_stream<S, Events, Sink>(
+        &self,
+        stream_id: &S::StreamId,
+        stream_type: &str,
+        metadata: replay::Metadata,
+        domain_events: Events,
+        expected_version: Option<i64>,
+        sink: Sink,
+    ) -> Result<(), replay::Error>
+    where
+        S: replay::EventStream,
+        Events: TryStream<Ok = S::Event, Error = replay::Error> + Send,
+        Sink: EventSink<S::Event> + Send,
+    {
+        self.hot
+            .store_events_stream::<S, _, _>(
+                stream_id,
+                stream_type,
+                metadata,
+                domain_events,
+                expected_version,
+                sink,
+            )
+            .await
+    }
+
+    fn stream_events<E: Event>(
+        &self,
+        filter: StreamFilter,
+    ) -> impl TryStream<Ok = PersistedEvent<E>, Error = replay::Error> + Send {
+        async_stream::try_stream! {
+            // Held until the hot events are read, so no batch moves between the two reads.
+            let _reading = self.moving.read().await;
+
+            let mut cold_ids = HashSet::new();
+            let cold = self.cold.stream_events::<E>(filter.clone()).into_stream();
+            futures::pin_mut!(cold);
+            while let Some(event) = cold.try_next().await? {
+                cold_ids.insert(event.id);
+                yield event;
+            }
+
+            let hot = self.hot.stream_events::<E>(filter).into_stream();
+            futures::pin_mut!(hot);
+            while let Some(event) = hot.try_next().await? {
+                if !cold_ids.contains(&event.id) {
+                    yield event;
+                }
+            }
+        }
+    }
+
+    async fn needs_compaction(&self, stream_id: &impl StreamId) -> Result<bool, replay::Error> {
+        self.hot.needs_compaction(stream_id).await
+    }
+
+    async fn compact<A>(
+        &self,
+        aggregate: &A,
+        metadata: replay::Metadata,
+    ) -> Result<CompactionOutcome, replay::Error>
+    where
+        A: replay::Aggregate + Compactable + Sync,
+    {
+        self.ensure_hot_only(aggregate.get_id().clone().into())
+            .await
+            .map_err(|e| e.with_operation("compact"))?;
+        self.hot.compact(aggregate, metadata).await
+    }
+
+    async fn compact_stream<S>(
+        &self,
+        stream_id: &S::StreamId,
+        up_to_version: i64,
+        metadata: replay::Metadata,
+    ) -> Result<u64, replay::Error>
+    where
+        S: Snapshottable,
+    {
+        self.ensure_hot_only(stream_id.clone().into())
+            .await
+            .map_err(|e| e.with_operation("compact_stream"))?;
+        self.hot
+            .compact_stream::<S>(stream_id, up_to_version, metadata)
+            .await
+    }
+
+    /// Deletes the stream from both tiers; it is a `NotFound` only when neither has it.
+    async fn delete_stream(
+        &self,
+        stream_id: &impl StreamId,
+        mode: DeletionMode,
+    ) -> Result<(), replay::Error> {
+        let hot = self.hot.delete_stream(stream_id, mode).await;
+        let cold = self.cold.delete_stream(stream_id, mode).await;
+        match (hot, cold) {
+            (Err(error), _) if error.kind() != ErrorKind::NotFound => Err(error),
+            (_, Err(error)) if error.kind() != ErrorKind::NotFound => Err(error),
+            (Err(error), Err(_)) => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    async fn is_deleted(&self, stream_id: &impl StreamId) -> Result<bool, replay::Error> {
+        self.hot.is_deleted(stream_id).await
+    }
+
+    /// Truncates both tiers, returning the total; it is a `NotFound` only when neither has
+    /// the stream. The cold tier keeps its own newest event of the stream, which reads
+    /// still return below the hot ones.
+    async fn truncate_stream(
+        &self,
+        stream_id: &impl StreamId,
+        before_version: i64,
+    ) -> Result<u64, replay::Error> {
+        let hot = self.hot.truncate_stream(stream_id, before_version).await;
+        let cold = self.cold.truncate_stream(stream_id, before_version).await;
+        match (hot, cold) {
+            (Err(error), _) if error.kind() != ErrorKind::NotFound => Err(error),
+            (_, Err(error)) if error.kind() != ErrorKind::NotFound => Err(error),
+            (Err(error), Err(_)) => Err(error),
+            (hot, cold) => Ok(hot.unwrap_or(0) + cold.unwrap_or(0)),
+        }
+    }
+
+    /// Sets the limits in both tiers; it is a `NotFound` only when neither has the stream.
+    /// Each tier applies them to the events it holds.
+    async fn set_stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+        retention: StreamRetention,
+    ) -> Result<(), replay::Error> {
+        let hot = self.hot.set_stream_retention(stream_id, retention).await;
+        let cold = self.cold.set_stream_retention(stream_id, retention).await;
+        match (hot, cold) {
+            (Err(error), _) if error.kind() != ErrorKind::NotFound => Err(error),
+            (_, Err(error)) if error.kind() != ErrorKind::NotFound => Err(error),
+            (Err(error), Err(_)) => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    async fn stream_retention(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> Result<StreamRetention, replay::Error> {
+        self.hot.stream_retention(stream_id).await
+    }
+
+    /// Enforces in both tiers and returns the total.
+    async fn enforce_stream_retention(&self) -> Result<u64, replay::Error> {
+        let (hot, cold) = futures::try_join!(
+            self.hot.enforce_stream_retention(),
+            self.cold.enforce_stream_retention()
+        )?;
+        Ok(hot + cold)
+    }
+
+    async fn set_stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+        metadata: StreamMetadata,
+    ) -> Result<(), replay::Error> {
+        let hot = self
+            .hot
+            .set_stream_metadata(stream_id, metadata.clone())
+            .await;
+        let cold = self.cold.set_stream_metadata(stream_id, metadata).await;
+        match (hot, cold) {
+            (Err(error), _) if error.kind() != ErrorKind::NotFound => Err(error),
+            (_, Err(error)) if error.kind() != ErrorKind::NotFound => Err(error),
+            (Err(error), Err(_)) => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    async fn stream_metadata(
+        &self,
+        stream_id: &impl StreamId,
+    ) -> Result<StreamMetadata, replay::Error> {
+        self.hot.stream_metadata(stream_id).await
+    }
+
+    /// Lists the hot tier, which keeps every stream's newest event.
+    async fn list_streams(
+        &self,
+        stream_type: Option<&str>,
+        page: StreamPage,
+    ) -> Result<StreamListing, replay::Error> {
+        self.hot.list_streams(stream_type, page).await
+    }
+
+    /// Combines both tiers: `created` usually comes from the cold one, the rest from the hot
+    /// one.
+    async fn stream_info(&self, stream_id: &impl StreamId) -> Result<StreamState, replay::Error> {
+        let (hot, cold) = futures::try_join!(
+            self.hot.stream_info(stream_id),
+            self.cold.stream_info(stream_id)
+        )?;
+        Ok(StreamState {
+            exists: hot.exists || cold.exists,
+            version: hot.version.max(cold.version),
+            created: hot.created.into_iter().chain(cold.created).min(),
+            updated: hot.updated.max(cold.updated),
+            deleted: hot.deleted,
+        })
+    }
+
+    /// Renames the stream in both tiers, holding off tiering passes meanwhile; it is a
+    /// `NotFound` only when neither has the stream. The tiers rename one after the other,
+    /// so a failure in the cold one leaves the stream split across both ids.
+    async fn rename_stream(
+        &self,
+        old_id: &impl StreamId,
+        new_id: &impl StreamId,
+    ) -> Result<(), replay::Error> {
+        let _moving = self.moving.write().await;
+        let hot = self.hot.rename_stream(old_id, new_id).await;
+        if let Err(error) = &hot {
+            if error.kind() != ErrorKind::NotFound {
+                return hot;
+            }
+        }
+        let cold = self.cold.rename_stream(old_id, new_id).await;
+        match (hot, cold) {
+            (_, Err(error)) if error.kind() != ErrorKind::NotFound => Err(error),
+            (Err(error), Err(_)) => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    /// Probes both tiers and reports the slower; fails when either does.
+    async fn health_check(&self) -> Result<StoreHealth, replay::Error> {
+        let (hot, cold) = futures::try_join!(self.hot.health_check(), self.cold.health_check())?;
+        Ok(if cold.latency > hot.latency {
+            cold
+        } else {
+            hot
+        })
+    }
+}
+
+/// Handle for the background task spawned by [`TieredEventStore::start_tiering`].
+pub struct TieringDaemon {
+    shutdown_tx: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+impl TieringDaemon {
+    /// Stop the task, letting a pass in progress finish its current batch.
+    pub async fn shutdown(self) {
+        let _ = self.shutdown_tx.send(true);
+        let _ = self.task.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use replay::Metadata;
+
+    use super::*;
+    use crate::conformance::{ConformanceAccount, ConformanceAccountUrn, ConformanceEvent};
+
+    type Tiered = TieredEventStore<InMemoryEventStore, InMemoryEventStore>;
+
+    async fn deposit(store: &Tiered, id: &ConformanceAccountUrn, amount: i64) {
+        store
+            .store_events::<ConformanceAccount>(
+                id,
+                ConformanceAccount::stream_type(),
+                Metadata::default(),
+                &[ConformanceEvent::Deposited { amount }],
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn versions(store: &impl EventStore, id: &ConformanceAccountUrn) -> Vec<i64> {
+        store
+            .stream_events::<ConformanceEvent>(StreamFilter::with_stream_id::<ConformanceAccount>(
+                id,
+            ))
+            .map_ok(|event| event.version)
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn aged_events_move_cold_and_reads_span_both_tiers() {
+        let store = Tiered::new(InMemoryEventStore::new(), InMemoryEventStore::new());
+        let id = ConformanceAccountUrn::new_random();
+        for amount in 1..=3 {
+            deposit(&store, &id, amount).await;
+        }
+
+        let moved = store
+            .apply_tiering::<ConformanceAccount>(&TieringPolicy::older_than(Duration::ZERO))
+            .await
+            .unwrap();
+
+        // The stream head stays hot, so appends still check the expected version.
+        assert_eq!(moved, 2);
+        assert_eq!(versions(store.cold_store(), &id).await, [1, 2]);
+        assert_eq!(versions(store.hot_store(), &id).await, [3]);
+        store
+            .store_events::<ConformanceAccount>(
+                &id,
+                ConformanceAccount::stream_type(),
+                Metadata::default(),
+                &[ConformanceEvent::Withdrawn { amount: 1 }],
+                Some(3),
+            )
+            .await
+            .unwrap();
+        assert_eq!(versions(&store, &id).await, [1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn an_interrupted_pass_is_finished_by_the_next_one() {
+        let store = Tiered::new(InMemoryEventStore::new(), InMemoryEventStore::new());
+        let id = ConformanceAccountUrn::new_random();
+        for amount in 1..=3 {
+            deposit(&store, &id, amount).await;
+        }
+        // A pass that imported the first event but stopped before deleting it.
+        let first: Vec<PersistedEvent<ConformanceEvent>> = store
+            .hot_store()
+            .stream_events(StreamFilter::up_to_version(1))
+            .try_collect()
+            .await
+            .unwrap();
+        store
+            .import_cold::<ConformanceAccount>(&first)
+            .await
+            .unwrap();
+        assert_eq!(versions(&store, &id).await, [1, 2, 3]);
+
+        let moved = store
+            .apply_tiering::<ConformanceAccount>(&TieringPolicy::older_than(Duration::ZERO))
+            .await
+            .unwrap();
+        assert_eq!(moved, 2);
+        assert_eq!(versions(store.cold_store(), &id).await, [1, 2]);
+        assert_eq!(versions(&store, &id).await, [1, 2, 3]);
+    }
+}
@@ -0,0 +1,40 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use futures::{Stream, StreamExt};
+
+/// Where the time of one [`Cqrs::execute_timed`](crate::Cqrs::execute_timed) went, to tell
+/// database latency apart from domain logic.
+///
+/// Event producers returned by `handle_stream` run while the append consumes them; the time
+/// spent inside the producer counts as `handle`, the rest of the append as `append`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExecutionTimeline {
+    /// Loading the aggregate's events and folding them into its state.
+    pub hydrate: Duration,
+    /// Running the command handler and producing its events.
+    pub handle: Duration,
+    /// Writing the events, including inline projections and folding them into the aggregate.
+    pub append: Duration,
+    /// Work after the append committed and before the result was returned.
+    pub post_commit: Duration,
+}
+
+impl ExecutionTimeline {
+    pub fn total(&self) -> Duration {
+        self.hydrate + self.handle + self.append + self.post_commit
+    }
+}
+
+/// Wrap `stream`, adding the time spent polling it to `spent` (in nanoseconds).
+pub(crate) fn timed<'a, S>(mut stream: S, spent: &'a AtomicU64) -> impl Stream<Item = S::Item> + 'a
+where
+    S: Stream + Unpin + 'a,
+{
+    futures::stream::poll_fn(move |cx| {
+        let started = Instant::now();
+        let next = stream.poll_next_unpin(cx);
+        spent.fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        next
+    })
+}
@@ -0,0 +1,147 @@
+//! W3C trace-context propagation through event metadata (feature `opentelemetry`).
+//!
+//! On append, [`Cqrs::execute`](crate::Cqrs::execute) records the current span's context as
+//! `traceparent`/`tracestate` metadata. Consumers — the policy runner and inline projections —
+//! read it back so the spans processing an event join the trace of the command that wrote it.
+//! Custom subscribers do the same with [`PersistedEvent::follow_trace`] or
+//! [`PersistedEvent::link_trace`].
+
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use replay::Metadata;
+
+use crate::PersistedEvent;
+
+/// Record the current span's trace context in `metadata`, unless it already carries one.
+///
+/// Leaves the metadata untouched when there is no valid OpenTelemetry context, e.g. when
+/// no `tracing-opentelemetry` layer is installed.
+pub fn inject_trace_context(metadata: Metadata) -> Metadata {
+    if metadata.traceparent().is_some() {
+        return metadata;
+    }
+
+    let context = tracing::Span::current().context();
+    let span = context.span();
+    let span_context = span.span_context();
+    if !span_context.is_valid() {
+        return metadata;
+    }
+
+    metadata.with_trace_context(
+        format_traceparent(span_context),
+        &span_context.trace_state().header(),
+    )
+}
+
+/// The remote span context recorded in `metadata`, if it holds a valid `traceparent`.
+pub fn extract_trace_context(metadata: &Metadata) -> Option<SpanContext> {
+    parse_traceparent(metadata.traceparent()?, metadata.tracestate())
+}
+
+impl<E> PersistedEvent<E> {
+    /// The span context of the command that appended this event, if recorded.
+    pub fn trace_context(&self) -> Option<SpanContext> {
+        extract_trace_context(&self.metadata)
+    }
+
+    /// Make `span` a child of the span that appended this event.
+    ///
+    /// Use for per-event processing; must be called before `span` is first entered.
+    pub fn follow_trace(&self, span: &tracing::Span) {
+        if let Some(span_context) = self.trace_context() {
+            // Only fails once the span has started, which the contract above rules out.
+            let _ = span
+                .set_parent(opentelemetry::Context::new().with_remote_span_context(span_context));
+        }
+    }
+
+    /// Link `span` to the span that appended this event.
+    ///
+    /// Use for batch processing, where one span handles events from many traces.
+    pub fn link_trace(&self, span: &tracing::Span) {
+        if let Some(span_context) = self.trace_context() {
+            span.add_link(span_context);
+        }
+    }
+}
+
+fn format_traceparent(span_context: &SpanContext) -> String {
+    format!(
+        "00-{}-{}-{:02x}",
+        span_context.trace_id(),
+        span_context.span_id(),
+        span_context.trace_flags().to_u8()
+    )
+}
+
+/// Parse a version `00` `traceparent`; malformed or all-zero ids yield `None`.
+fn parse_traceparent(traceparent: &str, tracestate: Option<&str>) -> Option<SpanContext> {
+    let mut parts = traceparent.split('-');
+    let (version, trace_id, span_id, flags) =
+        (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+    if version != "00"
+        || parts.next().is_some()
+        || trace_id.len() != 32
+        || span_id.len() != 16
+        || flags.len() != 2
+    {
+        return None;
+    }
+
+    let span_context = SpanContext::new(
+        TraceId::from_hex(trace_id).ok()?,
+        SpanId::from_hex(span_id).ok()?,
+        TraceFlags::new(u8::from_str_radix(flags, 16).ok()?),
+        true,
+        tracestate
+            .and_then(|state| state.parse::<TraceState>().ok())
+            .unwrap_or_default(),
+    );
+
+    span_context.is_valid().then_some(span_context)
+}
+
+#[cfg(test)]
+mod tests {
+    use replay::Metadata;
+
+    use super::*;
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn traceparent_round_trips() {
+        let metadata = Metadata::default().with_trace_context(TRACEPARENT, "vendor=opaque");
+
+        let span_context = extract_trace_context(&metadata).expect("valid traceparent");
+
+        assert!(span_context.is_remote());
+        assert!(span_context.is_sampled());
+        assert_eq!(format_traceparent(&span_context), TRACEPARENT);
+        assert_eq!(span_context.trace_state().header(), "vendor=opaque");
+    }
+
+    #[test]
+    fn malformed_traceparents_are_ignored() {
+        for traceparent in [
+            "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-not-hex-01",
+        ] {
+            assert!(
+                parse_traceparent(traceparent, None).is_none(),
+                "{traceparent}"
+            );
+        }
+    }
+
+    #[test]
+    fn inject_is_a_no_op_without_an_otel_context() {
+        let metadata = inject_trace_context(Metadata::default());
+
+        assert_eq!(metadata.traceparent(), None);
+    }
+}
@@ -0,0 +1,310 @@
+//! Consistency checks over a whole store, for after a migration, a restore or an incident.
+
+use std::collections::HashMap;
+use std::future::Future;
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use replay::EventStream;
+use urn::Urn;
+use uuid::Uuid;
+
+use crate::persisted_event::AnyEvent;
+use crate::{EventStore, InMemoryEventStore, PostgresEventStore, StreamFilter};
+
+/// Stores that keep each stream's head version apart from its events, which [`verify`]
+/// checks the events against.
+pub trait VerifiableStore: EventStore {
+    /// The recorded head version of every stream of `stream_type`, keyed by stream id.
+    fn stream_heads(
+        &self,
+        stream_type: &str,
+    ) -> impl Future<Output = Result<HashMap<String, i64>, replay::Error>> + Send;
+}
+
+impl VerifiableStore for InMemoryEventStore {
+    async fn stream_heads(&self, stream_type: &str) -> Result<HashMap<String, i64>, replay::Error> {
+        Ok(InMemoryEventStore::stream_heads(self, stream_type)
+            .into_iter()
+            .map(|(stream_id, head)| (stream_id.to_string(), head))
+            .collect())
+    }
+}
+
+/// Heads are the `streams` table.
+impl VerifiableStore for PostgresEventStore {
+    async fn stream_heads(&self, stream_type: &str) -> Result<HashMap<String, i64>, replay::Error> {
+        let rows: Vec<(String, i64)> =
+            sqlx::query_as("SELECT id, version FROM streams WHERE type = $1")
+                .bind(stream_type)
+                .fetch_all(self.pool())
+                .await
+                .map_err(|e| {
+                    self.map_db_error(e)
+                        .with_operation("stream_heads")
+                        .with_context("stream_type", stream_type)
+                })?;
+        Ok(rows.into_iter().collect())
+    }
+}
+
+/// Something [`verify`] found wrong.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Anomaly {
+    /// A live event whose version doesn't follow the previous live one of its stream.
+    UnexpectedVersion {
+        stream_id: Urn,
+        event_id: Uuid,
+        expected: i64,
+        found: i64,
+    },
+    /// A live event created before the previous live one of its stream.
+    TimestampRegression {
+        stream_id: Urn,
+        event_id: Uuid,
+        previous: DateTime<Utc>,
+        created: DateTime<Utc>,
+    },
+    /// A stream id the stream type's id doesn't accept.
+    InvalidStreamId { stream_id: Urn, error: String },
+    /// A payload that doesn't deserialize as the stream type's event.
+    UnreadablePayload {
+        stream_id: Urn,
+        event_id: Uuid,
+        event_type: String,
+        error: String,
+    },
+    /// A stream whose recorded head isn't its last live version; `recorded` is `None` when
+    /// the stream has events but no record, and `events` is `0` when it has a record but no
+    /// events.
+    HeadMismatch {
+        stream_id: String,
+        recorded: Option<i64>,
+        events: i64,
+    },
+}
+
+/// What [`verify`] checked and found.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConsistencyReport {
+    pub streams: u64,
+    pub events: u64,
+    pub anomalies: Vec<Anomaly>,
+}
+
+impl ConsistencyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+}
+
+/// Where the live events of one stream got to while verifying.
+struct StreamCursor {
+    version: i64,
+    created: DateTime<Utc>,
+}
+
+/// Check the `S` streams matching `filter`: live versions follow each other without gaps,
+/// live timestamps never go back, stream ids convert to `S::StreamId`, payloads deserialize
+/// as `S::Event`, and each stream's recorded head is its last live version.
+///
+/// Every anomaly is reported, not just the first; the store is only read. Narrow `filter`
+/// to whole streams (ids, tenant, metadata): one that leaves out some events of a stream
+/// reports the gaps it leaves. A stream compacted with
+/// [`compact_stream`](EventStore::compact_stream) starts at its snapshot's version, and one
+/// cut with [`truncate_stream`](EventStore::truncate_stream) at its first kept version.
+///
+/// ```rust,ignore
+/// let report = verify::<BankAccount, _>(&store, StreamFilter::All).await?;
+/// for anomaly in &report.anomalies {
+///     tracing::error!(?anomaly, "store inconsistency");
+/// }
+/// ```
+pub async fn verify<S, Store>(
+    store: &Store,
+    filter: StreamFilter,
+) -> Result<ConsistencyReport, replay::Error>
+where
+    S: EventStream,
+    Store: VerifiableStore,
+{
+    let map_error = |e: replay::Error| {
+        e.with_operation("verify")
+            .with_context("stream_type", S::stream_type())
+    };
+    let whole_type = matches!(filter, StreamFilter::All);
+    let events = store
+        .stream_events::<AnyEvent>(
+            StreamFilter::for_stream_type::<S>()
+                .and(filter)
+                .including_deleted(),
+        )
+        .into_stream();
+    futures::pin_mut!(events);
+
+    let mut report = ConsistencyReport::default();
+    let mut streams: HashMap<Urn, Option<StreamCursor>> = HashMap::new();
+    while let Some(event) = events.try_next().await.map_err(map_error)? {
+        report.events += 1;
+        let cursor = streams.entry(event.stream_id.clone()).or_insert_with(|| {
+            if let Err(error) = S::StreamId::try_from(event.stream_id.clone()) {
+                report.anomalies.push(Anomaly::InvalidStreamId {
+                    stream_id: event.stream_id.clone(),
+                    error: format!("{error:?}"),
+                });
+            }
+            None
+        });
+
+        if let Err(error) = serde_json::from_value::<S::Event>(event.data.0) {
+            report.anomalies.push(Anomaly::UnreadablePayload {
+                stream_id: event.stream_id.clone(),
+                event_id: event.id,
+                event_type: event.r#type.clone(),
+                error: error.to_string(),
+            });
+        }
+
+        if event.aggregate_version.is_some() {
+            continue;
+        }
+        if let Some(previous) = cursor {
+            if event.version != previous.version + 1 {
+                report.anomalies.push(Anomaly::UnexpectedVersion {
+                    stream_id: event.stream_id.clone(),
+                    event_id: event.id,
+                    expected: previous.version + 1,
+                    found: event.version,
+                });
+            }
+            if event.created < previous.created {
+                report.anomalies.push(Anomaly::TimestampRegression {
+                    stream_id: event.stream_id.clone(),
+                    event_id: event.id,
+                    previous: previous.created,
+                    created: event.created,
+                });
+            }
+        }
+        *cursor = Some(StreamCursor {
+            version: event.version,
+            created: event.created,
+        });
+    }
+    report.streams = streams.len() as u64;
+
+    let mut heads = store
+        .stream_heads(S::stream_type())
+        .await
+        .map_err(map_error)?;
+    for (stream_id, cursor) in streams {
+        let events = cursor.map_or(0, |cursor| cursor.version);
+        let stream_id = stream_id.to_string();
+        let recorded = heads.remove(&stream_id);
+        if recorded != Some(events) {
+            report.anomalies.push(Anomaly::HeadMismatch {
+                stream_id,
+                recorded,
+                events,
+            });
+        }
+    }
+    // The filter may leave out streams; only a full check can tell they lost their events.
+    if whole_type {
+        report.streams += heads.len() as u64;
+        for (stream_id, recorded) in heads.into_iter().filter(|(_, recorded)| *recorded > 0) {
+            report.anomalies.push(Anomaly::HeadMismatch {
+                stream_id,
+                recorded: Some(recorded),
+                events: 0,
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+    use replay::Metadata;
+
+    use super::*;
+    use crate::conformance::{ConformanceAccount, ConformanceAccountUrn, ConformanceEvent};
+    use crate::PersistedEvent;
+
+    async fn deposit(store: &InMemoryEventStore, id: &ConformanceAccountUrn) {
+        store
+            .store_events::<ConformanceAccount>(
+                id,
+                ConformanceAccount::stream_type(),
+                Metadata::default(),
+                &[ConformanceEvent::Deposited { amount: 1 }],
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn appended_streams_are_consistent() {
+        let store = InMemoryEventStore::new();
+        for _ in 0..3 {
+            let id = ConformanceAccountUrn::new_random();
+            deposit(&store, &id).await;
+            deposit(&store, &id).await;
+        }
+
+        let report = verify::<ConformanceAccount, _>(&store, StreamFilter::All)
+            .await
+            .unwrap();
+        assert!(report.is_consistent(), "{report:?}");
+        assert_eq!((report.streams, report.events), (3, 6));
+    }
+
+    #[tokio::test]
+    async fn gaps_and_timestamp_regressions_are_reported() {
+        let store = InMemoryEventStore::new();
+        let id = ConformanceAccountUrn::new_random();
+        deposit(&store, &id).await;
+        let events: Vec<PersistedEvent<ConformanceEvent>> = store
+            .stream_events(StreamFilter::All)
+            .try_collect()
+            .await
+            .unwrap();
+        let first = events[0].clone();
+        // Version 3 without a version 2, and dated before version 1.
+        let stray = PersistedEvent {
+            id: Uuid::now_v7(),
+            version: 3,
+            created: first.created - chrono::Duration::seconds(1),
+            ..first
+        };
+        store
+            .bulk_import::<ConformanceAccount, _>(stream::iter([Ok(stray.clone())]))
+            .await
+            .unwrap();
+
+        let report = verify::<ConformanceAccount, _>(&store, StreamFilter::All)
+            .await
+            .unwrap();
+        let stream_id: Urn = id.into();
+        assert_eq!(
+            report.anomalies,
+            [
+                Anomaly::UnexpectedVersion {
+                    stream_id: stream_id.clone(),
+                    event_id: stray.id,
+                    expected: 2,
+                    found: 3,
+                },
+                Anomaly::TimestampRegression {
+                    stream_id,
+                    event_id: stray.id,
+                    previous: first.created,
+                    created: stray.created,
+                },
+            ]
+        );
+    }
+}
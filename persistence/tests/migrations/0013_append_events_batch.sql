@@ -0,0 +1,73 @@
+-- Batch append: write every event of a command with a single statement.
+--
+-- `append_event` (0004) appends one event per call, so a command emitting N events
+-- cost N round trips inside the append transaction. `append_events` takes the events
+-- as parallel arrays (element i of each array describes event i) and inserts them all
+-- with one INSERT ... SELECT FROM UNNEST, then returns them with one SELECT.
+--
+-- Semantics match N consecutive `append_event` calls:
+-- - the stream row is locked (and created at version 0 if missing) first;
+-- - the optimistic-concurrency check runs once, against the head before the batch;
+--   on a mismatch zero rows are returned and nothing is written;
+-- - the events take versions head + 1 .. head + N in array order, and
+--   `global_position` is assigned in the same order.
+--
+-- Returns one (id, version, created) row per appended event.
+CREATE OR REPLACE FUNCTION append_events(
+    p_ids uuid[],
+    p_data jsonb[],
+    p_metadata jsonb[],
+    p_types text[],
+    p_stream_id text,
+    p_stream_type text,
+    p_expected_stream_version bigint default null
+) RETURNS TABLE(id uuid, version bigint, created timestamp with time zone)
+  LANGUAGE plpgsql
+  AS $$
+  DECLARE
+    stream_version bigint;
+  BEGIN
+    SELECT
+      s.version INTO stream_version
+    FROM streams as s
+    WHERE
+      s.id = p_stream_id FOR UPDATE;
+
+    IF stream_version IS NULL THEN
+      stream_version := 0;
+
+      INSERT INTO streams
+      (id, type, version)
+      VALUES
+      (p_stream_id, p_stream_type, stream_version);
+    END IF;
+
+    IF p_expected_stream_version IS NOT NULL AND stream_version != p_expected_stream_version THEN
+        RETURN;
+    END IF;
+
+    UPDATE streams as s
+        SET version = stream_version + cardinality(p_ids)
+    WHERE
+        s.id = p_stream_id;
+
+    INSERT INTO events
+        (id, data, metadata, stream_id, type, version)
+    SELECT
+        batch.event_id, batch.event_data, batch.event_metadata, p_stream_id,
+        batch.event_type, stream_version + batch.position
+    FROM UNNEST(p_ids, p_data, p_metadata, p_types)
+        WITH ORDINALITY AS batch(event_id, event_data, event_metadata, event_type, position)
+    ORDER BY batch.position;
+
+    -- The rows just written, read back by stream and version.
+    RETURN QUERY
+    SELECT e.id, e.version, e.created
+    FROM events as e
+    WHERE
+        e.stream_id = p_stream_id
+        AND e.aggregate_version IS NULL
+        AND e.version > stream_version
+    ORDER BY e.version;
+  END;
+$$;
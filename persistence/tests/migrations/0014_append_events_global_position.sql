@@ -0,0 +1,71 @@
+-- Return each appended event's `global_position` from `append_events` (0013), so the
+-- store can report it on the `PersistedEvent`s it hands to sinks and inline projections
+-- without reading the rows again.
+--
+-- Postgres cannot change a function's return type with CREATE OR REPLACE, so drop it
+-- first (as 0004 did for `append_event`).
+DROP FUNCTION IF EXISTS append_events(uuid[], jsonb[], jsonb[], text[], text, text, bigint);
+
+CREATE OR REPLACE FUNCTION append_events(
+    p_ids uuid[],
+    p_data jsonb[],
+    p_metadata jsonb[],
+    p_types text[],
+    p_stream_id text,
+    p_stream_type text,
+    p_expected_stream_version bigint default null
+) RETURNS TABLE(
+    id uuid,
+    version bigint,
+    created timestamp with time zone,
+    global_position bigint
+)
+  LANGUAGE plpgsql
+  AS $$
+  DECLARE
+    stream_version bigint;
+  BEGIN
+    SELECT
+      s.version INTO stream_version
+    FROM streams as s
+    WHERE
+      s.id = p_stream_id FOR UPDATE;
+
+    IF stream_version IS NULL THEN
+      stream_version := 0;
+
+      INSERT INTO streams
+      (id, type, version)
+      VALUES
+      (p_stream_id, p_stream_type, stream_version);
+    END IF;
+
+    IF p_expected_stream_version IS NOT NULL AND stream_version != p_expected_stream_version THEN
+        RETURN;
+    END IF;
+
+    UPDATE streams as s
+        SET version = stream_version + cardinality(p_ids)
+    WHERE
+        s.id = p_stream_id;
+
+    INSERT INTO events
+        (id, data, metadata, stream_id, type, version)
+    SELECT
+        batch.event_id, batch.event_data, batch.event_metadata, p_stream_id,
+        batch.event_type, stream_version + batch.position
+    FROM UNNEST(p_ids, p_data, p_metadata, p_types)
+        WITH ORDINALITY AS batch(event_id, event_data, event_metadata, event_type, position)
+    ORDER BY batch.position;
+
+    -- The rows just written, read back by stream and version.
+    RETURN QUERY
+    SELECT e.id, e.version, e.created, e.global_position
+    FROM events as e
+    WHERE
+        e.stream_id = p_stream_id
+        AND e.aggregate_version IS NULL
+        AND e.version > stream_version
+    ORDER BY e.version;
+  END;
+$$;
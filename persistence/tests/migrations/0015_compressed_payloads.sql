@@ -0,0 +1,94 @@
+-- Optional compression of large payloads.
+--
+-- A compressed event stores its payload in `data_compressed`, names the codec in
+-- `data_encoding` (e.g. 'zstd') and leaves `data` NULL. Plain events are unchanged:
+-- `data` holds the jsonb and both new columns are NULL. The check constraint keeps
+-- exactly one of the two forms per row.
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS data_compressed bytea,
+    ADD COLUMN IF NOT EXISTS data_encoding text;
+
+ALTER TABLE events ALTER COLUMN data DROP NOT NULL;
+
+ALTER TABLE events
+    ADD CONSTRAINT events_data_or_compressed CHECK (
+        (data IS NOT NULL AND data_encoding IS NULL AND data_compressed IS NULL)
+        OR (data IS NULL AND data_encoding IS NOT NULL AND data_compressed IS NOT NULL)
+    );
+
+-- `append_events` (0014) gains two optional arrays, parallel to the others: the
+-- compressed payload and its encoding per event, with NULL elements for plain events.
+-- A compressed event's `p_data` element is NULL. A new parameter list is a new
+-- function, so drop the old one rather than leave an overload behind.
+DROP FUNCTION IF EXISTS append_events(uuid[], jsonb[], jsonb[], text[], text, text, bigint);
+
+CREATE OR REPLACE FUNCTION append_events(
+    p_ids uuid[],
+    p_data jsonb[],
+    p_metadata jsonb[],
+    p_types text[],
+    p_stream_id text,
+    p_stream_type text,
+    p_expected_stream_version bigint default null,
+    p_data_compressed bytea[] default null,
+    p_data_encodings text[] default null
+) RETURNS TABLE(
+    id uuid,
+    version bigint,
+    created timestamp with time zone,
+    global_position bigint
+)
+  LANGUAGE plpgsql
+  AS $$
+  DECLARE
+    stream_version bigint;
+  BEGIN
+    SELECT
+      s.version INTO stream_version
+    FROM streams as s
+    WHERE
+      s.id = p_stream_id FOR UPDATE;
+
+    IF stream_version IS NULL THEN
+      stream_version := 0;
+
+      INSERT INTO streams
+      (id, type, version)
+      VALUES
+      (p_stream_id, p_stream_type, stream_version);
+    END IF;
+
+    IF p_expected_stream_version IS NOT NULL AND stream_version != p_expected_stream_version THEN
+        RETURN;
+    END IF;
+
+    UPDATE streams as s
+        SET version = stream_version + cardinality(p_ids)
+    WHERE
+        s.id = p_stream_id;
+
+    INSERT INTO events
+        (id, data, metadata, stream_id, type, version, data_compressed, data_encoding)
+    SELECT
+        batch.event_id, batch.event_data, batch.event_metadata, p_stream_id,
+        batch.event_type, stream_version + batch.position,
+        batch.event_data_compressed, batch.event_data_encoding
+    -- UNNEST pads shorter (or NULL) arrays with NULLs, so the new arrays may be omitted.
+    FROM UNNEST(p_ids, p_data, p_metadata, p_types, p_data_compressed, p_data_encodings)
+        WITH ORDINALITY AS batch(
+            event_id, event_data, event_metadata, event_type,
+            event_data_compressed, event_data_encoding, position
+        )
+    ORDER BY batch.position;
+
+    -- The rows just written, read back by stream and version.
+    RETURN QUERY
+    SELECT e.id, e.version, e.created, e.global_position
+    FROM events as e
+    WHERE
+        e.stream_id = p_stream_id
+        AND e.aggregate_version IS NULL
+        AND e.version > stream_version
+    ORDER BY e.version;
+  END;
+$$;
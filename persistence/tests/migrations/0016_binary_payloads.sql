@@ -0,0 +1,88 @@
+-- Binary payload formats.
+--
+-- Payloads written by a non-JSON serializer (CBOR, MessagePack, ...) are stored as bytes
+-- like compressed ones (0015), so the bytes column is renamed for what it now holds,
+-- and `data_encoding` names the format, optionally followed by the compression:
+-- `<format>[+<codec>]`, e.g. 'cbor', 'msgpack+zstd'. Plain jsonb payloads still record
+-- no encoding. Rows compressed under 0015 were JSON, so their 'zstd' becomes 'json+zstd'.
+ALTER TABLE events RENAME COLUMN data_compressed TO data_binary;
+
+UPDATE events SET data_encoding = 'json+zstd' WHERE data_encoding = 'zstd';
+
+-- The function body names the renamed column, and parameter names can't change in
+-- place, so recreate `append_events` with `p_data_binary` in place of
+-- `p_data_compressed`.
+DROP FUNCTION IF EXISTS append_events(
+    uuid[], jsonb[], jsonb[], text[], text, text, bigint, bytea[], text[]
+);
+
+CREATE OR REPLACE FUNCTION append_events(
+    p_ids uuid[],
+    p_data jsonb[],
+    p_metadata jsonb[],
+    p_types text[],
+    p_stream_id text,
+    p_stream_type text,
+    p_expected_stream_version bigint default null,
+    p_data_binary bytea[] default null,
+    p_data_encodings text[] default null
+) RETURNS TABLE(
+    id uuid,
+    version bigint,
+    created timestamp with time zone,
+    global_position bigint
+)
+  LANGUAGE plpgsql
+  AS $$
+  DECLARE
+    stream_version bigint;
+  BEGIN
+    SELECT
+      s.version INTO stream_version
+    FROM streams as s
+    WHERE
+      s.id = p_stream_id FOR UPDATE;
+
+    IF stream_version IS NULL THEN
+      stream_version := 0;
+
+      INSERT INTO streams
+      (id, type, version)
+      VALUES
+      (p_stream_id, p_stream_type, stream_version);
+    END IF;
+
+    IF p_expected_stream_version IS NOT NULL AND stream_version != p_expected_stream_version THEN
+        RETURN;
+    END IF;
+
+    UPDATE streams as s
+        SET version = stream_version + cardinality(p_ids)
+    WHERE
+        s.id = p_stream_id;
+
+    INSERT INTO events
+        (id, data, metadata, stream_id, type, version, data_binary, data_encoding)
+    SELECT
+        batch.event_id, batch.event_data, batch.event_metadata, p_stream_id,
+        batch.event_type, stream_version + batch.position,
+        batch.event_data_binary, batch.event_data_encoding
+    -- UNNEST pads shorter (or NULL) arrays with NULLs, so the new arrays may be omitted.
+    FROM UNNEST(p_ids, p_data, p_metadata, p_types, p_data_binary, p_data_encodings)
+        WITH ORDINALITY AS batch(
+            event_id, event_data, event_metadata, event_type,
+            event_data_binary, event_data_encoding, position
+        )
+    ORDER BY batch.position;
+
+    -- The rows just written, read back by stream and version.
+    RETURN QUERY
+    SELECT e.id, e.version, e.created, e.global_position
+    FROM events as e
+    WHERE
+        e.stream_id = p_stream_id
+        AND e.aggregate_version IS NULL
+        AND e.version > stream_version
+    ORDER BY e.version;
+  END;
+$$;
@@ -0,0 +1,156 @@
+-- Events-table partitioning.
+--
+-- Installs helpers only; the table stays as it is until `replay_partition_events` is
+-- called, once, in a maintenance window. Two layouts are supported:
+--
+-- * 'created_month': RANGE on `created`, one partition per UTC month named
+--   `events_YYYY_MM`. Old months can be dropped whole, and time-bounded reads only scan
+--   the months they cover. Partitions must exist before events land in them: run
+--   `replay_create_event_partitions` ahead of time (e.g. monthly), or appends fail.
+-- * 'stream_hash': HASH on `stream_id`, a fixed number of partitions named `events_pNN`.
+--   A stream's events share a partition, so stream reads and appends touch only one.
+--
+-- Postgres requires every unique index of a partitioned table to include the partition
+-- key, so the primary key becomes (id, <key>) and, for 'created_month', the per-stream
+-- version indexes include `created`. Version uniqueness within a stream is then enforced
+-- by `append_events` locking the stream row, not by the index.
+
+CREATE OR REPLACE FUNCTION replay_create_event_partitions(
+    p_from timestamptz default now(),
+    p_through timestamptz default now() + interval '3 months'
+) RETURNS integer
+  LANGUAGE plpgsql
+  AS $$
+  DECLARE
+    month_start timestamptz := date_trunc('month', p_from, 'UTC');
+    partition_name text;
+    created_count integer := 0;
+  BEGIN
+    WHILE month_start <= p_through LOOP
+      partition_name := 'events_' || to_char(month_start AT TIME ZONE 'UTC', 'YYYY_MM');
+      IF to_regclass(partition_name) IS NULL THEN
+        EXECUTE format(
+          'CREATE TABLE %I PARTITION OF events FOR VALUES FROM (%L) TO (%L)',
+          partition_name, month_start, month_start + interval '1 month'
+        );
+        created_count := created_count + 1;
+      END IF;
+      month_start := month_start + interval '1 month';
+    END LOOP;
+
+    RETURN created_count;
+  END;
+$$;
+
+-- Drops the monthly partitions that end on or before `p_before`, returning their names.
+-- Their events are gone for good: only drop months that are archived, or whose streams
+-- were compacted since (the snapshot lives in a later month).
+CREATE OR REPLACE FUNCTION replay_drop_event_partitions(
+    p_before timestamptz
+) RETURNS SETOF text
+  LANGUAGE plpgsql
+  AS $$
+  DECLARE
+    partition_name text;
+  BEGIN
+    FOR partition_name IN
+      SELECT c.relname
+      FROM pg_inherits i
+      JOIN pg_class c ON c.oid = i.inhrelid
+      WHERE i.inhparent = 'events'::regclass
+        AND c.relname ~ '^events_\d{4}_\d{2}$'
+      ORDER BY c.relname
+    LOOP
+      IF to_date(substr(partition_name, 8), 'YYYY_MM')::timestamp AT TIME ZONE 'UTC'
+           + interval '1 month' <= p_before THEN
+        EXECUTE format('DROP TABLE %I', partition_name);
+        RETURN NEXT partition_name;
+      END IF;
+    END LOOP;
+  END;
+$$;
+
+-- Rebuilds `events` as a partitioned table and copies its rows over, holding an
+-- ACCESS EXCLUSIVE lock throughout. 'created_month' creates a partition for every month
+-- from the oldest event through `p_months_ahead` months from now; 'stream_hash' creates
+-- `p_partitions` partitions.
+CREATE OR REPLACE FUNCTION replay_partition_events(
+    p_strategy text,
+    p_partitions integer default 16,
+    p_months_ahead integer default 3
+) RETURNS void
+  LANGUAGE plpgsql
+  AS $$
+  DECLARE
+    key text;
+    oldest timestamptz;
+  BEGIN
+    IF EXISTS (SELECT 1 FROM pg_partitioned_table WHERE partrelid = 'events'::regclass) THEN
+      RAISE EXCEPTION 'events is already partitioned';
+    END IF;
+
+    CASE p_strategy
+      WHEN 'created_month' THEN key := 'created';
+      WHEN 'stream_hash' THEN key := 'stream_id';
+      ELSE RAISE EXCEPTION 'unknown partitioning strategy %, expected created_month or stream_hash',
+        p_strategy;
+    END CASE;
+
+    LOCK TABLE events IN ACCESS EXCLUSIVE MODE;
+
+    IF p_strategy = 'created_month' THEN
+      EXECUTE 'CREATE TABLE events_partitioned (LIKE events INCLUDING DEFAULTS INCLUDING CONSTRAINTS)
+               PARTITION BY RANGE (created)';
+    ELSE
+      EXECUTE 'CREATE TABLE events_partitioned (LIKE events INCLUDING DEFAULTS INCLUDING CONSTRAINTS)
+               PARTITION BY HASH (stream_id)';
+    END IF;
+
+    -- The global_position sequence belongs to the old table and would be dropped with it.
+    ALTER SEQUENCE events_global_position_seq OWNED BY events_partitioned.global_position;
+
+    -- Move the old table aside so the partitions are created under `events`.
+    ALTER TABLE events RENAME TO events_unpartitioned;
+    ALTER TABLE events_partitioned RENAME TO events;
+
+    IF p_strategy = 'created_month' THEN
+      SELECT min(created) INTO oldest FROM events_unpartitioned;
+      PERFORM replay_create_event_partitions(
+        least(coalesce(oldest, now()), now()),
+        now() + make_interval(months => p_months_ahead)
+      );
+    ELSE
+      FOR i IN 0 .. p_partitions - 1 LOOP
+        EXECUTE format(
+          'CREATE TABLE %I PARTITION OF events FOR VALUES WITH (MODULUS %s, REMAINDER %s)',
+          'events_p' || lpad(i::text, 2, '0'), p_partitions, i
+        );
+      END LOOP;
+    END IF;
+
+    INSERT INTO events SELECT * FROM events_unpartitioned;
+    DROP TABLE events_unpartitioned;
+
+    EXECUTE format('ALTER TABLE events ADD CONSTRAINT events_pkey PRIMARY KEY (id, %I)', key);
+    ALTER TABLE events ADD CONSTRAINT events_stream_id_fkey
+      FOREIGN KEY (stream_id) REFERENCES streams(id);
+
+    IF p_strategy = 'created_month' THEN
+      CREATE UNIQUE INDEX uidx_events_live_version
+        ON events (stream_id, version, created) WHERE aggregate_version IS NULL;
+      CREATE UNIQUE INDEX uidx_events_archived_version
+        ON events (stream_id, version, aggregate_version, created)
+        WHERE aggregate_version IS NOT NULL;
+    ELSE
+      CREATE UNIQUE INDEX uidx_events_live_version
+        ON events (stream_id, version) WHERE aggregate_version IS NULL;
+      CREATE UNIQUE INDEX uidx_events_archived_version
+        ON events (stream_id, version, aggregate_version) WHERE aggregate_version IS NOT NULL;
+    END IF;
+    CREATE INDEX idx_events_aggregate_version ON events (stream_id, aggregate_version);
+    CREATE INDEX idx_events_created_version ON events (created, version);
+    CREATE INDEX idx_events_global_position ON events (global_position);
+    CREATE INDEX idx_events_policy_feed ON events (global_position)
+      WHERE compacted_snapshot = false;
+  END;
+$$;
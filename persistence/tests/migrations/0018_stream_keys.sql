@@ -0,0 +1,11 @@
+-- Per-stream data keys for payload encryption.
+--
+-- A stream's key is created by its first append through a store with encryption installed
+-- and is stored as the `EventCrypto` returned it, normally wrapped by a master key held
+-- outside the database. There is no foreign key to `streams`: the key is written before
+-- the stream row exists on a first append.
+CREATE TABLE IF NOT EXISTS stream_keys (
+    stream_id text PRIMARY KEY,
+    key bytea NOT NULL,
+    created timestamp with time zone NOT NULL DEFAULT now()
+);
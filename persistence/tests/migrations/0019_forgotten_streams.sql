@@ -0,0 +1,7 @@
+-- Crypto-shredding.
+--
+-- `PostgresEventStore::forget_stream` sets a stream's key to NULL and records when, so the
+-- encrypted parts of its events can't be decrypted anymore and new encrypted appends to it
+-- are refused.
+ALTER TABLE stream_keys ALTER COLUMN key DROP NOT NULL;
+ALTER TABLE stream_keys ADD COLUMN IF NOT EXISTS forgotten timestamp with time zone;
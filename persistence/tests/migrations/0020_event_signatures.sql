@@ -0,0 +1,6 @@
+-- Event signatures.
+--
+-- A store with a signer records each event's signature over its stream id, version, type,
+-- stored payload and metadata, checked on read and by `PostgresEventStore::verify_stream`.
+-- Events written without a signer keep a NULL signature.
+ALTER TABLE events ADD COLUMN IF NOT EXISTS signature bytea;
@@ -0,0 +1,14 @@
+-- Tenant isolation with row-level security.
+--
+-- Roles bound by the policy only see and write events whose `tenant_id` metadata equals
+-- the `app.tenant_id` setting, which `PostgresEventStore::with_row_level_security` sets per
+-- transaction; with no setting they see nothing. The tables' owner and `BYPASSRLS` roles are
+-- not bound (the table is not `FORCE`d), so migrations, policy runners and maintenance keep
+-- working when they connect as one of those and tenants are served through another role.
+ALTER TABLE events ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS tenant_isolation ON events;
+
+CREATE POLICY tenant_isolation ON events
+    USING (metadata ->> 'tenant_id' = NULLIF(current_setting('app.tenant_id', true), ''))
+    WITH CHECK (metadata ->> 'tenant_id' = NULLIF(current_setting('app.tenant_id', true), ''));
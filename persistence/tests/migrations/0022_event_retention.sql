@@ -0,0 +1,11 @@
+-- Retention of archived history.
+--
+-- `PostgresEventStore::apply_retention` deletes expired archived events, which leaves holes
+-- in `global_position`. It only deletes at or below the contiguous high-water mark and
+-- records that mark here as the retention horizon, so the policy feed and
+-- `contiguous_high_water_mark` know that holes up to it are expired events, not appends
+-- still in flight. A single row, created by the first retention pass that removes events.
+CREATE TABLE IF NOT EXISTS event_retention (
+    id boolean PRIMARY KEY DEFAULT TRUE CHECK (id),
+    horizon bigint NOT NULL
+);
@@ -0,0 +1,6 @@
+-- Soft deletion of streams.
+--
+-- `EventStore::delete_stream` with `DeletionMode::Soft` records when a stream was deleted.
+-- Its events stay, but reads leave them out unless the filter includes deleted streams.
+-- Hard deletion removes the stream row and its events instead.
+ALTER TABLE streams ADD COLUMN IF NOT EXISTS deleted timestamp with time zone;
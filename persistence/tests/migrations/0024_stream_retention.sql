@@ -0,0 +1,7 @@
+-- Per-stream retention limits.
+--
+-- `EventStore::set_stream_retention` records a stream's `$maxAge` and `$maxCount`. Reads
+-- leave out the live events past either limit, except the stream's last one, and
+-- `enforce_stream_retention` deletes them.
+ALTER TABLE streams ADD COLUMN IF NOT EXISTS max_age interval;
+ALTER TABLE streams ADD COLUMN IF NOT EXISTS max_count bigint;
@@ -0,0 +1,206 @@
+-- Link streams.
+--
+-- `event_links` indexes every event under derived streams, in `global_position` order:
+-- '$ce-<stream type>' for its stream's category and, once
+-- `replay_enable_correlation_links` has been called, '$bc-<correlation_id>' for the
+-- workflow it belongs to. Reads filtered by stream type (and, with
+-- `PostgresEventStoreBuilder::with_correlation_links`, by correlation id) scan a link
+-- stream instead of joining `streams`. Links are written by statement triggers on
+-- `events`, so every append path, compaction and import keeps them, and deleted events
+-- take their links with them (partitions dropped whole leave theirs behind, matching no
+-- event).
+CREATE TABLE IF NOT EXISTS event_links (
+    link text NOT NULL,
+    position bigint NOT NULL,
+    PRIMARY KEY (link, position)
+);
+
+CREATE INDEX IF NOT EXISTS idx_event_links_position ON event_links (position);
+
+CREATE OR REPLACE FUNCTION replay_link_category_events() RETURNS trigger
+  LANGUAGE plpgsql
+  AS $$
+  BEGIN
+    INSERT INTO event_links (link, position)
+    SELECT '$ce-' || s.type, a.global_position
+    FROM appended a
+    JOIN streams s ON s.id = a.stream_id
+    ON CONFLICT DO NOTHING;
+    RETURN NULL;
+  END;
+$$;
+
+CREATE OR REPLACE FUNCTION replay_link_correlated_events() RETURNS trigger
+  LANGUAGE plpgsql
+  AS $$
+  BEGIN
+    INSERT INTO event_links (link, position)
+    SELECT '$bc-' || (a.metadata ->> 'correlation_id'), a.global_position
+    FROM appended a
+    WHERE a.metadata ->> 'correlation_id' IS NOT NULL
+    ON CONFLICT DO NOTHING;
+    RETURN NULL;
+  END;
+$$;
+
+CREATE OR REPLACE FUNCTION replay_unlink_events() RETURNS trigger
+  LANGUAGE plpgsql
+  AS $$
+  BEGIN
+    DELETE FROM event_links l USING removed r WHERE l.position = r.global_position;
+    RETURN NULL;
+  END;
+$$;
+
+-- (Re)creates the link triggers on `events`; `replay_partition_events` calls it for the
+-- table it builds. Correlation links are kept only if they were already enabled.
+CREATE OR REPLACE FUNCTION replay_install_link_triggers(
+    p_correlation boolean default false
+) RETURNS void
+  LANGUAGE plpgsql
+  AS $$
+  BEGIN
+    DROP TRIGGER IF EXISTS events_category_links ON events;
+    CREATE TRIGGER events_category_links
+      AFTER INSERT ON events
+      REFERENCING NEW TABLE AS appended
+      FOR EACH STATEMENT EXECUTE FUNCTION replay_link_category_events();
+
+    DROP TRIGGER IF EXISTS events_unlink ON events;
+    CREATE TRIGGER events_unlink
+      AFTER DELETE ON events
+      REFERENCING OLD TABLE AS removed
+      FOR EACH STATEMENT EXECUTE FUNCTION replay_unlink_events();
+
+    DROP TRIGGER IF EXISTS events_correlation_links ON events;
+    IF p_correlation THEN
+      CREATE TRIGGER events_correlation_links
+        AFTER INSERT ON events
+        REFERENCING NEW TABLE AS appended
+        FOR EACH STATEMENT EXECUTE FUNCTION replay_link_correlated_events();
+    END IF;
+  END;
+$$;
+
+-- Starts linking events under their correlation id, backfilling the events already
+-- stored. Run it before building a store `with_correlation_links`.
+CREATE OR REPLACE FUNCTION replay_enable_correlation_links() RETURNS void
+  LANGUAGE plpgsql
+  AS $$
+  BEGIN
+    PERFORM replay_install_link_triggers(true);
+    INSERT INTO event_links (link, position)
+    SELECT '$bc-' || (metadata ->> 'correlation_id'), global_position
+    FROM events
+    WHERE metadata ->> 'correlation_id' IS NOT NULL
+    ON CONFLICT DO NOTHING;
+  END;
+$$;
+
+-- Stops linking events under their correlation id and drops the correlation links.
+CREATE OR REPLACE FUNCTION replay_disable_correlation_links() RETURNS void
+  LANGUAGE plpgsql
+  AS $$
+  BEGIN
+    PERFORM replay_install_link_triggers(false);
+    DELETE FROM event_links WHERE link LIKE '$bc-%';
+  END;
+$$;
+
+SELECT replay_install_link_triggers(false);
+
+INSERT INTO event_links (link, position)
+SELECT '$ce-' || s.type, e.global_position
+FROM events e
+JOIN streams s ON s.id = e.stream_id
+ON CONFLICT DO NOTHING;
+
+-- As in 0017, and reinstalls the link triggers, which don't carry over to the new table.
+CREATE OR REPLACE FUNCTION replay_partition_events(
+    p_strategy text,
+    p_partitions integer default 16,
+    p_months_ahead integer default 3
+) RETURNS void
+  LANGUAGE plpgsql
+  AS $$
+  DECLARE
+    key text;
+    oldest timestamptz;
+    correlation boolean;
+  BEGIN
+    IF EXISTS (SELECT 1 FROM pg_partitioned_table WHERE partrelid = 'events'::regclass) THEN
+      RAISE EXCEPTION 'events is already partitioned';
+    END IF;
+
+    CASE p_strategy
+      WHEN 'created_month' THEN key := 'created';
+      WHEN 'stream_hash' THEN key := 'stream_id';
+      ELSE RAISE EXCEPTION 'unknown partitioning strategy %, expected created_month or stream_hash',
+        p_strategy;
+    END CASE;
+
+    LOCK TABLE events IN ACCESS EXCLUSIVE MODE;
+
+    SELECT EXISTS (
+      SELECT 1 FROM pg_trigger
+      WHERE tgrelid = 'events'::regclass AND tgname = 'events_correlation_links'
+    ) INTO correlation;
+
+    IF p_strategy = 'created_month' THEN
+      EXECUTE 'CREATE TABLE events_partitioned (LIKE events INCLUDING DEFAULTS INCLUDING CONSTRAINTS)
+               PARTITION BY RANGE (created)';
+    ELSE
+      EXECUTE 'CREATE TABLE events_partitioned (LIKE events INCLUDING DEFAULTS INCLUDING CONSTRAINTS)
+               PARTITION BY HASH (stream_id)';
+    END IF;
+
+    -- The global_position sequence belongs to the old table and would be dropped with it.
+    ALTER SEQUENCE events_global_position_seq OWNED BY events_partitioned.global_position;
+
+    -- Move the old table aside so the partitions are created under `events`.
+    ALTER TABLE events RENAME TO events_unpartitioned;
+    ALTER TABLE events_partitioned RENAME TO events;
+
+    IF p_strategy = 'created_month' THEN
+      SELECT min(created) INTO oldest FROM events_unpartitioned;
+      PERFORM replay_create_event_partitions(
+        least(coalesce(oldest, now()), now()),
+        now() + make_interval(months => p_months_ahead)
+      );
+    ELSE
+      FOR i IN 0 .. p_partitions - 1 LOOP
+        EXECUTE format(
+          'CREATE TABLE %I PARTITION OF events FOR VALUES WITH (MODULUS %s, REMAINDER %s)',
+          'events_p' || lpad(i::text, 2, '0'), p_partitions, i
+        );
+      END LOOP;
+    END IF;
+
+    INSERT INTO events SELECT * FROM events_unpartitioned;
+    DROP TABLE events_unpartitioned;
+
+    EXECUTE format('ALTER TABLE events ADD CONSTRAINT events_pkey PRIMARY KEY (id, %I)', key);
+    ALTER TABLE events ADD CONSTRAINT events_stream_id_fkey
+      FOREIGN KEY (stream_id) REFERENCES streams(id);
+
+    IF p_strategy = 'created_month' THEN
+      CREATE UNIQUE INDEX uidx_events_live_version
+        ON events (stream_id, version, created) WHERE aggregate_version IS NULL;
+      CREATE UNIQUE INDEX uidx_events_archived_version
+        ON events (stream_id, version, aggregate_version, created)
+        WHERE aggregate_version IS NOT NULL;
+    ELSE
+      CREATE UNIQUE INDEX uidx_events_live_version
+        ON events (stream_id, version) WHERE aggregate_version IS NULL;
+      CREATE UNIQUE INDEX uidx_events_archived_version
+        ON events (stream_id, version, aggregate_version) WHERE aggregate_version IS NOT NULL;
+    END IF;
+    CREATE INDEX idx_events_aggregate_version ON events (stream_id, aggregate_version);
+    CREATE INDEX idx_events_created_version ON events (created, version);
+    CREATE INDEX idx_events_global_position ON events (global_position);
+    CREATE INDEX idx_events_policy_feed ON events (global_position)
+      WHERE compacted_snapshot = false;
+
+    PERFORM replay_install_link_triggers(correlation);
+  END;
+$$;
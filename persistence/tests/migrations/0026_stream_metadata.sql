@@ -0,0 +1,8 @@
+-- Stream metadata.
+--
+-- `EventStore::set_stream_metadata` records a stream's owner, ACL and labels as a JSON
+-- document; `list_streams` selects streams by label through the index.
+ALTER TABLE streams ADD COLUMN IF NOT EXISTS metadata jsonb NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS idx_streams_labels
+    ON streams USING gin ((COALESCE(metadata -> 'labels', '{}'::jsonb)));
@@ -0,0 +1,80 @@
+-- Appending to a soft-deleted stream (0023) fails.
+--
+-- `append_events` checks the stream row it already locks, so commands don't need a
+-- separate lookup before appending. The error is raised as `no_data_found` (P0002), which
+-- the store reports as `NotFound`. Hard-deleted streams have no row and start over.
+CREATE OR REPLACE FUNCTION append_events(
+    p_ids uuid[],
+    p_data jsonb[],
+    p_metadata jsonb[],
+    p_types text[],
+    p_stream_id text,
+    p_stream_type text,
+    p_expected_stream_version bigint default null,
+    p_data_binary bytea[] default null,
+    p_data_encodings text[] default null
+) RETURNS TABLE(
+    id uuid,
+    version bigint,
+    created timestamp with time zone,
+    global_position bigint
+)
+  LANGUAGE plpgsql
+  AS $$
+  DECLARE
+    stream_version bigint;
+    stream_deleted timestamp with time zone;
+  BEGIN
+    SELECT
+      s.version, s.deleted INTO stream_version, stream_deleted
+    FROM streams as s
+    WHERE
+      s.id = p_stream_id FOR UPDATE;
+
+    IF stream_deleted IS NOT NULL THEN
+      RAISE EXCEPTION 'stream % was deleted', p_stream_id USING ERRCODE = 'no_data_found';
+    END IF;
+
+    IF stream_version IS NULL THEN
+      stream_version := 0;
+
+      INSERT INTO streams
+      (id, type, version)
+      VALUES
+      (p_stream_id, p_stream_type, stream_version);
+    END IF;
+
+    IF p_expected_stream_version IS NOT NULL AND stream_version != p_expected_stream_version THEN
+        RETURN;
+    END IF;
+
+    UPDATE streams as s
+        SET version = stream_version + cardinality(p_ids)
+    WHERE
+        s.id = p_stream_id;
+
+    INSERT INTO events
+        (id, data, metadata, stream_id, type, version, data_binary, data_encoding)
+    SELECT
+        batch.event_id, batch.event_data, batch.event_metadata, p_stream_id,
+        batch.event_type, stream_version + batch.position,
+        batch.event_data_binary, batch.event_data_encoding
+    -- UNNEST pads shorter (or NULL) arrays with NULLs, so the new arrays may be omitted.
+    FROM UNNEST(p_ids, p_data, p_metadata, p_types, p_data_binary, p_data_encodings)
+        WITH ORDINALITY AS batch(
+            event_id, event_data, event_metadata, event_type,
+            event_data_binary, event_data_encoding, position
+        )
+    ORDER BY batch.position;
+
+    -- The rows just written, read back by stream and version.
+    RETURN QUERY
+    SELECT e.id, e.version, e.created, e.global_position
+    FROM events as e
+    WHERE
+        e.stream_id = p_stream_id
+        AND e.aggregate_version IS NULL
+        AND e.version > stream_version
+    ORDER BY e.version;
+  END;
+$$;
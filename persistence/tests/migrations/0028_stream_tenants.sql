@@ -0,0 +1,99 @@
+-- Streams belong to the tenant of their first append.
+--
+-- `streams.tenant_id` is set from the `tenant_id` metadata of the append that creates the
+-- stream, under the same lock `append_events` already takes, so two tenants can't both claim
+-- a new stream. An append naming a tenant that doesn't own the stream, including a stream
+-- created without one, fails with `insufficient_privilege` (42501), which the store reports
+-- as `Forbidden`. Appends without a tenant aren't checked.
+ALTER TABLE streams ADD COLUMN IF NOT EXISTS tenant_id text;
+
+UPDATE streams AS s
+SET tenant_id = (
+    SELECT e.metadata ->> 'tenant_id'
+    FROM events AS e
+    WHERE e.stream_id = s.id
+    ORDER BY e.version
+    LIMIT 1
+)
+WHERE s.tenant_id IS NULL;
+
+CREATE OR REPLACE FUNCTION append_events(
+    p_ids uuid[],
+    p_data jsonb[],
+    p_metadata jsonb[],
+    p_types text[],
+    p_stream_id text,
+    p_stream_type text,
+    p_expected_stream_version bigint default null,
+    p_data_binary bytea[] default null,
+    p_data_encodings text[] default null
+) RETURNS TABLE(
+    id uuid,
+    version bigint,
+    created timestamp with time zone,
+    global_position bigint
+)
+  LANGUAGE plpgsql
+  AS $$
+  DECLARE
+    stream_version bigint;
+    stream_deleted timestamp with time zone;
+    stream_tenant text;
+    append_tenant text := p_metadata[1] ->> 'tenant_id';
+  BEGIN
+    SELECT
+      s.version, s.deleted, s.tenant_id INTO stream_version, stream_deleted, stream_tenant
+    FROM streams as s
+    WHERE
+      s.id = p_stream_id FOR UPDATE;
+
+    IF stream_deleted IS NOT NULL THEN
+      RAISE EXCEPTION 'stream % was deleted', p_stream_id USING ERRCODE = 'no_data_found';
+    END IF;
+
+    IF stream_version IS NULL THEN
+      stream_version := 0;
+
+      INSERT INTO streams
+      (id, type, version, tenant_id)
+      VALUES
+      (p_stream_id, p_stream_type, stream_version, append_tenant);
+    ELSIF append_tenant IS NOT NULL AND stream_tenant IS DISTINCT FROM append_tenant THEN
+      RAISE EXCEPTION 'stream % belongs to another tenant', p_stream_id
+        USING ERRCODE = 'insufficient_privilege';
+    END IF;
+
+    IF p_expected_stream_version IS NOT NULL AND stream_version != p_expected_stream_version THEN
+        RETURN;
+    END IF;
+
+    UPDATE streams as s
+        SET version = stream_version + cardinality(p_ids)
+    WHERE
+        s.id = p_stream_id;
+
+    INSERT INTO events
+        (id, data, metadata, stream_id, type, version, data_binary, data_encoding)
+    SELECT
+        batch.event_id, batch.event_data, batch.event_metadata, p_stream_id,
+        batch.event_type, stream_version + batch.position,
+        batch.event_data_binary, batch.event_data_encoding
+    -- UNNEST pads shorter (or NULL) arrays with NULLs, so the new arrays may be omitted.
+    FROM UNNEST(p_ids, p_data, p_metadata, p_types, p_data_binary, p_data_encodings)
+        WITH ORDINALITY AS batch(
+            event_id, event_data, event_metadata, event_type,
+            event_data_binary, event_data_encoding, position
+        )
+    ORDER BY batch.position;
+
+    -- The rows just written, read back by stream and version.
+    RETURN QUERY
+    SELECT e.id, e.version, e.created, e.global_position
+    FROM events as e
+    WHERE
+        e.stream_id = p_stream_id
+        AND e.aggregate_version IS NULL
+        AND e.version > stream_version
+    ORDER BY e.version;
+  END;
+$$;
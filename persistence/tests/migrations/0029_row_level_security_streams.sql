@@ -0,0 +1,244 @@
+-- Row-level security for the tables around `events`.
+--
+-- 0021 only bound `events`. Bound roles now also see and write just their tenant's rows of:
+--
+-- - `streams`, by the `tenant_id` of 0028;
+-- - `stream_keys`, by a `tenant_id` column defaulting to the `app.tenant_id` setting, since a
+--   stream's key is written before the stream row on a first append;
+-- - `event_links`, by the event they point at, which the `events` policy already scopes.
+--
+-- `replay_partition_events` now also re-binds the `events` table it rebuilds.
+--
+-- As in 0021 the tables aren't `FORCE`d, so their owner and `BYPASSRLS` roles are not bound.
+-- A bound role can't see another tenant's stream row, so `append_events` now reports an
+-- append to it, which collides with the hidden row, as `insufficient_privilege` instead of a
+-- unique violation. `replay_unlink_events` runs as its owner: the links of deleted events
+-- point at rows the `event_links` policy can no longer see.
+ALTER TABLE streams ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS tenant_isolation ON streams;
+
+CREATE POLICY tenant_isolation ON streams
+    USING (tenant_id = NULLIF(current_setting('app.tenant_id', true), ''))
+    WITH CHECK (tenant_id = NULLIF(current_setting('app.tenant_id', true), ''));
+
+ALTER TABLE stream_keys ADD COLUMN IF NOT EXISTS tenant_id text
+    DEFAULT NULLIF(current_setting('app.tenant_id', true), '');
+
+UPDATE stream_keys AS k
+SET tenant_id = s.tenant_id
+FROM streams AS s
+WHERE s.id = k.stream_id AND k.tenant_id IS NULL;
+
+ALTER TABLE stream_keys ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS tenant_isolation ON stream_keys;
+
+CREATE POLICY tenant_isolation ON stream_keys
+    USING (tenant_id = NULLIF(current_setting('app.tenant_id', true), ''))
+    WITH CHECK (tenant_id = NULLIF(current_setting('app.tenant_id', true), ''));
+
+ALTER TABLE event_links ENABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS tenant_isolation ON event_links;
+
+CREATE POLICY tenant_isolation ON event_links
+    USING (EXISTS (SELECT 1 FROM events AS e WHERE e.global_position = event_links.position))
+    WITH CHECK (EXISTS (SELECT 1 FROM events AS e WHERE e.global_position = event_links.position));
+
+ALTER FUNCTION replay_unlink_events() SECURITY DEFINER SET search_path FROM CURRENT;
+
+CREATE OR REPLACE FUNCTION append_events(
+    p_ids uuid[],
+    p_data jsonb[],
+    p_metadata jsonb[],
+    p_types text[],
+    p_stream_id text,
+    p_stream_type text,
+    p_expected_stream_version bigint default null,
+    p_data_binary bytea[] default null,
+    p_data_encodings text[] default null
+) RETURNS TABLE(
+    id uuid,
+    version bigint,
+    created timestamp with time zone,
+    global_position bigint
+)
+  LANGUAGE plpgsql
+  AS $$
+  DECLARE
+    stream_version bigint;
+    stream_deleted timestamp with time zone;
+    stream_tenant text;
+    append_tenant text := p_metadata[1] ->> 'tenant_id';
+  BEGIN
+    SELECT
+      s.version, s.deleted, s.tenant_id INTO stream_version, stream_deleted, stream_tenant
+    FROM streams as s
+    WHERE
+      s.id = p_stream_id FOR UPDATE;
+
+    IF stream_version IS NULL THEN
+      INSERT INTO streams
+      (id, type, version, tenant_id)
+      VALUES
+      (p_stream_id, p_stream_type, 0, append_tenant)
+      ON CONFLICT ON CONSTRAINT streams_pkey DO NOTHING;
+
+      IF FOUND THEN
+        stream_version := 0;
+        stream_tenant := append_tenant;
+      ELSE
+        -- Created by a concurrent append, now committed, or hidden by row-level security.
+        SELECT
+          s.version, s.deleted, s.tenant_id INTO stream_version, stream_deleted, stream_tenant
+        FROM streams as s
+        WHERE
+          s.id = p_stream_id FOR UPDATE;
+
+        IF NOT FOUND THEN
+          RAISE EXCEPTION 'stream % belongs to another tenant', p_stream_id
+            USING ERRCODE = 'insufficient_privilege';
+        END IF;
+      END IF;
+    END IF;
+
+    IF stream_deleted IS NOT NULL THEN
+      RAISE EXCEPTION 'stream % was deleted', p_stream_id USING ERRCODE = 'no_data_found';
+    END IF;
+
+    IF append_tenant IS NOT NULL AND stream_tenant IS DISTINCT FROM append_tenant THEN
+      RAISE EXCEPTION 'stream % belongs to another tenant', p_stream_id
+        USING ERRCODE = 'insufficient_privilege';
+    END IF;
+
+    IF p_expected_stream_version IS NOT NULL AND stream_version != p_expected_stream_version THEN
+        RETURN;
+    END IF;
+
+    UPDATE streams as s
+        SET version = stream_version + cardinality(p_ids)
+    WHERE
+        s.id = p_stream_id;
+
+    INSERT INTO events
+        (id, data, metadata, stream_id, type, version, data_binary, data_encoding)
+    SELECT
+        batch.event_id, batch.event_data, batch.event_metadata, p_stream_id,
+        batch.event_type, stream_version + batch.position,
+        batch.event_data_binary, batch.event_data_encoding
+    -- UNNEST pads shorter (or NULL) arrays with NULLs, so the new arrays may be omitted.
+    FROM UNNEST(p_ids, p_data, p_metadata, p_types, p_data_binary, p_data_encodings)
+        WITH ORDINALITY AS batch(
+            event_id, event_data, event_metadata, event_type,
+            event_data_binary, event_data_encoding, position
+        )
+    ORDER BY batch.position;
+
+    -- The rows just written, read back by stream and version.
+    RETURN QUERY
+    SELECT e.id, e.version, e.created, e.global_position
+    FROM events as e
+    WHERE
+        e.stream_id = p_stream_id
+        AND e.aggregate_version IS NULL
+        AND e.version > stream_version
+    ORDER BY e.version;
+  END;
+$$;
+
+-- As in 0025, and re-enables row-level security on the new table, whose policy doesn't carry
+-- over either.
+CREATE OR REPLACE FUNCTION replay_partition_events(
+    p_strategy text,
+    p_partitions integer default 16,
+    p_months_ahead integer default 3
+) RETURNS void
+  LANGUAGE plpgsql
+  AS $$
+  DECLARE
+    key text;
+    oldest timestamptz;
+    correlation boolean;
+  BEGIN
+    IF EXISTS (SELECT 1 FROM pg_partitioned_table WHERE partrelid = 'events'::regclass) THEN
+      RAISE EXCEPTION 'events is already partitioned';
+    END IF;
+
+    CASE p_strategy
+      WHEN 'created_month' THEN key := 'created';
+      WHEN 'stream_hash' THEN key := 'stream_id';
+      ELSE RAISE EXCEPTION 'unknown partitioning strategy %, expected created_month or stream_hash',
+        p_strategy;
+    END CASE;
+
+    LOCK TABLE events IN ACCESS EXCLUSIVE MODE;
+
+    SELECT EXISTS (
+      SELECT 1 FROM pg_trigger
+      WHERE tgrelid = 'events'::regclass AND tgname = 'events_correlation_links'
+    ) INTO correlation;
+
+    IF p_strategy = 'created_month' THEN
+      EXECUTE 'CREATE TABLE events_partitioned (LIKE events INCLUDING DEFAULTS INCLUDING CONSTRAINTS)
+               PARTITION BY RANGE (created)';
+    ELSE
+      EXECUTE 'CREATE TABLE events_partitioned (LIKE events INCLUDING DEFAULTS INCLUDING CONSTRAINTS)
+               PARTITION BY HASH (stream_id)';
+    END IF;
+
+    -- The global_position sequence belongs to the old table and would be dropped with it.
+    ALTER SEQUENCE events_global_position_seq OWNED BY events_partitioned.global_position;
+
+    -- Move the old table aside so the partitions are created under `events`.
+    ALTER TABLE events RENAME TO events_unpartitioned;
+    ALTER TABLE events_partitioned RENAME TO events;
+
+    IF p_strategy = 'created_month' THEN
+      SELECT min(created) INTO oldest FROM events_unpartitioned;
+      PERFORM replay_create_event_partitions(
+        least(coalesce(oldest, now()), now()),
+        now() + make_interval(months => p_months_ahead)
+      );
+    ELSE
+      FOR i IN 0 .. p_partitions - 1 LOOP
+        EXECUTE format(
+          'CREATE TABLE %I PARTITION OF events FOR VALUES WITH (MODULUS %s, REMAINDER %s)',
+          'events_p' || lpad(i::text, 2, '0'), p_partitions, i
+        );
+      END LOOP;
+    END IF;
+
+    INSERT INTO events SELECT * FROM events_unpartitioned;
+    DROP TABLE events_unpartitioned;
+
+    EXECUTE format('ALTER TABLE events ADD CONSTRAINT events_pkey PRIMARY KEY (id, %I)', key);
+    ALTER TABLE events ADD CONSTRAINT events_stream_id_fkey
+      FOREIGN KEY (stream_id) REFERENCES streams(id);
+
+    IF p_strategy = 'created_month' THEN
+      CREATE UNIQUE INDEX uidx_events_live_version
+        ON events (stream_id, version, created) WHERE aggregate_version IS NULL;
+      CREATE UNIQUE INDEX uidx_events_archived_version
+        ON events (stream_id, version, aggregate_version, created)
+        WHERE aggregate_version IS NOT NULL;
+    ELSE
+      CREATE UNIQUE INDEX uidx_events_live_version
+        ON events (stream_id, version) WHERE aggregate_version IS NULL;
+      CREATE UNIQUE INDEX uidx_events_archived_version
+        ON events (stream_id, version, aggregate_version) WHERE aggregate_version IS NOT NULL;
+    END IF;
+    CREATE INDEX idx_events_aggregate_version ON events (stream_id, aggregate_version);
+    CREATE INDEX idx_events_created_version ON events (created, version);
+    CREATE INDEX idx_events_global_position ON events (global_position);
+    CREATE INDEX idx_events_policy_feed ON events (global_position)
+      WHERE compacted_snapshot = false;
+
+    PERFORM replay_install_link_triggers(correlation);
+
+    ALTER TABLE events ENABLE ROW LEVEL SECURITY;
+    CREATE POLICY tenant_isolation ON events
+      USING (metadata ->> 'tenant_id' = NULLIF(current_setting('app.tenant_id', true), ''))
+      WITH CHECK (metadata ->> 'tenant_id' = NULLIF(current_setting('app.tenant_id', true), ''));
+  END;
+$$;